@@ -5,8 +5,10 @@
 #pragma once
 
 #include <memory>
+#include "rocksdb/table.h"
 #include "rocksdb/utilities/transaction_db.h"
 #include "rocksdb/utilities/options_util.h"
+#include "rocksdb/utilities/backup_engine.h"
 
 using namespace std;
 using namespace rocksdb;
@@ -74,6 +76,11 @@ struct DbOptionsWrapper
         db_options.create_missing_column_families = val;
     }
 
+    void enable_statistics()
+    {
+        db_options.statistics = CreateDBStatistics();
+    }
+
     void set_compression(CompressionType comp)
     {
         for (ColumnFamilyDescriptor &x : cf_descriptors)
@@ -82,6 +89,41 @@ struct DbOptionsWrapper
         }
     }
 
+    void set_compression_per_level(const CompressionType *levels, size_t len)
+    {
+        for (ColumnFamilyDescriptor &x : cf_descriptors)
+        {
+            x.options.compression_per_level.assign(levels, levels + len);
+        }
+    }
+
+    // Shared by all column families.
+    void set_block_cache_size(size_t size)
+    {
+        auto cache = NewLRUCache(size);
+        for (ColumnFamilyDescriptor &x : cf_descriptors)
+        {
+            auto table_options = x.options.table_factory->GetOptions<BlockBasedTableOptions>();
+            if (table_options != nullptr)
+            {
+                table_options->block_cache = cache;
+            }
+        }
+    }
+
+    void set_write_buffer_size(size_t size)
+    {
+        for (ColumnFamilyDescriptor &x : cf_descriptors)
+        {
+            x.options.write_buffer_size = size;
+        }
+    }
+
+    void set_max_background_jobs(int jobs)
+    {
+        db_options.max_background_jobs = jobs;
+    }
+
     Status load(Slice options_file, size_t cache_size)
     {
         // Number of columns excluding the default.
@@ -285,6 +327,18 @@ struct TransactionDBWrapper
         return db->GetIntProperty(cf, property, value);
     }
 
+    // Returns false if statistics is not enabled.
+    bool get_ticker_count(Tickers ticker, uint64_t *value) const
+    {
+        auto statistics = db->GetDBOptions().statistics;
+        if (!statistics)
+        {
+            return false;
+        }
+        *value = statistics->getTickerCount(ticker);
+        return true;
+    }
+
     unique_ptr<Iterator> iter(const ReadOptions &options, ColumnFamilyHandle *cf) const
     {
         return unique_ptr<Iterator>(db->NewIterator(options, cf));
@@ -331,6 +385,35 @@ struct ReadOnlyDbWrapper
         return status;
     }
 
+    // Secondary instances can catch up with the primary, which can be
+    // running at the same time.
+    Status open_as_secondary(
+        const DbOptionsWrapper &options,
+        Slice secondary_path)
+    {
+        DBOptions db_options = options.db_options;
+        // Required by secondary instances.
+        db_options.max_open_files = -1;
+        DB *ptr;
+        Status status = DB::OpenAsSecondary(
+            db_options,
+            options.path,
+            secondary_path.ToString(),
+            options.cf_descriptors,
+            &cf_handles,
+            &ptr);
+        if (status.ok())
+        {
+            db.reset(ptr);
+        }
+        return status;
+    }
+
+    Status try_catch_up_with_primary() const
+    {
+        return db->TryCatchUpWithPrimary();
+    }
+
     ~ReadOnlyDbWrapper()
     {
         for (auto cf : cf_handles)
@@ -415,3 +498,82 @@ inline TransactionWrapper TransactionDBWrapper::begin(const WriteOptions &write_
 {
     return {unique_ptr<Transaction>(db->BeginTransaction(write_options, transaction_options))};
 }
+
+// Note: make sure BackupEngineWrapper is Unpin.
+struct BackupEngineWrapper
+{
+    unique_ptr<BackupEngine> engine;
+    // Loaded by load_backup_infos, because autocxx cannot handle vectors of
+    // BackupInfo.
+    vector<BackupInfo> infos;
+
+    Status open(Slice backup_dir)
+    {
+        BackupEngine *ptr;
+        Status status = BackupEngine::Open(
+            BackupEngineOptions(backup_dir.ToString()),
+            Env::Default(),
+            &ptr);
+        if (status.ok())
+        {
+            engine.reset(ptr);
+        }
+        return status;
+    }
+
+    Status create_new_backup(const TransactionDBWrapper &db, bool flush_before_backup, uint32_t *backup_id)
+    {
+        CreateBackupOptions options;
+        options.flush_before_backup = flush_before_backup;
+        return engine->CreateNewBackup(options, db.db.get(), backup_id);
+    }
+
+    Status verify_backup(uint32_t backup_id) const
+    {
+        return engine->VerifyBackup(backup_id, true);
+    }
+
+    Status purge_old_backups(uint32_t num_backups_to_keep)
+    {
+        return engine->PurgeOldBackups(num_backups_to_keep);
+    }
+
+    Status restore(uint32_t backup_id, Slice db_dir) const
+    {
+        auto dir = db_dir.ToString();
+        return engine->RestoreDBFromBackup(backup_id, dir, dir);
+    }
+
+    Status restore_latest(Slice db_dir) const
+    {
+        auto dir = db_dir.ToString();
+        return engine->RestoreDBFromLatestBackup(dir, dir);
+    }
+
+    size_t load_backup_infos()
+    {
+        infos.clear();
+        engine->GetBackupInfo(&infos);
+        return infos.size();
+    }
+
+    uint32_t backup_id(size_t i) const
+    {
+        return infos[i].backup_id;
+    }
+
+    int64_t backup_timestamp(size_t i) const
+    {
+        return infos[i].timestamp;
+    }
+
+    uint64_t backup_size(size_t i) const
+    {
+        return infos[i].size;
+    }
+
+    uint32_t backup_number_files(size_t i) const
+    {
+        return infos[i].number_files;
+    }
+};
@@ -5,6 +5,7 @@ autocxx::include_cpp! {
     #include "rocksdb/options.h"
     #include "rocksdb/iterator.h"
     #include "rocksdb/status.h"
+    #include "rocksdb/statistics.h"
     #include "db.h"
 
     safety!(unsafe_ffi)
@@ -22,6 +23,7 @@ autocxx::include_cpp! {
     generate!("rocksdb::Status")
     generate!("rocksdb::ColumnFamilyOptions")
     generate!("rocksdb::ColumnFamilyDescriptor")
+    generate!("rocksdb::Tickers")
     // Unfortunately cannot generate these because of shared_ptr<const Snapshot>.
     //
     // generate!("rocksdb::TransactionDB")
@@ -34,6 +36,7 @@ autocxx::include_cpp! {
     generate!("TransactionDBWrapper")
     generate!("ReadOnlyDbWrapper")
     generate!("TransactionWrapper")
+    generate!("BackupEngineWrapper")
 }
 
 pub use ffi::*;
@@ -41,6 +44,7 @@ pub use ffi::*;
 impl Unpin for TransactionDBWrapper {}
 impl Unpin for ReadOnlyDbWrapper {}
 impl Unpin for TransactionWrapper {}
+impl Unpin for BackupEngineWrapper {}
 
 unsafe impl Send for TransactionDBWrapper {}
 unsafe impl Sync for TransactionDBWrapper {}
@@ -52,6 +56,8 @@ unsafe impl Send for TransactionWrapper {}
 // Sync because mutable methods take Pin<&mut Self>.
 unsafe impl Sync for TransactionWrapper {}
 
+unsafe impl Send for BackupEngineWrapper {}
+
 unsafe impl Send for rocksdb::WriteBatch {}
 // Sync because mutable methods take Pin<&mut Self>.
 unsafe impl Sync for rocksdb::WriteBatch {}
@@ -0,0 +1,100 @@
+use std::{os::unix::prelude::OsStrExt, path::Path, pin::Pin};
+
+use autorocks_sys::BackupEngineWrapper;
+use moveit::{moveit, Emplace};
+
+use crate::{into_result, Result, TransactionDb};
+
+/// RocksDB backup engine. Backups are incremental: files shared with
+/// previous backups in the same directory are not copied again.
+pub struct BackupEngine {
+    inner: Pin<Box<BackupEngineWrapper>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BackupInfo {
+    pub id: u32,
+    /// Unix timestamp in seconds.
+    pub timestamp: i64,
+    pub size: u64,
+    pub number_files: u32,
+}
+
+impl BackupEngine {
+    pub fn open(backup_dir: &Path) -> Result<Self> {
+        let mut inner = Box::emplace(BackupEngineWrapper::new());
+        moveit! {
+            let status = inner.as_mut().open(backup_dir.as_os_str().as_bytes().into());
+        }
+        into_result(&status)?;
+        Ok(Self { inner })
+    }
+
+    /// Create a new backup of `db`, which can be in use. Returns the backup
+    /// id.
+    pub fn create_new_backup(
+        &mut self,
+        db: &TransactionDb,
+        flush_before_backup: bool,
+    ) -> Result<u32> {
+        let mut backup_id = 0;
+        moveit! {
+            let status = unsafe {
+                self.inner
+                    .as_mut()
+                    .create_new_backup(db.as_inner(), flush_before_backup, &mut backup_id)
+            };
+        }
+        into_result(&status)?;
+        Ok(backup_id)
+    }
+
+    /// Check sizes and checksums of files of the backup.
+    pub fn verify_backup(&self, backup_id: u32) -> Result<()> {
+        moveit! {
+            let status = self.inner.verify_backup(backup_id);
+        }
+        into_result(&status)
+    }
+
+    /// Delete all backups but the latest `num_backups_to_keep` ones.
+    pub fn purge_old_backups(&mut self, num_backups_to_keep: u32) -> Result<()> {
+        moveit! {
+            let status = self.inner.as_mut().purge_old_backups(num_backups_to_keep);
+        }
+        into_result(&status)
+    }
+
+    /// Restore the backup, or the latest one if `backup_id` is None, into
+    /// `db_dir`. The db must not be open.
+    pub fn restore(&self, backup_id: Option<u32>, db_dir: &Path) -> Result<()> {
+        let db_dir = db_dir.as_os_str().as_bytes().into();
+        match backup_id {
+            Some(id) => {
+                moveit! {
+                    let status = self.inner.restore(id, db_dir);
+                }
+                into_result(&status)
+            }
+            None => {
+                moveit! {
+                    let status = self.inner.restore_latest(db_dir);
+                }
+                into_result(&status)
+            }
+        }
+    }
+
+    /// Backups in ascending order of id.
+    pub fn backup_infos(&mut self) -> Vec<BackupInfo> {
+        let len = self.inner.as_mut().load_backup_infos();
+        (0..len)
+            .map(|i| BackupInfo {
+                id: self.inner.backup_id(i),
+                timestamp: self.inner.backup_timestamp(i),
+                size: self.inner.backup_size(i),
+                number_files: self.inner.backup_number_files(i),
+            })
+            .collect()
+    }
+}
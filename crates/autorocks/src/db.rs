@@ -3,7 +3,7 @@ use std::{mem::MaybeUninit, os::unix::prelude::OsStrExt, path::Path, pin::Pin, s
 use autorocks_sys::{
     new_transaction_db_options, new_write_batch,
     rocksdb::{
-        CompressionType, PinnableSlice, ReadOptions, Slice, TransactionDBOptions,
+        CompressionType, PinnableSlice, ReadOptions, Slice, Tickers, TransactionDBOptions,
         TransactionDBWriteOptimizations, TransactionOptions, WriteOptions,
     },
     DbOptionsWrapper, ReadOnlyDbWrapper, TransactionDBWrapper, TransactionWrapper,
@@ -53,12 +53,51 @@ impl DbOptions {
         self
     }
 
+    /// Collect statistics, e.g. block cache hits, for `get_ticker_count`.
+    ///
+    /// Call this after `load_options_from_file`, which resets all options.
+    pub fn enable_statistics(&mut self) -> &mut Self {
+        self.inner.as_mut().enable_statistics();
+        self
+    }
+
     /// The corresponding feature must be enabled for this to actually work.
     pub fn compression(&mut self, c: CompressionType) -> &mut Self {
         self.inner.as_mut().set_compression(c);
         self
     }
 
+    /// Compression of each level, starting from level 0. The corresponding
+    /// features must be enabled.
+    pub fn compression_per_level(&mut self, levels: &[CompressionType]) -> &mut Self {
+        unsafe {
+            self.inner
+                .as_mut()
+                .set_compression_per_level(levels.as_ptr(), levels.len());
+        }
+        self
+    }
+
+    /// Use a block cache of `size` bytes shared by all column families.
+    pub fn block_cache_size(&mut self, size: usize) -> &mut Self {
+        self.inner.as_mut().set_block_cache_size(size);
+        self
+    }
+
+    /// Memtable size of each column family.
+    pub fn write_buffer_size(&mut self, size: usize) -> &mut Self {
+        self.inner.as_mut().set_write_buffer_size(size);
+        self
+    }
+
+    /// Max concurrent flush and compaction jobs.
+    pub fn max_background_jobs(&mut self, jobs: i32) -> &mut Self {
+        self.inner
+            .as_mut()
+            .set_max_background_jobs(autocxx::c_int(jobs));
+        self
+    }
+
     pub fn repair(&self) -> Result<()> {
         moveit! {
             let status = self.inner.repair();
@@ -70,6 +109,13 @@ impl DbOptions {
         ReadOnlyDb::open(&self.inner)
     }
 
+    /// Open a secondary instance, which keeps its info logs in
+    /// `secondary_path`. Call `try_catch_up_with_primary` to see new writes
+    /// of the primary.
+    pub fn open_as_secondary(&self, secondary_path: &Path) -> Result<ReadOnlyDb> {
+        ReadOnlyDb::open_as_secondary(&self.inner, secondary_path)
+    }
+
     pub fn open(&self) -> Result<TransactionDb> {
         moveit! {
             let txn_db_options = new_transaction_db_options();
@@ -219,6 +265,13 @@ impl TransactionDb {
         got.then_some(val)
     }
 
+    /// None if statistics is not enabled.
+    pub fn get_ticker_count(&self, ticker: Tickers) -> Option<u64> {
+        let mut val = 0;
+        let got = unsafe { self.inner.get_ticker_count(ticker, &mut val) };
+        got.then_some(val)
+    }
+
     pub fn snapshot(&self) -> Snapshot {
         Snapshot {
             inner: self.inner.get_snapshot(),
@@ -353,6 +406,25 @@ impl ReadOnlyDb {
         Ok(ReadOnlyDb { inner: db })
     }
 
+    fn open_as_secondary(options: &DbOptionsWrapper, secondary_path: &Path) -> Result<ReadOnlyDb> {
+        let db = Arc::emplace(ReadOnlyDbWrapper::new());
+        let mut db = Pin::into_inner(db);
+        let db_mut = Arc::get_mut(&mut db).unwrap();
+        moveit! {
+            let status = Pin::new(db_mut).open_as_secondary(options, secondary_path.as_os_str().as_bytes().into());
+        }
+        into_result(&status)?;
+        Ok(ReadOnlyDb { inner: db })
+    }
+
+    /// Only for secondary instances.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        moveit! {
+            let status = self.inner.try_catch_up_with_primary();
+        }
+        into_result(&status)
+    }
+
     pub fn default_col(&self) -> usize {
         self.inner.default_col()
     }
@@ -7,5 +7,6 @@ criterion_main! {
     benchmarks::init_db::init_db,
     benchmarks::sudt::sudt,
     benchmarks::smt::smt,
+    benchmarks::smt_batch::smt_batch,
     benchmarks::fee_queue::fee_queue,
 }
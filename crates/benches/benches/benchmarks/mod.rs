@@ -1,4 +1,5 @@
 pub mod fee_queue;
 pub mod init_db;
 pub mod smt;
+pub mod smt_batch;
 pub mod sudt;
@@ -0,0 +1,64 @@
+use criterion::{criterion_group, BatchSize, BenchmarkId, Criterion, Throughput};
+use gw_common::blake2b::new_blake2b;
+use gw_smt::smt::{default_store::DefaultStore, SMT, SMTH256};
+
+criterion_group! {
+    name = smt_batch;
+    config = Criterion::default();
+    targets = bench_smt_batch_update
+}
+
+fn hash(n: u32, salt: u8) -> SMTH256 {
+    let mut hasher = new_blake2b();
+    hasher.update(&n.to_le_bytes());
+    hasher.update(&[salt]);
+    let mut buf = [0u8; 32];
+    hasher.finalize(&mut buf);
+    buf.into()
+}
+
+fn prepare_tree(accounts: u32) -> SMT<DefaultStore<SMTH256>> {
+    let mut tree = SMT::default();
+    let leaves = (0..accounts).map(|i| (hash(i, 0), hash(i, 1))).collect();
+    tree.update_all(leaves).unwrap();
+    tree
+}
+
+pub fn bench_smt_batch_update(c: &mut Criterion) {
+    let tree = prepare_tree(10_000);
+    let mut group = c.benchmark_group("smt_batch_update");
+    for keys in [100u32, 1000] {
+        // Half updates existing keys, half inserts new keys.
+        let leaves: Vec<_> = (keys / 2..keys + keys / 2)
+            .map(|i| (hash(i * 10, 0), hash(i, 2)))
+            .collect();
+        group.throughput(Throughput::Elements(keys.into()));
+        group.bench_with_input(
+            BenchmarkId::new("one_by_one", keys),
+            &leaves,
+            |b, leaves| {
+                b.iter_batched(
+                    || SMT::new(*tree.root(), tree.store().clone()),
+                    |mut tree| {
+                        for (k, v) in leaves {
+                            tree.update(*k, *v).unwrap();
+                        }
+                        tree
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("batch", keys), &leaves, |b, leaves| {
+            b.iter_batched(
+                || (SMT::new(*tree.root(), tree.store().clone()), leaves.clone()),
+                |(mut tree, leaves)| {
+                    tree.update_all(leaves).unwrap();
+                    tree
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
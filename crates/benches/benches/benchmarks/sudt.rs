@@ -68,6 +68,7 @@ fn build_backend_manage(rollup_config: &RollupConfig) -> BackendManage {
     BackendManage::from_config(vec![BackendForkConfig {
         fork_height: 0,
         sudt_proxy: Default::default(),
+        batch_create_eth_accounts: None,
         backends: configs,
     }])
     .expect("default backend")
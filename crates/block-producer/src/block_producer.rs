@@ -1,12 +1,16 @@
 #![allow(clippy::mutable_key_type)]
 //! Block producing and block submit tx composing.
 
-use std::{collections::HashSet, sync::Arc, time::Instant};
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::{bail, ensure, Context, Result};
 use ckb_chain_spec::consensus::MAX_BLOCK_BYTES;
 use gw_chain::chain::Chain;
-use gw_config::{BlockProducerConfig, ContractsCellDep};
+use gw_config::{BlockProducerConfig, ContractsCellDep, PscConfig};
 use gw_generator::Generator;
 use gw_jsonrpc_types::{test_mode::TestModePayload, JsonCalcHash};
 use gw_mem_pool::{
@@ -36,10 +40,11 @@ use tokio::sync::Mutex;
 use tracing::instrument;
 
 use crate::{
-    custodian::query_mergeable_custodians,
+    custodian::{query_mergeable_custodians, CustodianConsolidation, MAX_CUSTODIANS},
     produce_block::{
         generate_produce_block_param, produce_block, ProduceBlockParam, ProduceBlockResult,
     },
+    submitter::SubmitterRotation,
     test_mode_control::TestModeControl,
 };
 
@@ -82,10 +87,14 @@ pub struct BlockProducer {
     chain: Arc<Mutex<Chain>>,
     generator: Arc<Generator>,
     wallet: Wallet,
+    // Extra wallets paying submission tx fees, `wallet` is submitter 0.
+    submitter_wallets: Vec<Wallet>,
+    submitter_rotation: std::sync::Mutex<SubmitterRotation>,
     rpc_client: RPCClient,
     ckb_genesis_info: CKBGenesisInfo,
     tests_control: Option<TestModeControl>,
     contracts_dep_manager: ContractsCellDepManager,
+    custodian_consolidation: Option<Arc<CustodianConsolidation>>,
 }
 
 pub struct BlockProducerCreateArgs {
@@ -98,6 +107,7 @@ pub struct BlockProducerCreateArgs {
     pub config: BlockProducerConfig,
     pub tests_control: Option<TestModeControl>,
     pub contracts_dep_manager: ContractsCellDepManager,
+    pub custodian_consolidation: Option<Arc<CustodianConsolidation>>,
 }
 
 impl BlockProducer {
@@ -112,12 +122,17 @@ impl BlockProducer {
             config,
             tests_control,
             contracts_dep_manager,
+            custodian_consolidation,
         } = args;
 
         let wallet = match config.wallet_config {
             Some(ref c) => Wallet::from_config(c).with_context(|| "init wallet")?,
             None => bail!("no wallet config for block producer"),
         };
+        let submitter_wallets = (config.submitter_wallets.iter())
+            .map(|c| Wallet::from_config(c).with_context(|| "init submitter wallet"))
+            .collect::<Result<Vec<_>>>()?;
+        let submitter_rotation = SubmitterRotation::new(submitter_wallets.len() + 1);
 
         let block_producer = BlockProducer {
             rollup_config_hash,
@@ -125,14 +140,34 @@ impl BlockProducer {
             chain,
             rpc_client,
             wallet,
+            submitter_wallets,
+            submitter_rotation: std::sync::Mutex::new(submitter_rotation),
             ckb_genesis_info,
             tests_control,
             store,
             contracts_dep_manager,
+            custodian_consolidation,
         };
         Ok(block_producer)
     }
 
+    fn submitter_wallet(&self, index: usize) -> &Wallet {
+        match index {
+            0 => &self.wallet,
+            i => &self.submitter_wallets[i - 1],
+        }
+    }
+
+    /// Submission tx `tx_hash` is confirmed.
+    pub fn confirm_submit_tx(&self, tx_hash: &H256) {
+        self.submitter_rotation.lock().unwrap().confirm(tx_hash);
+    }
+
+    /// Submission txs not confirmed are dropped.
+    pub fn reset_submit_txs(&self) {
+        self.submitter_rotation.lock().unwrap().reset();
+    }
+
     pub fn generator(&self) -> &Generator {
         &self.generator
     }
@@ -315,6 +350,11 @@ impl BlockProducer {
         let scripts = self.contracts_dep_manager.load_scripts();
         let d = scripts.delegate_cell.as_ref().map(|s| s.hash());
         let dl = scripts.delegate_cell_lock.as_ref().map(|s| s.hash());
+        // A rollup cell locked by the delegate cell lock requires an input of
+        // the block producer wallet.
+        let delegated = dl.map_or(false, |dl| {
+            rollup_cell.output.as_reader().lock().code_hash().as_slice() == dl.as_slice()
+        });
         let rollup_output = if let (Some(d), Some(dl)) = (d, dl) {
             let new_lock = Script::new_builder()
                 .code_hash(dl.pack())
@@ -396,11 +436,16 @@ impl BlockProducer {
         )
         .await?
         .expect_any();
+        let max_custodians = match self.custodian_consolidation {
+            Some(ref c) => c.take_max_custodians(),
+            None => MAX_CUSTODIANS,
+        };
         let finalized_custodians = query_mergeable_custodians(
             local_cells_manager,
             rpc_client,
             finalized_custodians,
             &prev_compatible_finalized_timepoint,
+            max_custodians,
         )
         .await?
         .expect_any();
@@ -495,10 +540,16 @@ impl BlockProducer {
         }
 
         // tx fee cell
+        let submitter = if delegated {
+            0
+        } else {
+            self.submitter_rotation.lock().unwrap().pick()
+        };
+        let submitter_wallet = self.submitter_wallet(submitter);
         fill_tx_fee_with_local(
             &mut tx_skeleton,
             &self.rpc_client.indexer,
-            self.wallet.lock_script().to_owned(),
+            submitter_wallet.lock_script().to_owned(),
             local_cells_manager,
             fee_rate,
         )
@@ -509,12 +560,14 @@ impl BlockProducer {
             "check duplicated inputs"
         );
         // sign
-        let tx = self.wallet.sign_tx_skeleton(tx_skeleton)?;
+        let tx = (self.wallet).sign_tx_skeleton_with(tx_skeleton, &[submitter_wallet])?;
         ensure!(
             (tx.as_slice().len() as u64) < MAX_BLOCK_BYTES,
             TransactionSizeError::TransactionTooLarge
         );
         log::debug!("final tx size: {}", tx.as_slice().len());
+        let nonce = (self.submitter_rotation.lock().unwrap()).track(submitter, tx.hash());
+        log::info!("submitter {} pays fee, nonce {}", submitter, nonce);
         Ok(tx)
     }
 
@@ -524,10 +577,10 @@ impl BlockProducer {
         let delegate_cell_lock_dep = contracts_dep.delegate_cell_lock.as_ref().unwrap();
         let delegate_cell_lock_cell = self
             .rpc_client
-            .get_cell(delegate_cell_lock_dep.out_point.clone().into())
+            .get_cell_content(delegate_cell_lock_dep.out_point.clone().into())
             .await?
             .context("get delegate cell lock cell")?;
-        let delegate_cell_lock_data = delegate_cell_lock_cell.cell.unwrap().data;
+        let delegate_cell_lock_data = delegate_cell_lock_cell.data;
         // This is short living code, so just hard code the script path.
         let delegate_cell_lock_program =
             std::fs::read("/scripts/godwoken-scripts/delegate-cell-lock")
@@ -539,10 +592,10 @@ impl BlockProducer {
         let delegate_cell = contracts_dep.delegate_cell.as_ref().unwrap();
         let delegate_cell = self
             .rpc_client
-            .get_cell(delegate_cell.out_point.clone().into())
+            .get_cell_content(delegate_cell.out_point.clone().into())
             .await?
             .context("get delegate cell")?;
-        let delegate_cell_data = delegate_cell.cell.unwrap().data;
+        let delegate_cell_data = delegate_cell.data;
         let wallet_lock_hash = self.wallet.lock_script().hash();
         ensure!(
             delegate_cell_data == wallet_lock_hash[..20],
@@ -563,6 +616,64 @@ pub struct ComposeSubmitTxArgs<'a> {
     pub fee_rate: u64,
 }
 
+/// Adaptive block interval based on mem block usage.
+///
+/// Full mem blocks are produced early, empty ones are delayed or produced as
+/// heartbeats. Produce at the fixed `block_interval_secs` if none is
+/// configured.
+#[derive(Debug, Clone)]
+pub struct BlockSchedule {
+    interval: Duration,
+    max_empty_interval: Duration,
+    early_fullness_percent: Option<u64>,
+    /// Whether `max_empty_interval` is the heartbeat interval.
+    heartbeat: bool,
+}
+
+impl BlockSchedule {
+    /// How often mem block usage is checked.
+    pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new(config: &PscConfig) -> Self {
+        let interval = Duration::from_secs(config.block_interval_secs);
+        let max_empty_interval = config
+            .heartbeat_block_interval_secs
+            .or(config.max_empty_block_interval_secs)
+            .map_or(interval, Duration::from_secs)
+            .max(interval);
+        Self {
+            interval,
+            max_empty_interval,
+            early_fullness_percent: config.early_block_fullness_percent,
+            heartbeat: config.heartbeat_block_interval_secs.is_some(),
+        }
+    }
+
+    pub fn is_adaptive(&self) -> bool {
+        self.early_fullness_percent.is_some()
+            || self.max_empty_interval > self.interval
+            || self.heartbeat
+    }
+
+    /// Whether to produce a block `elapsed` after the last one. `fullness` is
+    /// the mem block usage in percent, None if it's empty.
+    pub fn should_produce(&self, elapsed: Duration, fullness: Option<u64>) -> bool {
+        match fullness {
+            None => elapsed >= self.max_empty_interval,
+            Some(percent) => {
+                elapsed >= self.interval
+                    || matches!(self.early_fullness_percent, Some(p) if percent >= p)
+            }
+        }
+    }
+
+    /// Whether the block to produce is triggered by the heartbeat timer, i.e.
+    /// a heartbeat interval is configured and passed without activity.
+    pub fn is_heartbeat(&self, elapsed: Duration, fullness: Option<u64>) -> bool {
+        self.heartbeat && fullness.is_none() && elapsed >= self.max_empty_interval
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum TransactionSizeError {
     #[error("transaction too large")]
@@ -571,6 +682,46 @@ pub enum TransactionSizeError {
     WitnessTooLarge,
 }
 
+#[test]
+fn test_block_schedule() {
+    let config = PscConfig {
+        block_interval_secs: 8,
+        early_block_fullness_percent: Some(80),
+        max_empty_block_interval_secs: Some(60),
+        ..Default::default()
+    };
+    let schedule = BlockSchedule::new(&config);
+    assert!(schedule.is_adaptive());
+    let secs = Duration::from_secs;
+    // Full mem block is produced early.
+    assert!(schedule.should_produce(secs(2), Some(80)));
+    assert!(!schedule.should_produce(secs(2), Some(79)));
+    assert!(schedule.should_produce(secs(8), Some(0)));
+    // Empty mem block is delayed.
+    assert!(!schedule.should_produce(secs(59), None));
+    assert!(schedule.should_produce(secs(60), None));
+    // No heartbeat is configured.
+    assert!(!schedule.is_heartbeat(secs(60), None));
+
+    // Heartbeat blocks.
+    let config = PscConfig {
+        block_interval_secs: 8,
+        max_empty_block_interval_secs: Some(60),
+        heartbeat_block_interval_secs: Some(30),
+        ..Default::default()
+    };
+    let schedule = BlockSchedule::new(&config);
+    assert!(schedule.is_adaptive());
+    assert!(!schedule.should_produce(secs(29), None));
+    assert!(schedule.should_produce(secs(30), None));
+    assert!(schedule.should_produce(secs(8), Some(1)));
+    assert!(schedule.is_heartbeat(secs(30), None));
+    assert!(!schedule.is_heartbeat(secs(8), Some(1)));
+    assert!(!schedule.is_heartbeat(secs(30), Some(1)));
+
+    assert!(!BlockSchedule::new(&PscConfig::default()).is_adaptive());
+}
+
 #[test]
 fn test_witness_size_overhead() {
     let block = L2Block::default();
@@ -2,13 +2,16 @@
 
 use std::{collections::VecDeque, sync::Arc, time::Duration};
 
-use anyhow::{ensure, Context, Result};
+use anyhow::{anyhow, ensure, Context, Result};
 use bytes::Bytes;
 use futures::TryStreamExt;
 use gw_chain::chain::Chain;
+use gw_common::blake2b::hash;
 use gw_generator::generator::CyclesPool;
 use gw_mem_pool::pool::MemPool;
-use gw_p2p_network::{FnSpawn, P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME};
+use gw_p2p_network::{
+    FnSpawn, PeerLedger, SubstreamMetrics, P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME,
+};
 use gw_rpc_client::rpc_client::RPCClient;
 use gw_store::{autorocks::RocksDBStatusError, traits::chain_store::ChainStore, Store};
 use gw_telemetry::{
@@ -16,17 +19,26 @@ use gw_telemetry::{
     traits::{TelemetryContextNewSpan, TraceContextExt},
 };
 use gw_types::{
+    h256::H256,
     packed::{
-        BlockSync, BlockSyncReader, BlockSyncUnion, NumberHash, P2PSyncRequest,
-        P2PSyncResponseReader, P2PSyncResponseUnionReader, Script,
+        BlockSync, BlockSyncReader, BlockSyncUnion, Byte, L2Transaction, MemBlockAck,
+        MemBlockSnapshot, NextMemBlock, NumberHash, P2PBlockRangeRequest,
+        P2PBlockRangeResponseReader, P2PSyncRequest, P2PSyncResponseReader,
+        P2PSyncResponseUnionReader, Script,
     },
     prelude::*,
 };
-use gw_utils::{compression::StreamDecoder, liveness::Liveness};
+use gw_utils::{
+    compression::StreamDecoder,
+    liveness::Liveness,
+    mem_block_signature::{signed_message, verify_block_sync},
+};
 
 use tentacle::{
     builder::MetaBuilder,
+    secio::PeerId,
     service::{ProtocolMeta, ServiceAsyncControl},
+    utils::extract_peer_id,
     SessionId, SubstreamReadPart,
 };
 use tokio::{sync::Mutex, task::block_in_place};
@@ -34,6 +46,7 @@ use tracing::{info_span, Instrument};
 
 use crate::{
     chain_updater::ChainUpdater,
+    shadow_production::ShadowProducer,
     sync_l1::{revert, sync_l1, SyncL1Context},
 };
 
@@ -47,6 +60,12 @@ pub struct BlockSyncClient {
     pub p2p_stream_inbox: Arc<std::sync::Mutex<Option<P2PStream>>>,
     pub completed_initial_syncing: bool,
     pub liveness: Arc<Liveness>,
+    pub shadow_producer: Option<ShadowProducer>,
+    /// Only accept block sync messages signed by this signer, i.e. blake160
+    /// of the block producer's public key.
+    pub mem_block_signer: Option<[u8; 20]>,
+    /// Parent block of the current mem block, which signed txs are bound to.
+    pub mem_block_parent: Option<NumberHash>,
 }
 
 impl SyncL1Context for BlockSyncClient {
@@ -81,6 +100,9 @@ impl BlockSyncClient {
                         log::error!("db error, exiting: {:#}", err);
                         return;
                     }
+                    if err.is::<InvalidMessageCtx>() {
+                        s.ban();
+                    }
                     if !err.is::<RecoverableCtx>() {
                         let _ = s.disconnect().await;
                         p2p_stream = None;
@@ -118,6 +140,17 @@ impl std::fmt::Display for RecoverableCtx {
     }
 }
 
+/// The peer sent a well-formed but invalid message, e.g. an unsigned message,
+/// and will be banned.
+#[derive(Debug)]
+struct InvalidMessageCtx;
+
+impl std::fmt::Display for InvalidMessageCtx {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid message")
+    }
+}
+
 async fn run_once_without_p2p_stream(client: &mut BlockSyncClient) -> Result<()> {
     sync_l1(client).await?;
     notify_new_tip(client, true).await?;
@@ -146,12 +179,16 @@ async fn run_with_p2p_stream(client: &mut BlockSyncClient, stream: &mut P2PStrea
             P2PSyncResponseUnionReader::Found(_) => break,
             P2PSyncResponseUnionReader::TryAgain(_) => {}
         }
+        // Too far behind for the peer's buffer, catch up with block ranges.
+        if sync_block_ranges(client, stream).await? > 0 {
+            continue;
+        }
         log::info!("will try again");
         tokio::time::sleep(Duration::from_secs(3)).await;
     }
     log::info!("receiving block sync messages from peer");
     let (tx, mut rx) = tokio::sync::mpsc::channel(1);
-    let mut stream = stream.take_receiver();
+    let mut receiver = stream.take_receiver();
     // Receive from the stream promptly but only send to tx when the previous
     // one has been applied.
     //
@@ -163,7 +200,7 @@ async fn run_with_p2p_stream(client: &mut BlockSyncClient, stream: &mut P2PStrea
         loop {
             tokio::select! {
                 biased;
-                recv_result = stream.recv(), if !stream_ended && buffer.len() < 1024 => {
+                recv_result = receiver.recv(), if !stream_ended && buffer.len() < 1024 => {
                     if let Some(msg) = recv_result? {
                         BlockSyncReader::from_slice(&msg[..])?;
                         buffer.push_back(BlockSync::new_unchecked(msg));
@@ -184,15 +221,16 @@ async fn run_with_p2p_stream(client: &mut BlockSyncClient, stream: &mut P2PStrea
             }
             if buffer.len() >= 512
                 && matches!(
-                    buffer[buffer.len() - 1].to_enum(),
+                    unsigned_message(&buffer[buffer.len() - 1]).to_enum(),
                     BlockSyncUnion::LocalBlock(_)
                 )
             {
                 log::warn!("receive buffer too large, skipping transactions and mem blocks");
                 #[allow(clippy::match_like_matches_macro)]
-                buffer.retain(|msg| match msg.to_enum() {
+                buffer.retain(|msg| match unsigned_message(msg).to_enum() {
                     BlockSyncUnion::PushTransaction(_) => false,
                     BlockSyncUnion::NextMemBlock(_) => false,
+                    BlockSyncUnion::MemBlockSnapshot(_) => false,
                     _ => true,
                 });
                 log::info!("receive buffer: {}", buffer.len());
@@ -204,13 +242,81 @@ async fn run_with_p2p_stream(client: &mut BlockSyncClient, stream: &mut P2PStrea
         anyhow::Ok(())
     });
     while let Some(msg) = rx.recv().await {
-        apply_msg(client, msg).await?;
+        if let Some(ack) = apply_msg(client, msg).await? {
+            stream.send(ack.as_bytes()).await?;
+        }
     }
     recv_handle.await??;
     Ok(())
 }
 
-async fn apply_msg(client: &mut BlockSyncClient, msg: BlockSync) -> Result<()> {
+/// Blocks requested in one `P2PBlockRangeRequest`. The peer may send fewer.
+const BLOCK_RANGE_MAX_BLOCKS: u32 = 16;
+
+/// Fetch and apply confirmed blocks after the last confirmed block, one range
+/// at a time. Returns the number of applied blocks.
+async fn sync_block_ranges(client: &mut BlockSyncClient, stream: &mut P2PStream) -> Result<u64> {
+    let mut applied = 0;
+    loop {
+        let last_confirmed = client
+            .store
+            .get_last_confirmed_block_number_hash()
+            .context("last confirmed")?;
+        let start = last_confirmed.number().unpack() + 1;
+        let request = P2PBlockRangeRequest::new_builder()
+            .start_block_number(start.pack())
+            .max_blocks(BLOCK_RANGE_MAX_BLOCKS.pack())
+            .build();
+        stream.send(request.as_bytes()).await?;
+        let response = stream.recv().await?.context("unexpected end of stream")?;
+        let blocks: u32 = P2PBlockRangeResponseReader::from_slice(&response)?
+            .blocks()
+            .unpack();
+        ensure!(blocks <= BLOCK_RANGE_MAX_BLOCKS, "too many blocks in range");
+        if blocks == 0 {
+            return Ok(applied);
+        }
+        log::info!("receiving blocks {}..{}", start, start + u64::from(blocks));
+        // LocalBlock, Submitted and Confirmed of each block.
+        for _ in 0..blocks * 3 {
+            let msg = stream.recv().await?.context("unexpected end of stream")?;
+            BlockSyncReader::from_slice(&msg)?;
+            apply_msg(client, BlockSync::new_unchecked(msg)).await?;
+        }
+        applied += u64::from(blocks);
+    }
+}
+
+/// The message in a signed message, or the message itself.
+fn unsigned_message(msg: &BlockSync) -> BlockSync {
+    match msg.to_enum() {
+        BlockSyncUnion::SignedBlockSync(signed) => {
+            signed_message(&signed).unwrap_or_else(|_| msg.clone())
+        }
+        _ => msg.clone(),
+    }
+}
+
+/// Apply the message, returns the ack to send to the peer for a
+/// `MemBlockDelta`.
+async fn apply_msg(client: &mut BlockSyncClient, msg: BlockSync) -> Result<Option<MemBlockAck>> {
+    // Block the message is signed with.
+    let (msg, bound) = match msg.to_enum() {
+        BlockSyncUnion::SignedBlockSync(signed) => {
+            let msg = match client.mem_block_signer {
+                Some(ref signer) => {
+                    let rollup_type_hash = client.rollup_type_script.hash();
+                    verify_block_sync(&signed, &rollup_type_hash, signer)
+                }
+                None => signed_message(&signed),
+            };
+            (msg.context(InvalidMessageCtx)?, Some(signed.number_hash()))
+        }
+        _ if client.mem_block_signer.is_some() => {
+            return Err(anyhow!("block sync message is not signed").context(InvalidMessageCtx));
+        }
+        _ => (msg, None),
+    };
     match msg.to_enum() {
         BlockSyncUnion::Revert(r) => {
             log::info!(
@@ -276,18 +382,71 @@ async fn apply_msg(client: &mut BlockSyncClient, msg: BlockSync) -> Result<()> {
         }
         BlockSyncUnion::NextMemBlock(m) => {
             log::info!("received mem block {}", m.block_info().number().unpack());
-            if let Some(ref mem_pool) = client.mem_pool {
-                let mut mem_pool = mem_pool.lock().await;
-                let result = mem_pool.refresh_mem_block(
-                    m.block_info(),
-                    m.withdrawals().into_iter().collect(),
-                    m.deposits().unpack(),
-                );
-                if let Err(err) = result {
-                    log::warn!("{:#}", err);
+            if let Some(parent) = bound {
+                if !is_on_tip(client, &parent)? {
+                    log::warn!("ignore mem block not on the tip");
+                    return Ok(None);
                 }
+                client.mem_block_parent = Some(parent);
             }
-            client.liveness.tick();
+            refresh_mem_block(client, m).await;
+        }
+        BlockSyncUnion::MemBlockSnapshot(s) => {
+            log::info!(
+                "received mem block {} snapshot, txs: {}",
+                s.mem_block().block_info().number().unpack(),
+                s.transactions().len()
+            );
+            if let Some(parent) = bound {
+                if !is_on_tip(client, &parent)? {
+                    log::warn!("ignore mem block snapshot not on the tip");
+                    return Ok(None);
+                }
+                client.mem_block_parent = Some(parent);
+            }
+            resync_mem_block(client, s).await;
+        }
+        BlockSyncUnion::MemBlockDelta(d) => {
+            let number: u64 = d.block_number().unpack();
+            let start: u32 = d.start().unpack();
+            let tx_hashes: Vec<H256> = d.tx_hashes().unpack();
+            log::debug!(
+                "received mem block {} delta, txs: {}",
+                number,
+                tx_hashes.len()
+            );
+            // Deltas that can't be checked are acked as is.
+            let mut tx_count = Some(start as usize + tx_hashes.len());
+            let current = match bound {
+                Some(ref parent) => {
+                    Some(parent.as_slice())
+                        == client.mem_block_parent.as_ref().map(|p| p.as_slice())
+                }
+                None => true,
+            };
+            if !current {
+                log::warn!("ignore mem block delta not of the current mem block");
+            } else if let Some(ref mem_pool) = client.mem_pool {
+                let mem_pool = mem_pool.lock().await;
+                let local = mem_pool.mem_block();
+                tx_count = if local.block_info().number().unpack() == number {
+                    acked_tx_count(local.txs(), start as usize, &tx_hashes)
+                } else {
+                    None
+                };
+            }
+            if tx_count.is_none() {
+                log::warn!("mem block {} misses txs, request a snapshot", number);
+            }
+            let ack = MemBlockAck::new_builder()
+                .block_number(number.pack())
+                .tx_count((tx_count.unwrap_or_default() as u32).pack())
+                .resync(Byte::new(tx_count.is_none().into()))
+                .build();
+            return Ok(Some(ack));
+        }
+        BlockSyncUnion::SignedBlockSync(_) => {
+            return Err(anyhow!("nested signed message").context(InvalidMessageCtx));
         }
         BlockSyncUnion::PushTransaction(push_tx) => {
             // Use remote span context as parent.
@@ -306,23 +465,120 @@ async fn apply_msg(client: &mut BlockSyncClient, msg: BlockSync) -> Result<()> {
 
             let tx = push_tx.transaction();
             log::info!("received L2Transaction 0x{}", hex::encode(tx.hash()));
+            if let Some(parent) = bound {
+                // Signed txs of other mem blocks, e.g. replayed ones, are
+                // ignored.
+                if Some(parent.as_slice()) != client.mem_block_parent.as_ref().map(|p| p.as_slice())
+                {
+                    log::warn!("ignore tx not of the current mem block");
+                    return Ok(None);
+                }
+            }
             if let Some(ref mem_pool) = client.mem_pool {
                 let mut mem_pool = mem_pool.lock().await;
                 let _guard = span.enter();
-                let mem_block_config = mem_pool.config();
-                *mem_pool.cycles_pool_mut() = CyclesPool::new(
-                    mem_block_config.max_cycles_limit,
-                    mem_block_config.syscall_cycles.clone(),
-                );
+                push_transaction(&mut mem_pool, tx);
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Signed mem blocks of other tips, e.g. replayed ones, are ignored.
+fn is_on_tip(client: &BlockSyncClient, parent: &NumberHash) -> Result<bool> {
+    let tip = client.store.get_last_valid_tip_block_hash()?;
+    Ok(parent.block_hash().as_slice() == tip.as_slice())
+}
+
+fn push_transaction(mem_pool: &mut MemPool, tx: L2Transaction) {
+    *mem_pool.cycles_pool_mut() = CyclesPool::new(
+        mem_pool.block_cycles_limit(),
+        mem_pool.config().syscall_cycles.clone(),
+    );
+
+    let result = mem_pool.push_transaction(tx);
+    if let Err(err) = result {
+        log::warn!("{:#}", err);
+    }
+}
+
+/// Index of the first snapshot tx missing in the local mem block, or None if
+/// the local mem block has txs that are not in the snapshot in that order.
+fn first_missing_tx(local: &[H256], snapshot: &[H256]) -> Option<usize> {
+    snapshot.starts_with(local).then_some(local.len())
+}
 
-                let result = mem_pool.push_transaction(tx);
+/// Number of txs of the local mem block to ack for a delta of `tx_hashes` from
+/// `start`, or None if the local mem block misses txs of the delta or has
+/// diverged.
+fn acked_tx_count(local: &[H256], start: usize, tx_hashes: &[H256]) -> Option<usize> {
+    let end = start + tx_hashes.len();
+    (local.get(start..end)? == tx_hashes).then_some(end)
+}
+
+/// Push snapshot txs missing in the local mem block, or rebuild the mem block
+/// from the snapshot if they have diverged.
+async fn resync_mem_block(client: &BlockSyncClient, s: MemBlockSnapshot) {
+    if let Some(ref mem_pool) = client.mem_pool {
+        let mut mem_pool = mem_pool.lock().await;
+        let m = s.mem_block();
+        let txs: Vec<L2Transaction> = s.transactions().into_iter().collect();
+        let tx_hashes: Vec<H256> = txs.iter().map(|tx| tx.hash()).collect();
+        let withdrawal_hashes: Vec<H256> = m.withdrawals().into_iter().map(|w| w.hash()).collect();
+        let deposit_hashes: Vec<H256> = m
+            .deposits()
+            .into_iter()
+            .map(|d| hash(d.as_slice()))
+            .collect();
+        let local = mem_pool.mem_block();
+        let same_mem_block = local.block_info().as_slice() == m.block_info().as_slice()
+            && local.withdrawals() == withdrawal_hashes.as_slice()
+            && local
+                .deposits()
+                .iter()
+                .map(|d| hash(d.as_slice()))
+                .eq(deposit_hashes);
+        let start = match first_missing_tx(local.txs(), &tx_hashes) {
+            Some(start) if same_mem_block => start,
+            _ => {
+                log::warn!("mem block diverged, rebuild from snapshot");
+                let result = mem_pool.reset_read_only(None, true).and_then(|_| {
+                    mem_pool.refresh_mem_block(
+                        m.block_info(),
+                        m.withdrawals().into_iter().collect(),
+                        m.deposits().unpack(),
+                    )
+                });
                 if let Err(err) = result {
                     log::warn!("{:#}", err);
+                    return;
                 }
+                0
             }
+        };
+        if start < txs.len() {
+            log::info!("resync {} missing txs", txs.len() - start);
+        }
+        for tx in txs.into_iter().skip(start) {
+            push_transaction(&mut mem_pool, tx);
         }
     }
-    Ok(())
+    client.liveness.tick();
+}
+
+async fn refresh_mem_block(client: &BlockSyncClient, m: NextMemBlock) {
+    if let Some(ref mem_pool) = client.mem_pool {
+        let mut mem_pool = mem_pool.lock().await;
+        let result = mem_pool.refresh_mem_block(
+            m.block_info(),
+            m.withdrawals().into_iter().collect(),
+            m.deposits().unpack(),
+        );
+        if let Err(err) = result {
+            log::warn!("{:#}", err);
+        }
+    }
+    client.liveness.tick();
 }
 
 async fn handle_local_block(
@@ -347,6 +603,12 @@ async fn handle_local_block(
             return Ok(());
         }
     }
+    if let (Some(shadow_producer), Some(mem_pool)) = (&client.shadow_producer, &client.mem_pool) {
+        let mem_pool = mem_pool.lock().await;
+        if let Err(err) = shadow_producer.check(&mem_pool, &l.block()) {
+            log::warn!("[shadow production] block {}: {:#}", block_number, err);
+        }
+    }
     {
         log::info!("update local block");
         let mut chain = client.chain.lock().await;
@@ -361,6 +623,7 @@ async fn handle_local_block(
                 l.post_global_state(),
             )?;
             chain.calculate_and_store_finalized_custodians(&mut store_tx, block_number)?;
+            chain.prune_state_history(&mut store_tx, &l.block())?;
             store_tx.commit()?;
             anyhow::Ok(())
         })?;
@@ -371,9 +634,12 @@ async fn handle_local_block(
 
 pub struct P2PStream {
     id: SessionId,
+    peer_id: Option<PeerId>,
     control: ServiceAsyncControl,
     read_part: Option<SubstreamReadPart>,
     decoder: StreamDecoder,
+    peer_ledger: Arc<PeerLedger>,
+    metrics: Arc<SubstreamMetrics>,
 }
 
 impl P2PStream {
@@ -383,15 +649,19 @@ impl P2PStream {
     fn take_receiver(&mut self) -> Self {
         Self {
             id: self.id,
+            peer_id: self.peer_id.clone(),
             control: self.control.clone(),
             read_part: self.read_part.take(),
             decoder: core::mem::take(&mut self.decoder),
+            peer_ledger: self.peer_ledger.clone(),
+            metrics: self.metrics.clone(),
         }
     }
 
-    async fn recv(&mut self) -> Result<Option<Bytes>> {
+    pub(crate) async fn recv(&mut self) -> Result<Option<Bytes>> {
         let receiver = self.read_part.as_mut().context("stream is taken")?;
         Ok(if let Some(msg) = receiver.try_next().await? {
+            self.metrics.received(&msg);
             // Decompress message.
             Some(self.decoder.decode(&msg)?.into())
         } else {
@@ -399,31 +669,45 @@ impl P2PStream {
         })
     }
 
-    async fn send(&mut self, msg: Bytes) -> Result<()> {
+    pub(crate) async fn send(&mut self, msg: Bytes) -> Result<()> {
+        self.metrics.sent(&msg);
         self.control
             .send_message_to(self.id, P2P_SYNC_PROTOCOL, msg)
             .await?;
         Ok(())
     }
 
-    async fn disconnect(&mut self) -> Result<()> {
+    pub(crate) async fn disconnect(&mut self) -> Result<()> {
         self.control.disconnect(self.id).await?;
         Ok(())
     }
+
+    /// Refuse sessions of the peer for a while. This doesn't disconnect the
+    /// current session.
+    pub(crate) fn ban(&self) {
+        match self.peer_id {
+            Some(ref peer_id) => self.peer_ledger.ban(peer_id.clone()),
+            None => log::warn!("can't ban peer of session {}, no peer id", self.id),
+        }
+    }
 }
 
 /// The p2p protocol just sends the p2p stream to the client.
 pub fn block_sync_client_protocol(
     stream_inbox: Arc<std::sync::Mutex<Option<P2PStream>>>,
+    peer_ledger: Arc<PeerLedger>,
 ) -> ProtocolMeta {
     let spawn = FnSpawn(move |context, control, read_part| {
         let control = control.clone();
         let id = context.id;
         let stream = P2PStream {
             id,
+            peer_id: extract_peer_id(&context.address),
             control,
             read_part: Some(read_part),
             decoder: StreamDecoder::new(),
+            peer_ledger: peer_ledger.clone(),
+            metrics: Arc::new(SubstreamMetrics::new(P2P_SYNC_PROTOCOL_NAME, &context)),
         };
         *stream_inbox.lock().unwrap() = Some(stream);
     });
@@ -462,3 +746,25 @@ fn check_number_hash(client: &BlockSyncClient, number_hash: &NumberHash) -> Resu
     ensure!(store_block_hash.as_slice() == number_hash.block_hash().as_slice());
     Ok(())
 }
+
+#[test]
+fn test_first_missing_tx() {
+    let (a, b, c) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+    assert_eq!(first_missing_tx(&[], &[a, b]), Some(0));
+    assert_eq!(first_missing_tx(&[a], &[a, b, c]), Some(1));
+    assert_eq!(first_missing_tx(&[a, b], &[a, b]), Some(2));
+    // Missed a tx in the middle, or has a tx the full node doesn't.
+    assert_eq!(first_missing_tx(&[a, c], &[a, b, c]), None);
+    assert_eq!(first_missing_tx(&[a, b, c], &[a, b]), None);
+}
+
+#[test]
+fn test_acked_tx_count() {
+    let (a, b, c) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+    assert_eq!(acked_tx_count(&[a, b], 0, &[a, b]), Some(2));
+    assert_eq!(acked_tx_count(&[a, b, c], 1, &[b]), Some(2));
+    assert_eq!(acked_tx_count(&[a], 1, &[]), Some(1));
+    // Missed txs of the delta, or has other txs.
+    assert_eq!(acked_tx_count(&[a], 1, &[b]), None);
+    assert_eq!(acked_tx_count(&[a, c], 1, &[b]), None);
+}
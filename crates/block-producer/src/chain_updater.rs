@@ -7,6 +7,7 @@ use std::{
 
 use anyhow::{anyhow, Context, Result};
 use ckb_fixed_hash::H256;
+use futures::{stream, StreamExt, TryStreamExt};
 use gw_chain::chain::{Chain, ChallengeCell, L1Action, L1ActionContext, SyncParam};
 use gw_rpc_client::rpc_client::RPCClient;
 use gw_types::{
@@ -24,6 +25,9 @@ use gw_utils::RollupContext;
 use tokio::sync::Mutex;
 use tracing::instrument;
 
+/// Maximum number of concurrent queries of deposit cells in a transaction.
+const MAX_CONCURRENT_INPUT_QUERIES: usize = 16;
+
 #[derive(thiserror::Error, Debug)]
 #[error("chain updater query l1 tx {tx_hash} error {source}")]
 pub struct QueryL1TxError {
@@ -65,6 +69,14 @@ impl ChainUpdater {
 
     #[instrument(skip_all)]
     pub async fn update_single(&self, tx_hash: &H256) -> anyhow::Result<()> {
+        let update = self.fetch_l1_action(tx_hash).await?;
+        self.apply_l1_action(update).await
+    }
+
+    /// Query the L1 transaction and the cells it needs, without touching the
+    /// chain, so it can be fetched ahead of applying.
+    #[instrument(skip_all)]
+    pub async fn fetch_l1_action(&self, tx_hash: &H256) -> anyhow::Result<L1Action> {
         let tx = self
             .rpc_client
             .ckb
@@ -106,10 +118,14 @@ impl ChainUpdater {
             }
         };
 
-        let update = L1Action {
+        Ok(L1Action {
             transaction: tx,
             context,
-        };
+        })
+    }
+
+    #[instrument(skip_all)]
+    pub async fn apply_l1_action(&self, update: L1Action) -> anyhow::Result<()> {
         let sync_param = SyncParam {
             reverts: vec![],
             updates: vec![update],
@@ -204,18 +220,29 @@ impl ChainUpdater {
         &self,
         tx: &Transaction,
     ) -> Result<(DepositInfoVec, HashSet<Script>)> {
+        // Load transactions of inputs concurrently, a block may collect
+        // hundreds of deposits.
+        let inputs: Vec<_> = tx.raw().inputs().into_iter().collect();
+        let input_txs: Vec<Transaction> = stream::iter(inputs.iter())
+            .map(|input| async move {
+                let tx_hash: H256 = input.previous_output().tx_hash().unpack();
+                let tx = self
+                    .rpc_client
+                    .ckb
+                    .get_packed_transaction(tx_hash.0)
+                    .await?
+                    .ok_or_else(|| QueryL1TxError::new(&tx_hash, anyhow!("cannot locate tx")))?;
+                Result::<_>::Ok(tx)
+            })
+            .buffered(MAX_CONCURRENT_INPUT_QUERIES)
+            .try_collect()
+            .await?;
+
         let mut deposits = DepositInfoVec::new_builder();
         let mut asset_type_scripts = HashSet::new();
-        for input in tx.raw().inputs().into_iter() {
+        for (input, tx) in inputs.into_iter().zip(input_txs) {
             // Load cell denoted by the transaction input
-            let tx_hash: H256 = input.previous_output().tx_hash().unpack();
             let index = input.previous_output().index().unpack();
-            let tx = self
-                .rpc_client
-                .ckb
-                .get_packed_transaction(tx_hash.0)
-                .await?
-                .ok_or_else(|| QueryL1TxError::new(&tx_hash, anyhow!("cannot locate tx")))?;
             let cell_output = tx
                 .raw()
                 .outputs()
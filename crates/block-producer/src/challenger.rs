@@ -16,7 +16,7 @@ use gw_challenge::{
     },
     enter_challenge::EnterChallenge,
     offchain::{
-        mock_cancel_challenge_tx,
+        dump_tx, mock_cancel_challenge_tx,
         verify_tx::{verify_tx, TxWithContext},
         OffChainMockContext,
     },
@@ -25,7 +25,10 @@ use gw_challenge::{
 };
 use gw_config::{BlockProducerConfig, DebugConfig};
 use gw_generator::types::vm::ChallengeContext;
-use gw_jsonrpc_types::{test_mode::TestModePayload, JsonCalcHash};
+use gw_jsonrpc_types::{
+    test_mode::{ChallengeStage, ChallengeStep, TestModePayload},
+    JsonCalcHash,
+};
 use gw_rpc_client::{contract::ContractsCellDepManager, rpc_client::RPCClient};
 use gw_types::{
     bytes::Bytes,
@@ -47,6 +50,7 @@ use tracing::instrument;
 
 use crate::{
     cleaner::{Cleaner, Verifier},
+    debugger,
     test_mode_control::TestModeControl,
     types::ChainEvent,
     utils,
@@ -70,6 +74,8 @@ pub struct Challenger {
     offchain_mock_context: OffChainMockContext,
     contracts_dep_manager: ContractsCellDepManager,
     last_submit_tx: Option<H256>,
+    // Target of the last dry run challenge, so its tx is only dumped once.
+    last_dry_run_target: Option<ChallengeTarget>,
 }
 
 pub struct ChallengerNewArgs {
@@ -118,6 +124,7 @@ impl Challenger {
             offchain_mock_context,
             contracts_dep_manager,
             last_submit_tx: None,
+            last_dry_run_target: None,
         }
     }
 
@@ -127,6 +134,8 @@ impl Challenger {
             match tests_control.payload().await {
                 Some(TestModePayload::Challenge { .. })
                     | Some(TestModePayload::WaitForChallengeMaturity)
+                    | Some(TestModePayload::CancelChallenge)
+                    | Some(TestModePayload::RevertChallenge)
                     | Some(TestModePayload::None) => (),
                     Some(TestModePayload::BadBlock { .. }) // Payload not match (BadBlock for block producer)
                         | None => return Ok(()), // Wait payload
@@ -153,7 +162,18 @@ impl Challenger {
                     Status::Halting => return Ok(()), // Already halting, do nothing, we can't challenge block when rollup is halted
                     Status::Running => {
                         let context = tests_control.challenge().await?;
-                        return self.challenge_block(rollup, context).await;
+                        let target = context.target.clone();
+                        self.challenge_block(rollup, context).await?;
+                        if let Some(tx_hash) = self.last_submit_tx {
+                            tests_control
+                                .record_challenge_step(
+                                    ChallengeStep::Challenge,
+                                    Some(&target),
+                                    tx_hash,
+                                )
+                                .await;
+                        }
+                        return Ok(());
                     }
                 };
             }
@@ -163,13 +183,22 @@ impl Challenger {
         log::debug!("load chain last sync event {:?}", last_sync_event);
 
         match last_sync_event {
-            SyncEvent::Success => Ok(()),
+            SyncEvent::Success => {
+                if let Some(ref tests_control) = self.tests_control {
+                    tests_control
+                        .set_challenge_stage(ChallengeStage::None, None)
+                        .await;
+                }
+                Ok(())
+            }
             SyncEvent::BadBlock { context } => {
                 if let Some(ref tests_control) = self.tests_control {
+                    tests_control
+                        .set_challenge_stage(ChallengeStage::BadBlock, Some(&context.target))
+                        .await;
                     match tests_control.payload().await {
-                        Some(TestModePayload::WaitForChallengeMaturity) => return Ok(()), // do nothing
                         Some(TestModePayload::None) => tests_control.clear_none().await?,
-                        _ => unreachable!(),
+                        _ => return Ok(()), // Wait payload
                     }
                 }
                 {
@@ -182,17 +211,34 @@ impl Challenger {
                 self.challenge_block(rollup, context).await
             }
             SyncEvent::BadChallenge { cell, context } => {
-                if let Some(ref tests_control) = self.tests_control {
+                let tests_control = self.tests_control.clone();
+                let mut step_payload = None;
+                if let Some(ref tests_control) = tests_control {
+                    let target = extract_challenge_target(&cell).ok();
+                    tests_control
+                        .set_challenge_stage(ChallengeStage::BadChallenge, target.as_ref())
+                        .await;
                     match tests_control.payload().await {
-                        Some(TestModePayload::WaitForChallengeMaturity) => return Ok(()), // do nothing
                         Some(TestModePayload::None) => tests_control.clear_none().await?,
-                        _ => unreachable!(),
+                        // Consumed after cancel tx is submitted
+                        Some(TestModePayload::CancelChallenge) => {
+                            step_payload = Some(TestModePayload::CancelChallenge)
+                        }
+                        _ => return Ok(()), // Wait payload
                     }
                 }
-                self.cancel_challenge(rollup, cell, *context).await
+                self.cancel_challenge(rollup, cell, *context).await?;
+                self.record_tests_step(tests_control, step_payload, ChallengeStep::Cancel)
+                    .await
             }
             SyncEvent::WaitChallenge { cell, context } => {
-                if let Some(ref tests_control) = self.tests_control {
+                let tests_control = self.tests_control.clone();
+                let mut step_payload = None;
+                if let Some(ref tests_control) = tests_control {
+                    let target = extract_challenge_target(&cell).ok();
+                    tests_control
+                        .set_challenge_stage(ChallengeStage::WaitChallenge, target.as_ref())
+                        .await;
                     match tests_control.payload().await {
                         Some(TestModePayload::WaitForChallengeMaturity) => {
                             tests_control
@@ -200,15 +246,41 @@ impl Challenger {
                                 .await?
                         }
                         Some(TestModePayload::None) => tests_control.clear_none().await?,
-                        _ => unreachable!(),
+                        // Consumed after revert tx is submitted
+                        Some(TestModePayload::RevertChallenge) => {
+                            step_payload = Some(TestModePayload::RevertChallenge)
+                        }
+                        _ => return Ok(()), // Wait payload
                     }
                 }
                 let tip_number = to_tip_number(&event);
-                self.revert(rollup, cell, context, tip_number).await
+                self.revert(rollup, cell, context, tip_number).await?;
+                self.record_tests_step(tests_control, step_payload, ChallengeStep::Revert)
+                    .await
             }
         }
     }
 
+    async fn record_tests_step(
+        &self,
+        tests_control: Option<TestModeControl>,
+        step_payload: Option<TestModePayload>,
+        step: ChallengeStep,
+    ) -> Result<()> {
+        let (tests_control, tx_hash) = match (tests_control, self.last_submit_tx) {
+            (Some(tests_control), Some(tx_hash)) => (tests_control, tx_hash),
+            _ => return Ok(()),
+        };
+
+        tests_control
+            .record_challenge_step(step, None, tx_hash)
+            .await;
+        if let Some(payload) = step_payload {
+            tests_control.consume(&payload).await?;
+        }
+        Ok(())
+    }
+
     async fn challenge_block(
         &mut self,
         rollup_state: RollupState,
@@ -219,7 +291,14 @@ impl Challenger {
             return Ok(());
         }
 
+        let dry_run = self.config.challenger_config.dry_run;
+        let last_dry_run_target = self.last_dry_run_target.as_ref();
+        if dry_run && last_dry_run_target.map(|t| t.as_slice()) == Some(context.target.as_slice()) {
+            return Ok(());
+        }
+
         let block_numer = context.witness.raw_l2block().number().unpack();
+        let target = context.target.clone();
         let rewards_lock = {
             let challenger_config = &self.config.challenger_config;
             challenger_config.rewards_receiver_lock.clone().into()
@@ -229,6 +308,16 @@ impl Challenger {
             EnterChallenge::new(prev_state, &self.rollup_context, context, rewards_lock);
         let challenge_output = enter_challenge.build_output();
 
+        let target_type = ChallengeTargetType::try_from(target.target_type())
+            .map_err(|_| anyhow!("invalid challenge type"))?;
+        if target_type == ChallengeTargetType::Withdrawal {
+            self.check_withdrawal_challenge_offchain(
+                &target,
+                challenge_output.post_global_state.clone(),
+            )
+            .await?;
+        }
+
         // Build challenge transaction
         let omni_lock_code_hash = self.contracts_dep_manager.load_scripts().omni_lock.hash();
         let mut tx_skeleton = TransactionSkeleton::new(omni_lock_code_hash.0);
@@ -282,13 +371,104 @@ impl Challenger {
             bail!(err);
         }
 
-        let tx_hash = self.rpc_client.send_transaction(&tx).await?;
+        if dry_run {
+            utils::dump_transaction(&self.debug_config.debug_tx_dump_path, &self.rpc_client, &tx)
+                .await;
+            log::info!(
+                "Dry run, dump challenge block {} tx {} into {}",
+                block_numer,
+                to_hex(&tx.hash()),
+                self.debug_config.debug_tx_dump_path.display()
+            );
+            self.last_dry_run_target = Some(target);
+            return Ok(());
+        }
+
+        let tx_hash = match self.rpc_client.send_transaction(&tx).await {
+            Ok(tx_hash) => tx_hash,
+            Err(err) => {
+                utils::dump_transaction(
+                    &self.debug_config.debug_tx_dump_path,
+                    &self.rpc_client,
+                    &tx,
+                )
+                .await;
+                bail!(err);
+            }
+        };
         log::info!("Challenge block {} in tx {}", block_numer, to_hex(&tx_hash));
         self.last_submit_tx = Some(tx_hash);
 
         Ok(())
     }
 
+    /// Withdrawal signatures are finally judged by the sender's lock script
+    /// on L1. Make sure the challenge can't be cancelled before putting the
+    /// challenge capacity at stake.
+    async fn check_withdrawal_challenge_offchain(
+        &self,
+        target: &ChallengeTarget,
+        challenged_global_state: GlobalState,
+    ) -> Result<()> {
+        let chain = self.chain.lock().await;
+        let cancel = || -> Result<_> {
+            let verify_context = chain.build_verify_context(target)?;
+            let mock_output = mock_cancel_challenge_tx(
+                &self.offchain_mock_context.mock_rollup,
+                challenged_global_state,
+                target.clone(),
+                verify_context,
+                None,
+            )?;
+            verify_tx(
+                &self.offchain_mock_context.rollup_cell_deps,
+                TxWithContext::from(mock_output),
+                MAX_CANCEL_CYCLES,
+            )
+        };
+        match cancel() {
+            Ok(_) => bail!("withdrawal signature is valid on L1, the challenge would be cancelled"),
+            Err(err) => {
+                log::info!("withdrawal challenge can't be cancelled: {:#}", err);
+                Ok(())
+            }
+        }
+    }
+
+    /// Dump the cancel challenge tx mocked offchain. Unlike the submitted one,
+    /// it doesn't depend on live L1 cells, so a rejected cancellation can be
+    /// reproduced by ckb-debugger later.
+    fn dump_offchain_cancel_tx(
+        &self,
+        global_state: GlobalState,
+        target: ChallengeTarget,
+        context: VerifyContext,
+        load_data_strategy: Option<LoadDataStrategy>,
+    ) {
+        let dump = || -> Result<_> {
+            let mock_output = mock_cancel_challenge_tx(
+                &self.offchain_mock_context.mock_rollup,
+                global_state,
+                target,
+                context,
+                load_data_strategy,
+            )?;
+            let tx_hash = mock_output.tx.hash();
+            let mock_tx = dump_tx(
+                &self.offchain_mock_context.rollup_cell_deps,
+                TxWithContext::from(mock_output),
+            )?;
+            debugger::dump_mock_transaction(
+                &self.debug_config.debug_tx_dump_path,
+                &tx_hash,
+                &mock_tx,
+            )
+        };
+        if let Err(err) = dump() {
+            log::error!("Failed to dump offchain cancel challenge tx: {:#}", err);
+        }
+    }
+
     async fn cancel_challenge(
         &mut self,
         rollup_state: RollupState,
@@ -301,12 +481,30 @@ impl Challenger {
         }
 
         let prev_state = rollup_state.get_state().to_owned();
-        let load_data_strategy = validate_load_data_strategy_offchain(
+        let target = extract_challenge_target(&challenge_cell)?;
+        let load_data_strategy = match validate_load_data_strategy_offchain(
             &self.offchain_mock_context,
             prev_state.clone(),
             &challenge_cell,
             context.clone(),
-        )?;
+        ) {
+            Ok(strategy) => strategy,
+            Err(err) => {
+                self.dump_offchain_cancel_tx(prev_state, target, context, None);
+                bail!(err);
+            }
+        };
+        let dump_offchain_cancel_tx = {
+            let (prev_state, target, context) = (prev_state.clone(), target, context.clone());
+            move |challenger: &Self| {
+                challenger.dump_offchain_cancel_tx(
+                    prev_state,
+                    target,
+                    context,
+                    Some(load_data_strategy),
+                )
+            }
+        };
 
         let challenge_cell = to_cell_info(challenge_cell);
         let burn_lock = self
@@ -339,7 +537,19 @@ impl Challenger {
                 .await?
         };
         let verifier_spent_inputs = extract_inputs(&tx);
-        let verifier_tx_hash = self.rpc_client.send_transaction(&tx).await?;
+        let verifier_tx_hash = match self.rpc_client.send_transaction(&tx).await {
+            Ok(tx_hash) => tx_hash,
+            Err(err) => {
+                utils::dump_transaction(
+                    &self.debug_config.debug_tx_dump_path,
+                    &self.rpc_client,
+                    &tx,
+                )
+                .await;
+                dump_offchain_cancel_tx(self);
+                bail!(err);
+            }
+        };
         log::info!("Create verifier in tx {}", to_hex(&verifier_tx_hash));
 
         tokio::time::timeout(
@@ -386,6 +596,7 @@ impl Challenger {
         if let Err(err) = self.dry_run_transaction(&tx, "cancel challenge").await {
             utils::dump_transaction(&self.debug_config.debug_tx_dump_path, &self.rpc_client, &tx)
                 .await;
+            dump_offchain_cancel_tx(self);
             bail!(err);
         }
 
@@ -406,6 +617,13 @@ impl Challenger {
             Err(err) => {
                 self.cleaner.watch_verifier(verifier, None).await;
                 log::warn!("Cancel challenge failed {}", err);
+                utils::dump_transaction(
+                    &self.debug_config.debug_tx_dump_path,
+                    &self.rpc_client,
+                    &tx,
+                )
+                .await;
+                dump_offchain_cancel_tx(self);
             }
         }
 
@@ -559,7 +777,18 @@ impl Challenger {
             bail!(err);
         }
 
-        let tx_hash = self.rpc_client.send_transaction(&tx).await?;
+        let tx_hash = match self.rpc_client.send_transaction(&tx).await {
+            Ok(tx_hash) => tx_hash,
+            Err(err) => {
+                utils::dump_transaction(
+                    &self.debug_config.debug_tx_dump_path,
+                    &self.rpc_client,
+                    &tx,
+                )
+                .await;
+                bail!(err);
+            }
+        };
         log::info!("Revert block in tx {}", to_hex(&tx_hash));
         self.last_submit_tx = Some(tx_hash);
 
@@ -1,5 +1,7 @@
+use crate::custodian::CustodianConsolidation;
 use crate::types::ChainEvent;
 use gw_utils::genesis_info::CKBGenesisInfo;
+use gw_utils::local_cells::LocalCellsManager;
 use gw_utils::transaction_skeleton::TransactionSkeleton;
 use gw_utils::{fee::fill_tx_fee, wallet::Wallet};
 
@@ -8,8 +10,8 @@ use gw_challenge::cancel_challenge::RecoverAccountsContext;
 use gw_rpc_client::rpc_client::RPCClient;
 use gw_types::core::Status;
 use gw_types::h256::*;
-use gw_types::offchain::{global_state_from_slice, InputCellInfo};
-use gw_types::packed::{CellDep, Transaction, WitnessArgs};
+use gw_types::offchain::{global_state_from_slice, CompatibleFinalizedTimepoint, InputCellInfo};
+use gw_types::packed::{CellDep, GlobalState, Transaction, WitnessArgs};
 use gw_types::prelude::Unpack;
 use tracing::instrument;
 
@@ -61,6 +63,8 @@ pub struct Cleaner {
     wallet: Wallet,
     consumed_verifiers: ConsumedVerifiers,
     fee_rate: u64,
+    local_cells_manager: Arc<Mutex<LocalCellsManager>>,
+    custodian_consolidation: Option<Arc<CustodianConsolidation>>,
 }
 
 impl Cleaner {
@@ -69,6 +73,8 @@ impl Cleaner {
         ckb_genesis_info: CKBGenesisInfo,
         wallet: Wallet,
         fee_rate: u64,
+        local_cells_manager: Arc<Mutex<LocalCellsManager>>,
+        custodian_consolidation: Option<Arc<CustodianConsolidation>>,
     ) -> Self {
         Cleaner {
             rpc_client,
@@ -76,18 +82,38 @@ impl Cleaner {
             wallet,
             consumed_verifiers: Arc::new(Mutex::new(Vec::new())),
             fee_rate,
+            local_cells_manager,
+            custodian_consolidation,
         }
     }
 
     #[instrument(skip_all, name = "cleaner handle_event")]
     pub async fn handle_event(&self, _event: ChainEvent) -> Result<()> {
-        if matches!(self.query_rollup_status().await?, Status::Halting) {
+        let global_state = self.query_global_state().await?;
+        let status: u8 = global_state.status().into();
+        let status = Status::try_from(status).map_err(|n| anyhow!("invalid status {}", n))?;
+        if matches!(status, Status::Halting) {
             return Ok(());
         }
 
         self.reclaim_uncomsumed_verifiers().await?;
         self.prune().await?;
 
+        if let Some(ref consolidation) = self.custodian_consolidation {
+            let compatible_finalized_timepoint = CompatibleFinalizedTimepoint::from_global_state(
+                &global_state,
+                self.rpc_client.rollup_config.finality_blocks().unpack(),
+            );
+            let local_cells_manager = self.local_cells_manager.lock().await;
+            consolidation
+                .check(
+                    &local_cells_manager,
+                    &self.rpc_client,
+                    &compatible_finalized_timepoint,
+                )
+                .await?;
+        }
+
         Ok(())
     }
 
@@ -197,13 +223,10 @@ impl Cleaner {
         Ok(())
     }
 
-    async fn query_rollup_status(&self) -> Result<Status> {
+    async fn query_global_state(&self) -> Result<GlobalState> {
         let query_cell = self.rpc_client.query_rollup_cell().await?;
         let rollup_cell = query_cell.ok_or_else(|| anyhow!("rollup cell not found"))?;
-        let global_state = global_state_from_slice(&rollup_cell.data)?;
-
-        let status: u8 = global_state.status().into();
-        Status::try_from(status).map_err(|n| anyhow!("invalid status {}", n))
+        Ok(global_state_from_slice(&rollup_cell.data)?)
     }
 
     async fn build_reclaim_verifier_tx(&self, verifier: Verifier) -> Result<Transaction> {
@@ -0,0 +1,99 @@
+use anyhow::{bail, Result};
+use gw_config::{Config, NodeMode};
+
+/// Subsystems to run, resolved from `[components]` config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Components {
+    pub rpc: bool,
+    pub p2p: bool,
+    pub indexer_publisher: bool,
+    pub metrics: bool,
+    pub block_producer: bool,
+    pub challenger: bool,
+}
+
+impl Components {
+    pub fn resolve(config: &Config) -> Result<Self> {
+        let c = &config.components;
+        let readonly = config.node_mode == NodeMode::ReadOnly;
+
+        let resolve =
+            |name: &str, enabled: Option<bool>, supported: bool, reason: &str| match enabled {
+                Some(true) if !supported => bail!("component {} requires {}", name, reason),
+                Some(enabled) => Ok(enabled),
+                None => Ok(supported),
+            };
+
+        let rpc = resolve("rpc", c.rpc, true, "")?;
+        let metrics = resolve("metrics", c.metrics, rpc, "rpc")?;
+        let p2p = resolve(
+            "p2p",
+            c.p2p,
+            config.p2p_network_config.is_some(),
+            "p2p_network_config",
+        )?;
+        let block_producer = resolve(
+            "block_producer",
+            c.block_producer,
+            !readonly && config.block_producer.is_some(),
+            "fullnode or test mode and block_producer config",
+        )?;
+        let challenger = resolve(
+            "challenger",
+            c.challenger,
+            !readonly && config.block_producer.is_some(),
+            "fullnode or test mode and block_producer config",
+        )?;
+        let indexer_publisher = resolve(
+            "indexer_publisher",
+            c.indexer_publisher,
+            !readonly && config.block_producer.is_some() && p2p,
+            "fullnode or test mode, block_producer config and p2p",
+        )?;
+
+        Ok(Self {
+            rpc,
+            p2p,
+            indexer_publisher,
+            metrics,
+            block_producer,
+            challenger,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gw_config::{BlockProducerConfig, ComponentsConfig, P2PNetworkConfig};
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_components() {
+        let mut config = Config::default();
+        let c = Components::resolve(&config).unwrap();
+        assert!(c.rpc && c.metrics);
+        assert!(!c.p2p && !c.block_producer && !c.challenger && !c.indexer_publisher);
+
+        config.components = ComponentsConfig {
+            block_producer: Some(true),
+            ..Default::default()
+        };
+        assert!(Components::resolve(&config).is_err());
+
+        config.node_mode = NodeMode::FullNode;
+        config.block_producer = Some(BlockProducerConfig::default());
+        config.p2p_network_config = Some(P2PNetworkConfig::default());
+        config.components = ComponentsConfig {
+            challenger: Some(false),
+            rpc: Some(false),
+            ..Default::default()
+        };
+        let c = Components::resolve(&config).unwrap();
+        assert!(c.block_producer && c.p2p && c.indexer_publisher);
+        assert!(!c.challenger && !c.rpc && !c.metrics);
+
+        config.components.metrics = Some(true);
+        assert!(Components::resolve(&config).is_err());
+    }
+}
@@ -1,8 +1,16 @@
 #![allow(clippy::mutable_key_type)]
 
-use std::collections::HashSet;
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
 
 use anyhow::{anyhow, bail, Result};
+use gw_config::CustodianConsolidationConfig;
 use gw_rpc_client::{
     indexer_types::{Order, SearchKey, SearchKeyFilter},
     rpc_client::{QueryResult, RPCClient},
@@ -22,14 +30,85 @@ use tracing::instrument;
 
 pub const MAX_CUSTODIANS: usize = 50;
 
+/// Finalized custodian consolidation.
+///
+/// Custodian cells can only be spent together with the rollup cell, so they
+/// are merged by submission txs. The cleaner checks the number of mergeable
+/// finalized custodians every `interval_secs`, and once there are enough the
+/// next submission tx merges up to `max_custodians` of them instead of
+/// `MAX_CUSTODIANS`.
+pub struct CustodianConsolidation {
+    config: CustodianConsolidationConfig,
+    last_check: Mutex<Option<Instant>>,
+    scheduled: AtomicBool,
+}
+
+impl CustodianConsolidation {
+    pub fn new(config: CustodianConsolidationConfig) -> Self {
+        Self {
+            config,
+            last_check: Mutex::new(None),
+            scheduled: AtomicBool::new(false),
+        }
+    }
+
+    /// Maximum number of custodians to merge in the next submission tx.
+    pub fn take_max_custodians(&self) -> usize {
+        if self.scheduled.swap(false, Ordering::SeqCst) {
+            self.config.max_custodians.max(MAX_CUSTODIANS)
+        } else {
+            MAX_CUSTODIANS
+        }
+    }
+
+    #[instrument(skip_all, err(Debug))]
+    pub async fn check(
+        &self,
+        local_cells_manager: &LocalCellsManager,
+        rpc_client: &RPCClient,
+        compatible_finalized_timepoint: &CompatibleFinalizedTimepoint,
+    ) -> Result<()> {
+        {
+            let mut last_check = self.last_check.lock().unwrap();
+            let interval = Duration::from_secs(self.config.interval_secs);
+            if matches!(*last_check, Some(t) if t.elapsed() < interval) {
+                return Ok(());
+            }
+            *last_check = Some(Instant::now());
+        }
+        if self.scheduled.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let query_result = query_mergeable_custodians(
+            local_cells_manager,
+            rpc_client,
+            CollectedCustodianCells::default(),
+            compatible_finalized_timepoint,
+            self.config.min_custodians,
+        )
+        .await?;
+        if let QueryResult::Full(collected) = query_result {
+            log::info!(
+                "[custodian consolidation] {} mergeable custodians, merge up to {} in next submission",
+                collected.cells_info.len(),
+                self.config.max_custodians.max(MAX_CUSTODIANS)
+            );
+            self.scheduled.store(true, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+}
+
 #[instrument(skip_all, err(Debug), fields(timepoint = ?compatible_finalized_timepoint))]
 pub async fn query_mergeable_custodians(
     local_cells_manager: &LocalCellsManager,
     rpc_client: &RPCClient,
     collected_custodians: CollectedCustodianCells,
     compatible_finalized_timepoint: &CompatibleFinalizedTimepoint,
+    max_custodians: usize,
 ) -> Result<QueryResult<CollectedCustodianCells>> {
-    if collected_custodians.cells_info.len() >= MAX_CUSTODIANS {
+    if collected_custodians.cells_info.len() >= max_custodians {
         return Ok(QueryResult::Full(collected_custodians));
     }
 
@@ -38,7 +117,7 @@ pub async fn query_mergeable_custodians(
         rpc_client,
         collected_custodians,
         compatible_finalized_timepoint,
-        MAX_CUSTODIANS,
+        max_custodians,
     )
     .await?;
     if matches!(query_result, QueryResult::Full(_)) {
@@ -50,6 +129,7 @@ pub async fn query_mergeable_custodians(
         query_result.expect_any(),
         compatible_finalized_timepoint,
         local_cells_manager,
+        max_custodians,
     )
     .await
 }
@@ -60,8 +140,9 @@ async fn query_mergeable_sudt_custodians(
     collected: CollectedCustodianCells,
     compatible_finalized_timepoint: &CompatibleFinalizedTimepoint,
     local_cells_manager: &LocalCellsManager,
+    max_custodians: usize,
 ) -> Result<QueryResult<CollectedCustodianCells>> {
-    if collected.cells_info.len() >= MAX_CUSTODIANS {
+    if collected.cells_info.len() >= max_custodians {
         return Ok(QueryResult::Full(collected));
     }
 
@@ -70,7 +151,7 @@ async fn query_mergeable_sudt_custodians(
         rpc_client,
         collected,
         compatible_finalized_timepoint,
-        MAX_CUSTODIANS,
+        max_custodians,
     )
     .await
 }
@@ -51,6 +51,29 @@ pub async fn dump_transaction<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Dump a mock transaction built offchain, i.e. without resolving cells from
+/// CKB, so it can be reproduced by ckb-debugger after the cells are spent.
+pub fn dump_mock_transaction<P: AsRef<Path>>(
+    dir: P,
+    tx_hash: &H256,
+    mock_tx: &ReprMockTransaction,
+) -> Result<PathBuf> {
+    // ensure dir is exist
+    create_dir_all(&dir)?;
+
+    let mut dump_path = PathBuf::new();
+    dump_path.push(dir);
+    dump_path.push(format!("{}-offchain-mock-tx.json", hex::encode(tx_hash)));
+    let json_content = serde_json::to_string_pretty(mock_tx)?;
+    log::info!(
+        "Dump offchain mock transaction {} to {:?}",
+        hex::encode(tx_hash),
+        dump_path
+    );
+    write(&dump_path, json_content)?;
+    Ok(dump_path)
+}
+
 pub async fn build_mock_transaction(
     rpc_client: &RPCClient,
     tx: &Transaction,
@@ -0,0 +1,73 @@
+//! Stream execution summaries of attached blocks to the configured sink.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use gw_config::{ExecutionEventSinkConfig, ExecutionEventsConfig};
+use gw_jsonrpc_types::godwoken::BlockExecutionEvents;
+use gw_utils::ExponentialBackoff;
+use reqwest::{header::CONTENT_TYPE, Client};
+use tokio::sync::mpsc;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Spawn the sink task, returns the sender for `Chain`.
+pub fn spawn_execution_events_sink(
+    config: &ExecutionEventsConfig,
+) -> Result<mpsc::Sender<BlockExecutionEvents>> {
+    let (sender, receiver) = mpsc::channel(config.queue_size);
+    match config.sink {
+        ExecutionEventSinkConfig::Http { ref url } => {
+            let client = Client::builder()
+                .timeout(HTTP_TIMEOUT)
+                .build()
+                .context("execution events http client")?;
+            log::info!("[execution events] streaming to {}", url);
+            tokio::spawn(run_http_sink(client, url.clone(), receiver));
+        }
+    }
+    Ok(sender)
+}
+
+async fn run_http_sink(
+    client: Client,
+    url: String,
+    mut receiver: mpsc::Receiver<BlockExecutionEvents>,
+) {
+    let mut backoff = ExponentialBackoff::new(Duration::from_secs(1));
+    while let Some(events) = receiver.recv().await {
+        let body = match serde_json::to_vec(&events) {
+            Ok(body) => body,
+            Err(err) => {
+                log::error!("[execution events] serialize: {}", err);
+                continue;
+            }
+        };
+        // Retry until delivered, new blocks queue up meanwhile.
+        loop {
+            let result = client
+                .post(&url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(body.clone())
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status());
+            match result {
+                Ok(_) => {
+                    backoff.reset();
+                    break;
+                }
+                Err(err) => {
+                    let next = backoff.next_sleep();
+                    log::warn!(
+                        "[execution events] send block #{}, will retry in {:.2}s: {}",
+                        events.block_number,
+                        next.as_secs_f64(),
+                        err
+                    );
+                    tokio::time::sleep(next).await;
+                }
+            }
+        }
+    }
+}
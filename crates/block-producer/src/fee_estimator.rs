@@ -0,0 +1,120 @@
+//! L1 fee rate estimation. Fee rates are in shannons/KB.
+
+use std::sync::Arc;
+
+use anyhow::{ensure, Context, Result};
+use async_trait::async_trait;
+use gw_config::{BlockProducerConfig, FeeEstimatorConfig, FeeEstimatorStrategy};
+use gw_rpc_client::ckb_client::CkbClient;
+
+#[async_trait]
+pub trait FeeEstimator: Send + Sync {
+    async fn estimate_fee_rate(&self) -> Result<u64>;
+}
+
+pub struct StaticFeeEstimator(pub u64);
+
+#[async_trait]
+impl FeeEstimator for StaticFeeEstimator {
+    async fn estimate_fee_rate(&self) -> Result<u64> {
+        Ok(self.0)
+    }
+}
+
+/// Median fee rate of recent L1 txs.
+pub struct MedianFeeEstimator {
+    ckb: CkbClient,
+    target_blocks: Option<u64>,
+    fallback: u64,
+}
+
+#[async_trait]
+impl FeeEstimator for MedianFeeEstimator {
+    async fn estimate_fee_rate(&self) -> Result<u64> {
+        let statics = (self.ckb)
+            .get_fee_rate_statics(self.target_blocks.map(Into::into))
+            .await
+            .context("get_fee_rate_statics")?;
+        match statics {
+            Some(statics) => Ok(statics.median.value()),
+            None => {
+                log::debug!("[fee estimator] no fee rate statistics, use fallback");
+                Ok(self.fallback)
+            }
+        }
+    }
+}
+
+/// Clamp estimates of the inner estimator.
+pub struct CappedFeeEstimator<E> {
+    inner: E,
+    min_fee_rate: u64,
+    max_fee_rate: u64,
+}
+
+#[async_trait]
+impl<E: FeeEstimator> FeeEstimator for CappedFeeEstimator<E> {
+    async fn estimate_fee_rate(&self) -> Result<u64> {
+        let fee_rate = self.inner.estimate_fee_rate().await?;
+        Ok(fee_rate.clamp(self.min_fee_rate, self.max_fee_rate))
+    }
+}
+
+/// Estimator for `config.fee_estimator`, static `config.fee_rate` if None.
+pub fn build_fee_estimator(
+    config: &BlockProducerConfig,
+    ckb: &CkbClient,
+) -> Result<Arc<dyn FeeEstimator>> {
+    let estimator_config = match config.fee_estimator {
+        Some(ref c) => c,
+        None => return Ok(Arc::new(StaticFeeEstimator(config.fee_rate))),
+    };
+    let FeeEstimatorConfig {
+        ref strategy,
+        min_fee_rate,
+        max_fee_rate,
+    } = *estimator_config;
+    let min_fee_rate = min_fee_rate.unwrap_or(0);
+    let max_fee_rate = max_fee_rate.unwrap_or(u64::MAX);
+    ensure!(
+        min_fee_rate <= max_fee_rate,
+        "fee estimator min_fee_rate {} > max_fee_rate {}",
+        min_fee_rate,
+        max_fee_rate
+    );
+
+    let estimator: Arc<dyn FeeEstimator> = match *strategy {
+        FeeEstimatorStrategy::Static => Arc::new(CappedFeeEstimator {
+            inner: StaticFeeEstimator(config.fee_rate),
+            min_fee_rate,
+            max_fee_rate,
+        }),
+        FeeEstimatorStrategy::Median { target_blocks } => Arc::new(CappedFeeEstimator {
+            inner: MedianFeeEstimator {
+                ckb: ckb.clone(),
+                target_blocks,
+                fallback: config.fee_rate,
+            },
+            min_fee_rate,
+            max_fee_rate,
+        }),
+    };
+    Ok(estimator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_capped_fee_estimator() {
+        let capped = |fee_rate| CappedFeeEstimator {
+            inner: StaticFeeEstimator(fee_rate),
+            min_fee_rate: 1000,
+            max_fee_rate: 5000,
+        };
+        assert_eq!(capped(500).estimate_fee_rate().await.unwrap(), 1000);
+        assert_eq!(capped(2000).estimate_fee_rate().await.unwrap(), 2000);
+        assert_eq!(capped(9000).estimate_fee_rate().await.unwrap(), 5000);
+    }
+}
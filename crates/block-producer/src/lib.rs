@@ -3,14 +3,23 @@ pub mod block_sync_client;
 pub mod chain_updater;
 pub mod challenger;
 pub mod cleaner;
+pub mod components;
 pub mod custodian;
 pub mod debugger;
 pub mod deposit;
+pub mod execution_events;
+pub mod fee_estimator;
 pub mod produce_block;
 pub(crate) mod psc;
 pub mod replay_block;
 pub mod runner;
+pub mod shadow_production;
+pub(crate) mod slo_metrics;
 pub mod stake;
+pub mod state_sync_client;
+pub mod submission_window;
+pub mod submitter;
+pub(crate) mod sync_batch;
 pub mod sync_l1;
 pub mod test_mode_control;
 pub mod types;
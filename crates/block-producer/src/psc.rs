@@ -4,43 +4,60 @@ use std::{collections::HashSet, fmt::Display, sync::Arc, time::Duration};
 
 use anyhow::{bail, ensure, Context, Result};
 use gw_chain::chain::Chain;
-use gw_config::PscConfig;
-use gw_mem_pool::{block_sync_server::BlockSyncServerState, pool::MemPool};
+use gw_config::{FeeBumpConfig, PscConfig};
+use gw_mem_pool::{
+    block_sync_server::{load_confirmed, load_local_block, load_submitted, BlockSyncServerState},
+    pool::MemPool,
+};
 use gw_rpc_client::{
     error::{get_jsonrpc_error_code, CkbRpcError},
     rpc_client::RPCClient,
 };
-use gw_store::{snapshot::StoreSnapshot, traits::chain_store::ChainStore, Store};
+use gw_store::{snapshot::StoreSnapshot, traits::chain_store::ChainStore, Store, WriteStall};
 use gw_telemetry::traits::{OpenTelemetrySpanExt, TraceContextExt};
 use gw_types::{
     h256::*,
     offchain::{CellStatus, DepositInfo},
     packed::{
-        self, Confirmed, GlobalState, LocalBlock, NumberHash, OutPoint, Revert, Script, ScriptVec,
-        Submitted, Transaction, WithdrawalKey,
+        self, GlobalState, LocalBlock, NumberHash, OutPoint, Revert, Script, ScriptVec,
+        Transaction, WithdrawalKey,
     },
     prelude::*,
 };
 use gw_utils::{
-    abort_on_drop::spawn_abort_on_drop, liveness::Liveness, local_cells::LocalCellsManager,
-    since::Since, RollupContext,
+    abort_on_drop::spawn_abort_on_drop,
+    liveness::Liveness,
+    local_cells::LocalCellsManager,
+    maintenance::{MaintenanceMode, MaintenanceState},
+    production::{ProductionControl, ProductionState},
+    since::Since,
+    RollupContext,
 };
 use pid::Pid;
 use rand::{thread_rng, Rng};
 use tokio::{
     signal::unix::{signal, SignalKind},
-    sync::Mutex,
-    time::Instant,
+    sync::{Mutex, OwnedMutexGuard},
+    time::{Instant, Interval},
 };
 use tracing::instrument;
 
 use crate::{
-    block_producer::{check_block_size, BlockProducer, ComposeSubmitTxArgs, TransactionSizeError},
+    block_producer::{
+        check_block_size, BlockProducer, BlockSchedule, ComposeSubmitTxArgs, TransactionSizeError,
+    },
     chain_updater::ChainUpdater,
+    fee_estimator::FeeEstimator,
     produce_block::ProduceBlockResult,
+    slo_metrics::SloMetrics,
+    submission_window::{unix_millis, SubmissionWindow},
     sync_l1::{revert, sync_l1, SyncL1Context},
 };
 
+const SLO_METRICS_INTERVAL: Duration = Duration::from_secs(10);
+/// Retry producing after this long if rocksdb writes are stopped.
+const WRITE_STALL_BACKOFF_MILLIS: u64 = 3000;
+
 /// Block producing, submitting and confirming state machine.
 pub struct ProduceSubmitConfirm {
     context: Arc<PSCContext>,
@@ -48,6 +65,10 @@ pub struct ProduceSubmitConfirm {
     submitted_count: u64,
     current_fee_rate: u64,
     fee_rate_controller: Option<Pid<f64>>,
+    // Submission txs of blocks up to this number are replacements of txs
+    // with a lower fee rate, 0 if none.
+    replaced_until: u64,
+    slo_metrics: SloMetrics,
 }
 
 impl ProduceSubmitConfirm {
@@ -58,6 +79,8 @@ impl ProduceSubmitConfirm {
             context,
             local_count: 0,
             submitted_count: 0,
+            replaced_until: 0,
+            slo_metrics: SloMetrics::new(),
         }
     }
 
@@ -73,6 +96,16 @@ impl ProduceSubmitConfirm {
 
         gw_metrics::block_producer().submitted_blocks.set(count);
     }
+
+    fn update_slo_metrics(&mut self) {
+        let uncommitted_blocks = self.local_count + self.submitted_count;
+        let context = &self.context;
+        if let Err(err) =
+            (self.slo_metrics).update(&context.store, context.rollup_context(), uncommitted_blocks)
+        {
+            log::warn!("failed to update slo metrics: {:#}", err);
+        }
+    }
 }
 
 pub struct PSCContext {
@@ -87,6 +120,10 @@ pub struct PSCContext {
     pub psc_config: PscConfig,
     pub block_sync_server_state: Option<Arc<std::sync::Mutex<BlockSyncServerState>>>,
     pub liveness: Arc<Liveness>,
+    pub maintenance_mode: Arc<MaintenanceMode>,
+    pub production_control: Arc<ProductionControl>,
+    /// Submission txs pay at least the estimated fee rate if set.
+    pub fee_estimator: Option<Arc<dyn FeeEstimator>>,
 }
 
 impl PSCContext {
@@ -118,6 +155,9 @@ impl SyncL1Context for PSCContext {
 
 impl ProduceSubmitConfirm {
     pub async fn init(context: Arc<PSCContext>) -> Result<Self> {
+        if let Some(ref window) = context.psc_config.submission_window {
+            SubmissionWindow::new(window).context("invalid submission window config")?;
+        }
         sync_l1(&*context).await?;
         // Get again because they may have changed after syncing with L1.
         let snap = context.store.get_snapshot();
@@ -177,7 +217,19 @@ impl ProduceSubmitConfirm {
                 Err(e) => {
                     log::warn!("{:#}", e);
                     if e.is::<ShouldResetTxError>() {
-                        reset_submission_txs(&mut self).await?;
+                        match self.context.psc_config.fee_bump {
+                            Some(ref fee_bump) => {
+                                let fee_bump = fee_bump.clone();
+                                bump_submission_txs(&mut self, &fee_bump).await?;
+                            }
+                            None => {
+                                // Use random fee rate.
+                                let c = &self.context.psc_config;
+                                let fee_rate =
+                                    thread_rng().gen_range(c.min_fee_rate..=c.max_fee_rate);
+                                reset_submission_txs(&mut self, fee_rate).await?;
+                            }
+                        }
                         continue;
                     }
 
@@ -262,6 +314,7 @@ impl ProduceSubmitConfirm {
                         }
                         self.set_local_count(last_valid - last_submitted);
                         self.set_submitted_count(last_submitted - last_confirmed);
+                        self.replaced_until = 0;
                     } else {
                         bail!(e);
                     }
@@ -276,18 +329,38 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
     let mut submit_handle = spawn_abort_on_drop(async { anyhow::Ok(NumberHash::default()) });
     let mut confirming = false;
     let mut confirm_handle = spawn_abort_on_drop(async { anyhow::Ok(NumberHash::default()) });
+    // Packaging the next mem block after a block is produced. The task is not
+    // aborted on drop, so the mem pool is never left half reset.
+    let mut packaging = false;
+    let mut package_handle = tokio::spawn(async { anyhow::Ok(()) });
     let ctx = state.context.clone();
     let config = &ctx.psc_config;
     let mut interval = tokio::time::interval(Duration::from_secs(config.block_interval_secs));
     interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let submission_window = config
+        .submission_window
+        .as_ref()
+        .map(SubmissionWindow::new)
+        .transpose()?;
+    let mut last_produced_round = None;
+    let schedule = BlockSchedule::new(config);
+    let mut last_produced = Instant::now()
+        .checked_sub(Duration::from_secs(config.block_interval_secs))
+        .unwrap_or_else(Instant::now);
+    // Retry a failed production after this unix timestamp in milliseconds.
+    let mut produce_not_before = 0;
 
     let mut revert_local_signal = signal(SignalKind::user_defined1())?;
     let mut revert_submitted_signal = signal(SignalKind::user_defined2())?;
+    let mut maintenance_changed = ctx.maintenance_mode.subscribe();
+    let mut production_changed = ctx.production_control.subscribe();
 
     let has_fee_rate_controller = state.fee_rate_controller.is_some();
     let mut fee_rate_adjust_interval =
         tokio::time::interval(Duration::from_secs(config.fee_rate_pid_interval_secs));
     fee_rate_adjust_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut slo_metrics_interval = tokio::time::interval(SLO_METRICS_INTERVAL);
+    slo_metrics_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
     let half_fee_rate_range = (config.max_fee_rate - config.min_fee_rate) / 2;
     let mid_fee_rate = config.min_fee_rate + half_fee_rate_range;
@@ -296,17 +369,28 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
     }
 
     loop {
+        let maintenance_state = ctx.maintenance_mode.state();
+        if maintenance_state == MaintenanceState::Entering
+            && state.local_count == 0
+            && state.submitted_count == 0
+            && !submitting
+            && !confirming
+            && !packaging
+        {
+            enter_maintenance(&ctx).await?;
+        }
         if !submitting && state.local_count > 0 && state.submitted_count < config.submitted_limit {
             submitting = true;
             let context = state.context.clone();
             let fee_rate = state.current_fee_rate;
+            let replaced_until = state.replaced_until;
             submit_handle.replace_with(tokio::spawn(async move {
                 loop {
                     submit_pending_l1_upgrade(&context)
                         .await
                         .with_context(|| "failed to submit pending l1 upgrade")?;
 
-                    match submit_next_block(&context, fee_rate).await {
+                    match submit_next_block(&context, fee_rate, replaced_until).await {
                         Ok(nh) => return Ok(nh),
                         Err(err) => {
                             if err.is::<ShouldResyncError>() || err.is::<ShouldRevertError>() {
@@ -346,9 +430,23 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
                 }
             }));
         }
-        // One of the producing, submitting or confirming branch is always
-        // enabled. Otherwise we'd be stuck waiting for one of the signals.
-        assert!(state.local_count < config.local_limit || confirming || submitting);
+        // With a submission window, produce the block of the next round
+        // shortly before its window opens instead of at fixed intervals.
+        let (produce_round, produce_delay) = match submission_window {
+            Some(ref window) => {
+                let now = unix_millis();
+                let (round, at) = window.next_produce(now, last_produced_round);
+                let at = at.max(produce_not_before);
+                (
+                    Some(round),
+                    Some(Duration::from_millis(at.saturating_sub(now))),
+                )
+            }
+            None => (None, None),
+        };
+        // One of the producing, packaging, submitting or confirming branch is
+        // always enabled. Otherwise we'd be stuck waiting for one of the signals.
+        assert!(state.local_count < config.local_limit || confirming || submitting || packaging);
         tokio::select! {
             biased;
             _ = revert_local_signal.recv() => {
@@ -373,6 +471,17 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
                     .unpack();
                 bail!(ShouldRevertError(last_confirmed));
             }
+            _ = maintenance_changed.changed() => {
+                log::info!("maintenance mode changed: {:?}", ctx.maintenance_mode.state());
+            }
+            _ = production_changed.changed() => {
+                let production_state = ctx.production_control.state();
+                log::info!("block production changed: {:?}", production_state);
+                gw_metrics::block_producer().production_state.set(production_state.as_metric());
+            }
+            _ = slo_metrics_interval.tick() => {
+                state.update_slo_metrics();
+            }
             _ = fee_rate_adjust_interval.tick(), if has_fee_rate_controller => {
                 let fee_rate_pid = state.fee_rate_controller.as_mut().unwrap();
                 let output = fee_rate_pid.next_control_output(state.submitted_count as f64 / config.submitted_limit as f64);
@@ -396,6 +505,13 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
                         }
                         state.set_submitted_count(state.submitted_count - 1);
                         state.context.liveness.tick();
+                        if state.replaced_until > 0 && nh.number().unpack() >= state.replaced_until {
+                            state.replaced_until = 0;
+                            // Replacements are confirmed, go back to the normal fee rate.
+                            if config.fee_bump.is_some() {
+                                state.current_fee_rate = config.min_fee_rate;
+                            }
+                        }
                     }
                     _ => {}
                 }
@@ -416,28 +532,183 @@ async fn run(state: &mut ProduceSubmitConfirm) -> Result<()> {
                         }
                         state.set_local_count(state.local_count - 1);
                         state.set_submitted_count(state.submitted_count + 1);
+                        state.slo_metrics.block_submitted();
                         state.context.liveness.tick();
                     }
                     _ => {}
                 }
             }
+            // Next mem block packaged.
+            result = &mut package_handle, if packaging => {
+                packaging = false;
+                match result {
+                    Err(err) if err.is_panic() => bail!("package task panic: {:?}", err.into_panic()),
+                    Ok(result) => result.context("package next mem block")?,
+                    _ => {}
+                }
+            }
             // Produce a new local block if the produce timer has expired and
-            // there are not too many local blocks.
-            _ = interval.tick(), if state.local_count < config.local_limit => {
-                log::info!("producing next block");
-                if let Err(e) = produce_local_block(&state.context).await {
-                    log::warn!("failed to produce local block: {:#}", e);
-                } else {
-                    state.set_local_count(state.local_count + 1);
-                    state.context.liveness.tick();
+            // there are not too many local blocks. Stop producing in maintenance mode,
+            // or once block production is paused.
+            heartbeat = produce_timer(&mut interval, produce_delay, &schedule, &ctx.mem_pool, last_produced), if !packaging
+                && state.local_count < config.local_limit
+                && maintenance_state == MaintenanceState::Running
+                && ctx.production_control.state() != ProductionState::Paused => {
+                // Committing the block would hang until compaction catches
+                // up, try again later instead.
+                let write_stall = check_write_stall(&ctx.store);
+                if write_stall == WriteStall::Stopped {
+                    log::warn!("rocksdb writes are stopped, postpone producing next block");
+                    gw_metrics::block_producer().write_stall_postponed.inc();
+                    produce_not_before = unix_millis() + WRITE_STALL_BACKOFF_MILLIS;
+                    continue;
+                }
+                match produce_round {
+                    Some(round) => log::info!("producing next block for submission round {}", round),
+                    None => log::info!("producing next block"),
+                }
+                last_produced = Instant::now();
+                // Write less while writes are delayed.
+                let shrink = match write_stall {
+                    WriteStall::Delayed { .. } => 1,
+                    _ => 0,
+                };
+                match produce_local_block(&state.context, shrink, heartbeat).await {
+                    Err(e) => {
+                        log::warn!("failed to produce local block: {:#}", e);
+                        produce_not_before = unix_millis() + 1000;
+                    }
+                    Ok((pool, block_hash)) => {
+                        // The block is saved, so it can be submitted while the
+                        // mem pool packages the next mem block on top of it.
+                        packaging = true;
+                        let context = state.context.clone();
+                        package_handle = tokio::spawn(async move {
+                            package_next_mem_block(&context, pool, block_hash).await
+                        });
+                        last_produced_round = produce_round;
+                        state.set_local_count(state.local_count + 1);
+                        state.context.liveness.tick();
+                        // The in-flight mem block is produced.
+                        if ctx.production_control.set_paused() {
+                            log::info!("block production paused");
+                        }
+                    }
                 }
             }
         }
     }
 }
 
+/// Check and report rocksdb write stall.
+fn check_write_stall(store: &Store) -> WriteStall {
+    let write_stall = store.write_stall();
+    let metric = match write_stall {
+        WriteStall::None => 0,
+        WriteStall::Delayed { rate } => {
+            log::warn!("rocksdb writes are delayed to {} bytes/s", rate);
+            1
+        }
+        WriteStall::Stopped => 2,
+    };
+    gw_metrics::block_producer().write_stall.set(metric);
+    write_stall
+}
+
+/// Fires after `delay` if there is a submission window. Otherwise on the next
+/// tick of the block interval, when the adaptive schedule says so, or when
+/// the mem pool state overlay is full.
+///
+/// Returns whether it's fired by the heartbeat timer.
+async fn produce_timer(
+    interval: &mut Interval,
+    delay: Option<Duration>,
+    schedule: &BlockSchedule,
+    mem_pool: &Mutex<MemPool>,
+    last_produced: Instant,
+) -> bool {
+    if let Some(delay) = delay {
+        tokio::time::sleep(delay).await;
+        return false;
+    }
+    let overlay_capped = mem_pool.lock().await.config().max_overlay_bytes.is_some();
+    if !schedule.is_adaptive() && !overlay_capped {
+        interval.tick().await;
+        return false;
+    }
+    loop {
+        tokio::time::sleep(BlockSchedule::POLL_INTERVAL).await;
+        let mem_pool = mem_pool.lock().await;
+        if mem_pool.is_overlay_full() {
+            log::info!("mem pool state overlay is full, produce block early");
+            return false;
+        }
+        let fullness = mem_pool.mem_block_fullness();
+        let elapsed = last_produced.elapsed();
+        if schedule.should_produce(elapsed, fullness) {
+            return schedule.is_heartbeat(elapsed, fullness);
+        }
+    }
+}
+
+/// All local blocks are submitted and confirmed, save mem block so pending
+/// txs survive a restart during maintenance, then mark maintenance mode active.
+async fn enter_maintenance(ctx: &PSCContext) -> Result<()> {
+    let mut pool = ctx.mem_pool.lock().await;
+    pool.save_mem_block().context("save mem block")?;
+    if ctx.maintenance_mode.set_active() {
+        log::info!("maintenance mode active, block producing stopped");
+    }
+    Ok(())
+}
+
+/// Replace unconfirmed submission txs with txs paying a higher fee rate.
+///
+/// The replacements spend the same rollup cell, so CKB replaces the pending
+/// txs (RBF) if the fee is high enough, or rejects them if the pending txs
+/// are committed in the meantime.
+async fn bump_submission_txs(
+    state: &mut ProduceSubmitConfirm,
+    fee_bump: &FeeBumpConfig,
+) -> Result<()> {
+    let fee_rate = match bumped_fee_rate(state.current_fee_rate, fee_bump) {
+        Some(fee_rate) => fee_rate,
+        None => {
+            log::warn!(
+                "fee rate {} reached the fee bump limit, keep waiting",
+                state.current_fee_rate
+            );
+            return Ok(());
+        }
+    };
+    gw_metrics::block_producer().fee_bumps.inc();
+    reset_submission_txs(state, fee_rate).await
+}
+
+fn bumped_fee_rate(fee_rate: u64, fee_bump: &FeeBumpConfig) -> Option<u64> {
+    if fee_rate >= fee_bump.max_fee_rate {
+        return None;
+    }
+    let bumped = (fee_rate * (100 + fee_bump.percent) / 100).max(fee_rate + fee_bump.min_increase);
+    Some(bumped.min(fee_bump.max_fee_rate))
+}
+
+#[test]
+fn test_bumped_fee_rate() {
+    let fee_bump = FeeBumpConfig {
+        timeout_secs: 60,
+        percent: 50,
+        min_increase: 1500,
+        max_fee_rate: 10000,
+    };
+    assert_eq!(bumped_fee_rate(1000, &fee_bump), Some(2500));
+    assert_eq!(bumped_fee_rate(4000, &fee_bump), Some(6000));
+    assert_eq!(bumped_fee_rate(8000, &fee_bump), Some(10000));
+    assert_eq!(bumped_fee_rate(10000, &fee_bump), None);
+}
+
 /// Reset last submitted to last confirmed.
-async fn reset_submission_txs(state: &mut ProduceSubmitConfirm) -> Result<()> {
+async fn reset_submission_txs(state: &mut ProduceSubmitConfirm, fee_rate: u64) -> Result<()> {
     let mut store_tx = state.context.store.begin_transaction();
     let last_confirmed = store_tx
         .get_last_confirmed_block_number_hash()
@@ -448,19 +719,17 @@ async fn reset_submission_txs(state: &mut ProduceSubmitConfirm) -> Result<()> {
         .context("get last submitted")?
         .number()
         .unpack();
-    for i in last_confirmed_block_number + 1..last_submitted {
+    for i in last_confirmed_block_number + 1..=last_submitted {
         store_tx.delete_submit_tx(i)?;
     }
     store_tx.set_last_submitted_block_number_hash(&last_confirmed.as_reader())?;
     store_tx.commit()?;
+    state.replaced_until = last_submitted;
 
     state.set_local_count(state.local_count + state.submitted_count);
     state.set_submitted_count(0);
     state.context.local_cells_manager.lock().await.reset();
-
-    // Use random fee rate.
-    let c = &state.context.psc_config;
-    let fee_rate = thread_rng().gen_range(c.min_fee_rate..=c.max_fee_rate);
+    state.context.block_producer.reset_submit_txs();
     state.current_fee_rate = fee_rate;
 
     log::info!("reset last submitted to #{last_confirmed_block_number}, fee rate to {fee_rate}");
@@ -468,18 +737,27 @@ async fn reset_submission_txs(state: &mut ProduceSubmitConfirm) -> Result<()> {
     Ok(())
 }
 
-/// Produce and save local block.
+/// Produce and save local block. The mem block is halved `shrink` times.
+/// `heartbeat` is whether the heartbeat timer triggered it.
+///
+/// Returns the still locked mem pool and the hash of the new block. The mem
+/// pool must be reset to the new tip with [`package_next_mem_block`] before it
+/// is unlocked.
 #[instrument(skip_all)]
-async fn produce_local_block(ctx: &PSCContext) -> Result<()> {
+async fn produce_local_block(
+    ctx: &PSCContext,
+    shrink: usize,
+    heartbeat: bool,
+) -> Result<(OwnedMutexGuard<MemPool>, H256)> {
     // TODO: check block and retry.
 
     // Lock mem pool the whole time we produce and update the next block. Don't
     // push transactions. Transactions pushed in this period of time will need
     // to be re-injected after the mem pool is reset anyway, and that creates a
     // quite some pressure on p2p syncing and read-only nodes.
-    let mut pool = ctx.mem_pool.lock().await;
+    let mut pool = ctx.mem_pool.clone().lock_owned().await;
 
-    let mut retry_count = 0;
+    let mut retry_count = shrink;
     let ProduceBlockResult {
         block,
         global_state,
@@ -504,6 +782,9 @@ async fn produce_local_block(ctx: &PSCContext) -> Result<()> {
 
     let block_txs = block.transactions().len();
     let block_withdrawals = block.withdrawals().len();
+    // Deposits may be collected after the heartbeat timer fired.
+    let heartbeat =
+        heartbeat && block_txs == 0 && block_withdrawals == 0 && deposit_cells.is_empty();
 
     // Now update db about the new local L2 block
 
@@ -539,6 +820,7 @@ async fn produce_local_block(ctx: &PSCContext) -> Result<()> {
     let mut chain = ctx.chain.lock().await;
     tokio::task::block_in_place(|| {
         let mut store_tx = ctx.store.begin_transaction();
+        store_tx.account_block_fee_rebates(&block)?;
         chain.update_local(
             &mut store_tx,
             block,
@@ -563,6 +845,10 @@ async fn produce_local_block(ctx: &PSCContext) -> Result<()> {
         anyhow::Ok(())
     })?;
     drop(chain);
+    if heartbeat {
+        log::info!("block #{} is an empty heartbeat block", number);
+        gw_metrics::block_producer().heartbeat_blocks.inc();
+    }
 
     // Lock collected deposits.
     let mut local_cells_manager = ctx.local_cells_manager.lock().await;
@@ -570,14 +856,31 @@ async fn produce_local_block(ctx: &PSCContext) -> Result<()> {
         local_cells_manager.lock_cell(d.cell.out_point);
     }
 
-    pool.notify_new_tip(block_hash, &local_cells_manager)
-        .await
-        .expect("notify new tip");
+    Ok((pool, block_hash))
+}
 
-    Ok(())
+/// Reset the mem pool to the newly produced block, re-collecting deposits and
+/// re-injecting pending txs, then unlock it.
+///
+/// This overlaps with submitting the block. Deposits are collected with a
+/// copy of local cells, so the submission is not blocked on the local cells
+/// manager meanwhile. Cells consumed by the submission that are not already
+/// locked are not deposits.
+#[instrument(skip_all)]
+async fn package_next_mem_block(
+    ctx: &PSCContext,
+    mut pool: OwnedMutexGuard<MemPool>,
+    block_hash: H256,
+) -> Result<()> {
+    let local_cells_manager = ctx.local_cells_manager.lock().await.clone();
+    pool.notify_new_tip(block_hash, &local_cells_manager).await
 }
 
-async fn submit_next_block(ctx: &PSCContext, fee_rate: u64) -> Result<NumberHash> {
+async fn submit_next_block(
+    ctx: &PSCContext,
+    fee_rate: u64,
+    replaced_until: u64,
+) -> Result<NumberHash> {
     let snap = ctx.store.get_snapshot();
     // L2 block number to submit.
     let block_number = snap
@@ -595,14 +898,22 @@ async fn submit_next_block(ctx: &PSCContext, fee_rate: u64) -> Result<NumberHash
     // it does, it means that previous block is probably not confirmed
     // anymore, and we should sync with L1 again.
     let is_first = block_number == last_confirmed + 1;
-    submit_block(ctx, snap, is_first, block_number, fee_rate).await
+    let replacing = block_number <= replaced_until;
+    // The PID controller and fee bumps still raise the fee rate above the
+    // estimate.
+    let fee_rate = match ctx.fee_estimator {
+        Some(ref estimator) => fee_rate.max(estimator.estimate_fee_rate().await?),
+        None => fee_rate,
+    };
+    submit_block(ctx, snap, is_first, replacing, block_number, fee_rate).await
 }
 
-#[instrument(skip(ctx, snap, is_first))]
+#[instrument(skip(ctx, snap, is_first, replacing))]
 async fn submit_block(
     ctx: &PSCContext,
     snap: StoreSnapshot,
     is_first: bool,
+    replacing: bool,
     block_number: u64,
     fee_rate: u64,
 ) -> Result<NumberHash> {
@@ -697,6 +1008,16 @@ async fn submit_block(
         }
     }
 
+    // Only send in the submission window. Wait for the next window if the
+    // current one is about to close, the block stays valid.
+    if let Some(ref window) = ctx.psc_config.submission_window {
+        let window = SubmissionWindow::new(window)?;
+        while let Some(wait) = window.wait_to_send(unix_millis()) {
+            log::info!("wait {}ms for the submission window", wait.as_millis());
+            tokio::time::sleep(wait).await;
+        }
+    }
+
     {
         // Deposits should be live.
         let deposits = ctx
@@ -729,6 +1050,11 @@ async fn submit_block(
             }
             bail!(e);
         } else if e.is::<DeadCellError>() {
+            // The replaced tx may have been committed, sync with L1 instead of
+            // reverting the block.
+            if replacing {
+                bail!(e.context(ShouldResyncError));
+            }
             bail!(e.context(ShouldRevertError(block_number)));
         } else {
             bail!(e);
@@ -820,7 +1146,9 @@ async fn confirm_block(
         .expect("get submit tx");
     drop(snap);
     // Use a timeout if there is room for fee rate bumping.
-    let timeout = if context.psc_config.max_fee_rate > context.psc_config.min_fee_rate {
+    let timeout = if let Some(ref fee_bump) = context.psc_config.fee_bump {
+        Some(Duration::from_secs(fee_bump.timeout_secs))
+    } else if context.psc_config.max_fee_rate > context.psc_config.min_fee_rate {
         context
             .psc_config
             .confirm_timeout_secs
@@ -843,6 +1171,7 @@ async fn confirm_block(
         })?;
     log::info!("block confirmed");
     context.local_cells_manager.lock().await.confirm_tx(&tx);
+    context.block_producer.confirm_submit_tx(&tx.hash());
     Ok(NumberHash::new_builder()
         .block_hash(block_hash.pack())
         .number(block_number.pack())
@@ -1083,53 +1412,7 @@ fn publish_local_block(
     snap: &StoreSnapshot,
     b: u64,
 ) -> Result<()> {
-    let block_hash = snap
-        .get_block_hash_by_number(b)?
-        .context("get block hash")?;
-    let block = snap.get_block(&block_hash)?.context("get block")?;
-    let global_state = snap
-        .get_block_post_global_state(&block_hash)?
-        .context("get block post global state")?;
-    let deposit_info_vec = snap
-        .get_block_deposit_info_vec(b)
-        .context("get block deposit info vec")?;
-    let deposit_asset_scripts = {
-        let reader = deposit_info_vec.as_reader();
-        let asset_hashes: HashSet<H256> = reader
-            .iter()
-            .filter_map(|r| {
-                let h: H256 = r.request().sudt_script_hash().unpack();
-                if h.is_zero() {
-                    None
-                } else {
-                    Some(h)
-                }
-            })
-            .collect();
-        let asset_scripts = asset_hashes.into_iter().map(|h| {
-            snap.get_asset_script(&h)?
-                .with_context(|| format!("block {} asset script {} not found", b, h.pack()))
-        });
-        asset_scripts.collect::<Result<Vec<_>>>()?
-    };
-    let withdrawals = {
-        let reqs = block.as_reader().withdrawals();
-        let extra_reqs = reqs.iter().map(|w| {
-            let h = w.hash();
-            snap.get_withdrawal(&h)?
-                .with_context(|| format!("block {} withdrawal {} not found", b, h.pack()))
-        });
-        extra_reqs.collect::<Result<Vec<_>>>()?
-    };
-    sync_server.publish_local_block(
-        LocalBlock::new_builder()
-            .block(block)
-            .post_global_state(global_state)
-            .deposit_info_vec(deposit_info_vec)
-            .deposit_asset_scripts(ScriptVec::new_builder().set(deposit_asset_scripts).build())
-            .withdrawals(withdrawals.pack())
-            .build(),
-    );
+    sync_server.publish_local_block(load_local_block(snap, b)?);
     Ok(())
 }
 
@@ -1138,23 +1421,7 @@ fn publish_submitted(
     snap: &StoreSnapshot,
     b: u64,
 ) -> Result<()> {
-    let block_hash = snap
-        .get_block_hash_by_number(b)?
-        .context("get block hash")?;
-    let tx_hash = snap
-        .get_block_submit_tx_hash(b)
-        .context("get submit tx hash")?;
-    sync_server.publish_submitted(
-        Submitted::new_builder()
-            .tx_hash(tx_hash.pack())
-            .number_hash(
-                NumberHash::new_builder()
-                    .number(b.pack())
-                    .block_hash(block_hash.pack())
-                    .build(),
-            )
-            .build(),
-    );
+    sync_server.publish_submitted(load_submitted(snap, b)?);
     Ok(())
 }
 
@@ -1163,22 +1430,6 @@ fn publish_confirmed(
     snap: &StoreSnapshot,
     b: u64,
 ) -> Result<()> {
-    let block_hash = snap
-        .get_block_hash_by_number(b)?
-        .context("get block hash")?;
-    let tx_hash = snap
-        .get_block_submit_tx_hash(b)
-        .context("get submit tx hash")?;
-    sync_server.publish_confirmed(
-        Confirmed::new_builder()
-            .tx_hash(tx_hash.pack())
-            .number_hash(
-                NumberHash::new_builder()
-                    .number(b.pack())
-                    .block_hash(block_hash.pack())
-                    .build(),
-            )
-            .build(),
-    );
+    sync_server.publish_confirmed(load_confirmed(snap, b)?);
     Ok(())
 }
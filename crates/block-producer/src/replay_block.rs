@@ -9,8 +9,11 @@ use gw_store::state::traits::JournalDB;
 use gw_store::state::MemStateDB;
 use gw_store::traits::chain_store::ChainStore;
 use gw_store::Store;
+use gw_traits::{ChainView as ChainViewTrait, CodeStore};
 use gw_types::h256::*;
-use gw_types::packed::{BlockInfo, DepositRequest, L2Block, RawL2Block, WithdrawalRequestExtra};
+use gw_types::packed::{
+    AccountMerkleState, BlockInfo, DepositRequest, L2Block, RawL2Block, WithdrawalRequestExtra,
+};
 use gw_types::prelude::*;
 
 pub struct ReplayBlock;
@@ -24,8 +27,7 @@ impl ReplayBlock {
         withdrawals: &[WithdrawalRequestExtra],
     ) -> Result<()> {
         let raw_block = block.raw();
-        let block_info = get_block_info(&raw_block);
-        let block_number = raw_block.number().unpack();
+        let block_number: u64 = raw_block.number().unpack();
         log::info!("replay block {}", block_number);
 
         let parent_block_hash: H256 = raw_block.parent_block_hash().unpack();
@@ -44,6 +46,39 @@ impl ReplayBlock {
             );
         };
 
+        let db = &store.begin_transaction();
+        let chain_view = ChainView::new(&db, parent_block_hash);
+        Self::replay_on_state(
+            &mut state,
+            &chain_view,
+            generator,
+            block,
+            deposits,
+            withdrawals,
+        )?;
+
+        Ok(())
+    }
+
+    /// Replay the block on `state`, which must be the post state of its
+    /// parent block. Checkpoints are checked since the fork enforcing them,
+    /// the post state is returned for the caller to check.
+    pub fn replay_on_state<S, C>(
+        state: &mut S,
+        chain_view: &C,
+        generator: &Generator,
+        block: &L2Block,
+        deposits: &[DepositRequest],
+        withdrawals: &[WithdrawalRequestExtra],
+    ) -> Result<AccountMerkleState>
+    where
+        S: State + CodeStore + JournalDB,
+        C: ChainViewTrait,
+    {
+        let raw_block = block.raw();
+        let block_info = get_block_info(&raw_block);
+        let block_number = raw_block.number().unpack();
+
         // apply withdrawal to state
         let block_producer = {
             let block_producer: Bytes = block_info.block_producer().unpack();
@@ -53,13 +88,14 @@ impl ReplayBlock {
         let state_checkpoint_list: Vec<H256> = raw_block.state_checkpoint_list().unpack();
 
         for (wth_idx, withdrawal) in withdrawals.iter().enumerate() {
-            generator.check_withdrawal_signature(&state, withdrawal)?;
+            generator.check_withdrawal_signature(state, withdrawal)?;
 
             state.apply_withdrawal_request(
                 generator.rollup_context(),
                 &block_producer,
                 &withdrawal.request(),
             )?;
+            state.finalise()?;
 
             if generator
                 .fork_config()
@@ -80,6 +116,7 @@ impl ReplayBlock {
         for req in deposits {
             state.apply_deposit_request(generator.rollup_context(), req)?;
         }
+        state.finalise()?;
         if generator
             .fork_config()
             .enforce_correctness_of_state_checkpoint_list(block_number)
@@ -95,10 +132,8 @@ impl ReplayBlock {
         }
 
         // handle transactions
-        let db = &store.begin_transaction();
-        let chain_view = ChainView::new(&db, parent_block_hash);
         for (tx_index, tx) in block.transactions().into_iter().enumerate() {
-            generator.check_transaction_signature(&state, &tx)?;
+            generator.check_transaction_signature(state, &tx)?;
 
             // check nonce
             let raw_tx = tx.raw();
@@ -115,14 +150,7 @@ impl ReplayBlock {
 
             // build call context
             // NOTICE users only allowed to send HandleMessage CallType txs
-            generator.execute_transaction(
-                &chain_view,
-                &mut state,
-                &block_info,
-                &raw_tx,
-                None,
-                None,
-            )?;
+            generator.execute_transaction(chain_view, state, &block_info, &raw_tx, None, None)?;
 
             state.finalise()?;
 
@@ -142,7 +170,7 @@ impl ReplayBlock {
             }
         }
 
-        Ok(())
+        Ok(state.calculate_merkle_state()?)
     }
 }
 
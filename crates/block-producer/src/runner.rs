@@ -1,6 +1,7 @@
 use std::{
     collections::HashMap,
     net::{SocketAddr, ToSocketAddrs},
+    path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -16,20 +17,25 @@ use gw_config::{BlockProducerConfig, Config, ForkConfig, NodeMode, RegistryType}
 use gw_generator::{
     account_lock_manage::{secp256k1::Secp256k1Eth, AccountLockManage},
     backend_manage::BackendManage,
+    cycles_profile::CyclesProfiler,
     genesis::init_genesis,
+    verification::deployment::DeploymentPolicy,
     Generator,
 };
 use gw_mem_pool::{
     account_creator::AccountCreator,
-    block_sync_server::{block_sync_server_protocol, BlockSyncServerState},
+    block_sync_server::{
+        block_sync_server_protocol, BlockRangeServer, BlockSyncServerState, StateSyncServer,
+    },
     default_provider::DefaultMemPoolProvider,
     pool::{MemPool, MemPoolCreateArgs},
+    tx_relay::{tx_relay_server_protocol, TxRelay},
 };
-use gw_p2p_network::P2PNetwork;
+use gw_p2p_network::{P2PNetwork, PeerLedger};
 use gw_polyjuice_sender_recover::recover::PolyjuiceSenderRecover;
 use gw_rpc_client::{
-    ckb_client::CkbClient, contract::ContractsCellDepManager, error::get_jsonrpc_error_code,
-    indexer_client::CkbIndexerClient, rpc_client::RPCClient,
+    cell_cache::CellCache, ckb_client::CkbClient, contract::ContractsCellDepManager,
+    error::get_jsonrpc_error_code, indexer_client::CkbIndexerClient, rpc_client::RPCClient,
 };
 use gw_rpc_server::{
     registry::{BoxedTestModeRpc, Registry, RegistryArgs},
@@ -37,6 +43,7 @@ use gw_rpc_server::{
 };
 use gw_store::{
     migrate::{init_migration_factory, open_or_create_db},
+    traits::chain_store::ChainStore,
     Store,
 };
 use gw_types::{
@@ -47,8 +54,14 @@ use gw_types::{
     prelude::*,
 };
 use gw_utils::{
-    genesis_info::CKBGenesisInfo, liveness::Liveness, local_cells::LocalCellsManager,
-    wallet::Wallet, ExponentialBackoff, RollupContext,
+    genesis_info::CKBGenesisInfo,
+    liveness::Liveness,
+    local_cells::LocalCellsManager,
+    maintenance::MaintenanceMode,
+    mem_block_signature::{sighash_lock_signer, BlockSyncSigner},
+    production::ProductionControl,
+    wallet::Wallet,
+    ExponentialBackoff, RollupContext,
 };
 use semver::Version;
 use tentacle::service::ProtocolMeta;
@@ -64,7 +77,13 @@ use crate::{
     chain_updater::ChainUpdater,
     challenger::{Challenger, ChallengerNewArgs},
     cleaner::Cleaner,
+    components::Components,
+    custodian::CustodianConsolidation,
+    execution_events::spawn_execution_events_sink,
+    fee_estimator::build_fee_estimator,
     psc::{PSCContext, ProduceSubmitConfirm},
+    shadow_production::ShadowProducer,
+    state_sync_client::{is_empty_store, sync_state_from_peer},
     test_mode_control::TestModeControl,
     types::ChainEvent,
     withdrawal_unlocker::FinalizedWithdrawalUnlocker,
@@ -72,6 +91,7 @@ use crate::{
 
 const MIN_CKB_VERSION: &str = "0.40.0";
 const EVENT_TIMEOUT_SECONDS: u64 = 30;
+const STORE_METRICS_UPDATE_INTERVAL: Duration = Duration::from_secs(15);
 
 struct ChainTaskContext {
     challenger: Option<Challenger>,
@@ -277,6 +297,9 @@ pub struct BaseInitComponents {
 impl BaseInitComponents {
     pub async fn init(config: &Config, skip_config_check: bool) -> Result<Self> {
         let consensus = config.consensus.get_config();
+        consensus
+            .check_cycle_limits()
+            .map_err(|err| anyhow!("invalid cycle limit forks: {}", err))?;
         let rollup_config: RollupConfig = consensus.genesis.rollup_config.clone().into();
         let rollup_context = RollupContext {
             rollup_config: rollup_config.clone(),
@@ -291,12 +314,16 @@ impl BaseInitComponents {
             } else {
                 CkbIndexerClient::from(ckb_client.clone())
             };
-            RPCClient::new(
+            let rpc_client = RPCClient::new(
                 rollup_type_script.clone(),
                 rollup_context.rollup_config.clone(),
                 ckb_client,
                 indexer_client,
-            )
+            );
+            match config.rpc_client.cell_cache_dir {
+                Some(ref dir) => rpc_client.with_cell_cache(CellCache::open(dir)?),
+                None => rpc_client,
+            }
         };
 
         let opt_block_producer_config = config.block_producer.as_ref();
@@ -330,7 +357,8 @@ impl BaseInitComponents {
 
         // Open store
         let timer = Instant::now();
-        let store = Store::new(open_or_create_db(&config.store, init_migration_factory())?);
+        let store = Store::new(open_or_create_db(&config.store, init_migration_factory())?)
+            .with_state_cache(&config.store.state_cache);
         let elapsed_ms = timer.elapsed().as_millis();
         log::debug!("Open rocksdb costs: {}ms.", elapsed_ms);
 
@@ -376,6 +404,19 @@ impl BaseInitComponents {
                 eth_lock_script_type_hash.hash().unpack(),
                 Arc::new(Secp256k1Eth),
             );
+            for lock in &config.account_locks {
+                let lock_type_hash: H256 = lock.lock_type_hash.clone().into();
+                if !(allowed_eoa_type_hashes.iter()).any(|th| th.hash().unpack() == lock_type_hash)
+                {
+                    bail!(
+                        "account lock {} is not in allowed_eoa_type_hashes",
+                        lock.lock_type_hash
+                    );
+                }
+            }
+            account_lock_manage
+                .register_from_config(&config.account_locks)
+                .context("config account locks")?;
             let mut gen = Generator::new(
                 backend_manage,
                 account_lock_manage,
@@ -385,6 +426,13 @@ impl BaseInitComponents {
             if config.trace_generator_state {
                 gen.enable_trace_state()?;
             }
+            if let Some(ref dir) = config.cycles_profile_dir {
+                let syscall_cycles = config.mem_pool.mem_block.syscall_cycles.clone();
+                gen.enable_cycles_profile(CyclesProfiler::new(dir.clone(), syscall_cycles)?);
+            }
+            if let Some(policy) = DeploymentPolicy::from_config(&config.deployment_policy) {
+                gen.set_deployment_policy(policy);
+            }
             Arc::new(gen)
         };
 
@@ -453,7 +501,14 @@ impl BaseInitComponents {
     }
 }
 
-pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
+/// `config_path` is the file `config` is read from. Backend forks are
+/// reloaded from it on SIGHUP.
+pub async fn run(
+    config: Config,
+    config_path: Option<PathBuf>,
+    skip_config_check: bool,
+    override_state_mismatch_halt: bool,
+) -> Result<()> {
     spawn_starvation_detector();
 
     // Set up runtim monitor.
@@ -471,17 +526,101 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
             });
         }
     }
+    let components = Components::resolve(&config).context("resolve components")?;
+    log::info!("components: {:?}", components);
+
+    if config.p2p_state_sync
+        && config.node_mode == NodeMode::ReadOnly
+        && is_empty_store(&config.store.path)?
+    {
+        let p2p_network_config = config
+            .p2p_network_config
+            .as_ref()
+            .filter(|_| components.p2p)
+            .context("p2p_state_sync requires the p2p network")?;
+        let consensus = config.consensus.get_config();
+        let rollup_type_hash: H256 = consensus.genesis.rollup_type_hash.clone().into();
+        let ckb_client = CkbClient::with_url(&config.rpc_client.ckb_url)?;
+        log::info!("store is empty, syncing state from p2p peer");
+        sync_state_from_peer(
+            &config.store,
+            p2p_network_config,
+            &rollup_type_hash,
+            &ckb_client,
+        )
+        .await
+        .context("sync state from p2p peer")?;
+    }
+
     let base = BaseInitComponents::init(&config, skip_config_check).await?;
+    spawn_store_metrics_updater(base.store.clone());
+
+    if let Some(block_hash) = base.store.get_state_mismatch_halt() {
+        let dump_path = config.debug.state_mismatch_dump_path.display();
+        if !override_state_mismatch_halt {
+            bail!(
+                "halted on post state mismatch of block 0x{}, see diagnostics in {}. \
+                Restart with --override-state-mismatch-halt to continue",
+                hex::encode(block_hash),
+                dump_path
+            );
+        }
+        log::warn!(
+            "override halt on post state mismatch of block 0x{}, diagnostics in {}",
+            hex::encode(block_hash),
+            dump_path
+        );
+        let mut store_tx = base.store.begin_transaction();
+        store_tx.clear_state_mismatch_halt()?;
+        store_tx.commit()?;
+    }
 
-    let has_block_producer_and_p2p =
-        config.block_producer.is_some() && config.p2p_network_config.is_some();
-    let block_sync_server_state = if has_block_producer_and_p2p {
-        Some(Arc::new(std::sync::Mutex::new(BlockSyncServerState::new(
-            &config.sync_server,
-        ))))
+    // Sign published block sync messages with the block producer wallet, for
+    // readonly nodes with `sync_client.mem_block_signer_lock`.
+    let block_sync_signer = match config.block_producer.as_ref() {
+        Some(c) if components.indexer_publisher => c
+            .wallet_config
+            .as_ref()
+            .map(|c| {
+                let wallet = Wallet::from_config(c).with_context(|| "block sync signer wallet")?;
+                let rollup_type_hash = base.rollup_type_script.hash();
+                anyhow::Ok(Arc::new(BlockSyncSigner::new(wallet, rollup_type_hash)))
+            })
+            .transpose()?,
+        _ => None,
+    };
+    let block_sync_server_state = if components.indexer_publisher {
+        let mut state = BlockSyncServerState::new(&config.sync_server);
+        if let Some(ref signer) = block_sync_signer {
+            state.set_signer(signer.clone());
+        }
+        let state = Arc::new(std::sync::Mutex::new(state));
+        if let Some(secs) = config.sync_server.mem_block_snapshot_interval_secs {
+            spawn_mem_block_snapshot_publisher(state.clone(), Duration::from_secs(secs));
+        }
+        Some(state)
     } else {
         None
     };
+    let state_sync_server = if components.indexer_publisher && config.sync_server.serve_state_sync {
+        Some(Arc::new(StateSyncServer::new(
+            base.store.clone(),
+            base.rollup_context.clone(),
+        )))
+    } else {
+        None
+    };
+
+    let block_range_server =
+        if components.indexer_publisher && config.sync_server.block_range_max_blocks > 0 {
+            let mut server = BlockRangeServer::new(base.store.clone(), &config.sync_server);
+            if let Some(ref signer) = block_sync_signer {
+                server.set_signer(signer.clone());
+            }
+            Some(Arc::new(server))
+        } else {
+            None
+        };
 
     let (mem_pool, wallet, offchain_mock_context) = match config.block_producer.as_ref() {
         Some(block_producer_config) => {
@@ -562,6 +701,24 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
     let liveness = Arc::new(Liveness::new(Duration::from_secs(
         config.liveness_duration_secs.unwrap_or(60),
     )));
+    let maintenance_mode = Arc::new(MaintenanceMode::default());
+    let production_control = Arc::new(ProductionControl::default());
+    let debug_generator = match config.debug_backend_forks.clone() {
+        Some(configs) => {
+            let backend_manage = BackendManage::from_debug_config(configs)?;
+            Some(Arc::new(generator.clone_with_new_backends(backend_manage)))
+        }
+        None => None,
+    };
+    #[cfg(unix)]
+    if let Some(ref config_path) = config_path {
+        spawn_backend_forks_reloader(
+            config_path.clone(),
+            generator.clone(),
+            debug_generator.clone(),
+            store.clone(),
+        );
+    }
 
     // check state db
     {
@@ -570,8 +727,8 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
         log::info!("Check state db done: {}ms", t.elapsed().as_millis());
     }
     let consensus = config.consensus.get_config();
-    let chain = Arc::new(Mutex::new(
-        Chain::create(
+    let chain = {
+        let mut chain = Chain::create(
             rollup_config.clone(),
             &consensus.chain.rollup_type_script.clone().into(),
             &consensus.chain,
@@ -579,8 +736,16 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
             generator.clone(),
             mem_pool.clone(),
         )
-        .with_context(|| "create chain")?,
-    ));
+        .with_context(|| "create chain")?;
+        if let Some(ref c) = config.execution_events {
+            chain.set_execution_events_sender(spawn_execution_events_sink(c)?);
+        }
+        chain.set_state_mismatch_dump_path(config.debug.state_mismatch_dump_path.clone());
+        if let Some(blocks) = config.store.state_history_retention_blocks {
+            chain.set_state_history_retention_blocks(blocks);
+        }
+        Arc::new(Mutex::new(chain))
+    };
 
     // create chain updater
     let chain_updater = ChainUpdater::new(
@@ -634,14 +799,20 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
                 contracts_dep_manager.clone(),
                 unlocker_wallet,
                 config.debug.clone(),
-                block_producer_config.fee_rate,
+                build_fee_estimator(&block_producer_config, &rpc_client.ckb)?,
+                &block_producer_config.withdrawal_unlocker,
             );
 
+            let custodian_consolidation = (block_producer_config.custodian_consolidation)
+                .clone()
+                .map(|c| Arc::new(CustodianConsolidation::new(c)));
             let cleaner = Arc::new(Cleaner::new(
                 rpc_client.clone(),
                 ckb_genesis_info.clone(),
                 wallet,
                 block_producer_config.fee_rate,
+                local_cells_manager.clone(),
+                custodian_consolidation.clone(),
             ));
 
             let challenger_wallet = match block_producer_config.wallet_config {
@@ -677,16 +848,17 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
                 config: block_producer_config,
                 tests_control: tests_control.clone(),
                 contracts_dep_manager,
+                custodian_consolidation,
             };
             let block_producer =
                 BlockProducer::create(create_args).with_context(|| "init block producer")?;
 
             (
-                Some(block_producer),
-                Some(challenger),
+                Some(block_producer).filter(|_| components.block_producer),
+                Some(challenger).filter(|_| components.challenger),
                 tests_control,
-                Some(withdrawal_unlocker),
-                Some(cleaner),
+                Some(withdrawal_unlocker).filter(|_| components.block_producer),
+                Some(cleaner).filter(|_| components.challenger),
             )
         }
     };
@@ -698,34 +870,74 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
 
     let block_sync_client_p2p_stream_inbox: Arc<std::sync::Mutex<Option<P2PStream>>> =
         Arc::new(std::sync::Mutex::new(None));
+    let mem_block_signer = match config.sync_client.mem_block_signer_lock {
+        Some(ref lock) => {
+            let signer = sighash_lock_signer(&lock.clone().into())
+                .context("invalid sync_client.mem_block_signer_lock")?;
+            Some(signer)
+        }
+        None => {
+            if config.node_mode == NodeMode::ReadOnly {
+                log::warn!(
+                    "p2p block sync messages are unauthenticated, see sync_client.mem_block_signer_lock"
+                );
+            }
+            None
+        }
+    };
 
     // P2P network.
-    let p2p_control_and_handle = if let Some(ref p2p_network_config) = config.p2p_network_config {
-        let mut protocols: Vec<ProtocolMeta> = Vec::new();
-        match config.node_mode {
-            NodeMode::ReadOnly => {
-                log::info!("will enable p2p block sync client");
-                protocols.push(block_sync_client_protocol(
-                    block_sync_client_p2p_stream_inbox.clone(),
-                ));
-            }
-            NodeMode::FullNode | NodeMode::Test => {
-                if let Some(ref state) = block_sync_server_state {
-                    log::info!("will enable p2p block sync server");
-                    protocols.push(block_sync_server_protocol(state.clone()));
+    let p2p_network_config = config.p2p_network_config.as_ref();
+    let mut tx_relay = None;
+    let mut relayed_txs = None;
+    let p2p_control_and_handle =
+        if let Some(p2p_network_config) = p2p_network_config.filter(|_| components.p2p) {
+            let ledger = Arc::new(PeerLedger::new(&p2p_network_config.peer_score)?);
+            let mut protocols: Vec<ProtocolMeta> = Vec::new();
+            match config.node_mode {
+                NodeMode::ReadOnly => {
+                    log::info!("will enable p2p block sync client");
+                    protocols.push(block_sync_client_protocol(
+                        block_sync_client_p2p_stream_inbox.clone(),
+                        ledger.clone(),
+                    ));
+                    if config.sync_client.relay_transactions {
+                        log::info!("will enable p2p tx relay client");
+                        let (relay, protocol) = TxRelay::new();
+                        tx_relay = Some(Arc::new(relay));
+                        protocols.push(protocol);
+                    }
+                }
+                NodeMode::FullNode | NodeMode::Test => {
+                    if let Some(ref state) = block_sync_server_state {
+                        log::info!("will enable p2p block sync server");
+                        protocols.push(block_sync_server_protocol(
+                            state.clone(),
+                            state_sync_server.clone(),
+                            block_range_server.clone(),
+                            ledger.clone(),
+                        ));
+                    }
+                    if config.sync_server.accept_relayed_transactions {
+                        log::info!("will enable p2p tx relay server");
+                        let (protocol, receiver) = tx_relay_server_protocol(ledger.clone());
+                        relayed_txs = Some(receiver);
+                        protocols.push(protocol);
+                    }
                 }
             }
-        }
-        let mut network = P2PNetwork::init(p2p_network_config, protocols).await?;
-        let control = network.control().clone();
-        let handle = tokio::spawn(async move {
-            log::info!("running the p2p network");
-            network.run().await;
-        });
-        Some((control, handle))
-    } else {
-        None
-    };
+            let mut network =
+                P2PNetwork::init_with_peer_ledger(p2p_network_config, protocols, ledger).await?;
+            let control = network.control().clone();
+            let status = network.status();
+            let handle = tokio::spawn(async move {
+                log::info!("running the p2p network");
+                network.run().await;
+            });
+            Some((control, handle, status))
+        } else {
+            None
+        };
 
     // RPC registry
     let polyjuice_sender_recover = {
@@ -747,7 +959,7 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
     let args = RegistryArgs {
         store: store.clone(),
         mem_pool: mem_pool.clone(),
-        generator,
+        generator: generator.clone(),
         tests_rpc_impl: test_mode_control.map(|t| Arc::new(t) as BoxedTestModeRpc),
         rollup_config,
         chain_config: consensus.chain.to_owned(),
@@ -759,22 +971,36 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
         send_tx_rate_limit: config.rpc_server.send_tx_rate_limit.clone(),
         server_config: config.rpc_server.clone(),
         polyjuice_sender_recover,
-        debug_backend_forks: config.debug_backend_forks.clone(),
+        debug_generator,
         gasless_tx_support_config: config.gasless_tx_support.clone(),
+        p2p_network_status: p2p_control_and_handle
+            .as_ref()
+            .map(|(_, _, status)| status.clone()),
+        maintenance_mode: block_producer.as_ref().map(|_| maintenance_mode.clone()),
+        production_control: block_producer.as_ref().map(|_| production_control.clone()),
+        backup_dir: config.store.backup_dir.clone(),
+        config_path: config_path.clone(),
+        tx_relay,
+        relayed_txs,
     };
 
-    let rpc_registry = Registry::create(args).await?;
-    let rpc_handler = Arc::new(rpc_registry.to_handler());
+    let rpc = if components.rpc {
+        let rpc_registry = Registry::create(args).await?;
+        let rpc_handler = Arc::new(rpc_registry.to_handler());
 
-    let rpc_address: SocketAddr = {
-        let mut addrs: Vec<_> = config.rpc_server.listen.to_socket_addrs()?.collect();
-        if addrs.len() != 1 {
-            return Err(anyhow!(
-                "Invalid RPC listen address `{}`",
-                &config.rpc_server.listen
-            ));
-        }
-        addrs.remove(0)
+        let rpc_address: SocketAddr = {
+            let mut addrs: Vec<_> = config.rpc_server.listen.to_socket_addrs()?.collect();
+            if addrs.len() != 1 {
+                return Err(anyhow!(
+                    "Invalid RPC listen address `{}`",
+                    &config.rpc_server.listen
+                ));
+            }
+            addrs.remove(0)
+        };
+        Some((rpc_address, rpc_handler))
+    } else {
+        None
     };
 
     {
@@ -792,6 +1018,10 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
 
     let bm = (block_producer, mem_pool.clone()); // To keep the next line short.
     let psc_task = if let (Some(block_producer), Some(mem_pool)) = bm {
+        let block_producer_config = config.block_producer.as_ref().unwrap();
+        let fee_estimator = (block_producer_config.fee_estimator.as_ref())
+            .map(|_| build_fee_estimator(block_producer_config, &rpc_client.ckb))
+            .transpose()?;
         let psc_state = ProduceSubmitConfirm::init(Arc::new(PSCContext {
             store: store.clone(),
             block_producer,
@@ -801,9 +1031,12 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
             local_cells_manager,
             chain_updater: chain_updater.clone(),
             rollup_type_script: rollup_type_script.clone(),
-            psc_config: config.block_producer.as_ref().unwrap().psc_config.clone(),
+            psc_config: block_producer_config.psc_config.clone(),
             block_sync_server_state: block_sync_server_state.clone(),
             liveness: liveness.clone(),
+            maintenance_mode,
+            production_control,
+            fee_estimator,
         }))
         .await
         .context("create ProduceSubmitConfirm")?;
@@ -837,6 +1070,11 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
             p2p_stream_inbox: block_sync_client_p2p_stream_inbox,
             completed_initial_syncing: false,
             liveness: liveness.clone(),
+            shadow_producer: config
+                .shadow_production
+                .then(|| ShadowProducer::new(store.clone(), generator.clone())),
+            mem_block_signer,
+            mem_block_parent: None,
         };
         let shutdown_completed_send = shutdown_completed_send.clone();
         let mut shutdown_event_recv = shutdown_event.subscribe();
@@ -927,26 +1165,31 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
         }
     });
 
-    let sub_shutdown = shutdown_event.subscribe();
-    let rpc_shutdown_send = shutdown_completed_send.clone();
-    let rpc_task = spawn(async move {
-        if let Err(err) = start_jsonrpc_server(
-            rpc_address,
-            rpc_handler,
-            liveness,
-            rpc_shutdown_send,
-            sub_shutdown,
-        )
-        .await
-        {
-            log::error!("Error running JSONRPC server: {:?}", err);
-        }
+    let rpc_task = rpc.map(|(rpc_address, rpc_handler)| {
+        let sub_shutdown = shutdown_event.subscribe();
+        let rpc_shutdown_send = shutdown_completed_send.clone();
+        spawn(async move {
+            if let Err(err) = start_jsonrpc_server(
+                rpc_address,
+                rpc_handler,
+                liveness,
+                components.metrics,
+                rpc_shutdown_send,
+                sub_shutdown,
+            )
+            .await
+            {
+                log::error!("Error running JSONRPC server: {:?}", err);
+            }
+        })
     });
+    let has_rpc_task = rpc_task.is_some();
+    let rpc_task = OptionFuture::from(rpc_task);
 
     tokio::select! {
         _ = sigint_or_sigterm() => {},
         _ = chain_task => {},
-        _ = rpc_task => {},
+        _ = rpc_task, if has_rpc_task => {},
         _ = psc_task, if has_psc_task => {},
         _ = block_sync_task, if has_block_sync_task => {},
     };
@@ -957,7 +1200,7 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
         log::error!("Failed to brodcast error message: {:?}", err);
     }
     // Shutdown p2p network.
-    if let Some((control, handle)) = p2p_control_and_handle {
+    if let Some((control, handle, _)) = p2p_control_and_handle {
         log::info!("closing p2p network");
         let _ = control.close().await;
         let _ = handle.await;
@@ -995,7 +1238,7 @@ async fn check_rollup_config_cell(
     rpc_client: &RPCClient,
 ) -> Result<()> {
     let rollup_config_cell = rpc_client
-        .get_cell(
+        .get_cell_content(
             fork_config
                 .chain
                 .rollup_config_cell_dep
@@ -1004,7 +1247,6 @@ async fn check_rollup_config_cell(
                 .into(),
         )
         .await?
-        .and_then(|cell_with_status| cell_with_status.cell)
         .ok_or_else(|| anyhow!("can't find rollup config cell"))?;
     let cell_data = RollupConfig::from_slice(&rollup_config_cell.data)?;
     let eoa_set = rollup_config
@@ -1100,6 +1342,73 @@ async fn sigint_or_sigterm() {
     log::info!("received sigint or sigterm, shutting down");
 }
 
+/// Reload backend forks, and debug backend forks of the debug generator if
+/// any, from the config file on SIGHUP.
+#[cfg(unix)]
+fn spawn_backend_forks_reloader(
+    config_path: PathBuf,
+    generator: Arc<Generator>,
+    debug_generator: Option<Arc<Generator>>,
+    store: Store,
+) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(s) => s,
+        Err(err) => {
+            log::error!("creating SIGHUP stream: {}", err);
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            log::info!("reload backend forks due to signal");
+            let config_path = config_path.clone();
+            let generator = generator.clone();
+            let debug_generator = debug_generator.clone();
+            let store = store.clone();
+            let result = tokio::task::spawn_blocking(move || -> Result<bool> {
+                let tip_number = store.get_last_valid_tip_block()?.raw().number().unpack();
+                // The mem block may have executed txs with the current forks.
+                generator.backend_manage().reload_from_config_file(
+                    &config_path,
+                    tip_number + 1,
+                    debug_generator.as_deref().map(Generator::backend_manage),
+                )
+            })
+            .await;
+            match result {
+                Ok(Ok(true)) => log::info!("backend forks reloaded"),
+                Ok(Ok(false)) => log::info!("backend forks unchanged"),
+                Ok(Err(err)) => log::error!("reload backend forks: {:#}", err),
+                Err(err) => log::error!("reload backend forks: {}", err),
+            }
+        }
+    });
+}
+
+fn spawn_store_metrics_updater(store: Store) {
+    tokio::spawn(async move {
+        loop {
+            let store = store.clone();
+            let _ = tokio::task::spawn_blocking(move || gw_metrics::store().update(&store)).await;
+            tokio::time::sleep(STORE_METRICS_UPDATE_INTERVAL).await;
+        }
+    });
+}
+
+fn spawn_mem_block_snapshot_publisher(
+    state: Arc<std::sync::Mutex<BlockSyncServerState>>,
+    interval: Duration,
+) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            state.lock().unwrap().publish_mem_block_snapshot();
+        }
+    });
+}
+
 fn spawn_starvation_detector() {
     tokio::spawn(async move {
         let mut instant = Instant::now();
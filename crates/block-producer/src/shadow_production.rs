@@ -0,0 +1,128 @@
+//! Shadow block production on read-only nodes.
+//!
+//! Read-only nodes mirror the mem block of the full node by p2p block sync.
+//! In shadow production mode, when a block of the full node arrives, the
+//! read-only node produces a block of the same number from its own mem block,
+//! without signing or submitting it, and compares the two. Divergence means
+//! the read-only node could not take over block production with identical
+//! results.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use gw_generator::Generator;
+use gw_mem_pool::pool::{MemPool, OutputParam};
+use gw_store::Store;
+use gw_types::{
+    h256::*,
+    packed::{L2Block, RawL2Block},
+    prelude::*,
+};
+use tokio::task::block_in_place;
+
+use crate::produce_block::{generate_produce_block_param, produce_block, ProduceBlockParam};
+
+pub struct ShadowProducer {
+    store: Store,
+    generator: Arc<Generator>,
+    rollup_config_hash: H256,
+}
+
+impl ShadowProducer {
+    pub fn new(store: Store, generator: Arc<Generator>) -> Self {
+        let rollup_config_hash = generator.rollup_context().rollup_config.hash();
+        Self {
+            store,
+            generator,
+            rollup_config_hash,
+        }
+    }
+
+    /// Produce a block from the mem block and compare it with `block` of the
+    /// full node. Must be called before `block` is applied, i.e. when the mem
+    /// block is still based on its parent.
+    pub fn check(&self, mem_pool: &MemPool, block: &L2Block) -> Result<()> {
+        let raw = block.raw();
+        let block_number = raw.number().unpack();
+        let mem_block_number = mem_pool.mem_block().block_info().number().unpack();
+        if mem_block_number != block_number {
+            log::info!(
+                "[shadow production] skip block {}, mem block {} is not in sync",
+                block_number,
+                mem_block_number
+            );
+            return Ok(());
+        }
+
+        let produced = block_in_place(|| self.produce(mem_pool, &raw))?;
+        gw_metrics::block_producer().shadow_blocks.inc();
+        if produced.hash() == raw.hash() {
+            log::info!("[shadow production] block {} is identical", block_number);
+            return Ok(());
+        }
+
+        gw_metrics::block_producer().shadow_divergences.inc();
+        log::error!(
+            "[shadow production] block {} diverged: {}",
+            block_number,
+            diverged_fields(&produced, &raw).join(", ")
+        );
+        Ok(())
+    }
+
+    fn produce(&self, mem_pool: &MemPool, raw: &RawL2Block) -> Result<RawL2Block> {
+        let (mut mem_block, post_merkle_state) = mem_pool.output_mem_block(&OutputParam::default());
+        mem_block.take_finalized_custodians_capacity();
+        let block_param = generate_produce_block_param(&self.store, mem_block, post_merkle_state)?;
+
+        // Nothing is committed, the db transaction is dropped after producing.
+        let mut db = self.store.begin_transaction();
+        let reverted_block_root: H256 = (*db.reverted_block_smt()?.root()).into();
+        let param = ProduceBlockParam {
+            stake_cell_owner_lock_hash: raw.stake_cell_owner_lock_hash().unpack(),
+            reverted_block_root,
+            rollup_config_hash: self.rollup_config_hash,
+            block_param,
+        };
+        let result = produce_block(&mut db, &self.generator, param)?;
+        Ok(result.block.raw())
+    }
+}
+
+fn diverged_fields(produced: &RawL2Block, expected: &RawL2Block) -> Vec<&'static str> {
+    let fields = [
+        (
+            "post account",
+            produced.post_account().as_slice() != expected.post_account().as_slice(),
+        ),
+        (
+            "submit transactions",
+            produced.submit_transactions().as_slice() != expected.submit_transactions().as_slice(),
+        ),
+        (
+            "submit withdrawals",
+            produced.submit_withdrawals().as_slice() != expected.submit_withdrawals().as_slice(),
+        ),
+        (
+            "state checkpoint list",
+            produced.state_checkpoint_list().as_slice()
+                != expected.state_checkpoint_list().as_slice(),
+        ),
+        (
+            "timestamp",
+            produced.timestamp().as_slice() != expected.timestamp().as_slice(),
+        ),
+        (
+            "parent block hash",
+            produced.parent_block_hash().as_slice() != expected.parent_block_hash().as_slice(),
+        ),
+    ];
+    let diverged: Vec<_> = (fields.into_iter())
+        .filter_map(|(name, diverged)| diverged.then_some(name))
+        .collect();
+    if diverged.is_empty() {
+        vec!["other fields"]
+    } else {
+        diverged
+    }
+}
@@ -0,0 +1,98 @@
+//! Gauges operators alert on: time since the last submission, number of
+//! uncommitted blocks, and age of the oldest unfinalized withdrawal.
+
+use anyhow::{Context, Result};
+use gw_store::{traits::chain_store::ChainStore, Store};
+use gw_types::{h256::*, prelude::*};
+use gw_utils::{find_finalized_upper_bound, RollupContext};
+use tokio::time::Instant;
+
+use crate::submission_window::unix_millis;
+
+/// Maximum number of blocks scanned for withdrawals in one update, so a long
+/// finality window doesn't stall the caller.
+const MAX_SCAN_BLOCKS: u64 = 1000;
+
+pub struct SloMetrics {
+    last_submitted_at: Instant,
+    /// Blocks after the last finalized block and before `cursor` have no
+    /// withdrawals.
+    cursor: u64,
+    /// Hash of block `cursor - 1`, to detect reverts.
+    cursor_parent: H256,
+}
+
+impl SloMetrics {
+    pub fn new() -> Self {
+        Self {
+            last_submitted_at: Instant::now(),
+            cursor: 0,
+            cursor_parent: H256::zero(),
+        }
+    }
+
+    pub fn block_submitted(&mut self) {
+        self.last_submitted_at = Instant::now();
+    }
+
+    pub fn update(
+        &mut self,
+        store: &Store,
+        rollup_context: &RollupContext,
+        uncommitted_blocks: u64,
+    ) -> Result<()> {
+        let metrics = gw_metrics::block_producer();
+        metrics
+            .seconds_since_last_submission
+            .set(self.last_submitted_at.elapsed().as_secs());
+        metrics.uncommitted_blocks.set(uncommitted_blocks);
+
+        let snap = store.get_snapshot();
+        let tip = snap.get_last_valid_tip_block()?;
+        let tip_number = tip.raw().number().unpack();
+        let finalized = find_finalized_upper_bound(
+            &rollup_context.rollup_config,
+            &rollup_context.fork_config,
+            &snap,
+            &tip,
+        )?;
+
+        let reverted = self.cursor > 0
+            && snap.get_block_hash_by_number(self.cursor - 1)? != Some(self.cursor_parent);
+        if reverted || self.cursor <= finalized {
+            self.cursor = finalized + 1;
+            self.cursor_parent = snap
+                .get_block_hash_by_number(finalized)?
+                .context("get finalized block hash")?;
+        }
+
+        let scan_end = tip_number.min(self.cursor.saturating_add(MAX_SCAN_BLOCKS));
+        let mut oldest = None;
+        while self.cursor <= scan_end {
+            let block_hash = snap
+                .get_block_hash_by_number(self.cursor)?
+                .context("get block hash")?;
+            let block = snap.get_block(&block_hash)?.context("get block")?;
+            if !block.withdrawals().is_empty() {
+                oldest = Some(block.raw().timestamp().unpack());
+                break;
+            }
+            self.cursor += 1;
+            self.cursor_parent = block_hash;
+        }
+
+        match oldest {
+            Some(timestamp) => {
+                let age = unix_millis().saturating_sub(timestamp) / 1000;
+                metrics.oldest_unfinalized_withdrawal_age.set(age);
+            }
+            // All unfinalized blocks are scanned.
+            None if self.cursor > tip_number => {
+                metrics.oldest_unfinalized_withdrawal_age.set(0);
+            }
+            // Continue scanning in the next update.
+            None => {}
+        }
+        Ok(())
+    }
+}
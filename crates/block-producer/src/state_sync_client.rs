@@ -0,0 +1,186 @@
+//! Bootstrap the store of a readonly node from the state at a recent block
+//! served by the p2p peer, instead of syncing all blocks from genesis.
+//!
+//! The state is downloaded into a temporary store next to the configured one
+//! and moved into place when it is complete and checked, so an interrupted
+//! sync leaves no partial store behind.
+
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use gw_config::{P2PNetworkConfig, StoreConfig};
+use gw_p2p_network::{P2PNetwork, PeerLedger};
+use gw_rpc_client::ckb_client::CkbClient;
+use gw_store::{
+    migrate::{init_migration_factory, open_or_create_db},
+    Store,
+};
+use gw_types::{
+    h256::*,
+    packed::{
+        P2PStateSyncRequest, P2PSyncResponseReader, P2PSyncResponseUnionReader, StateSnapshotEntry,
+        StateSnapshotEntryReader, StateSnapshotHeader, StateSnapshotRecordUnion,
+    },
+    prelude::*,
+};
+use gw_utils::state_snapshot::{check_snapshot_on_chain, SnapshotImporter};
+use tokio::task::block_in_place;
+
+use crate::block_sync_client::{block_sync_client_protocol, P2PStream};
+
+/// How long to wait for the submission tx of the state block to be committed.
+const ON_CHAIN_CHECK_ATTEMPTS: usize = 20;
+
+/// Whether the store at `path` has not been created yet.
+pub fn is_empty_store(path: &Path) -> Result<bool> {
+    Ok(!path.exists() || fs::read_dir(path)?.next().is_none())
+}
+
+/// Download the state from the peer into a new store at `store_config.path`.
+pub async fn sync_state_from_peer(
+    store_config: &StoreConfig,
+    p2p_network_config: &P2PNetworkConfig,
+    rollup_type_hash: &H256,
+    ckb_client: &CkbClient,
+) -> Result<()> {
+    let tmp_path = {
+        let mut path = OsString::from(store_config.path.as_os_str());
+        path.push(".state-sync");
+        PathBuf::from(path)
+    };
+    if tmp_path.exists() {
+        fs::remove_dir_all(&tmp_path).context("remove incomplete state sync store")?;
+    }
+
+    // Only dial, the node listens after it's bootstrapped.
+    let config = P2PNetworkConfig {
+        listen: None,
+        ..p2p_network_config.clone()
+    };
+    let inbox = Arc::new(std::sync::Mutex::new(None));
+    let peer_ledger = Arc::new(PeerLedger::new(&config.peer_score)?);
+    let protocol = block_sync_client_protocol(inbox.clone(), peer_ledger.clone());
+    let mut network = P2PNetwork::init_with_peer_ledger(&config, [protocol], peer_ledger)
+        .await
+        .context("init p2p network")?;
+    let control = network.control().clone();
+    let network_handle = tokio::spawn(async move { network.run().await });
+
+    let result = async {
+        let mut stream = loop {
+            if let Some(stream) = inbox.lock().unwrap().take() {
+                break stream;
+            }
+            log::info!("waiting for p2p peer");
+            tokio::time::sleep(Duration::from_secs(3)).await;
+        };
+        let store_config = StoreConfig {
+            path: tmp_path.clone(),
+            ..store_config.clone()
+        };
+        let store = Store::new(open_or_create_db(&store_config, init_migration_factory())?);
+        let result = download_state(&mut stream, &store, rollup_type_hash, ckb_client).await;
+        let _ = stream.disconnect().await;
+        result
+    }
+    .await;
+
+    let _ = control.close().await;
+    let _ = network_handle.await;
+
+    match result {
+        Ok(header) => {
+            fs::rename(&tmp_path, &store_config.path).with_context(|| {
+                format!(
+                    "move {} to {}",
+                    tmp_path.display(),
+                    store_config.path.display()
+                )
+            })?;
+            log::info!(
+                "synced state at block {} from peer",
+                header.block_number().unpack()
+            );
+            Ok(())
+        }
+        Err(err) => {
+            let _ = fs::remove_dir_all(&tmp_path);
+            Err(err)
+        }
+    }
+}
+
+async fn download_state(
+    stream: &mut P2PStream,
+    store: &Store,
+    rollup_type_hash: &H256,
+    ckb_client: &CkbClient,
+) -> Result<StateSnapshotHeader> {
+    loop {
+        stream
+            .send(P2PStateSyncRequest::default().as_bytes())
+            .await?;
+        let response = stream.recv().await?.context("unexpected end of stream")?;
+        let response = P2PSyncResponseReader::from_slice(&response)?;
+        match response.to_enum() {
+            P2PSyncResponseUnionReader::Found(_) => break,
+            P2PSyncResponseUnionReader::TryAgain(_) => {}
+        }
+        log::info!("state is not available on the peer, will try again");
+        tokio::time::sleep(Duration::from_secs(3)).await;
+    }
+
+    let entry = recv_entry(stream).await?;
+    let header = match entry.record().to_enum() {
+        StateSnapshotRecordUnion::StateSnapshotHeader(header) => header,
+        _ => bail!("snapshot header is not the first entry"),
+    };
+    let header_rollup_type_hash: H256 = header.rollup_type_hash().unpack();
+    ensure!(
+        &header_rollup_type_hash == rollup_type_hash,
+        "state of rollup {}, expect {}",
+        header.rollup_type_hash(),
+        rollup_type_hash.pack()
+    );
+    log::info!(
+        "receiving state at block {}",
+        header.block_number().unpack()
+    );
+    // The peer is not trusted, the state must match a block committed on L1.
+    // The block may be just submitted.
+    let mut attempt = 1;
+    while let Err(err) = check_snapshot_on_chain(ckb_client, &header).await {
+        if attempt >= ON_CHAIN_CHECK_ATTEMPTS {
+            return Err(err);
+        }
+        log::info!("waiting for the state block on L1: {:#}", err);
+        attempt += 1;
+        tokio::time::sleep(Duration::from_secs(6)).await;
+    }
+
+    let mut importer = SnapshotImporter::new(store)?;
+    importer.push(entry)?;
+    let mut entries = 1;
+    loop {
+        let entry = recv_entry(stream).await?;
+        if let Some(header) = block_in_place(|| importer.push(entry))? {
+            return Ok(header);
+        }
+        entries += 1;
+        if entries % 100 == 0 {
+            log::info!("received {} state entries", entries);
+        }
+    }
+}
+
+async fn recv_entry(stream: &mut P2PStream) -> Result<StateSnapshotEntry> {
+    let msg = stream.recv().await?.context("unexpected end of stream")?;
+    StateSnapshotEntryReader::verify(&msg, false)?;
+    Ok(StateSnapshotEntry::new_unchecked(msg))
+}
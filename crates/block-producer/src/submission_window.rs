@@ -0,0 +1,141 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{ensure, Result};
+use gw_config::SubmissionWindowConfig;
+
+/// L1 submission schedule of the operator, all times are unix timestamps in
+/// milliseconds.
+#[derive(Debug, Clone)]
+pub struct SubmissionWindow {
+    round: u64,
+    offset: u64,
+    duration: u64,
+    produce_lead: u64,
+    send_margin: u64,
+}
+
+impl SubmissionWindow {
+    pub fn new(config: &SubmissionWindowConfig) -> Result<Self> {
+        ensure!(config.round_secs > 0, "round_secs must be positive");
+        ensure!(
+            config.offset_secs + config.duration_secs <= config.round_secs,
+            "submission window must end before the round ends"
+        );
+        ensure!(
+            config.send_margin_millis < config.duration_secs * 1000,
+            "send_margin_millis must be less than the window duration"
+        );
+        Ok(Self {
+            round: config.round_secs * 1000,
+            offset: config.offset_secs * 1000,
+            duration: config.duration_secs * 1000,
+            produce_lead: config.produce_lead_millis,
+            send_margin: config.send_margin_millis,
+        })
+    }
+
+    /// Open and close time of the window of `round`.
+    pub fn window(&self, round: u64) -> (u64, u64) {
+        let open = round * self.round + self.offset;
+        (open, open + self.duration)
+    }
+
+    /// The first round whose window is still usable at `now`, i.e. it has not
+    /// opened yet or there is enough time left to send a transaction.
+    pub fn usable_round(&self, now: u64) -> u64 {
+        let round = now / self.round;
+        let (_, close) = self.window(round);
+        if now + self.send_margin < close {
+            round
+        } else {
+            round + 1
+        }
+    }
+
+    /// Round to produce the next block for and when to start producing it.
+    ///
+    /// Produce for one round after another, skip rounds that are no longer
+    /// usable so that a late block waits for the next window.
+    pub fn next_produce(&self, now: u64, last_produced_round: Option<u64>) -> (u64, u64) {
+        let mut round = self.usable_round(now);
+        if let Some(last) = last_produced_round {
+            round = round.max(last + 1);
+        }
+        let (open, _) = self.window(round);
+        (round, open.saturating_sub(self.produce_lead))
+    }
+
+    /// How long to wait before a transaction can be sent at `now`, None if it
+    /// can be sent right away.
+    pub fn wait_to_send(&self, now: u64) -> Option<Duration> {
+        let (open, _) = self.window(self.usable_round(now));
+        (open > now).then(|| Duration::from_millis(open - now))
+    }
+}
+
+/// Current unix timestamp in milliseconds.
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("unix timestamp")
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window() -> SubmissionWindow {
+        // Rounds of 60s, window [10s, 25s) of each round.
+        SubmissionWindow::new(&SubmissionWindowConfig {
+            round_secs: 60,
+            offset_secs: 10,
+            duration_secs: 15,
+            produce_lead_millis: 2000,
+            send_margin_millis: 1000,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_usable_round() {
+        let w = window();
+        assert_eq!(w.window(1), (70_000, 85_000));
+        assert_eq!(w.usable_round(60_000), 1);
+        assert_eq!(w.usable_round(80_000), 1);
+        // Not enough time left in the window.
+        assert_eq!(w.usable_round(84_000), 2);
+        assert_eq!(w.usable_round(119_999), 2);
+    }
+
+    #[test]
+    fn test_next_produce() {
+        let w = window();
+        assert_eq!(w.next_produce(60_000, None), (1, 68_000));
+        // Already in the window, produce right away.
+        assert_eq!(w.next_produce(75_000, Some(0)), (1, 68_000));
+        assert_eq!(w.next_produce(75_000, Some(1)), (2, 128_000));
+        assert_eq!(w.next_produce(84_500, Some(0)), (2, 128_000));
+    }
+
+    #[test]
+    fn test_wait_to_send() {
+        let w = window();
+        assert_eq!(w.wait_to_send(68_000), Some(Duration::from_millis(2000)));
+        assert_eq!(w.wait_to_send(70_000), None);
+        assert_eq!(w.wait_to_send(83_999), None);
+        assert_eq!(w.wait_to_send(84_000), Some(Duration::from_millis(46_000)));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config = SubmissionWindowConfig {
+            round_secs: 60,
+            offset_secs: 50,
+            duration_secs: 15,
+            produce_lead_millis: 2000,
+            send_margin_millis: 1000,
+        };
+        assert!(SubmissionWindow::new(&config).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! Rotation of the wallets paying fees of submission transactions.
+//!
+//! Submission transactions are chained, a wallet whose previous transaction is
+//! not confirmed yet pays with unconfirmed change cells. Rotating between
+//! wallets lets a new transaction pay with confirmed cells of another wallet,
+//! and avoids wallets whose transactions got stuck.
+
+use std::collections::HashSet;
+
+use gw_types::h256::H256;
+
+#[derive(Debug, Default)]
+struct Submitter {
+    /// Number of transactions composed by this wallet.
+    nonce: u64,
+    /// Transactions composed but not confirmed.
+    in_flight: HashSet<H256>,
+    /// Sequence of the last reset this wallet had transactions stuck in.
+    stuck: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct SubmitterRotation {
+    submitters: Vec<Submitter>,
+    resets: u64,
+}
+
+impl SubmitterRotation {
+    pub fn new(wallets: usize) -> Self {
+        assert!(wallets > 0, "at least one submitter wallet");
+        Self {
+            submitters: (0..wallets).map(|_| Submitter::default()).collect(),
+            resets: 0,
+        }
+    }
+
+    /// Wallet to pay the next transaction. Prefer wallets with the fewest in
+    /// flight transactions, then wallets that never got stuck or got stuck
+    /// longest ago.
+    pub fn pick(&self) -> usize {
+        let key = |(i, s): &(usize, &Submitter)| (s.in_flight.len(), s.stuck, *i);
+        let (index, _) = (self.submitters.iter().enumerate())
+            .min_by_key(key)
+            .expect("submitters");
+        index
+    }
+
+    /// Track a transaction paid by wallet `index`, returns its nonce.
+    pub fn track(&mut self, index: usize, tx_hash: H256) -> u64 {
+        let submitter = &mut self.submitters[index];
+        let nonce = submitter.nonce;
+        submitter.nonce += 1;
+        submitter.in_flight.insert(tx_hash);
+        nonce
+    }
+
+    pub fn confirm(&mut self, tx_hash: &H256) {
+        for submitter in self.submitters.iter_mut() {
+            if submitter.in_flight.remove(tx_hash) {
+                submitter.stuck = None;
+            }
+        }
+    }
+
+    /// Submission transactions are dropped, e.g. they are not confirmed in
+    /// time. Wallets that have transactions in flight are marked stuck.
+    pub fn reset(&mut self) {
+        self.resets += 1;
+        for submitter in self.submitters.iter_mut() {
+            if !submitter.in_flight.is_empty() {
+                submitter.in_flight.clear();
+                submitter.stuck = Some(self.resets);
+            }
+        }
+    }
+
+    pub fn in_flight(&self, index: usize) -> usize {
+        self.submitters[index].in_flight.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submitter_rotation() {
+        let mut r = SubmitterRotation::new(3);
+        assert_eq!(r.pick(), 0);
+        assert_eq!(r.track(0, [1; 32]), 0);
+        // Rotate to wallets without in flight transactions.
+        assert_eq!(r.pick(), 1);
+        r.track(1, [2; 32]);
+        r.track(2, [3; 32]);
+        assert_eq!(r.pick(), 0);
+        r.confirm(&[1; 32]);
+        assert_eq!(r.track(0, [4; 32]), 1);
+        assert_eq!(r.in_flight(0), 1);
+
+        // Wallet 1 and 2 get stuck.
+        r.confirm(&[4; 32]);
+        r.reset();
+        assert_eq!(r.in_flight(1), 0);
+        assert_eq!(r.pick(), 0);
+        r.track(0, [5; 32]);
+        r.reset();
+        // All got stuck, wallet 1 and 2 longest ago.
+        assert_eq!(r.pick(), 1);
+        r.track(1, [6; 32]);
+        r.confirm(&[6; 32]);
+        assert_eq!(r.pick(), 1);
+    }
+}
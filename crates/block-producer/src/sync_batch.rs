@@ -0,0 +1,82 @@
+//! Adaptive batch size of L1 transactions queried and applied at a time when
+//! syncing from L1.
+//!
+//! Batches grow while they are processed quickly, so catch-up sync doesn't
+//! trickle, and shrink when they are slow or hold too many bytes of L1
+//! transactions, so the CKB node and memory are not overwhelmed.
+
+use std::time::Duration;
+
+const MIN_BATCH_SIZE: usize = 10;
+/// Limit of indexer queries.
+const MAX_BATCH_SIZE: usize = 500;
+const INITIAL_BATCH_SIZE: usize = 50;
+/// Processing time of a batch to aim for.
+const TARGET_LATENCY: Duration = Duration::from_secs(10);
+/// Maximum bytes of L1 transactions in a batch.
+const MAX_BATCH_BYTES: usize = 32 * 1024 * 1024;
+/// Maximum number of L1 transactions being fetched, and of fetched ones
+/// buffered, ahead of the one being applied.
+pub const MAX_PREFETCH: usize = 4;
+
+pub struct AdaptiveBatch {
+    size: usize,
+}
+
+impl Default for AdaptiveBatch {
+    fn default() -> Self {
+        Self {
+            size: INITIAL_BATCH_SIZE,
+        }
+    }
+}
+
+impl AdaptiveBatch {
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Adjust batch size after `txs` transactions of `bytes` in total are
+    /// processed in `elapsed`.
+    pub fn record(&mut self, txs: usize, bytes: usize, elapsed: Duration) {
+        if bytes > MAX_BATCH_BYTES || elapsed > TARGET_LATENCY {
+            self.size = (self.size / 2).max(MIN_BATCH_SIZE);
+        } else if elapsed < TARGET_LATENCY / 2 && txs >= self.size {
+            // Only grow on full batches, i.e. when there are more to sync.
+            self.size = (self.size * 2).min(MAX_BATCH_SIZE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adaptive_batch() {
+        let mut batch = AdaptiveBatch::default();
+        let fast = Duration::from_secs(1);
+
+        // Partial batch, e.g. caught up with L1.
+        batch.record(5, 1024, fast);
+        assert_eq!(batch.size(), INITIAL_BATCH_SIZE);
+
+        batch.record(batch.size(), 1024, fast);
+        assert_eq!(batch.size(), INITIAL_BATCH_SIZE * 2);
+        for _ in 0..10 {
+            batch.record(batch.size(), 1024, fast);
+        }
+        assert_eq!(batch.size(), MAX_BATCH_SIZE);
+
+        // Slow
+        batch.record(batch.size(), 1024, TARGET_LATENCY * 2);
+        assert_eq!(batch.size(), MAX_BATCH_SIZE / 2);
+        // Memory pressure
+        batch.record(batch.size(), MAX_BATCH_BYTES + 1, fast);
+        assert_eq!(batch.size(), MAX_BATCH_SIZE / 4);
+        for _ in 0..10 {
+            batch.record(batch.size(), MAX_BATCH_BYTES + 1, fast);
+        }
+        assert_eq!(batch.size(), MIN_BATCH_SIZE);
+    }
+}
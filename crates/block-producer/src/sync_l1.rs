@@ -1,7 +1,9 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{bail, Context, Result};
-use gw_chain::chain::{Chain, RevertedL1Action};
+use ckb_fixed_hash::H256;
+use futures::{stream, StreamExt};
+use gw_chain::chain::{Chain, L1Action};
 use gw_jsonrpc_types::ckb_jsonrpc_types::BlockNumber;
 use gw_rpc_client::{
     indexer_types::{Order, SearchKey, SearchKeyFilter},
@@ -16,9 +18,12 @@ use gw_types::{
     prelude::*,
 };
 use gw_utils::{liveness::Liveness, ExponentialBackoff};
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
 
-use crate::chain_updater::ChainUpdater;
+use crate::{
+    chain_updater::ChainUpdater,
+    sync_batch::{AdaptiveBatch, MAX_PREFETCH},
+};
 
 pub trait SyncL1Context {
     fn store(&self) -> &Store;
@@ -76,6 +81,13 @@ async fn sync_l1_impl(ctx: &(dyn SyncL1Context + Sync + Send)) -> Result<()> {
             break;
         }
     }
+    let last_confirmed_local: u64 = last_confirmed_local.number().unpack();
+    if last_confirmed_l1 < last_confirmed_local {
+        log::warn!(
+            "L1 reorg dropped confirmed L2 blocks {}..={last_confirmed_local}",
+            last_confirmed_l1 + 1
+        );
+    }
 
     sync_l1_unknown(ctx, &mut store_tx, last_confirmed_l1).await?;
 
@@ -117,11 +129,14 @@ async fn sync_l1_unknown(
     let last_confirmed_tx_hash = tx_hash.into();
     let mut seen_last_confirmed = false;
     let mut reverted = false;
+    let mut batch = AdaptiveBatch::default();
     loop {
+        let started = Instant::now();
+        let limit = batch.size() as u32;
         let mut txs = ctx
             .rpc_client()
             .indexer
-            .get_transactions(&search_key, &Order::Asc, 500.into(), &last_cursor)
+            .get_transactions(&search_key, &Order::Asc, limit.into(), &last_cursor)
             .await?;
         txs.objects.dedup_by_key(|obj| obj.tx_hash.clone());
         if txs.objects.is_empty() {
@@ -129,6 +144,7 @@ async fn sync_l1_unknown(
         }
         last_cursor = Some(txs.last_cursor);
 
+        let mut to_apply = Vec::new();
         for tx in txs.objects {
             if !seen_last_confirmed {
                 log::info!("skipping transaction {}", tx.tx_hash);
@@ -138,7 +154,6 @@ async fn sync_l1_unknown(
                 continue;
             }
 
-            log::info!("syncing L1 transaction {}", tx.tx_hash);
             if !reverted {
                 // It's likely that this transaction confirms the next block. In
                 // this case, we just update the last confirmed block.
@@ -148,20 +163,50 @@ async fn sync_l1_unknown(
                     continue;
                 }
 
-                log::info!("L2 fork detected, reverting to L2 block {last_confirmed}");
+                let last_valid: u64 = store_tx
+                    .get_last_valid_tip_block()
+                    .context("get last valid tip block")?
+                    .raw()
+                    .number()
+                    .unpack();
+                let depth = last_valid.saturating_sub(last_confirmed);
+                log::warn!(
+                    "L2 fork detected, reorg depth {depth}, reverting to L2 block {last_confirmed}"
+                );
+                gw_metrics::block_producer().l1_reorgs.inc();
+                gw_metrics::block_producer().l1_reorg_depth.set(depth);
                 revert(ctx, store_tx, last_confirmed).await?;
-                // Commit transaction because chain_updater.update_single will open and commit new transactions.
+                // Commit transaction because chain_updater.apply_l1_action will open and commit new transactions.
                 store_tx.commit()?;
                 reverted = true;
             }
+            to_apply.push(tx.tx_hash);
+        }
+
+        // Fetch and decode L1 actions in a separate task, so that later
+        // actions are fetched while the current one is being applied. At most
+        // MAX_PREFETCH requests are in flight and MAX_PREFETCH actions are
+        // buffered.
+        let chain_updater = ctx.chain_updater();
+        let batch_txs = to_apply.len();
+        let mut actions = spawn_prefetch(chain_updater.clone(), to_apply);
+        let mut bytes = 0;
+        while let Some(action) = actions.recv().await {
+            let action = action?;
+            log::info!(
+                "syncing L1 transaction {}",
+                action.transaction.hash().pack()
+            );
+            bytes += action.transaction.as_slice().len();
             let rt_handle = tokio::runtime::Handle::current();
-            let chain_updater = ctx.chain_updater().clone();
+            let chain_updater = chain_updater.clone();
             tokio::task::spawn_blocking(move || {
-                rt_handle.block_on(async move { chain_updater.update_single(&tx.tx_hash).await })
+                rt_handle.block_on(async move { chain_updater.apply_l1_action(action).await })
             })
             .await??;
             ctx.liveness().tick();
         }
+        batch.record(batch_txs, bytes, started.elapsed());
     }
     if !reverted {
         // Reset last confirmed and last_submitted.
@@ -180,29 +225,44 @@ async fn sync_l1_unknown(
     Ok(())
 }
 
+/// Fetch L1 actions of `tx_hashes` in order in the background.
+///
+/// The task stops after the first error or when the receiver is dropped.
+fn spawn_prefetch(
+    chain_updater: ChainUpdater,
+    tx_hashes: Vec<H256>,
+) -> mpsc::Receiver<Result<L1Action>> {
+    let (sender, receiver) = mpsc::channel(MAX_PREFETCH);
+    tokio::spawn(async move {
+        let mut actions = stream::iter(tx_hashes)
+            .map(|tx_hash| {
+                let chain_updater = chain_updater.clone();
+                async move { chain_updater.fetch_l1_action(&tx_hash).await }
+            })
+            .buffered(MAX_PREFETCH);
+        while let Some(action) = actions.next().await {
+            let is_err = action.is_err();
+            if sender.send(action).await.is_err() || is_err {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
 /// Revert L2 blocks.
+///
+/// Bad blocks and challenges after the last valid tip are rewound first, so
+/// reorgs beyond the last valid tip don't need a manual
+/// `rewind-to-last-valid-block`.
 pub async fn revert(
     ctx: &(dyn SyncL1Context + Send + Sync),
     store_tx: &mut StoreTransaction,
     revert_to_last_valid: u64,
 ) -> Result<()> {
     let mut chain = ctx.chain().lock().await;
-    loop {
-        let block = store_tx.get_last_valid_tip_block()?;
-        let block_number = block.raw().number().unpack();
-        if block_number <= revert_to_last_valid {
-            break;
-        }
-        let prev_global_state = store_tx
-            .get_block_post_global_state(&block.raw().parent_block_hash().unpack())?
-            .context("get parent global state")?;
-        let action = RevertedL1Action {
-            prev_global_state,
-            context: gw_chain::chain::RevertL1ActionContext::SubmitValidBlock { l2block: block },
-        };
-        log::info!("reverting L2 block {}", block_number);
-        chain.revert_l1action(store_tx, action)?;
+    if chain.rewind_to_last_valid_tip(store_tx)? {
+        log::warn!("rewound to last valid tip");
     }
-
-    Ok(())
+    chain.revert_valid_blocks(store_tx, revert_to_last_valid)
 }
@@ -2,7 +2,9 @@ use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use gw_common::merkle_utils::{calculate_ckb_merkle_root, ckb_merkle_leaf_hash};
 use gw_generator::types::vm::ChallengeContext;
-use gw_jsonrpc_types::test_mode::ChallengeType;
+use gw_jsonrpc_types::test_mode::{
+    ChallengeStage, ChallengeStatus, ChallengeStep, ChallengeTargetInfo, ChallengeType,
+};
 use gw_jsonrpc_types::{godwoken::GlobalState as JsonGlobalState, test_mode::TestModePayload};
 use gw_rpc_client::rpc_client::RPCClient;
 use gw_rpc_server::registry::TestModeRpc;
@@ -12,6 +14,7 @@ use gw_store::traits::chain_store::ChainStore;
 use gw_store::Store;
 use gw_types::bytes::Bytes;
 use gw_types::core::{ChallengeTargetType, Status};
+use gw_types::h256::H256;
 use gw_types::offchain::global_state_from_slice;
 use gw_types::packed::{
     BlockMerkleState, ChallengeTarget, ChallengeWitness, GlobalState, L2Block, L2Transaction,
@@ -20,11 +23,22 @@ use gw_types::packed::{
 use gw_types::prelude::*;
 use tokio::sync::Mutex;
 
+use std::convert::TryFrom;
 use std::sync::Arc;
 
+#[derive(Default)]
+struct ChallengeProgress {
+    stage: ChallengeStage,
+    target: Option<ChallengeTargetInfo>,
+    steps: u64,
+    last_step: Option<ChallengeStep>,
+    last_tx_hash: Option<H256>,
+}
+
 #[derive(Clone)]
 pub struct TestModeControl {
     payload: Arc<Mutex<Option<TestModePayload>>>,
+    challenge_progress: Arc<Mutex<ChallengeProgress>>,
     rpc_client: RPCClient,
     store: Store,
 }
@@ -33,6 +47,7 @@ impl TestModeControl {
     pub fn new(rpc_client: RPCClient, store: Store) -> Self {
         TestModeControl {
             payload: Arc::new(Mutex::new(None)),
+            challenge_progress: Arc::new(Mutex::new(ChallengeProgress::default())),
             rpc_client,
             store,
         }
@@ -52,6 +67,48 @@ impl TestModeControl {
         Ok(())
     }
 
+    /// Consume payload if it equals to the expected one.
+    pub async fn consume(&self, expected: &TestModePayload) -> Result<()> {
+        let mut payload = self.payload.lock().await;
+        if Some(expected) != payload.as_ref() {
+            return Err(anyhow!("not {:?} payload", expected));
+        }
+
+        payload.take();
+        Ok(())
+    }
+
+    /// Update challenge stage observed by challenger.
+    pub async fn set_challenge_stage(
+        &self,
+        stage: ChallengeStage,
+        target: Option<&ChallengeTarget>,
+    ) {
+        let mut progress = self.challenge_progress.lock().await;
+        progress.stage = stage;
+        if let Some(target) = target.and_then(to_target_info) {
+            progress.target = Some(target);
+        }
+    }
+
+    /// Record a challenge, cancel or revert tx submitted by challenger.
+    pub async fn record_challenge_step(
+        &self,
+        step: ChallengeStep,
+        target: Option<&ChallengeTarget>,
+        tx_hash: H256,
+    ) {
+        log::info!("test mode challenge step {:?}", step);
+
+        let mut progress = self.challenge_progress.lock().await;
+        if let Some(target) = target.and_then(to_target_info) {
+            progress.target = Some(target);
+        }
+        progress.steps = progress.steps.saturating_add(1);
+        progress.last_step = Some(step);
+        progress.last_tx_hash = Some(tx_hash);
+    }
+
     pub async fn generate_a_bad_block(
         &self,
         block: L2Block,
@@ -316,6 +373,20 @@ impl TestModeRpc for TestModeControl {
         Ok(global_state.into())
     }
 
+    async fn tests_get_challenge_status(&self) -> gw_rpc_server::registry::Result<ChallengeStatus> {
+        let payload = self.payload().await;
+        let progress = self.challenge_progress.lock().await;
+
+        Ok(ChallengeStatus {
+            payload,
+            stage: progress.stage,
+            target: progress.target.clone(),
+            steps: progress.steps.into(),
+            last_step: progress.last_step,
+            last_tx_hash: progress.last_tx_hash.map(ckb_types::H256),
+        })
+    }
+
     async fn tests_produce_block(
         &self,
         payload: TestModePayload,
@@ -327,3 +398,18 @@ impl TestModeRpc for TestModeControl {
         Ok(())
     }
 }
+
+fn to_target_info(target: &ChallengeTarget) -> Option<ChallengeTargetInfo> {
+    let target_type = match ChallengeTargetType::try_from(target.target_type()).ok()? {
+        ChallengeTargetType::TxExecution => ChallengeType::TxExecution,
+        ChallengeTargetType::TxSignature => ChallengeType::TxSignature,
+        ChallengeTargetType::Withdrawal => ChallengeType::WithdrawalSignature,
+    };
+    let target_index: u32 = target.target_index().unpack();
+
+    Some(ChallengeTargetInfo {
+        block_hash: ckb_types::H256(target.block_hash().unpack()),
+        target_index: target_index.into(),
+        target_type,
+    })
+}
@@ -3,11 +3,12 @@
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use anyhow::{bail, Result};
 use async_trait::async_trait;
-use gw_config::{ContractsCellDep, DebugConfig};
+use gw_config::{ContractsCellDep, DebugConfig, WithdrawalUnlockerConfig};
 pub use gw_rpc_client::contract::Guard;
 use gw_rpc_client::{contract::ContractsCellDepManager, rpc_client::RPCClient};
 use gw_types::{
@@ -19,17 +20,24 @@ use gw_types::{
 use gw_utils::{
     fee::fill_tx_fee, genesis_info::CKBGenesisInfo, local_cells::LocalCellsManager,
     query_rollup_cell, transaction_skeleton::TransactionSkeleton, wallet::Wallet,
+    ExponentialBackoff,
 };
 use tokio::sync::Mutex;
 use tracing::instrument;
 
-use crate::{types::ChainEvent, utils, utils::global_state_last_finalized_timepoint_to_since};
+use crate::{
+    fee_estimator::FeeEstimator, types::ChainEvent, utils,
+    utils::global_state_last_finalized_timepoint_to_since,
+};
 
 pub struct FinalizedWithdrawalUnlocker {
     unlocker: DefaultUnlocker,
     unlocked_set: HashSet<OutPoint>,
     unlock_txs: HashMap<H256, Vec<OutPoint>>,
     debug_config: DebugConfig,
+    retry_backoff: ExponentialBackoff,
+    /// Don't try new unlock txs before this after a failure.
+    retry_at: Option<Instant>,
 }
 
 impl FinalizedWithdrawalUnlocker {
@@ -40,7 +48,8 @@ impl FinalizedWithdrawalUnlocker {
         contracts_dep_manager: ContractsCellDepManager,
         wallet: Wallet,
         debug_config: DebugConfig,
-        fee_rate: u64,
+        fee_estimator: Arc<dyn FeeEstimator>,
+        config: &WithdrawalUnlockerConfig,
     ) -> Self {
         let unlocker = DefaultUnlocker::new(
             rpc_client,
@@ -48,19 +57,45 @@ impl FinalizedWithdrawalUnlocker {
             ckb_genesis_info,
             contracts_dep_manager,
             wallet,
-            fee_rate,
+            fee_estimator,
+            config,
         );
+        let retry_backoff =
+            ExponentialBackoff::new(Duration::from_secs(config.retry_delay_secs.max(1)))
+                .with_max_sleep(Duration::from_secs(config.max_retry_delay_secs))
+                .with_jitter(false);
 
         FinalizedWithdrawalUnlocker {
             unlocker,
             unlocked_set: Default::default(),
             unlock_txs: Default::default(),
             debug_config,
+            retry_backoff,
+            retry_at: None,
         }
     }
 
+    fn retry_later(&mut self) -> Duration {
+        let delay = self.retry_backoff.next_sleep();
+        self.retry_at = Some(Instant::now() + delay);
+        delay
+    }
+
     #[instrument(skip_all, name = "withdrawal unlocker handle_event")]
     pub async fn handle_event(&mut self, _event: &ChainEvent) -> Result<()> {
+        if let Err(err) = self.try_unlock().await {
+            let delay = self.retry_later();
+            bail!("{:#}, retry in {}s", err, delay.as_secs());
+        }
+        self.check_unlock_txs().await;
+        Ok(())
+    }
+
+    async fn try_unlock(&mut self) -> Result<()> {
+        if matches!(self.retry_at, Some(retry_at) if Instant::now() < retry_at) {
+            return Ok(());
+        }
+
         let unlocked = &self.unlocked_set;
         let rpc_client = &self.unlocker.rpc_client;
         if let Some((tx, to_unlock)) = self.unlocker.query_and_unlock_to_owner(unlocked).await? {
@@ -82,9 +117,13 @@ impl FinalizedWithdrawalUnlocker {
             self.unlocked_set.extend(to_unlock.clone());
             self.unlock_txs.insert(tx_hash, to_unlock);
         }
+        Ok(())
+    }
 
-        // Check unlock tx
+    async fn check_unlock_txs(&mut self) {
+        let rpc_client = &self.unlocker.rpc_client;
         let mut drop_txs = vec![];
+        let mut failed = false;
         for (tx_hash, withdrawal_to_unlock) in self.unlock_txs.iter() {
             match rpc_client.ckb.get_transaction_status(*tx_hash).await {
                 Err(err) => {
@@ -98,6 +137,7 @@ impl FinalizedWithdrawalUnlocker {
                 Ok(None) => {
                     log::info!("[unlock withdrawal] dropped unlock tx {}", tx_hash.pack());
                     drop_txs.push(*tx_hash);
+                    failed = true;
                 }
                 Ok(Some(tx_status)) => {
                     use gw_jsonrpc_types::ckb_jsonrpc_types::Status;
@@ -109,6 +149,7 @@ impl FinalizedWithdrawalUnlocker {
                                 withdrawal_to_unlock.len(),
                                 tx_hash.pack(),
                             );
+                            self.retry_backoff.reset();
                         }
                         Status::Unknown | Status::Rejected => {
                             log::debug!(
@@ -116,6 +157,7 @@ impl FinalizedWithdrawalUnlocker {
                                 tx_hash.pack(),
                                 tx_status
                             );
+                            failed = true;
                         }
                     }
                     drop_txs.push(*tx_hash);
@@ -131,7 +173,10 @@ impl FinalizedWithdrawalUnlocker {
             }
         }
 
-        Ok(())
+        if failed {
+            let delay = self.retry_later();
+            log::info!("[unlock withdrawal] retry in {}s", delay.as_secs());
+        }
     }
 }
 
@@ -209,19 +254,20 @@ struct DefaultUnlocker {
     ckb_genesis_info: CKBGenesisInfo,
     contracts_dep_manager: ContractsCellDepManager,
     wallet: Wallet,
-    fee_rate: u64,
+    fee_estimator: Arc<dyn FeeEstimator>,
+    owner_lock_hashes: HashSet<H256>,
+    max_withdrawals_per_tx: usize,
 }
 
 impl DefaultUnlocker {
-    pub const MAX_WITHDRAWALS_PER_TX: usize = 100;
-
     pub fn new(
         rpc_client: RPCClient,
         local_cells_manager: Arc<Mutex<LocalCellsManager>>,
         ckb_genesis_info: CKBGenesisInfo,
         contracts_dep_manager: ContractsCellDepManager,
         wallet: Wallet,
-        fee_rate: u64,
+        fee_estimator: Arc<dyn FeeEstimator>,
+        config: &WithdrawalUnlockerConfig,
     ) -> Self {
         DefaultUnlocker {
             rpc_client,
@@ -229,7 +275,9 @@ impl DefaultUnlocker {
             ckb_genesis_info,
             contracts_dep_manager,
             wallet,
-            fee_rate,
+            fee_estimator,
+            owner_lock_hashes: config.owner_lock_hashes.iter().map(|h| h.0).collect(),
+            max_withdrawals_per_tx: config.max_withdrawals_per_tx.max(1),
         }
     }
 }
@@ -258,7 +306,8 @@ impl BuildUnlockWithdrawalToOwner for DefaultUnlocker {
             .query_finalized_owner_lock_withdrawal_cells(
                 compatible_finalized_timepoint,
                 unlocked,
-                Self::MAX_WITHDRAWALS_PER_TX,
+                &self.owner_lock_hashes,
+                self.max_withdrawals_per_tx,
             )
             .await
     }
@@ -268,11 +317,12 @@ impl BuildUnlockWithdrawalToOwner for DefaultUnlocker {
         tx_skeleton.cell_deps_mut().push(owner_lock_dep);
 
         let owner_lock = self.wallet.lock_script().to_owned();
+        let fee_rate = self.fee_estimator.estimate_fee_rate().await?;
         fill_tx_fee(
             &mut tx_skeleton,
             &self.rpc_client.indexer,
             owner_lock,
-            self.fee_rate,
+            fee_rate,
         )
         .await?;
         self.wallet.sign_tx_skeleton(tx_skeleton)
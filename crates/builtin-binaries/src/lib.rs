@@ -11,6 +11,9 @@
 //! let binary = Resource::bundled("builtin/godwoken-polyjuice-v1.2.0/generator".to_string()).get().unwrap();
 //! ```
 //!
+//! Binaries not bundled can also be downloaded via `Resource::Url`, which are
+//! verified against the pinned sha256 checksum and cached locally.
+//!
 mod bundled {
     #![allow(missing_docs, clippy::unreadable_literal)]
     include!(concat!(env!("OUT_DIR"), "/bundled.rs"));
@@ -19,18 +22,21 @@ mod bundled {
 use std::{
     borrow::Cow,
     fmt, fs,
-    io::{BufReader, Read, Write},
+    io::{BufReader, Cursor, Read, Write},
     path::{Path, PathBuf},
+    thread,
+    time::Duration,
 };
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
 /// Bundled resources
 pub use bundled::BUNDLED;
+use ckb_fixed_hash::H256;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-/// Represents a resource, which is either bundled in the GW binary or resident in the local file
-/// system.
+/// Represents a resource, which is either bundled in the GW binary, resident in the local file
+/// system or downloaded from a URL.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Resource {
@@ -44,13 +50,28 @@ pub enum Resource {
         /// The file path to the resource.
         file: PathBuf,
     },
+    /// A resource downloaded from a URL and cached in the local file system.
+    Url {
+        /// The URL to download the resource.
+        url: String,
+        /// The pinned sha256 checksum of the resource.
+        sha256: H256,
+        /// The directory to cache downloaded resources, defaults to
+        /// `gw-builtin-binaries` in the temp dir.
+        #[serde(default)]
+        cache_dir: Option<PathBuf>,
+    },
 }
 
+/// Timeout of downloading a URL resource.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
 impl fmt::Display for Resource {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Resource::Bundled { bundled } => write!(f, "Bundled({})", bundled),
             Resource::FileSystem { file } => write!(f, "FileSystem({})", file.display()),
+            Resource::Url { url, sha256, .. } => write!(f, "Url({}, sha256: {:#x})", url, sha256),
         }
     }
 }
@@ -66,6 +87,15 @@ impl Resource {
         Resource::FileSystem { file }
     }
 
+    /// Creates a reference to the resource downloaded from the URL.
+    pub fn url(url: String, sha256: H256) -> Resource {
+        Resource::Url {
+            url,
+            sha256,
+            cache_dir: None,
+        }
+    }
+
     /// Returns `true` if this is a bundled resource.
     pub fn is_bundled(&self) -> bool {
         matches!(self, Resource::Bundled { .. })
@@ -76,10 +106,15 @@ impl Resource {
     /// The bundled resource exists only when the identifier is included in the bundle.
     ///
     /// The file system resource exists only when the file exists.
+    ///
+    /// The URL resource exists only when it's cached, it's downloaded by `get` or `read`.
     pub fn exists(&self) -> bool {
         match self {
             Resource::Bundled { bundled } => BUNDLED.is_available(bundled),
             Resource::FileSystem { file } => file.exists(),
+            Resource::Url {
+                sha256, cache_dir, ..
+            } => cache_path(cache_dir.as_deref(), sha256).exists(),
         }
     }
 
@@ -88,6 +123,11 @@ impl Resource {
         match self {
             Resource::Bundled { bundled } => BUNDLED.get(bundled).map_err(Into::into),
             Resource::FileSystem { file } => Ok(Cow::Owned(fs::read(file)?)),
+            Resource::Url {
+                url,
+                sha256,
+                cache_dir,
+            } => Ok(Cow::Owned(fetch(url, sha256, cache_dir.as_deref())?)),
         }
     }
 
@@ -96,12 +136,13 @@ impl Resource {
         match self {
             Resource::Bundled { bundled } => BUNDLED.read(bundled).map_err(Into::into),
             Resource::FileSystem { file } => Ok(Box::new(BufReader::new(fs::File::open(file)?))),
+            Resource::Url { .. } => Ok(Box::new(Cursor::new(self.get()?))),
         }
     }
 
     /// Exports a bundled resource.
     ///
-    /// This function returns `Ok` immediatly when invoked on a file system resource.
+    /// This function returns `Ok` immediatly when invoked on a file system or URL resource.
     ///
     /// The file is exported to the path by combining `root_dir` and the resource indentifier.
     ///
@@ -128,6 +169,62 @@ fn join_bundled_key(mut root_dir: PathBuf, key: &str) -> PathBuf {
     root_dir
 }
 
+fn cache_path(cache_dir: Option<&Path>, sha256: &H256) -> PathBuf {
+    let cache_dir = match cache_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => std::env::temp_dir().join("gw-builtin-binaries"),
+    };
+    cache_dir.join(format!("{:x}", sha256))
+}
+
+/// Reads the resource from the cache, or downloads it to the cache if it's
+/// not cached yet. The content must match the pinned checksum.
+fn fetch(url: &str, sha256: &H256, cache_dir: Option<&Path>) -> Result<Vec<u8>> {
+    let path = cache_path(cache_dir, sha256);
+    if let Ok(content) = fs::read(&path) {
+        if content_checksum(&content) == sha256.0 {
+            return Ok(content);
+        }
+        // Re-download corrupted cache.
+        fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+    }
+
+    let content = download(url).with_context(|| format!("download {}", url))?;
+    let checksum = content_checksum(&content);
+    if checksum != sha256.0 {
+        bail!(
+            "{} checksum mismatch, expected: {:#x}, actual: {:#x}",
+            url,
+            sha256,
+            H256(checksum)
+        );
+    }
+
+    // Write to a temp file first, so the cache is never partially written.
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, &content).with_context(|| format!("write {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, &path).with_context(|| format!("write {}", path.display()))?;
+    Ok(content)
+}
+
+fn download(url: &str) -> Result<Vec<u8>> {
+    // The blocking client can't be used in an async runtime, e.g. when backends
+    // are loaded on startup, so download in a dedicated thread.
+    let url = url.to_string();
+    thread::spawn(move || -> Result<Vec<u8>> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(DOWNLOAD_TIMEOUT)
+            .build()?;
+        let response = client.get(url).send()?.error_for_status()?;
+        Ok(response.bytes()?.to_vec())
+    })
+    .join()
+    .map_err(|_| anyhow!("download thread panicked"))?
+}
+
 pub fn content_checksum(content: &[u8]) -> [u8; 32] {
     Sha256::digest(content).into()
 }
@@ -136,3 +233,27 @@ pub fn file_checksum<P: AsRef<Path>>(path: P) -> Result<[u8; 32]> {
     let content = std::fs::read(path)?;
     Ok(content_checksum(&content))
 }
+
+#[cfg(test)]
+mod tests {
+    use ckb_fixed_hash::H256;
+
+    use crate::Resource;
+
+    #[test]
+    fn test_deserialize_url_resource() {
+        let resource: Resource = toml::from_str(
+            r#"
+            url = "https://example.com/generator"
+            sha256 = "0x0000000000000000000000000000000000000000000000000000000000000001"
+            "#,
+        )
+        .unwrap();
+        let mut sha256 = [0u8; 32];
+        sha256[31] = 1;
+        assert_eq!(
+            resource,
+            Resource::url("https://example.com/generator".to_string(), H256(sha256))
+        );
+    }
+}
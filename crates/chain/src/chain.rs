@@ -5,12 +5,12 @@ use gw_challenge::offchain::{verify_tx::TxWithContext, OffChainMockContext};
 use gw_common::{state::State, CKB_SUDT_SCRIPT_ARGS};
 use gw_config::ChainConfig;
 use gw_generator::{
-    generator::{ApplyBlockArgs, ApplyBlockResult},
+    generator::{ApplyBlockArgs, ApplyBlockResult, StateMismatch},
     traits::StateExt,
     types::vm::ChallengeContext,
     Generator,
 };
-use gw_jsonrpc_types::debugger::ReprMockTransaction;
+use gw_jsonrpc_types::{debugger::ReprMockTransaction, godwoken::BlockExecutionEvents};
 use gw_mem_pool::pool::MemPool;
 use gw_store::{
     chain_view::ChainView,
@@ -31,11 +31,19 @@ use gw_types::{
     },
     prelude::*,
 };
-use gw_utils::calc_finalizing_range;
-use std::{collections::HashSet, convert::TryFrom, sync::Arc, time::Instant};
-use tokio::sync::Mutex;
+use gw_utils::{calc_finalizing_range, find_finalized_upper_bound};
+use std::{collections::HashSet, convert::TryFrom, path::PathBuf, sync::Arc, time::Instant};
+use tokio::sync::{mpsc, Mutex};
+
+/// Max number of blocks whose state history is pruned with a new block.
+const PRUNE_BATCH_BLOCKS: u64 = 100;
 use tracing::instrument;
 
+use crate::{
+    execution_events::block_execution_events,
+    state_mismatch::{dump_state_mismatch, StateMismatchHaltError},
+};
+
 #[derive(Debug, Clone)]
 pub struct ChallengeCell {
     pub input: CellInput,
@@ -153,6 +161,11 @@ pub struct Chain {
     generator: Arc<Generator>,
     mem_pool: Option<Arc<Mutex<MemPool>>>,
     skipped_invalid_block_list: HashSet<H256>,
+    execution_events: Option<mpsc::Sender<BlockExecutionEvents>>,
+    state_mismatch_dump_path: Option<PathBuf>,
+    /// Block the chain halted on because of a post state mismatch.
+    state_mismatch_halt: Option<H256>,
+    state_history_retention_blocks: Option<u64>,
 }
 
 impl Chain {
@@ -190,6 +203,7 @@ impl Chain {
             .cloned()
             .map(H256::from)
             .collect();
+        let state_mismatch_halt = store.get_state_mismatch_halt();
         Ok(Chain {
             store,
             challenge_target: None,
@@ -200,9 +214,30 @@ impl Chain {
             rollup_type_script_hash,
             rollup_config,
             skipped_invalid_block_list,
+            execution_events: None,
+            state_mismatch_dump_path: None,
+            state_mismatch_halt,
+            state_history_retention_blocks: None,
         })
     }
 
+    /// Dump diagnostics to `path` when halting on a post state mismatch.
+    pub fn set_state_mismatch_dump_path(&mut self, path: PathBuf) {
+        self.state_mismatch_dump_path = Some(path);
+    }
+
+    /// Prune state history of finalized blocks older than `blocks` from the
+    /// tip, as new blocks are attached.
+    pub fn set_state_history_retention_blocks(&mut self, blocks: u64) {
+        self.state_history_retention_blocks = Some(blocks);
+    }
+
+    /// Send execution summaries of attached blocks to `sender`. Events are
+    /// dropped if the sink falls behind.
+    pub fn set_execution_events_sender(&mut self, sender: mpsc::Sender<BlockExecutionEvents>) {
+        self.execution_events = Some(sender);
+    }
+
     /// return local state
     pub fn local_state(&self) -> &LocalState {
         &self.local_state
@@ -238,19 +273,26 @@ impl Chain {
             .map(|t| t.block_hash().unpack())
     }
 
+    /// Build the context to cancel a challenge on `target`. Withdrawal
+    /// targets of bad blocks are supported too.
+    pub fn build_verify_context(
+        &self,
+        target: &ChallengeTarget,
+    ) -> Result<gw_challenge::types::VerifyContext> {
+        let mut db = self.store().begin_transaction();
+        gw_challenge::context::build_verify_context(Arc::clone(&self.generator), &mut db, target)
+    }
+
     pub fn dump_cancel_challenge_tx(
         &self,
         offchain_mock_context: &OffChainMockContext,
         target: ChallengeTarget,
     ) -> Result<ReprMockTransaction> {
-        let mut db = self.store().begin_transaction();
+        let db = self.store().begin_transaction();
 
-        let verify_context = gw_challenge::context::build_verify_context(
-            Arc::clone(&self.generator),
-            &mut db,
-            &target,
-        )
-        .with_context(|| "dump cancel challenge tx from chain")?;
+        let verify_context = self
+            .build_verify_context(&target)
+            .with_context(|| "dump cancel challenge tx from chain")?;
 
         let global_state = {
             let get_state = db.get_block_post_global_state(&target.block_hash().unpack())?;
@@ -318,6 +360,7 @@ impl Chain {
 
                     if let Some(ref target) = self.challenge_target {
                         db.insert_bad_block(&l2block, &global_state)?;
+                        db.insert_bad_block_withdrawals(&l2block.hash(), &withdrawals)?;
                         log::info!("insert bad block 0x{}", hex::encode(l2block.hash()));
 
                         let global_block_root: H256 = global_state.block().merkle_root().unpack();
@@ -337,7 +380,7 @@ impl Chain {
                         global_state.clone(),
                         deposit_info_vec,
                         deposit_asset_scripts,
-                        withdrawals,
+                        withdrawals.clone(),
                     )? {
                         db.rollback()?;
 
@@ -349,6 +392,7 @@ impl Chain {
                         );
 
                         db.insert_bad_block(&l2block, &global_state)?;
+                        db.insert_bad_block_withdrawals(&l2block.hash(), &withdrawals)?;
                         log::info!("insert bad block 0x{}", hex::encode(l2block.hash()));
 
                         let global_block_root: H256 = global_state.block().merkle_root().unpack();
@@ -371,6 +415,7 @@ impl Chain {
                             .build();
 
                         self.calculate_and_store_finalized_custodians(db, block_number)?;
+                        self.prune_state_history(db, &l2block)?;
                         db.set_last_submitted_block_number_hash(&nh.as_reader())?;
                         db.set_last_confirmed_block_number_hash(&nh.as_reader())?;
                         db.set_block_submit_tx(block_number, &transaction.as_reader())?;
@@ -571,6 +616,41 @@ impl Chain {
         Ok(())
     }
 
+    /// Prune state history of finalized blocks out of the retention window,
+    /// `block` is the new tip.
+    pub fn prune_state_history(&self, db: &mut StoreTransaction, block: &L2Block) -> Result<()> {
+        let retention_blocks = match self.state_history_retention_blocks {
+            Some(blocks) => blocks,
+            None => return Ok(()),
+        };
+        let block_number = block.raw().number().unpack();
+        if block_number <= retention_blocks {
+            return Ok(());
+        }
+        let finalized = find_finalized_upper_bound(
+            &self.rollup_config,
+            self.generator.fork_config(),
+            db,
+            block,
+        )
+        .context("find last finalized block to prune state history")?;
+        let to_block = (block_number - retention_blocks).min(finalized);
+        // Catch up with a limited number of blocks at a time.
+        let to_block = match db.get_pruned_block_number() {
+            Some(pruned) => to_block.min(pruned + PRUNE_BATCH_BLOCKS),
+            None => to_block.min(PRUNE_BATCH_BLOCKS - 1),
+        };
+        let pruned = db.prune_state_history(to_block)?;
+        if pruned > 0 {
+            log::debug!(
+                "pruned state history of {} blocks up to #{}",
+                pruned,
+                to_block
+            );
+        }
+        Ok(())
+    }
+
     /// Calculate and store the finalized_custodian_capacity for block block_number.
     ///
     /// Initialize by the block parent's finalized_custodian_capacity;
@@ -720,29 +800,11 @@ impl Chain {
                     let local_state_reverted_block_root: H256 =
                         local_state_global_state.reverted_block_root().unpack();
                     if local_state_reverted_block_root != prev_state_reverted_block_root {
-                        // Rewind reverted block smt
-                        let genesis_hash = db.get_block_hash_by_number(0)?.expect("genesis hash");
-                        let genesis_reverted_block_root: H256 = {
-                            let genesis_global_state = db
-                                .get_block_post_global_state(&genesis_hash)?
-                                .expect("genesis global state should exists");
-                            genesis_global_state.reverted_block_root().unpack()
-                        };
-                        let mut current_reverted_block_root = local_state_reverted_block_root;
-                        while current_reverted_block_root != prev_state_reverted_block_root {
-                            if current_reverted_block_root == genesis_reverted_block_root {
-                                break;
-                            }
-
-                            let reverted_block_hashes = db
-                                .get_reverted_block_hashes_by_root(&current_reverted_block_root)?
-                                .expect("reverted block hashes should exists")
-                                .block_hashes;
-
-                            db.rewind_reverted_block_smt(reverted_block_hashes)?;
-                            current_reverted_block_root = db.get_reverted_block_smt_root()?;
-                        }
-                        assert_eq!(current_reverted_block_root, prev_state_reverted_block_root);
+                        rewind_reverted_block_smt(
+                            db,
+                            local_state_reverted_block_root,
+                            prev_state_reverted_block_root,
+                        )?;
                     }
 
                     // Check current state
@@ -851,6 +913,108 @@ impl Chain {
         Ok(())
     }
 
+    /// Rewind bad blocks and challenges after the last valid tip, e.g. when
+    /// the L1 transactions submitting them are reorged out. Same as the
+    /// rewind-to-last-valid-block subcommand.
+    ///
+    /// Returns false if the local state is already the last valid tip.
+    pub fn rewind_to_last_valid_tip(&mut self, db: &mut StoreTransaction) -> Result<bool> {
+        let last_valid_tip_block_hash = db.get_last_valid_tip_block_hash()?;
+        let prev_global_state = db
+            .get_block_post_global_state(&last_valid_tip_block_hash)?
+            .context("last valid tip post global state not found")?;
+        if self.challenge_target.is_none()
+            && self.local_state.tip.hash() == last_valid_tip_block_hash
+            && self.local_state.last_global_state.as_slice() == prev_global_state.as_slice()
+        {
+            return Ok(false);
+        }
+
+        let rewind_to_last_valid_tip = RevertedL1Action {
+            prev_global_state,
+            context: RevertL1ActionContext::RewindToLastValidTip,
+        };
+        self.revert_l1action(db, rewind_to_last_valid_tip)?;
+        Ok(true)
+    }
+
+    /// Revert valid blocks after `revert_to`, e.g. blocks whose submissions
+    /// are reverted on L1.
+    ///
+    /// Same as reverting `SubmitValidBlock` actions from the tip one by one,
+    /// but the state tree is rolled back once for all blocks, which is much
+    /// faster for long reverts.
+    pub fn revert_valid_blocks(
+        &mut self,
+        mut db: &mut StoreTransaction,
+        revert_to: u64,
+    ) -> Result<()> {
+        assert!(
+            self.challenge_target.is_none(),
+            "rewind to last valid tip first"
+        );
+        let last_valid_tip = db.get_last_valid_tip_block()?;
+        assert_eq!(
+            self.local_state.tip.hash(),
+            last_valid_tip.hash(),
+            "rewind to last valid tip first"
+        );
+        let tip_number: u64 = last_valid_tip.raw().number().unpack();
+        if tip_number <= revert_to {
+            return Ok(());
+        }
+        log::info!("reverting L2 blocks {}..={}", revert_to + 1, tip_number);
+
+        let mut first_block = last_valid_tip;
+        // detach blocks from DB
+        for block_number in (revert_to + 1..=tip_number).rev() {
+            let block_hash = db
+                .get_block_hash_by_number(block_number)?
+                .context("get reverted block hash")?;
+            let block = db.get_block(&block_hash)?.context("get reverted block")?;
+            db.detach_block(&block)?;
+            first_block = block;
+        }
+        // detach blocks state from state tree
+        {
+            let mut tree = BlockStateDB::from_store(&mut db, RWConfig::detach_block())?;
+            tree.detach_blocks_state(revert_to + 1, tip_number)?;
+        }
+
+        let local_tip = db.get_tip_block()?;
+        let parent_block_hash: H256 = first_block.raw().parent_block_hash().unpack();
+        assert_eq!(parent_block_hash, local_tip.hash());
+        let prev_global_state = db
+            .get_block_post_global_state(&parent_block_hash)?
+            .context("get parent global state")?;
+
+        // Rewind reverted block smt
+        let prev_state_reverted_block_root: H256 = prev_global_state.reverted_block_root().unpack();
+        let local_state_reverted_block_root: H256 = (self.local_state.last_global_state)
+            .reverted_block_root()
+            .unpack();
+        if local_state_reverted_block_root != prev_state_reverted_block_root {
+            rewind_reverted_block_smt(
+                db,
+                local_state_reverted_block_root,
+                prev_state_reverted_block_root,
+            )?;
+        }
+
+        // Check current state
+        let expected_state = first_block.raw().prev_account();
+        let tree = BlockStateDB::from_store(&mut db, RWConfig::readonly())?;
+        let expected_root: H256 = expected_state.merkle_root().unpack();
+        let expected_count: u32 = expected_state.count().unpack();
+        assert_eq!(tree.calculate_root()?, expected_root);
+        assert_eq!(tree.get_account_count()?, expected_count);
+
+        self.last_sync_event = SyncEvent::Success;
+        self.local_state.last_global_state = prev_global_state;
+        self.local_state.tip = local_tip;
+        Ok(())
+    }
+
     /// Sync chain from layer1
     pub async fn sync(&mut self, param: SyncParam) -> Result<()> {
         let mut db = self.store.begin_transaction();
@@ -998,6 +1162,9 @@ impl Chain {
         deposit_asset_scripts: HashSet<Script>,
         withdrawals: Vec<WithdrawalRequestExtra>,
     ) -> Result<Option<ChallengeTarget>> {
+        if let Some(block_hash) = self.state_mismatch_halt {
+            bail!(StateMismatchHaltError(block_hash.into()));
+        }
         let tip_number: u64 = self.local_state.tip.raw().number().unpack();
         let tip_block_hash = self.local_state.tip.raw().hash();
         let block_number: u64 = l2block.raw().number().unpack();
@@ -1063,6 +1230,9 @@ impl Chain {
                 log::warn!("verify #{} state transition error {}", block_number, error);
                 return Ok(Some(target));
             }
+            ApplyBlockResult::StateMismatch(mismatch) => {
+                return Err(self.halt_on_state_mismatch(&l2block, &global_state, &mismatch));
+            }
             ApplyBlockResult::Error(err) => return Err(err),
         };
 
@@ -1070,6 +1240,8 @@ impl Chain {
         let deposit_info_vec_len = deposit_info_vec.len() as u64;
         let withdrawals_len = withdrawals.len() as u64;
         let tx_receipts_len = tx_receipts.len() as u64;
+        let execution_events = (self.execution_events.as_ref())
+            .map(|_| block_execution_events(&l2block, &prev_txs_state, &tx_receipts));
         db.insert_block(
             l2block.clone(),
             global_state.clone(),
@@ -1092,10 +1264,58 @@ impl Chain {
         gw_metrics::chain().withdrawals.inc_by(withdrawals_len);
         gw_metrics::chain().transactions.inc_by(tx_receipts_len);
 
+        if let (Some(sender), Some(events)) = (&self.execution_events, execution_events) {
+            if let Err(err) = sender.try_send(events) {
+                log::warn!(
+                    "[execution events] drop events of block #{}: {}",
+                    block_number,
+                    err
+                );
+            }
+        }
+
         self.local_state.tip = l2block;
         self.local_state.last_global_state = global_state;
         Ok(None)
     }
+
+    /// Dump diagnostics and stop processing blocks, also after restarts until
+    /// the halt is overridden.
+    fn halt_on_state_mismatch(
+        &mut self,
+        l2block: &L2Block,
+        global_state: &GlobalState,
+        mismatch: &StateMismatch,
+    ) -> anyhow::Error {
+        let block_number: u64 = l2block.raw().number().unpack();
+        let block_hash = l2block.hash();
+        let expected_count: u32 = mismatch.expected.count().unpack();
+        let actual_count: u32 = mismatch.actual.count().unpack();
+        log::error!(
+            "post state mismatch of block #{} 0x{}: expected root 0x{} count {}, actual root 0x{} count {}",
+            block_number,
+            hex::encode(block_hash),
+            hex::encode(mismatch.expected.merkle_root().as_slice()),
+            expected_count,
+            hex::encode(mismatch.actual.merkle_root().as_slice()),
+            actual_count,
+        );
+        if let Some(ref path) = self.state_mismatch_dump_path {
+            match dump_state_mismatch(path, l2block, global_state, mismatch) {
+                Ok(dir) => log::error!("state mismatch diagnostics dumped to {}", dir.display()),
+                Err(err) => log::error!("failed to dump state mismatch diagnostics: {:#}", err),
+            }
+        }
+        self.state_mismatch_halt = Some(block_hash);
+        let mut db = self.store.begin_transaction();
+        if let Err(err) = db
+            .set_state_mismatch_halt(&block_hash)
+            .and_then(|_| db.commit())
+        {
+            log::error!("failed to persist state mismatch halt: {:#}", err);
+        }
+        StateMismatchHaltError(block_hash.into()).into()
+    }
 }
 
 fn parse_global_state(tx: &Transaction, rollup_id: &[u8; 32]) -> Result<GlobalState> {
@@ -1158,3 +1378,34 @@ fn package_bad_blocks(db: &StoreTransaction, start_block_hash: &H256) -> Result<
     );
     Ok(blocks)
 }
+
+/// Rewind reverted block smt from `current_root` to `target_root`.
+fn rewind_reverted_block_smt(
+    db: &mut StoreTransaction,
+    current_root: H256,
+    target_root: H256,
+) -> Result<()> {
+    let genesis_hash = db.get_block_hash_by_number(0)?.expect("genesis hash");
+    let genesis_reverted_block_root: H256 = {
+        let genesis_global_state = db
+            .get_block_post_global_state(&genesis_hash)?
+            .expect("genesis global state should exists");
+        genesis_global_state.reverted_block_root().unpack()
+    };
+    let mut current_reverted_block_root = current_root;
+    while current_reverted_block_root != target_root {
+        if current_reverted_block_root == genesis_reverted_block_root {
+            break;
+        }
+
+        let reverted_block_hashes = db
+            .get_reverted_block_hashes_by_root(&current_reverted_block_root)?
+            .expect("reverted block hashes should exists")
+            .block_hashes;
+
+        db.rewind_reverted_block_smt(reverted_block_hashes)?;
+        current_reverted_block_root = db.get_reverted_block_smt_root()?;
+    }
+    assert_eq!(current_reverted_block_root, target_root);
+    Ok(())
+}
@@ -0,0 +1,134 @@
+//! Execution summaries of attached blocks for the execution event sink.
+
+use ckb_fixed_hash::H160;
+use gw_jsonrpc_types::godwoken::{
+    BlockExecutionEvents, SudtMovement, SudtMovementKind, TxExecutionSummary,
+};
+use gw_types::{
+    packed::{AccountMerkleState, L2Block, TxReceipt},
+    prelude::*,
+};
+use gw_utils::script_log::{parse_log, GwLog};
+
+pub fn block_execution_events(
+    block: &L2Block,
+    prev_txs_state: &AccountMerkleState,
+    tx_receipts: &[TxReceipt],
+) -> BlockExecutionEvents {
+    let mut prev_count: u32 = prev_txs_state.count().unpack();
+    let transactions = (block.transactions().into_iter())
+        .zip(tx_receipts)
+        .map(|(tx, receipt)| {
+            let raw = tx.raw();
+            let count: u32 = receipt.post_state().count().unpack();
+            let mut summary = TxExecutionSummary {
+                tx_hash: tx.hash().into(),
+                from_id: raw.from_id().unpack(),
+                to_id: raw.to_id().unpack(),
+                nonce: raw.nonce().unpack(),
+                exit_code: receipt.exit_code().into(),
+                gas_used: None,
+                status_code: None,
+                created_accounts: (prev_count..count).map(Into::into).collect(),
+                created_address: None,
+                sudt_movements: Vec::new(),
+            };
+            prev_count = count;
+
+            for item in receipt.logs() {
+                let log = match parse_log(&item) {
+                    Ok(log) => log,
+                    Err(err) => {
+                        log::debug!("[execution events] skip log: {}", err);
+                        continue;
+                    }
+                };
+                match log {
+                    GwLog::SudtTransfer {
+                        sudt_id,
+                        from_address,
+                        to_address,
+                        amount,
+                    } => summary.sudt_movements.push(SudtMovement {
+                        kind: SudtMovementKind::Transfer,
+                        sudt_id,
+                        from: from_address.into(),
+                        to: to_address.into(),
+                        amount,
+                    }),
+                    GwLog::SudtPayFee {
+                        sudt_id,
+                        from_address,
+                        block_producer_address,
+                        amount,
+                    } => summary.sudt_movements.push(SudtMovement {
+                        kind: SudtMovementKind::PayFee,
+                        sudt_id,
+                        from: from_address.into(),
+                        to: block_producer_address.into(),
+                        amount,
+                    }),
+                    GwLog::PolyjuiceSystem {
+                        gas_used,
+                        created_address,
+                        status_code,
+                        ..
+                    } => {
+                        summary.gas_used = Some(gas_used);
+                        summary.status_code = Some(status_code);
+                        if created_address != [0u8; 20] {
+                            summary.created_address = Some(H160(created_address));
+                        }
+                    }
+                    GwLog::PolyjuiceUser { .. } => {}
+                }
+            }
+            summary
+        })
+        .collect();
+
+    BlockExecutionEvents {
+        block_number: block.raw().number().unpack(),
+        block_hash: block.hash().into(),
+        transactions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gw_types::packed::{L2Transaction, RawL2Block, RawL2Transaction};
+
+    use super::*;
+
+    fn receipt(count: u32) -> TxReceipt {
+        let post_state = AccountMerkleState::new_builder()
+            .count(count.pack())
+            .build();
+        TxReceipt::new_builder().post_state(post_state).build()
+    }
+
+    #[test]
+    fn test_created_accounts() {
+        let tx = |nonce: u32| {
+            let raw = RawL2Transaction::new_builder()
+                .from_id(2u32.pack())
+                .nonce(nonce.pack())
+                .build();
+            L2Transaction::new_builder().raw(raw).build()
+        };
+        let block = L2Block::new_builder()
+            .raw(RawL2Block::new_builder().number(3u64.pack()).build())
+            .transactions(vec![tx(0), tx(1)].pack())
+            .build();
+        let prev = AccountMerkleState::new_builder().count(5u32.pack()).build();
+
+        let events = block_execution_events(&block, &prev, &[receipt(7), receipt(7)]);
+        assert_eq!(events.block_number, 3);
+        let created: Vec<u32> = (events.transactions[0].created_accounts.iter())
+            .map(|id| id.value())
+            .collect();
+        assert_eq!(created, vec![5, 6]);
+        assert!(events.transactions[1].created_accounts.is_empty());
+        assert_eq!(events.transactions[1].nonce, 1);
+    }
+}
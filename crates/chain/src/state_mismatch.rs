@@ -0,0 +1,120 @@
+//! Diagnostics of blocks whose post state root mismatches the locally
+//! computed one.
+//!
+//! The node halts on such a block instead of panicking, and dumps the block,
+//! state written by each withdrawal, the deposits and each transaction, and
+//! both post states to a directory, so that the state divergence can be
+//! debugged offline.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use ckb_fixed_hash::H256;
+use gw_generator::generator::{StateDiff, StateDiffSource, StateMismatch};
+use gw_jsonrpc_types::godwoken::{AccountMerkleState, GlobalState, L2BlockView};
+use gw_types::{packed, prelude::*};
+use serde::Serialize;
+
+/// The node refuses to process blocks after a state mismatch.
+#[derive(thiserror::Error, Debug)]
+#[error("halted on post state mismatch of block {0:#x}, restart with --override-state-mismatch-halt to continue")]
+pub struct StateMismatchHaltError(pub H256);
+
+#[derive(Serialize)]
+struct BlockDump {
+    block: L2BlockView,
+    global_state: GlobalState,
+}
+
+#[derive(Serialize)]
+struct RootsDump {
+    prev_account: AccountMerkleState,
+    /// Post account state in the block.
+    expected_post_account: AccountMerkleState,
+    /// Locally computed post account state.
+    actual_post_account: AccountMerkleState,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum SourceDump {
+    Withdrawal,
+    Deposits,
+    Transaction,
+}
+
+#[derive(Serialize)]
+struct StateDiffDump {
+    source: SourceDump,
+    hash: Option<H256>,
+    post_account: AccountMerkleState,
+    writes: Vec<WriteDump>,
+}
+
+#[derive(Serialize)]
+struct WriteDump {
+    key: H256,
+    value: H256,
+}
+
+impl From<&StateDiff> for StateDiffDump {
+    fn from(diff: &StateDiff) -> Self {
+        let (source, hash) = match diff.source {
+            StateDiffSource::Withdrawal(hash) => (SourceDump::Withdrawal, Some(H256(hash))),
+            StateDiffSource::Deposits => (SourceDump::Deposits, None),
+            StateDiffSource::Transaction(hash) => (SourceDump::Transaction, Some(H256(hash))),
+        };
+        Self {
+            source,
+            hash,
+            post_account: diff.post_state.clone().into(),
+            writes: (diff.writes.iter())
+                .map(|(k, v)| WriteDump {
+                    key: H256(*k),
+                    value: H256(*v),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Write `block.json`, `roots.json` and `state_diffs.json` to a directory
+/// named by the block number and hash under `dir`. Returns the directory.
+pub fn dump_state_mismatch(
+    dir: &Path,
+    block: &packed::L2Block,
+    global_state: &packed::GlobalState,
+    mismatch: &StateMismatch,
+) -> Result<PathBuf> {
+    let number: u64 = block.raw().number().unpack();
+    let dir = dir.join(format!("{}-{}", number, hex::encode(block.hash())));
+    fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+
+    write_json(
+        &dir.join("block.json"),
+        &BlockDump {
+            block: block.clone().into(),
+            global_state: global_state.clone().into(),
+        },
+    )?;
+    write_json(
+        &dir.join("roots.json"),
+        &RootsDump {
+            prev_account: block.raw().prev_account().into(),
+            expected_post_account: mismatch.expected.clone().into(),
+            actual_post_account: mismatch.actual.clone().into(),
+        },
+    )?;
+    let diffs: Vec<StateDiffDump> = mismatch.state_diffs.iter().map(Into::into).collect();
+    write_json(&dir.join("state_diffs.json"), &diffs)?;
+
+    Ok(dir)
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let json = serde_json::to_vec_pretty(value)?;
+    fs::write(path, json).with_context(|| format!("write {}", path.display()))
+}
@@ -135,9 +135,13 @@ fn build_verify_withdrawal_witness(
     block_hash: H256,
     withdrawal_index: u32,
 ) -> Result<VerifyContext> {
-    let block = db
-        .get_block(&block_hash)?
-        .ok_or_else(|| anyhow!("block not found"))?;
+    // Withdrawals of bad blocks are saved too.
+    let block = match db.get_block(&block_hash)? {
+        Some(block) => block,
+        None => db
+            .get_bad_block(&block_hash)
+            .ok_or_else(|| anyhow!("block not found"))?,
+    };
 
     // Build withdrawal proof
     let mut target = None;
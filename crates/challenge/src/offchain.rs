@@ -575,12 +575,10 @@ async fn resolve_cell_deps(
 
     let mut resolved_deps = Vec::with_capacity(flatten_deps.len());
     for dep in flatten_deps {
-        let dep_cell = {
-            let query = rpc_client.get_cell(dep.out_point()).await?;
-            query
-                .and_then(|q| q.cell)
-                .ok_or_else(|| anyhow!("can't find dep cell"))?
-        };
+        let dep_cell = rpc_client
+            .get_cell_content(dep.out_point())
+            .await?
+            .ok_or_else(|| anyhow!("can't find dep cell"))?;
         resolved_deps.push(dep_cell.into());
     }
 
@@ -594,12 +592,10 @@ async fn resolve_dep_group(rpc_client: &RPCClient, dep: &CellDep) -> Result<Vec<
     }
 
     // parse dep group
-    let cell = {
-        let query = rpc_client.get_cell(dep.out_point()).await?;
-        query
-            .and_then(|q| q.cell)
-            .ok_or_else(|| anyhow!("can't find dep group cell"))?
-    };
+    let cell = rpc_client
+        .get_cell_content(dep.out_point())
+        .await?
+        .ok_or_else(|| anyhow!("can't find dep group cell"))?;
 
     let out_points =
         OutPointVec::from_slice(&cell.data).map_err(|_| anyhow!("invalid dep group"))?;
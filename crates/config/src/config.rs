@@ -13,7 +13,10 @@ use gw_jsonrpc_types::{
 use pid::Pid;
 use serde::{Deserialize, Serialize};
 
-use crate::{consensus::Consensus, fork_config::BackendForkConfig};
+use crate::{
+    consensus::Consensus,
+    fork_config::{BackendForkConfig, ForkConfig},
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
 #[serde(rename_all = "lowercase")]
@@ -29,6 +32,11 @@ pub struct Config {
     pub liveness_duration_secs: Option<u64>,
     #[serde(default)]
     pub trace_generator_state: bool,
+    /// Profile cycles of txs per syscall and per backend, and dump them as
+    /// `<block_number>.json` in this directory for each applied block.
+    /// Disabled if None.
+    #[serde(default)]
+    pub cycles_profile_dir: Option<PathBuf>,
     #[serde(default)]
     pub contract_log_config: ContractLogConfig,
     pub consensus: Consensus,
@@ -51,9 +59,142 @@ pub struct Config {
     pub p2p_network_config: Option<P2PNetworkConfig>,
     #[serde(default)]
     pub sync_server: SyncServerConfig,
+    #[serde(default)]
+    pub sync_client: SyncClientConfig,
     /// Gasless tx support is enabled when this config presents.
     #[serde(default)]
     pub gasless_tx_support: Option<GaslessTxSupportConfig>,
+    #[serde(default)]
+    pub components: ComponentsConfig,
+    /// Stream execution summaries of attached blocks to an external sink.
+    #[serde(default)]
+    pub execution_events: Option<ExecutionEventsConfig>,
+    /// Read-only nodes produce a block from the synced mem block, without
+    /// signing or submitting it, whenever a block of the full node arrives,
+    /// and report divergence from the full node's block.
+    #[serde(default)]
+    pub shadow_production: bool,
+    /// Read-only nodes with an empty store download the state at a recent
+    /// block from the p2p peer, instead of syncing all blocks from genesis.
+    #[serde(default)]
+    pub p2p_state_sync: bool,
+    /// Verifiers of account locks besides the built-in eth lock, e.g. new
+    /// wallet lock types. Lock type hashes must be in the rollup config's
+    /// `allowed_eoa_type_hashes`.
+    #[serde(default)]
+    pub account_locks: Vec<AccountLockConfig>,
+    /// Restrict deployers of Polyjuice contracts. Open by default.
+    #[serde(default)]
+    pub deployment_policy: DeploymentPolicyConfig,
+}
+
+/// Deployers of Polyjuice contracts, checked when verifying txs of mem pool
+/// and RPC. It isn't a consensus rule, blocks of other nodes are not checked.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeploymentPolicyConfig {
+    pub mode: DeploymentPolicyMode,
+    /// Registry addresses of deployers allowed by `allowlist` mode, or denied
+    /// by `denylist` mode.
+    pub deployers: Vec<RegistryAddressConfig>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentPolicyMode {
+    /// Anyone can deploy contracts.
+    #[default]
+    Open,
+    /// Only `deployers` can deploy contracts.
+    Allowlist,
+    /// Anyone except `deployers` can deploy contracts.
+    Denylist,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountLockConfig {
+    pub lock_type_hash: H256,
+    pub verifier: AccountLockVerifierConfig,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AccountLockVerifierConfig {
+    /// A built-in lock algorithm.
+    Builtin { algorithm: BuiltinLockAlgorithm },
+    /// An external verification service called with JSON-RPC over HTTP,
+    /// see `gw_generator::account_lock_manage::external` for the methods.
+    External {
+        url: String,
+        /// Time limit of a call, including the wait for a free slot.
+        #[serde(default = "default_external_lock_timeout_ms")]
+        timeout_ms: u64,
+        /// Calls in flight at most, others wait.
+        #[serde(default = "default_external_lock_max_concurrent_calls")]
+        max_concurrent_calls: usize,
+    },
+}
+
+const fn default_external_lock_timeout_ms() -> u64 {
+    3000
+}
+
+const fn default_external_lock_max_concurrent_calls() -> usize {
+    16
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinLockAlgorithm {
+    Secp256k1Eth,
+    /// Accepts any signature. Only available in debug builds.
+    AlwaysSuccess,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExecutionEventsConfig {
+    pub sink: ExecutionEventSinkConfig,
+    /// Maximum number of blocks waiting for the sink, events of new blocks
+    /// are dropped when the queue is full. Default is 1000.
+    #[serde(default = "default_execution_events_queue_size")]
+    pub queue_size: usize,
+}
+
+const fn default_execution_events_queue_size() -> usize {
+    1000
+}
+
+/// Only HTTP sinks are supported, Kafka or NATS can be fed through an HTTP
+/// bridge.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExecutionEventSinkConfig {
+    /// POST events of each block as JSON to `url`.
+    Http { url: String },
+}
+
+/// Subsystems to run. Unset components are enabled if the node mode and the
+/// rest of the config support them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ComponentsConfig {
+    /// JSONRPC server.
+    pub rpc: Option<bool>,
+    /// P2P network, requires `p2p_network_config`.
+    pub p2p: Option<bool>,
+    /// Block sync server publishing blocks to readonly nodes and indexers
+    /// over p2p. Not supported in readonly mode.
+    pub indexer_publisher: Option<bool>,
+    /// `/metrics` endpoint of the JSONRPC server.
+    pub metrics: Option<bool>,
+    /// Produce, submit and confirm blocks, and unlock finalized withdrawals.
+    /// Not supported in readonly mode.
+    pub block_producer: Option<bool>,
+    /// Challenge invalid blocks and clean challenge cells. Not supported in
+    /// readonly mode.
+    pub challenger: Option<bool>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
@@ -62,6 +203,7 @@ pub enum RPCMethods {
     PProf,
     Test,
     Debug,
+    Admin,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -71,6 +213,34 @@ pub struct RPCServerConfig {
     #[serde(default)]
     pub enable_methods: HashSet<RPCMethods>,
     pub send_tx_rate_limit: Option<RPCRateLimit>,
+    /// Origins accepted by `gw_submit_l2transaction`, e.g. RPC providers
+    /// forwarding txs. Fees of packaged txs are credited to their origins.
+    /// Empty means origins are not accepted.
+    #[serde(default)]
+    pub fee_rebate_origins: Vec<FeeRebateOrigin>,
+    /// Remember the reasons of this many recently dropped requests, for
+    /// `gw_get_dropped_request`. 0 disables it.
+    #[serde(default)]
+    pub dropped_requests_cache_size: usize,
+    /// Record packaging decisions of this many recent mem blocks, for
+    /// `debug_explain_packaging`. Requires debug methods. 0 disables it.
+    #[serde(default)]
+    pub packaging_records_size: usize,
+    /// Cache results of this many recent `gw_execute_raw_l2transaction`
+    /// (`eth_call`) requests. Results at the mem block are invalidated when
+    /// the mem pool state changes. 0 disables it.
+    #[serde(default)]
+    pub execute_cache_size: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeeRebateOrigin {
+    /// Id of the origin, fee rebates are reported by it.
+    pub origin: String,
+    /// Secret shared with the origin, submitted as `origin_token` to
+    /// authenticate it.
+    pub token: String,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -81,6 +251,10 @@ pub struct RPCClientConfig {
     /// If this is None we use CKB builtin indexer RPC instead.
     pub indexer_url: Option<String>,
     pub ckb_url: String,
+    /// Persist content of frequently fetched L1 cells, e.g. the rollup config
+    /// cell and dep groups, in this directory. Cached in memory only if None.
+    #[serde(default)]
+    pub cell_cache_dir: Option<PathBuf>,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -109,6 +283,10 @@ pub struct WalletConfig {
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ChallengerConfig {
     pub rewards_receiver_lock: Script,
+    /// Build and dump challenge txs into `debug.debug_tx_dump_path` without
+    /// sending them.
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -150,6 +328,17 @@ pub struct BlockProducerConfig {
     pub challenger_config: ChallengerConfig,
     pub wallet_config: Option<WalletConfig>,
     pub withdrawal_unlocker_wallet_config: Option<WalletConfig>,
+    pub withdrawal_unlocker: WithdrawalUnlockerConfig,
+    /// Extra wallets paying fees of submission transactions, in rotation with
+    /// `wallet_config`. `wallet_config` still owns the stake cell.
+    pub submitter_wallets: Vec<WalletConfig>,
+    /// Estimate fee rates of submission and withdrawal unlock txs. Submission
+    /// txs use the PSC fee rate settings, unlock txs use `fee_rate` if None.
+    pub fee_estimator: Option<FeeEstimatorConfig>,
+    /// Merge more finalized custodian cells into submission txs when there
+    /// are many of them. Checked by the cleaner, so requires the challenger
+    /// component.
+    pub custodian_consolidation: Option<CustodianConsolidationConfig>,
 }
 
 impl Default for BlockProducerConfig {
@@ -162,6 +351,10 @@ impl Default for BlockProducerConfig {
             challenger_config: ChallengerConfig::default(),
             wallet_config: None,
             withdrawal_unlocker_wallet_config: None,
+            withdrawal_unlocker: WithdrawalUnlockerConfig::default(),
+            submitter_wallets: Vec::new(),
+            fee_estimator: None,
+            custodian_consolidation: None,
         }
     }
 }
@@ -183,6 +376,17 @@ pub struct PscConfig {
     pub submitted_limit: u64,
     /// Minimum delay between blocks. Default is 8 seconds.
     pub block_interval_secs: u64,
+    /// Produce before `block_interval_secs` elapses once the mem block is this
+    /// full, in percent of its txs, withdrawals or cycles limit.
+    pub early_block_fullness_percent: Option<u64>,
+    /// Keep delaying empty blocks up to this interval, to save L1 fees.
+    /// Default is `block_interval_secs`.
+    pub max_empty_block_interval_secs: Option<u64>,
+    /// Produce an empty heartbeat block after this long without activity,
+    /// for downstream systems that need regular blocks, e.g. finality timers
+    /// and timestamp oracles. Takes precedence over
+    /// `max_empty_block_interval_secs`.
+    pub heartbeat_block_interval_secs: Option<u64>,
     pub min_fee_rate: u64,
     pub max_fee_rate: u64,
     pub fee_rate_pid: Option<Pid<f64>>,
@@ -190,6 +394,12 @@ pub struct PscConfig {
     /// Reset submission txs if a tx cannot be confirmed after the specified duration.
     /// It's not very reliable. Don't use in prod.
     pub confirm_timeout_secs: Option<u64>,
+    /// Only submit blocks in the operator's L1 submission windows. Blocks are
+    /// produced at any time if None.
+    pub submission_window: Option<SubmissionWindowConfig>,
+    /// Replace submission txs that are not confirmed in time with txs paying
+    /// a higher fee rate. Overrides `confirm_timeout_secs`.
+    pub fee_bump: Option<FeeBumpConfig>,
 }
 
 impl Default for PscConfig {
@@ -198,15 +408,149 @@ impl Default for PscConfig {
             local_limit: 2,
             submitted_limit: 10,
             block_interval_secs: 8,
+            early_block_fullness_percent: None,
+            max_empty_block_interval_secs: None,
+            heartbeat_block_interval_secs: None,
             fee_rate_pid: None,
             min_fee_rate: 1000,
             max_fee_rate: 1100,
             fee_rate_pid_interval_secs: 10,
             confirm_timeout_secs: None,
+            submission_window: None,
+            fee_bump: None,
         }
     }
 }
 
+/// A repeating L1 submission schedule, e.g. the operator's PoA round.
+///
+/// Rounds start at unix timestamps that are multiples of `round_secs`. The
+/// window of a round opens `offset_secs` after the round starts and lasts
+/// `duration_secs`. One block is produced for each window.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmissionWindowConfig {
+    pub round_secs: u64,
+    #[serde(default)]
+    pub offset_secs: u64,
+    pub duration_secs: u64,
+    /// Start producing the block this long before the window opens, so that
+    /// it's ready to be signed and sent when the window opens. Default is 2000.
+    #[serde(default = "default_produce_lead_millis")]
+    pub produce_lead_millis: u64,
+    /// Don't produce or send a block if less than this is left in the window,
+    /// wait for the next window instead. Default is 1000.
+    #[serde(default = "default_send_margin_millis")]
+    pub send_margin_millis: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeBumpConfig {
+    /// Bump the fee rate if the next submitted block is not confirmed in this
+    /// duration.
+    pub timeout_secs: u64,
+    /// Increase of the fee rate in percent. Default is 50.
+    #[serde(default = "default_fee_bump_percent")]
+    pub percent: u64,
+    /// Minimum increase of the fee rate in shannons/KB. CKB rejects
+    /// replacements paying less than `min_rbf_rate` more. Default is 1500,
+    /// the CKB default.
+    #[serde(default = "default_fee_bump_min_increase")]
+    pub min_increase: u64,
+    /// Stop bumping at this fee rate.
+    pub max_fee_rate: u64,
+}
+
+/// L1 fee rate estimation, in shannons/KB.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeEstimatorConfig {
+    #[serde(flatten)]
+    pub strategy: FeeEstimatorStrategy,
+    /// Estimates are raised to at least this fee rate.
+    pub min_fee_rate: Option<u64>,
+    /// Estimates are capped at this fee rate.
+    pub max_fee_rate: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum FeeEstimatorStrategy {
+    /// Always `BlockProducerConfig::fee_rate`.
+    Static,
+    /// Median fee rate of recent L1 txs, as reported by CKB
+    /// `get_fee_rate_statics`. Falls back to `BlockProducerConfig::fee_rate`
+    /// when CKB has no statistics yet.
+    Median {
+        /// Number of recent blocks to sample. CKB samples 21 blocks if None.
+        target_blocks: Option<u64>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustodianConsolidationConfig {
+    /// Interval between checks of finalized custodian cells. Default is 600
+    /// seconds.
+    pub interval_secs: u64,
+    /// Consolidate once there are at least this many mergeable finalized
+    /// custodian cells. Default is 100.
+    pub min_custodians: usize,
+    /// Maximum number of custodian cells merged by one submission tx, bounds
+    /// the size and fee of the tx. Default is 200.
+    pub max_custodians: usize,
+}
+
+impl Default for CustodianConsolidationConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 600,
+            min_custodians: 100,
+            max_custodians: 200,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WithdrawalUnlockerConfig {
+    /// Only unlock finalized withdrawals to these owner locks. Unlock
+    /// withdrawals of all owners if empty.
+    pub owner_lock_hashes: Vec<H256>,
+    /// Maximum number of withdrawals unlocked by one tx. Default is 100.
+    pub max_withdrawals_per_tx: usize,
+    /// Initial delay before retrying after a failed unlock, doubled on each
+    /// consecutive failure up to `max_retry_delay_secs`. Default is 6 seconds.
+    pub retry_delay_secs: u64,
+    /// Default is 600 seconds.
+    pub max_retry_delay_secs: u64,
+}
+
+impl Default for WithdrawalUnlockerConfig {
+    fn default() -> Self {
+        Self {
+            owner_lock_hashes: Vec::new(),
+            max_withdrawals_per_tx: 100,
+            retry_delay_secs: 6,
+            max_retry_delay_secs: 600,
+        }
+    }
+}
+
+fn default_fee_bump_percent() -> u64 {
+    50
+}
+
+fn default_fee_bump_min_increase() -> u64 {
+    1500
+}
+
+fn default_produce_lead_millis() -> u64 {
+    2000
+}
+
+fn default_send_margin_millis() -> u64 {
+    1000
+}
+
 #[test]
 fn test_psc_config_optional() {
     #[derive(Deserialize)]
@@ -231,6 +575,10 @@ pub struct DebugConfig {
     pub debug_tx_dump_path: PathBuf,
     #[serde(default = "default_enable_debug_rpc")]
     pub enable_debug_rpc: bool,
+    /// Directory to dump the block, state diffs and post states to when the
+    /// node halts on a post state root mismatch
+    #[serde(default = "default_state_mismatch_dump_path")]
+    pub state_mismatch_dump_path: PathBuf,
 }
 
 // Field default value for backward config file compitability
@@ -238,6 +586,10 @@ fn default_enable_debug_rpc() -> bool {
     false
 }
 
+fn default_state_mismatch_dump_path() -> PathBuf {
+    "state-mismatch-dump".into()
+}
+
 impl Default for DebugConfig {
     fn default() -> Self {
         const EXPECTED_TX_UPPER_BOUND_CYCLES: u64 = 350000000u64;
@@ -248,6 +600,7 @@ impl Default for DebugConfig {
             output_l1_tx_cycles: true,
             expected_l1_tx_upper_bound_cycles: EXPECTED_TX_UPPER_BOUND_CYCLES,
             enable_debug_rpc: false,
+            state_mismatch_dump_path: default_state_mismatch_dump_path(),
         }
     }
 }
@@ -276,13 +629,107 @@ impl Default for OffChainValidatorConfig {
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct P2PNetworkConfig {
-    /// Multiaddr listen address, e.g. /ip4/1.2.3.4/tcp/443
+    /// Multiaddr listen address, e.g. /ip4/1.2.3.4/tcp/443. Nodes that can't
+    /// listen, e.g. behind NAT, only dial.
     pub listen: Option<String>,
     /// Multiaddr dial addresses, e.g. /ip4/1.2.3.4/tcp/443
     #[serde(default)]
     pub dial: Vec<String>,
     pub secret_key_path: Option<PathBuf>,
     pub allowed_peer_ids: Option<Vec<String>>,
+    #[serde(default)]
+    pub peer_score: PeerScoreConfig,
+    #[serde(default)]
+    pub session_limits: SessionLimitsConfig,
+    /// Ping peers, and close sessions that are silent for too long. Both
+    /// sides should enable it.
+    #[serde(default)]
+    pub keepalive: Option<KeepaliveConfig>,
+    /// Required for listen and dial addresses over TLS.
+    #[serde(default)]
+    pub tls: Option<P2PTlsConfig>,
+}
+
+/// TLS transport for listen and dial addresses ending with /tls/<server name>,
+/// e.g. /ip4/1.2.3.4/tcp/443/tls/godwoken.example. Secio still runs over TLS,
+/// so peer ids work as usual.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct P2PTlsConfig {
+    /// PEM certificate chain of this node. It's also presented to peers that
+    /// require client auth.
+    pub cert_path: PathBuf,
+    /// PEM private key of the certificate.
+    pub key_path: PathBuf,
+    /// PEM CA certificates to verify certificates of peers.
+    pub ca_path: PathBuf,
+    /// Require and verify certificates of peers dialing this node.
+    #[serde(default)]
+    pub client_auth: bool,
+}
+
+/// Application level keepalive. It keeps NAT mappings of idle sessions alive,
+/// and detects dead sessions faster than TCP.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeepaliveConfig {
+    pub interval_secs: u64,
+    /// Close the session if nothing is received from the peer in this time.
+    pub timeout_secs: u64,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 15,
+            timeout_secs: 45,
+        }
+    }
+}
+
+/// Limits of inbound sessions, so that a public listening node can't be
+/// exhausted by connections. Sessions dialed by the node itself don't count.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionLimitsConfig {
+    pub max_inbound_sessions: usize,
+    pub max_inbound_sessions_per_ip: usize,
+    /// Close connections that don't complete the handshake in this time.
+    pub handshake_timeout_secs: u64,
+}
+
+impl Default for SessionLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_inbound_sessions: 128,
+            max_inbound_sessions_per_ip: 8,
+            handshake_timeout_secs: 10,
+        }
+    }
+}
+
+/// Misbehaving peers, e.g. sending garbage frames or invalid messages, gain
+/// scores, and are banned when their scores reach `ban_threshold`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PeerScoreConfig {
+    pub ban_threshold: u32,
+    pub ban_secs: u64,
+    /// Scores halve every `score_half_life_secs`.
+    pub score_half_life_secs: u64,
+    /// Keep banned peers in this file, so that bans survive restarts.
+    pub ban_list_path: Option<PathBuf>,
+}
+
+impl Default for PeerScoreConfig {
+    fn default() -> Self {
+        Self {
+            ban_threshold: 100,
+            ban_secs: 3600,
+            score_half_life_secs: 600,
+            ban_list_path: None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -290,6 +737,31 @@ pub struct P2PNetworkConfig {
 pub struct SyncServerConfig {
     pub buffer_capacity: u64,
     pub broadcast_channel_capacity: usize,
+    /// Serve the state at the tip block to readonly nodes bootstrapping with
+    /// `p2p_state_sync`, one peer at a time.
+    #[serde(default)]
+    pub serve_state_sync: bool,
+    /// Accept txs relayed by readonly nodes with `relay_transactions`. They
+    /// are checked and queued like txs submitted to the RPC.
+    #[serde(default)]
+    pub accept_relayed_transactions: bool,
+    /// Serve readonly nodes that fell behind the buffer confirmed blocks in
+    /// ranges of at most this many blocks. 0 disables it.
+    #[serde(default = "default_block_range_max_blocks")]
+    pub block_range_max_blocks: u32,
+    /// Publish the current mem block with all its txs this often, so that
+    /// readonly nodes that missed txs resync. Disabled if None.
+    #[serde(default)]
+    pub mem_block_snapshot_interval_secs: Option<u64>,
+    /// Send each readonly node the hashes of the mem block txs since its last
+    /// ack this often. Readonly nodes that miss txs get a snapshot at once.
+    /// Disabled if None.
+    #[serde(default)]
+    pub mem_block_delta_interval_secs: Option<u64>,
+}
+
+const fn default_block_range_max_blocks() -> u32 {
+    16
 }
 
 impl Default for SyncServerConfig {
@@ -297,10 +769,30 @@ impl Default for SyncServerConfig {
         Self {
             buffer_capacity: 16,
             broadcast_channel_capacity: 1024,
+            serve_state_sync: false,
+            accept_relayed_transactions: false,
+            block_range_max_blocks: default_block_range_max_blocks(),
+            mem_block_snapshot_interval_secs: None,
+            mem_block_delta_interval_secs: None,
         }
     }
 }
 
+/// P2P block sync of readonly nodes.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SyncClientConfig {
+    /// Lock of the block producer wallet, a secp256k1 sighash lock. When set,
+    /// only block sync messages signed by it are accepted, and peers sending
+    /// unsigned or invalid messages are banned for `peer_score.ban_secs`.
+    #[serde(default)]
+    pub mem_block_signer_lock: Option<Script>,
+    /// Accept `gw_submit_l2transaction`, and relay the txs to the full node
+    /// over p2p. The full node needs `sync_server.accept_relayed_transactions`.
+    #[serde(default)]
+    pub relay_transactions: bool,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct MemPoolConfig {
@@ -311,6 +803,116 @@ pub struct MemPoolConfig {
     pub mem_block: MemBlockConfig,
     pub fee: FeeConfig,
     pub extra: MemPoolExtraConfig,
+    #[serde(default)]
+    pub priority_lane: PriorityLaneConfig,
+    #[serde(default)]
+    pub account_throttle: AccountThrottleConfig,
+    /// Write every finished mem block into this directory in a replayable
+    /// format, see `godwoken replay-mem-block`. Files are never cleaned up,
+    /// only enable it for debugging.
+    #[serde(default)]
+    pub mem_block_record_path: Option<PathBuf>,
+    #[serde(default)]
+    pub denylist: DenylistConfig,
+    #[serde(default)]
+    pub withdrawal_limit: WithdrawalLimitConfig,
+    #[serde(default)]
+    pub tx_ordering: TxOrdering,
+}
+
+/// Order of queued txs and withdrawals packaged into the mem block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxOrdering {
+    /// Strict arrival order.
+    Fifo,
+    /// Higher fee rate first, then arrival order.
+    #[default]
+    Fee,
+    /// Take turns between senders, so a single sender can't monopolize mem
+    /// blocks. Higher fee rate first within a turn.
+    RoundRobin,
+}
+
+/// Senders whose txs and withdrawals are rejected by mem pool.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DenylistConfig {
+    /// Account script hashes of denied senders.
+    pub script_hashes: Vec<H256>,
+    pub registry_addresses: Vec<RegistryAddressConfig>,
+    /// Entries added or removed by admin RPC are saved into this file, and
+    /// loaded together with entries above on start.
+    pub path: Option<PathBuf>,
+}
+
+/// Rolling window caps on withdrawals admitted into mem pool, a risk control
+/// during incidents. Caps can be overridden at runtime by admin RPC.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WithdrawalLimitConfig {
+    /// Length of the rolling window. Default is 86400 seconds, i.e. one day.
+    pub window_secs: u64,
+    /// Max total CKB capacity withdrawn in the window, in shannons.
+    pub ckb_capacity: Option<u64>,
+    /// Max amounts withdrawn in the window per sUDT.
+    pub sudt: Vec<SudtWithdrawalLimit>,
+}
+
+impl Default for WithdrawalLimitConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: 86400,
+            ckb_capacity: None,
+            sudt: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SudtWithdrawalLimit {
+    pub sudt_script_hash: H256,
+    pub amount: u128,
+}
+
+/// Priority lane for operator-critical txs, e.g. registry maintenance and
+/// oracle updates. These txs are fetched before others and can use reserved
+/// mem block slots, so they are included even when the mem pool is saturated.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PriorityLaneConfig {
+    /// Script hashes of senders whose txs go into the priority lane.
+    pub sender_script_hashes: Vec<H256>,
+    /// Number of mem block tx slots reserved for the priority lane. Priority
+    /// lane txs beyond this cap are treated as normal txs.
+    pub reserved_txs: usize,
+    /// Cycles of each mem block reserved for the priority lane, normal txs
+    /// are deferred when they would eat into them.
+    pub reserved_cycles: u64,
+}
+
+/// Per-account throttling of txs pushed into mem pool, so a single sender
+/// can't starve others. Priority lane senders are exempt.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccountThrottleConfig {
+    /// Max txs of an account in mem pool waiting to be packaged into a block.
+    /// 0 means unlimited.
+    pub max_in_flight_txs: usize,
+    /// Max txs an account can push in a rate limit window. 0 means unlimited.
+    pub rate_limit_txs: usize,
+    pub rate_limit_window_secs: u64,
+}
+
+impl Default for AccountThrottleConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight_txs: 0,
+            rate_limit_txs: 0,
+            rate_limit_window_secs: 1,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -325,8 +927,51 @@ pub struct MemBlockConfig {
         with = "toml_u64_serde_workaround"
     )]
     pub max_cycles_limit: u64,
+    /// Stop packaging txs once the cycles used by the mem block reach this
+    /// limit, txs whose cycles limit exceeds the remaining budget are deferred
+    /// to the next mem block. `max_cycles_limit` is the hard limit and
+    /// remains enforced during execution. Txs aren't deferred if unset.
+    #[serde(default)]
+    pub soft_cycles_limit: Option<u64>,
     #[serde(default = "default_syscall_cycles")]
     pub syscall_cycles: SyscallCyclesConfig,
+    /// Reject withdrawals with capacity lower than this, in shannons. The
+    /// withdrawal cell occupied capacity is always required.
+    #[serde(default)]
+    pub min_withdrawal_capacity: u64,
+    #[serde(default)]
+    pub deposit_filter: DepositFilterConfig,
+    /// Produce the block early once the state changes of the mem block held
+    /// in memory exceed this many bytes, so that the overlay can't grow
+    /// unbounded between blocks. No limit if None.
+    #[serde(default)]
+    pub max_overlay_bytes: Option<u64>,
+    /// Execute txs re-injected on a new tip on this many threads, against
+    /// snapshots of the state. Txs reading keys written by previous txs are
+    /// re-executed serially. Disabled if 0 or 1.
+    #[serde(default)]
+    pub parallel_execution_workers: usize,
+}
+
+/// Deposit cells not conforming to the filter are skipped by the deposit
+/// collector. They are left on L1 and collected once the filter allows them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DepositFilterConfig {
+    /// Min capacity of deposit cells, in shannons. Deposits lower than the
+    /// custodian cell occupied capacity are always skipped.
+    pub min_capacity: u64,
+    /// Type hashes of sUDTs accepted, all sUDTs are accepted if not set.
+    pub sudt_allowlist: Option<Vec<H256>>,
+    /// Min amounts of deposits per sUDT.
+    pub sudt_min_amounts: Vec<SudtDepositMinAmount>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SudtDepositMinAmount {
+    pub sudt_script_hash: H256,
+    pub amount: u128,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -356,6 +1001,18 @@ impl Default for DepositTimeoutConfig {
     }
 }
 
+impl MemBlockConfig {
+    /// Total cycles limit of the block, i.e. `max_cycles_limit` capped by the
+    /// `max_block_cycles` of the fork.
+    pub fn block_cycles_limit(&self, fork_config: &ForkConfig, block_number: u64) -> u64 {
+        fork_config
+            .max_block_cycles(block_number)
+            .map_or(self.max_cycles_limit, |limit| {
+                limit.min(self.max_cycles_limit)
+            })
+    }
+}
+
 const fn default_max_block_cycles_limit() -> u64 {
     u64::MAX
 }
@@ -399,6 +1056,12 @@ impl Default for MemPoolConfig {
             mem_block: MemBlockConfig::default(),
             fee: Default::default(),
             extra: Default::default(),
+            priority_lane: Default::default(),
+            account_throttle: Default::default(),
+            mem_block_record_path: None,
+            denylist: Default::default(),
+            withdrawal_limit: Default::default(),
+            tx_ordering: Default::default(),
         }
     }
 }
@@ -411,7 +1074,12 @@ impl Default for MemBlockConfig {
             max_txs: 1000,
             deposit_timeout_config: Default::default(),
             max_cycles_limit: default_max_block_cycles_limit(),
+            soft_cycles_limit: None,
             syscall_cycles: SyscallCyclesConfig::default(),
+            min_withdrawal_capacity: 0,
+            deposit_filter: Default::default(),
+            max_overlay_bytes: None,
+            parallel_execution_workers: 0,
         }
     }
 }
@@ -432,6 +1100,10 @@ pub struct DBBlockValidatorConfig {
     pub parallel_verify_blocks: bool,
     pub replace_scripts: Option<HashMap<H256, PathBuf>>,
     pub skip_targets: Option<HashSet<(u64, ChallengeTargetType, u32)>>,
+    /// Dump syscall traces of failed tx execution targets next to the cancel
+    /// challenge txs, and replay them in a bare VM to locate divergences.
+    #[serde(default)]
+    pub dump_syscall_trace: bool,
 }
 
 impl Default for DBBlockValidatorConfig {
@@ -441,6 +1113,7 @@ impl Default for DBBlockValidatorConfig {
             replace_scripts: None,
             skip_targets: None,
             parallel_verify_blocks: true,
+            dump_syscall_trace: false,
         }
     }
 }
@@ -454,6 +1127,80 @@ pub struct StoreConfig {
     pub cache_size: Option<usize>,
     #[serde(default)]
     pub options_file: Option<PathBuf>,
+    /// Record per-column latency histograms of store operations.
+    #[serde(default)]
+    pub latency_metrics: bool,
+    /// Log store operations slower than this threshold.
+    #[serde(default)]
+    pub slow_op_threshold_ms: Option<u64>,
+    /// Prune state history of blocks older than this many blocks from the
+    /// tip. Only finalized blocks are pruned. Keep all history if None.
+    #[serde(default)]
+    pub state_history_retention_blocks: Option<u64>,
+    /// Collect RocksDB statistics, e.g. block cache hits and write stall
+    /// time, for metrics. This has a small performance cost.
+    #[serde(default)]
+    pub statistics: bool,
+    /// Override RocksDB options, including those of `options_file`.
+    #[serde(default)]
+    pub tuning: StoreTuningConfig,
+    /// Directory of online backups created by the `gw_create_backup` admin
+    /// RPC.
+    #[serde(default)]
+    pub backup_dir: Option<PathBuf>,
+    /// In-memory LRU caches of hot state reads.
+    #[serde(default)]
+    pub state_cache: StoreStateCacheConfig,
+}
+
+/// Capacities of the caches in entries, 0 disables a cache.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StoreStateCacheConfig {
+    /// Scripts by script hash. Default is 10000.
+    pub scripts: usize,
+    /// Data, e.g. contract code, by data hash. Default is 256.
+    pub data: usize,
+    /// Account fields, e.g. nonces, balances and script hashes of accounts,
+    /// by state key. Default is 100000.
+    pub account_fields: usize,
+}
+
+impl Default for StoreStateCacheConfig {
+    fn default() -> Self {
+        Self {
+            scripts: 10_000,
+            data: 256,
+            account_fields: 100_000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StoreTuningConfig {
+    /// Block cache shared by all columns, in bytes.
+    #[serde(default)]
+    pub block_cache_size: Option<usize>,
+    /// Memtable size of each column, in bytes.
+    #[serde(default)]
+    pub write_buffer_size: Option<usize>,
+    /// Compression of each level, starting from level 0. Lz4 and zstd
+    /// require the corresponding features of autorocks.
+    #[serde(default)]
+    pub compression_per_level: Option<Vec<StoreCompression>>,
+    /// Max concurrent flush and compaction jobs.
+    #[serde(default)]
+    pub max_background_jobs: Option<i32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StoreCompression {
+    None,
+    Snappy,
+    Lz4,
+    Zstd,
 }
 
 fn default_store_path() -> PathBuf {
@@ -38,6 +38,31 @@ pub struct BackendForkConfig {
     pub fork_height: u64,
     pub backends: Vec<BackendConfig>,
     pub sudt_proxy: Option<SUDTProxyConfig>,
+    /// Let the generator build of the meta contract create the accounts of
+    /// `BatchCreateEthAccounts` with one syscall, inherited from the previous
+    /// fork if None. The state changes and syscall cycles are the same as
+    /// creating them one by one.
+    #[serde(default)]
+    pub batch_create_eth_accounts: Option<bool>,
+}
+
+/// Cycle limits activated at `fork_height`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CycleLimitForkConfig {
+    pub fork_height: u64,
+    /// Max cycles of a layer2 tx.
+    pub max_l2_tx_cycles: u64,
+    /// Max cycles of a tx executed by the `execute_l2transaction` and
+    /// `execute_raw_l2transaction` RPCs, e.g. eth_call. Falls back to
+    /// `mem_pool.execute_l2tx_max_cycles` if None.
+    #[serde(default)]
+    pub max_execute_tx_cycles: Option<u64>,
+    /// Max total cycles of txs of a block. Falls back to
+    /// `mem_pool.mem_block.max_cycles_limit` if None, which still caps the
+    /// limit otherwise.
+    #[serde(default)]
+    pub max_block_cycles: Option<u64>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -112,7 +137,17 @@ pub struct ForkConfig {
     ///   - Remove `state_checkpoints` from RawL2Block
     pub upgrade_global_state_version_to_v2: Option<u64>,
 
+    /// Cycle limits by fork height, in ascending order. Limits of the last
+    /// fork at or below a block apply to it. Below the first fork, l2 tx
+    /// cycles are limited by `increase_max_l2_tx_cycles_to_500m`.
+    #[serde(default)]
+    pub cycle_limit_forks: Vec<CycleLimitForkConfig>,
+
     /// Backend fork configs
+    ///
+    /// Can be reloaded without restarting, on SIGHUP or with the
+    /// `gw_reload_backend_forks` admin RPC. Only forks above the mem block
+    /// can be added or changed.
     pub backend_forks: Vec<BackendForkConfig>,
 
     /// Genesis config
@@ -149,8 +184,18 @@ impl ForkConfig {
         self.global_state_version(block_number) <= 1
     }
 
+    fn cycle_limit_fork(&self, block_number: u64) -> Option<&CycleLimitForkConfig> {
+        self.cycle_limit_forks
+            .iter()
+            .rev()
+            .find(|fork| fork.fork_height <= block_number)
+    }
+
     /// Return l2 tx cycles limit by block height
     pub fn max_l2_tx_cycles(&self, block_number: u64) -> u64 {
+        if let Some(fork) = self.cycle_limit_fork(block_number) {
+            return fork.max_l2_tx_cycles;
+        }
         match self.increase_max_l2_tx_cycles_to_500m {
             None => L2TX_MAX_CYCLES_150M,
             Some(fork_number) if block_number < fork_number => L2TX_MAX_CYCLES_150M,
@@ -158,6 +203,47 @@ impl ForkConfig {
         }
     }
 
+    /// Return cycles limit of executing a tx by RPCs by block height, if
+    /// configured.
+    pub fn max_execute_tx_cycles(&self, block_number: u64) -> Option<u64> {
+        self.cycle_limit_fork(block_number)
+            .and_then(|fork| fork.max_execute_tx_cycles)
+    }
+
+    /// Return total cycles limit of a block by block height, if configured.
+    pub fn max_block_cycles(&self, block_number: u64) -> Option<u64> {
+        self.cycle_limit_fork(block_number)
+            .and_then(|fork| fork.max_block_cycles)
+    }
+
+    /// Check that cycle limit forks are in ascending order and limits are
+    /// consistent.
+    pub fn check_cycle_limits(&self) -> Result<(), String> {
+        for forks in self.cycle_limit_forks.windows(2) {
+            if forks[0].fork_height >= forks[1].fork_height {
+                return Err(format!(
+                    "cycle limit fork at {} isn't above the previous fork at {}",
+                    forks[1].fork_height, forks[0].fork_height
+                ));
+            }
+        }
+        for fork in &self.cycle_limit_forks {
+            let height = fork.fork_height;
+            if fork.max_l2_tx_cycles == 0 || fork.max_execute_tx_cycles == Some(0) {
+                return Err(format!("zero cycle limit of fork at {}", height));
+            }
+            if let Some(max_block_cycles) = fork.max_block_cycles {
+                if max_block_cycles < fork.max_l2_tx_cycles {
+                    return Err(format!(
+                        "max block cycles {} is less than max l2 tx cycles {} of fork at {}",
+                        max_block_cycles, fork.max_l2_tx_cycles, height
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn max_tx_size(&self, _block_number: u64) -> usize {
         MAX_TX_SIZE
     }
@@ -179,6 +265,7 @@ impl ForkConfig {
 mod tests {
     use crate::{
         constants::{L2TX_MAX_CYCLES_150M, L2TX_MAX_CYCLES_500M},
+        fork_config::CycleLimitForkConfig,
         ForkConfig,
     };
 
@@ -202,4 +289,45 @@ mod tests {
         assert_eq!(fork.max_l2_tx_cycles(100), L2TX_MAX_CYCLES_500M);
         assert_eq!(fork.max_l2_tx_cycles(u64::MAX), L2TX_MAX_CYCLES_500M);
     }
+
+    #[test]
+    fn test_cycle_limit_forks() {
+        let fork = ForkConfig {
+            increase_max_l2_tx_cycles_to_500m: Some(10),
+            cycle_limit_forks: vec![
+                CycleLimitForkConfig {
+                    fork_height: 20,
+                    max_l2_tx_cycles: 1_000_000_000,
+                    max_execute_tx_cycles: Some(200_000_000),
+                    max_block_cycles: None,
+                },
+                CycleLimitForkConfig {
+                    fork_height: 30,
+                    max_l2_tx_cycles: 2_000_000_000,
+                    max_execute_tx_cycles: None,
+                    max_block_cycles: Some(10_000_000_000),
+                },
+            ],
+            ..Default::default()
+        };
+        fork.check_cycle_limits().unwrap();
+        assert_eq!(fork.max_l2_tx_cycles(9), L2TX_MAX_CYCLES_150M);
+        assert_eq!(fork.max_l2_tx_cycles(19), L2TX_MAX_CYCLES_500M);
+        assert_eq!(fork.max_execute_tx_cycles(19), None);
+        assert_eq!(fork.max_block_cycles(19), None);
+        assert_eq!(fork.max_l2_tx_cycles(20), 1_000_000_000);
+        assert_eq!(fork.max_execute_tx_cycles(29), Some(200_000_000));
+        assert_eq!(fork.max_block_cycles(29), None);
+        assert_eq!(fork.max_l2_tx_cycles(u64::MAX), 2_000_000_000);
+        assert_eq!(fork.max_execute_tx_cycles(30), None);
+        assert_eq!(fork.max_block_cycles(30), Some(10_000_000_000));
+
+        let mut unordered = fork.clone();
+        unordered.cycle_limit_forks.swap(0, 1);
+        assert!(unordered.check_cycle_limits().is_err());
+
+        let mut small_block = fork;
+        small_block.cycle_limit_forks[1].max_block_cycles = Some(1);
+        assert!(small_block.check_cycle_limits().is_err());
+    }
 }
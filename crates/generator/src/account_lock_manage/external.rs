@@ -0,0 +1,216 @@
+//! Lock algorithm delegating to an external verification service, so that
+//! new wallet lock types can be supported without forking the node.
+//!
+//! The service is called with JSON-RPC 2.0 over HTTP. Params are 0x-prefixed
+//! hex of molecule serialized structures, except numbers:
+//!
+//! - `recover(message, signature)` returns the recovered lock args.
+//! - `verify_tx(chain_id, rollup_script_hash, sender_address, sender_script,
+//!   receiver_script, tx)`
+//! - `verify_withdrawal(chain_id, rollup_script_hash, sender_script,
+//!   withdrawal, withdrawal_address)`
+//!
+//! Verify methods return null if the signature is valid, otherwise an error
+//! whose message is the reason.
+
+use std::{
+    sync::{mpsc, Arc},
+    time::Duration,
+};
+
+use anyhow::{ensure, Context, Result};
+use gw_common::registry_address::RegistryAddress;
+use gw_types::{
+    bytes::Bytes,
+    h256::*,
+    packed::{L2Transaction, Script, WithdrawalRequestExtra},
+    prelude::*,
+};
+use gw_utils::RollupContext;
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde_json::{json, Value};
+use tokio::{runtime::Runtime, sync::Semaphore};
+
+use super::LockAlgorithm;
+use crate::error::LockAlgorithmError;
+
+// Calls are async, a few threads serve many calls in flight.
+const WORKER_THREADS: usize = 2;
+
+pub struct ExternalLockAlgorithm {
+    url: String,
+    timeout: Duration,
+    client: Client,
+    // Calls are made on a dedicated runtime, since lock algorithms are
+    // called synchronously, often from async tasks. Always Some until
+    // dropped.
+    runtime: Option<Runtime>,
+    // Bounds calls in flight.
+    permits: Arc<Semaphore>,
+}
+
+impl ExternalLockAlgorithm {
+    pub fn new(url: String, timeout: Duration, max_concurrent_calls: usize) -> Result<Self> {
+        ensure!(
+            max_concurrent_calls > 0,
+            "external lock verifier max_concurrent_calls must be positive"
+        );
+        let client = Client::builder()
+            .build()
+            .context("external lock verifier http client")?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(WORKER_THREADS)
+            .thread_name("external lock verifier")
+            .enable_all()
+            .build()
+            .context("external lock verifier runtime")?;
+        Ok(Self {
+            url,
+            timeout,
+            client,
+            runtime: Some(runtime),
+            permits: Arc::new(Semaphore::new(max_concurrent_calls)),
+        })
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value, LockAlgorithmError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": method,
+            "params": params,
+        });
+        let (client, url, permits) = (self.client.clone(), self.url.clone(), self.permits.clone());
+        let timeout = self.timeout;
+        let (reply, result) = mpsc::sync_channel(1);
+        let runtime = self.runtime.as_ref().expect("runtime");
+        runtime.spawn(async move {
+            let call_with_permit = async {
+                let _permit = permits.acquire().await.expect("permits are never closed");
+                call(&client, &url, request).await
+            };
+            // The timeout covers the wait for a permit too.
+            let result = tokio::time::timeout(timeout, call_with_permit)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(LockAlgorithmError::VerifierUnavailable(format!(
+                        "timed out after {:?}",
+                        timeout
+                    )))
+                });
+            let _ = reply.send(result);
+        });
+        result
+            .recv()
+            .unwrap_or_else(|err| Err(LockAlgorithmError::VerifierUnavailable(err.to_string())))
+    }
+
+    fn verify(&self, method: &str, params: Value) -> Result<(), LockAlgorithmError> {
+        match self.call(method, params)? {
+            Value::Null => Ok(()),
+            result => Err(LockAlgorithmError::VerifierUnavailable(format!(
+                "unexpected {} result from {}: {}",
+                method, self.url, result
+            ))),
+        }
+    }
+}
+
+impl Drop for ExternalLockAlgorithm {
+    fn drop(&mut self) {
+        // Dropping a runtime blocks, which panics in async contexts, e.g.
+        // when the verifier is replaced on reload.
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+async fn call(client: &Client, url: &str, request: Value) -> Result<Value, LockAlgorithmError> {
+    let unavailable =
+        |err: reqwest::Error| LockAlgorithmError::VerifierUnavailable(err.to_string());
+    let body = client
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(request.to_string())
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(unavailable)?
+        .bytes()
+        .await
+        .map_err(unavailable)?;
+    let mut response: Value = serde_json::from_slice(&body).map_err(|err| {
+        LockAlgorithmError::VerifierUnavailable(format!("invalid response: {}", err))
+    })?;
+    if let Some(error) = response.get("error") {
+        let message = match error.get("message").and_then(Value::as_str) {
+            Some(message) => message.to_string(),
+            None => error.to_string(),
+        };
+        return Err(LockAlgorithmError::InvalidSignature(message));
+    }
+    Ok(response["result"].take())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+impl LockAlgorithm for ExternalLockAlgorithm {
+    fn recover(&self, message: H256, signature: &[u8]) -> Result<Bytes, LockAlgorithmError> {
+        let result = self.call(
+            "recover",
+            json!([to_hex(message.as_slice()), to_hex(signature)]),
+        )?;
+        let lock_args = result
+            .as_str()
+            .and_then(|s| s.strip_prefix("0x"))
+            .and_then(|s| hex::decode(s).ok())
+            .ok_or_else(|| {
+                LockAlgorithmError::VerifierUnavailable(format!(
+                    "unexpected recover result from {}: {}",
+                    self.url, result
+                ))
+            })?;
+        Ok(lock_args.into())
+    }
+
+    fn verify_tx(
+        &self,
+        ctx: &RollupContext,
+        sender_address: RegistryAddress,
+        sender_script: Script,
+        receiver_script: Script,
+        tx: L2Transaction,
+    ) -> Result<(), LockAlgorithmError> {
+        let chain_id: u64 = ctx.rollup_config.chain_id().unpack();
+        let params = json!([
+            chain_id,
+            to_hex(ctx.rollup_script_hash.as_slice()),
+            to_hex(&sender_address.to_bytes()),
+            to_hex(sender_script.as_slice()),
+            to_hex(receiver_script.as_slice()),
+            to_hex(tx.as_slice()),
+        ]);
+        self.verify("verify_tx", params)
+    }
+
+    fn verify_withdrawal(
+        &self,
+        ctx: &RollupContext,
+        sender_script: Script,
+        withdrawal: &WithdrawalRequestExtra,
+        withdrawal_address: RegistryAddress,
+    ) -> Result<(), LockAlgorithmError> {
+        let chain_id: u64 = ctx.rollup_config.chain_id().unpack();
+        let params = json!([
+            chain_id,
+            to_hex(ctx.rollup_script_hash.as_slice()),
+            to_hex(sender_script.as_slice()),
+            to_hex(withdrawal.as_slice()),
+            to_hex(&withdrawal_address.to_bytes()),
+        ]);
+        self.verify("verify_withdrawal", params)
+    }
+}
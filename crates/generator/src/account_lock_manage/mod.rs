@@ -1,6 +1,8 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
+use anyhow::Result;
 use gw_common::registry_address::RegistryAddress;
+use gw_config::{AccountLockConfig, AccountLockVerifierConfig, BuiltinLockAlgorithm};
 use gw_types::h256::*;
 use gw_types::{
     bytes::Bytes,
@@ -11,6 +13,7 @@ use gw_utils::RollupContext;
 #[cfg(any(debug_assertions, feature = "enable-always-success-lock"))]
 pub mod always_success;
 pub mod eip712;
+pub mod external;
 pub mod secp256k1;
 
 use crate::error::LockAlgorithmError;
@@ -50,6 +53,42 @@ impl AccountLockManage {
         self.locks.insert(code_hash, lock_algo);
     }
 
+    /// Register verifiers of the configured account locks, replacing those
+    /// registered before.
+    pub fn register_from_config(&mut self, configs: &[AccountLockConfig]) -> Result<()> {
+        for config in configs {
+            let lock_algo: Arc<dyn LockAlgorithm + Send + Sync> = match config.verifier {
+                AccountLockVerifierConfig::Builtin {
+                    algorithm: BuiltinLockAlgorithm::Secp256k1Eth,
+                } => Arc::new(secp256k1::Secp256k1Eth),
+                #[cfg(any(debug_assertions, feature = "enable-always-success-lock"))]
+                AccountLockVerifierConfig::Builtin {
+                    algorithm: BuiltinLockAlgorithm::AlwaysSuccess,
+                } => Arc::new(always_success::AlwaysSuccess),
+                #[cfg(not(any(debug_assertions, feature = "enable-always-success-lock")))]
+                AccountLockVerifierConfig::Builtin {
+                    algorithm: BuiltinLockAlgorithm::AlwaysSuccess,
+                } => anyhow::bail!("always success lock is not enabled in this build"),
+                AccountLockVerifierConfig::External {
+                    ref url,
+                    timeout_ms,
+                    max_concurrent_calls,
+                } => Arc::new(external::ExternalLockAlgorithm::new(
+                    url.clone(),
+                    Duration::from_millis(timeout_ms),
+                    max_concurrent_calls,
+                )?),
+            };
+            log::info!(
+                "register account lock {} with {:?}",
+                config.lock_type_hash,
+                config.verifier
+            );
+            self.register_lock_algorithm(config.lock_type_hash.clone().into(), lock_algo);
+        }
+        Ok(())
+    }
+
     #[allow(clippy::borrowed_box)]
     pub fn get_lock_algorithm(
         &self,
@@ -1,12 +1,47 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+    sync::Arc,
+};
 
 use anyhow::{bail, Context, Result};
-use gw_config::{content_checksum, BackendConfig, BackendForkConfig, BackendType};
+use arc_swap::ArcSwap;
+use gw_config::{content_checksum, BackendConfig, BackendForkConfig, BackendType, Config};
 use gw_types::{bytes::Bytes, h256::*};
 
+#[cfg(feature = "wasm-backend")]
+use crate::wasm_backend;
+
+const WASM_MAGIC: &[u8] = b"\0asm";
+
+/// Engine executing the generator of a backend off-chain.
+///
+/// Onchain validators are always RISC-V programs, so wasm backends are only
+/// allowed in debug backend forks, see [`BackendManage::from_debug_config`].
+#[derive(Clone)]
+pub enum BackendEngine {
+    RiscV,
+    /// Experimental, see [`wasm_backend`].
+    #[cfg(feature = "wasm-backend")]
+    Wasm(wasm_backend::WasmProgram),
+}
+
+impl BackendEngine {
+    fn detect(generator: &[u8]) -> Result<Self> {
+        if !generator.starts_with(WASM_MAGIC) {
+            return Ok(Self::RiscV);
+        }
+        #[cfg(feature = "wasm-backend")]
+        return Ok(Self::Wasm(wasm_backend::WasmProgram::compile(generator)?));
+        #[cfg(not(feature = "wasm-backend"))]
+        bail!("wasm generator requires the wasm-backend feature");
+    }
+}
+
 #[derive(Clone)]
 pub struct Backend {
     pub generator: Bytes,
+    pub engine: BackendEngine,
     pub sys_store_addr: Option<u64>,
     pub validator_script_type_hash: H256,
     pub backend_type: BackendType,
@@ -32,13 +67,31 @@ impl Backend {
             );
         }
 
+        let engine = BackendEngine::detect(&generator)
+            .with_context(|| format!("Backend {:?} generator", backend_type))?;
+        let g = ckb_types::H256::from(generator_checksum);
+        #[cfg(feature = "wasm-backend")]
+        if let BackendEngine::Wasm(_) = engine {
+            log::warn!(
+                "generator {g} of backend {:?} is experimental wasm, which is not the onchain validator",
+                backend_type
+            );
+            return Ok(Self {
+                generator,
+                engine,
+                sys_store_addr: None,
+                validator_script_type_hash,
+                backend_type,
+                generator_checksum,
+            });
+        }
+
         let addrs = get_symbol_addrs(
             generator_debug.as_ref().unwrap_or(&generator),
             &["_Z9sys_storeP12gw_context_tjPKhmS2_", "sys_store"],
         )
         .unwrap_or_default();
         let sys_store_addr = addrs.into_iter().flatten().next();
-        let g = ckb_types::H256::from(generator_checksum);
         if let Some(a) = sys_store_addr {
             log::info!("generator {g} sys_store addr: {:#x}", a);
         } else {
@@ -47,6 +100,7 @@ impl Backend {
 
         Ok(Self {
             generator,
+            engine,
             sys_store_addr,
             validator_script_type_hash,
             backend_type,
@@ -83,16 +137,23 @@ pub struct SUDTProxyConfig {
 pub struct BlockConsensus {
     pub sudt_proxy: SUDTProxyConfig,
     pub backends: HashMap<H256, Backend>,
+    /// Whether `sys_batch_create_eth_accounts` is enabled.
+    pub batch_create_eth_accounts: bool,
 }
 
+type BackendForks = Vec<(u64, Arc<BlockConsensus>)>;
+
 #[derive(Default)]
 pub struct BackendManage {
-    backend_forks: Vec<(u64, BlockConsensus)>,
+    // Replaced as a whole on reload, so readers always see a consistent set
+    // of forks.
+    backend_forks: ArcSwap<BackendForks>,
 }
 
 impl BackendManage {
     pub fn polyjuice_backends_have_sys_store_addr(&self) -> bool {
         self.backend_forks
+            .load()
             .iter()
             .flat_map(|(_, c)| c.backends.iter())
             .filter(|(_, b)| b.backend_type == BackendType::Polyjuice)
@@ -100,12 +161,25 @@ impl BackendManage {
     }
 
     pub fn from_config(configs: Vec<BackendForkConfig>) -> Result<Self> {
-        let mut backend_manage: BackendManage = Default::default();
+        Self::build(configs, false)
+    }
+
+    /// Build backend forks only used to execute txs off-chain, e.g. by debug
+    /// RPCs, which may use experimental wasm backends.
+    pub fn from_debug_config(configs: Vec<BackendForkConfig>) -> Result<Self> {
+        Self::build(configs, true)
+    }
+
+    fn build(configs: Vec<BackendForkConfig>, allow_wasm: bool) -> Result<Self> {
+        let mut backend_forks = Vec::with_capacity(configs.len());
         for config in configs {
-            backend_manage.register_backend_fork(config, true)?;
+            let fork = build_backend_fork(&backend_forks, config, allow_wasm)?;
+            backend_forks.push(fork);
         }
 
-        Ok(backend_manage)
+        Ok(BackendManage {
+            backend_forks: ArcSwap::from_pointee(backend_forks),
+        })
     }
 
     pub fn register_backend_fork(
@@ -113,115 +187,116 @@ impl BackendManage {
         config: BackendForkConfig,
         #[allow(unused_variables)] compile: bool,
     ) -> Result<()> {
-        if let Some((height, _backends)) = self.backend_forks.last() {
-            if config.fork_height <= *height {
-                bail!("BackendForkConfig with fork_height {} is less or equals to the last fork_height {}", config.fork_height, height);
-            }
+        let mut backend_forks = Vec::clone(&self.backend_forks.load());
+        let fork = build_backend_fork(&backend_forks, config, false)?;
+        backend_forks.push(fork);
+        self.backend_forks.store(Arc::new(backend_forks));
+        Ok(())
+    }
+
+    /// Replace the backend forks, e.g. to add a fork with a new polyjuice
+    /// version, without restarting the node.
+    ///
+    /// Checksums of all the backends are validated before anything is
+    /// replaced. Forks up to `frozen_height` must be unchanged, since blocks
+    /// up to it may have been executed already. Returns false if the forks
+    /// are unchanged.
+    pub fn reload(&self, configs: Vec<BackendForkConfig>, frozen_height: u64) -> Result<bool> {
+        self.replace(Self::from_config(configs)?, Some(frozen_height))
+    }
+
+    /// Replace the backend forks with `new`, only those above `frozen_height`
+    /// may be changed if it's set.
+    fn replace(&self, new: BackendManage, frozen_height: Option<u64>) -> Result<bool> {
+        let new_forks = new.backend_forks.load_full();
+        let old_forks = self.backend_forks.load_full();
+        if summarize(&old_forks, u64::MAX) == summarize(&new_forks, u64::MAX) {
+            return Ok(false);
         }
-        // inherit block consensus
-        let mut block_consensus = self
-            .backend_forks
-            .last()
-            .map(|(_height, consensus)| consensus.clone())
-            .unwrap_or_default();
-
-        let fork_height = config.fork_height;
-
-        // set sudt proxy
-        if let Some(sudt_proxy) = config.sudt_proxy {
-            block_consensus.sudt_proxy = SUDTProxyConfig {
-                permit_sudt_transfer_from_dangerous_contract: sudt_proxy
-                    .permit_sudt_transfer_from_dangerous_contract,
-                address_list: sudt_proxy
-                    .address_list
-                    .into_iter()
-                    .map(Into::into)
-                    .collect(),
-            };
+        if let Some(frozen_height) = frozen_height {
+            if summarize(&old_forks, frozen_height) != summarize(&new_forks, frozen_height) {
+                bail!(
+                    "backend forks at or below height {} can't be changed",
+                    frozen_height
+                );
+            }
         }
 
-        if block_consensus
-            .sudt_proxy
-            .permit_sudt_transfer_from_dangerous_contract
-        {
-            log::warn!(
-                "`permit_sudt_transfer_from_dangerous_contract` is set to `true` at height {}.",
-                fork_height
-            );
+        let prev = self
+            .backend_forks
+            .compare_and_swap(&old_forks, new_forks.clone());
+        if !Arc::ptr_eq(&*prev, &old_forks) {
+            bail!("backend forks were reloaded concurrently");
         }
 
-        // register backends
-        for config in config.backends {
-            let BackendConfig {
-                generator,
-                generator_checksum,
-                validator_script_type_hash,
-                backend_type,
-                generator_debug,
-            } = config;
-            let generator = generator
-                .get()
-                .with_context(|| format!("load generator from {}", generator))?
-                .into_owned()
-                .into();
-            let generator_debug = if let Some(d) = generator_debug {
-                Some(
-                    d.get()
-                        .with_context(|| format!("load generator debug from {}", d))?
-                        .into_owned()
-                        .into(),
-                )
-            } else {
-                None
-            };
-            let backend = Backend::build(
-                backend_type,
-                validator_script_type_hash.into(),
-                generator,
-                generator_checksum.into(),
-                generator_debug,
-            )?;
-
-            log::debug!(
-                "registry backend {:?}({}) at height {}",
-                backend.backend_type,
-                hex::encode(backend.generator_checksum),
-                fork_height
-            );
-
-            block_consensus
-                .backends
-                .insert(backend.validator_script_type_hash, backend);
+        for (height, consensus) in new_forks.iter() {
+            for backend in consensus.backends.values() {
+                let old_checksum = find_fork(&old_forks, *height).and_then(|(_, c)| {
+                    c.backends
+                        .get(&backend.validator_script_type_hash)
+                        .map(|b| b.generator_checksum)
+                });
+                if old_checksum != Some(backend.generator_checksum) {
+                    log::info!(
+                        "switch backend {:?} from {} to {} at height {}",
+                        backend.backend_type,
+                        old_checksum.map_or_else(|| "none".to_string(), hex::encode),
+                        hex::encode(backend.generator_checksum),
+                        height
+                    );
+                }
+            }
         }
-
-        self.backend_forks
-            .push((config.fork_height, block_consensus));
-        Ok(())
+        log::info!(
+            "reloaded {} backend forks, previously {}",
+            new_forks.len(),
+            old_forks.len()
+        );
+        Ok(true)
     }
 
-    pub fn get_block_consensus_at_height(
+    /// Reload the backend forks from the consensus config in the config file.
+    /// See `reload`.
+    ///
+    /// `debug` is the backend manage of the debug generator if it's built from
+    /// the debug backend forks, which are reloaded too. They are only used
+    /// off-chain, so any of them may be changed, but they can't be added or
+    /// removed without a restart.
+    pub fn reload_from_config_file(
         &self,
-        block_number: u64,
-    ) -> Option<&(u64, BlockConsensus)> {
-        self.backend_forks
-            .iter()
-            .rev()
-            .find(|(height, _)| block_number >= *height)
+        path: &Path,
+        frozen_height: u64,
+        debug: Option<&BackendManage>,
+    ) -> Result<bool> {
+        let content = std::fs::read(path)
+            .with_context(|| format!("read config file from {}", path.to_string_lossy()))?;
+        let config: Config = toml::from_slice(&content).context("parse config file")?;
+        // Validate the debug backends before anything is replaced.
+        let new_debug = match (debug, config.debug_backend_forks) {
+            (Some(debug), Some(configs)) => Some((debug, Self::from_debug_config(configs)?)),
+            (None, None) => None,
+            (Some(_), None) => bail!("debug backend forks can't be removed without a restart"),
+            (None, Some(_)) => bail!("debug backend forks can't be added without a restart"),
+        };
+        let configs = config.consensus.get_config().backend_forks.clone();
+        let reloaded = self.reload(configs, frozen_height)?;
+        let debug_reloaded = match new_debug {
+            Some((debug, new)) => debug.replace(new, None)?,
+            None => false,
+        };
+        Ok(reloaded || debug_reloaded)
     }
 
-    pub fn get_mut_block_consensus_at_height(
-        &mut self,
+    pub fn get_block_consensus_at_height(
+        &self,
         block_number: u64,
-    ) -> Option<&mut (u64, BlockConsensus)> {
-        self.backend_forks
-            .iter_mut()
-            .rev()
-            .find(|(height, _)| block_number >= *height)
+    ) -> Option<(u64, Arc<BlockConsensus>)> {
+        find_fork(&self.backend_forks.load(), block_number).cloned()
     }
 
-    pub fn get_backend(&self, block_number: u64, code_hash: &H256) -> Option<&Backend> {
+    pub fn get_backend(&self, block_number: u64, code_hash: &H256) -> Option<Backend> {
         self.get_block_consensus_at_height(block_number)
-            .and_then(|(_number, consensus)| consensus.backends.get(code_hash))
+            .and_then(|(_number, consensus)| consensus.backends.get(code_hash).cloned())
             .map(|backend| {
                 log::debug!(
                     "get backend {:?}({}) at height {}",
@@ -234,12 +309,149 @@ impl BackendManage {
     }
 }
 
+fn find_fork(
+    backend_forks: &BackendForks,
+    block_number: u64,
+) -> Option<&(u64, Arc<BlockConsensus>)> {
+    backend_forks
+        .iter()
+        .rev()
+        .find(|(height, _)| block_number >= *height)
+}
+
+/// Fork heights, backend checksums, sUDT proxy configs and syscall switches
+/// of forks up to `max_height`, for comparing forks.
+fn summarize(
+    backend_forks: &BackendForks,
+    max_height: u64,
+) -> Vec<(u64, BTreeMap<H256, H256>, &SUDTProxyConfig, bool)> {
+    backend_forks
+        .iter()
+        .take_while(|(height, _)| *height <= max_height)
+        .map(|(height, consensus)| {
+            let checksums = (consensus.backends.iter())
+                .map(|(code_hash, b)| (*code_hash, b.generator_checksum))
+                .collect();
+            (
+                *height,
+                checksums,
+                &consensus.sudt_proxy,
+                consensus.batch_create_eth_accounts,
+            )
+        })
+        .collect()
+}
+
+fn build_backend_fork(
+    backend_forks: &BackendForks,
+    config: BackendForkConfig,
+    #[cfg_attr(not(feature = "wasm-backend"), allow(unused_variables))] allow_wasm: bool,
+) -> Result<(u64, Arc<BlockConsensus>)> {
+    if let Some((height, _backends)) = backend_forks.last() {
+        if config.fork_height <= *height {
+            bail!("BackendForkConfig with fork_height {} is less or equals to the last fork_height {}", config.fork_height, height);
+        }
+    }
+    // inherit block consensus
+    let mut block_consensus = backend_forks
+        .last()
+        .map(|(_height, consensus)| BlockConsensus::clone(consensus))
+        .unwrap_or_default();
+
+    let fork_height = config.fork_height;
+
+    // set sudt proxy
+    if let Some(sudt_proxy) = config.sudt_proxy {
+        block_consensus.sudt_proxy = SUDTProxyConfig {
+            permit_sudt_transfer_from_dangerous_contract: sudt_proxy
+                .permit_sudt_transfer_from_dangerous_contract,
+            address_list: sudt_proxy
+                .address_list
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        };
+    }
+
+    if block_consensus
+        .sudt_proxy
+        .permit_sudt_transfer_from_dangerous_contract
+    {
+        log::warn!(
+            "`permit_sudt_transfer_from_dangerous_contract` is set to `true` at height {}.",
+            fork_height
+        );
+    }
+
+    // set batch creation of eth accounts
+    if let Some(enabled) = config.batch_create_eth_accounts {
+        block_consensus.batch_create_eth_accounts = enabled;
+    }
+
+    // register backends
+    for config in config.backends {
+        let BackendConfig {
+            generator,
+            generator_checksum,
+            validator_script_type_hash,
+            backend_type,
+            generator_debug,
+        } = config;
+        let generator = generator
+            .get()
+            .with_context(|| format!("load generator from {}", generator))?
+            .into_owned()
+            .into();
+        let generator_debug = if let Some(d) = generator_debug {
+            Some(
+                d.get()
+                    .with_context(|| format!("load generator debug from {}", d))?
+                    .into_owned()
+                    .into(),
+            )
+        } else {
+            None
+        };
+        let backend = Backend::build(
+            backend_type,
+            validator_script_type_hash.into(),
+            generator,
+            generator_checksum.into(),
+            generator_debug,
+        )?;
+        #[cfg(feature = "wasm-backend")]
+        if matches!(backend.engine, BackendEngine::Wasm(_)) && !allow_wasm {
+            bail!(
+                "wasm backend {:?} has no onchain validator, it's only allowed in debug backend forks",
+                backend.backend_type
+            );
+        }
+
+        log::debug!(
+            "registry backend {:?}({}) at height {}",
+            backend.backend_type,
+            hex::encode(backend.generator_checksum),
+            fork_height
+        );
+
+        block_consensus
+            .backends
+            .insert(backend.validator_script_type_hash, backend);
+    }
+
+    Ok((fork_height, Arc::new(block_consensus)))
+}
+
 #[cfg(test)]
 mod tests {
     use gw_builtin_binaries::Resource;
-    use gw_config::{content_checksum, BackendConfig, BackendForkConfig, BackendType};
+    use gw_config::{
+        content_checksum, BackendConfig, BackendForkConfig, BackendType, Config, Consensus,
+        ForkConfig,
+    };
+    use gw_types::bytes::Bytes;
 
-    use super::BackendManage;
+    use super::{Backend, BackendManage};
 
     #[test]
     fn test_get_block_consensus() {
@@ -261,6 +473,7 @@ mod tests {
                 permit_sudt_transfer_from_dangerous_contract: true,
                 address_list: vec![[1u8; 20].into()],
             }),
+            batch_create_eth_accounts: None,
             backends: vec![
                 BackendConfig {
                     validator_script_type_hash: [42u8; 32].into(),
@@ -324,6 +537,7 @@ mod tests {
                 permit_sudt_transfer_from_dangerous_contract: false,
                 address_list: vec![[42u8; 20].into()],
             }),
+            batch_create_eth_accounts: None,
             backends: vec![
                 BackendConfig {
                     validator_script_type_hash: [41u8; 32].into(),
@@ -407,6 +621,7 @@ mod tests {
         let config = BackendForkConfig {
             fork_height: 50,
             sudt_proxy: None,
+            batch_create_eth_accounts: None,
             backends: vec![],
         };
         m.register_backend_fork(config, false).unwrap();
@@ -431,4 +646,181 @@ mod tests {
             vec![[42u8; 20]]
         );
     }
+
+    #[test]
+    fn test_backend_engine() {
+        let build = |generator: &'static [u8]| {
+            Backend::build(
+                BackendType::Unknown,
+                [42u8; 32],
+                Bytes::from_static(generator),
+                content_checksum(generator),
+                None,
+            )
+        };
+        let backend = build(b"sudt_v0").unwrap();
+        assert!(matches!(backend.engine, super::BackendEngine::RiscV));
+
+        // An empty wasm module.
+        let wasm = b"\0asm\x01\0\0\0";
+        #[cfg(feature = "wasm-backend")]
+        {
+            let backend = build(wasm).unwrap();
+            assert!(matches!(backend.engine, super::BackendEngine::Wasm(_)));
+            assert!(backend.sys_store_addr.is_none());
+        }
+        #[cfg(not(feature = "wasm-backend"))]
+        assert!(build(wasm).is_err());
+
+        // Wasm backends are only allowed in debug backend forks.
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("wasm"), wasm).unwrap();
+        let configs = vec![BackendForkConfig {
+            fork_height: 0,
+            sudt_proxy: None,
+            batch_create_eth_accounts: None,
+            backends: vec![BackendConfig {
+                validator_script_type_hash: [42u8; 32].into(),
+                backend_type: BackendType::Unknown,
+                generator: Resource::file_system(dir.path().join("wasm")),
+                generator_checksum: content_checksum(wasm).into(),
+                generator_debug: None,
+            }],
+        }];
+        assert!(BackendManage::from_config(configs.clone()).is_err());
+        #[cfg(feature = "wasm-backend")]
+        assert!(BackendManage::from_debug_config(configs).is_ok());
+        #[cfg(not(feature = "wasm-backend"))]
+        assert!(BackendManage::from_debug_config(configs).is_err());
+    }
+
+    #[test]
+    fn test_reload_backend_forks() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("sudt_v0"), "sudt_v0").unwrap();
+        std::fs::write(dir.path().join("sudt_v1"), "sudt_v1").unwrap();
+        let fork = |fork_height, name: &str, checksum: &[u8]| BackendForkConfig {
+            fork_height,
+            sudt_proxy: None,
+            batch_create_eth_accounts: None,
+            backends: vec![BackendConfig {
+                validator_script_type_hash: [42u8; 32].into(),
+                backend_type: BackendType::Sudt,
+                generator: Resource::file_system(dir.path().join(name)),
+                generator_checksum: content_checksum(checksum).into(),
+                generator_debug: None,
+            }],
+        };
+        let generator_at = |m: &BackendManage, height| {
+            m.get_backend(height, &[42u8; 32])
+                .unwrap()
+                .generator
+                .to_vec()
+        };
+
+        let m = BackendManage::from_config(vec![fork(0, "sudt_v0", b"sudt_v0")]).unwrap();
+        // Add a fork.
+        let configs = vec![
+            fork(0, "sudt_v0", b"sudt_v0"),
+            fork(10, "sudt_v1", b"sudt_v1"),
+        ];
+        assert!(m.reload(configs.clone(), 5).unwrap());
+        assert_eq!(generator_at(&m, 9), b"sudt_v0".to_vec());
+        assert_eq!(generator_at(&m, 10), b"sudt_v1".to_vec());
+        assert!(!m.reload(configs, 5).unwrap());
+
+        // Checksum mismatch.
+        let configs = vec![
+            fork(0, "sudt_v0", b"sudt_v0"),
+            fork(20, "sudt_v1", b"sudt_v0"),
+        ];
+        assert!(m.reload(configs, 5).is_err());
+        // Forks up to the frozen height can't be changed.
+        let configs = vec![
+            fork(0, "sudt_v0", b"sudt_v0"),
+            fork(5, "sudt_v1", b"sudt_v1"),
+        ];
+        assert!(m.reload(configs, 5).is_err());
+        let configs = vec![fork(0, "sudt_v1", b"sudt_v1")];
+        assert!(m.reload(configs, 5).is_err());
+        // Unchanged after failed reloads.
+        assert_eq!(generator_at(&m, 9), b"sudt_v0".to_vec());
+        assert_eq!(generator_at(&m, 10), b"sudt_v1".to_vec());
+    }
+
+    #[test]
+    fn test_reload_from_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("sudt_v0"), "sudt_v0").unwrap();
+        std::fs::write(dir.path().join("sudt_v1"), "sudt_v1").unwrap();
+        let fork = |fork_height, name: &str| BackendForkConfig {
+            fork_height,
+            sudt_proxy: None,
+            batch_create_eth_accounts: None,
+            backends: vec![BackendConfig {
+                validator_script_type_hash: [42u8; 32].into(),
+                backend_type: BackendType::Sudt,
+                generator: Resource::file_system(dir.path().join(name)),
+                generator_checksum: content_checksum(name.as_bytes()).into(),
+                generator_debug: None,
+            }],
+        };
+        let config_path = dir.path().join("config.toml");
+        let write_config = |backend_forks, debug_backend_forks| {
+            let config = Config {
+                consensus: Consensus::Config {
+                    config: Box::new(ForkConfig {
+                        backend_forks,
+                        ..Default::default()
+                    }),
+                },
+                debug_backend_forks,
+                ..Default::default()
+            };
+            std::fs::write(&config_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        };
+        let generator_at = |m: &BackendManage, height| {
+            m.get_backend(height, &[42u8; 32])
+                .unwrap()
+                .generator
+                .to_vec()
+        };
+
+        let m = BackendManage::from_config(vec![fork(0, "sudt_v0")]).unwrap();
+        let debug = BackendManage::from_debug_config(vec![fork(0, "sudt_v0")]).unwrap();
+        // Add a fork, debug forks below the frozen height may be changed.
+        write_config(
+            vec![fork(0, "sudt_v0"), fork(10, "sudt_v1")],
+            Some(vec![fork(0, "sudt_v1")]),
+        );
+        assert!(m
+            .reload_from_config_file(&config_path, 5, Some(&debug))
+            .unwrap());
+        assert_eq!(generator_at(&m, 9), b"sudt_v0".to_vec());
+        assert_eq!(generator_at(&m, 10), b"sudt_v1".to_vec());
+        assert_eq!(generator_at(&debug, 9), b"sudt_v1".to_vec());
+        assert!(!m
+            .reload_from_config_file(&config_path, 5, Some(&debug))
+            .unwrap());
+
+        // Forks up to the frozen height can't be changed, and the debug forks
+        // aren't replaced either.
+        write_config(vec![fork(0, "sudt_v1")], Some(vec![fork(0, "sudt_v0")]));
+        assert!(m
+            .reload_from_config_file(&config_path, 5, Some(&debug))
+            .is_err());
+        assert_eq!(generator_at(&m, 9), b"sudt_v0".to_vec());
+        assert_eq!(generator_at(&debug, 9), b"sudt_v1".to_vec());
+
+        // Debug forks can't be added or removed.
+        write_config(vec![fork(0, "sudt_v0"), fork(10, "sudt_v1")], None);
+        assert!(m
+            .reload_from_config_file(&config_path, 5, Some(&debug))
+            .is_err());
+        write_config(
+            vec![fork(0, "sudt_v0"), fork(10, "sudt_v1")],
+            Some(vec![fork(0, "sudt_v1")]),
+        );
+        assert!(m.reload_from_config_file(&config_path, 5, None).is_err());
+    }
 }
@@ -0,0 +1,142 @@
+//! Cycles profiling.
+//!
+//! If enabled, the generator collects cycles of each executed tx by backend
+//! and syscall, and dumps them aggregated per applied block as JSON.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use gw_config::SyscallCyclesConfig;
+use gw_types::{h256::*, offchain::CyclesProfile};
+use serde_json::json;
+
+use crate::syscalls::{get_syscall_cycles, syscall_name};
+
+#[derive(Clone)]
+pub struct CyclesProfiler {
+    output_dir: PathBuf,
+    /// Used to estimate virtual cycles of syscalls, since txs of applied
+    /// blocks are executed without cycles pool.
+    syscall_cycles: SyscallCyclesConfig,
+}
+
+impl CyclesProfiler {
+    pub fn new(output_dir: PathBuf, syscall_cycles: SyscallCyclesConfig) -> Result<Self> {
+        fs::create_dir_all(&output_dir)
+            .with_context(|| format!("create {}", output_dir.to_string_lossy()))?;
+        Ok(Self {
+            output_dir,
+            syscall_cycles,
+        })
+    }
+
+    /// Write the profile of the block to `<output_dir>/<block_number>.json`.
+    pub fn dump(
+        &self,
+        block_number: u64,
+        block_hash: &H256,
+        profile: &BlockCyclesProfile,
+    ) -> Result<()> {
+        let backends: BTreeMap<_, _> = profile
+            .backends
+            .iter()
+            .map(|(backend, (txs, cycles))| {
+                let stat = json!({ "txs": txs, "execution_cycles": cycles });
+                (backend.clone(), stat)
+            })
+            .collect();
+        let syscalls: BTreeMap<_, _> = profile
+            .syscalls
+            .iter()
+            .map(|(&syscall, &calls)| {
+                let name = syscall_name(syscall)
+                    .map(ToString::to_string)
+                    .unwrap_or_else(|| syscall.to_string());
+                let cycles =
+                    calls.saturating_mul(get_syscall_cycles(syscall, &self.syscall_cycles));
+                (name, json!({ "calls": calls, "cycles": cycles }))
+            })
+            .collect();
+        let content = json!({
+            "block_number": block_number,
+            "block_hash": format!("0x{}", hex::encode(block_hash)),
+            "txs": profile.txs,
+            "backends": backends,
+            "syscalls": syscalls,
+        });
+
+        let path = self.output_dir.join(format!("{}.json", block_number));
+        fs::write(&path, serde_json::to_vec_pretty(&content)?)
+            .with_context(|| format!("write {}", path.to_string_lossy()))
+    }
+}
+
+/// Cycles profiles of txs of a block.
+#[derive(Debug, Default)]
+pub struct BlockCyclesProfile {
+    txs: u64,
+    /// Backend -> (txs, execution cycles).
+    backends: HashMap<String, (u64, u64)>,
+    /// Syscall number -> calls.
+    syscalls: HashMap<u64, u64>,
+}
+
+impl BlockCyclesProfile {
+    pub fn add_tx(&mut self, profile: &CyclesProfile) {
+        self.txs += 1;
+        for (backend, cycles) in &profile.backends {
+            let (txs, total) = self.backends.entry(backend.clone()).or_default();
+            *txs += 1;
+            *total += cycles;
+        }
+        for (syscall, calls) in &profile.syscalls {
+            *self.syscalls.entry(*syscall).or_default() += calls;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gw_config::SyscallCyclesConfig;
+    use gw_types::offchain::CyclesProfile;
+
+    use super::{BlockCyclesProfile, CyclesProfiler};
+
+    #[test]
+    fn test_dump_block_cycles_profile() {
+        const SYS_STORE: u64 = 3101;
+        const UNKNOWN_SYSCALL: u64 = 42;
+
+        let dir = tempfile::tempdir().unwrap();
+        let syscall_cycles = SyscallCyclesConfig::default();
+        let profiler =
+            CyclesProfiler::new(dir.path().join("profile"), syscall_cycles.clone()).unwrap();
+
+        let mut block_profile = BlockCyclesProfile::default();
+        for cycles in [100, 200] {
+            let mut profile = CyclesProfile::default();
+            profile.backends.insert("Sudt".to_string(), cycles);
+            profile.syscalls.insert(SYS_STORE, 2);
+            profile.syscalls.insert(UNKNOWN_SYSCALL, 1);
+            block_profile.add_tx(&profile);
+        }
+        profiler.dump(7, &[1u8; 32], &block_profile).unwrap();
+
+        let content = std::fs::read(dir.path().join("profile").join("7.json")).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&content).unwrap();
+        assert_eq!(json["block_number"], 7);
+        assert_eq!(json["txs"], 2);
+        assert_eq!(json["backends"]["Sudt"]["txs"], 2);
+        assert_eq!(json["backends"]["Sudt"]["execution_cycles"], 300);
+        assert_eq!(json["syscalls"]["sys_store"]["calls"], 4);
+        assert_eq!(
+            json["syscalls"]["sys_store"]["cycles"],
+            4 * syscall_cycles.sys_store_cycles
+        );
+        assert_eq!(json["syscalls"]["42"]["calls"], 2);
+    }
+}
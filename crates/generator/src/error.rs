@@ -41,6 +41,8 @@ pub enum LockAlgorithmError {
     UnknownAccountLock,
     #[error("Invalid transaction args")]
     InvalidTransactionArgs,
+    #[error("Lock verifier unavailable: {0}")]
+    VerifierUnavailable(String),
 }
 
 impl From<LockAlgorithmError> for Error {
@@ -172,6 +174,10 @@ pub enum TransactionError {
     PolyjuiceCreatorIdNotFound,
     #[error("Unknown tx type original exit_code {0}.")]
     UnknownTxType(i8),
+    #[error("Contract deployment is not allowed from account id: {account_id}")]
+    DeploymentNotAllowed { account_id: u32 },
+    #[error("Invalid chain id: expected {expected}, actual {actual}")]
+    InvalidChainId { expected: u64, actual: u64 },
 }
 
 impl From<VMError> for TransactionError {
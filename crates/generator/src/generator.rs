@@ -32,7 +32,7 @@ use gw_jsonrpc_types::{
 use gw_store::{
     state::{
         history::history_state::{HistoryState, RWConfig},
-        state_db::StateDB,
+        state_db::{StateDB, StateTracker},
         traits::JournalDB,
         BlockStateDB,
     },
@@ -44,7 +44,7 @@ use gw_types::{
     core::{AllowedContractType, ChallengeTargetType, ScriptHashType},
     h256::H256Ext,
     h256::*,
-    offchain::{CycleMeter, RunResult},
+    offchain::{CycleMeter, ExecutionTrace, RunResult},
     packed::{
         AccountMerkleState, BlockInfo, ChallengeTarget, DepositInfoVec, L2Block, L2Transaction,
         LogItem, RawL2Block, RawL2Transaction, TxReceipt, WithdrawalReceipt,
@@ -60,16 +60,21 @@ use tracing::{field, instrument};
 
 use crate::{
     account_lock_manage::AccountLockManage,
-    backend_manage::{Backend, BackendManage, BlockConsensus},
+    backend_manage::{Backend, BackendEngine, BackendManage, BlockConsensus},
+    cycles_profile::{BlockCyclesProfile, CyclesProfiler},
     error::{
         AccountError, BlockError, Error, LockAlgorithmError, TransactionError,
         TransactionValidateError, WithdrawalError,
     },
-    syscalls::{L2Syscalls, RunContext},
+    syscalls::{
+        trace::{ReplaySyscalls, TraceReplay, TracedSyscalls},
+        L2Syscalls, RunContext,
+    },
     traits::StateExt,
     typed_transaction::types::TypedRawTransaction,
     types::vm::VMVersion,
     utils::{get_polyjuice_creator_id, get_tx_type},
+    verification::deployment::DeploymentPolicy,
     vm_cost_model::instruction_cycles,
 };
 
@@ -95,9 +100,37 @@ pub enum ApplyBlockResult {
         target: ChallengeTarget,
         error: Error,
     },
+    /// The post state of the block differs from the locally computed one.
+    /// Unlike a challenge, this is not caught by checkpoints, so either the
+    /// block or local execution is broken.
+    StateMismatch(Box<StateMismatch>),
     Error(anyhow::Error),
 }
 
+pub struct StateMismatch {
+    /// Post account state in the block.
+    pub expected: AccountMerkleState,
+    /// Locally computed post account state.
+    pub actual: AccountMerkleState,
+    /// State written by each withdrawal, the deposits and each transaction,
+    /// in the order they are applied.
+    pub state_diffs: Vec<StateDiff>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateDiffSource {
+    Withdrawal(H256),
+    Deposits,
+    Transaction(H256),
+}
+
+pub struct StateDiff {
+    pub source: StateDiffSource,
+    /// Raw key values written, sorted by key.
+    pub writes: Vec<(H256, H256)>,
+    pub post_state: AccountMerkleState,
+}
+
 // Like try! but for ApplyBlockResult.
 macro_rules! try_apply {
     ($e:expr) => {
@@ -178,6 +211,7 @@ pub struct MachineRunArgs<'a, C, S> {
     max_cycles: u64,
     backend: &'a Backend,
     cycles_pool: Option<&'a mut CyclesPool>,
+    trace_syscalls: bool,
 }
 
 pub struct Generator {
@@ -187,6 +221,8 @@ pub struct Generator {
     contract_log_config: ContractLogConfig,
     polyjuice_creator_id: ArcSwapOption<u32>,
     trace_state: bool,
+    cycles_profiler: Option<CyclesProfiler>,
+    deployment_policy: Option<DeploymentPolicy>,
 }
 
 impl Generator {
@@ -203,6 +239,8 @@ impl Generator {
             contract_log_config,
             polyjuice_creator_id: ArcSwapOption::from(None),
             trace_state: false,
+            cycles_profiler: None,
+            deployment_policy: None,
         }
     }
 
@@ -212,6 +250,20 @@ impl Generator {
         Ok(())
     }
 
+    /// Profile cycles of txs and dump them per applied block.
+    pub fn enable_cycles_profile(&mut self, profiler: CyclesProfiler) {
+        self.cycles_profiler = Some(profiler);
+    }
+
+    /// Restrict deployers of Polyjuice contracts in tx verification.
+    pub fn set_deployment_policy(&mut self, policy: DeploymentPolicy) {
+        self.deployment_policy = Some(policy);
+    }
+
+    pub fn deployment_policy(&self) -> Option<&DeploymentPolicy> {
+        self.deployment_policy.as_ref()
+    }
+
     pub fn clone_with_new_backends(&self, backend_manage: BackendManage) -> Self {
         Self {
             backend_manage,
@@ -220,6 +272,8 @@ impl Generator {
             contract_log_config: self.contract_log_config.clone(),
             polyjuice_creator_id: ArcSwapOption::from(self.polyjuice_creator_id.load_full()),
             trace_state: self.trace_state,
+            cycles_profiler: self.cycles_profiler.clone(),
+            deployment_policy: self.deployment_policy.clone(),
         }
     }
 
@@ -251,18 +305,22 @@ impl Generator {
             max_cycles,
             backend,
             mut cycles_pool,
+            trace_syscalls,
         } = args;
 
         let mut context = RunContext::default();
         context.debug_log_buf.reserve(1024);
+        if self.cycles_profiler.is_some() {
+            context.cycles_profile = Some(Default::default());
+        }
+        let mut syscall_trace = trace_syscalls.then(Vec::new);
         let used_cycles;
         let exit_code;
         let org_cycles_pool = cycles_pool.as_mut().map(|p| p.clone());
         {
             let t = Instant::now();
-            let core_machine = VMVersion::V1.init_core_machine(max_cycles);
-            let machine_builder = DefaultMachineBuilder::new(core_machine)
-                .context(L2Syscalls {
+            let syscalls = TracedSyscalls {
+                syscalls: L2Syscalls {
                     chain,
                     state,
                     block_info,
@@ -272,48 +330,60 @@ impl Generator {
                     account_lock_manage: &self.account_lock_manage,
                     cycles_pool: &mut cycles_pool,
                     context: &mut context,
-                })
-                .instruction_cycle_func(instruction_cycles);
-            let default_machine = machine_builder.build();
-
-            #[cfg(has_asm)]
-            let mut machine = ckb_vm::machine::asm::AsmMachine::new(default_machine);
-
-            #[cfg(not(has_asm))]
-            let mut machine = TraceMachine::new(default_machine);
-
-            machine.load_program(&backend.generator, &[])?;
-
-            let maybe_ok = if backend.sys_store_addr.is_some() && self.trace_state {
-                // Use if let && after upgrading rust.
-                let sys_store_addr = backend.sys_store_addr.unwrap();
-                machine.machine.set_running(true);
-                let mut decoder =
-                    build_decoder::<u64>(machine.machine.isa(), machine.machine.version());
-                loop {
-                    if !machine.machine.running() {
-                        break Ok(machine.machine.exit_code());
-                    }
-                    if let Err(e) = machine.machine.step(&mut decoder) {
-                        break Err(e);
-                    }
-                    if *machine.machine.pc() == sys_store_addr {
-                        let [account_id, key_addr, key_len, _value_addr]: [u64; 4] =
-                            machine.machine.registers()[registers::A1..=registers::A4]
-                                .try_into()
-                                .unwrap();
-                        let key = load_bytes(machine.machine.memory_mut(), key_addr, key_len)?;
-                        // This records the key in the current account key map.
-                        build_account_key(account_id as u32, &key);
-                    }
+                },
+                trace: syscall_trace.as_mut(),
+            };
+            let (maybe_ok, execution_cycles) = match &backend.engine {
+                BackendEngine::RiscV => {
+                    let core_machine = VMVersion::V1.init_core_machine(max_cycles);
+                    let machine_builder = DefaultMachineBuilder::new(core_machine)
+                        .context(syscalls)
+                        .instruction_cycle_func(instruction_cycles);
+                    let default_machine = machine_builder.build();
+
+                    #[cfg(has_asm)]
+                    let mut machine = ckb_vm::machine::asm::AsmMachine::new(default_machine);
+
+                    #[cfg(not(has_asm))]
+                    let mut machine = TraceMachine::new(default_machine);
+
+                    machine.load_program(&backend.generator, &[])?;
+
+                    let maybe_ok = if backend.sys_store_addr.is_some() && self.trace_state {
+                        // Use if let && after upgrading rust.
+                        let sys_store_addr = backend.sys_store_addr.unwrap();
+                        machine.machine.set_running(true);
+                        let mut decoder =
+                            build_decoder::<u64>(machine.machine.isa(), machine.machine.version());
+                        loop {
+                            if !machine.machine.running() {
+                                break Ok(machine.machine.exit_code());
+                            }
+                            if let Err(e) = machine.machine.step(&mut decoder) {
+                                break Err(e);
+                            }
+                            if *machine.machine.pc() == sys_store_addr {
+                                let [account_id, key_addr, key_len, _value_addr]: [u64; 4] =
+                                    machine.machine.registers()[registers::A1..=registers::A4]
+                                        .try_into()
+                                        .unwrap();
+                                let key =
+                                    load_bytes(machine.machine.memory_mut(), key_addr, key_len)?;
+                                // This records the key in the current account key map.
+                                build_account_key(account_id as u32, &key);
+                            }
+                        }
+                    } else {
+                        machine.run()
+                    };
+                    (maybe_ok, machine.machine.cycles())
+                }
+                #[cfg(feature = "wasm-backend")]
+                BackendEngine::Wasm(program) => {
+                    crate::wasm_backend::run(program, syscalls, max_cycles)
                 }
-            } else {
-                machine.run()
             };
 
-            let execution_cycles = machine.machine.cycles();
-            drop(machine);
-
             // Subtract tx execution cycles.
             if let Some(cycles_pool) = &mut cycles_pool {
                 if cycles_pool.consume_cycles(execution_cycles).is_none() {
@@ -368,10 +438,105 @@ impl Generator {
         }
         context.cycle_meter.execution = used_cycles;
         context.exit_code = exit_code;
+        if let Some(profile) = context.cycles_profile.as_mut() {
+            let backend_name = format!(
+                "{:?}-0x{}",
+                backend.backend_type,
+                hex::encode(backend.generator_checksum)
+            );
+            *profile.backends.entry(backend_name).or_default() += used_cycles;
+        }
+        context.execution_trace = syscall_trace.map(|syscalls| ExecutionTrace {
+            generator_checksum: backend.generator_checksum,
+            exit_code,
+            syscalls,
+        });
 
         Ok(context)
     }
 
+    /// Replay an execution trace of a backend of `block_number` in a bare VM,
+    /// which serves syscalls from the trace instead of state, like the onchain
+    /// validator which only has the witness. Returns the first divergence of
+    /// the backend from the trace, if any.
+    pub fn replay_execution_trace(
+        &self,
+        block_number: u64,
+        trace: &ExecutionTrace,
+        max_cycles: u64,
+    ) -> Result<TraceReplay> {
+        let (_, block_consensus) = self
+            .backend_manage
+            .get_block_consensus_at_height(block_number)
+            .context("no backends")?;
+        let backend = block_consensus
+            .backends
+            .values()
+            .find(|b| b.generator_checksum == trace.generator_checksum)
+            .with_context(|| {
+                format!(
+                    "backend 0x{} not found at block {}",
+                    hex::encode(trace.generator_checksum),
+                    block_number
+                )
+            })?;
+
+        let mut replay = TraceReplay::default();
+        let syscalls = ReplaySyscalls {
+            trace: &trace.syscalls,
+            replay: &mut replay,
+        };
+        let (maybe_ok, cycles) = match &backend.engine {
+            BackendEngine::RiscV => {
+                let core_machine = VMVersion::V1.init_core_machine(max_cycles);
+                let machine_builder = DefaultMachineBuilder::new(core_machine)
+                    .context(syscalls)
+                    .instruction_cycle_func(instruction_cycles);
+                let default_machine = machine_builder.build();
+
+                #[cfg(has_asm)]
+                let mut machine = ckb_vm::machine::asm::AsmMachine::new(default_machine);
+
+                #[cfg(not(has_asm))]
+                let mut machine = TraceMachine::new(default_machine);
+
+                machine
+                    .load_program(&backend.generator, &[])
+                    .map_err(TransactionError::from)?;
+                let maybe_ok = machine.run();
+                (maybe_ok, machine.machine.cycles())
+            }
+            #[cfg(feature = "wasm-backend")]
+            BackendEngine::Wasm(program) => crate::wasm_backend::run(program, syscalls, max_cycles),
+        };
+
+        replay.cycles = cycles;
+        match maybe_ok {
+            Ok(exit_code) => {
+                replay.exit_code = exit_code;
+                if replay.syscalls < trace.syscalls.len() {
+                    replay.divergence = Some(format!(
+                        "exited after {} of {} traced syscalls",
+                        replay.syscalls,
+                        trace.syscalls.len()
+                    ));
+                } else if exit_code != trace.exit_code {
+                    replay.divergence = Some(format!(
+                        "exit code {}, traced {}",
+                        exit_code, trace.exit_code
+                    ));
+                }
+            }
+            Err(err) => {
+                if replay.divergence.is_none() {
+                    replay.divergence = Some(format!("VM error {}", err));
+                }
+            }
+        }
+
+        Ok(replay)
+    }
+
     /// Check withdrawal request signature
     #[instrument(skip_all, err(Debug))]
     pub fn check_withdrawal_signature<S: State + CodeStore>(
@@ -520,6 +685,12 @@ impl Generator {
         let mut check_signature_total_ms = 0;
         let mut execute_tx_total_ms = 0;
         let mut apply_state_total_duration = Duration::ZERO;
+        let mut cycles_profile = self
+            .cycles_profiler
+            .as_ref()
+            .map(|_| BlockCyclesProfile::default());
+        let mut state_diffs =
+            Vec::with_capacity(args.withdrawals.len() + 1 + args.l2block.transactions().len());
         let mut withdrawal_receipts = Vec::with_capacity(args.withdrawals.len());
         for (wth_idx, request) in args.withdrawals.into_iter().enumerate() {
             debug_assert_eq!(
@@ -567,12 +738,19 @@ impl Generator {
                 state_changes.smt_stat.update_kvs += update_kvs;
             }
 
+            let writes = state.dirty_raw_state();
             let now = Instant::now();
             try_apply!(state.finalise());
             apply_state_total_duration += now.elapsed();
 
+            let post_state = try_apply!(state.calculate_merkle_state());
+            state_diffs.push(StateDiff {
+                source: StateDiffSource::Withdrawal(request.hash()),
+                writes,
+                post_state: post_state.clone(),
+            });
             let withdrawal_receipt = WithdrawalReceipt::new_builder()
-                .post_state(try_apply!(state.calculate_merkle_state()))
+                .post_state(post_state)
                 .build();
             withdrawal_receipts.push(withdrawal_receipt);
 
@@ -618,6 +796,7 @@ impl Generator {
         }
 
         // finalise state
+        let writes = state.dirty_raw_state();
         let now = Instant::now();
         try_apply!(state.finalise());
         apply_state_total_duration += now.elapsed();
@@ -627,6 +806,13 @@ impl Generator {
                 return ApplyBlockResult::Error(err.into());
             }
         };
+        if !writes.is_empty() {
+            state_diffs.push(StateDiff {
+                source: StateDiffSource::Deposits,
+                writes,
+                post_state: prev_txs_state.clone(),
+            });
+        }
 
         // handle transactions
         let mut offchain_used_cycles: u64 = 0;
@@ -730,6 +916,12 @@ impl Generator {
                 }
             };
             execute_tx_total_ms += now.elapsed().as_millis();
+            if let Some((block_profile, profile)) = cycles_profile
+                .as_mut()
+                .zip(run_result.cycles_profile.as_ref())
+            {
+                block_profile.add_tx(profile);
+            }
 
             if self.trace_state {
                 let (events, update_kvs) = get_state_changes(&mut state, track_point);
@@ -751,6 +943,7 @@ impl Generator {
 
             {
                 // finalise tx state
+                let writes = state.dirty_raw_state();
                 let now = Instant::now();
                 try_apply!(state.finalise());
                 apply_state_total_duration += now.elapsed();
@@ -798,6 +991,11 @@ impl Generator {
                     Ok(merkle_state) => merkle_state,
                     Err(err) => return ApplyBlockResult::Error(err.into()),
                 };
+                state_diffs.push(StateDiff {
+                    source: StateDiffSource::Transaction(tx.hash()),
+                    writes,
+                    post_state: post_state.clone(),
+                });
                 let tx_receipt =
                     TxReceipt::build_receipt(tx.witness_hash(), run_result, post_state);
 
@@ -808,18 +1006,14 @@ impl Generator {
 
         // check post state
         if !skip_checkpoint_check {
-            let post_merkle_root: H256 = raw_block.post_account().merkle_root().unpack();
-            let post_merkle_count: u32 = raw_block.post_account().count().unpack();
-            assert_eq!(
-                state.calculate_root().expect("check post root"),
-                post_merkle_root,
-                "wrong block post root"
-            );
-            assert_eq!(
-                state.get_account_count().expect("check post count"),
-                post_merkle_count,
-                "wrong block post account count"
-            );
+            let post_state = try_apply!(state.calculate_merkle_state());
+            if post_state.as_slice() != raw_block.post_account().as_slice() {
+                return ApplyBlockResult::StateMismatch(Box::new(StateMismatch {
+                    expected: raw_block.post_account(),
+                    actual: post_state,
+                    state_diffs,
+                }));
+            }
         }
 
         log::debug!(
@@ -831,6 +1025,12 @@ impl Generator {
 
         state_changes.smt_stat.update_milliseconds = apply_state_total_duration.as_millis() as u64;
 
+        if let Some((profiler, profile)) = self.cycles_profiler.as_ref().zip(cycles_profile) {
+            if let Err(err) = profiler.dump(block_number, &block_hash, &profile) {
+                log::warn!("[cycles profile] dump block {}: {:#}", block_number, err);
+            }
+        }
+
         ApplyBlockResult::Success {
             withdrawal_receipts,
             prev_txs_state,
@@ -846,7 +1046,7 @@ impl Generator {
         block_number: u64,
         state: &S,
         script_hash: &H256,
-    ) -> Option<(&Backend, &BlockConsensus)> {
+    ) -> Option<(Backend, Arc<BlockConsensus>)> {
         log::debug!(
             "load_backend for script_hash: {}",
             hex::encode(script_hash.as_slice())
@@ -863,6 +1063,7 @@ impl Generator {
                     consensus
                         .backends
                         .get(&code_hash)
+                        .cloned()
                         .map(|backend| (backend, consensus))
                 })
             } else {
@@ -876,7 +1077,76 @@ impl Generator {
     }
 
     /// execute a layer2 tx
+    pub fn execute_transaction<S: State + CodeStore + JournalDB, C: ChainView>(
+        &self,
+        chain: &C,
+        state: &mut S,
+        block_info: &BlockInfo,
+        raw_tx: &RawL2Transaction,
+        override_max_cycles: Option<u64>,
+        cycles_pool: Option<&mut CyclesPool>,
+    ) -> Result<RunResult> {
+        self.execute(
+            chain,
+            state,
+            block_info,
+            raw_tx,
+            override_max_cycles,
+            cycles_pool,
+            false,
+            false,
+        )
+    }
+
+    /// Execute the tx as `execute_transaction`, and record keys read and
+    /// written by it, including the backend lookup, to `RunResult::read_keys`
+    /// and `RunResult::write_keys`. Used by speculative execution, see
+    /// [`crate::parallel`].
+    pub fn execute_transaction_with_keys<S: State + CodeStore + JournalDB, C: ChainView>(
+        &self,
+        chain: &C,
+        state: &mut S,
+        block_info: &BlockInfo,
+        raw_tx: &RawL2Transaction,
+        cycles_pool: Option<&mut CyclesPool>,
+    ) -> Result<RunResult> {
+        self.execute(
+            chain,
+            state,
+            block_info,
+            raw_tx,
+            None,
+            cycles_pool,
+            false,
+            true,
+        )
+    }
+
+    /// Execute the tx as `execute_transaction`, and record syscalls of
+    /// backends to `RunResult::execution_traces`, which can be replayed by
+    /// [`Generator::replay_execution_trace`].
+    pub fn trace_transaction<S: State + CodeStore + JournalDB, C: ChainView>(
+        &self,
+        chain: &C,
+        state: &mut S,
+        block_info: &BlockInfo,
+        raw_tx: &RawL2Transaction,
+        override_max_cycles: Option<u64>,
+    ) -> Result<RunResult> {
+        self.execute(
+            chain,
+            state,
+            block_info,
+            raw_tx,
+            override_max_cycles,
+            None,
+            true,
+            false,
+        )
+    }
+
     #[instrument(
+        name = "execute_transaction",
         skip_all,
         err(Debug),
         fields(
@@ -892,7 +1162,8 @@ impl Generator {
             write_data_count = field::Empty,
         )
     )]
-    pub fn execute_transaction<S: State + CodeStore + JournalDB, C: ChainView>(
+    #[allow(clippy::too_many_arguments)]
+    fn execute<S: State + CodeStore + JournalDB, C: ChainView>(
         &self,
         chain: &C,
         state: &mut S,
@@ -900,7 +1171,13 @@ impl Generator {
         raw_tx: &RawL2Transaction,
         override_max_cycles: Option<u64>,
         cycles_pool: Option<&mut CyclesPool>,
+        trace_syscalls: bool,
+        track_keys: bool,
     ) -> Result<RunResult> {
+        // read keys of the backend lookup matter to conflict detection
+        if track_keys {
+            state.set_state_tracker(StateTracker::with_keys());
+        }
         let account_id = raw_tx.to_id().unpack();
         let script_hash = state.get_script_hash(account_id)?;
         let (backend, block_consensus) = self
@@ -911,7 +1188,10 @@ impl Generator {
         let snap = state.snapshot();
         let sender_id: u32 = raw_tx.from_id().unpack();
         let nonce_before = state.get_nonce(sender_id)?;
-        state.set_state_tracker(Default::default());
+        match state.state_tracker() {
+            Some(tracker) if track_keys => tracker.clear_touched(),
+            _ => state.set_state_tracker(Default::default()),
+        }
 
         {
             let span = tracing::Span::current();
@@ -935,11 +1215,12 @@ impl Generator {
             chain,
             state,
             block_info,
-            block_consensus,
+            block_consensus: &block_consensus,
             raw_tx,
             max_cycles,
-            backend,
+            backend: &backend,
             cycles_pool,
+            trace_syscalls,
         };
 
         let run_context = self.machine_run(args).map_err(|err| {
@@ -1028,7 +1309,11 @@ impl Generator {
                 .keys()
                 .cloned()
                 .collect(),
+            read_keys: state_tracker.read_keys().lock().unwrap().drain().collect(),
+            write_keys: state_tracker.write_keys().lock().unwrap().drain().collect(),
             debug_log_buf: run_context.debug_log_buf,
+            cycles_profile: run_context.cycles_profile,
+            execution_traces: run_context.execution_trace.into_iter().collect(),
         };
 
         // Record run result
@@ -3,9 +3,11 @@
 
 pub mod account_lock_manage;
 pub mod backend_manage;
+pub mod cycles_profile;
 pub mod error;
 pub mod generator;
 pub mod genesis;
+pub mod parallel;
 pub mod sudt;
 pub mod syscalls;
 pub mod traits;
@@ -14,6 +16,8 @@ pub mod types;
 pub mod utils;
 pub mod verification;
 pub mod vm_cost_model;
+#[cfg(feature = "wasm-backend")]
+pub mod wasm_backend;
 
 #[cfg(test)]
 mod tests;
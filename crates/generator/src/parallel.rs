@@ -0,0 +1,158 @@
+//! Optimistic parallel execution of txs.
+//!
+//! Txs of a batch are executed concurrently, each against its own clone of the
+//! same state. Speculative results are then applied in order of the batch. A
+//! result is discarded if the tx read keys written by txs applied before it,
+//! the tx must be executed serially instead.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    thread,
+};
+
+use anyhow::Result;
+use gw_store::state::MemStateDB;
+use gw_traits::ChainView;
+use gw_types::{
+    h256::*,
+    offchain::RunResult,
+    packed::{BlockInfo, RawL2Transaction},
+};
+
+use crate::{generator::CyclesPool, Generator};
+
+/// Result of a tx executed against a clone of the state.
+pub struct Speculation {
+    /// The cloned state, changes of the tx are held as dirty state.
+    state: MemStateDB,
+    run_result: RunResult,
+    /// Cycles consumed from the cycles pool.
+    pool_cycles: u64,
+}
+
+impl Speculation {
+    /// Apply changes of the tx to `state`, and consume cycles from the pool.
+    pub fn apply(
+        self,
+        state: &mut MemStateDB,
+        cycles_pool: Option<&mut CyclesPool>,
+    ) -> Result<RunResult> {
+        state.apply_dirty(&self.state)?;
+        if let Some(cycles_pool) = cycles_pool {
+            cycles_pool.consume_cycles(self.pool_cycles);
+        }
+        Ok(self.run_result)
+    }
+}
+
+/// Speculative results of a batch of txs, see
+/// [`Generator::execute_transactions_speculatively`].
+#[derive(Default)]
+pub struct SpeculativeBatch {
+    speculations: HashMap<H256, Speculation>,
+    /// Keys written by txs applied after the batch was executed.
+    written_keys: HashSet<H256>,
+}
+
+impl SpeculativeBatch {
+    /// Take the speculative result of the tx if it's still valid, i.e. the tx
+    /// would execute the same serially: it didn't read keys written by txs
+    /// applied before it, and the cycles pool still has enough cycles for it.
+    pub fn take(
+        &mut self,
+        tx_hash: &H256,
+        cycles_pool: Option<&CyclesPool>,
+    ) -> Option<Speculation> {
+        let speculation = self.speculations.remove(tx_hash)?;
+        let run_result = &speculation.run_result;
+        if run_result
+            .read_keys
+            .iter()
+            .any(|key| self.written_keys.contains(key))
+        {
+            log::debug!(
+                "[parallel execution] tx {} conflicts with previous txs",
+                hex::encode(tx_hash)
+            );
+            return None;
+        }
+        if cycles_pool.map_or(false, |p| p.available_cycles() < speculation.pool_cycles) {
+            return None;
+        }
+        Some(speculation)
+    }
+
+    /// Record keys written by an applied tx, whether it's executed
+    /// speculatively or serially.
+    pub fn record(&mut self, run_result: &RunResult) {
+        self.written_keys
+            .extend(run_result.write_keys.iter().copied());
+    }
+
+    pub fn len(&self) -> usize {
+        self.speculations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.speculations.is_empty()
+    }
+}
+
+impl Generator {
+    /// Execute txs on `workers` threads, each against a clone of `state`,
+    /// which must not be dirty, see [`MemStateDB::apply_dirty`]. Txs failed to
+    /// execute are left out of the batch.
+    pub fn execute_transactions_speculatively<C: ChainView + Sync>(
+        &self,
+        chain: &C,
+        state: &MemStateDB,
+        block_info: &BlockInfo,
+        txs: &[RawL2Transaction],
+        cycles_pool: Option<&CyclesPool>,
+        workers: usize,
+    ) -> SpeculativeBatch {
+        debug_assert!(!state.is_dirty(), "speculative execution on dirty state");
+        let jobs: Vec<_> = txs.iter().map(|tx| (tx, state.clone())).collect();
+        let jobs = Mutex::new(jobs.into_iter());
+        let speculations = Mutex::new(HashMap::with_capacity(txs.len()));
+        thread::scope(|s| {
+            for _ in 0..workers.min(txs.len()) {
+                s.spawn(|| loop {
+                    let job = jobs.lock().unwrap().next();
+                    let (raw_tx, mut state) = match job {
+                        Some(job) => job,
+                        None => break,
+                    };
+                    let mut pool = cycles_pool.cloned();
+                    let run_result = match self.execute_transaction_with_keys(
+                        chain,
+                        &mut state,
+                        block_info,
+                        raw_tx,
+                        pool.as_mut(),
+                    ) {
+                        Ok(run_result) => run_result,
+                        Err(_) => continue,
+                    };
+                    let pool_cycles = cycles_pool.zip(pool).map_or(0, |(before, after)| {
+                        before.available_cycles() - after.available_cycles()
+                    });
+                    let speculation = Speculation {
+                        state,
+                        run_result,
+                        pool_cycles,
+                    };
+                    speculations
+                        .lock()
+                        .unwrap()
+                        .insert(raw_tx.hash(), speculation);
+                });
+            }
+        });
+        SpeculativeBatch {
+            speculations: speculations.into_inner().unwrap(),
+            written_keys: Default::default(),
+        }
+    }
+}
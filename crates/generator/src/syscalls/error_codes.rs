@@ -28,12 +28,16 @@ pub const GW_ERROR_INVALID_ACCOUNT_SCRIPT: i8 = 82;
 pub const GW_ERROR_NOT_FOUND: i8 = 83;
 pub const GW_ERROR_RECOVER: i8 = 84;
 pub const GW_ERROR_ACCOUNT_NOT_FOUND: i8 = 85;
+pub const GW_UNIMPLEMENTED: i8 = 86;
 
 /* SUDT */
 pub const GW_SUDT_ERROR_INSUFFICIENT_BALANCE: i8 = 92i8;
 pub const GW_SUDT_ERROR_AMOUNT_OVERFLOW: i8 = 93i8;
 pub const GW_SUDT_ERROR_UNPERMITTED_ADDRESS: i8 = 94i8;
 
+/* Registry Errors */
+pub const GW_REGISTRY_ERROR_DUPLICATE_MAPPING: i8 = 101i8;
+
 /* Bn Operations Errors*/
 pub const GW_BN_ADD_ERROR: i8 = 100i8;
 pub const GW_BN_MUL_ERROR: i8 = 101i8;
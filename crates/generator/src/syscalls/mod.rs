@@ -3,8 +3,10 @@ use crate::{
     backend_manage::BlockConsensus,
     generator::CyclesPool,
     syscalls::error_codes::{
-        GW_BN_ADD_ERROR, GW_BN_MUL_ERROR, GW_BN_PARIING_ERROR, GW_FATAL_UNKNOWN_ARGS,
+        GW_BN_ADD_ERROR, GW_BN_MUL_ERROR, GW_BN_PARIING_ERROR, GW_FATAL_INVALID_DATA,
+        GW_FATAL_UNKNOWN_ARGS, GW_REGISTRY_ERROR_DUPLICATE_MAPPING,
     },
+    traits::StateExt,
 };
 use ckb_vm::{
     memory::Memory,
@@ -13,6 +15,7 @@ use ckb_vm::{
 };
 use gw_common::{
     blake2b::new_blake2b,
+    builtins::ETH_REGISTRY_ACCOUNT_ID,
     registry_address::RegistryAddress,
     state::{
         build_account_field_key, build_data_hash_key, build_script_hash_to_account_id_key, State,
@@ -24,26 +27,29 @@ use gw_store::state::traits::JournalDB;
 use gw_traits::{ChainView, CodeStore};
 use gw_types::{
     bytes::Bytes,
-    core::ScriptHashType,
+    core::{AllowedEoaType, ScriptHashType},
     h256::*,
-    offchain::CycleMeter,
-    packed::{BlockInfo, LogItem, RawL2Transaction, Script},
+    offchain::{CycleMeter, CyclesProfile, ExecutionTrace},
+    packed::{BlockInfo, LogItem, RawL2Transaction, Script, ScriptVec},
     prelude::*,
 };
 use gw_utils::RollupContext;
-use std::cmp;
+use std::{cmp, collections::HashSet};
 
 use self::error_codes::{
     GW_ERROR_ACCOUNT_NOT_FOUND, GW_ERROR_DUPLICATED_SCRIPT_HASH, GW_ERROR_INVALID_ACCOUNT_SCRIPT,
     GW_ERROR_NOT_FOUND, GW_ERROR_RECOVER, GW_ERROR_UNKNOWN_SCRIPT_CODE_HASH,
-    GW_SUDT_ERROR_UNPERMITTED_ADDRESS, SUCCESS,
+    GW_SUDT_ERROR_UNPERMITTED_ADDRESS, GW_UNIMPLEMENTED, SUCCESS,
 };
 
 pub mod bn;
 pub mod error_codes;
+pub mod trace;
 
 /// Max buffer size: 4MB
 const MAX_BUF_SIZE: usize = 4 * 1024 * 1024;
+/// Max size of an account script, same as `GW_MAX_SCRIPT_SIZE` of gwos.
+const MAX_SCRIPT_SIZE: usize = 256;
 
 /* Constants */
 // Increasing from 25k(ethereum contract code size) to 128k.
@@ -54,6 +60,7 @@ const SYS_CREATE: u64 = 3100;
 const SYS_STORE: u64 = 3101;
 const SYS_LOAD: u64 = 3102;
 const SYS_LOAD_ACCOUNT_SCRIPT: u64 = 3105;
+const SYS_BATCH_CREATE_ETH_ACCOUNTS: u64 = 3106;
 /* Syscall call / return */
 const SYS_SET_RETURN_DATA: u64 = 3201;
 /* Syscall data store / load */
@@ -86,6 +93,8 @@ pub struct RunContext {
     pub return_data: Bytes,
     pub exit_code: i8,
     pub debug_log_buf: Vec<u8>,
+    pub cycles_profile: Option<CyclesProfile>,
+    pub execution_trace: Option<ExecutionTrace>,
 }
 
 impl RunContext {
@@ -130,7 +139,7 @@ fn load_data_h256<Mac: SupportMachine>(machine: &mut Mac, addr: u64) -> Result<H
 }
 
 #[allow(clippy::needless_range_loop)]
-fn load_bytes<Mac: SupportMachine>(
+pub(crate) fn load_bytes<Mac: SupportMachine>(
     machine: &mut Mac,
     addr: u64,
     len: usize,
@@ -172,25 +181,16 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
     fn ecall(&mut self, machine: &mut Mac) -> Result<bool, VMError> {
         let code = machine.registers()[A7].to_u64();
 
-        if let Some(cycles_pool) = self.cycles_pool {
-            let syscall_cycles = Self::get_syscall_cycles(code, cycles_pool.syscall_config());
-            if 0 != syscall_cycles {
-                self.context.cycle_meter.r#virtual = self
-                    .context
-                    .cycle_meter
-                    .r#virtual
-                    .saturating_add(syscall_cycles);
-
-                // Subtract cycles to interrupt execution eariler
-                let execution_and_virtual = machine
-                    .cycles()
-                    .saturating_add(self.context.cycle_meter.r#virtual);
-                if cycles_pool.consume_cycles(syscall_cycles).is_none()
-                    || execution_and_virtual > cycles_pool.limit()
-                {
-                    return Err(VMError::Unexpected("cycles pool limit reached".to_owned()));
-                }
-            }
+        if let Some(profile) = self.context.cycles_profile.as_mut() {
+            *profile.syscalls.entry(code).or_default() += 1;
+        }
+
+        let syscall_cycles = match self.cycles_pool {
+            Some(cycles_pool) => get_syscall_cycles(code, cycles_pool.syscall_config()),
+            None => 0,
+        };
+        if 0 != syscall_cycles {
+            self.consume_virtual_cycles(machine, syscall_cycles)?;
         }
 
         match code {
@@ -254,60 +254,9 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                 }
 
                 // Check script validity
-                {
-                    // check hash type
-                    if script.hash_type() != ScriptHashType::Type.into() {
-                        log::debug!("Invalid deposit account script: unexpected hash_type: Data");
-                        machine
-                            .set_register(A0, Mac::REG::from_i8(GW_ERROR_UNKNOWN_SCRIPT_CODE_HASH));
-                        return Ok(true);
-                    }
-
-                    // check code hash
-                    let is_eoa_account = self
-                        .rollup_context
-                        .rollup_config
-                        .allowed_eoa_type_hashes()
-                        .into_iter()
-                        .any(|type_hash| type_hash.hash() == script.code_hash());
-                    let is_contract_account = self
-                        .rollup_context
-                        .rollup_config
-                        .allowed_contract_type_hashes()
-                        .into_iter()
-                        .any(|type_hash| type_hash.hash() == script.code_hash());
-                    if !is_eoa_account && !is_contract_account {
-                        log::debug!(
-                            "Invalid deposit account script: unknown code_hash: {:?}",
-                            hex::encode(script.code_hash().as_slice())
-                        );
-                        machine
-                            .set_register(A0, Mac::REG::from_i8(GW_ERROR_UNKNOWN_SCRIPT_CODE_HASH));
-                        return Ok(true);
-                    }
-
-                    // check args
-                    let args: Bytes = script.args().unpack();
-                    if args.len() < 32 {
-                        log::debug!(
-                            "Invalid deposit account args, expect len: 32, got: {}",
-                            args.len()
-                        );
-                        machine
-                            .set_register(A0, Mac::REG::from_i8(GW_ERROR_INVALID_ACCOUNT_SCRIPT));
-                        return Ok(true);
-                    }
-                    if &args[..32] != self.rollup_context.rollup_script_hash.as_slice() {
-                        log::debug!(
-                            "Invalid deposit account args, expect rollup_script_hash: {}, got: {}",
-                            hex::encode(self.rollup_context.rollup_script_hash.as_slice()),
-                            hex::encode(&args[..32])
-                        );
-
-                        machine
-                            .set_register(A0, Mac::REG::from_i8(GW_ERROR_INVALID_ACCOUNT_SCRIPT));
-                        return Ok(true);
-                    }
+                if let Some(err_code) = self.check_account_script(&script) {
+                    machine.set_register(A0, Mac::REG::from_i8(err_code));
+                    return Ok(true);
                 }
 
                 // Same logic from State::create_account()
@@ -355,6 +304,125 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                 machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
                 Ok(true)
             }
+            SYS_BATCH_CREATE_ETH_ACCOUNTS => {
+                let scripts_addr = machine.registers()[A0].to_u64();
+                let scripts_len = machine.registers()[A1].to_u64();
+                let account_id_addr = machine.registers()[A2].clone();
+
+                // Let the caller create the accounts one by one if it's not
+                // enabled, or if it would do anything beyond creating and
+                // registering ETH EOA accounts
+                if !self.block_consensus.batch_create_eth_accounts {
+                    machine.set_register(A0, Mac::REG::from_i8(GW_UNIMPLEMENTED));
+                    return Ok(true);
+                }
+                let scripts_data = load_bytes(machine, scripts_addr, scripts_len as usize)?;
+                let scripts = match ScriptVec::from_slice(&scripts_data) {
+                    Ok(scripts) => scripts,
+                    Err(_) => {
+                        log::debug!("syscall error: invalid scripts to batch create");
+                        machine.set_register(A0, Mac::REG::from_i8(GW_FATAL_INVALID_DATA));
+                        return Ok(true);
+                    }
+                };
+                let eth_eoa_type_hashes: Vec<_> =
+                    { self.rollup_context.rollup_config.allowed_eoa_type_hashes() }
+                        .into_iter()
+                        .filter(|type_hash| type_hash.type_() == AllowedEoaType::Eth.into())
+                        .map(|type_hash| type_hash.hash())
+                        .collect();
+                let all_eth_eoa = { scripts.clone() }
+                    .into_iter()
+                    .all(|script| eth_eoa_type_hashes.contains(&script.code_hash()));
+                if !all_eth_eoa || self.get_script_hash(ETH_REGISTRY_ACCOUNT_ID)?.is_zero() {
+                    machine.set_register(A0, Mac::REG::from_i8(GW_UNIMPLEMENTED));
+                    return Ok(true);
+                }
+
+                // Check accounts in order and charge the virtual cycles of
+                // syscalls the meta contract invokes to create and register
+                // them one by one, so the results are the same
+                let mut script_hashes = HashSet::with_capacity(scripts.len());
+                let mut addresses = HashSet::with_capacity(scripts.len());
+                let mut accounts = Vec::with_capacity(scripts.len());
+                for script in scripts.into_iter() {
+                    if script.as_slice().len() > MAX_SCRIPT_SIZE {
+                        machine
+                            .set_register(A0, Mac::REG::from_i8(GW_ERROR_INVALID_ACCOUNT_SCRIPT));
+                        return Ok(true);
+                    }
+
+                    // sys_create
+                    self.consume_syscalls_cycles(machine, &[SYS_LOAD])?;
+                    let script_hash = script.hash();
+                    if !script_hashes.insert(script_hash)
+                        || self.get_account_id_by_script_hash(&script_hash)?.is_some()
+                    {
+                        machine
+                            .set_register(A0, Mac::REG::from_i8(GW_ERROR_DUPLICATED_SCRIPT_HASH));
+                        return Ok(true);
+                    }
+                    self.consume_syscalls_cycles(machine, &[SYS_CREATE])?;
+                    if let Some(err_code) = self.check_account_script(&script) {
+                        machine.set_register(A0, Mac::REG::from_i8(err_code));
+                        return Ok(true);
+                    }
+
+                    // sys_get_script_hash_by_account_id, then
+                    // gw_register_eth_address loads the account script
+                    self.consume_syscalls_cycles(
+                        machine,
+                        &[
+                            SYS_LOAD,
+                            SYS_LOAD,
+                            SYS_LOAD,
+                            SYS_LOAD,
+                            SYS_LOAD,
+                            SYS_LOAD_ACCOUNT_SCRIPT,
+                        ],
+                    )?;
+                    let args: Bytes = script.args().unpack();
+                    if args.len() != 52 {
+                        machine.set_register(A0, Mac::REG::from_i8(GW_FATAL_UNKNOWN_ARGS));
+                        return Ok(true);
+                    }
+
+                    // gw_update_eth_address_register
+                    self.consume_syscalls_cycles(machine, &[SYS_LOAD, SYS_LOAD])?;
+                    let addr = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, args[32..].to_vec());
+                    let is_mapped = self
+                        .state
+                        .get_script_hash_by_registry_address(&addr)
+                        .map_err(|err| {
+                            VMError::Unexpected(format!("get script hash by address: {}", err))
+                        })?
+                        .is_some();
+                    if is_mapped || addresses.contains(&addr) {
+                        machine.set_register(
+                            A0,
+                            Mac::REG::from_i8(GW_REGISTRY_ERROR_DUPLICATE_MAPPING),
+                        );
+                        return Ok(true);
+                    }
+                    self.consume_syscalls_cycles(
+                        machine,
+                        &[SYS_LOAD, SYS_STORE, SYS_LOAD, SYS_STORE],
+                    )?;
+                    addresses.insert(addr.clone());
+                    accounts.push((script, addr));
+                }
+
+                let ids = self.state.batch_create_accounts(accounts).map_err(|err| {
+                    VMError::Unexpected(format!("batch create accounts: {}", err))
+                })?;
+                if let Some(last_id) = ids.last() {
+                    machine
+                        .memory_mut()
+                        .store32(&account_id_addr, &Mac::REG::from_u32(*last_id))?;
+                }
+                machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
+                Ok(true)
+            }
             SYS_LOAD_BLOCKINFO => {
                 let data = self.block_info.as_slice();
                 store_data(machine, data)?;
@@ -689,6 +757,101 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
 }
 
 impl<'a, 'b, S: State, C: ChainView> L2Syscalls<'a, 'b, S, C> {
+    /// Consume virtual cycles from the cycles pool if it's set, interrupt
+    /// execution if the pool or the tx limit is exceeded.
+    fn consume_virtual_cycles<Mac: SupportMachine>(
+        &mut self,
+        machine: &Mac,
+        cycles: u64,
+    ) -> Result<(), VMError> {
+        if let Some(cycles_pool) = self.cycles_pool {
+            self.context.cycle_meter.r#virtual =
+                self.context.cycle_meter.r#virtual.saturating_add(cycles);
+
+            // Subtract cycles to interrupt execution eariler
+            let execution_and_virtual = machine
+                .cycles()
+                .saturating_add(self.context.cycle_meter.r#virtual);
+            if cycles_pool.consume_cycles(cycles).is_none()
+                || execution_and_virtual > cycles_pool.limit()
+            {
+                return Err(VMError::Unexpected("cycles pool limit reached".to_owned()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Consume the fixed virtual cycles of the syscalls, in order.
+    fn consume_syscalls_cycles<Mac: SupportMachine>(
+        &mut self,
+        machine: &Mac,
+        syscalls: &[u64],
+    ) -> Result<(), VMError> {
+        let syscalls_cycles: Vec<u64> = match self.cycles_pool {
+            Some(cycles_pool) => syscalls
+                .iter()
+                .map(|&syscall| get_syscall_cycles(syscall, cycles_pool.syscall_config()))
+                .collect(),
+            None => return Ok(()),
+        };
+        for cycles in syscalls_cycles {
+            if 0 != cycles {
+                self.consume_virtual_cycles(machine, cycles)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the script of an account to create, returns the error code of
+    /// `sys_create` if it's invalid.
+    fn check_account_script(&self, script: &Script) -> Option<i8> {
+        // check hash type
+        if script.hash_type() != ScriptHashType::Type.into() {
+            log::debug!("Invalid deposit account script: unexpected hash_type: Data");
+            return Some(GW_ERROR_UNKNOWN_SCRIPT_CODE_HASH);
+        }
+
+        // check code hash
+        let is_eoa_account = self
+            .rollup_context
+            .rollup_config
+            .allowed_eoa_type_hashes()
+            .into_iter()
+            .any(|type_hash| type_hash.hash() == script.code_hash());
+        let is_contract_account = self
+            .rollup_context
+            .rollup_config
+            .allowed_contract_type_hashes()
+            .into_iter()
+            .any(|type_hash| type_hash.hash() == script.code_hash());
+        if !is_eoa_account && !is_contract_account {
+            log::debug!(
+                "Invalid deposit account script: unknown code_hash: {:?}",
+                hex::encode(script.code_hash().as_slice())
+            );
+            return Some(GW_ERROR_UNKNOWN_SCRIPT_CODE_HASH);
+        }
+
+        // check args
+        let args: Bytes = script.args().unpack();
+        if args.len() < 32 {
+            log::debug!(
+                "Invalid deposit account args, expect len: 32, got: {}",
+                args.len()
+            );
+            return Some(GW_ERROR_INVALID_ACCOUNT_SCRIPT);
+        }
+        if &args[..32] != self.rollup_context.rollup_script_hash.as_slice() {
+            log::debug!(
+                "Invalid deposit account args, expect rollup_script_hash: {}, got: {}",
+                hex::encode(self.rollup_context.rollup_script_hash.as_slice()),
+                hex::encode(&args[..32])
+            );
+            return Some(GW_ERROR_INVALID_ACCOUNT_SCRIPT);
+        }
+        None
+    }
+
     fn get_script_hash(&mut self, id: u32) -> Result<H256, VMError> {
         let value = self
             .state
@@ -738,24 +901,54 @@ impl<'a, 'b, S: State, C: ChainView> L2Syscalls<'a, 'b, S, C> {
         self.context.debug_log_buf.extend_from_slice(&buffer);
         Ok(())
     }
+}
 
-    fn get_syscall_cycles(syscall: u64, cycles_config: &SyscallCyclesConfig) -> u64 {
-        match syscall {
-            SYS_STORE => cycles_config.sys_store_cycles,
-            SYS_LOAD => cycles_config.sys_load_cycles,
-            SYS_CREATE => cycles_config.sys_create_cycles,
-            SYS_LOAD_ACCOUNT_SCRIPT => cycles_config.sys_load_account_script_cycles,
-            SYS_STORE_DATA => cycles_config.sys_store_data_cycles,
-            SYS_LOAD_DATA => cycles_config.sys_load_data_cycles,
-            SYS_GET_BLOCK_HASH => cycles_config.sys_get_block_hash_cycles,
-            SYS_RECOVER_ACCOUNT => cycles_config.sys_recover_account_cycles,
-            SYS_LOG => cycles_config.sys_log_cycles,
-            SYS_BN_ADD => cycles_config.sys_bn_add_cycles,
-            SYS_BN_MUL => cycles_config.sys_bn_mul_cycles,
-            SYS_BN_PAIRING => cycles_config.sys_bn_fixed_pairing_cycles,
-            SYS_SNAPSHOT => cycles_config.sys_snapshot_cycles,
-            SYS_REVERT => cycles_config.sys_revert_cycles,
-            _ => 0,
-        }
+/// Fixed virtual cycles of the syscall.
+pub(crate) fn get_syscall_cycles(syscall: u64, cycles_config: &SyscallCyclesConfig) -> u64 {
+    match syscall {
+        SYS_STORE => cycles_config.sys_store_cycles,
+        SYS_LOAD => cycles_config.sys_load_cycles,
+        SYS_CREATE => cycles_config.sys_create_cycles,
+        SYS_LOAD_ACCOUNT_SCRIPT => cycles_config.sys_load_account_script_cycles,
+        SYS_STORE_DATA => cycles_config.sys_store_data_cycles,
+        SYS_LOAD_DATA => cycles_config.sys_load_data_cycles,
+        SYS_GET_BLOCK_HASH => cycles_config.sys_get_block_hash_cycles,
+        SYS_RECOVER_ACCOUNT => cycles_config.sys_recover_account_cycles,
+        SYS_LOG => cycles_config.sys_log_cycles,
+        SYS_BN_ADD => cycles_config.sys_bn_add_cycles,
+        SYS_BN_MUL => cycles_config.sys_bn_mul_cycles,
+        SYS_BN_PAIRING => cycles_config.sys_bn_fixed_pairing_cycles,
+        SYS_SNAPSHOT => cycles_config.sys_snapshot_cycles,
+        SYS_REVERT => cycles_config.sys_revert_cycles,
+        _ => 0,
     }
 }
+
+pub(crate) fn syscall_name(syscall: u64) -> Option<&'static str> {
+    let name = match syscall {
+        SYS_CREATE => "sys_create",
+        SYS_STORE => "sys_store",
+        SYS_LOAD => "sys_load",
+        SYS_LOAD_ACCOUNT_SCRIPT => "sys_load_account_script",
+        SYS_BATCH_CREATE_ETH_ACCOUNTS => "sys_batch_create_eth_accounts",
+        SYS_SET_RETURN_DATA => "sys_set_return_data",
+        SYS_STORE_DATA => "sys_store_data",
+        SYS_LOAD_DATA => "sys_load_data",
+        SYS_LOAD_ROLLUP_CONFIG => "sys_load_rollup_config",
+        SYS_LOAD_TRANSACTION => "sys_load_transaction",
+        SYS_LOAD_BLOCKINFO => "sys_load_blockinfo",
+        SYS_GET_BLOCK_HASH => "sys_get_block_hash",
+        SYS_PAY_FEE => "sys_pay_fee",
+        SYS_LOG => "sys_log",
+        SYS_RECOVER_ACCOUNT => "sys_recover_account",
+        SYS_BN_ADD => "sys_bn_add",
+        SYS_BN_MUL => "sys_bn_mul",
+        SYS_BN_PAIRING => "sys_bn_pairing",
+        SYS_SNAPSHOT => "sys_snapshot",
+        SYS_REVERT => "sys_revert",
+        SYS_CHECK_SUDT_ADDRESS => "sys_check_sudt_address",
+        DEBUG_PRINT_SYSCALL_NUMBER => "debug_print",
+        _ => return None,
+    };
+    Some(name)
+}
@@ -0,0 +1,221 @@
+//! Syscall traces, to reproduce executions of backends.
+//!
+//! Tracing records each syscall made by a backend: the number, arguments,
+//! input read from the VM memory, memory written by the syscall and the
+//! return value. Replaying runs the backend again and serves syscalls from the
+//! trace, without any state, so a divergence between executions, e.g. of the
+//! generator and the onchain validator, can be located at the first differing
+//! syscall.
+
+use std::cmp;
+
+use ckb_vm::{
+    memory::Memory,
+    registers::{A0, A1, A2, A3, A4, A5, A7},
+    Error as VMError, ExecutionContext, Register, SupportMachine,
+};
+use gw_types::offchain::SyscallTrace;
+
+use super::{
+    error_codes::SUCCESS, load_bytes, syscall_name, SYS_BATCH_CREATE_ETH_ACCOUNTS, SYS_BN_ADD,
+    SYS_BN_MUL, SYS_BN_PAIRING, SYS_CHECK_SUDT_ADDRESS, SYS_CREATE, SYS_GET_BLOCK_HASH, SYS_LOAD,
+    SYS_LOAD_ACCOUNT_SCRIPT, SYS_LOAD_BLOCKINFO, SYS_LOAD_DATA, SYS_LOAD_ROLLUP_CONFIG,
+    SYS_LOAD_TRANSACTION, SYS_LOG, SYS_PAY_FEE, SYS_RECOVER_ACCOUNT, SYS_SET_RETURN_DATA,
+    SYS_SNAPSHOT, SYS_STORE, SYS_STORE_DATA,
+};
+
+/// Result of replaying an execution trace.
+#[derive(Debug, Default)]
+pub struct TraceReplay {
+    pub exit_code: i8,
+    pub cycles: u64,
+    /// Syscalls replayed before exit or divergence.
+    pub syscalls: usize,
+    /// The first divergence from the trace.
+    pub divergence: Option<String>,
+}
+
+/// Records syscalls handled by `syscalls` to `trace`, if it's set.
+pub(crate) struct TracedSyscalls<'t, T> {
+    pub(crate) syscalls: T,
+    pub(crate) trace: Option<&'t mut Vec<SyscallTrace>>,
+}
+
+impl<T: ExecutionContext<Mac>, Mac: SupportMachine> ExecutionContext<Mac>
+    for TracedSyscalls<'_, T>
+{
+    fn ecall(&mut self, machine: &mut Mac) -> Result<bool, VMError> {
+        let trace = match self.trace.as_mut() {
+            Some(trace) => trace,
+            None => return self.syscalls.ecall(machine),
+        };
+
+        let number = machine.registers()[A7].to_u64();
+        let args = syscall_args(machine);
+        let input = read_input(machine, number, &args);
+        let buf_size = buffer_size(machine, number, &args);
+        if !self.syscalls.ecall(machine)? {
+            return Ok(false);
+        }
+
+        let ret = machine.registers()[A0].to_u64();
+        let mut writes = Vec::new();
+        if ret == SUCCESS as u64 {
+            for (addr, len) in written_ranges(machine, number, &args, buf_size)? {
+                writes.push((addr, load_bytes(machine, addr, len as usize)?.into()));
+            }
+        }
+        trace.push(SyscallTrace {
+            number,
+            args,
+            input: input.into(),
+            writes,
+            ret,
+        });
+        Ok(true)
+    }
+}
+
+/// Serves syscalls from a trace instead of state.
+pub(crate) struct ReplaySyscalls<'a> {
+    pub(crate) trace: &'a [SyscallTrace],
+    pub(crate) replay: &'a mut TraceReplay,
+}
+
+impl ReplaySyscalls<'_> {
+    fn diverge(&mut self, divergence: String) -> Result<bool, VMError> {
+        let err = VMError::Unexpected(divergence.clone());
+        self.replay.divergence = Some(divergence);
+        Err(err)
+    }
+}
+
+impl<Mac: SupportMachine> ExecutionContext<Mac> for ReplaySyscalls<'_> {
+    fn ecall(&mut self, machine: &mut Mac) -> Result<bool, VMError> {
+        let number = machine.registers()[A7].to_u64();
+        let args = syscall_args(machine);
+        let index = self.replay.syscalls;
+        let name = syscall_name(number).unwrap_or("unknown syscall");
+
+        let expected = match self.trace.get(index) {
+            Some(expected) => expected,
+            None => {
+                let divergence = format!("#{} {}({}) is not traced", index, name, number);
+                return self.diverge(divergence);
+            }
+        };
+        if expected.number != number || expected.args != args {
+            let divergence = format!(
+                "#{} expected {}({}) with args {:?}, got {}({}) with args {:?}",
+                index,
+                syscall_name(expected.number).unwrap_or("unknown syscall"),
+                expected.number,
+                expected.args,
+                name,
+                number,
+                args
+            );
+            return self.diverge(divergence);
+        }
+        if read_input(machine, number, &args) != expected.input {
+            let divergence = format!("#{} {}({}) input differs", index, name, number);
+            return self.diverge(divergence);
+        }
+
+        for (addr, data) in &expected.writes {
+            machine.memory_mut().store_bytes(*addr, data)?;
+        }
+        machine.set_register(A0, Mac::REG::from_u64(expected.ret));
+        self.replay.syscalls += 1;
+        Ok(true)
+    }
+}
+
+fn syscall_args<Mac: SupportMachine>(machine: &Mac) -> [u64; 6] {
+    let registers = machine.registers();
+    [A0, A1, A2, A3, A4, A5].map(|r| registers[r].to_u64())
+}
+
+/// Buffer size of syscalls returning data, which is overwritten by them.
+pub(crate) fn buffer_size<Mac: SupportMachine>(
+    machine: &mut Mac,
+    number: u64,
+    args: &[u64; 6],
+) -> u64 {
+    if returns_data(number) {
+        let size = machine.memory_mut().load64(&Mac::REG::from_u64(args[1]));
+        size.map_or(0, |size| size.to_u64())
+    } else {
+        0
+    }
+}
+
+/// Syscalls returning data with `store_data`.
+fn returns_data(number: u64) -> bool {
+    matches!(
+        number,
+        SYS_LOAD_BLOCKINFO
+            | SYS_LOAD_TRANSACTION
+            | SYS_LOAD_ACCOUNT_SCRIPT
+            | SYS_LOAD_DATA
+            | SYS_LOAD_ROLLUP_CONFIG
+            | SYS_BN_ADD
+            | SYS_BN_MUL
+            | SYS_BN_PAIRING
+    )
+}
+
+/// Memory read by the syscall, which determines its result together with
+/// the arguments. It's empty if the memory is invalid, the syscall fails on
+/// it either way.
+fn read_input<Mac: SupportMachine>(machine: &mut Mac, number: u64, args: &[u64; 6]) -> Vec<u8> {
+    let [a0, a1, a2, a3, a4, a5] = *args;
+    let ranges = match number {
+        SYS_STORE => vec![(a0, 32), (a1, 32)],
+        SYS_LOAD => vec![(a0, 32)],
+        SYS_SET_RETURN_DATA | SYS_CREATE | SYS_BATCH_CREATE_ETH_ACCOUNTS => vec![(a0, a1)],
+        SYS_STORE_DATA => vec![(a1, a0)],
+        SYS_LOAD_DATA => vec![(a3, 32)],
+        SYS_RECOVER_ACCOUNT => vec![(a2, 32), (a3, a4), (a5, 32)],
+        SYS_LOG => vec![(a3, a2)],
+        SYS_PAY_FEE => vec![(a0, a1), (a3, 32)],
+        SYS_BN_ADD | SYS_BN_MUL | SYS_BN_PAIRING => vec![(a3, a4)],
+        SYS_CHECK_SUDT_ADDRESS => vec![(a0, 20)],
+        _ => vec![],
+    };
+
+    let mut input = Vec::new();
+    for (addr, len) in ranges {
+        match load_bytes(machine, addr, len as usize) {
+            Ok(data) => input.extend(data),
+            Err(_) => return Vec::new(),
+        }
+    }
+    input
+}
+
+/// Memory written by the succeeded syscall.
+pub(crate) fn written_ranges<Mac: SupportMachine>(
+    machine: &mut Mac,
+    number: u64,
+    args: &[u64; 6],
+    buf_size: u64,
+) -> Result<Vec<(u64, u64)>, VMError> {
+    let [a0, a1, a2, ..] = *args;
+    let ranges = match number {
+        _ if returns_data(number) => {
+            let full_size = machine.memory_mut().load64(&Mac::REG::from_u64(a1))?;
+            vec![(a1, 8), (a0, cmp::min(buf_size, full_size.to_u64()))]
+        }
+        SYS_RECOVER_ACCOUNT => {
+            let script_len = machine.memory_mut().load64(&Mac::REG::from_u64(a1))?;
+            vec![(a1, 8), (a0, script_len.to_u64())]
+        }
+        SYS_LOAD => vec![(a1, 32)],
+        SYS_GET_BLOCK_HASH => vec![(a0, 32)],
+        SYS_CREATE | SYS_BATCH_CREATE_ETH_ACCOUNTS => vec![(a2, 4)],
+        SYS_SNAPSHOT => vec![(a0, 4)],
+        _ => vec![],
+    };
+    Ok(ranges)
+}
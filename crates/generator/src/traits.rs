@@ -1,9 +1,19 @@
 use crate::error::{AccountError, DepositError, Error, WithdrawalError};
 use crate::sudt::build_l2_sudt_script;
 use gw_common::ckb_decimal::{CKBCapacity, CKB_DECIMAL_POW_EXP};
+use gw_common::error::Error as StateError;
 use gw_common::registry::context::RegistryContext;
 use gw_common::registry_address::RegistryAddress;
-use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State, CKB_SUDT_SCRIPT_ARGS};
+use gw_common::state::{
+    build_account_field_key, build_account_key, build_registry_address_to_script_hash_key,
+    build_script_hash_to_account_id_key, build_script_hash_to_registry_address_key,
+    GW_ACCOUNT_NONCE_TYPE, GW_ACCOUNT_SCRIPT_HASH_TYPE,
+};
+use gw_common::{
+    builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID},
+    state::State,
+    CKB_SUDT_SCRIPT_ARGS,
+};
 use gw_store::state::traits::JournalDB;
 use gw_traits::CodeStore;
 use gw_types::U256;
@@ -14,10 +24,18 @@ use gw_types::{
     prelude::*,
 };
 use gw_utils::RollupContext;
+use std::collections::HashSet;
 use tracing::instrument;
 
 pub trait StateExt {
     fn create_account_from_script(&mut self, script: Script) -> Result<u32, Error>;
+    /// Create accounts of the scripts and map them to their registry
+    /// addresses in one batch of state updates. Returns ids of created
+    /// accounts.
+    fn batch_create_accounts(
+        &mut self,
+        accounts: Vec<(Script, RegistryAddress)>,
+    ) -> Result<Vec<u32>, Error>;
     fn calculate_merkle_state(&self) -> Result<AccountMerkleState, Error>;
     fn apply_deposit_request(
         &mut self,
@@ -53,6 +71,81 @@ impl<S: State + CodeStore + JournalDB> StateExt for S {
         Ok(id)
     }
 
+    fn batch_create_accounts(
+        &mut self,
+        accounts: Vec<(Script, RegistryAddress)>,
+    ) -> Result<Vec<u32>, Error> {
+        let mut script_hashes = HashSet::with_capacity(accounts.len());
+        for (script, addr) in &accounts {
+            // Godwoken requires account's script using ScriptHashType::Type
+            if script.hash_type() != ScriptHashType::Type.into() {
+                return Err(AccountError::UnknownScript.into());
+            }
+            let script_hash = script.hash();
+            if !script_hashes.insert(script_hash)
+                || self.get_account_id_by_script_hash(&script_hash)?.is_some()
+            {
+                return Err(StateError::DuplicatedScriptHash.into());
+            }
+            // Same as State::mapping_registry_address_to_script_hash()
+            if addr.address.len() != 20 || addr.registry_id != ETH_REGISTRY_ACCOUNT_ID {
+                return Err(StateError::InvalidArgs.into());
+            }
+            if self.get_script_hash_by_registry_address(addr)?.is_some() {
+                return Err(StateError::DuplicatedRegistryAddress.into());
+            }
+        }
+
+        let first_id = self.get_account_count()?;
+        let mut ids = Vec::with_capacity(accounts.len());
+        let mut kvs = Vec::with_capacity(accounts.len() * 5);
+        for (id, (script, addr)) in (first_id..).zip(accounts) {
+            let script_hash = script.hash();
+            // Same keys as State::create_account()
+            let script_hash_to_id_value: H256 = {
+                let mut buf: [u8; 32] = H256::from_u32(id);
+                // the first 4 bytes is id, set exists flag(fifth byte) to 1
+                buf[4] = 1;
+                buf
+            };
+            kvs.push((
+                build_account_field_key(id, GW_ACCOUNT_NONCE_TYPE),
+                H256::zero(),
+            ));
+            kvs.push((
+                build_account_field_key(id, GW_ACCOUNT_SCRIPT_HASH_TYPE),
+                script_hash,
+            ));
+            kvs.push((
+                build_script_hash_to_account_id_key(script_hash.as_slice()),
+                script_hash_to_id_value,
+            ));
+            // Same keys as State::mapping_registry_address_to_script_hash()
+            let mut addr_buf = [0u8; 32];
+            addr.write_to_slice(&mut addr_buf)
+                .expect("write addr to buf");
+            kvs.push((
+                build_account_key(
+                    addr.registry_id,
+                    &build_script_hash_to_registry_address_key(&script_hash),
+                ),
+                addr_buf,
+            ));
+            kvs.push((
+                build_account_key(
+                    addr.registry_id,
+                    &build_registry_address_to_script_hash_key(&addr),
+                ),
+                script_hash,
+            ));
+            self.insert_script(script_hash, script);
+            ids.push(id);
+        }
+        self.update_raw_batch(kvs)?;
+        self.set_account_count(first_id + ids.len() as u32)?;
+        Ok(ids)
+    }
+
     /// return current merkle state
     fn calculate_merkle_state(&self) -> Result<AccountMerkleState, Error> {
         let account_root = self.calculate_root()?;
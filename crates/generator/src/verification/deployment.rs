@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+use gw_common::{builtins::ETH_REGISTRY_ACCOUNT_ID, registry_address::RegistryAddress};
+use gw_config::{DeploymentPolicyConfig, DeploymentPolicyMode, RegistryType};
+
+/// Who can deploy Polyjuice contracts, see `DeploymentPolicyConfig`.
+#[derive(Clone, Debug)]
+pub struct DeploymentPolicy {
+    mode: DeploymentPolicyMode,
+    deployers: HashSet<RegistryAddress>,
+}
+
+impl DeploymentPolicy {
+    /// Returns None if the policy is open.
+    pub fn from_config(config: &DeploymentPolicyConfig) -> Option<Self> {
+        if config.mode == DeploymentPolicyMode::Open {
+            return None;
+        }
+        let deployers = config.deployers.iter().map(|addr| {
+            let registry_id = match addr.address_type {
+                RegistryType::Eth => ETH_REGISTRY_ACCOUNT_ID,
+            };
+            RegistryAddress::new(registry_id, addr.address.as_bytes().to_vec())
+        });
+        Some(Self {
+            mode: config.mode,
+            deployers: deployers.collect(),
+        })
+    }
+
+    pub fn is_allowed(&self, deployer: &RegistryAddress) -> bool {
+        match self.mode {
+            DeploymentPolicyMode::Open => true,
+            DeploymentPolicyMode::Allowlist => self.deployers.contains(deployer),
+            DeploymentPolicyMode::Denylist => !self.deployers.contains(deployer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gw_common::{builtins::ETH_REGISTRY_ACCOUNT_ID, registry_address::RegistryAddress};
+    use gw_config::{
+        DeploymentPolicyConfig, DeploymentPolicyMode, RegistryAddressConfig, RegistryType,
+    };
+    use gw_jsonrpc_types::ckb_jsonrpc_types::JsonBytes;
+
+    use super::DeploymentPolicy;
+
+    #[test]
+    fn test_deployment_policy() {
+        let config = |mode| DeploymentPolicyConfig {
+            mode,
+            deployers: vec![RegistryAddressConfig {
+                address_type: RegistryType::Eth,
+                address: JsonBytes::from_vec(vec![1u8; 20]),
+            }],
+        };
+        let deployer = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![1u8; 20]);
+        let other = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![2u8; 20]);
+
+        assert!(DeploymentPolicy::from_config(&config(DeploymentPolicyMode::Open)).is_none());
+
+        let allowlist = DeploymentPolicy::from_config(&config(DeploymentPolicyMode::Allowlist));
+        let allowlist = allowlist.unwrap();
+        assert!(allowlist.is_allowed(&deployer));
+        assert!(!allowlist.is_allowed(&other));
+
+        let denylist = DeploymentPolicy::from_config(&config(DeploymentPolicyMode::Denylist));
+        let denylist = denylist.unwrap();
+        assert!(!denylist.is_allowed(&deployer));
+        assert!(denylist.is_allowed(&other));
+    }
+}
@@ -0,0 +1,556 @@
+//! Intrinsic validation of layer2 txs.
+//!
+//! Intrinsic checks depend on the tx itself and accounts of the state, but
+//! not on the sender's nonce or balance, so a tx passing them against a
+//! recent state snapshot is expected to pass them again when it's pushed into
+//! mem pool. The RPC checks submitted txs before queuing them, and mem pool
+//! checks them again in [`TransactionVerifier`].
+//!
+//! [`TransactionVerifier`]: super::transaction::TransactionVerifier
+
+use anyhow::Error as AnyError;
+use gw_common::{builtins::ETH_REGISTRY_ACCOUNT_ID, error::Error as StateError, state::State};
+use gw_config::ForkConfig;
+use gw_traits::CodeStore;
+use gw_types::{
+    packed::{L2Transaction, RawL2Transaction},
+    prelude::*,
+    U256,
+};
+use gw_utils::RollupContext;
+use tracing::instrument;
+
+use crate::{
+    error::{AccountError, TransactionError, TransactionValidateError},
+    typed_transaction::types::TypedRawTransaction,
+    utils::get_tx_type,
+};
+
+use super::deployment::DeploymentPolicy;
+
+pub struct IntrinsicVerifier<'a, S> {
+    state: &'a S,
+    rollup_context: &'a RollupContext,
+    polyjuice_creator_id: Option<u32>,
+    fork_config: &'a ForkConfig,
+    deployment_policy: Option<&'a DeploymentPolicy>,
+}
+
+impl<'a, S: State + CodeStore> IntrinsicVerifier<'a, S> {
+    pub fn new(
+        state: &'a S,
+        rollup_context: &'a RollupContext,
+        polyjuice_creator_id: Option<u32>,
+        fork_config: &'a ForkConfig,
+        deployment_policy: Option<&'a DeploymentPolicy>,
+    ) -> Self {
+        Self {
+            state,
+            rollup_context,
+            polyjuice_creator_id,
+            fork_config,
+            deployment_policy,
+        }
+    }
+
+    /// Verify the chain id, tx size, sender's registry address, receivers'
+    /// backends, the fee and Polyjuice specific rules, i.e. gas limit,
+    /// native transfer target and deployment policy.
+    ///
+    /// Returns the cost of the tx, which the sender's balance must cover.
+    /// Notice this function do not perform signature check
+    #[instrument(skip_all, err(Debug))]
+    pub fn verify(
+        &self,
+        tx: &L2Transaction,
+        block_number: u64,
+    ) -> Result<U256, TransactionValidateError> {
+        let raw_tx = tx.raw();
+
+        // Non EIP-155 transaction's chain_id is zero, see the eth lock.
+        let expected_chain_id: u64 = self.rollup_context.rollup_config.chain_id().unpack();
+        let chain_id: u64 = raw_tx.chain_id().unpack();
+        if raw_tx.is_chain_id_protected() && chain_id != expected_chain_id {
+            return Err(TransactionError::InvalidChainId {
+                expected: expected_chain_id,
+                actual: chain_id,
+            }
+            .into());
+        }
+
+        let max_tx_size = self.fork_config.max_tx_size(block_number);
+        if tx.as_slice().len() > max_tx_size {
+            return Err(TransactionError::ExceededMaxTxSize {
+                max_size: max_tx_size,
+                tx_size: tx.as_slice().len(),
+            }
+            .into());
+        }
+
+        // Txs from id zero are sent by senders without accounts, which are
+        // created before the txs are pushed into mem pool.
+        let sender_id: u32 = raw_tx.from_id().unpack();
+        if sender_id != 0 {
+            let sender_script_hash = self.state.get_script_hash(sender_id)?;
+            self.state
+                .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script_hash)?
+                .ok_or(AccountError::RegistryAddressNotFound)?;
+        }
+
+        let tx_type =
+            get_tx_type(self.rollup_context, self.state, &raw_tx).map_err(into_validate_error)?;
+        let typed_tx = TypedRawTransaction::from_tx(raw_tx.clone(), tx_type)
+            .ok_or(AccountError::UnknownScript)?;
+        self.verify_typed_tx(&raw_tx, &typed_tx)?;
+        // reject txs has no cost, these transaction can only be execute without modify state tree
+        let tx_cost = typed_tx.cost().ok_or(TransactionError::NoCost)?;
+        Ok(tx_cost)
+    }
+
+    fn verify_typed_tx(
+        &self,
+        raw_tx: &RawL2Transaction,
+        typed_tx: &TypedRawTransaction,
+    ) -> Result<(), TransactionValidateError> {
+        if let TypedRawTransaction::Polyjuice(tx) = typed_tx {
+            // Intrinsic Gas
+            let p = tx
+                .parser()
+                .ok_or_else(|| TransactionError::IntrinsicGas("parser".into()))?;
+            let intrinsic_gas = tx
+                .intrinsic_gas()
+                .ok_or_else(|| TransactionError::IntrinsicGas("intrinsic gas".into()))?;
+            if p.gas() < intrinsic_gas {
+                return Err(TransactionError::IntrinsicGas(
+                    format!(
+                        "gas < intrinsic_gas, gas: {}, intrinsic gas: {}",
+                        p.gas(),
+                        intrinsic_gas
+                    )
+                    .into(),
+                )
+                .into());
+            }
+            // Native token transfer
+            if p.is_native_transfer() {
+                if self.polyjuice_creator_id.is_none() {
+                    return Err(TransactionError::PolyjuiceCreatorIdNotFound.into());
+                }
+                // Verify to_id is CREATOR_ID
+                let to_id = raw_tx.to_id().unpack();
+                if Some(to_id) != self.polyjuice_creator_id {
+                    return Err(TransactionError::NativeTransferInvalidToId(to_id).into());
+                }
+            }
+            // Contract deployment
+            if p.is_create() {
+                self.verify_deployer(raw_tx)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn verify_deployer(&self, raw_tx: &RawL2Transaction) -> Result<(), TransactionValidateError> {
+        let policy = match self.deployment_policy {
+            Some(policy) => policy,
+            None => return Ok(()),
+        };
+        let sender_id: u32 = raw_tx.from_id().unpack();
+        let sender_script_hash = self.state.get_script_hash(sender_id)?;
+        let sender_address = self
+            .state
+            .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script_hash)?
+            .ok_or(AccountError::RegistryAddressNotFound)?;
+        if !policy.is_allowed(&sender_address) {
+            return Err(TransactionError::DeploymentNotAllowed {
+                account_id: sender_id,
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Errors of getting tx types.
+fn into_validate_error(err: AnyError) -> TransactionValidateError {
+    let err = match err.downcast::<TransactionError>() {
+        Ok(err) => return err.into(),
+        Err(err) => err,
+    };
+    let err = match err.downcast::<AccountError>() {
+        Ok(err) => return err.into(),
+        Err(err) => err,
+    };
+    err.downcast::<StateError>().expect("tx error").into()
+}
+
+#[cfg(test)]
+mod tests {
+    use gw_common::{
+        builtins::ETH_REGISTRY_ACCOUNT_ID, registry_address::RegistryAddress, state::State,
+    };
+    use gw_config::{
+        DeploymentPolicyConfig, DeploymentPolicyMode, ForkConfig, RegistryAddressConfig,
+        RegistryType,
+    };
+    use gw_jsonrpc_types::ckb_jsonrpc_types::JsonBytes;
+    use gw_smt::smt::{SMT, SMTH256};
+    use gw_store::{
+        smt::smt_store::SMTStateStore,
+        state::{
+            overlay::{mem_state::MemStateTree, mem_store::MemStore},
+            MemStateDB,
+        },
+        Store,
+    };
+    use gw_types::{
+        bytes::Bytes,
+        core::{AllowedContractType, AllowedEoaType, ScriptHashType},
+        packed::{AllowedTypeHash, L2Transaction, RawL2Transaction, RollupConfig, Script},
+        prelude::*,
+        U256,
+    };
+    use gw_utils::RollupContext;
+
+    use super::IntrinsicVerifier;
+    use crate::{
+        error::{AccountError, TransactionError, TransactionValidateError},
+        traits::StateExt,
+        verification::deployment::DeploymentPolicy,
+    };
+
+    const CHAIN_ID: u64 = 42;
+    const META_CODE_HASH: [u8; 32] = [1u8; 32];
+    const SUDT_CODE_HASH: [u8; 32] = [2u8; 32];
+    const POLYJUICE_CODE_HASH: [u8; 32] = [3u8; 32];
+    const ETH_LOCK_CODE_HASH: [u8; 32] = [4u8; 32];
+    const UNKNOWN_CODE_HASH: [u8; 32] = [5u8; 32];
+    const SENDER_ETH_ADDRESS: [u8; 20] = [9u8; 20];
+    // EVMC call kinds
+    const CALL: u8 = 0;
+    const CREATE: u8 = 3;
+
+    struct Context {
+        _store: Store,
+        state: MemStateDB,
+        rollup_context: RollupContext,
+        meta_id: u32,
+        polyjuice_creator_id: u32,
+        contract_id: u32,
+        sender_id: u32,
+        unregistered_sender_id: u32,
+    }
+
+    impl Context {
+        fn setup() -> Self {
+            let store = Store::open_tmp().unwrap();
+            let mut state = {
+                let smt = SMT::new(
+                    SMTH256::zero(),
+                    SMTStateStore::new(MemStore::new(store.get_snapshot())),
+                );
+                MemStateDB::new(MemStateTree::new(smt, 0))
+            };
+            let rollup_config = RollupConfig::new_builder()
+                .chain_id(CHAIN_ID.pack())
+                .allowed_contract_type_hashes(
+                    vec![
+                        AllowedTypeHash::new(AllowedContractType::Meta, META_CODE_HASH),
+                        AllowedTypeHash::new(AllowedContractType::Sudt, SUDT_CODE_HASH),
+                        AllowedTypeHash::new(AllowedContractType::Polyjuice, POLYJUICE_CODE_HASH),
+                    ]
+                    .pack(),
+                )
+                .allowed_eoa_type_hashes(
+                    vec![AllowedTypeHash::new(
+                        AllowedEoaType::Eth,
+                        ETH_LOCK_CODE_HASH,
+                    )]
+                    .pack(),
+                )
+                .build();
+            let rollup_context = RollupContext {
+                rollup_config,
+                rollup_script_hash: [42u8; 32],
+                fork_config: ForkConfig::default(),
+            };
+
+            let mut create = |code_hash: [u8; 32], args: &[u8]| {
+                let script = Script::new_builder()
+                    .code_hash(code_hash.pack())
+                    .hash_type(ScriptHashType::Type.into())
+                    .args(Bytes::copy_from_slice(args).pack())
+                    .build();
+                state.create_account_from_script(script).unwrap()
+            };
+            let meta_id = create(META_CODE_HASH, &[0u8; 32]);
+            create(SUDT_CODE_HASH, &[1u8; 32]);
+            let registry_id = create(UNKNOWN_CODE_HASH, &[]);
+            assert_eq!(registry_id, ETH_REGISTRY_ACCOUNT_ID);
+            let polyjuice_creator_id = create(POLYJUICE_CODE_HASH, &[0u8; 36]);
+            let contract_id = create(POLYJUICE_CODE_HASH, &[1u8; 56]);
+            let sender_args = [&[42u8; 32][..], &SENDER_ETH_ADDRESS].concat();
+            let sender_id = create(ETH_LOCK_CODE_HASH, &sender_args);
+            let unregistered_sender_args = [&[42u8; 32][..], &[10u8; 20]].concat();
+            let unregistered_sender_id = create(ETH_LOCK_CODE_HASH, &unregistered_sender_args);
+            let unknown_id = create(UNKNOWN_CODE_HASH, &[1u8; 32]);
+            assert_eq!(unknown_id, unregistered_sender_id + 1);
+
+            let sender_script_hash = state.get_script_hash(sender_id).unwrap();
+            state
+                .mapping_registry_address_to_script_hash(sender_address(), sender_script_hash)
+                .unwrap();
+
+            Self {
+                _store: store,
+                state,
+                rollup_context,
+                meta_id,
+                polyjuice_creator_id,
+                contract_id,
+                sender_id,
+                unregistered_sender_id,
+            }
+        }
+
+        fn verify(&self, tx: &L2Transaction) -> Result<U256, TransactionValidateError> {
+            self.verify_with_policy(tx, None)
+        }
+
+        fn verify_with_policy(
+            &self,
+            tx: &L2Transaction,
+            deployment_policy: Option<&DeploymentPolicy>,
+        ) -> Result<U256, TransactionValidateError> {
+            IntrinsicVerifier::new(
+                &self.state,
+                &self.rollup_context,
+                Some(self.polyjuice_creator_id),
+                &self.rollup_context.fork_config,
+                deployment_policy,
+            )
+            .verify(tx, 0)
+        }
+    }
+
+    fn sender_address() -> RegistryAddress {
+        RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, SENDER_ETH_ADDRESS.to_vec())
+    }
+
+    /// Polyjuice args, with the native transfer target `to_address` if any.
+    fn polyjuice_args(
+        call_kind: u8,
+        gas: u64,
+        gas_price: u128,
+        data: &[u8],
+        to_address: Option<[u8; 20]>,
+    ) -> Bytes {
+        let mut args = b"\xFF\xFF\xFFPOLY".to_vec();
+        args.push(call_kind);
+        args.extend_from_slice(&gas.to_le_bytes());
+        args.extend_from_slice(&gas_price.to_le_bytes());
+        args.extend_from_slice(&0u128.to_le_bytes());
+        args.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        args.extend_from_slice(data);
+        if let Some(to_address) = to_address {
+            args.extend_from_slice(&to_address);
+        }
+        args.into()
+    }
+
+    fn build_tx(chain_id: u64, from_id: u32, to_id: u32, args: Bytes) -> L2Transaction {
+        let raw = RawL2Transaction::new_builder()
+            .chain_id(chain_id.pack())
+            .from_id(from_id.pack())
+            .to_id(to_id.pack())
+            .args(args.pack())
+            .build();
+        L2Transaction::new_builder().raw(raw).build()
+    }
+
+    fn polyjuice_call(ctx: &Context, gas: u64, gas_price: u128) -> L2Transaction {
+        let args = polyjuice_args(CALL, gas, gas_price, &[], None);
+        build_tx(CHAIN_ID, ctx.sender_id, ctx.contract_id, args)
+    }
+
+    fn tx_error(err: TransactionError) -> Result<U256, TransactionValidateError> {
+        Err(err.into())
+    }
+
+    #[test]
+    fn test_valid_tx() {
+        let ctx = Context::setup();
+        let tx = polyjuice_call(&ctx, 21000, 2);
+        assert_eq!(ctx.verify(&tx), Ok(U256::from(42000u64)));
+
+        // Txs without chain id protection are supported for compatibility.
+        let args = polyjuice_args(CALL, 21000, 2, &[], None);
+        let tx = build_tx(0, ctx.sender_id, ctx.contract_id, args);
+        assert_eq!(ctx.verify(&tx), Ok(U256::from(42000u64)));
+    }
+
+    #[test]
+    fn test_chain_id() {
+        let ctx = Context::setup();
+        let args = polyjuice_args(CALL, 21000, 1, &[], None);
+        let tx = build_tx(CHAIN_ID + 1, ctx.sender_id, ctx.contract_id, args);
+        let err = TransactionError::InvalidChainId {
+            expected: CHAIN_ID,
+            actual: CHAIN_ID + 1,
+        };
+        assert_eq!(ctx.verify(&tx), tx_error(err));
+    }
+
+    #[test]
+    fn test_tx_size() {
+        let ctx = Context::setup();
+        let max_size = ctx.rollup_context.fork_config.max_tx_size(0);
+        let data = vec![1u8; max_size];
+        let args = polyjuice_args(CALL, u64::MAX, 1, &data, None);
+        let tx = build_tx(CHAIN_ID, ctx.sender_id, ctx.contract_id, args);
+        let err = TransactionError::ExceededMaxTxSize {
+            max_size,
+            tx_size: tx.as_slice().len(),
+        };
+        assert_eq!(ctx.verify(&tx), tx_error(err));
+    }
+
+    #[test]
+    fn test_registry_existence() {
+        let ctx = Context::setup();
+        let args = polyjuice_args(CALL, 21000, 1, &[], None);
+        let tx = build_tx(CHAIN_ID, ctx.unregistered_sender_id, ctx.contract_id, args);
+        assert_eq!(
+            ctx.verify(&tx),
+            Err(AccountError::RegistryAddressNotFound.into())
+        );
+
+        // Senders of txs from id zero have no account yet.
+        let args = polyjuice_args(CALL, 21000, 1, &[], None);
+        let tx = build_tx(CHAIN_ID, 0, ctx.contract_id, args);
+        assert_eq!(ctx.verify(&tx), Ok(U256::from(21000u64)));
+    }
+
+    #[test]
+    fn test_receiver() {
+        let ctx = Context::setup();
+        let args = polyjuice_args(CALL, 21000, 1, &[], None);
+        let not_exist_id = ctx.unregistered_sender_id + 100;
+        let tx = build_tx(CHAIN_ID, ctx.sender_id, not_exist_id, args.clone());
+        assert_eq!(
+            ctx.verify(&tx),
+            tx_error(TransactionError::ScriptHashNotFound)
+        );
+
+        let unknown_id = ctx.unregistered_sender_id + 1;
+        let tx = build_tx(CHAIN_ID, ctx.sender_id, unknown_id, args);
+        let script_hash = ctx.state.get_script_hash(unknown_id).unwrap();
+        let err = TransactionError::BackendNotFound { script_hash };
+        assert_eq!(ctx.verify(&tx), tx_error(err));
+    }
+
+    #[test]
+    fn test_gas_limit() {
+        let ctx = Context::setup();
+        let tx = polyjuice_call(&ctx, 20999, 1);
+        assert!(matches!(
+            ctx.verify(&tx),
+            Err(TransactionValidateError::Transaction(
+                TransactionError::IntrinsicGas(_)
+            ))
+        ));
+
+        // Creating contracts and data cost more intrinsic gas.
+        let args = polyjuice_args(CREATE, 53000 + 16 + 4 - 1, 1, &[1, 0], None);
+        let tx = build_tx(CHAIN_ID, ctx.sender_id, ctx.polyjuice_creator_id, args);
+        assert!(matches!(
+            ctx.verify(&tx),
+            Err(TransactionValidateError::Transaction(
+                TransactionError::IntrinsicGas(_)
+            ))
+        ));
+        let args = polyjuice_args(CREATE, 53000 + 16 + 4, 1, &[1, 0], None);
+        let tx = build_tx(CHAIN_ID, ctx.sender_id, ctx.polyjuice_creator_id, args);
+        assert_eq!(ctx.verify(&tx), Ok(U256::from(53020u64)));
+
+        // Invalid Polyjuice args
+        let tx = build_tx(CHAIN_ID, ctx.sender_id, ctx.contract_id, Bytes::new());
+        assert_eq!(
+            ctx.verify(&tx),
+            tx_error(TransactionError::IntrinsicGas("parser".into()))
+        );
+    }
+
+    #[test]
+    fn test_fee() {
+        let ctx = Context::setup();
+        // Polyjuice txs may be free, e.g. gasless txs.
+        let tx = polyjuice_call(&ctx, 21000, 0);
+        assert_eq!(ctx.verify(&tx), Ok(U256::zero()));
+
+        // Invalid meta contract args, which have no fee
+        let tx = build_tx(CHAIN_ID, ctx.sender_id, ctx.meta_id, Bytes::new());
+        assert_eq!(ctx.verify(&tx), tx_error(TransactionError::NoCost));
+    }
+
+    #[test]
+    fn test_native_transfer() {
+        let ctx = Context::setup();
+        let args = polyjuice_args(CALL, 21000, 1, &[], Some([1u8; 20]));
+        let tx = build_tx(
+            CHAIN_ID,
+            ctx.sender_id,
+            ctx.polyjuice_creator_id,
+            args.clone(),
+        );
+        assert_eq!(ctx.verify(&tx), Ok(U256::from(21000u64)));
+
+        let tx = build_tx(CHAIN_ID, ctx.sender_id, ctx.contract_id, args.clone());
+        let err = TransactionError::NativeTransferInvalidToId(ctx.contract_id);
+        assert_eq!(ctx.verify(&tx), tx_error(err));
+
+        let tx = build_tx(CHAIN_ID, ctx.sender_id, ctx.polyjuice_creator_id, args);
+        let result = IntrinsicVerifier::new(
+            &ctx.state,
+            &ctx.rollup_context,
+            None,
+            &ctx.rollup_context.fork_config,
+            None,
+        )
+        .verify(&tx, 0);
+        assert_eq!(
+            result,
+            tx_error(TransactionError::PolyjuiceCreatorIdNotFound)
+        );
+    }
+
+    #[test]
+    fn test_deployment_policy() {
+        let ctx = Context::setup();
+        let args = polyjuice_args(CREATE, 53000, 1, &[], None);
+        let tx = build_tx(CHAIN_ID, ctx.sender_id, ctx.polyjuice_creator_id, args);
+        let policy = |mode| {
+            let config = DeploymentPolicyConfig {
+                mode,
+                deployers: vec![RegistryAddressConfig {
+                    address_type: RegistryType::Eth,
+                    address: JsonBytes::from_vec(SENDER_ETH_ADDRESS.to_vec()),
+                }],
+            };
+            DeploymentPolicy::from_config(&config).unwrap()
+        };
+
+        let allowlist = policy(DeploymentPolicyMode::Allowlist);
+        assert!(ctx.verify_with_policy(&tx, Some(&allowlist)).is_ok());
+        let denylist = policy(DeploymentPolicyMode::Denylist);
+        let err = TransactionError::DeploymentNotAllowed {
+            account_id: ctx.sender_id,
+        };
+        assert_eq!(ctx.verify_with_policy(&tx, Some(&denylist)), tx_error(err));
+
+        // Calls are not restricted.
+        let tx = polyjuice_call(&ctx, 21000, 1);
+        assert!(ctx.verify_with_policy(&tx, Some(&denylist)).is_ok());
+    }
+}
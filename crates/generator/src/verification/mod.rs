@@ -1,2 +1,4 @@
+pub mod deployment;
+pub mod intrinsic;
 pub mod transaction;
 pub mod withdrawal;
@@ -4,21 +4,20 @@ use gw_common::{
 };
 use gw_config::ForkConfig;
 use gw_traits::CodeStore;
-use gw_types::{packed::L2Transaction, prelude::*};
+use gw_types::{packed::L2Transaction, prelude::*, U256};
 use gw_utils::RollupContext;
 use tracing::instrument;
 
-use crate::{
-    error::{AccountError, TransactionError, TransactionValidateError},
-    typed_transaction::types::TypedRawTransaction,
-    utils::get_tx_type,
-};
+use crate::error::{AccountError, TransactionError, TransactionValidateError};
+
+use super::{deployment::DeploymentPolicy, intrinsic::IntrinsicVerifier};
 
 pub struct TransactionVerifier<'a, S> {
     state: &'a S,
     rollup_context: &'a RollupContext,
     polyjuice_creator_id: Option<u32>,
     fork_config: &'a ForkConfig,
+    deployment_policy: Option<&'a DeploymentPolicy>,
 }
 
 impl<'a, S: State + CodeStore> TransactionVerifier<'a, S> {
@@ -27,12 +26,14 @@ impl<'a, S: State + CodeStore> TransactionVerifier<'a, S> {
         rollup_context: &'a RollupContext,
         polyjuice_creator_id: Option<u32>,
         fork_config: &'a ForkConfig,
+        deployment_policy: Option<&'a DeploymentPolicy>,
     ) -> Self {
         Self {
             state,
             rollup_context,
             polyjuice_creator_id,
             fork_config,
+            deployment_policy,
         }
     }
     /// verify transaction
@@ -42,20 +43,28 @@ impl<'a, S: State + CodeStore> TransactionVerifier<'a, S> {
         &self,
         tx: &L2Transaction,
         block_number: u64,
+    ) -> Result<(), TransactionValidateError> {
+        let tx_cost = IntrinsicVerifier::new(
+            self.state,
+            self.rollup_context,
+            self.polyjuice_creator_id,
+            self.fork_config,
+            self.deployment_policy,
+        )
+        .verify(tx, block_number)?;
+        self.verify_nonce_and_balance(tx, tx_cost)
+    }
+
+    /// Verify nonce and balance of a tx which already passed the intrinsic
+    /// checks, `tx_cost` is returned by [`IntrinsicVerifier::verify`].
+    pub fn verify_nonce_and_balance(
+        &self,
+        tx: &L2Transaction,
+        tx_cost: U256,
     ) -> Result<(), TransactionValidateError> {
         let raw_tx = tx.raw();
         let sender_id: u32 = raw_tx.from_id().unpack();
 
-        // check tx size
-        let max_tx_size = self.fork_config.max_tx_size(block_number);
-        if tx.as_slice().len() > max_tx_size {
-            return Err(TransactionError::ExceededMaxTxSize {
-                max_size: max_tx_size,
-                tx_size: tx.as_slice().len(),
-            }
-            .into());
-        }
-
         // verify nonce
         let account_nonce: u32 = self.state.get_nonce(sender_id)?;
         let nonce: u32 = raw_tx.nonce().unpack();
@@ -78,50 +87,9 @@ impl<'a, S: State + CodeStore> TransactionVerifier<'a, S> {
         let balance = self
             .state
             .get_sudt_balance(CKB_SUDT_ACCOUNT_ID, &sender_address)?;
-        let tx_type = get_tx_type(self.rollup_context, self.state, &tx.raw())
-            .map_err(|err| err.downcast::<TransactionError>().expect("tx error"))?;
-        let typed_tx =
-            TypedRawTransaction::from_tx(tx.raw(), tx_type).ok_or(AccountError::UnknownScript)?;
-        // reject txs has no cost, these transaction can only be execute without modify state tree
-        let tx_cost = typed_tx
-            .cost()
-            .map(Into::into)
-            .ok_or(TransactionError::NoCost)?;
         if balance < tx_cost {
             return Err(TransactionError::InsufficientBalance.into());
         }
-        if let TypedRawTransaction::Polyjuice(tx) = typed_tx {
-            // Intrinsic Gas
-            let p = tx
-                .parser()
-                .ok_or_else(|| TransactionError::IntrinsicGas("parser".into()))?;
-            let intrinsic_gas = tx
-                .intrinsic_gas()
-                .ok_or_else(|| TransactionError::IntrinsicGas("intrinsic gas".into()))?;
-            if p.gas() < intrinsic_gas {
-                return Err(TransactionError::IntrinsicGas(
-                    format!(
-                        "gas < intrinsic_gas, gas: {}, intrinsic gas: {}",
-                        p.gas(),
-                        intrinsic_gas
-                    )
-                    .into(),
-                )
-                .into());
-            }
-            // Native token transfer
-            if p.is_native_transfer() {
-                if self.polyjuice_creator_id.is_none() {
-                    return Err(TransactionError::PolyjuiceCreatorIdNotFound.into());
-                }
-                // Verify to_id is CREATOR_ID
-                let to_id = raw_tx.to_id().unpack();
-                if Some(to_id) != self.polyjuice_creator_id {
-                    return Err(TransactionError::NativeTransferInvalidToId(to_id).into());
-                }
-            }
-        }
-
         Ok(())
     }
 }
@@ -0,0 +1,191 @@
+//! Experimental engine running backends compiled to wasm with wasmtime.
+//!
+//! This is for prototyping non-RISC-V backends off-chain. The onchain
+//! validators are still RISC-V programs, so a wasm backend can't be
+//! challenged. Wasm backends are only accepted in debug backend forks, which
+//! execute txs for debug RPCs, and are rejected in consensus backend forks.
+//!
+//! ABI of a wasm backend:
+//!
+//! - It exports its linear memory as `memory` and the entry `gw_main() -> i32`,
+//!   which returns the exit code.
+//! - It imports `env.gw_syscall(number, a0, a1, a2, a3, a4, a5: i64) -> i64`.
+//!   Numbers and arguments are the same as the RISC-V syscalls, with pointers
+//!   being offsets in the linear memory, and size fields being u64. Syscall 93
+//!   exits with the exit code in `a0`.
+//!
+//! Syscalls are served by the same [`ExecutionContext`] as the RISC-V engine,
+//! on a mirror machine which has a copy of the linear memory. Wasm fuel is
+//! used as execution cycles.
+
+use anyhow::{anyhow, Result};
+use ckb_vm::{
+    memory::{Memory, RISCV_MAX_MEMORY},
+    registers::{A0, A1, A2, A3, A4, A5, A7},
+    CoreMachine, DefaultCoreMachine, Error as VMError, ExecutionContext, Register, SparseMemory,
+    SupportMachine,
+};
+use lazy_static::lazy_static;
+use wasmtime::{Caller, Config, Engine, Linker, Module, Store, Trap};
+
+use crate::{
+    syscalls::{
+        error_codes::SUCCESS,
+        load_bytes,
+        trace::{buffer_size, written_ranges},
+    },
+    types::vm::VMVersion,
+};
+
+const SYS_EXIT: u64 = 93;
+
+/// Machine serving syscalls of wasm backends.
+pub type MirrorMachine = DefaultCoreMachine<u64, SparseMemory<u64>>;
+
+lazy_static! {
+    static ref ENGINE: Engine = {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        Engine::new(&config).expect("wasm engine")
+    };
+}
+
+/// Compiled wasm backend.
+#[derive(Clone)]
+pub struct WasmProgram {
+    module: Module,
+}
+
+impl WasmProgram {
+    pub fn compile(program: &[u8]) -> Result<Self> {
+        let module = Module::new(&ENGINE, program)?;
+        Ok(Self { module })
+    }
+}
+
+struct HostState {
+    // Lifetime erased, see `run`.
+    context: &'static mut dyn ExecutionContext<MirrorMachine>,
+    mirror: MirrorMachine,
+    exit_code: Option<i8>,
+    error: Option<VMError>,
+}
+
+/// Run the wasm backend with at most `max_cycles` fuel, returns the exit
+/// code and the consumed fuel like running a RISC-V machine.
+pub fn run<Ctx: ExecutionContext<MirrorMachine>>(
+    program: &WasmProgram,
+    mut context: Ctx,
+    max_cycles: u64,
+) -> (Result<i8, VMError>, u64) {
+    let context: &mut dyn ExecutionContext<MirrorMachine> = &mut context;
+    // SAFETY: `store` holding the context is dropped at the end of this
+    // function, so the context is never accessed after it's dropped.
+    let context: &'static mut dyn ExecutionContext<MirrorMachine> =
+        unsafe { std::mem::transmute(context) };
+    let version = VMVersion::V1;
+    let mut store = Store::new(
+        &ENGINE,
+        HostState {
+            context,
+            mirror: MirrorMachine::new(version.vm_isa(), version.vm_version(), max_cycles),
+            exit_code: None,
+            error: None,
+        },
+    );
+
+    let result = instantiate_and_run(program, &mut store, max_cycles);
+    let cycles = store.fuel_consumed().unwrap_or_default();
+    let state = store.data_mut();
+    let result = match (state.error.take(), state.exit_code) {
+        (Some(err), _) => Err(err),
+        (None, Some(exit_code)) => Ok(exit_code),
+        (None, None) => result.map_err(|err| match err.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => VMError::CyclesExceeded,
+            _ => VMError::Unexpected(format!("wasm backend: {:?}", err)),
+        }),
+    };
+    (result, cycles)
+}
+
+fn instantiate_and_run(
+    program: &WasmProgram,
+    store: &mut Store<HostState>,
+    max_cycles: u64,
+) -> Result<i8> {
+    store.add_fuel(max_cycles)?;
+    let mut linker = Linker::new(&ENGINE);
+    linker.func_wrap("env", "gw_syscall", gw_syscall)?;
+    let instance = linker.instantiate(&mut *store, &program.module)?;
+    let main = instance.get_typed_func::<(), i32>(&mut *store, "gw_main")?;
+    let exit_code = main.call(&mut *store, ())?;
+    Ok(exit_code as i8)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn gw_syscall(
+    mut caller: Caller<'_, HostState>,
+    number: i64,
+    a0: i64,
+    a1: i64,
+    a2: i64,
+    a3: i64,
+    a4: i64,
+    a5: i64,
+) -> Result<i64> {
+    let number = number as u64;
+    let args = [a0, a1, a2, a3, a4, a5].map(|a| a as u64);
+    if number == SYS_EXIT {
+        caller.data_mut().exit_code = Some(args[0] as i8);
+        return Err(anyhow!("exit"));
+    }
+
+    let memory = caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| anyhow!("memory not exported"))?;
+    let cycles = caller.fuel_consumed().unwrap_or_default();
+    let (data, state) = memory.data_and_store_mut(&mut caller);
+    match ecall(state, data, number, args, cycles) {
+        Ok(ret) => Ok(ret as i64),
+        Err(err) => {
+            state.error = Some(err);
+            Err(anyhow!("syscall {} failed", number))
+        }
+    }
+}
+
+fn ecall(
+    state: &mut HostState,
+    data: &mut [u8],
+    number: u64,
+    args: [u64; 6],
+    cycles: u64,
+) -> Result<u64, VMError> {
+    if data.len() as u64 > RISCV_MAX_MEMORY as u64 {
+        return Err(VMError::MemOutOfBound);
+    }
+    let mirror = &mut state.mirror;
+    mirror.memory_mut().store_bytes(0, data)?;
+    for (reg, arg) in [A0, A1, A2, A3, A4, A5].into_iter().zip(args) {
+        mirror.set_register(reg, arg);
+    }
+    mirror.set_register(A7, number);
+    mirror.set_cycles(cycles);
+
+    let buf_size = buffer_size(mirror, number, &args);
+    if !state.context.ecall(mirror)? {
+        return Err(VMError::InvalidEcall(number));
+    }
+    let ret = mirror.registers()[A0].to_u64();
+    if ret == SUCCESS as u64 {
+        for (addr, len) in written_ranges(mirror, number, &args, buf_size)? {
+            let bytes = load_bytes(mirror, addr, len as usize)?;
+            let start = addr as usize;
+            data.get_mut(start..start + bytes.len())
+                .ok_or(VMError::MemOutOfBound)?
+                .copy_from_slice(&bytes);
+        }
+    }
+    Ok(ret)
+}
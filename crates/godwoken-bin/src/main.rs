@@ -4,14 +4,24 @@ static GLOBAL_ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
 use anyhow::{Context, Result};
 use clap::{Arg, Command, CommandFactory, Parser};
+use godwoken_bin::subcommand::backup::{BackupCommand, COMMAND_BACKUP};
 use godwoken_bin::subcommand::db_block_validator;
 use godwoken_bin::subcommand::export_block::{ExportArgs, ExportBlock};
+use godwoken_bin::subcommand::fsck::{FsckCommand, COMMAND_FSCK};
 use godwoken_bin::subcommand::import_block::{ImportArgs, ImportBlock};
 use godwoken_bin::subcommand::migrate::{MigrateCommand, COMMAND_MIGRATE};
 use godwoken_bin::subcommand::peer_id::{PeerIdCommand, COMMAND_PEER_ID};
+use godwoken_bin::subcommand::replay_block::{ReplayBlockCommand, COMMAND_REPLAY_BLOCK};
+use godwoken_bin::subcommand::replay_mem_block::{ReplayMemBlockCommand, COMMAND_REPLAY_MEM_BLOCK};
 use godwoken_bin::subcommand::rewind_to_last_valid_block::{
     RewindToLastValidBlockCommand, COMMAND_REWIND_TO_LAST_VALID_BLOCK,
 };
+use godwoken_bin::subcommand::state_fixture::{
+    ExportStateFixtureCommand, COMMAND_EXPORT_STATE_FIXTURE,
+};
+use godwoken_bin::subcommand::state_snapshot::{
+    ExportSnapshotCommand, ImportSnapshotCommand, COMMAND_EXPORT_SNAPSHOT, COMMAND_IMPORT_SNAPSHOT,
+};
 use gw_block_producer::runner;
 use gw_config::{BuiltinConsensus, Config, Consensus};
 use gw_telemetry::trace;
@@ -26,12 +36,16 @@ const COMMAND_IMPORT_BLOCK: &str = "import-block";
 const ARG_OUTPUT_PATH: &str = "output-path";
 const ARG_CONFIG: &str = "config";
 const ARG_SKIP_CONFIG_CHECK: &str = "skip-config-check";
+const ARG_OVERRIDE_STATE_MISMATCH_HALT: &str = "override-state-mismatch-halt";
 const ARG_FROM_BLOCK: &str = "from-block";
 const ARG_TO_BLOCK: &str = "to-block";
 const ARG_SHOW_PROGRESS: &str = "show-progress";
+const ARG_COMPRESS: &str = "compress";
 const ARG_SOURCE_PATH: &str = "source-path";
 const ARG_READ_BATCH: &str = "read-batch";
 const ARG_REWIND_TO_LAST_VALID_TIP: &str = "rewind-to-last-valid-tip";
+const ARG_VERIFY_EXECUTION: &str = "verify-execution";
+const ARG_VERIFY_WORKERS: &str = "verify-workers";
 
 fn read_config<P: AsRef<Path>>(path: P) -> Result<Config> {
     let content = fs::read(&path)
@@ -75,6 +89,11 @@ async fn run_cli() -> Result<()> {
                         .long(ARG_SKIP_CONFIG_CHECK)
                         .help("Force to accept unsafe config file"),
                 )
+                .arg(
+                    Arg::new(ARG_OVERRIDE_STATE_MISMATCH_HALT)
+                        .long(ARG_OVERRIDE_STATE_MISMATCH_HALT)
+                        .help("Continue after halting on a post state root mismatch"),
+                )
                 .display_order(0),
         )
         .subcommand(
@@ -92,7 +111,7 @@ async fn run_cli() -> Result<()> {
         )
         .subcommand(
             Command::new(COMMAND_VERIFY_DB_BLOCK)
-                .about("Verify history blocks in db")
+                .about("Verify history blocks in db, see also `fsck`")
                 .arg(
                     Arg::new(ARG_CONFIG)
                         .short('c')
@@ -148,6 +167,14 @@ async fn run_cli() -> Result<()> {
                         .takes_value(true)
                         .help("To block number"),
                 )
+                .arg(
+                    Arg::new(ARG_COMPRESS)
+                        .short('z')
+                        .long("compress")
+                        .required(false)
+                        .takes_value(false)
+                        .help("Compress exported blocks with zstd"),
+                )
                 .arg(
                     Arg::new(ARG_SHOW_PROGRESS)
                         .short('p')
@@ -175,7 +202,7 @@ async fn run_cli() -> Result<()> {
                         .long("source-path")
                         .takes_value(true)
                         .required(true)
-                        .help("The source file for exported blocks"),
+                        .help("The source file for exported blocks, may be compressed"),
                 )
                 .arg(
                     Arg::new(ARG_READ_BATCH)
@@ -198,6 +225,20 @@ async fn run_cli() -> Result<()> {
                         .takes_value(false)
                         .help("Rewind to last valid tip block before import"),
                 )
+                .arg(
+                    Arg::new(ARG_VERIFY_EXECUTION)
+                        .long("verify-execution")
+                        .required(false)
+                        .takes_value(false)
+                        .help("Re-execute imported blocks and compare post state roots"),
+                )
+                .arg(
+                    Arg::new(ARG_VERIFY_WORKERS)
+                        .long("verify-workers")
+                        .takes_value(true)
+                        .requires(ARG_VERIFY_EXECUTION)
+                        .help("Number of workers to re-execute blocks, defaults to CPU count"),
+                )
                 .arg(
                     Arg::new(ARG_SHOW_PROGRESS)
                         .short('p')
@@ -210,7 +251,14 @@ async fn run_cli() -> Result<()> {
         )
         .subcommand(PeerIdCommand::command())
         .subcommand(RewindToLastValidBlockCommand::command())
-        .subcommand(MigrateCommand::command());
+        .subcommand(ReplayMemBlockCommand::command())
+        .subcommand(ReplayBlockCommand::command())
+        .subcommand(MigrateCommand::command())
+        .subcommand(ExportSnapshotCommand::command())
+        .subcommand(ImportSnapshotCommand::command())
+        .subcommand(BackupCommand::command())
+        .subcommand(FsckCommand::command())
+        .subcommand(ExportStateFixtureCommand::command());
 
     // handle subcommands
     let matches = app.clone().get_matches();
@@ -220,7 +268,13 @@ async fn run_cli() -> Result<()> {
             let config = read_config(config_path)?;
             let _guard = trace::init()?;
             gw_metrics::init(&config);
-            runner::run(config, m.is_present(ARG_SKIP_CONFIG_CHECK)).await?;
+            runner::run(
+                config,
+                Some(config_path.into()),
+                m.is_present(ARG_SKIP_CONFIG_CHECK),
+                m.is_present(ARG_OVERRIDE_STATE_MISMATCH_HALT),
+            )
+            .await?;
         }
         Some((COMMAND_EXAMPLE_CONFIG, m)) => {
             let path = m.value_of(ARG_OUTPUT_PATH).unwrap();
@@ -242,6 +296,7 @@ async fn run_cli() -> Result<()> {
             let output = m.value_of(ARG_OUTPUT_PATH).unwrap().into();
             let from_block: Option<u64> = m.value_of(ARG_FROM_BLOCK).map(str::parse).transpose()?;
             let to_block: Option<u64> = m.value_of(ARG_TO_BLOCK).map(str::parse).transpose()?;
+            let compress = m.is_present(ARG_COMPRESS);
             let show_progress = m.is_present(ARG_SHOW_PROGRESS);
 
             let args = ExportArgs {
@@ -249,6 +304,7 @@ async fn run_cli() -> Result<()> {
                 output,
                 from_block,
                 to_block,
+                compress,
                 show_progress,
             };
             ExportBlock::create(args)?.execute()?;
@@ -263,6 +319,13 @@ async fn run_cli() -> Result<()> {
             let to_block: Option<u64> = m.value_of(ARG_TO_BLOCK).map(str::parse).transpose()?;
             let rewind_to_last_valid_tip = m.is_present(ARG_REWIND_TO_LAST_VALID_TIP);
             let show_progress = m.is_present(ARG_SHOW_PROGRESS);
+            let verify_execution = if m.is_present(ARG_VERIFY_EXECUTION) {
+                let workers: Option<usize> =
+                    m.value_of(ARG_VERIFY_WORKERS).map(str::parse).transpose()?;
+                Some(workers.unwrap_or_else(num_cpus::get))
+            } else {
+                None
+            };
 
             let args = ImportArgs {
                 config,
@@ -271,6 +334,7 @@ async fn run_cli() -> Result<()> {
                 to_block,
                 rewind_to_last_valid_tip,
                 show_progress,
+                verify_execution,
             };
             ImportBlock::create(args).await?.execute().await?;
         }
@@ -280,16 +344,44 @@ async fn run_cli() -> Result<()> {
         Some((COMMAND_REWIND_TO_LAST_VALID_BLOCK, m)) => {
             RewindToLastValidBlockCommand::from_clap(m).run().await?;
         }
+        Some((COMMAND_REPLAY_MEM_BLOCK, m)) => {
+            let _guard = trace::init()?;
+            ReplayMemBlockCommand::from_clap(m).run().await?;
+        }
+        Some((COMMAND_REPLAY_BLOCK, m)) => {
+            let _guard = trace::init()?;
+            ReplayBlockCommand::from_clap(m).run().await?;
+        }
         Some((COMMAND_MIGRATE, m)) => {
             MigrateCommand::from_clap(m).run()?;
         }
+        Some((COMMAND_EXPORT_SNAPSHOT, m)) => {
+            let _guard = trace::init()?;
+            ExportSnapshotCommand::from_clap(m).run()?;
+        }
+        Some((COMMAND_IMPORT_SNAPSHOT, m)) => {
+            let _guard = trace::init()?;
+            ImportSnapshotCommand::from_clap(m).run().await?;
+        }
+        Some((COMMAND_BACKUP, m)) => {
+            let _guard = trace::init()?;
+            BackupCommand::from_clap(m).run().await?;
+        }
+        Some((COMMAND_FSCK, m)) => {
+            let _guard = trace::init()?;
+            FsckCommand::from_clap(m).run().await?;
+        }
+        Some((COMMAND_EXPORT_STATE_FIXTURE, m)) => {
+            let _guard = trace::init()?;
+            ExportStateFixtureCommand::from_clap(m).run()?;
+        }
         _ => {
             // default command: start a Godwoken node
             let config_path = "./config.toml";
             let config = read_config(config_path)?;
             let _guard = trace::init()?;
             gw_metrics::init(&config);
-            runner::run(config, false).await?;
+            runner::run(config, Some(config_path.into()), false, false).await?;
         }
     };
     Ok(())
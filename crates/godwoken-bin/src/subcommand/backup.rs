@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use gw_config::Config;
+use gw_rpc_client::gw_client::GWClient;
+use gw_store::backup::{list_backups, restore_backup, verify_backup};
+use gw_store::schema::COLUMNS;
+use gw_store::Store;
+
+pub const COMMAND_BACKUP: &str = "backup";
+
+/// Create, list, verify and restore incremental backups of the database
+#[derive(Parser)]
+#[clap(name = COMMAND_BACKUP)]
+pub struct BackupCommand {
+    /// The config file path
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// The backup directory, defaults to `store.backup_dir` in the config
+    #[clap(short, long)]
+    backup_dir: Option<PathBuf>,
+    #[clap(subcommand)]
+    command: BackupSubcommand,
+}
+
+#[derive(Subcommand)]
+enum BackupSubcommand {
+    /// Create a new backup
+    Create {
+        /// Ask the running node at this RPC url to create the backup in its
+        /// `store.backup_dir`. Admin RPC methods must be enabled. Otherwise
+        /// the node must be stopped.
+        #[clap(long)]
+        rpc_url: Option<String>,
+    },
+    /// List backups
+    List,
+    /// Verify sizes and checksums of files of a backup
+    Verify {
+        #[clap(long)]
+        backup_id: u32,
+    },
+    /// Restore a backup to an empty database directory
+    Restore {
+        /// Restore the latest backup if not specified
+        #[clap(long)]
+        backup_id: Option<u32>,
+        /// The database directory, defaults to `store.path` in the config
+        #[clap(long)]
+        db_path: Option<PathBuf>,
+    },
+}
+
+impl BackupCommand {
+    pub async fn run(self) -> Result<()> {
+        let config = read_config(&self.config_path)?;
+        if let BackupSubcommand::Create {
+            rpc_url: Some(ref url),
+        } = self.command
+        {
+            let info = GWClient::with_url(url)?.gw_create_backup().await?;
+            println!(
+                "created backup {}, size: {}, files: {}",
+                info.id.value(),
+                info.size.value(),
+                info.number_files.value()
+            );
+            return Ok(());
+        }
+
+        let backup_dir = self
+            .backup_dir
+            .or_else(|| config.store.backup_dir.clone())
+            .context("backup dir is not specified")?;
+        match self.command {
+            BackupSubcommand::Create { .. } => {
+                let store = Store::open(&config.store, COLUMNS).context("open database")?;
+                let info = store.create_backup(&backup_dir)?;
+                println!(
+                    "created backup {}, size: {}, files: {}",
+                    info.id, info.size, info.number_files
+                );
+            }
+            BackupSubcommand::List => {
+                for info in list_backups(&backup_dir)? {
+                    println!(
+                        "backup {}, timestamp: {}, size: {}, files: {}",
+                        info.id, info.timestamp, info.size, info.number_files
+                    );
+                }
+            }
+            BackupSubcommand::Verify { backup_id } => {
+                verify_backup(&backup_dir, backup_id)?;
+                println!("backup {} is ok", backup_id);
+            }
+            BackupSubcommand::Restore { backup_id, db_path } => {
+                let db_path = db_path.unwrap_or(config.store.path);
+                restore_backup(&backup_dir, backup_id, &db_path)?;
+                println!("restored to {}", db_path.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_config(path: &Path) -> Result<Config> {
+    let content = fs::read(path)
+        .with_context(|| format!("read config file from {}", path.to_string_lossy()))?;
+    toml::from_slice(&content).context("parse config file")
+}
@@ -9,14 +9,30 @@ use gw_challenge::{
         OffChainMockContext,
     },
 };
+use gw_common::registry_address::RegistryAddress;
 use gw_config::{Config, DBBlockValidatorConfig, DebugConfig};
-use gw_generator::Generator;
-use gw_jsonrpc_types::godwoken::ChallengeTargetType as JsonChallengeTargetType;
-use gw_store::{traits::chain_store::ChainStore, Store};
+use gw_generator::{traits::StateExt, Generator};
+use gw_jsonrpc_types::{
+    debug::ExecutionTrace as JsonExecutionTrace,
+    godwoken::ChallengeTargetType as JsonChallengeTargetType,
+};
+use gw_store::{
+    chain_view::ChainView,
+    state::{
+        history::history_state::{RWConfig, ReadOpt, WriteOpt},
+        overlay::mem_store::MemStore,
+        traits::JournalDB,
+        BlockStateDB,
+    },
+    traits::chain_store::ChainStore,
+    Store,
+};
 use gw_types::{
+    bytes::Bytes,
     core::{ChallengeTargetType, Status},
     h256::*,
-    packed::{ChallengeTarget, GlobalState, L2Block},
+    offchain::ExecutionTrace,
+    packed::{BlockInfo, ChallengeTarget, GlobalState, L2Block},
     prelude::{Builder, Entity, Pack, Unpack},
 };
 use rayon::prelude::*;
@@ -290,6 +306,7 @@ impl DBBlockCancelChallengeValidator {
         };
 
         if verify_with_strategy(LoadDataStrategy::Witness).is_err() {
+            self.dump_syscall_trace_to_file(&dump_context);
             if let Err(err) = verify_with_strategy(LoadDataStrategy::CellDep) {
                 if !err.to_string().contains("exceeded max cycles, used") {
                     return Err(err);
@@ -331,6 +348,137 @@ impl DBBlockCancelChallengeValidator {
             log::error!("unable to dump offchain cancel challenge tx {}", err);
         }
     }
+
+    /// Trace the tx of a tx execution target, dump the syscall trace and
+    /// replay it, so a divergence of the onchain validator can be compared
+    /// with the syscalls of the generator.
+    fn dump_syscall_trace_to_file(&self, dump_context: &DumpContext) {
+        if !self.config.dump_syscall_trace
+            || !matches!(dump_context.target_type, ChallengeTargetType::TxExecution)
+        {
+            return;
+        }
+
+        let dump = || -> Result<_> {
+            let block_number = dump_context.block_number;
+            let traces = self.trace_tx(block_number, dump_context.target_index)?;
+
+            let max_cycles = self.generator.fork_config().max_l2_tx_cycles(block_number);
+            for (idx, trace) in traces.iter().enumerate() {
+                let replay =
+                    self.generator
+                        .replay_execution_trace(block_number, trace, max_cycles)?;
+                match replay.divergence {
+                    Some(divergence) => log::warn!(
+                        "replay {:?} call #{} diverged: {}",
+                        dump_context.info(),
+                        idx,
+                        divergence
+                    ),
+                    None => log::info!(
+                        "replay {:?} call #{}: {} syscalls, exit code {}, cycles {}",
+                        dump_context.info(),
+                        idx,
+                        replay.syscalls,
+                        replay.exit_code,
+                        replay.cycles
+                    ),
+                }
+            }
+
+            let dir = self.debug_config.debug_tx_dump_path.as_path();
+            create_dir_all(dir)?;
+            let dump_path = dir.join(format!("{}-syscall-trace.json", dump_context.info()));
+            let traces: Vec<JsonExecutionTrace> = traces.into_iter().map(Into::into).collect();
+            let json_traces = serde_json::to_string_pretty(&traces)?;
+            log::info!(
+                "dump syscall trace from {:?} to {:?}",
+                dump_context.info(),
+                dump_path
+            );
+            write(dump_path, json_traces)?;
+
+            Ok(())
+        };
+
+        if let Err(err) = dump() {
+            log::error!("unable to dump syscall trace {}", err);
+        }
+    }
+
+    /// Execute the tx at its state in the block with syscall tracing.
+    fn trace_tx(&self, block_number: u64, tx_index: u32) -> Result<Vec<ExecutionTrace>> {
+        let db = self.store.begin_transaction();
+        let block_hash: H256 = {
+            let maybe = db.get_block_hash_by_number(block_number)?;
+            maybe.ok_or_else(|| anyhow!("block #{} not found", block_number))?
+        };
+        let block = {
+            let maybe = db.get_block(&block_hash)?;
+            maybe.ok_or_else(|| anyhow!("block #{} not found", block_number))?
+        };
+        let deposits = db
+            .get_block_deposit_info_vec(block_number)
+            .ok_or_else(|| anyhow!("block #{} deposits not found", block_number))?;
+        let tip_block_hash = db.get_last_valid_tip_block_hash()?;
+        let snap = db.snapshot();
+        let chain_view = ChainView::new(&snap, tip_block_hash);
+
+        // build state before txs of the block
+        let rollup_context = self.generator.rollup_context();
+        let raw_block = block.raw();
+        let parent_block_number = block_number.saturating_sub(1);
+        let mut state = BlockStateDB::from_store(
+            MemStore::new(db),
+            RWConfig {
+                read: ReadOpt::Block(parent_block_number),
+                write: WriteOpt::Block(parent_block_number),
+            },
+        )?;
+        let block_producer = {
+            let block_producer: Bytes = raw_block.block_producer().unpack();
+            RegistryAddress::from_slice(&block_producer)
+                .ok_or_else(|| anyhow!("invalid block producer"))?
+        };
+        for withdrawal in block.withdrawals() {
+            state.apply_withdrawal_request(rollup_context, &block_producer, &withdrawal)?;
+        }
+        for deposit in deposits {
+            state.apply_deposit_request(rollup_context, &deposit.request())?;
+        }
+        state.finalise()?;
+
+        let block_info = BlockInfo::new_builder()
+            .block_producer(raw_block.block_producer())
+            .timestamp(raw_block.timestamp())
+            .number(raw_block.number())
+            .build();
+        for tx in block.transactions().into_iter().take(tx_index as usize) {
+            self.generator.execute_transaction(
+                &chain_view,
+                &mut state,
+                &block_info,
+                &tx.raw(),
+                None,
+                None,
+            )?;
+            state.finalise()?;
+        }
+
+        let tx = block
+            .transactions()
+            .get(tx_index as usize)
+            .ok_or_else(|| anyhow!("block #{} tx #{} not found", block_number, tx_index))?;
+        let run_result = self.generator.trace_transaction(
+            &chain_view,
+            &mut state,
+            &block_info,
+            &tx.raw(),
+            None,
+        )?;
+
+        Ok(run_result.execution_traces)
+    }
 }
 
 #[derive(Clone)]
@@ -342,22 +490,27 @@ struct DumpContext {
 }
 
 impl DumpContext {
-    fn info_with_load_data_strategy(&self, load_data_strategy: LoadDataStrategy) -> String {
+    fn info(&self) -> String {
         let type_ = match self.target_type {
             ChallengeTargetType::TxSignature => "tx-signature",
             ChallengeTargetType::TxExecution => "tx-execution",
             ChallengeTargetType::Withdrawal => "withdrawal",
         };
         let hash = ckb_types::H256(self.target_hash);
+
+        format!(
+            "block-#{}-{}-{}-{}",
+            self.block_number, type_, self.target_index, hash
+        )
+    }
+
+    fn info_with_load_data_strategy(&self, load_data_strategy: LoadDataStrategy) -> String {
         let strategy = match load_data_strategy {
             LoadDataStrategy::Witness => "with-witness-load-data",
             LoadDataStrategy::CellDep => "with-celldep-load-data",
         };
 
-        format!(
-            "block-#{}-{}-{}-{}-{}",
-            self.block_number, type_, self.target_index, hash, strategy
-        )
+        format!("{}-{}", self.info(), strategy)
     }
 }
 
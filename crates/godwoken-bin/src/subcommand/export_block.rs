@@ -9,6 +9,7 @@ use gw_store::schema::COLUMNS;
 use gw_store::traits::chain_store::ChainStore;
 use gw_types::packed;
 use gw_types::prelude::{Entity, Unpack};
+use gw_utils::export_block::compress_writer;
 use indicatif::{ProgressBar, ProgressStyle};
 
 pub struct ExportArgs {
@@ -16,6 +17,7 @@ pub struct ExportArgs {
     pub output: PathBuf,
     pub from_block: Option<u64>,
     pub to_block: Option<u64>,
+    pub compress: bool,
     pub show_progress: bool,
 }
 
@@ -27,6 +29,7 @@ pub struct ExportBlock {
     output: PathBuf,
     from_block: u64,
     to_block: u64,
+    compress: bool,
     progress_bar: Option<ProgressBar>,
 }
 
@@ -44,10 +47,18 @@ impl ExportBlock {
             output,
             from_block,
             to_block,
+            compress: false,
             progress_bar: None,
         }
     }
 
+    // Disable warning for bin
+    #[allow(dead_code)]
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
     pub fn create(args: ExportArgs) -> Result<Self> {
         let snap =
             StoreReadonly::open(&args.config.store.path, COLUMNS).context("open database")?;
@@ -103,6 +114,9 @@ impl ExportBlock {
                 args.config.consensus.get_config().genesis.rollup_type_hash
             ));
             file_name.push(format!("_{}_{}", from_block, to_block));
+            if args.compress {
+                file_name.push(".zst");
+            }
 
             output.set_file_name(file_name);
             output
@@ -113,6 +127,7 @@ impl ExportBlock {
             output,
             from_block,
             to_block,
+            compress: args.compress,
             progress_bar,
         };
 
@@ -140,6 +155,23 @@ impl ExportBlock {
             .open(self.output)?;
 
         let mut writer = io::BufWriter::new(f);
+        if self.compress {
+            let mut encoder = compress_writer(writer)?;
+            self.write_blocks(&mut encoder)?;
+            writer = encoder.finish()?;
+        } else {
+            self.write_blocks(&mut writer)?;
+        }
+
+        if let Some(ref progress_bar) = self.progress_bar {
+            progress_bar.finish_with_message("done");
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    fn write_blocks(&self, writer: &mut impl Write) -> Result<()> {
         for block_number in self.from_block..=self.to_block {
             let exported_block = gw_utils::export_block::export_block(&self.snap, block_number)?;
             let packed: packed::ExportedBlock = exported_block.into();
@@ -151,11 +183,6 @@ impl ExportBlock {
             }
         }
 
-        if let Some(ref progress_bar) = self.progress_bar {
-            progress_bar.finish_with_message("done");
-        }
-        writer.flush()?;
-
         Ok(())
     }
 }
@@ -0,0 +1,94 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use gw_config::Config;
+use gw_store::fsck::FsckOptions;
+use gw_store::schema::COLUMNS;
+use gw_store::Store;
+
+use super::db_block_validator;
+
+pub const COMMAND_FSCK: &str = "fsck";
+
+/// Check integrity of the database: the main chain index, block contents, tx
+/// and receipt references, SMT roots and orphaned keys
+#[derive(Parser)]
+#[clap(name = COMMAND_FSCK)]
+pub struct FsckCommand {
+    /// The config file path
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// From block number, defaults to the genesis block
+    #[clap(short, long)]
+    from_block: Option<u64>,
+    /// To block number, defaults to the last valid tip block
+    #[clap(short, long)]
+    to_block: Option<u64>,
+    /// Scan columns of block contents for keys of blocks neither on the main
+    /// chain nor bad blocks. Reads the whole columns
+    #[clap(long)]
+    check_orphans: bool,
+    /// Delete orphaned keys found, e.g. contents of reverted blocks. They are
+    /// no longer returned by RPCs querying blocks or txs by hash
+    #[clap(long)]
+    delete_orphans: bool,
+    /// Also verify blocks in the range with offchain cancel challenges, as
+    /// `verify-db-block` does. Requires the block producer config
+    #[clap(long)]
+    verify_blocks: bool,
+    /// Print the report as JSON
+    #[clap(long)]
+    json: bool,
+}
+
+impl FsckCommand {
+    pub async fn run(self) -> Result<()> {
+        let config = read_config(&self.config_path)?;
+        let opts = FsckOptions {
+            from_block: self.from_block,
+            to_block: self.to_block,
+            check_orphans: self.check_orphans,
+            delete_orphans: self.delete_orphans,
+        };
+        let report = {
+            let store = Store::open(&config.store, COLUMNS).context("open database")?;
+            store.fsck(&opts)?
+        };
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            println!(
+                "checked blocks #{}..=#{}",
+                report.from_block, report.to_block
+            );
+            for finding in &report.findings {
+                println!(
+                    "{}\n  suggestion: {}",
+                    serde_json::to_string(&finding.issue)?,
+                    finding.suggestion
+                );
+            }
+            if report.deleted_orphans > 0 {
+                println!("deleted {} orphaned keys", report.deleted_orphans);
+            }
+        }
+
+        let issues = report.issues().count();
+        if issues > 0 {
+            bail!("found {} issues", issues);
+        }
+        if self.verify_blocks {
+            db_block_validator::verify(config, self.from_block, self.to_block).await?;
+        }
+        Ok(())
+    }
+}
+
+fn read_config(path: &Path) -> Result<Config> {
+    let content = fs::read(path)
+        .with_context(|| format!("read config file from {}", path.to_string_lossy()))?;
+    toml::from_slice(&content).context("parse config file")
+}
@@ -1,18 +1,29 @@
 use std::collections::HashSet;
 use std::fs;
-use std::io::BufReader;
+use std::io::{self, BufReader, Read};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 use anyhow::{anyhow, bail, Context, Result};
-use gw_block_producer::runner::BaseInitComponents;
+use gw_block_producer::{replay_block::ReplayBlock, runner::BaseInitComponents};
 use gw_chain::chain::{Chain, RevertL1ActionContext, RevertedL1Action, SyncParam};
 use gw_config::Config;
-use gw_store::{traits::chain_store::ChainStore, Store};
-use gw_types::{offchain::ExportedBlock, packed::NumberHash, prelude::*};
+use gw_generator::Generator;
+use gw_store::{chain_view::ChainView, traits::chain_store::ChainStore, Store};
+use gw_types::{
+    offchain::ExportedBlock,
+    packed::{DepositRequest, NumberHash},
+    prelude::*,
+};
 use gw_utils::export_block::{
-    check_block_post_state, insert_bad_block_hashes, ExportedBlockReader,
+    check_block_post_state, decompress_reader, export_block, insert_bad_block_hashes,
+    ExportedBlockReader,
 };
 use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+
+use super::replay_block::restore_block_state;
 
 pub const DEFAULT_READ_BATCH: usize = 500;
 
@@ -23,6 +34,8 @@ pub struct ImportArgs {
     pub to_block: Option<u64>,
     pub rewind_to_last_valid_tip: bool,
     pub show_progress: bool,
+    /// Number of workers to re-execute imported blocks, None to skip.
+    pub verify_execution: Option<usize>,
 }
 
 pub struct ImportBlock {
@@ -32,6 +45,7 @@ pub struct ImportBlock {
     to_block: Option<u64>,
     rewind_to_last_valid_tip: bool,
     progress_bar: Option<ProgressBar>,
+    verify_execution: Option<usize>,
 }
 
 impl ImportBlock {
@@ -45,9 +59,17 @@ impl ImportBlock {
             to_block: None,
             rewind_to_last_valid_tip: false,
             progress_bar: None,
+            verify_execution: None,
         }
     }
 
+    // Disable warning for bin
+    #[allow(dead_code)]
+    pub fn verify_execution(mut self, workers: usize) -> Self {
+        self.verify_execution = Some(workers);
+        self
+    }
+
     pub async fn create(args: ImportArgs) -> Result<Self> {
         let base = BaseInitComponents::init(&args.config, true).await?;
         let chain = Chain::create(
@@ -79,6 +101,7 @@ impl ImportBlock {
             to_block: args.to_block,
             rewind_to_last_valid_tip: args.rewind_to_last_valid_tip,
             progress_bar,
+            verify_execution: args.verify_execution,
         };
 
         Ok(import_block)
@@ -122,8 +145,14 @@ impl ImportBlock {
 
     pub fn read_from_mol(&mut self) -> Result<()> {
         let store = self.chain.store();
-        let f = fs::File::open(&self.source)?;
-        let mut block_reader = ExportedBlockReader::new(BufReader::new(f));
+        // Progress is measured in bytes of the source file, which may be
+        // compressed.
+        let source_read = Arc::new(AtomicU64::new(0));
+        let f = ReadCounter {
+            inner: fs::File::open(&self.source)?,
+            read: source_read.clone(),
+        };
+        let mut block_reader = ExportedBlockReader::new(decompress_reader(BufReader::new(f))?);
 
         // Seek new block
         let snap = store.get_snapshot();
@@ -145,13 +174,13 @@ impl ImportBlock {
 
         if first_block_number <= db_tip_block_number {
             let new_block_offset = db_tip_block_number + 1 - first_block_number;
-            let (n, size) = block_reader.skip_blocks(new_block_offset)?;
+            let (n, _size) = block_reader.skip_blocks(new_block_offset)?;
             if n != new_block_offset {
                 bail!("no new block")
             }
 
             if let Some(ref progress_bar) = self.progress_bar {
-                progress_bar.inc(size)
+                progress_bar.set_position(source_read.load(Ordering::Relaxed))
             }
         }
 
@@ -175,7 +204,11 @@ impl ImportBlock {
                     {
                         return
                     }
-                    Ok(_) => tx.send(maybe_new_block).expect("send block in background"),
+                    Ok(_) => {
+                        let read = source_read.load(Ordering::Relaxed);
+                        let maybe_new_block = maybe_new_block.map(|(block, _size)| (block, read));
+                        tx.send(maybe_new_block).expect("send block in background")
+                    }
                 };
             }
         });
@@ -183,7 +216,7 @@ impl ImportBlock {
         let mut last_submitted_block = None;
         let mut next_block_number = db_tip_block_number + 1;
         for maybe_new_block in rx.into_iter() {
-            let (block, size) = maybe_new_block
+            let (block, read) = maybe_new_block
                 .map_err(|err| anyhow!("read block {} {}", next_block_number, err))?;
             let block_number = block.block_number();
 
@@ -191,7 +224,7 @@ impl ImportBlock {
                 .map_err(|err| anyhow!("insert block {} {}", block_number, err))?;
 
             if let Some(ref progress_bar) = self.progress_bar {
-                progress_bar.inc(size as u64)
+                progress_bar.set_position(read)
             }
 
             next_block_number += 1;
@@ -223,10 +256,112 @@ impl ImportBlock {
 
         read_in_background.join().expect("join read background");
 
+        let first_block = db_tip_block_number + 1;
+        let last_block = next_block_number - 1;
+        if let Some(workers) = self.verify_execution {
+            if last_block >= first_block {
+                verify_execution(
+                    self.chain.store(),
+                    self.chain.generator(),
+                    first_block,
+                    last_block,
+                    workers,
+                )?;
+                println!(
+                    "verified execution of blocks #{}..=#{}",
+                    first_block, last_block
+                );
+            }
+        }
+
         Ok(())
     }
 }
 
+struct ReadCounter<R> {
+    inner: R,
+    read: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for ReadCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// Re-execute blocks `first..=last` of the main chain and compare their post
+/// state roots, independent of the state the import built.
+///
+/// Importing executes blocks too, but skips checking blocks in the skipped
+/// invalid block list. Blocks are split into contiguous ranges verified by a
+/// pool of `workers` threads, each restoring the state before its range in
+/// memory by detaching later blocks.
+fn verify_execution(
+    store: &Store,
+    generator: &Generator,
+    first: u64,
+    last: u64,
+    workers: usize,
+) -> Result<()> {
+    let workers = workers.max(1);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(workers)
+        .build()?;
+    let range_len = (last - first + 1 + workers as u64 - 1) / workers as u64;
+    let ranges: Vec<_> = (first..=last)
+        .step_by(range_len as usize)
+        .map(|start| (start, last.min(start + range_len - 1)))
+        .collect();
+    pool.install(|| {
+        ranges
+            .into_par_iter()
+            .try_for_each(|(start, end)| verify_execution_range(store, generator, start, end))
+    })
+}
+
+fn verify_execution_range(
+    store: &Store,
+    generator: &Generator,
+    first: u64,
+    last: u64,
+) -> Result<()> {
+    log::info!("verify execution of blocks #{}..=#{}", first, last);
+    let snap = store.get_snapshot();
+    let mut state = restore_block_state(store.begin_transaction(), first - 1)?;
+
+    for block_number in first..=last {
+        let exported = export_block(&snap, block_number)?;
+        let raw_block = exported.block.raw();
+        let chain_view = ChainView::new(&snap, raw_block.parent_block_hash().unpack());
+        let deposits: Vec<DepositRequest> = exported
+            .deposit_info_vec
+            .into_iter()
+            .map(|info| info.request())
+            .collect();
+        let post_account = ReplayBlock::replay_on_state(
+            &mut state,
+            &chain_view,
+            generator,
+            &exported.block,
+            &deposits,
+            &exported.withdrawals,
+        )
+        .with_context(|| format!("re-execute block {}", block_number))?;
+        if post_account.as_slice() != raw_block.post_account().as_slice() {
+            bail!(
+                "block {} post account state mismatch, expected {} actual {}",
+                block_number,
+                raw_block.post_account(),
+                post_account
+            );
+        }
+    }
+
+    Ok(())
+}
+
 fn insert_block(
     chain: &mut Chain,
     exported: ExportedBlock,
@@ -1,9 +1,11 @@
 use std::path::PathBuf;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::{ArgGroup, Parser};
 use gw_config::{Config, StoreConfig};
-use gw_store::migrate::{init_migration_factory, open_or_create_db};
+use gw_store::migrate::{
+    dry_run_migrations, init_migration_factory, open_or_create_db, MigrationFactory,
+};
 use gw_telemetry::trace;
 
 #[cfg(feature = "smt-trie")]
@@ -23,6 +25,10 @@ pub struct MigrateCommand {
     /// Db path
     #[clap(long, group = "db-or-config")]
     db: Option<PathBuf>,
+    /// Report migrations to run with estimated downtime, without running
+    /// them. The node should be stopped
+    #[clap(long)]
+    dry_run: bool,
 }
 
 impl MigrateCommand {
@@ -47,8 +53,49 @@ impl MigrateCommand {
         let mut factory = init_migration_factory();
         #[cfg(feature = "smt-trie")]
         assert!(factory.insert(Box::new(smt_trie::SMTTrieMigration)));
+        if self.dry_run {
+            return dry_run(&store_config, &factory);
+        }
         open_or_create_db(&store_config, factory).context("open and migrate database")?;
 
         Ok(())
     }
 }
+
+fn dry_run(store_config: &StoreConfig, factory: &MigrationFactory) -> Result<()> {
+    let plan = dry_run_migrations(store_config, factory).context("plan migrations")?;
+    println!(
+        "database version: {}, target version: {}",
+        plan.db_version.as_deref().unwrap_or("none"),
+        plan.target_version.as_deref().unwrap_or("none"),
+    );
+    if plan.migrations.is_empty() {
+        println!("no migration to run");
+        return Ok(());
+    }
+    for migration in &plan.migrations {
+        let rows = match migration.estimate.rows {
+            Some(rows) => rows.to_string(),
+            None => "unknown".to_string(),
+        };
+        let duration = match migration.estimate.duration {
+            Some(duration) => format!("{:?}", duration),
+            None => "unknown".to_string(),
+        };
+        println!(
+            "migration {}: rows: {}, estimated time: {}",
+            migration.version, rows, duration
+        );
+        if let Some(ref blocker) = migration.blocker {
+            println!("  would fail: {}", blocker);
+        }
+    }
+    match plan.estimated_duration() {
+        Some(duration) => println!("estimated downtime: {:?}", duration),
+        None => println!("estimated downtime: unknown"),
+    }
+    if plan.is_blocked() {
+        bail!("some migrations would fail");
+    }
+    Ok(())
+}
@@ -1,6 +1,12 @@
+pub mod backup;
 pub mod db_block_validator;
 pub mod export_block;
+pub mod fsck;
 pub mod import_block;
 pub mod migrate;
 pub mod peer_id;
+pub mod replay_block;
+pub mod replay_mem_block;
 pub mod rewind_to_last_valid_block;
+pub mod state_fixture;
+pub mod state_snapshot;
@@ -0,0 +1,327 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, ensure, Context, Result};
+use ckb_types::bytes::Bytes;
+use clap::Parser;
+use gw_block_producer::runner::BaseInitComponents;
+use gw_common::{registry_address::RegistryAddress, state::State};
+use gw_config::Config;
+use gw_generator::{
+    cycles_profile::{BlockCyclesProfile, CyclesProfiler},
+    traits::StateExt,
+    Generator,
+};
+use gw_jsonrpc_types::debug::ExecutionTrace as JsonExecutionTrace;
+use gw_smt::smt::SMT;
+use gw_store::{
+    chain_view::ChainView,
+    smt::smt_store::SMTStateStore,
+    snapshot::StoreSnapshot,
+    state::{
+        history::history_state::{HistoryState, RWConfig},
+        overlay::mem_store::MemStore,
+        state_db::StateDB,
+        traits::JournalDB,
+        BlockStateDB,
+    },
+    traits::chain_store::ChainStore,
+    transaction::StoreTransaction,
+};
+use gw_types::{
+    h256::H256,
+    offchain::{ExecutionTrace, ExportedBlock},
+    packed::BlockInfo,
+    prelude::*,
+};
+use gw_utils::export_block::export_block;
+
+pub const COMMAND_REPLAY_BLOCK: &str = "replay-block";
+
+type ReplayState = BlockStateDB<MemStore<StoreTransaction>>;
+
+/// Re-execute a block of the main chain on the post state of its parent
+/// block, and report the first withdrawal, deposits or tx whose state
+/// checkpoint differs from the block
+#[derive(Parser)]
+#[clap(name = COMMAND_REPLAY_BLOCK)]
+pub struct ReplayBlockCommand {
+    /// The config file path
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// The block number
+    #[clap(short, long)]
+    number: u64,
+    /// Only re-execute until the tx of this index, and only report it
+    #[clap(long)]
+    tx_index: Option<usize>,
+    /// Profile cycles of the reported txs by backend and syscall, and dump
+    /// them as `<number>.json` into this directory
+    #[clap(long)]
+    profile_dir: Option<PathBuf>,
+    /// Record syscall traces of the reported txs, replay them to check that
+    /// backends are deterministic, and dump them into this directory
+    #[clap(long)]
+    trace_dir: Option<PathBuf>,
+}
+
+impl ReplayBlockCommand {
+    pub async fn run(self) -> Result<()> {
+        let mut config = read_config(&self.config_path)?;
+        if let Some(ref dir) = self.profile_dir {
+            // Let the generator collect cycles profiles of txs.
+            config.cycles_profile_dir = Some(dir.clone());
+        }
+        let base = BaseInitComponents::init(&config, true).await?;
+        ensure!(self.number > 0, "can't replay the genesis block");
+
+        let snap = base.store.get_snapshot();
+        let exported = export_block(&snap, self.number)?;
+        let txs_count = exported.block.transactions().len();
+        if let Some(tx_index) = self.tx_index {
+            ensure!(
+                tx_index < txs_count,
+                "tx #{} not found, block #{} has {} txs",
+                tx_index,
+                self.number,
+                txs_count
+            );
+        }
+        println!(
+            "replay block #{} {:#x}: {} withdrawals, {} deposits, {} txs",
+            self.number,
+            ckb_types::H256(exported.block_hash()),
+            exported.withdrawals.len(),
+            exported.deposit_info_vec.len(),
+            txs_count
+        );
+
+        let mut state = restore_block_state(base.store.begin_transaction(), self.number - 1)?;
+        let chain_view = ChainView::new(&snap, exported.block.raw().parent_block_hash().unpack());
+        let mut block_profile = BlockCyclesProfile::default();
+        let divergence = self.replay(
+            &base.generator,
+            &mut state,
+            &chain_view,
+            &exported,
+            &mut block_profile,
+        )?;
+
+        if let Some(ref dir) = self.profile_dir {
+            let syscall_cycles = config.mem_pool.mem_block.syscall_cycles.clone();
+            let profiler = CyclesProfiler::new(dir.clone(), syscall_cycles)?;
+            profiler.dump(self.number, &exported.block_hash(), &block_profile)?;
+        }
+        match divergence {
+            Some(divergence) => println!("diverged at {}", divergence),
+            None => println!("no divergence found"),
+        }
+        Ok(())
+    }
+
+    /// Returns the first diverged part of the block.
+    fn replay(
+        &self,
+        generator: &Generator,
+        state: &mut ReplayState,
+        chain_view: &ChainView<StoreSnapshot>,
+        exported: &ExportedBlock,
+        block_profile: &mut BlockCyclesProfile,
+    ) -> Result<Option<String>> {
+        let raw_block = exported.block.raw();
+        let block_number = self.number;
+        let rollup_context = generator.rollup_context();
+        let block_producer = {
+            let block_producer: Bytes = raw_block.block_producer().unpack();
+            RegistryAddress::from_slice(&block_producer)
+                .ok_or_else(|| anyhow!("invalid block producer"))?
+        };
+        // Checkpoints of blocks before the fork may be incorrect, only the
+        // post state is checked.
+        let check_checkpoints = generator
+            .fork_config()
+            .enforce_correctness_of_state_checkpoint_list(block_number);
+        let checkpoints: Vec<H256> = raw_block.state_checkpoint_list().unpack();
+        let check = |state: &ReplayState, expected: Option<&H256>| -> Result<bool> {
+            if !check_checkpoints {
+                return Ok(true);
+            }
+            Ok(Some(&state.calculate_state_checkpoint()?) == expected)
+        };
+
+        for (idx, withdrawal) in exported.withdrawals.iter().enumerate() {
+            state.apply_withdrawal_request(
+                rollup_context,
+                &block_producer,
+                &withdrawal.request(),
+            )?;
+            state.finalise()?;
+            if !check(state, checkpoints.get(idx))? {
+                return Ok(Some(format!("withdrawal #{}", idx)));
+            }
+        }
+
+        for deposit in exported.deposit_info_vec.clone() {
+            state.apply_deposit_request(rollup_context, &deposit.request())?;
+        }
+        state.finalise()?;
+        let prev_txs_checkpoint: H256 = raw_block
+            .submit_transactions()
+            .prev_state_checkpoint()
+            .unpack();
+        if !check(state, Some(&prev_txs_checkpoint))? {
+            return Ok(Some("deposits".to_string()));
+        }
+
+        let block_info = BlockInfo::new_builder()
+            .block_producer(raw_block.block_producer())
+            .timestamp(raw_block.timestamp())
+            .number(raw_block.number())
+            .build();
+        let max_cycles = generator.fork_config().max_l2_tx_cycles(block_number);
+        for (tx_index, tx) in exported.block.transactions().into_iter().enumerate() {
+            let report = self.tx_index.map_or(true, |idx| idx == tx_index);
+            let raw_tx = tx.raw();
+            let run_result = if report && self.trace_dir.is_some() {
+                generator.trace_transaction(chain_view, state, &block_info, &raw_tx, None)?
+            } else {
+                generator.execute_transaction(
+                    chain_view,
+                    state,
+                    &block_info,
+                    &raw_tx,
+                    None,
+                    None,
+                )?
+            };
+            state.finalise()?;
+
+            let tx_info = format!("tx #{} {:#x}", tx_index, ckb_types::H256(tx.hash()));
+            if report {
+                println!(
+                    "{}: exit code {}, execution cycles {}, virtual cycles {}",
+                    tx_info,
+                    run_result.exit_code,
+                    run_result.cycles.execution,
+                    run_result.cycles.r#virtual
+                );
+                if let Some(ref profile) = run_result.cycles_profile {
+                    for (backend, cycles) in &profile.backends {
+                        println!("  backend {}: {} execution cycles", backend, cycles);
+                    }
+                    block_profile.add_tx(profile);
+                }
+                if let Some(ref dir) = self.trace_dir {
+                    let traces = &run_result.execution_traces;
+                    replay_traces(generator, block_number, traces, max_cycles)?;
+                    let path = dir.join(format!(
+                        "block-#{}-tx-{}-syscall-trace.json",
+                        block_number, tx_index
+                    ));
+                    dump_traces(&path, traces)?;
+                }
+            }
+
+            let checkpoint = checkpoints.get(exported.withdrawals.len() + tx_index);
+            if !check(state, checkpoint)? {
+                return Ok(Some(tx_info));
+            }
+            if self.tx_index == Some(tx_index) {
+                return Ok(None);
+            }
+        }
+
+        let post_account = state.calculate_merkle_state()?;
+        if post_account.as_slice() != raw_block.post_account().as_slice() {
+            return Ok(Some(format!(
+                "post account state, expected {} actual {}",
+                raw_block.post_account(),
+                post_account
+            )));
+        }
+        Ok(None)
+    }
+}
+
+/// Restore the post state of the main chain block `block_number` in memory,
+/// by detaching state of later blocks.
+pub(crate) fn restore_block_state(db: StoreTransaction, block_number: u64) -> Result<ReplayState> {
+    let block = {
+        let hash = db
+            .get_block_hash_by_number(block_number)?
+            .ok_or_else(|| anyhow!("block {} not found", block_number))?;
+        db.get_block(&hash)?
+            .ok_or_else(|| anyhow!("block {} not found", block_number))?
+    };
+    if let Some(pruned) = db.get_pruned_block_number() {
+        ensure!(
+            block_number >= pruned,
+            "state history before block {} is pruned",
+            pruned
+        );
+    }
+    let tip = db.get_last_valid_tip_block()?;
+    let tip_number: u64 = tip.raw().number().unpack();
+    ensure!(
+        block_number <= tip_number,
+        "block {} is after the last valid tip",
+        block_number
+    );
+
+    let tip_root: H256 = tip.raw().post_account().merkle_root().unpack();
+    let smt = SMT::new(tip_root.into(), SMTStateStore::new(MemStore::new(db)));
+    let mut history = HistoryState::new(
+        smt,
+        tip.raw().post_account().count().unpack(),
+        RWConfig::detach_block(),
+    );
+    if block_number < tip_number {
+        history.detach_blocks_state(block_number + 1, tip_number)?;
+    }
+    let post_account = block.raw().post_account();
+    history.set_account_count(post_account.count().unpack())?;
+    let state = StateDB::new(history);
+    let root: H256 = post_account.merkle_root().unpack();
+    ensure!(
+        state.calculate_root()? == root,
+        "restore state of block {}",
+        block_number
+    );
+    Ok(state)
+}
+
+fn replay_traces(
+    generator: &Generator,
+    block_number: u64,
+    traces: &[ExecutionTrace],
+    max_cycles: u64,
+) -> Result<()> {
+    for (idx, trace) in traces.iter().enumerate() {
+        let replay = generator.replay_execution_trace(block_number, trace, max_cycles)?;
+        match replay.divergence {
+            Some(divergence) => println!("  replay call #{} diverged: {}", idx, divergence),
+            None => println!(
+                "  replay call #{}: {} syscalls, exit code {}, cycles {}",
+                idx, replay.syscalls, replay.exit_code, replay.cycles
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn dump_traces(path: &Path, traces: &[ExecutionTrace]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let traces: Vec<JsonExecutionTrace> = traces.iter().cloned().map(Into::into).collect();
+    fs::write(path, serde_json::to_string_pretty(&traces)?)
+        .with_context(|| format!("write {}", path.to_string_lossy()))?;
+    println!("  dump syscall traces to {}", path.to_string_lossy());
+    Ok(())
+}
+
+fn read_config(path: &Path) -> Result<Config> {
+    let content = fs::read(path)
+        .with_context(|| format!("read config file from {}", path.to_string_lossy()))?;
+    toml::from_slice(&content).context("parse config file")
+}
@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use gw_block_producer::runner::BaseInitComponents;
+use gw_mem_pool::mem_block_record::MemBlockRecord;
+
+pub const COMMAND_REPLAY_MEM_BLOCK: &str = "replay-mem-block";
+
+/// Replay recorded mem blocks against the last valid tip of the store
+#[derive(Parser)]
+#[clap(name = COMMAND_REPLAY_MEM_BLOCK)]
+pub struct ReplayMemBlockCommand {
+    /// The config file path
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// Mem block record files, see `mem_pool.mem_block_record_path`
+    #[clap(required = true)]
+    records: Vec<PathBuf>,
+}
+
+impl ReplayMemBlockCommand {
+    pub async fn run(self) -> Result<()> {
+        let content = std::fs::read(&self.config_path).with_context(|| {
+            format!(
+                "read config file from {}",
+                self.config_path.to_string_lossy()
+            )
+        })?;
+        let config = toml::from_slice(&content).context("parse config file")?;
+        let base = BaseInitComponents::init(&config, true).await?;
+
+        for path in self.records {
+            let record = MemBlockRecord::read(&path)?;
+            record
+                .replay(&base.store, &base.generator)
+                .with_context(|| format!("replay {}", path.to_string_lossy()))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use gw_config::Config;
+use gw_store::fixture::FixtureOptions;
+use gw_store::schema::COLUMNS;
+use gw_store::Store;
+use gw_types::h256::H256;
+
+pub const COMMAND_EXPORT_STATE_FIXTURE: &str = "export-state-fixture";
+
+/// Export selected accounts of the state at the last valid tip block as a
+/// test fixture, loadable into a `MemStateDB`
+#[derive(Parser)]
+#[clap(name = COMMAND_EXPORT_STATE_FIXTURE)]
+pub struct ExportStateFixtureCommand {
+    /// The config file path
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// The output file for the fixture
+    #[clap(short, long)]
+    output_path: PathBuf,
+    /// Account ids to export, e.g. `--accounts 4,5`
+    #[clap(long, use_value_delimiter = true)]
+    accounts: Vec<u32>,
+    /// sUDT ids to export balances of the accounts, e.g. `--sudt-ids 1`
+    #[clap(long, use_value_delimiter = true)]
+    sudt_ids: Vec<u32>,
+    /// Storage slots to export, as `<account id>:<0x prefixed 32 bytes key>`.
+    /// Can be specified multiple times
+    #[clap(long, multiple_occurrences = true, parse(try_from_str = parse_slot))]
+    storage: Vec<(u32, H256)>,
+}
+
+impl ExportStateFixtureCommand {
+    pub fn run(self) -> Result<()> {
+        let config = read_config(&self.config_path)?;
+        let opts = FixtureOptions {
+            accounts: self.accounts,
+            sudt_ids: self.sudt_ids,
+            storage: self.storage,
+        };
+        let fixture = {
+            let store = Store::open(&config.store, COLUMNS).context("open database")?;
+            store.export_state_fixture(&opts)?
+        };
+
+        if let Some(parent) = self.output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut content = serde_json::to_string_pretty(&fixture)?;
+        content.push('\n');
+        fs::write(&self.output_path, content)
+            .with_context(|| format!("write {}", self.output_path.to_string_lossy()))?;
+        println!(
+            "exported {} keys, {} scripts and {} data of block #{} to {}",
+            fixture.kvs.len(),
+            fixture.scripts.len(),
+            fixture.data.len(),
+            fixture.block_number,
+            self.output_path.to_string_lossy()
+        );
+        Ok(())
+    }
+}
+
+fn parse_slot(s: &str) -> Result<(u32, H256)> {
+    let (id, key) = s
+        .split_once(':')
+        .ok_or_else(|| anyhow!("expect <account id>:<key>"))?;
+    let id = id.parse().context("parse account id")?;
+    let key = ckb_types::H256::from_str(key.trim_start_matches("0x"))
+        .map_err(|err| anyhow!("parse key: {}", err))?;
+    Ok((id, key.0))
+}
+
+fn read_config(path: &Path) -> Result<Config> {
+    let content = fs::read(path)
+        .with_context(|| format!("read config file from {}", path.to_string_lossy()))?;
+    toml::from_slice(&content).context("parse config file")
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use gw_config::Config;
+use gw_rpc_client::ckb_client::CkbClient;
+use gw_store::migrate::{init_migration_factory, open_or_create_db};
+use gw_store::readonly::StoreReadonly;
+use gw_store::schema::COLUMNS;
+use gw_store::Store;
+use gw_types::{h256::H256, packed::RollupConfig, prelude::*};
+use gw_utils::state_snapshot::{
+    check_snapshot_on_chain, export_snapshot, import_snapshot, verify_snapshot,
+};
+use gw_utils::wallet::Wallet;
+
+pub const COMMAND_EXPORT_SNAPSHOT: &str = "export-snapshot";
+pub const COMMAND_IMPORT_SNAPSHOT: &str = "import-snapshot";
+
+/// Export a signed state snapshot at the last valid tip block, to bootstrap
+/// other nodes
+#[derive(Parser)]
+#[clap(name = COMMAND_EXPORT_SNAPSHOT)]
+pub struct ExportSnapshotCommand {
+    /// The config file path
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// The output file for the snapshot
+    #[clap(short, long)]
+    output_path: PathBuf,
+    /// Private key file to sign the snapshot
+    #[clap(long)]
+    privkey_path: PathBuf,
+}
+
+impl ExportSnapshotCommand {
+    pub fn run(self) -> Result<()> {
+        let config = read_config(&self.config_path)?;
+        let consensus = config.consensus.get_config();
+        let rollup_config: RollupConfig = consensus.genesis.rollup_config.clone().into();
+        let wallet = Wallet::from_privkey_path(&self.privkey_path)?;
+        let snap = StoreReadonly::open(&config.store.path, COLUMNS).context("open database")?;
+
+        if let Some(parent) = self.output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let f = fs::OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(&self.output_path)?;
+        let header = export_snapshot(&snap, &rollup_config, consensus, &wallet, BufWriter::new(f))?;
+
+        let block_number: u64 = header.block_number().unpack();
+        println!(
+            "exported snapshot at block {} {}, signed by {}",
+            block_number,
+            header.block_hash(),
+            wallet.lock_script().args()
+        );
+        Ok(())
+    }
+}
+
+/// Bootstrap an empty database from a state snapshot
+#[derive(Parser)]
+#[clap(name = COMMAND_IMPORT_SNAPSHOT)]
+pub struct ImportSnapshotCommand {
+    /// The config file path
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// The snapshot file
+    #[clap(short, long)]
+    source_path: PathBuf,
+    /// Blake160 of the public key of the trusted snapshot signer, i.e. args
+    /// of its secp256k1 lock
+    #[clap(long)]
+    trusted_signer: String,
+}
+
+impl ImportSnapshotCommand {
+    pub async fn run(self) -> Result<()> {
+        let config = read_config(&self.config_path)?;
+        let trusted_signer =
+            ckb_types::H160::from_str(self.trusted_signer.trim_start_matches("0x"))
+                .context("parse trusted signer")?;
+
+        let header = verify_snapshot(
+            BufReader::new(fs::File::open(&self.source_path)?),
+            &trusted_signer.0,
+        )?;
+        let rollup_type_hash: H256 = header.rollup_type_hash().unpack();
+        let expected_rollup_type_hash: H256 = config
+            .consensus
+            .get_config()
+            .genesis
+            .rollup_type_hash
+            .clone()
+            .into();
+        if rollup_type_hash != expected_rollup_type_hash {
+            bail!(
+                "snapshot of rollup {}, expect {}",
+                header.rollup_type_hash(),
+                expected_rollup_type_hash.pack()
+            );
+        }
+        let ckb_client = CkbClient::with_url(&config.rpc_client.ckb_url)?;
+        check_snapshot_on_chain(&ckb_client, &header).await?;
+
+        let store = Store::new(open_or_create_db(&config.store, init_migration_factory())?);
+        let header = import_snapshot(&store, BufReader::new(fs::File::open(&self.source_path)?))?;
+
+        let block_number: u64 = header.block_number().unpack();
+        println!(
+            "imported snapshot at block {} {}",
+            block_number,
+            header.block_hash()
+        );
+        Ok(())
+    }
+}
+
+fn read_config(path: &Path) -> Result<Config> {
+    let content = fs::read(path)
+        .with_context(|| format!("read config file from {}", path.to_string_lossy()))?;
+    toml::from_slice(&content).context("parse config file")
+}
@@ -1,7 +1,7 @@
 use std::convert::TryFrom;
 
 use ckb_fixed_hash::H256 as JsonH256;
-use ckb_jsonrpc_types::{JsonBytes, Uint64};
+use ckb_jsonrpc_types::{JsonBytes, Uint128, Uint64};
 use gw_types::offchain::{self};
 use serde::{Deserialize, Serialize};
 
@@ -37,7 +37,9 @@ pub struct DebugRunResult {
     pub read_data_hashes: Vec<JsonH256>,
     pub write_data_hashes: Vec<JsonH256>,
     pub debug_log: Vec<String>,
+    #[serde(with = "crate::hex::quantity")]
     pub execution_time_ms: u32,
+    #[serde(with = "crate::hex::quantity")]
     pub write_mem_smt_time_ms: u32,
 }
 
@@ -76,3 +78,99 @@ impl TryFrom<offchain::RunResult> for DebugRunResult {
         })
     }
 }
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct MemoryWrite {
+    pub addr: Uint64,
+    pub data: JsonBytes,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct SyscallTrace {
+    pub number: Uint64,
+    pub args: Vec<Uint64>,
+    pub input: JsonBytes,
+    pub writes: Vec<MemoryWrite>,
+    pub ret: Uint64,
+}
+
+impl From<offchain::SyscallTrace> for SyscallTrace {
+    fn from(t: offchain::SyscallTrace) -> Self {
+        Self {
+            number: t.number.into(),
+            args: t.args.into_iter().map(Into::into).collect(),
+            input: JsonBytes::from_bytes(t.input),
+            writes: t
+                .writes
+                .into_iter()
+                .map(|(addr, data)| MemoryWrite {
+                    addr: addr.into(),
+                    data: JsonBytes::from_bytes(data),
+                })
+                .collect(),
+            ret: t.ret.into(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct ExecutionTrace {
+    pub generator_checksum: JsonH256,
+    pub exit_code: i8,
+    pub syscalls: Vec<SyscallTrace>,
+}
+
+impl From<offchain::ExecutionTrace> for ExecutionTrace {
+    fn from(t: offchain::ExecutionTrace) -> Self {
+        Self {
+            generator_checksum: JsonH256::from_slice(&t.generator_checksum).unwrap(),
+            exit_code: t.exit_code,
+            syscalls: t.syscalls.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PackagingItemKind {
+    Tx,
+    PendingCreateSenderTx,
+    Withdrawal,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PackagingDecision {
+    Packaged,
+    Deferred,
+    Dropped,
+    NotFetched,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct PackagingCandidate {
+    pub hash: JsonH256,
+    pub kind: PackagingItemKind,
+    pub fee: Uint128,
+    pub cycles_limit: Uint64,
+    // arrival order in the fee queue
+    pub order: Uint64,
+    // unix timestamp in milliseconds
+    pub received_at: Uint64,
+    pub decision: PackagingDecision,
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct PackagingExplanation {
+    pub block_number: Uint64,
+    // last decision of the request in the block, None if it wasn't a candidate
+    pub candidate: Option<PackagingCandidate>,
+    // candidates decided before the request, in order
+    pub considered_before: Vec<PackagingCandidate>,
+}
@@ -1207,6 +1207,9 @@ pub struct NodeInfo {
     // field instead of saying it's null.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gasless_tx_support: Option<GaslessTxSupportConfig>,
+    /// Only on nodes producing blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_production: Option<BlockProductionStatus>,
 }
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
@@ -1218,6 +1221,240 @@ pub enum NodeMode {
     ReadOnly,
 }
 
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockProductionStatus {
+    Running,
+    Pausing,
+    Paused,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceStatus {
+    Running,
+    Entering,
+    Active,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DenylistEntry {
+    ScriptHash(H256),
+    RegistryAddress(RegistryAddress),
+}
+
+/// Withdrawal limit of an asset over the rolling window.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct WithdrawalLimit {
+    /// None for CKB capacity.
+    pub sudt_script_hash: Option<H256>,
+    /// None if not limited.
+    pub limit: Option<Uint128>,
+    /// Withdrawn in the current window.
+    pub withdrawn: Uint128,
+    pub window_secs: Uint64,
+}
+
+/// An online backup of the store.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct BackupInfo {
+    pub id: Uint32,
+    /// Unix timestamp in seconds.
+    pub timestamp: Uint64,
+    pub size: Uint64,
+    pub number_files: Uint32,
+}
+
+/// A request dropped after it was queued.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct DroppedRequest {
+    pub hash: H256,
+    pub reason: String,
+    /// Unix timestamp in milliseconds
+    pub dropped_at: Uint64,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestStatusKind {
+    /// Not known by this node, or dropped too long ago.
+    Unknown,
+    /// Submitted but not verified and pushed into the mem pool yet.
+    Queued,
+    /// In the mem block.
+    Pending,
+    Committed,
+    Dropped,
+}
+
+/// Status of a submitted transaction or withdrawal.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct RequestStatus {
+    pub hash: H256,
+    pub status: RequestStatusKind,
+    /// Block of a committed request.
+    pub block_number: Option<Uint64>,
+    pub block_hash: Option<H256>,
+    /// Reason and unix timestamp in milliseconds of a dropped request.
+    pub reason: Option<String>,
+    pub dropped_at: Option<Uint64>,
+}
+
+/// Fee credited to an origin in a block.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct FeeRebate {
+    pub block_number: Uint64,
+    pub origin: String,
+    pub txs: Uint64,
+    pub fee: Uint128,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MemPoolSyncMode {
+    Disabled,
+    // Full node publishes mem block to readonly nodes
+    Publishing,
+    // Readonly node syncs mem block from full node
+    Subscribing,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct MemPoolSenderUsage {
+    pub account_id: Uint32,
+    pub txs: Uint32,
+    pub withdrawals: Uint32,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct MemBlockUsage {
+    pub number: Uint64,
+    pub txs: Uint32,
+    pub max_txs: Uint32,
+    pub withdrawals: Uint32,
+    pub max_withdrawals: Uint32,
+    pub deposits: Uint32,
+    pub cycles_used: Uint64,
+    pub max_cycles: Uint64,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct MemPoolStats {
+    pub pending_txs: Uint32,
+    pub pending_withdrawals: Uint32,
+    pub pending_accounts: Uint32,
+    // requests waiting in RPC submit queue
+    pub queued_txs: Uint32,
+    pub queued_withdrawals: Uint32,
+    pub top_senders: Vec<MemPoolSenderUsage>,
+    pub oldest_entry_age_ms: Option<Uint64>,
+    pub mem_block: MemBlockUsage,
+    pub sync_mode: MemPoolSyncMode,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct AccountQueue {
+    pub account_id: Uint32,
+    // nonce expected by the next request
+    pub next_nonce: Uint32,
+    // nonces of requests in mem block
+    pub pending_nonces: Vec<Uint32>,
+    // requests waiting in RPC submit queue, sorted by nonce
+    pub queued: Vec<QueuedRequest>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct QueuedRequest {
+    pub hash: H256,
+    pub kind: QueuedRequestKind,
+    pub nonce: Uint32,
+    pub promotable: bool,
+    // why the request isn't promotable
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum QueuedRequestKind {
+    Tx,
+    Withdrawal,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct StateRootProof {
+    pub block_hash: H256,
+    // post_account.merkle_root is the post state root of the block
+    pub raw_block: RawL2Block,
+    pub finalized_block_number: Uint64,
+    pub finalized_block_hash: H256,
+    // post global state of the finalized block
+    pub global_state: GlobalState,
+    // compiled block SMT proof of the block against global_state.block.merkle_root
+    pub block_proof: JsonBytes,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct StateProof {
+    pub block_hash: H256,
+    pub block_number: Uint64,
+    // post account state of the block, the proof is against its merkle_root
+    pub post_account: AccountMerkleState,
+    // values of the keys in the order requested, zero if not set
+    pub leaves: Vec<KVPair>,
+    // compiled SMT proof of the leaves
+    pub proof: JsonBytes,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct DepositHistory {
+    // newest first
+    pub deposits: Vec<DepositRecord>,
+    // cursor of the next page, null if there are no more deposits
+    pub cursor: Option<JsonBytes>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct DepositRecord {
+    // L2 block which credited the deposit
+    pub block_number: Uint64,
+    // out point of the deposit cell on L1
+    pub l1_tx_hash: H256,
+    pub l1_output_index: Uint32,
+    pub capacity: Uint64,
+    pub amount: Uint128,
+    pub sudt_script_hash: H256,
+}
+
+/// A polyjuice user log, i.e. an EVM log.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct IndexedLog {
+    pub block_number: Uint64,
+    pub block_hash: H256,
+    pub tx_hash: H256,
+    pub tx_index: Uint32,
+    // index of the log in the tx receipt, including non-user logs
+    pub log_index: Uint32,
+    pub address: H160,
+    pub data: JsonBytes,
+    pub topics: Vec<H256>,
+}
+
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
 #[serde(rename_all = "snake_case")]
 pub struct BackendInfo {
@@ -1539,7 +1776,9 @@ pub struct TransactionStateChanges {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SmtStat {
+    #[serde(with = "crate::hex::quantity")]
     pub update_kvs: u64,
+    #[serde(with = "crate::hex::quantity")]
     pub update_milliseconds: u64,
 }
 
@@ -1547,14 +1786,17 @@ pub struct SmtStat {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum StateChangeEvent {
     AccountState {
+        #[serde(with = "crate::hex::quantity")]
         id: u32,
         address: Option<H160>,
         key: JsonBytes,
         value: H256,
     },
     AccountNonce {
+        #[serde(with = "crate::hex::option_quantity")]
         id: Option<u32>,
         address: Option<H160>,
+        #[serde(with = "crate::hex::quantity")]
         nonce: u32,
     },
     Log {
@@ -1568,3 +1810,51 @@ pub enum StateChangeEvent {
         address: H160,
     },
 }
+
+/// Execution summaries of the transactions of an attached block, streamed to
+/// the execution event sink.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockExecutionEvents {
+    #[serde(with = "crate::hex::quantity")]
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub transactions: Vec<TxExecutionSummary>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TxExecutionSummary {
+    pub tx_hash: H256,
+    #[serde(with = "crate::hex::quantity")]
+    pub from_id: u32,
+    #[serde(with = "crate::hex::quantity")]
+    pub to_id: u32,
+    #[serde(with = "crate::hex::quantity")]
+    pub nonce: u32,
+    #[serde(with = "crate::hex::quantity")]
+    pub exit_code: u8,
+    // gas used and status code of polyjuice transactions
+    #[serde(with = "crate::hex::option_quantity")]
+    pub gas_used: Option<u64>,
+    #[serde(with = "crate::hex::option_quantity")]
+    pub status_code: Option<u32>,
+    pub created_accounts: Vec<Uint32>,
+    pub created_address: Option<H160>,
+    pub sudt_movements: Vec<SudtMovement>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SudtMovementKind {
+    Transfer,
+    PayFee,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SudtMovement {
+    pub kind: SudtMovementKind,
+    #[serde(with = "crate::hex::quantity")]
+    pub sudt_id: u32,
+    pub from: RegistryAddress,
+    pub to: RegistryAddress,
+    pub amount: gw_types::U256,
+}
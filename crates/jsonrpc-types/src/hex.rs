@@ -0,0 +1,192 @@
+//! Canonical hex encoding of RPC values.
+//!
+//! Quantities are encoded as minimal 0x-prefixed hex without leading zeros,
+//! zero is `0x0`. Byte arrays are encoded as even-length 0x-prefixed hex,
+//! empty bytes is `0x`. This matches `Uint64`, `U256` and `JsonBytes`, fields
+//! with plain integer types use [`quantity`] or [`option_quantity`] so every
+//! endpoint returns the same format.
+
+use std::convert::TryFrom;
+
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+pub fn encode_quantity(value: u128) -> String {
+    format!("{:#x}", value)
+}
+
+pub fn encode_bytes(bytes: &[u8]) -> String {
+    format!("0x{}", faster_hex::hex_string(bytes))
+}
+
+/// Decode a quantity. Non-minimal input is accepted.
+pub fn decode_quantity(s: &str) -> Result<u128, String> {
+    let digits = s
+        .strip_prefix("0x")
+        .ok_or_else(|| format!("quantity {} without 0x prefix", s))?;
+    if digits.is_empty() {
+        return Err("empty quantity".to_string());
+    }
+    u128::from_str_radix(digits, 16).map_err(|err| format!("quantity {}: {}", s, err))
+}
+
+pub fn is_canonical_quantity(s: &str) -> bool {
+    match s.strip_prefix("0x") {
+        Some("0") => true,
+        Some(digits) => {
+            !digits.is_empty()
+                && !digits.starts_with('0')
+                && digits.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+        }
+        None => false,
+    }
+}
+
+pub fn is_canonical_bytes(s: &str) -> bool {
+    match s.strip_prefix("0x") {
+        Some(digits) => {
+            digits.len() % 2 == 0 && digits.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+        }
+        None => false,
+    }
+}
+
+/// Serde with module for integer fields, e.g. `#[serde(with = "crate::hex::quantity")]`.
+pub mod quantity {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, s: S) -> Result<S::Ok, S::Error>
+    where
+        T: Copy + Into<u128>,
+        S: Serializer,
+    {
+        s.serialize_str(&encode_quantity((*value).into()))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: TryFrom<u128>,
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        let value = decode_quantity(&s).map_err(D::Error::custom)?;
+        T::try_from(value).map_err(|_| D::Error::custom(format!("quantity {} overflow", s)))
+    }
+}
+
+/// Serde with module for optional integer fields.
+pub mod option_quantity {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &Option<T>, s: S) -> Result<S::Ok, S::Error>
+    where
+        T: Copy + Into<u128>,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => quantity::serialize(value, s),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: TryFrom<u128>,
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(s) => {
+                let value = decode_quantity(&s).map_err(D::Error::custom)?;
+                let value = T::try_from(value)
+                    .map_err(|_| D::Error::custom(format!("quantity {} overflow", s)))?;
+                Ok(Some(value))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_fixed_hash::H256;
+    use ckb_jsonrpc_types::{JsonBytes, Uint128, Uint32, Uint64};
+    use gw_types::U256;
+    use proptest::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Fields {
+        #[serde(with = "quantity")]
+        a: u32,
+        #[serde(with = "quantity")]
+        b: u64,
+        #[serde(with = "option_quantity")]
+        c: Option<u32>,
+    }
+
+    fn as_str(value: serde_json::Value) -> String {
+        value.as_str().expect("string").to_owned()
+    }
+
+    #[test]
+    fn test_canonical_format() {
+        assert_eq!(encode_quantity(0), "0x0");
+        assert_eq!(encode_quantity(16), "0x10");
+        assert_eq!(encode_bytes(&[]), "0x");
+        assert_eq!(encode_bytes(&[0, 1]), "0x0001");
+
+        assert!(!is_canonical_quantity("0x"));
+        assert!(!is_canonical_quantity("0x01"));
+        assert!(!is_canonical_quantity("0xA"));
+        assert!(!is_canonical_quantity("10"));
+        assert!(!is_canonical_bytes("0x1"));
+        assert!(!is_canonical_bytes("00"));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_quantity_fields(a: u32, b: u64, c: Option<u32>) {
+            let fields = Fields { a, b, c };
+            let value = serde_json::to_value(&fields).unwrap();
+            for key in ["a", "b"] {
+                prop_assert!(is_canonical_quantity(value[key].as_str().unwrap()));
+            }
+            match c {
+                Some(_) => prop_assert!(is_canonical_quantity(value["c"].as_str().unwrap())),
+                None => prop_assert!(value["c"].is_null()),
+            }
+            let restored: Fields = serde_json::from_value(value).unwrap();
+            prop_assert_eq!(restored, fields);
+        }
+
+        #[test]
+        fn prop_decode_non_minimal(value: u64, zeros in 0usize..4) {
+            let s = format!("0x{}{:x}", "0".repeat(zeros), value);
+            prop_assert_eq!(decode_quantity(&s).unwrap(), value as u128);
+        }
+
+        #[test]
+        fn prop_uint_types(a: u32, b: u64, c: u128, d: [u8; 32]) {
+            let a = as_str(serde_json::to_value(Uint32::from(a)).unwrap());
+            let b = as_str(serde_json::to_value(Uint64::from(b)).unwrap());
+            let c_hex = as_str(serde_json::to_value(Uint128::from(c)).unwrap());
+            let d = as_str(serde_json::to_value(U256::from_big_endian(&d)).unwrap());
+            for s in [&a, &b, &c_hex, &d] {
+                prop_assert!(is_canonical_quantity(s), "{}", s);
+            }
+            prop_assert_eq!(c_hex, encode_quantity(c));
+        }
+
+        #[test]
+        fn prop_bytes_types(bytes: Vec<u8>, hash: [u8; 32]) {
+            let json_bytes = as_str(serde_json::to_value(JsonBytes::from_vec(bytes.clone())).unwrap());
+            prop_assert!(is_canonical_bytes(&json_bytes));
+            prop_assert_eq!(json_bytes, encode_bytes(&bytes));
+
+            let h256 = as_str(serde_json::to_value(H256(hash)).unwrap());
+            prop_assert!(is_canonical_bytes(&h256));
+            prop_assert_eq!(h256, encode_bytes(&hash));
+        }
+    }
+}
@@ -6,6 +6,7 @@ pub use ckb_jsonrpc_types as blockchain;
 pub mod debug;
 pub mod debugger;
 pub mod godwoken;
+pub mod hex;
 pub mod test_mode;
 
 pub mod number_hash {
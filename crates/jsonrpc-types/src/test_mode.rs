@@ -1,3 +1,4 @@
+use ckb_fixed_hash::H256;
 use ckb_jsonrpc_types::{Uint32, Uint64};
 use serde::{Deserialize, Serialize};
 
@@ -31,4 +32,56 @@ pub enum TestModePayload {
         target_type: ChallengeType,
     },
     WaitForChallengeMaturity,
+    /// Allow the challenger to cancel the current challenge, consumed once
+    /// the cancel tx is submitted.
+    CancelChallenge,
+    /// Allow the challenger to revert the challenged block, consumed once the
+    /// revert tx is submitted.
+    RevertChallenge,
+}
+
+/// Challenge lifecycle stage the challenger observed from the last sync event.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeStage {
+    None,
+    BadBlock,
+    BadChallenge,
+    WaitChallenge,
+}
+
+impl Default for ChallengeStage {
+    fn default() -> Self {
+        ChallengeStage::None
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeStep {
+    Challenge,
+    Cancel,
+    Revert,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ChallengeTargetInfo {
+    pub block_hash: H256,
+    pub target_index: Uint32,
+    pub target_type: ChallengeType,
+}
+
+/// Returned by `tests_get_challenge_status`. `steps` increases by one every
+/// time the challenger submits a challenge, cancel or revert tx, so tests can
+/// wait for it to step deterministically.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct ChallengeStatus {
+    pub payload: Option<TestModePayload>,
+    pub stage: ChallengeStage,
+    pub target: Option<ChallengeTargetInfo>,
+    pub steps: Uint64,
+    pub last_step: Option<ChallengeStep>,
+    pub last_tx_hash: Option<H256>,
 }
@@ -1,39 +1,87 @@
-//! P2P sync server for local/submitted/confirmed Blocks.
+//! P2P sync server for local/submitted/confirmed Blocks, and the state for
+//! bootstrapping readonly nodes.
 
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashSet},
+    future::Future,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
+use anyhow::{anyhow, Context, Result};
 use bytes::Bytes;
 use futures::{StreamExt, TryStreamExt};
 use gw_config::SyncServerConfig;
-use gw_p2p_network::{FnSpawn, P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME};
+use gw_p2p_network::{
+    FnSpawn, Misbehavior, PeerLedger, SubstreamMetrics, P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME,
+};
+use gw_store::{snapshot::StoreSnapshot, traits::chain_store::ChainStore, Store};
 use gw_telemetry::traits::{OpenTelemetrySpanExt, TraceContextExt};
 use gw_types::{
     h256::*,
     packed::{
-        self, BlockSync, BlockSyncUnion, Confirmed, Found, L2Transaction, LocalBlock, NextMemBlock,
-        P2PSyncRequest, P2PSyncRequestReader, P2PSyncResponse, PushTransaction, Revert, Submitted,
-        TryAgain,
+        self, BlockSync, BlockSyncUnion, Confirmed, Found, L2Transaction, L2TransactionVec,
+        LocalBlock, MemBlockAck, MemBlockAckReader, MemBlockDelta, MemBlockSnapshot, NextMemBlock,
+        NumberHash, P2PBlockRangeRequest, P2PBlockRangeRequestReader, P2PBlockRangeResponse,
+        P2PStateSyncRequestReader, P2PSyncRequest, P2PSyncRequestReader, P2PSyncResponse,
+        PushTransaction, Revert, ScriptVec, Submitted, TryAgain,
     },
     prelude::*,
 };
-use gw_utils::compression::StreamEncoder;
-use tentacle::{builder::MetaBuilder, service::ProtocolMeta};
-use tokio::sync::broadcast::{channel, Receiver, Sender};
+use gw_utils::{
+    compression::StreamEncoder,
+    mem_block_signature::BlockSyncSigner,
+    state_snapshot::{export_snapshot_entries, snapshot_header},
+    RollupContext,
+};
+use tentacle::{builder::MetaBuilder, service::ProtocolMeta, utils::extract_peer_id};
+use tokio::{
+    sync::{
+        broadcast::{channel, error::TryRecvError, Receiver, Sender},
+        mpsc, Semaphore,
+    },
+    time::Interval,
+};
 
 #[derive(Default)]
 struct BlockMessages {
     hash: H256,
+    // Messages of a mem block rather than a local block.
+    mem_block: bool,
     messages: Vec<BlockSync>,
 }
 
+/// Build the `BlockSync` message, signed and bound to the block `number_hash`
+/// if there is a signer.
+fn block_sync_message(
+    signer: Option<&BlockSyncSigner>,
+    number_hash: NumberHash,
+    message: impl Into<BlockSyncUnion>,
+) -> Result<BlockSync> {
+    let message = BlockSync::new_builder().set(message).build();
+    match signer {
+        Some(signer) => {
+            let signed = signer.sign(number_hash, message)?;
+            Ok(BlockSync::new_builder().set(signed).build())
+        }
+        None => Ok(message),
+    }
+}
+
 pub struct BlockSyncServerState {
     // Block number -> block hash and messages.
     buffer: BTreeMap<u64, BlockMessages>,
     tx: Sender<BlockSync>,
     buffer_capacity: u64,
+    // Signs messages with the block producer wallet.
+    signer: Option<Arc<BlockSyncSigner>>,
+    // Parent block of the current mem block.
+    mem_block_parent: NumberHash,
+    // The current mem block and its txs, for snapshots.
+    mem_block: Option<NextMemBlock>,
+    mem_block_txs: Vec<L2Transaction>,
+    // Send mem block deltas to each peer this often.
+    mem_block_delta_interval: Option<Duration>,
 }
 
 impl BlockSyncServerState {
@@ -43,6 +91,34 @@ impl BlockSyncServerState {
             buffer: Default::default(),
             tx,
             buffer_capacity: config.buffer_capacity,
+            signer: None,
+            mem_block_parent: NumberHash::default(),
+            mem_block: None,
+            mem_block_txs: Vec::new(),
+            mem_block_delta_interval: config
+                .mem_block_delta_interval_secs
+                .map(Duration::from_secs),
+        }
+    }
+
+    /// Publish messages signed by `signer`, for readonly nodes that require
+    /// signed messages.
+    pub fn set_signer(&mut self, signer: Arc<BlockSyncSigner>) {
+        self.signer = Some(signer);
+    }
+
+    fn message(
+        &self,
+        number_hash: NumberHash,
+        message: impl Into<BlockSyncUnion>,
+    ) -> Option<BlockSync> {
+        let number: u64 = number_hash.number().unpack();
+        match block_sync_message(self.signer.as_deref(), number_hash, message) {
+            Ok(msg) => Some(msg),
+            Err(err) => {
+                log::error!("sign block sync message of block {}: {:#}", number, err);
+                None
+            }
         }
     }
 
@@ -52,11 +128,22 @@ impl BlockSyncServerState {
         let raw = reader.block().raw();
         let number = raw.number().unpack();
         let hash = raw.hash();
-        let msg = BlockSync::new_builder().set(local_block).build();
+        let number_hash = NumberHash::new_builder()
+            .number(number.pack())
+            .block_hash(hash.pack())
+            .build();
+        let msg = match self.message(number_hash, local_block) {
+            Some(msg) => msg,
+            None => return,
+        };
+        // The mem block is produced.
+        self.mem_block = None;
+        self.mem_block_txs.clear();
         self.buffer.insert(
             number,
             BlockMessages {
                 hash,
+                mem_block: false,
                 messages: vec![msg.clone()],
             },
         );
@@ -65,7 +152,10 @@ impl BlockSyncServerState {
 
     pub fn publish_submitted(&mut self, submitted: Submitted) {
         let number = submitted.as_reader().number_hash().number().unpack();
-        let msg = BlockSync::new_builder().set(submitted).build();
+        let msg = match self.message(submitted.number_hash(), submitted) {
+            Some(msg) => msg,
+            None => return,
+        };
         if let Some(msgs) = self.buffer.get_mut(&number) {
             msgs.messages.push(msg.clone());
         }
@@ -74,7 +164,10 @@ impl BlockSyncServerState {
 
     pub fn publish_confirmed(&mut self, confirmed: Confirmed) {
         let number = confirmed.number_hash().number().unpack();
-        let msg = BlockSync::new_builder().set(confirmed).build();
+        let msg = match self.message(confirmed.number_hash(), confirmed) {
+            Some(msg) => msg,
+            None => return,
+        };
         if let Some(msgs) = self.buffer.get_mut(&number) {
             msgs.messages.push(msg.clone());
         }
@@ -90,8 +183,11 @@ impl BlockSyncServerState {
         let number = revert.number_hash().number().unpack();
         // Remove messages for reverted blocks.
         self.buffer.split_off(&(number + 1));
-        let msg = BlockSync::new_builder().set(revert).build();
-        let _ = self.tx.send(msg);
+        self.mem_block = None;
+        self.mem_block_txs.clear();
+        if let Some(msg) = self.message(revert.number_hash(), revert) {
+            let _ = self.tx.send(msg);
+        }
     }
 
     pub fn publish_transaction(&mut self, tx: L2Transaction) {
@@ -103,31 +199,42 @@ impl BlockSyncServerState {
         let msg = PushTransaction::new_builder()
             .trace_id(packed::Byte16::from_slice(&span_context.trace_id().to_bytes()).unwrap())
             .span_id(packed::Byte8::from_slice(&span_context.span_id().to_bytes()).unwrap())
-            .transaction(tx)
+            .transaction(tx.clone())
             .build();
-        let msg = BlockSync::new_builder().set(msg).build();
+        let msg = match self.message(self.mem_block_parent.clone(), msg) {
+            Some(msg) => msg,
+            None => return,
+        };
+        if self.mem_block.is_some() {
+            self.mem_block_txs.push(tx);
+        }
         if let Some((_, messages)) = self.buffer.iter_mut().next_back() {
             // The first message is either a LocalBlock or a NextMemBlock. We
             // only need to buffer it for NextMemBlock.
-            if matches!(
-                messages.messages[0].to_enum(),
-                BlockSyncUnion::NextMemBlock(_)
-            ) {
+            if messages.mem_block {
                 messages.messages.push(msg.clone());
             }
         }
         let _ = self.tx.send(msg);
     }
 
-    pub fn publish_next_mem_block(&mut self, mem_block: NextMemBlock) {
+    /// Publish the mem block on top of the `parent` block.
+    pub fn publish_next_mem_block(&mut self, parent: NumberHash, mem_block: NextMemBlock) {
         log::info!("publish next mem block");
         let number = mem_block.block_info().number().unpack();
 
-        let msg = BlockSync::new_builder().set(mem_block).build();
+        self.mem_block_parent = parent.clone();
+        self.mem_block = Some(mem_block.clone());
+        self.mem_block_txs.clear();
+        let msg = match self.message(parent, mem_block) {
+            Some(msg) => msg,
+            None => return,
+        };
         self.buffer.insert(
             number,
             BlockMessages {
                 hash: [0; 32],
+                mem_block: true,
                 messages: vec![msg.clone()],
             },
         );
@@ -135,6 +242,66 @@ impl BlockSyncServerState {
         let _ = self.tx.send(msg);
     }
 
+    /// Publish the current mem block with all its txs, so that readonly nodes
+    /// that missed some txs resync. It replaces the buffered messages of the
+    /// mem block, so reconnecting peers get the snapshot instead.
+    pub fn publish_mem_block_snapshot(&mut self) {
+        let (number, msg) = match self.mem_block_snapshot() {
+            Some((number, _, msg)) => (number, msg),
+            None => return,
+        };
+        log::debug!(
+            "publish mem block {} snapshot, txs: {}",
+            number,
+            self.mem_block_txs.len()
+        );
+        if let Some(messages) = self.buffer.get_mut(&number) {
+            if messages.mem_block {
+                messages.messages = vec![msg.clone()];
+            }
+        }
+        let _ = self.tx.send(msg);
+    }
+
+    /// Snapshot of the current mem block, with the mem block number and the
+    /// number of txs.
+    fn mem_block_snapshot(&self) -> Option<(u64, u32, BlockSync)> {
+        let mem_block = self.mem_block.clone()?;
+        let number = mem_block.block_info().number().unpack();
+        let snapshot = MemBlockSnapshot::new_builder()
+            .mem_block(mem_block)
+            .transactions(
+                L2TransactionVec::new_builder()
+                    .set(self.mem_block_txs.clone())
+                    .build(),
+            )
+            .build();
+        let msg = self.message(self.mem_block_parent.clone(), snapshot)?;
+        Some((number, self.mem_block_txs.len() as u32, msg))
+    }
+
+    /// Delta of the current mem block for a peer that has acked `tx_count`
+    /// txs of mem block `number`, or None if there are no new txs.
+    fn mem_block_delta(&self, (number, tx_count): (u64, u32)) -> Option<BlockSync> {
+        let mem_block = self.mem_block.as_ref()?;
+        let mem_block_number = mem_block.block_info().number().unpack();
+        let start = if number == mem_block_number {
+            tx_count as usize
+        } else {
+            0
+        };
+        let txs = self
+            .mem_block_txs
+            .get(start..)
+            .filter(|txs| !txs.is_empty())?;
+        let delta = MemBlockDelta::new_builder()
+            .block_number(mem_block_number.pack())
+            .start((start as u32).pack())
+            .tx_hashes(txs.iter().map(|tx| tx.hash()).collect::<Vec<_>>().pack())
+            .build();
+        self.message(self.mem_block_parent.clone(), delta)
+    }
+
     fn get_and_subscribe(
         &self,
         after: P2PSyncRequest,
@@ -154,12 +321,251 @@ impl BlockSyncServerState {
     }
 }
 
-pub fn block_sync_server_protocol(publisher: Arc<Mutex<BlockSyncServerState>>) -> ProtocolMeta {
+/// Serves the state at the tip block to readonly nodes, see
+/// `P2PStateSyncRequest`.
+pub struct StateSyncServer {
+    store: Store,
+    rollup_context: RollupContext,
+    // Exporting the state is expensive, serve one peer at a time.
+    permit: Arc<Semaphore>,
+}
+
+impl StateSyncServer {
+    pub fn new(store: Store, rollup_context: RollupContext) -> Self {
+        Self {
+            store,
+            rollup_context,
+            permit: Arc::new(Semaphore::new(1)),
+        }
+    }
+
+    /// Send the state to the peer, or `TryAgain` if it's not available now.
+    async fn serve<F, Fut, E>(&self, send: &mut F) -> Result<()>
+    where
+        F: FnMut(Bytes) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let try_again = P2PSyncResponse::new_builder()
+            .set(TryAgain::default())
+            .build();
+        let _permit = match self.permit.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                log::info!("busy serving state to another peer");
+                send(try_again.as_bytes()).await?;
+                return Ok(());
+            }
+        };
+        let snap = self.store.get_snapshot();
+        let rollup_config = &self.rollup_context.rollup_config;
+        let header = match snapshot_header(&snap, rollup_config, &self.rollup_context.fork_config) {
+            Ok(header) => header,
+            Err(err) => {
+                log::info!("state is not available: {:#}", err);
+                send(try_again.as_bytes()).await?;
+                return Ok(());
+            }
+        };
+        log::info!("serve state at block {}", header.block_number().unpack());
+        let found = P2PSyncResponse::new_builder().set(Found::default()).build();
+        send(found.as_bytes()).await?;
+
+        let (tx, mut rx) = mpsc::channel(4);
+        let export_handle = tokio::task::spawn_blocking(move || {
+            export_snapshot_entries(&snap, &header, |entry| {
+                tx.blocking_send(entry)
+                    .map_err(|_| anyhow!("state sync session closed"))
+            })
+        });
+        while let Some(entry) = rx.recv().await {
+            send(entry.as_bytes()).await?;
+        }
+        export_handle.await??;
+        Ok(())
+    }
+}
+
+/// Local block `b` as published to the sync server.
+pub fn load_local_block(snap: &StoreSnapshot, b: u64) -> Result<LocalBlock> {
+    let block_hash = snap
+        .get_block_hash_by_number(b)?
+        .context("get block hash")?;
+    let block = snap.get_block(&block_hash)?.context("get block")?;
+    let global_state = snap
+        .get_block_post_global_state(&block_hash)?
+        .context("get block post global state")?;
+    let deposit_info_vec = snap
+        .get_block_deposit_info_vec(b)
+        .context("get block deposit info vec")?;
+    let deposit_asset_scripts = {
+        let reader = deposit_info_vec.as_reader();
+        let asset_hashes: HashSet<H256> = reader
+            .iter()
+            .filter_map(|r| {
+                let h: H256 = r.request().sudt_script_hash().unpack();
+                if h.is_zero() {
+                    None
+                } else {
+                    Some(h)
+                }
+            })
+            .collect();
+        let asset_scripts = asset_hashes.into_iter().map(|h| {
+            snap.get_asset_script(&h)?
+                .with_context(|| format!("block {} asset script {} not found", b, h.pack()))
+        });
+        asset_scripts.collect::<Result<Vec<_>>>()?
+    };
+    let withdrawals = {
+        let reqs = block.as_reader().withdrawals();
+        let extra_reqs = reqs.iter().map(|w| {
+            let h = w.hash();
+            snap.get_withdrawal(&h)?
+                .with_context(|| format!("block {} withdrawal {} not found", b, h.pack()))
+        });
+        extra_reqs.collect::<Result<Vec<_>>>()?
+    };
+    let local_block = LocalBlock::new_builder()
+        .block(block)
+        .post_global_state(global_state)
+        .deposit_info_vec(deposit_info_vec)
+        .deposit_asset_scripts(ScriptVec::new_builder().set(deposit_asset_scripts).build())
+        .withdrawals(withdrawals.pack())
+        .build();
+    Ok(local_block)
+}
+
+pub fn load_submitted(snap: &StoreSnapshot, b: u64) -> Result<Submitted> {
+    let (number_hash, tx_hash) = load_number_hash_and_submit_tx_hash(snap, b)?;
+    Ok(Submitted::new_builder()
+        .number_hash(number_hash)
+        .tx_hash(tx_hash.pack())
+        .build())
+}
+
+pub fn load_confirmed(snap: &StoreSnapshot, b: u64) -> Result<Confirmed> {
+    let (number_hash, tx_hash) = load_number_hash_and_submit_tx_hash(snap, b)?;
+    Ok(Confirmed::new_builder()
+        .number_hash(number_hash)
+        .tx_hash(tx_hash.pack())
+        .build())
+}
+
+fn load_number_hash_and_submit_tx_hash(snap: &StoreSnapshot, b: u64) -> Result<(NumberHash, H256)> {
+    let block_hash = snap
+        .get_block_hash_by_number(b)?
+        .context("get block hash")?;
+    let tx_hash = snap
+        .get_block_submit_tx_hash(b)
+        .context("get submit tx hash")?;
+    let number_hash = NumberHash::new_builder()
+        .number(b.pack())
+        .block_hash(block_hash.pack())
+        .build();
+    Ok((number_hash, tx_hash))
+}
+
+/// Serves confirmed blocks in ranges to readonly nodes that are too far
+/// behind for the buffer of `BlockSyncServerState`, see
+/// `P2PBlockRangeRequest`.
+pub struct BlockRangeServer {
+    store: Store,
+    max_blocks: u32,
+    signer: Option<Arc<BlockSyncSigner>>,
+}
+
+impl BlockRangeServer {
+    pub fn new(store: Store, config: &SyncServerConfig) -> Self {
+        Self {
+            store,
+            max_blocks: config.block_range_max_blocks,
+            signer: None,
+        }
+    }
+
+    /// Serve blocks signed by `signer`, see `BlockSyncServerState::set_signer`.
+    pub fn set_signer(&mut self, signer: Arc<BlockSyncSigner>) {
+        self.signer = Some(signer);
+    }
+
+    async fn serve<F, Fut, E>(&self, request: P2PBlockRangeRequest, send: &mut F) -> Result<()>
+    where
+        F: FnMut(Bytes) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let snap = self.store.get_snapshot();
+        let start = request.start_block_number().unpack();
+        let max_blocks: u32 = request.max_blocks().unpack();
+        let last_confirmed = snap
+            .get_last_confirmed_block_number_hash()
+            .context("last confirmed")?
+            .number()
+            .unpack();
+        let end = last_confirmed
+            .saturating_add(1)
+            .min(start.saturating_add(max_blocks.min(self.max_blocks).into()));
+        let blocks = end.saturating_sub(start) as u32;
+        log::info!("serve {} blocks from {}", blocks, start);
+        let response = P2PBlockRangeResponse::new_builder()
+            .blocks(blocks.pack())
+            .build();
+        send(response.as_bytes()).await?;
+        let signer = self.signer.as_deref();
+        for b in start..end {
+            let submitted = load_submitted(&snap, b)?;
+            let number_hash = submitted.number_hash();
+            let local_block = load_local_block(&snap, b)?;
+            let msg = block_sync_message(signer, number_hash.clone(), local_block)?;
+            send(msg.as_bytes()).await?;
+            let msg = block_sync_message(signer, number_hash.clone(), submitted)?;
+            send(msg.as_bytes()).await?;
+            let confirmed = load_confirmed(&snap, b)?;
+            let msg = block_sync_message(signer, number_hash, confirmed)?;
+            send(msg.as_bytes()).await?;
+        }
+        Ok(())
+    }
+}
+
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+pub fn block_sync_server_protocol(
+    publisher: Arc<Mutex<BlockSyncServerState>>,
+    state_sync_server: Option<Arc<StateSyncServer>>,
+    block_range_server: Option<Arc<BlockRangeServer>>,
+    peer_ledger: Arc<PeerLedger>,
+) -> ProtocolMeta {
     let spawn = FnSpawn(move |context, control, mut read_part| {
         let publisher = publisher.clone();
+        let state_sync_server = state_sync_server.clone();
+        let block_range_server = block_range_server.clone();
+        let peer_ledger = peer_ledger.clone();
         let control = control.clone();
         let session_id = context.id;
+        let peer_id = extract_peer_id(&context.address);
+        let metrics = SubstreamMetrics::new(P2P_SYNC_PROTOCOL_NAME, &context);
+        // The protocol may open before the network closes sessions of banned
+        // peers.
+        let banned = matches!(peer_id, Some(ref p) if peer_ledger.is_banned(p));
+        let report = move |misbehavior| {
+            if let Some(ref peer_id) = peer_id {
+                peer_ledger.report(peer_id, misbehavior);
+            }
+        };
         tokio::spawn(async move {
+            if banned {
+                let _ = control.disconnect(session_id).await;
+                return Ok(());
+            }
             // Compress messages.
             //
             // We keep using the same compression context in one session. This
@@ -168,14 +574,49 @@ pub fn block_sync_server_protocol(publisher: Arc<Mutex<BlockSyncServerState>>) -
             // will be compressed to just a few bytes.
             let mut encoder = StreamEncoder::new(3).expect("create StreamEncoder");
             'outer: while let Some(msg) = read_part.try_next().await? {
-                P2PSyncRequestReader::from_slice(msg.as_ref())?;
-                let request = P2PSyncRequest::new_unchecked(msg);
+                metrics.received(&msg);
                 let mut send = |x: Bytes| {
                     let compressed: Bytes = encoder.encode(&x).expect("compress").into();
                     log::debug!("compression: {} -> {}", x.len(), compressed.len());
+                    metrics.sent(&compressed);
                     control.send_message_to(session_id, P2P_SYNC_PROTOCOL, compressed)
                 };
-                let result = publisher.lock().unwrap().get_and_subscribe(request);
+                if P2PStateSyncRequestReader::from_slice(msg.as_ref()).is_ok() {
+                    log::info!("state sync request. session: {}", session_id);
+                    match state_sync_server {
+                        Some(ref server) => server.serve(&mut send).await?,
+                        None => {
+                            let response = P2PSyncResponse::new_builder()
+                                .set(TryAgain::default())
+                                .build();
+                            send(response.as_bytes()).await?;
+                        }
+                    }
+                    continue;
+                }
+                if P2PBlockRangeRequestReader::from_slice(msg.as_ref()).is_ok() {
+                    let request = P2PBlockRangeRequest::new_unchecked(msg);
+                    match block_range_server {
+                        Some(ref server) => server.serve(request, &mut send).await?,
+                        None => {
+                            let response = P2PBlockRangeResponse::default();
+                            send(response.as_bytes()).await?;
+                        }
+                    }
+                    continue;
+                }
+                if let Err(err) = P2PSyncRequestReader::from_slice(msg.as_ref()) {
+                    log::warn!("invalid request, closing. session: {}: {}", session_id, err);
+                    report(Misbehavior::InvalidMessage);
+                    let _ = control.disconnect(session_id).await;
+                    break;
+                }
+                let request = P2PSyncRequest::new_unchecked(msg);
+                let (result, delta_interval) = {
+                    let publisher = publisher.lock().unwrap();
+                    let result = publisher.get_and_subscribe(request);
+                    (result, publisher.mem_block_delta_interval)
+                };
                 match result {
                     Ok((msgs, mut receiver)) => {
                         let response = P2PSyncResponse::new_builder().set(Found::default()).build();
@@ -183,17 +624,92 @@ pub fn block_sync_server_protocol(publisher: Arc<Mutex<BlockSyncServerState>>) -
                         for msg in msgs {
                             send(msg.as_bytes()).await?;
                         }
+                        let mut delta_interval = delta_interval.map(tokio::time::interval);
+                        // Mem block number and number of txs the peer has acked.
+                        let mut acked: (u64, u32) = (0, 0);
+                        // Deltas sent but not acked yet.
+                        let mut pending_acks = 0usize;
                         loop {
                             let result = tokio::select! {
-                                // We don't expect more messages from the peer.
-                                _ = read_part.next() => break 'outer,
-                                result = receiver.recv() => result,
+                                // We only expect acks of mem block deltas from the peer.
+                                msg = read_part.next() => {
+                                    let ack = match msg {
+                                        Some(Ok(msg))
+                                            if pending_acks > 0
+                                                && MemBlockAckReader::from_slice(&msg).is_ok() =>
+                                        {
+                                            metrics.received(&msg);
+                                            MemBlockAck::new_unchecked(msg)
+                                        }
+                                        Some(_) => {
+                                            log::warn!(
+                                                "unexpected message, closing. session: {}",
+                                                session_id
+                                            );
+                                            report(Misbehavior::Flooding);
+                                            let _ = control.disconnect(session_id).await;
+                                            break 'outer;
+                                        }
+                                        None => break 'outer,
+                                    };
+                                    pending_acks -= 1;
+                                    acked = (ack.block_number().unpack(), ack.tx_count().unpack());
+                                    if u8::from(ack.resync()) == 0 {
+                                        continue;
+                                    }
+                                    let snapshot = publisher.lock().unwrap().mem_block_snapshot();
+                                    match snapshot {
+                                        Some((number, tx_count, msg)) if number == acked.0 => {
+                                            log::info!(
+                                                "peer missed txs of mem block {}. session: {}",
+                                                number,
+                                                session_id
+                                            );
+                                            acked = (number, tx_count);
+                                            Ok(msg)
+                                        }
+                                        _ => continue,
+                                    }
+                                }
+                                _ = tick(&mut delta_interval) => {
+                                    let delta = publisher.lock().unwrap().mem_block_delta(acked);
+                                    match delta {
+                                        Some(msg) => {
+                                            pending_acks += 1;
+                                            Ok(msg)
+                                        }
+                                        None => continue,
+                                    }
+                                }
+                                result = receiver.recv() => {
+                                    match result {
+                                        Ok(msg) => {
+                                            send(msg.as_bytes()).await?;
+                                            continue;
+                                        }
+                                        Err(err) => Err(err),
+                                    }
+                                }
+                            };
+                            // A snapshot or delta of the current mem block.
+                            // Send messages published before it first, so
+                            // that the peer applies the txs before checking
+                            // them.
+                            let result = match result {
+                                Ok(msg) => loop {
+                                    match receiver.try_recv() {
+                                        Ok(published) => send(published.as_bytes()).await?,
+                                        Err(TryRecvError::Empty) => break Ok(msg),
+                                        Err(_) => break Err(()),
+                                    }
+                                },
+                                Err(_) => Err(()),
                             };
                             match result {
                                 Ok(msg) => {
                                     send(msg.as_bytes()).await?;
                                 }
-                                Err(_) => {
+                                Err(()) => {
                                     log::warn!(
                                         "subscription lagged, closing. session: {}",
                                         session_id
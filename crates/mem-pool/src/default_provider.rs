@@ -85,6 +85,7 @@ impl MemPoolProvider for DefaultMemPoolProvider {
                     .deposit_minimal_blocks,
                 MIN_CKB_DEPOSIT_CAPACITY,
                 MIN_SUDT_DEPOSIT_CAPACITY,
+                &self.mem_block_config.deposit_filter,
                 local_cells_manager.dead_cells(),
             )
             .await
@@ -0,0 +1,258 @@
+//! Denylist of senders rejected by mem pool admission.
+//!
+//! Senders are matched by account script hash or registry address. Entries
+//! can be added or removed at runtime by admin RPC, changes take effect
+//! immediately and are saved to the configured file.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use anyhow::{Context, Result};
+use gw_common::{
+    builtins::ETH_REGISTRY_ACCOUNT_ID, registry_address::RegistryAddress, state::State,
+};
+use gw_config::{DenylistConfig, RegistryType};
+use gw_types::h256::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+#[error("sender 0x{} is denied", hex::encode(.script_hash))]
+pub struct DeniedError {
+    pub script_hash: H256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DenylistEntry {
+    ScriptHash(H256),
+    RegistryAddress(RegistryAddress),
+}
+
+#[derive(Default)]
+struct Entries {
+    script_hashes: HashSet<H256>,
+    registry_addresses: HashSet<RegistryAddress>,
+}
+
+impl Entries {
+    fn insert(&mut self, entry: DenylistEntry) -> bool {
+        match entry {
+            DenylistEntry::ScriptHash(hash) => self.script_hashes.insert(hash),
+            DenylistEntry::RegistryAddress(addr) => self.registry_addresses.insert(addr),
+        }
+    }
+
+    fn remove(&mut self, entry: &DenylistEntry) -> bool {
+        match entry {
+            DenylistEntry::ScriptHash(hash) => self.script_hashes.remove(hash),
+            DenylistEntry::RegistryAddress(addr) => self.registry_addresses.remove(addr),
+        }
+    }
+
+    fn to_vec(&self) -> Vec<DenylistEntry> {
+        let hashes = self.script_hashes.iter().cloned();
+        let addrs = self.registry_addresses.iter().cloned();
+        hashes
+            .map(DenylistEntry::ScriptHash)
+            .chain(addrs.map(DenylistEntry::RegistryAddress))
+            .collect()
+    }
+}
+
+pub struct Denylist {
+    entries: RwLock<Entries>,
+    path: Option<PathBuf>,
+}
+
+impl Denylist {
+    pub fn new(config: &DenylistConfig) -> Result<Self> {
+        let mut entries = Entries::default();
+        for hash in config.script_hashes.iter().cloned() {
+            entries.insert(DenylistEntry::ScriptHash(hash.into()));
+        }
+        for addr in config.registry_addresses.iter() {
+            let registry_id = match addr.address_type {
+                RegistryType::Eth => ETH_REGISTRY_ACCOUNT_ID,
+            };
+            let addr = RegistryAddress::new(registry_id, addr.address.as_bytes().to_vec());
+            entries.insert(DenylistEntry::RegistryAddress(addr));
+        }
+        if let Some(ref path) = config.path {
+            if path.exists() {
+                for entry in DenylistFile::read(path)?.into_entries()? {
+                    entries.insert(entry);
+                }
+            }
+        }
+
+        Ok(Denylist {
+            entries: RwLock::new(entries),
+            path: config.path.clone(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let entries = self.entries.read().unwrap();
+        entries.script_hashes.is_empty() && entries.registry_addresses.is_empty()
+    }
+
+    pub fn entries(&self) -> Vec<DenylistEntry> {
+        self.entries.read().unwrap().to_vec()
+    }
+
+    /// Returns false if the entry already exists.
+    pub fn add(&self, entry: DenylistEntry) -> Result<bool> {
+        let mut entries = self.entries.write().unwrap();
+        let added = entries.insert(entry);
+        if added {
+            self.save(&entries)?;
+        }
+        Ok(added)
+    }
+
+    /// Returns false if the entry doesn't exist. Entries from config are loaded
+    /// again after restart.
+    pub fn remove(&self, entry: &DenylistEntry) -> Result<bool> {
+        let mut entries = self.entries.write().unwrap();
+        let removed = entries.remove(entry);
+        if removed {
+            self.save(&entries)?;
+        }
+        Ok(removed)
+    }
+
+    /// Check whether the sender is denied.
+    pub fn check(&self, state: &impl State, sender_script_hash: &H256) -> Result<()> {
+        let entries = self.entries.read().unwrap();
+        if entries.script_hashes.contains(sender_script_hash) {
+            return Err(DeniedError {
+                script_hash: *sender_script_hash,
+            }
+            .into());
+        }
+
+        let registry_ids: BTreeSet<u32> = entries
+            .registry_addresses
+            .iter()
+            .map(|addr| addr.registry_id)
+            .collect();
+        for registry_id in registry_ids {
+            let addr =
+                state.get_registry_address_by_script_hash(registry_id, sender_script_hash)?;
+            if matches!(addr, Some(addr) if entries.registry_addresses.contains(&addr)) {
+                return Err(DeniedError {
+                    script_hash: *sender_script_hash,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn save(&self, entries: &Entries) -> Result<()> {
+        match self.path {
+            Some(ref path) => DenylistFile::from_entries(entries).write(path),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Denylist file, hashes and addresses in hex.
+#[derive(Default, Serialize, Deserialize)]
+struct DenylistFile {
+    script_hashes: Vec<String>,
+    registry_addresses: Vec<RegistryAddressEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RegistryAddressEntry {
+    registry_id: u32,
+    address: String,
+}
+
+impl DenylistFile {
+    fn read(path: &Path) -> Result<Self> {
+        let content = fs::read(path).with_context(|| format!("read denylist {:?}", path))?;
+        serde_json::from_slice(&content).context("parse denylist")
+    }
+
+    fn write(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_vec_pretty(self)?;
+        fs::write(path, content).with_context(|| format!("write denylist {:?}", path))
+    }
+
+    fn from_entries(entries: &Entries) -> Self {
+        let mut script_hashes: Vec<_> = entries
+            .script_hashes
+            .iter()
+            .map(|hash| format!("0x{}", hex::encode(hash)))
+            .collect();
+        script_hashes.sort_unstable();
+        let mut registry_addresses: Vec<_> = entries
+            .registry_addresses
+            .iter()
+            .map(|addr| RegistryAddressEntry {
+                registry_id: addr.registry_id,
+                address: format!("0x{}", hex::encode(&addr.address)),
+            })
+            .collect();
+        registry_addresses.sort_unstable_by(|a, b| a.address.cmp(&b.address));
+
+        DenylistFile {
+            script_hashes,
+            registry_addresses,
+        }
+    }
+
+    fn into_entries(self) -> Result<Vec<DenylistEntry>> {
+        let decode = |s: &str| hex::decode(s.strip_prefix("0x").unwrap_or(s));
+
+        let mut entries = Vec::new();
+        for hash in self.script_hashes {
+            let hash: H256 = decode(&hash)?
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("invalid script hash {}", hash))?;
+            entries.push(DenylistEntry::ScriptHash(hash));
+        }
+        for addr in self.registry_addresses {
+            let address = decode(&addr.address)?;
+            let addr = RegistryAddress::new(addr.registry_id, address);
+            entries.push(DenylistEntry::RegistryAddress(addr));
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denylist_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DenylistConfig {
+            script_hashes: vec![[1u8; 32].into()],
+            path: Some(dir.path().join("denylist.json")),
+            ..Default::default()
+        };
+        let denylist = Denylist::new(&config).unwrap();
+        let addr = DenylistEntry::RegistryAddress(RegistryAddress::new(2, vec![3u8; 20]));
+        assert!(denylist.add(addr.clone()).unwrap());
+        assert!(!denylist.add(addr.clone()).unwrap());
+        assert!(denylist
+            .remove(&DenylistEntry::ScriptHash([1u8; 32]))
+            .unwrap());
+
+        // removed config entry is loaded again, runtime entry is restored
+        let restored = Denylist::new(&config).unwrap();
+        let entries: HashSet<_> = restored.entries().into_iter().collect();
+        assert!(entries.contains(&addr));
+        assert!(entries.contains(&DenylistEntry::ScriptHash([1u8; 32])));
+        assert_eq!(entries.len(), 2);
+    }
+}
@@ -1,2 +1,3 @@
+pub mod ordering;
 pub mod queue;
 pub mod types;
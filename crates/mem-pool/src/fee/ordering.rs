@@ -0,0 +1,68 @@
+//! Ordering policies of fee queue, i.e. which queued items are packaged into
+//! the mem block first.
+
+use std::cmp::Ordering;
+
+use gw_config::TxOrdering;
+
+use super::types::FeeEntry;
+
+/// A queued entry as seen by ordering policies.
+pub struct QueuedEntry<'a> {
+    pub entry: &'a FeeEntry,
+    /// Number of queued nonces of the same sender lower than this entry's,
+    /// when the entry is added.
+    pub sender_rank: usize,
+}
+
+pub trait OrderingPolicy: Send + Sync {
+    /// Compare two queued entries, the greater one is fetched first.
+    ///
+    /// Entries of a sender are still fetched in nonce order regardless of
+    /// the policy.
+    fn cmp(&self, a: &QueuedEntry, b: &QueuedEntry) -> Ordering;
+}
+
+/// Strict arrival order.
+pub struct ArrivalOrder;
+
+impl OrderingPolicy for ArrivalOrder {
+    fn cmp(&self, a: &QueuedEntry, b: &QueuedEntry) -> Ordering {
+        // lower order is priority
+        b.entry
+            .order
+            .cmp(&a.entry.order)
+            .then_with(|| a.entry.cmp(b.entry))
+    }
+}
+
+/// Higher fee rate first, see `Ord` of `FeeEntry`.
+pub struct FeePriority;
+
+impl OrderingPolicy for FeePriority {
+    fn cmp(&self, a: &QueuedEntry, b: &QueuedEntry) -> Ordering {
+        a.entry.cmp(b.entry)
+    }
+}
+
+/// Senders take turns: the lowest nonce of every sender is fetched before the
+/// second lowest of any sender, and so on. Higher fee rate first within a
+/// turn.
+pub struct SenderRoundRobin;
+
+impl OrderingPolicy for SenderRoundRobin {
+    fn cmp(&self, a: &QueuedEntry, b: &QueuedEntry) -> Ordering {
+        // lower rank is priority
+        b.sender_rank
+            .cmp(&a.sender_rank)
+            .then_with(|| a.entry.cmp(b.entry))
+    }
+}
+
+pub fn ordering_policy(ordering: TxOrdering) -> &'static dyn OrderingPolicy {
+    match ordering {
+        TxOrdering::Fifo => &ArrivalOrder,
+        TxOrdering::Fee => &FeePriority,
+        TxOrdering::RoundRobin => &SenderRoundRobin,
+    }
+}
@@ -3,7 +3,10 @@ use gw_common::state::State;
 use gw_telemetry::traits::{
     TelemetryContext, TelemetryContextNewSpan, TelemetrySpanExt, TraceContextExt,
 };
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+};
 use tracing::{field, instrument};
 
 /// Max queue size
@@ -11,19 +14,67 @@ const MAX_QUEUE_SIZE: usize = 100_000;
 /// Drop size when queue is full
 const DROP_SIZE: usize = 100;
 
-use super::types::{FeeEntry, FeeItemSender};
+use super::{
+    ordering::{FeePriority, OrderingPolicy, QueuedEntry},
+    types::{FeeEntry, FeeItemSender},
+};
+
+#[derive(Clone)]
+struct QueueKey {
+    entry: FeeEntry,
+    sender_rank: usize,
+    policy: &'static dyn OrderingPolicy,
+}
+
+impl QueueKey {
+    fn queued(&self) -> QueuedEntry {
+        QueuedEntry {
+            entry: &self.entry,
+            sender_rank: self.sender_rank,
+        }
+    }
+}
+
+impl PartialEq for QueueKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueueKey {}
+
+impl PartialOrd for QueueKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.policy.cmp(&self.queued(), &other.queued())
+    }
+}
 
-/// Txs & withdrawals queue sorted by fee rate
+/// Txs & withdrawals queue sorted by the ordering policy, fee rate by default
 pub struct FeeQueue<T: TelemetryContext> {
     // priority queue to store tx and withdrawal
-    queue: BTreeMap<FeeEntry, T>,
+    queue: BTreeMap<QueueKey, T>,
+    // queued nonces of each sender and their number of entries
+    sender_nonces: HashMap<FeeItemSender, BTreeMap<u32, usize>>,
+    policy: &'static dyn OrderingPolicy,
 }
 
 impl<T: TelemetryContext> FeeQueue<T> {
     #[inline]
     pub fn new() -> Self {
+        Self::with_policy(&FeePriority)
+    }
+
+    pub fn with_policy(policy: &'static dyn OrderingPolicy) -> Self {
         Self {
             queue: BTreeMap::new(),
+            sender_nonces: HashMap::new(),
+            policy,
         }
     }
 
@@ -46,7 +97,16 @@ impl<T: TelemetryContext> FeeQueue<T> {
             entry.item.kind(),
             hex::encode(entry.item.hash().as_slice())
         );
-        self.queue.insert(entry, handle);
+        let nonce = entry.item.nonce();
+        let nonces = self.sender_nonces.entry(entry.sender).or_default();
+        let sender_rank = nonces.range(..nonce).count();
+        *nonces.entry(nonce).or_default() += 1;
+        let key = QueueKey {
+            entry,
+            sender_rank,
+            policy: self.policy,
+        };
+        self.queue.insert(key, handle);
 
         // drop items if full
         if self.is_full() {
@@ -55,7 +115,8 @@ impl<T: TelemetryContext> FeeQueue<T> {
                 let keep = self.queue.split_off(&first_to_keep);
                 let drop = std::mem::replace(&mut self.queue, keep);
 
-                for (_, handle) in drop.iter() {
+                for (key, handle) in drop.iter() {
+                    self.untrack(&key.entry);
                     if let Some(cx) = handle.telemetry_context() {
                         let span = cx.span();
                         span.record_error(anyhow!("queue is full").as_ref());
@@ -71,20 +132,37 @@ impl<T: TelemetryContext> FeeQueue<T> {
         }
     }
 
+    /// Iterate queued items, highest priority first.
+    pub fn iter(&self) -> impl Iterator<Item = (&FeeEntry, &T)> {
+        self.queue.iter().rev().map(|(key, t)| (&key.entry, t))
+    }
+
     #[inline]
     pub fn is_full(&self) -> bool {
         self.queue.len() > MAX_QUEUE_SIZE
     }
 
     fn pop_last(&mut self) -> Option<(FeeEntry, T)> {
-        if let Some(entry) = self.queue.keys().next_back().cloned() {
-            self.queue.remove_entry(&entry)
-        } else {
-            None
+        let (key, t) = self.queue.pop_last()?;
+        self.untrack(&key.entry);
+        Some((key.entry, t))
+    }
+
+    fn untrack(&mut self, entry: &FeeEntry) {
+        if let Some(nonces) = self.sender_nonces.get_mut(&entry.sender) {
+            if let Some(count) = nonces.get_mut(&entry.item.nonce()) {
+                *count -= 1;
+                if *count == 0 {
+                    nonces.remove(&entry.item.nonce());
+                }
+            }
+            if nonces.is_empty() {
+                self.sender_nonces.remove(&entry.sender);
+            }
         }
     }
 
-    /// Fetch items by fee sort
+    /// Fetch items in the order of the ordering policy
     #[instrument(skip_all, err(Debug), fields(count = count, remain = field::Empty))]
     pub fn fetch(&mut self, state: &impl State, count: usize) -> Result<Vec<(FeeEntry, T)>> {
         // sorted fee items
@@ -177,6 +255,55 @@ impl<T: TelemetryContext> FeeQueue<T> {
 
         Ok(fetched_items)
     }
+
+    /// Remove items whose nonce is already consumed, called after tip changed.
+    /// Returns the number of removed items.
+    #[instrument(skip_all, err(Debug), fields(count = self.len()))]
+    pub fn remove_stale(&mut self, state: &impl State) -> Result<usize> {
+        let mut nonces: HashMap<u32, u32> = Default::default();
+        let mut stale_entries = Vec::new();
+        for key in self.queue.keys() {
+            let entry = &key.entry;
+            let account_id = match entry.sender {
+                FeeItemSender::AccountId(account_id) => account_id,
+                FeeItemSender::PendingCreate(_) => continue,
+            };
+            let nonce = match nonces.get(&account_id) {
+                Some(&nonce) => nonce,
+                None => {
+                    let nonce = state.get_nonce(account_id)?;
+                    nonces.insert(account_id, nonce);
+                    nonce
+                }
+            };
+            if entry.item.nonce() < nonce {
+                stale_entries.push(key.clone());
+            }
+        }
+
+        for key in stale_entries.iter() {
+            let entry = &key.entry;
+            if let Some(t) = self.queue.remove(key) {
+                self.untrack(entry);
+                if let Some(cx) = t.telemetry_context() {
+                    let err = anyhow!("stale nonce {}", entry.item.nonce());
+                    let span = cx.span();
+                    span.record_error(err.as_ref());
+                    span.set_status(gw_telemetry::trace::Status::error("drop stale nonce"));
+                }
+            }
+        }
+
+        if !stale_entries.is_empty() {
+            log::debug!(
+                "QueueLen: {} | removed stale entries {}",
+                self.len(),
+                stale_entries.len()
+            );
+        }
+
+        Ok(stale_entries.len())
+    }
 }
 
 impl<T: TelemetryContext> Default for FeeQueue<T> {
@@ -204,6 +331,7 @@ mod tests {
     };
 
     use crate::fee::{
+        ordering::{ArrivalOrder, SenderRoundRobin},
         queue::MAX_QUEUE_SIZE,
         types::{FeeEntry, FeeItem, FeeItemSender},
     };
@@ -424,6 +552,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_remove_stale() {
+        let mut queue = FeeQueue::new();
+
+        let store = Store::open_tmp().expect("open store");
+        setup_genesis(&store);
+        {
+            let mut db = store.begin_transaction();
+            let mut state = BlockStateDB::from_store(&mut db, RWConfig::attach_block(1)).unwrap();
+
+            // create accounts
+            for i in 0..4 {
+                state.create_account(H256::from_u32(i)).unwrap();
+            }
+            state.set_nonce(2, 1).unwrap();
+
+            db.commit().expect("commit");
+        }
+
+        for nonce in 0..3u32 {
+            let entry = FeeEntry {
+                item: FeeItem::Tx(
+                    L2Transaction::new_builder()
+                        .raw(RawL2Transaction::new_builder().nonce(nonce.pack()).build())
+                        .build(),
+                ),
+                fee: (100 * 1000u64).into(),
+                cycles_limit: 1000,
+                sender: FeeItemSender::AccountId(2),
+                order: queue.len(),
+            };
+            queue.add(entry, ());
+        }
+
+        let snap = store.get_snapshot();
+        let tree = MemStateDB::from_store(snap).unwrap();
+
+        assert_eq!(queue.remove_stale(&tree).expect("remove stale"), 1);
+        assert_eq!(queue.len(), 2);
+
+        let items = queue.fetch(&tree, 3).expect("fetch");
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].0.item.nonce(), 1);
+        assert_eq!(items[1].0.item.nonce(), 2);
+    }
+
     #[test]
     fn test_replace_by_fee() {
         let mut queue = FeeQueue::new();
@@ -857,6 +1031,98 @@ mod tests {
 
     const ALWAYS_SUCCESS_CODE_HASH: [u8; 32] = [42u8; 32];
 
+    fn tx_entry(sender: u32, nonce: u32, fee: u64, order: usize) -> FeeEntry {
+        FeeEntry {
+            item: FeeItem::Tx(
+                L2Transaction::new_builder()
+                    .raw(RawL2Transaction::new_builder().nonce(nonce.pack()).build())
+                    .build(),
+            ),
+            fee: (fee * 1000).into(),
+            cycles_limit: 1000,
+            sender: FeeItemSender::AccountId(sender),
+            order,
+        }
+    }
+
+    #[test]
+    fn test_fetch_by_arrival_order() {
+        let mut queue = FeeQueue::with_policy(&ArrivalOrder);
+
+        let store = Store::open_tmp().expect("open store");
+        setup_genesis(&store);
+        {
+            let mut db = store.begin_transaction();
+            let mut state = BlockStateDB::from_store(&mut db, RWConfig::attach_block(1)).unwrap();
+
+            // create accounts
+            for i in 0..4 {
+                state.create_account(H256::from_u32(i)).unwrap();
+            }
+
+            db.commit().expect("commit");
+        }
+
+        queue.add(tx_entry(2, 0, 10, 0), ());
+        queue.add(tx_entry(3, 0, 100, 1), ());
+        queue.add(tx_entry(2, 1, 1000, 2), ());
+        queue.add(tx_entry(4, 0, 1, 3), ());
+
+        let snap = store.get_snapshot();
+        let tree = MemStateDB::from_store(snap).unwrap();
+
+        let items = queue.fetch(&tree, 5).expect("fetch");
+        let orders: Vec<_> = items.iter().map(|(entry, _)| entry.order).collect();
+        assert_eq!(orders, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_fetch_by_sender_round_robin() {
+        let mut queue = FeeQueue::with_policy(&SenderRoundRobin);
+
+        let store = Store::open_tmp().expect("open store");
+        setup_genesis(&store);
+        {
+            let mut db = store.begin_transaction();
+            let mut state = BlockStateDB::from_store(&mut db, RWConfig::attach_block(1)).unwrap();
+
+            // create accounts
+            for i in 0..4 {
+                state.create_account(H256::from_u32(i)).unwrap();
+            }
+
+            db.commit().expect("commit");
+        }
+
+        // Sender 2 floods the queue with higher fee
+        for nonce in 0..3 {
+            queue.add(tx_entry(2, nonce, 100, queue.len()), ());
+        }
+        queue.add(tx_entry(3, 0, 10, queue.len()), ());
+        queue.add(tx_entry(3, 1, 10, queue.len()), ());
+        queue.add(tx_entry(4, 0, 1, queue.len()), ());
+
+        let snap = store.get_snapshot();
+        let tree = MemStateDB::from_store(snap).unwrap();
+
+        let items = queue.fetch(&tree, 10).expect("fetch");
+        let fetched: Vec<_> = items
+            .iter()
+            .map(|(entry, _)| (entry.sender, entry.item.nonce()))
+            .collect();
+        assert_eq!(
+            fetched,
+            vec![
+                (FeeItemSender::AccountId(2), 0),
+                (FeeItemSender::AccountId(3), 0),
+                (FeeItemSender::AccountId(4), 0),
+                (FeeItemSender::AccountId(2), 1),
+                (FeeItemSender::AccountId(3), 1),
+                (FeeItemSender::AccountId(2), 2),
+            ]
+        );
+    }
+
     fn setup_genesis(store: &Store) {
         let rollup_type_hash = H256::from_u32(42);
         let rollup_config = RollupConfig::new_builder()
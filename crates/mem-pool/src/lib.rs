@@ -8,11 +8,17 @@ pub mod block_sync_server;
 mod constants;
 pub mod custodian;
 pub mod default_provider;
+pub mod denylist;
 mod deposit;
 pub mod fee;
 pub mod mem_block;
+pub mod mem_block_record;
 pub mod pool;
+pub mod priority_lane;
 pub mod restore_manager;
+pub mod throttle;
 pub mod traits;
+pub mod tx_relay;
 mod types;
 pub mod withdrawal;
+pub mod withdrawal_limit;
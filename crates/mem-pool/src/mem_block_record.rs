@@ -0,0 +1,280 @@
+//! Replayable mem block records.
+//!
+//! When `mem_block_record_path` is set, the mem pool writes every finished mem
+//! block (block info, withdrawals, deposits, txs, prev state and the post state
+//! of each item) to a JSON file before resetting it. A record can be replayed
+//! against a store whose last valid tip is the parent of the mem block, the
+//! first diverged item is reported, so we can diagnose why a readonly node's
+//! mem block diverged from the full node's.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use gw_common::registry_address::RegistryAddress;
+use gw_generator::{traits::StateExt, Generator};
+use gw_store::{chain_view::ChainView, state::MemStateDB, traits::chain_store::ChainStore, Store};
+use gw_types::{
+    bytes::Bytes,
+    h256::H256,
+    packed::{AccountMerkleState, L2Transaction, NextMemBlock, WithdrawalRequestExtra},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::mem_block::MemBlock;
+
+const RECORD_FILENAME_PREFIX: &str = "mem_block_";
+
+/// Mem block record, molecule serialized entities in hex.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemBlockRecord {
+    /// Block info, withdrawals and deposits
+    pub next_mem_block: String,
+    pub prev_merkle_state: String,
+    pub txs: Vec<String>,
+    pub withdrawal_post_states: Vec<String>,
+    pub deposit_post_states: Vec<String>,
+    pub tx_post_states: Vec<String>,
+}
+
+impl MemBlockRecord {
+    pub(crate) fn new(
+        mem_block: &MemBlock,
+        withdrawals: Vec<WithdrawalRequestExtra>,
+        txs: Vec<L2Transaction>,
+    ) -> Self {
+        let next_mem_block = NextMemBlock::new_builder()
+            .block_info(mem_block.block_info().clone())
+            .withdrawals(withdrawals.pack())
+            .deposits(mem_block.deposits().to_vec().pack())
+            .build();
+        let states_to_hex = |states: &[AccountMerkleState]| -> Vec<String> {
+            states.iter().map(|s| to_hex(s.as_slice())).collect()
+        };
+
+        MemBlockRecord {
+            next_mem_block: to_hex(next_mem_block.as_slice()),
+            prev_merkle_state: to_hex(mem_block.prev_merkle_state().as_slice()),
+            txs: txs.iter().map(|tx| to_hex(tx.as_slice())).collect(),
+            withdrawal_post_states: states_to_hex(mem_block.withdrawal_post_states()),
+            deposit_post_states: states_to_hex(mem_block.deposit_post_states()),
+            tx_post_states: states_to_hex(mem_block.tx_post_states()),
+        }
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read(path.as_ref())
+            .with_context(|| format!("read mem block record {:?}", path.as_ref()))?;
+        serde_json::from_slice(&content).context("parse mem block record")
+    }
+
+    pub fn next_mem_block(&self) -> Result<NextMemBlock> {
+        let bytes = from_hex(&self.next_mem_block)?;
+        Ok(NextMemBlock::from_slice(&bytes)?)
+    }
+
+    pub fn prev_merkle_state(&self) -> Result<AccountMerkleState> {
+        decode_merkle_state(&self.prev_merkle_state)
+    }
+
+    pub fn txs(&self) -> Result<Vec<L2Transaction>> {
+        self.txs
+            .iter()
+            .map(|tx| Ok(L2Transaction::from_slice(&from_hex(tx)?)?))
+            .collect()
+    }
+
+    /// Re-execute the record against the last valid tip of the store, and
+    /// check the post state of each withdrawal, deposit and tx.
+    pub fn replay(&self, store: &Store, generator: &Generator) -> Result<()> {
+        let next_mem_block = self.next_mem_block()?;
+        let block_info = next_mem_block.block_info();
+        let block_number: u64 = block_info.number().unpack();
+
+        let snap = store.get_snapshot();
+        let tip_block_hash = snap.get_last_valid_tip_block_hash()?;
+        let chain_view = ChainView::new(&snap, tip_block_hash);
+        let mut state = MemStateDB::from_store(store.get_snapshot())?;
+
+        let prev_merkle_state = self.prev_merkle_state()?;
+        let tip_merkle_state = state.calculate_merkle_state()?;
+        if tip_merkle_state.as_slice() != prev_merkle_state.as_slice() {
+            bail!(
+                "store tip state {} doesn't match mem block {} prev state {}, \
+                 rewind or import the store to block {}",
+                tip_merkle_state,
+                block_number,
+                prev_merkle_state,
+                block_number.saturating_sub(1)
+            );
+        }
+
+        let rollup_context = generator.rollup_context();
+        let block_producer = {
+            let address: Bytes = block_info.block_producer().unpack();
+            RegistryAddress::from_slice(&address)
+                .ok_or_else(|| anyhow!("invalid block producer"))?
+        };
+
+        let withdrawals = next_mem_block.withdrawals();
+        check_len(
+            "withdrawal",
+            withdrawals.len(),
+            &self.withdrawal_post_states,
+        )?;
+        for (index, withdrawal) in withdrawals.into_iter().enumerate() {
+            state
+                .apply_withdrawal_request(rollup_context, &block_producer, &withdrawal.request())
+                .with_context(|| format!("apply withdrawal {}", index))?;
+            state.finalise()?;
+            check_post_state(&state, "withdrawal", index, &self.withdrawal_post_states)?;
+        }
+
+        let deposits = next_mem_block.deposits();
+        check_len("deposit", deposits.len(), &self.deposit_post_states)?;
+        for (index, deposit) in deposits.into_iter().enumerate() {
+            state
+                .apply_deposit_request(rollup_context, &deposit.request())
+                .with_context(|| format!("apply deposit {}", index))?;
+            state.finalise()?;
+            check_post_state(&state, "deposit", index, &self.deposit_post_states)?;
+        }
+
+        let txs = self.txs()?;
+        check_len("tx", txs.len(), &self.tx_post_states)?;
+        for (index, tx) in txs.into_iter().enumerate() {
+            let run_result = generator
+                .execute_transaction(&chain_view, &mut state, &block_info, &tx.raw(), None, None)
+                .with_context(|| format!("execute tx {} {}", index, to_hex(&tx.hash())))?;
+            state.finalise()?;
+            log::debug!(
+                "[replay] tx {} exit code {} cycles {}",
+                to_hex(&tx.hash()),
+                run_result.exit_code,
+                run_result.cycles.execution
+            );
+            check_post_state(&state, "tx", index, &self.tx_post_states)?;
+        }
+
+        log::info!(
+            "[replay] mem block {} replayed, withdrawals {}, deposits {}, txs {}",
+            block_number,
+            self.withdrawal_post_states.len(),
+            self.deposit_post_states.len(),
+            self.tx_post_states.len()
+        );
+        Ok(())
+    }
+}
+
+/// Write mem block records into a directory.
+#[derive(Clone)]
+pub struct MemBlockRecorder {
+    path: PathBuf,
+}
+
+impl MemBlockRecorder {
+    pub fn build<P: AsRef<Path>>(path: P) -> Result<Self> {
+        fs::create_dir_all(path.as_ref())?;
+        Ok(MemBlockRecorder {
+            path: path.as_ref().to_owned(),
+        })
+    }
+
+    pub fn save(&self, block_number: u64, record: &MemBlockRecord) -> Result<PathBuf> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let file_path = self.path.join(format!(
+            "{}{}_{}.json",
+            RECORD_FILENAME_PREFIX, block_number, now
+        ));
+        fs::write(&file_path, serde_json::to_vec_pretty(record)?)?;
+        Ok(file_path)
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn from_hex(s: &str) -> Result<Vec<u8>> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    Ok(hex::decode(s)?)
+}
+
+fn decode_merkle_state(s: &str) -> Result<AccountMerkleState> {
+    Ok(AccountMerkleState::from_slice(&from_hex(s)?)?)
+}
+
+fn check_len(kind: &str, len: usize, post_states: &[String]) -> Result<()> {
+    if len != post_states.len() {
+        bail!(
+            "{} count {} doesn't match post states count {}",
+            kind,
+            len,
+            post_states.len()
+        );
+    }
+    Ok(())
+}
+
+fn check_post_state(
+    state: &MemStateDB,
+    kind: &str,
+    index: usize,
+    post_states: &[String],
+) -> Result<()> {
+    let expected = decode_merkle_state(&post_states[index])?;
+    let actual = state.calculate_merkle_state()?;
+    if actual.as_slice() != expected.as_slice() {
+        let expected_root: H256 = expected.merkle_root().unpack();
+        let actual_root: H256 = actual.merkle_root().unpack();
+        bail!(
+            "{} {} diverged, expected post state root {} count {}, actual {} count {}",
+            kind,
+            index,
+            to_hex(&expected_root),
+            expected.count().unpack(),
+            to_hex(&actual_root),
+            actual.count().unpack()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
+    use gw_types::packed::{BlockInfo, RawL2Transaction};
+
+    use super::*;
+
+    #[test]
+    fn test_record_roundtrip() {
+        let block_producer = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![1u8; 20]);
+        let block_info = BlockInfo::new_builder()
+            .block_producer(block_producer.to_bytes().pack())
+            .number(42u64.pack())
+            .build();
+        let mem_block = MemBlock::new(block_info.clone(), Default::default(), false);
+        let tx = L2Transaction::new_builder()
+            .raw(RawL2Transaction::new_builder().nonce(1u32.pack()).build())
+            .build();
+        let record = MemBlockRecord::new(&mem_block, vec![], vec![tx.clone()]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = MemBlockRecorder::build(dir.path()).unwrap();
+        let file_path = recorder.save(42, &record).unwrap();
+        let restored = MemBlockRecord::read(file_path).unwrap();
+
+        assert_eq!(restored, record);
+        assert_eq!(
+            restored.next_mem_block().unwrap().block_info().as_slice(),
+            block_info.as_slice()
+        );
+        assert_eq!(restored.txs().unwrap(), vec![tx]);
+    }
+}
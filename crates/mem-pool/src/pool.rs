@@ -24,16 +24,21 @@ use gw_common::{
 };
 use gw_config::{MemBlockConfig, MemPoolConfig, NodeMode, SyscallCyclesConfig};
 use gw_generator::{
-    error::TransactionError,
+    error::{TransactionError, TransactionValidateError},
     generator::CyclesPool,
+    parallel::SpeculativeBatch,
     traits::StateExt,
-    verification::{transaction::TransactionVerifier, withdrawal::WithdrawalVerifier},
+    verification::{
+        intrinsic::IntrinsicVerifier, transaction::TransactionVerifier,
+        withdrawal::WithdrawalVerifier,
+    },
     Generator,
 };
 use gw_store::{
     chain_view::ChainView,
+    fee_rebate::TxOrigin,
     mem_pool_state::{self, MemPoolState, Shared},
-    state::{traits::JournalDB, MemStateDB},
+    state::{overlay::mem_store::OverlaySize, traits::JournalDB, MemStateDB},
     traits::chain_store::ChainStore,
     transaction::StoreTransaction,
     Store,
@@ -47,10 +52,11 @@ use gw_types::{
     h256::*,
     offchain::{DepositInfo, FinalizedCustodianCapacity},
     packed::{
-        AccountMerkleState, BlockInfo, GlobalState, L2Block, L2Transaction, NextMemBlock, Script,
-        TxReceipt, WithdrawalKey, WithdrawalRequest, WithdrawalRequestExtra,
+        AccountMerkleState, BlockInfo, GlobalState, L2Block, L2Transaction, NextMemBlock,
+        NumberHash, Script, TxReceipt, WithdrawalKey, WithdrawalRequest, WithdrawalRequestExtra,
     },
     prelude::*,
+    U256,
 };
 use gw_utils::{calc_finalizing_range, local_cells::LocalCellsManager};
 use tokio::task::block_in_place;
@@ -59,11 +65,16 @@ use tracing::instrument;
 use crate::{
     account_creator::{filter_new_address, AccountCreator},
     block_sync_server::BlockSyncServerState,
+    denylist::Denylist,
     mem_block::MemBlock,
+    mem_block_record::{MemBlockRecord, MemBlockRecorder},
+    priority_lane::PriorityLane,
     restore_manager::RestoreManager,
+    throttle::AccountThrottle,
     traits::MemPoolProvider,
     types::EntryList,
     withdrawal::Generator as WithdrawalGenerator,
+    withdrawal_limit::WithdrawalLimits,
 };
 
 type StateDB = gw_store::state::MemStateDB;
@@ -79,6 +90,35 @@ impl OutputParam {
     }
 }
 
+/// Pending entries of a sender account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SenderUsage {
+    pub account_id: u32,
+    pub txs: usize,
+    pub withdrawals: usize,
+}
+
+/// Snapshot of mem pool statistics
+#[derive(Debug, Clone)]
+pub struct MemPoolStats {
+    pub pending_txs: usize,
+    pub pending_withdrawals: usize,
+    pub pending_accounts: usize,
+    /// Senders with the most pending entries, in descending order
+    pub top_senders: Vec<SenderUsage>,
+    pub oldest_entry_age: Option<Duration>,
+    pub mem_block_number: u64,
+    pub mem_block_txs: usize,
+    pub max_txs: usize,
+    pub mem_block_withdrawals: usize,
+    pub max_withdrawals: usize,
+    pub mem_block_deposits: usize,
+    pub cycles_used: u64,
+    pub max_cycles: u64,
+    /// Whether mem block is published to readonly nodes
+    pub sync_server_enabled: bool,
+}
+
 /// MemPool
 pub struct MemPool {
     /// store
@@ -90,6 +130,9 @@ pub struct MemPool {
     generator: Arc<Generator>,
     /// pending queue, contains executable contents
     pending: HashMap<u32, EntryList>,
+    /// Pending txs that didn't fit in the mem block when re-injected, e.g. the
+    /// mem block is full, retried in the next mem block
+    deferred_txs: Vec<L2Transaction>,
     /// memory block
     mem_block: MemBlock,
     /// Mem pool provider
@@ -108,6 +151,16 @@ pub struct MemPool {
     cycles_pool: CyclesPool,
     /// Account creator
     account_creator: Option<AccountCreator>,
+    /// Reserved mem block slots for operator-critical txs
+    priority_lane: PriorityLane,
+    /// Per-account txs throttling
+    account_throttle: Arc<AccountThrottle>,
+    /// Write finished mem blocks for replaying
+    mem_block_recorder: Option<MemBlockRecorder>,
+    /// Denied senders
+    denylist: Arc<Denylist>,
+    /// Rolling window withdrawal caps
+    withdrawal_limits: Arc<WithdrawalLimits>,
 }
 
 pub struct MemPoolCreateArgs {
@@ -182,6 +235,15 @@ impl MemPool {
             Arc::new(MemPoolState::new(state_db, false))
         };
 
+        let priority_lane = PriorityLane::new(&config.priority_lane);
+        let mem_block_recorder = match config.mem_block_record_path {
+            Some(ref path) => Some(MemBlockRecorder::build(path)?),
+            None => None,
+        };
+        let account_throttle = Arc::new(AccountThrottle::new(config.account_throttle));
+        let denylist = Arc::new(Denylist::new(&config.denylist)?);
+        let withdrawal_limits = Arc::new(WithdrawalLimits::new(&config.withdrawal_limit));
+
         let cycles_pool = CyclesPool::new(
             config.mem_block.max_cycles_limit,
             config.mem_block.syscall_cycles.clone(),
@@ -204,6 +266,7 @@ impl MemPool {
             current_tip: tip,
             generator,
             pending,
+            deferred_txs: Vec::new(),
             mem_block,
             provider,
             pending_deposits,
@@ -214,8 +277,13 @@ impl MemPool {
             mem_block_config: config.mem_block,
             cycles_pool,
             account_creator,
+            priority_lane,
+            account_throttle,
             polyjuice_contract_creator_allowlist,
             sudt_proxy_account_allowlist,
+            mem_block_recorder,
+            denylist,
+            withdrawal_limits,
         };
         mem_pool.restore_pending_withdrawals().await?;
         mem_pool.remove_reinjected_failed_txs()?;
@@ -262,6 +330,22 @@ impl MemPool {
         &self.mem_block_config
     }
 
+    /// Total cycles limit of the mem block.
+    pub fn block_cycles_limit(&self) -> u64 {
+        let block_number = self.mem_block.block_info().number().unpack();
+        self.mem_block_config
+            .block_cycles_limit(self.generator.fork_config(), block_number)
+    }
+
+    /// Cycles left before the soft cycles limit of the mem block, None if no
+    /// soft cycles limit is configured. Cycles used are counted by the vm cost
+    /// model during execution.
+    pub fn soft_available_cycles(&self) -> Option<u64> {
+        let soft_limit = self.mem_block_config.soft_cycles_limit?;
+        let soft_limit = soft_limit.min(self.block_cycles_limit());
+        Some(soft_limit.saturating_sub(self.cycles_pool.cycles_used()))
+    }
+
     pub fn restore_manager(&self) -> &RestoreManager {
         &self.restore_manager
     }
@@ -295,12 +379,211 @@ impl MemPool {
             .save_with_suffix(self.mem_block(), suffix)
     }
 
+    /// Write current mem block into a replayable record if recording is enabled.
+    fn record_mem_block(&self, db: &impl ChainStore) {
+        let recorder = match self.mem_block_recorder {
+            Some(ref recorder) => recorder,
+            None => return,
+        };
+        let block_number: u64 = self.mem_block.block_info().number().unpack();
+        if block_number == 0 {
+            return;
+        }
+
+        let record = || -> Result<_> {
+            let mut withdrawals = Vec::with_capacity(self.mem_block.withdrawals().len());
+            for withdrawal_hash in self.mem_block.withdrawals() {
+                if let Some(withdrawal) = db.get_mem_pool_withdrawal(withdrawal_hash)? {
+                    withdrawals.push(withdrawal);
+                }
+            }
+            let mut txs = Vec::with_capacity(self.mem_block.txs().len());
+            for tx_hash in self.mem_block.txs() {
+                if let Some(tx) = db.get_mem_pool_transaction(tx_hash)? {
+                    txs.push(tx);
+                }
+            }
+            let record = MemBlockRecord::new(&self.mem_block, withdrawals, txs);
+            recorder.save(block_number, &record)
+        };
+        match record() {
+            Ok(path) => log::debug!("[mem-pool] record mem block {} to {:?}", block_number, path),
+            Err(err) => log::warn!("[mem-pool] record mem block {} error {}", block_number, err),
+        }
+    }
+
     pub fn set_provider(&mut self, provider: Box<dyn MemPoolProvider + Send + Sync>) {
         self.provider = provider;
     }
 
+    pub fn priority_lane(&self) -> &PriorityLane {
+        &self.priority_lane
+    }
+
+    pub fn account_throttle(&self) -> Arc<AccountThrottle> {
+        Arc::clone(&self.account_throttle)
+    }
+
+    pub fn denylist(&self) -> Arc<Denylist> {
+        Arc::clone(&self.denylist)
+    }
+
+    pub fn withdrawal_limits(&self) -> Arc<WithdrawalLimits> {
+        Arc::clone(&self.withdrawal_limits)
+    }
+
+    fn check_denylist(&self, state: &impl State, sender_script_hash: &H256) -> Result<()> {
+        if self.denylist.is_empty() {
+            return Ok(());
+        }
+        let ret = self.denylist.check(state, sender_script_hash);
+        if ret.is_err() {
+            gw_metrics::mem_pool().denied_requests.inc();
+        }
+        ret
+    }
+
+    /// Check whether the mem block is full for normal txs, slots reserved for
+    /// the priority lane are excluded.
     pub fn is_mem_txs_full(&self, expect_slots: usize) -> bool {
-        self.mem_block.txs().len().saturating_add(expect_slots) > self.mem_block_config.max_txs
+        self.mem_block.txs().len().saturating_add(expect_slots) > self.max_normal_txs()
+    }
+
+    /// Nonces of the account's txs and withdrawals in mem block, sorted.
+    pub fn pending_nonces(&self, account_id: u32) -> Vec<u32> {
+        let list = match self.pending.get(&account_id) {
+            Some(list) => list,
+            None => return Vec::new(),
+        };
+        let tx_nonces = list.txs.iter().map(|tx| tx.raw().nonce().unpack());
+        let withdrawal_nonces = list.withdrawals.iter().map(|w| w.raw().nonce().unpack());
+        let mut nonces: Vec<u32> = tx_nonces.chain(withdrawal_nonces).collect();
+        nonces.sort_unstable();
+        nonces
+    }
+
+    /// Collect mem pool statistics, at most `top_senders` senders are returned.
+    pub fn stats(&self, top_senders: usize) -> MemPoolStats {
+        let mut senders: Vec<SenderUsage> = self
+            .pending
+            .iter()
+            .map(|(&account_id, list)| SenderUsage {
+                account_id,
+                txs: list.txs.len(),
+                withdrawals: list.withdrawals.len(),
+            })
+            .collect();
+        let pending_txs = senders.iter().map(|s| s.txs).sum();
+        let pending_withdrawals = senders.iter().map(|s| s.withdrawals).sum();
+        senders.sort_unstable_by(|a, b| {
+            (b.txs + b.withdrawals)
+                .cmp(&(a.txs + a.withdrawals))
+                .then(a.account_id.cmp(&b.account_id))
+        });
+        senders.truncate(top_senders);
+
+        let oldest_entry_age = self
+            .pending
+            .values()
+            .filter_map(|list| list.since())
+            .min()
+            .map(|since| since.elapsed());
+
+        MemPoolStats {
+            pending_txs,
+            pending_withdrawals,
+            pending_accounts: self.pending.len(),
+            top_senders: senders,
+            oldest_entry_age,
+            mem_block_number: self.mem_block.block_info().number().unpack(),
+            mem_block_txs: self.mem_block.txs().len(),
+            max_txs: self.mem_block_config.max_txs,
+            mem_block_withdrawals: self.mem_block.withdrawals().len(),
+            max_withdrawals: self.mem_block_config.max_withdrawals,
+            mem_block_deposits: self.mem_block.deposits().len(),
+            cycles_used: self.cycles_pool.cycles_used(),
+            max_cycles: self.block_cycles_limit(),
+            sync_server_enabled: self.sync_server.is_some(),
+        }
+    }
+
+    /// Usage of the mem block in percent of its most used limit among txs,
+    /// withdrawals and cycles. None if the mem block is empty.
+    pub fn mem_block_fullness(&self) -> Option<u64> {
+        let txs = self.mem_block.txs().len();
+        let withdrawals = self.mem_block.withdrawals().len();
+        if txs == 0 && withdrawals == 0 && self.mem_block.deposits().is_empty() {
+            return None;
+        }
+        let percent = |used: u64, limit: u64| used.saturating_mul(100) / limit.max(1);
+        let fullness = [
+            percent(txs as u64, self.mem_block_config.max_txs as u64),
+            percent(
+                withdrawals as u64,
+                self.mem_block_config.max_withdrawals as u64,
+            ),
+            percent(self.cycles_pool.cycles_used(), self.block_cycles_limit()),
+        ];
+        fullness.into_iter().max()
+    }
+
+    /// Size of the mem pool state overlay, also updates the metrics.
+    pub fn overlay_size(&self) -> OverlaySize {
+        let size = self.mem_pool_state.overlay_size();
+        let metrics = gw_metrics::mem_pool();
+        metrics.overlay_keys.set(size.keys as u64);
+        metrics.overlay_bytes.set(size.bytes as u64);
+        size
+    }
+
+    /// Whether the mem pool state overlay exceeds `max_overlay_bytes`, and
+    /// the mem block should be produced early.
+    pub fn is_overlay_full(&self) -> bool {
+        match self.mem_block_config.max_overlay_bytes {
+            Some(max) => self.overlay_size().bytes as u64 > max,
+            None => false,
+        }
+    }
+
+    /// Check whether there are reserved slots or cycles left for priority lane txs.
+    pub fn has_priority_lane_slots(&self) -> bool {
+        self.priority_lane.has_remaining_reserved()
+            && self.mem_block.txs().len() < self.mem_block_config.max_txs
+    }
+
+    /// Number of priority lane txs that can be pushed into current mem block.
+    pub fn priority_lane_slots(&self) -> usize {
+        if !self.has_priority_lane_slots() {
+            return 0;
+        }
+        match self.priority_lane.remaining_reserved_txs() {
+            // Only cycles are reserved, any free slot can be used.
+            0 => self.mem_block_config.max_txs - self.mem_block.txs().len(),
+            reserved_txs => reserved_txs,
+        }
+    }
+
+    /// Available cycles of current mem block for normal txs, cycles reserved
+    /// for the priority lane are excluded.
+    pub fn normal_available_cycles(&self) -> u64 {
+        self.cycles_pool
+            .available_cycles()
+            .saturating_sub(self.priority_lane.remaining_reserved_cycles())
+    }
+
+    fn max_normal_txs(&self) -> usize {
+        self.mem_block_config
+            .max_txs
+            .saturating_sub(self.priority_lane.remaining_reserved_txs())
+    }
+
+    fn is_priority_lane_tx(&self, state: &StateDB, tx: &L2Transaction) -> Result<bool> {
+        if !self.priority_lane.is_enabled() {
+            return Ok(false);
+        }
+        let from_id: u32 = tx.raw().from_id().unpack();
+        let sender_script_hash = state.get_script_hash(from_id)?;
+        Ok(self.priority_lane.contains(&sender_script_hash))
     }
 
     pub fn pending_restored_tx_hashes(&mut self) -> &mut VecDeque<H256> {
@@ -314,13 +597,77 @@ impl MemPool {
     /// Push a layer2 tx into pool
     #[instrument(skip_all)]
     pub fn push_transaction(&mut self, tx: L2Transaction) -> Result<()> {
+        self.push_transaction_inner(tx, None, true, None)
+    }
+
+    /// Push a layer2 tx into pool, the origin is recorded with the tx and
+    /// removed when the tx leaves the pool.
+    #[instrument(skip_all)]
+    pub fn push_transaction_with_origin(
+        &mut self,
+        tx: L2Transaction,
+        origin: Option<TxOrigin>,
+    ) -> Result<()> {
+        self.push_transaction_inner(tx, None, true, origin)
+    }
+
+    /// Re-inject a tx restored from saved mem block, the account throttle is skipped.
+    #[instrument(skip_all)]
+    pub fn reinject_transaction(&mut self, tx: L2Transaction) -> Result<()> {
+        self.push_transaction_inner(tx, None, false, None)
+    }
+
+    /// Push a layer2 tx which already passed [`pre_verify_transaction`] into pool.
+    ///
+    /// The intrinsic and signature checks are skipped, the stateful checks
+    /// (nonce, balance) are still performed against the mem pool state. The tx
+    /// is fully verified again if the mem block has changed since.
+    #[instrument(skip_all)]
+    pub fn push_pre_verified_transaction(
+        &mut self,
+        tx: PreVerifiedTransaction,
+        origin: Option<TxOrigin>,
+    ) -> Result<()> {
+        let block_number: u64 = self.mem_block.block_info().number().unpack();
+        let tx_cost = (tx.block_number == block_number).then_some(tx.tx_cost);
+        self.push_transaction_inner(tx.tx, tx_cost, true, origin)
+    }
+
+    /// `pre_verified_cost` is the cost of a tx which passed
+    /// [`pre_verify_transaction`] in the current mem block.
+    fn push_transaction_inner(
+        &mut self,
+        tx: L2Transaction,
+        pre_verified_cost: Option<U256>,
+        throttle: bool,
+        origin: Option<TxOrigin>,
+    ) -> Result<()> {
         tokio::task::block_in_place(|| {
             let mut db = self.store.begin_transaction();
 
             let mut state = self.mem_pool_state.load_state_db();
-            self.push_transaction_with_db(&mut db, &mut state, tx)?;
+            let account_id: u32 = tx.raw().from_id().unpack();
+            let throttle = throttle && !self.is_priority_lane_tx(&state, &tx)?;
+            if throttle {
+                if let Err(err) = self.account_throttle.check(account_id) {
+                    gw_metrics::mem_pool().throttled_txs.inc();
+                    return Err(err.into());
+                }
+            }
+
+            let tx_hash = tx.hash();
+            self.push_transaction_with_db(&mut db, &mut state, tx, pre_verified_cost, None)?;
+            if let Some(origin) = origin {
+                db.insert_tx_origin(&tx_hash, &origin)?;
+            }
             db.commit()?;
             self.mem_pool_state.store_state_db(state);
+            self.overlay_size();
+
+            if throttle {
+                let in_flight_txs = self.pending.get(&account_id).map_or(0, |l| l.txs.len());
+                self.account_throttle.record_push(account_id, in_flight_txs);
+            }
 
             Ok(())
         })
@@ -333,6 +680,8 @@ impl MemPool {
         db: &mut StoreTransaction,
         state: &mut StateDB,
         tx: L2Transaction,
+        pre_verified_cost: Option<U256>,
+        speculative: Option<&mut SpeculativeBatch>,
     ) -> Result<()> {
         // check duplication
         let tx_hash: H256 = tx.raw().hash();
@@ -340,30 +689,49 @@ impl MemPool {
             return Err(anyhow!("duplicated tx"));
         }
 
+        let sender_id: u32 = tx.raw().from_id().unpack();
+        let sender_script_hash = state.get_script_hash(sender_id)?;
+        self.check_denylist(state, &sender_script_hash)?;
+
         // reject if mem block is full
         // TODO: we can use the pool as a buffer
-        if self.mem_block.txs().len() >= self.mem_block_config.max_txs {
-            return Err(anyhow!(
-                "Mem block is full, MAX_MEM_BLOCK_TXS: {}",
-                self.mem_block_config.max_txs
-            ));
+        let is_priority_lane_tx = self.is_priority_lane_tx(state, &tx)?;
+        let use_priority_lane = is_priority_lane_tx && self.has_priority_lane_slots();
+        let max_txs = if use_priority_lane {
+            self.mem_block_config.max_txs
+        } else {
+            self.max_normal_txs()
+        };
+        if self.mem_block.txs().len() >= max_txs {
+            if is_priority_lane_tx {
+                gw_metrics::mem_pool().priority_lane_rejected_txs.inc();
+            }
+            return Err(anyhow!("Mem block is full, MAX_MEM_BLOCK_TXS: {}", max_txs));
         }
 
-        // verify transaction
+        // verify transaction, intrinsic checks and signature are done before
+        // taking the lock for pre-verified txs
         let polyjuice_creator_id = self.generator.get_polyjuice_creator_id(state)?;
-        TransactionVerifier::new(
+        let verifier = TransactionVerifier::new(
             state,
             self.generator.rollup_context(),
             polyjuice_creator_id,
             self.generator.fork_config(),
-        )
-        .verify(&tx, self.mem_block.block_info().number().unpack())?;
-        // verify signature
-        self.generator.check_transaction_signature(state, &tx)?;
+            self.generator.deployment_policy(),
+        );
+        match pre_verified_cost {
+            Some(tx_cost) => verifier.verify_nonce_and_balance(&tx, tx_cost)?,
+            None => {
+                verifier.verify(&tx, self.mem_block.block_info().number().unpack())?;
+                self.generator.check_transaction_signature(state, &tx)?;
+            }
+        }
 
         // instantly run tx in background & update local state
         let t = Instant::now();
-        let tx_receipt = self.execute_tx(db, state, tx.clone())?;
+        let cycles_used = self.cycles_pool.cycles_used();
+        let tx_receipt = self.execute_tx(db, state, tx.clone(), speculative)?;
+        let tx_cycles = self.cycles_pool.cycles_used().saturating_sub(cycles_used);
         log::debug!("[push tx] finalize tx time: {}ms", t.elapsed().as_millis());
 
         // save new addresses
@@ -383,7 +751,12 @@ impl MemPool {
         let account_id: u32 = tx.raw().from_id().unpack();
         db.insert_mem_pool_transaction(&tx_hash, tx.clone())?;
         let entry_list = self.pending.entry(account_id).or_default();
-        entry_list.txs.push(tx);
+        entry_list.push_tx(tx);
+
+        if use_priority_lane {
+            self.priority_lane.consume(tx_cycles);
+            gw_metrics::mem_pool().priority_lane_txs.inc();
+        }
 
         Ok(())
     }
@@ -393,6 +766,16 @@ impl MemPool {
     pub async fn push_withdrawal_request(
         &mut self,
         withdrawal: WithdrawalRequestExtra,
+    ) -> Result<()> {
+        self.push_withdrawal_request_inner(withdrawal, true).await
+    }
+
+    /// `check_limits` is false for withdrawals restored from db, they have
+    /// been admitted before.
+    async fn push_withdrawal_request_inner(
+        &mut self,
+        withdrawal: WithdrawalRequestExtra,
+        check_limits: bool,
     ) -> Result<()> {
         // check duplication
         let withdrawal_hash: H256 = withdrawal.raw().hash();
@@ -403,21 +786,26 @@ impl MemPool {
         // basic verification without write into state
         // withdrawals will be write into state in the finalize_withdrawals function
         let state = self.mem_pool_state.load_state_db();
+        let account_script_hash: H256 = withdrawal.raw().account_script_hash().unpack();
+        self.check_denylist(&state, &account_script_hash)?;
         self.verify_withdrawal_request(&withdrawal, &state).await?;
+        if check_limits {
+            self.withdrawal_limits.check(&withdrawal.raw())?;
+        }
 
         // Check replace-by-fee
         // TODO
 
-        let account_script_hash: H256 = withdrawal.raw().account_script_hash().unpack();
         let account_id = state
             .get_account_id_by_script_hash(&account_script_hash)?
             .expect("get account_id");
         let entry_list = self.pending.entry(account_id).or_default();
-        entry_list.withdrawals.push(withdrawal.clone());
+        entry_list.push_withdrawal(withdrawal.clone());
         // Add to pool
         let mut db = self.store.begin_transaction();
-        db.insert_mem_pool_withdrawal(&withdrawal_hash, withdrawal)?;
+        db.insert_mem_pool_withdrawal(&withdrawal_hash, withdrawal.clone())?;
         db.commit()?;
+        self.withdrawal_limits.record(&withdrawal.raw());
         Ok(())
     }
 
@@ -468,6 +856,17 @@ impl MemPool {
         withdrawal: &WithdrawalRequestExtra,
         state: &(impl State + CodeStore),
     ) -> Result<()> {
+        // verify withdrawal capacity
+        let capacity: u64 = withdrawal.raw().capacity().unpack();
+        let min_capacity = self.mem_block_config.min_withdrawal_capacity;
+        if capacity < min_capacity {
+            return Err(anyhow!(
+                "withdrawal capacity {} is lower than minimal withdrawal capacity {}",
+                capacity,
+                min_capacity
+            ));
+        }
+
         // verify withdrawal signature
         self.generator
             .check_withdrawal_signature(state, withdrawal)?;
@@ -596,11 +995,13 @@ impl MemPool {
             // re-injecting discarded txs/withdrawals.
             let snapshot = self.store.get_snapshot();
             self.mem_block.reset(&new_tip_block, Duration::ZERO);
+            self.priority_lane.reset();
             let shared = mem_pool_state::Shared {
                 state_db: MemStateDB::from_store(snapshot)?,
                 mem_block: Some(self.mem_block.block_info().to_owned()),
             };
             self.mem_pool_state.store_shared(Arc::new(shared));
+            self.overlay_size();
         }
 
         Ok(())
@@ -742,7 +1143,9 @@ impl MemPool {
             let snap_last_valid_tip = snapshot.get_last_valid_tip_block_hash()?;
             assert_eq!(snap_last_valid_tip, new_tip, "set new snapshot");
 
+            self.record_mem_block(&db);
             let mem_block_content = self.mem_block.reset(&new_tip_block, estimated_timestamp);
+            self.priority_lane.reset();
 
             // set tip
             let new_tip_global_state = self
@@ -783,10 +1186,12 @@ impl MemPool {
 
             // remove from pending
             self.remove_unexecutables(&mut state_db, &mut db)?;
+            let mut revalidating_txs = self.take_pending_txs();
 
             log::info!("[mem-pool] reset reinject txs: {} mem-block txs: {} reinject withdrawals: {} mem-block withdrawals: {}", reinject_txs.len(), mem_block_txs.len(), reinject_withdrawals.len(), mem_block_withdrawals.len());
             // re-inject txs
-            let txs = reinject_txs.into_iter().chain(mem_block_txs).collect();
+            let mut txs: Vec<_> = reinject_txs.into_iter().chain(mem_block_txs).collect();
+            revalidating_txs.extend(self.append_deferred_txs(&mut txs));
 
             // re-inject withdrawals
             let mut withdrawals: Vec<_> = reinject_withdrawals.into_iter().collect();
@@ -801,7 +1206,7 @@ impl MemPool {
             // To simplify logic, don't restrict reinjected txs
             self.cycles_pool = CyclesPool::new(u64::MAX, SyscallCyclesConfig::default());
 
-            self.prepare_next_mem_block(
+            let failed_txs = self.prepare_next_mem_block(
                 &mut db,
                 &mut state_db,
                 withdrawals,
@@ -816,7 +1221,8 @@ impl MemPool {
                 {
                     Ok(Some((tx, next_batch))) => {
                         self.mem_block.append_new_addresses(next_batch);
-                        if let Err(err) = self.push_transaction_with_db(&mut db, &mut state_db, tx)
+                        if let Err(err) =
+                            self.push_transaction_with_db(&mut db, &mut state_db, tx, None, None)
                         {
                             tracing::error!("account creator err {}", err);
                         }
@@ -828,10 +1234,12 @@ impl MemPool {
                 }
             }
 
+            self.drop_invalidated_txs(&mut db, revalidating_txs, failed_txs)?;
+
             // Update block remained cycles
             let used_cycles = self.cycles_pool.cycles_used();
             self.cycles_pool = CyclesPool::new(
-                self.mem_block_config.max_cycles_limit,
+                self.block_cycles_limit(),
                 self.mem_block_config.syscall_cycles.clone(),
             );
             self.cycles_pool.consume_cycles(used_cycles);
@@ -843,6 +1251,7 @@ impl MemPool {
             };
             self.mem_pool_state.store_shared(Arc::new(shared));
             db.commit()?;
+            self.overlay_size();
 
             Ok(())
         })
@@ -865,18 +1274,21 @@ impl MemPool {
         }
         withdrawals.retain(|w| filter_withdrawals(state, w));
 
-        // package withdrawals
-        if withdrawals.len() < self.mem_block_config.max_withdrawals {
-            for entry in self.pending().values() {
-                if let Some(withdrawal) = entry.withdrawals.first() {
-                    if filter_withdrawals(state, withdrawal) {
-                        withdrawals.push(withdrawal.clone());
-                    }
-                    if withdrawals.len() >= self.mem_block_config.max_withdrawals {
-                        break;
-                    }
-                }
-            }
+        // package withdrawals, higher fee first
+        let remaining = self
+            .mem_block_config
+            .max_withdrawals
+            .saturating_sub(withdrawals.len());
+        if remaining > 0 {
+            let mut candidates: Vec<_> = self
+                .pending()
+                .values()
+                .filter_map(|entry| entry.withdrawals.first())
+                .filter(|withdrawal| filter_withdrawals(state, withdrawal))
+                .cloned()
+                .collect();
+            crate::withdrawal::sort_by_fee(&mut candidates);
+            withdrawals.extend(candidates.into_iter().take(remaining));
         }
     }
 
@@ -925,6 +1337,92 @@ impl MemPool {
         for account_id in remove_list {
             self.pending.remove(&account_id);
         }
+        self.account_throttle.update_in_flight(
+            self.pending
+                .iter()
+                .map(|(&account_id, list)| (account_id, list.txs.len())),
+        );
+        Ok(())
+    }
+
+    /// Take pending txs out before they are re-injected into the next mem
+    /// block, so re-injection re-validates them against the new tip instead of
+    /// appending duplicates.
+    fn take_pending_txs(&mut self) -> Vec<H256> {
+        self.pending
+            .values_mut()
+            .flat_map(|list| std::mem::take(&mut list.txs))
+            .map(|tx| tx.hash())
+            .collect()
+    }
+
+    /// Append txs deferred by the previous mem block to the txs to re-inject,
+    /// returns their hashes. A deferred tx is skipped, and so dropped, if a tx
+    /// of the same sender with the same or a higher nonce is re-injected.
+    fn append_deferred_txs(&mut self, txs: &mut Vec<L2Transaction>) -> Vec<H256> {
+        let deferred_txs = std::mem::take(&mut self.deferred_txs);
+        let hashes = deferred_txs.iter().map(|tx| tx.hash()).collect();
+
+        let mut max_nonces: HashMap<u32, u32> = HashMap::default();
+        for tx in txs.iter() {
+            let id: u32 = tx.raw().from_id().unpack();
+            let nonce: u32 = tx.raw().nonce().unpack();
+            let max_nonce = max_nonces.entry(id).or_insert(nonce);
+            *max_nonce = (*max_nonce).max(nonce);
+        }
+        txs.extend(deferred_txs.into_iter().filter(|tx| {
+            let id: u32 = tx.raw().from_id().unpack();
+            let nonce: u32 = tx.raw().nonce().unpack();
+            max_nonces
+                .get(&id)
+                .map_or(true, |&max_nonce| nonce > max_nonce)
+        }));
+        hashes
+    }
+
+    /// Drop previously pending txs that failed re-validation against the new
+    /// tip, i.e. nonce consumed or balance spent by txs in the new block. Txs
+    /// failed for other reasons, e.g. the mem block is full or the cycles
+    /// limit is reached, are kept and retried in the next mem block.
+    #[instrument(skip_all, fields(count = revalidated_txs.len()))]
+    fn drop_invalidated_txs(
+        &mut self,
+        db: &mut StoreTransaction,
+        revalidated_txs: Vec<H256>,
+        failed_txs: Vec<(L2Transaction, anyhow::Error)>,
+    ) -> Result<()> {
+        let mut failed_txs: HashMap<H256, _> = failed_txs
+            .into_iter()
+            .map(|(tx, err)| (tx.hash(), (tx, err)))
+            .collect();
+        let mut dropped = 0;
+        for tx_hash in revalidated_txs {
+            if self.mem_block.txs_set().contains(&tx_hash) {
+                continue;
+            }
+            if let Some((tx, err)) = failed_txs.remove(&tx_hash) {
+                if !is_invalidated_tx_error(&err) {
+                    log::debug!("[mem-pool] defer tx {:x}: {}", tx_hash.pack(), err);
+                    self.deferred_txs.push(tx);
+                    continue;
+                }
+            }
+            log::debug!("[mem-pool] drop invalidated tx {:x}", tx_hash.pack());
+            db.remove_mem_pool_transaction(&tx_hash)?;
+            dropped += 1;
+        }
+        self.pending.retain(|_, list| !list.is_empty());
+        self.account_throttle.update_in_flight(
+            self.pending
+                .iter()
+                .map(|(&account_id, list)| (account_id, list.txs.len())),
+        );
+        if dropped > 0 {
+            log::info!("[mem-pool] drop {} invalidated txs", dropped);
+            gw_metrics::mem_pool()
+                .invalidated_txs
+                .inc_by(dropped as u64);
+        }
         Ok(())
     }
 
@@ -937,7 +1435,7 @@ impl MemPool {
         withdrawals: Vec<WithdrawalRequestExtra>,
         deposit_cells: Vec<DepositInfo>,
         mut txs: Vec<L2Transaction>,
-    ) -> Result<()> {
+    ) -> Result<Vec<(L2Transaction, anyhow::Error)>> {
         // remove txs nonce is lower than current state
         fn filter_tx(state: &StateDB, tx: &L2Transaction) -> bool {
             let raw_tx = tx.raw();
@@ -973,8 +1471,13 @@ impl MemPool {
         self.finalize_deposits(state, deposit_cells.clone())?;
 
         if let Some(ref sync_server) = self.sync_server {
+            let parent = NumberHash::new_builder()
+                .number(self.current_tip.1.pack())
+                .block_hash(self.current_tip.0.pack())
+                .build();
             let mut sync_server = sync_server.lock().unwrap();
             sync_server.publish_next_mem_block(
+                parent,
                 NextMemBlock::new_builder()
                     .block_info(self.mem_block.block_info().clone())
                     .withdrawals(withdrawals.pack())
@@ -984,18 +1487,57 @@ impl MemPool {
         }
 
         // re-inject txs
+        let mut speculative = self.execute_txs_speculatively(db, state, &txs)?;
+        let mut failed_txs = Vec::new();
         for tx in txs {
-            if let Err(err) = self.push_transaction_with_db(db, state, tx.clone()) {
+            let result =
+                self.push_transaction_with_db(db, state, tx.clone(), None, speculative.as_mut());
+            if let Err(err) = result {
                 let tx_hash = tx.hash();
                 log::info!(
                     "[mem pool] fail to re-inject tx {}, error: {}",
                     hex::encode(tx_hash),
                     err
                 );
+                failed_txs.push((tx, err));
             }
         }
 
-        Ok(())
+        Ok(failed_txs)
+    }
+
+    /// Execute txs in parallel before re-injecting them, see
+    /// [`MemBlockConfig::parallel_execution_workers`].
+    fn execute_txs_speculatively(
+        &self,
+        db: &StoreTransaction,
+        state: &StateDB,
+        txs: &[L2Transaction],
+    ) -> Result<Option<SpeculativeBatch>> {
+        let workers = self.mem_block_config.parallel_execution_workers;
+        if workers <= 1 || txs.len() <= 1 {
+            return Ok(None);
+        }
+        let t = Instant::now();
+        let tip_block_hash = db.get_tip_block_hash()?;
+        let chain_view = ChainView::new(&self.store, tip_block_hash);
+        let raw_txs: Vec<_> = txs.iter().map(|tx| tx.raw()).collect();
+        let batch = self.generator.execute_transactions_speculatively(
+            &chain_view,
+            state,
+            self.mem_block.block_info(),
+            &raw_txs,
+            Some(&self.cycles_pool),
+            workers,
+        );
+        log::info!(
+            "[mem pool] speculatively executed {}/{} txs with {} workers in {}ms",
+            batch.len(),
+            raw_txs.len(),
+            workers,
+            t.elapsed().as_millis()
+        );
+        Ok(Some(batch))
     }
 
     /// refresh pending deposits
@@ -1167,7 +1709,7 @@ impl MemPool {
                         .expect("get account_id");
                     let entry_list = self.pending.entry(account_id).or_default();
                     if !entry_list.withdrawals.contains(&withdrawal) {
-                        entry_list.withdrawals.push(withdrawal.clone());
+                        entry_list.push_withdrawal(withdrawal.clone());
                         db.insert_mem_pool_withdrawal(&withdrawal_hash, withdrawal)?;
                     }
 
@@ -1204,6 +1746,7 @@ impl MemPool {
         db: &StoreTransaction,
         state: &mut StateDB,
         tx: L2Transaction,
+        mut speculative: Option<&mut SpeculativeBatch>,
     ) -> Result<TxReceipt> {
         let tip_block_hash = db.get_tip_block_hash()?;
         let chain_view = ChainView::new(&db, tip_block_hash);
@@ -1236,23 +1779,36 @@ impl MemPool {
         let cycles_pool = &mut self.cycles_pool;
         let generator = Arc::clone(&self.generator);
 
-        // execute tx
+        // execute tx, or apply its speculative result if still valid
         let raw_tx = tx.raw();
         let snap = state.snapshot();
-        let run_result = generator
-            .execute_transaction(
+        let speculation = speculative
+            .as_mut()
+            .and_then(|batch| batch.take(&raw_tx.hash(), Some(&*cycles_pool)));
+        let run_result = match speculation {
+            Some(speculation) => speculation.apply(state, Some(cycles_pool)),
+            // written keys are recorded to the batch for conflict detection
+            None if speculative.is_some() => generator.execute_transaction_with_keys(
+                &chain_view,
+                state,
+                block_info,
+                &raw_tx,
+                Some(cycles_pool),
+            ),
+            None => generator.execute_transaction(
                 &chain_view,
                 state,
                 block_info,
                 &raw_tx,
                 None,
                 Some(cycles_pool),
-            )
-            .map_err(|err| {
-                // revert state
-                state.revert(snap).unwrap();
-                err
-            })?;
+            ),
+        }
+        .map_err(|err| {
+            // revert state
+            state.revert(snap).unwrap();
+            err
+        })?;
 
         // check account id of sudt proxy contract creator is from whitelist
         {
@@ -1270,6 +1826,9 @@ impl MemPool {
             }
         }
 
+        if let Some(batch) = speculative {
+            batch.record(&run_result);
+        }
         state.finalise()?;
         // finalise dirty state
         let merkle_state = state.calculate_merkle_state()?;
@@ -1294,7 +1853,7 @@ impl MemPool {
                 continue;
             }
 
-            if let Err(err) = self.push_withdrawal_request(withdrawal).await {
+            if let Err(err) = self.push_withdrawal_request_inner(withdrawal, false).await {
                 // Outdated withdrawal in db before bug fix
                 log::info!(
                     "[mem-pool] withdrawal restore outdated pending {:x} {}, drop it",
@@ -1387,7 +1946,9 @@ impl MemPool {
                 post_merkle_state,
                 enforce_correctness_of_state_checkpoint_list,
             );
+            self.record_mem_block(&snapshot);
             self.mem_block = mem_block;
+            self.priority_lane.reset();
 
             let mut state = StateDB::from_store(snapshot)?;
             let mem_block = self.mem_block.block_info().to_owned();
@@ -1395,6 +1956,7 @@ impl MemPool {
             // remove from pending
             let mut db = self.store.begin_transaction();
             self.remove_unexecutables(&mut state, &mut db)?;
+            let revalidating_txs = self.take_pending_txs();
 
             // reset cycles pool available cycles.
             self.cycles_pool = CyclesPool::new(u64::MAX, SyscallCyclesConfig::default());
@@ -1402,6 +1964,8 @@ impl MemPool {
             // prepare next mem block
             self.try_package_more_withdrawals(&state, &mut withdrawals);
             self.prepare_next_mem_block(&mut db, &mut state, withdrawals, deposits, mem_block_txs)?;
+            // The mem block mirrors the full node's one, nothing is deferred.
+            self.drop_invalidated_txs(&mut db, revalidating_txs, Vec::new())?;
 
             // update mem state
             let shared = Shared {
@@ -1410,6 +1974,7 @@ impl MemPool {
             };
             self.mem_pool_state.store_shared(Arc::new(shared));
             db.commit()?;
+            self.overlay_size();
 
             let mem_block = &self.mem_block;
             log::info!(
@@ -1425,6 +1990,50 @@ impl MemPool {
     }
 }
 
+/// Stateless pre-verification of a layer2 tx: intrinsic checks, see
+/// [`IntrinsicVerifier`], and signature.
+///
+/// It doesn't need the mem pool lock, so callers can run it for a batch of txs
+/// in parallel against a mem pool state snapshot, then push the passed txs with
+/// [`MemPool::push_pre_verified_transaction`].
+#[instrument(skip_all, err(Debug))]
+pub fn pre_verify_transaction(
+    generator: &Generator,
+    state: &(impl State + CodeStore),
+    tx: L2Transaction,
+    block_number: u64,
+) -> Result<PreVerifiedTransaction> {
+    let polyjuice_creator_id = generator.get_polyjuice_creator_id(state)?;
+    let tx_cost = IntrinsicVerifier::new(
+        state,
+        generator.rollup_context(),
+        polyjuice_creator_id,
+        generator.fork_config(),
+        generator.deployment_policy(),
+    )
+    .verify(&tx, block_number)?;
+    generator.check_transaction_signature(state, &tx)?;
+    Ok(PreVerifiedTransaction {
+        tx,
+        tx_cost,
+        block_number,
+    })
+}
+
+/// A layer2 tx which passed [`pre_verify_transaction`] in the mem block
+/// `block_number`.
+pub struct PreVerifiedTransaction {
+    tx: L2Transaction,
+    tx_cost: U256,
+    block_number: u64,
+}
+
+impl PreVerifiedTransaction {
+    pub fn tx(&self) -> &L2Transaction {
+        &self.tx
+    }
+}
+
 pub(crate) fn repackage_count(
     mem_block: &MemBlock,
     output_param: &OutputParam,
@@ -1698,3 +2307,20 @@ mod test {
             .build()
     }
 }
+
+/// Whether a pending tx failed re-validation because its nonce is consumed or
+/// its balance is spent, such a tx can never be packaged.
+fn is_invalidated_tx_error(err: &anyhow::Error) -> bool {
+    let tx_err = match err.downcast_ref::<TransactionValidateError>() {
+        Some(TransactionValidateError::Transaction(tx_err)) => tx_err,
+        Some(_) => return false,
+        None => match err.downcast_ref::<TransactionError>() {
+            Some(tx_err) => tx_err,
+            None => return false,
+        },
+    };
+    matches!(
+        tx_err,
+        TransactionError::Nonce { .. } | TransactionError::InsufficientBalance
+    )
+}
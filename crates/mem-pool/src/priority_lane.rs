@@ -0,0 +1,134 @@
+//! Priority lane for operator-critical txs.
+//!
+//! Txs sent by the configured senders can use the reserved mem block slots and
+//! cycles, other txs can only fill the rest of the mem block. The reservation
+//! is released when the mem block is reset.
+
+use std::collections::HashSet;
+
+use gw_config::PriorityLaneConfig;
+use gw_types::h256::*;
+
+#[derive(Debug, Default)]
+pub struct PriorityLane {
+    senders: HashSet<H256>,
+    reserved_txs: usize,
+    used_txs: usize,
+    reserved_cycles: u64,
+    used_cycles: u64,
+}
+
+impl PriorityLane {
+    pub fn new(config: &PriorityLaneConfig) -> Self {
+        PriorityLane {
+            senders: config
+                .sender_script_hashes
+                .iter()
+                .cloned()
+                .map(Into::into)
+                .collect(),
+            reserved_txs: config.reserved_txs,
+            used_txs: 0,
+            reserved_cycles: config.reserved_cycles,
+            used_cycles: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.senders.is_empty() && (self.reserved_txs > 0 || self.reserved_cycles > 0)
+    }
+
+    pub fn contains(&self, sender_script_hash: &H256) -> bool {
+        self.senders.contains(sender_script_hash)
+    }
+
+    /// Reserved slots not used by priority lane txs in current mem block.
+    pub fn remaining_reserved_txs(&self) -> usize {
+        if self.is_enabled() {
+            self.reserved_txs.saturating_sub(self.used_txs)
+        } else {
+            0
+        }
+    }
+
+    /// Reserved cycles not used by priority lane txs in current mem block.
+    pub fn remaining_reserved_cycles(&self) -> u64 {
+        if self.is_enabled() {
+            self.reserved_cycles.saturating_sub(self.used_cycles)
+        } else {
+            0
+        }
+    }
+
+    /// Whether priority lane txs can still use the reservation.
+    pub fn has_remaining_reserved(&self) -> bool {
+        self.remaining_reserved_txs() > 0 || self.remaining_reserved_cycles() > 0
+    }
+
+    pub(crate) fn consume(&mut self, cycles: u64) {
+        self.used_txs = self.used_txs.saturating_add(1);
+        self.used_cycles = self.used_cycles.saturating_add(cycles);
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.used_txs = 0;
+        self.used_cycles = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_lane_reserved_txs() {
+        let sender = [1u8; 32];
+        let mut lane = PriorityLane::new(&PriorityLaneConfig {
+            sender_script_hashes: vec![sender.into()],
+            reserved_txs: 2,
+            ..Default::default()
+        });
+        assert!(lane.contains(&sender));
+        assert!(!lane.contains(&[2u8; 32]));
+        assert_eq!(lane.remaining_reserved_txs(), 2);
+
+        lane.consume(0);
+        lane.consume(0);
+        lane.consume(0);
+        assert_eq!(lane.remaining_reserved_txs(), 0);
+
+        lane.reset();
+        assert_eq!(lane.remaining_reserved_txs(), 2);
+
+        let disabled = PriorityLane::new(&PriorityLaneConfig {
+            sender_script_hashes: vec![sender.into()],
+            ..Default::default()
+        });
+        assert!(!disabled.is_enabled());
+        assert_eq!(disabled.remaining_reserved_txs(), 0);
+        assert!(!disabled.has_remaining_reserved());
+    }
+
+    #[test]
+    fn test_priority_lane_reserved_cycles() {
+        let sender = [1u8; 32];
+        let mut lane = PriorityLane::new(&PriorityLaneConfig {
+            sender_script_hashes: vec![sender.into()],
+            reserved_cycles: 1000,
+            ..Default::default()
+        });
+        assert!(lane.is_enabled());
+        assert_eq!(lane.remaining_reserved_txs(), 0);
+        assert_eq!(lane.remaining_reserved_cycles(), 1000);
+
+        lane.consume(600);
+        assert_eq!(lane.remaining_reserved_cycles(), 400);
+        assert!(lane.has_remaining_reserved());
+        lane.consume(600);
+        assert_eq!(lane.remaining_reserved_cycles(), 0);
+        assert!(!lane.has_remaining_reserved());
+
+        lane.reset();
+        assert_eq!(lane.remaining_reserved_cycles(), 1000);
+    }
+}
@@ -0,0 +1,186 @@
+//! Per-account throttling of txs pushed into mem pool.
+//!
+//! Limits both the number of txs an account can have in mem pool waiting to be
+//! packaged, and the number of txs it can push in a rate limit window.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use gw_config::AccountThrottleConfig;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum AccountThrottleError {
+    #[error(
+        "account {account_id} has {in_flight} txs in mem pool, exceeded max in flight txs {max}"
+    )]
+    TooManyInFlightTxs {
+        account_id: u32,
+        in_flight: usize,
+        max: usize,
+    },
+    #[error("account {account_id} exceeded rate limit {max} txs per {window_secs}s")]
+    RateLimited {
+        account_id: u32,
+        max: usize,
+        window_secs: u64,
+    },
+}
+
+struct AccountUsage {
+    window_start: Instant,
+    window_txs: usize,
+    in_flight_txs: usize,
+}
+
+pub struct AccountThrottle {
+    config: AccountThrottleConfig,
+    accounts: Mutex<HashMap<u32, AccountUsage>>,
+}
+
+impl AccountThrottle {
+    pub fn new(config: AccountThrottleConfig) -> Self {
+        AccountThrottle {
+            config,
+            accounts: Default::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.max_in_flight_txs > 0 || self.config.rate_limit_txs > 0
+    }
+
+    fn window(&self) -> Duration {
+        Duration::from_secs(self.config.rate_limit_window_secs.max(1))
+    }
+
+    /// Check whether the account can push one more tx.
+    pub fn check(&self, account_id: u32) -> Result<(), AccountThrottleError> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let accounts = self.accounts.lock().unwrap();
+        let usage = match accounts.get(&account_id) {
+            Some(usage) => usage,
+            None => return Ok(()),
+        };
+
+        let max_in_flight = self.config.max_in_flight_txs;
+        if max_in_flight > 0 && usage.in_flight_txs >= max_in_flight {
+            return Err(AccountThrottleError::TooManyInFlightTxs {
+                account_id,
+                in_flight: usage.in_flight_txs,
+                max: max_in_flight,
+            });
+        }
+
+        let max_rate = self.config.rate_limit_txs;
+        if max_rate > 0
+            && usage.window_start.elapsed() < self.window()
+            && usage.window_txs >= max_rate
+        {
+            return Err(AccountThrottleError::RateLimited {
+                account_id,
+                max: max_rate,
+                window_secs: self.window().as_secs(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Record a tx pushed by the account, `in_flight_txs` is the number of
+    /// account txs in mem pool after the push.
+    pub(crate) fn record_push(&self, account_id: u32, in_flight_txs: usize) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let window = self.window();
+        let mut accounts = self.accounts.lock().unwrap();
+        let usage = accounts.entry(account_id).or_insert_with(|| AccountUsage {
+            window_start: Instant::now(),
+            window_txs: 0,
+            in_flight_txs: 0,
+        });
+        if usage.window_start.elapsed() >= window {
+            usage.window_start = Instant::now();
+            usage.window_txs = 0;
+        }
+        usage.window_txs = usage.window_txs.saturating_add(1);
+        usage.in_flight_txs = in_flight_txs;
+    }
+
+    /// Update in flight txs of all accounts after mem pool pending txs changed,
+    /// accounts without in flight txs and with expired window are removed.
+    pub(crate) fn update_in_flight(&self, in_flight: impl IntoIterator<Item = (u32, usize)>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let in_flight: HashMap<u32, usize> = in_flight.into_iter().collect();
+        let window = self.window();
+        let mut accounts = self.accounts.lock().unwrap();
+        accounts.retain(|account_id, usage| {
+            usage.in_flight_txs = in_flight.get(account_id).copied().unwrap_or(0);
+            usage.in_flight_txs > 0 || usage.window_start.elapsed() < window
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_in_flight_txs() {
+        let throttle = AccountThrottle::new(AccountThrottleConfig {
+            max_in_flight_txs: 2,
+            ..Default::default()
+        });
+        throttle.record_push(1, 1);
+        assert!(throttle.check(1).is_ok());
+        throttle.record_push(1, 2);
+        assert!(matches!(
+            throttle.check(1),
+            Err(AccountThrottleError::TooManyInFlightTxs { account_id: 1, .. })
+        ));
+        assert!(throttle.check(2).is_ok());
+
+        // txs packaged into block
+        throttle.update_in_flight(vec![(1, 1)]);
+        assert!(throttle.check(1).is_ok());
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let throttle = AccountThrottle::new(AccountThrottleConfig {
+            rate_limit_txs: 2,
+            rate_limit_window_secs: 60,
+            ..Default::default()
+        });
+        throttle.record_push(1, 1);
+        throttle.record_push(1, 2);
+        assert!(matches!(
+            throttle.check(1),
+            Err(AccountThrottleError::RateLimited { account_id: 1, .. })
+        ));
+
+        // rate limit still applies after txs are packaged
+        throttle.update_in_flight(vec![]);
+        assert!(throttle.check(1).is_err());
+    }
+
+    #[test]
+    fn test_disabled() {
+        let throttle = AccountThrottle::new(Default::default());
+        for _ in 0..100 {
+            throttle.record_push(1, 100);
+        }
+        assert!(throttle.check(1).is_ok());
+    }
+}
@@ -0,0 +1,161 @@
+//! Relay txs submitted to readonly nodes to the full node over p2p.
+//!
+//! Readonly nodes queue submitted txs, and the queue is drained into the
+//! session with the full node. The full node checks and queues relayed txs
+//! like txs submitted to its own RPC. Both sides use bounded queues, so a slow
+//! full node makes readonly nodes reject submissions instead of buffering
+//! them without limit.
+
+use std::sync::{Arc, Mutex};
+
+use futures::StreamExt;
+use gw_p2p_network::{
+    FnSpawn, Misbehavior, PeerLedger, SubstreamMetrics, P2P_TX_RELAY_PROTOCOL,
+    P2P_TX_RELAY_PROTOCOL_NAME,
+};
+use gw_types::{
+    h256::*,
+    packed::{L2Transaction, L2TransactionReader},
+    prelude::*,
+};
+use lru::LruCache;
+use tentacle::{builder::MetaBuilder, service::ProtocolMeta, utils::extract_peer_id};
+use tokio::sync::mpsc;
+
+const QUEUE_SIZE: usize = 1024;
+const RECENT_TXS_SIZE: usize = 16 * 1024;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum TxRelayError {
+    #[error("tx relay queue is full")]
+    Full,
+    #[error("tx relay is closed")]
+    Closed,
+}
+
+/// Readonly node side of the relay.
+pub struct TxRelay {
+    sender: mpsc::Sender<L2Transaction>,
+    // Hashes of recently relayed txs, so that resubmitted txs are only
+    // relayed once.
+    recent: Mutex<LruCache<H256, ()>>,
+}
+
+impl TxRelay {
+    /// Returns the relay and its p2p protocol.
+    pub fn new() -> (Self, ProtocolMeta) {
+        let (sender, receiver) = mpsc::channel(QUEUE_SIZE);
+        let relay = Self {
+            sender,
+            recent: Mutex::new(LruCache::new(RECENT_TXS_SIZE)),
+        };
+        (relay, tx_relay_client_protocol(receiver))
+    }
+
+    /// Queue the tx for relaying. Returns false if it was relayed recently.
+    pub fn relay(&self, tx: L2Transaction) -> Result<bool, TxRelayError> {
+        let tx_hash = tx.hash();
+        let mut recent = self.recent.lock().unwrap();
+        if recent.contains(&tx_hash) {
+            return Ok(false);
+        }
+        self.sender.try_send(tx).map_err(|err| match err {
+            mpsc::error::TrySendError::Full(_) => TxRelayError::Full,
+            mpsc::error::TrySendError::Closed(_) => TxRelayError::Closed,
+        })?;
+        recent.put(tx_hash, ());
+        Ok(true)
+    }
+}
+
+fn tx_relay_client_protocol(receiver: mpsc::Receiver<L2Transaction>) -> ProtocolMeta {
+    let receiver = Arc::new(tokio::sync::Mutex::new(receiver));
+    let spawn = FnSpawn(move |context, control, mut read_part| {
+        let receiver = receiver.clone();
+        let control = control.clone();
+        let session_id = context.id;
+        let metrics = SubstreamMetrics::new(P2P_TX_RELAY_PROTOCOL_NAME, &context);
+        tokio::spawn(async move {
+            // Drain the queue into one session at a time. A readonly node
+            // should only be connected to the full node anyway.
+            let mut receiver = receiver.lock().await;
+            loop {
+                tokio::select! {
+                    // The full node doesn't send messages.
+                    msg = read_part.next() => {
+                        if msg.is_some() {
+                            log::warn!("unexpected message, closing. session: {}", session_id);
+                            let _ = control.disconnect(session_id).await;
+                        }
+                        break;
+                    }
+                    tx = receiver.recv() => {
+                        let msg = match tx {
+                            Some(tx) => tx.as_bytes(),
+                            None => break,
+                        };
+                        metrics.sent(&msg);
+                        let result = control
+                            .send_message_to(session_id, P2P_TX_RELAY_PROTOCOL, msg)
+                            .await;
+                        if let Err(err) = result {
+                            log::warn!("relay tx. session: {}: {}", session_id, err);
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    });
+    MetaBuilder::new()
+        .name(|_| P2P_TX_RELAY_PROTOCOL_NAME.into())
+        .id(P2P_TX_RELAY_PROTOCOL)
+        .protocol_spawn(spawn)
+        .build()
+}
+
+/// Full node side of the relay. Returns the protocol and the receiver of
+/// relayed txs. The protocol stops reading from peers while the receiver is
+/// full.
+pub fn tx_relay_server_protocol(
+    peer_ledger: Arc<PeerLedger>,
+) -> (ProtocolMeta, mpsc::Receiver<L2Transaction>) {
+    let (sender, receiver) = mpsc::channel(QUEUE_SIZE);
+    let spawn = FnSpawn(move |context, control, mut read_part| {
+        let sender = sender.clone();
+        let peer_ledger = peer_ledger.clone();
+        let control = control.clone();
+        let session_id = context.id;
+        let peer_id = extract_peer_id(&context.address);
+        let metrics = SubstreamMetrics::new(P2P_TX_RELAY_PROTOCOL_NAME, &context);
+        tokio::spawn(async move {
+            // The protocol may open before the network closes sessions of
+            // banned peers.
+            if matches!(peer_id, Some(ref p) if peer_ledger.is_banned(p)) {
+                let _ = control.disconnect(session_id).await;
+                return;
+            }
+            while let Some(Ok(msg)) = read_part.next().await {
+                metrics.received(&msg);
+                if let Err(err) = L2TransactionReader::from_slice(msg.as_ref()) {
+                    log::warn!("invalid tx, closing. session: {}: {}", session_id, err);
+                    if let Some(ref peer_id) = peer_id {
+                        peer_ledger.report(peer_id, Misbehavior::InvalidMessage);
+                    }
+                    let _ = control.disconnect(session_id).await;
+                    break;
+                }
+                let tx = L2Transaction::new_unchecked(msg);
+                if sender.send(tx).await.is_err() {
+                    break;
+                }
+            }
+        });
+    });
+    let protocol = MetaBuilder::new()
+        .name(|_| P2P_TX_RELAY_PROTOCOL_NAME.into())
+        .id(P2P_TX_RELAY_PROTOCOL)
+        .protocol_spawn(spawn)
+        .build();
+    (protocol, receiver)
+}
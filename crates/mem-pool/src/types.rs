@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use gw_common::ckb_decimal::CKBCapacity;
 use gw_types::{
     packed::{L2Transaction, WithdrawalRequestExtra},
@@ -10,6 +12,8 @@ pub struct EntryList {
     pub txs: Vec<L2Transaction>,
     // withdrawals sorted by nonce
     pub withdrawals: Vec<WithdrawalRequestExtra>,
+    // when the first entry of the list became pending
+    since: Option<Instant>,
 }
 
 impl EntryList {
@@ -17,6 +21,29 @@ impl EntryList {
         self.txs.is_empty() && self.withdrawals.is_empty()
     }
 
+    pub fn push_tx(&mut self, tx: L2Transaction) {
+        self.since.get_or_insert_with(Instant::now);
+        self.txs.push(tx);
+    }
+
+    pub fn push_withdrawal(&mut self, withdrawal: WithdrawalRequestExtra) {
+        self.since.get_or_insert_with(Instant::now);
+        self.withdrawals.push(withdrawal);
+    }
+
+    /// Time since the first entry of the list became pending.
+    pub fn since(&self) -> Option<Instant> {
+        self.since
+    }
+
+    fn refresh_since(&mut self) {
+        self.since = if self.is_empty() {
+            None
+        } else {
+            Some(Instant::now())
+        };
+    }
+
     // remove and return txs which tx.nonce is lower than nonce
     pub fn remove_lower_nonce_txs(&mut self, nonce: u32) -> Vec<L2Transaction> {
         let mut removed = Vec::default();
@@ -27,6 +54,9 @@ impl EntryList {
             }
             removed.push(self.txs.remove(0));
         }
+        if !removed.is_empty() {
+            self.refresh_since();
+        }
         removed
     }
 
@@ -58,6 +88,9 @@ impl EntryList {
             }
         }
 
+        if !removed.is_empty() {
+            self.refresh_since();
+        }
         removed
     }
 }
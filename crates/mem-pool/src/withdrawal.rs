@@ -8,7 +8,7 @@ use gw_types::{
     prelude::*,
 };
 use gw_utils::{finalized_timepoint, RollupContext};
-use std::collections::HashMap;
+use std::{cmp::Reverse, collections::HashMap};
 
 use crate::custodian::{
     build_finalized_custodian_lock, calc_ckb_custodian_min_capacity, generate_finalized_custodian,
@@ -312,6 +312,12 @@ impl<'a> Generator<'a> {
     }
 }
 
+/// Sort withdrawals by paid fee in descending order, withdrawals with the same
+/// fee keep their original order.
+pub fn sort_by_fee(withdrawals: &mut [WithdrawalRequestExtra]) {
+    withdrawals.sort_by_key(|w| Reverse(Unpack::<u128>::unpack(&w.raw().fee())));
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::HashMap;
@@ -327,7 +333,7 @@ mod test {
     use gw_types::prelude::*;
     use gw_utils::RollupContext;
 
-    use crate::withdrawal::Generator;
+    use crate::withdrawal::{sort_by_fee, Generator};
 
     #[test]
     fn test_withdrawal_generator() {
@@ -441,4 +447,30 @@ mod test {
         let (output, _data) = outputs.get(2).unwrap(); // the second is sudt change
         assert_eq!(output.capacity().unpack(), u64::MAX - 1);
     }
+
+    #[test]
+    fn test_sort_by_fee() {
+        let build_req = |nonce: u32, fee: u128| {
+            let raw = RawWithdrawalRequest::new_builder()
+                .nonce(nonce.pack())
+                .fee(fee.pack())
+                .build();
+            WithdrawalRequestExtra::new_builder()
+                .request(WithdrawalRequest::new_builder().raw(raw).build())
+                .build()
+        };
+        let mut withdrawals = vec![
+            build_req(0, 1),
+            build_req(1, 3),
+            build_req(2, 1),
+            build_req(3, 2),
+        ];
+        sort_by_fee(&mut withdrawals);
+
+        let nonces: Vec<u32> = withdrawals
+            .iter()
+            .map(|w| w.raw().nonce().unpack())
+            .collect();
+        assert_eq!(nonces, vec![1, 3, 0, 2]);
+    }
 }
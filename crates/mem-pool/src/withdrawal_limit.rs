@@ -0,0 +1,251 @@
+//! Rolling window caps on withdrawals admitted into mem pool.
+//!
+//! Withdrawn amounts are capped per sUDT, and in total CKB capacity, over a
+//! rolling window. Caps can be overridden at runtime by admin RPC during
+//! incidents, overrides are not persisted and config caps apply again after
+//! restart.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use gw_config::WithdrawalLimitConfig;
+use gw_types::{h256::*, packed::RawWithdrawalRequest, prelude::*};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum WithdrawalAsset {
+    Ckb,
+    Sudt(H256),
+}
+
+impl std::fmt::Display for WithdrawalAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WithdrawalAsset::Ckb => write!(f, "CKB"),
+            WithdrawalAsset::Sudt(hash) => write!(f, "sUDT 0x{}", hex::encode(hash)),
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+#[error(
+    "withdrawal of {amount} {asset} exceeds limit {limit} per {window_secs}s, {withdrawn} already withdrawn"
+)]
+pub struct WithdrawalLimitError {
+    pub asset: WithdrawalAsset,
+    pub amount: u128,
+    pub withdrawn: u128,
+    pub limit: u128,
+    pub window_secs: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WithdrawalLimitStatus {
+    pub asset: WithdrawalAsset,
+    /// None if not limited.
+    pub limit: Option<u128>,
+    /// Withdrawn in the current window.
+    pub withdrawn: u128,
+}
+
+#[derive(Default)]
+struct Inner {
+    limits: HashMap<WithdrawalAsset, u128>,
+    withdrawn: HashMap<WithdrawalAsset, VecDeque<(Instant, u128)>>,
+}
+
+impl Inner {
+    fn expire(&mut self, window: Duration) {
+        for records in self.withdrawn.values_mut() {
+            while matches!(records.front(), Some((t, _)) if t.elapsed() >= window) {
+                records.pop_front();
+            }
+        }
+        self.withdrawn.retain(|_, records| !records.is_empty());
+    }
+
+    fn withdrawn(&self, asset: &WithdrawalAsset) -> u128 {
+        match self.withdrawn.get(asset) {
+            Some(records) => records
+                .iter()
+                .fold(0u128, |sum, (_, a)| sum.saturating_add(*a)),
+            None => 0,
+        }
+    }
+}
+
+pub struct WithdrawalLimits {
+    window: Duration,
+    inner: Mutex<Inner>,
+}
+
+fn withdrawal_amounts(raw: &RawWithdrawalRequest) -> Vec<(WithdrawalAsset, u128)> {
+    let capacity: u64 = raw.capacity().unpack();
+    let mut amounts = vec![(WithdrawalAsset::Ckb, capacity.into())];
+    let amount: u128 = raw.amount().unpack();
+    if amount > 0 {
+        let sudt_script_hash: H256 = raw.sudt_script_hash().unpack();
+        amounts.push((WithdrawalAsset::Sudt(sudt_script_hash), amount));
+    }
+    amounts
+}
+
+impl WithdrawalLimits {
+    pub fn new(config: &WithdrawalLimitConfig) -> Self {
+        let mut inner = Inner::default();
+        if let Some(capacity) = config.ckb_capacity {
+            inner.limits.insert(WithdrawalAsset::Ckb, capacity.into());
+        }
+        for sudt in config.sudt.iter() {
+            let asset = WithdrawalAsset::Sudt(sudt.sudt_script_hash.clone().into());
+            inner.limits.insert(asset, sudt.amount);
+        }
+        WithdrawalLimits {
+            window: Duration::from_secs(config.window_secs.max(1)),
+            inner: Mutex::new(inner),
+        }
+    }
+
+    pub fn window_secs(&self) -> u64 {
+        self.window.as_secs()
+    }
+
+    /// Check whether the withdrawal fits in the limits of its assets.
+    pub fn check(&self, raw: &RawWithdrawalRequest) -> Result<(), WithdrawalLimitError> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.limits.is_empty() {
+            return Ok(());
+        }
+        inner.expire(self.window);
+
+        for (asset, amount) in withdrawal_amounts(raw) {
+            let limit = match inner.limits.get(&asset) {
+                Some(limit) => *limit,
+                None => continue,
+            };
+            let withdrawn = inner.withdrawn(&asset);
+            if withdrawn.saturating_add(amount) > limit {
+                return Err(WithdrawalLimitError {
+                    asset,
+                    amount,
+                    withdrawn,
+                    limit,
+                    window_secs: self.window_secs(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Record an admitted withdrawal. Withdrawals are counted even if they
+    /// are dropped later.
+    pub(crate) fn record(&self, raw: &RawWithdrawalRequest) {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        for (asset, amount) in withdrawal_amounts(raw) {
+            inner
+                .withdrawn
+                .entry(asset)
+                .or_default()
+                .push_back((now, amount));
+        }
+        inner.expire(self.window);
+    }
+
+    /// Override the limit of the asset, None lifts the limit.
+    pub fn set_limit(&self, asset: WithdrawalAsset, limit: Option<u128>) {
+        let mut inner = self.inner.lock().unwrap();
+        match limit {
+            Some(limit) => inner.limits.insert(asset, limit),
+            None => inner.limits.remove(&asset),
+        };
+    }
+
+    /// Limited assets and assets withdrawn in the current window.
+    pub fn status(&self) -> Vec<WithdrawalLimitStatus> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.expire(self.window);
+        let mut assets: Vec<WithdrawalAsset> = (inner.limits.keys())
+            .chain(inner.withdrawn.keys())
+            .copied()
+            .collect();
+        assets.sort_unstable();
+        assets.dedup();
+        assets
+            .into_iter()
+            .map(|asset| WithdrawalLimitStatus {
+                asset,
+                limit: inner.limits.get(&asset).copied(),
+                withdrawn: inner.withdrawn(&asset),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gw_config::SudtWithdrawalLimit;
+
+    use super::*;
+
+    fn withdrawal(capacity: u64, sudt_script_hash: H256, amount: u128) -> RawWithdrawalRequest {
+        RawWithdrawalRequest::new_builder()
+            .capacity(capacity.pack())
+            .sudt_script_hash(sudt_script_hash.pack())
+            .amount(amount.pack())
+            .build()
+    }
+
+    #[test]
+    fn test_withdrawal_limits() {
+        let sudt = [1u8; 32];
+        let limits = WithdrawalLimits::new(&WithdrawalLimitConfig {
+            ckb_capacity: Some(1000),
+            sudt: vec![SudtWithdrawalLimit {
+                sudt_script_hash: sudt.into(),
+                amount: 100,
+            }],
+            ..Default::default()
+        });
+
+        let w = withdrawal(600, sudt, 60);
+        limits.check(&w).unwrap();
+        limits.record(&w);
+
+        // sUDT exceeded
+        let err = limits.check(&withdrawal(100, sudt, 50)).unwrap_err();
+        assert_eq!(err.asset, WithdrawalAsset::Sudt(sudt));
+        assert_eq!(err.withdrawn, 60);
+        // CKB exceeded
+        let err = limits.check(&withdrawal(500, [0u8; 32], 0)).unwrap_err();
+        assert_eq!(err.asset, WithdrawalAsset::Ckb);
+        // Unlimited sUDT
+        limits.check(&withdrawal(100, [2u8; 32], 1000)).unwrap();
+
+        // Operator override
+        limits.set_limit(WithdrawalAsset::Sudt(sudt), None);
+        limits.check(&withdrawal(100, sudt, 50)).unwrap();
+        limits.set_limit(WithdrawalAsset::Ckb, Some(2000));
+        limits.check(&withdrawal(500, [0u8; 32], 0)).unwrap();
+
+        let status = limits.status();
+        assert_eq!(
+            status,
+            vec![
+                WithdrawalLimitStatus {
+                    asset: WithdrawalAsset::Ckb,
+                    limit: Some(2000),
+                    withdrawn: 600,
+                },
+                WithdrawalLimitStatus {
+                    asset: WithdrawalAsset::Sudt(sudt),
+                    limit: None,
+                    withdrawn: 60,
+                },
+            ]
+        );
+    }
+}
@@ -15,11 +15,23 @@ pub fn block_producer() -> &'static BlockProducerMetrics {
 #[derive(Default)]
 pub struct BlockProducerMetrics {
     pub resend: Counter,
+    pub fee_bumps: Counter,
     pub witness_size: Counter,
     pub tx_size: Counter,
     pub sync_buffer_len: Gauge,
+    pub l1_reorgs: Counter,
+    pub l1_reorg_depth: Gauge,
     pub local_blocks: Gauge,
     pub submitted_blocks: Gauge,
+    pub seconds_since_last_submission: Gauge,
+    pub uncommitted_blocks: Gauge,
+    pub oldest_unfinalized_withdrawal_age: Gauge,
+    pub production_state: Gauge,
+    pub write_stall: Gauge,
+    pub heartbeat_blocks: Counter,
+    pub write_stall_postponed: Counter,
+    pub shadow_blocks: Counter,
+    pub shadow_divergences: Counter,
 }
 
 impl BlockProducerMetrics {
@@ -29,6 +41,29 @@ impl BlockProducerMetrics {
             "Number of messages in the block sync receive buffer",
             Box::new(self.sync_buffer_len.clone()),
         );
+        registry.register(
+            "l1_reorgs",
+            "Number of L2 forks detected when syncing from L1, e.g. caused by L1 reorgs",
+            Box::new(self.l1_reorgs.clone()),
+        );
+        registry.register(
+            "l1_reorg_depth",
+            "Number of L2 blocks reverted by the last detected L2 fork",
+            Box::new(self.l1_reorg_depth.clone()),
+        );
+
+        if config.node_mode == gw_config::NodeMode::ReadOnly {
+            registry.register(
+                "shadow_blocks",
+                "Number of blocks produced in shadow production mode",
+                Box::new(self.shadow_blocks.clone()),
+            );
+            registry.register(
+                "shadow_divergences",
+                "Number of shadow produced blocks that diverged from the full node's",
+                Box::new(self.shadow_divergences.clone()),
+            );
+        }
 
         if config.node_mode == gw_config::NodeMode::FullNode {
             registry.register(
@@ -36,6 +71,11 @@ impl BlockProducerMetrics {
                 "Number of times resending submission transactions",
                 Box::new(self.resend.clone()),
             );
+            registry.register(
+                "fee_bumps",
+                "Number of times replacing submission transactions with a higher fee rate",
+                Box::new(self.fee_bumps.clone()),
+            );
             registry.register_with_unit(
                 "witness_size",
                 "Block submission txs witness size",
@@ -58,6 +98,43 @@ impl BlockProducerMetrics {
                 "Number of submitted blocks",
                 Box::new(self.submitted_blocks.clone()),
             );
+            registry.register_with_unit(
+                "seconds_since_last_submission",
+                "Time since the last block submission, or since start if none",
+                Unit::Seconds,
+                Box::new(self.seconds_since_last_submission.clone()),
+            );
+            registry.register(
+                "uncommitted_blocks",
+                "Number of L2 blocks not yet committed on L1, i.e. local and submitted blocks",
+                Box::new(self.uncommitted_blocks.clone()),
+            );
+            registry.register_with_unit(
+                "oldest_unfinalized_withdrawal_age",
+                "Age of the oldest block with unfinalized withdrawals, 0 if none",
+                Unit::Seconds,
+                Box::new(self.oldest_unfinalized_withdrawal_age.clone()),
+            );
+            registry.register(
+                "production_state",
+                "Block production state, 0 running, 1 pausing, 2 paused",
+                Box::new(self.production_state.clone()),
+            );
+            registry.register(
+                "heartbeat_blocks",
+                "Number of empty blocks produced by the heartbeat timer",
+                Box::new(self.heartbeat_blocks.clone()),
+            );
+            registry.register(
+                "write_stall",
+                "RocksDB write stall seen by block production, 0 none, 1 delayed, 2 stopped",
+                Box::new(self.write_stall.clone()),
+            );
+            registry.register(
+                "write_stall_postponed",
+                "Number of block productions postponed because RocksDB writes are stopped",
+                Box::new(self.write_stall_postponed.clone()),
+            );
         }
     }
 }
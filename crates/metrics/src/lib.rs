@@ -30,12 +30,18 @@ const ENV_METRIC_MONITOR_CUSTODIAN_VEC_JSON: &str = "METRIC_MONITOR_CUSTODIAN_VE
 pub mod block_producer;
 pub mod chain;
 pub mod custodian;
+pub mod mem_pool;
+pub mod p2p;
 pub mod rpc;
+pub mod store;
 
 pub use block_producer::block_producer;
 pub use chain::chain;
 pub use custodian::custodian;
+pub use mem_pool::mem_pool;
+pub use p2p::p2p;
 pub use rpc::rpc;
+pub use store::store;
 
 /// Global metrics registry.
 type TextEncodeRegistry = Registry<Box<dyn encoding::text::SendSyncEncodeMetric>>;
@@ -61,7 +67,12 @@ pub fn init(config: &gw_config::Config) {
     block_producer().register(&config, registry.sub_registry_with_prefix("block_producer"));
     chain().register(&config, registry.sub_registry_with_prefix("chain"));
     custodian().register(&config, registry.sub_registry_with_prefix("custodian"));
+    mem_pool().register(&config, registry.sub_registry_with_prefix("mem_pool"));
+    p2p().register(registry.sub_registry_with_prefix("p2p"));
     rpc().register(&config, registry.sub_registry_with_prefix("rpc"));
+    let store_registry = registry.sub_registry_with_prefix("store");
+    gw_store::latency::store_latency().register(store_registry);
+    store().register(store_registry);
 
     METRIC_REGISTRY.store(Arc::new(Some(registry)));
     CONFIG.store(Arc::new(config));
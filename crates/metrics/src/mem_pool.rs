@@ -0,0 +1,78 @@
+use gw_telemetry::metric::{counter::Counter, gauge::Gauge, registry::Registry, Lazy};
+
+static MEM_POOL_METRICS: Lazy<MemPoolMetrics> = Lazy::new(MemPoolMetrics::default);
+
+pub fn mem_pool() -> &'static MemPoolMetrics {
+    &MEM_POOL_METRICS
+}
+
+#[derive(Default)]
+pub struct MemPoolMetrics {
+    pub priority_lane_txs: Counter,
+    pub priority_lane_rejected_txs: Counter,
+    pub throttled_txs: Counter,
+    pub invalidated_txs: Counter,
+    pub denied_requests: Counter,
+    pub cycles_deferred_txs: Counter,
+    pub paused: Gauge,
+    pub paused_rejected_requests: Counter,
+    pub overlay_keys: Gauge,
+    pub overlay_bytes: Gauge,
+}
+
+impl MemPoolMetrics {
+    pub(crate) fn register(&self, config: &crate::Config, registry: &mut Registry) {
+        if config.node_mode == gw_config::NodeMode::FullNode {
+            registry.register(
+                "priority_lane_txs",
+                "Number of txs pushed into reserved priority lane slots",
+                Box::new(self.priority_lane_txs.clone()),
+            );
+            registry.register(
+                "priority_lane_rejected_txs",
+                "Number of priority lane txs rejected because the mem block is full",
+                Box::new(self.priority_lane_rejected_txs.clone()),
+            );
+            registry.register(
+                "throttled_txs",
+                "Number of txs rejected by per-account throttling",
+                Box::new(self.throttled_txs.clone()),
+            );
+            registry.register(
+                "invalidated_txs",
+                "Number of pending txs dropped by re-validation on new tip",
+                Box::new(self.invalidated_txs.clone()),
+            );
+            registry.register(
+                "denied_requests",
+                "Number of txs and withdrawals rejected by denylist",
+                Box::new(self.denied_requests.clone()),
+            );
+            registry.register(
+                "cycles_deferred_txs",
+                "Number of txs deferred by the soft cycles limit of mem block",
+                Box::new(self.cycles_deferred_txs.clone()),
+            );
+            registry.register(
+                "mem_pool_paused",
+                "1 if the mem pool is paused by the admin RPC",
+                Box::new(self.paused.clone()),
+            );
+            registry.register(
+                "paused_rejected_requests",
+                "Number of txs and withdrawals rejected because the mem pool is paused",
+                Box::new(self.paused_rejected_requests.clone()),
+            );
+            registry.register(
+                "mem_pool_overlay_keys",
+                "Number of state changes of the mem block held in memory",
+                Box::new(self.overlay_keys.clone()),
+            );
+            registry.register(
+                "mem_pool_overlay_bytes",
+                "Bytes of state changes of the mem block held in memory",
+                Box::new(self.overlay_bytes.clone()),
+            );
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use gw_telemetry::metric::{
+    counter::Counter,
+    encoding::text::Encode,
+    family::Family,
+    gauge::Gauge,
+    registry::{Registry, Unit},
+    Lazy,
+};
+
+static P2P_METRICS: Lazy<P2PMetrics> = Lazy::new(P2PMetrics::default);
+
+pub fn p2p() -> &'static P2PMetrics {
+    &P2P_METRICS
+}
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Direction {
+    In,
+    Out,
+}
+
+#[derive(Default)]
+pub struct P2PMetrics {
+    messages: Family<MessageLabel, Counter>,
+    message_bytes: Family<MessageLabel, Counter>,
+    active_substreams: Family<SubstreamLabel, Gauge>,
+    reconnects: Family<AddressLabel, Counter>,
+}
+
+impl P2PMetrics {
+    pub(crate) fn register(&self, registry: &mut Registry) {
+        registry.register(
+            "messages",
+            "Number of p2p messages by protocol, peer and direction",
+            Box::new(self.messages.clone()),
+        );
+        registry.register_with_unit(
+            "message",
+            "Size of p2p messages on the wire by protocol, peer and direction",
+            Unit::Bytes,
+            Box::new(self.message_bytes.clone()),
+        );
+        registry.register(
+            "active_substreams",
+            "Number of open protocol substreams by protocol and peer",
+            Box::new(self.active_substreams.clone()),
+        );
+        registry.register(
+            "reconnects",
+            "Number of times redialing a dial address",
+            Box::new(self.reconnects.clone()),
+        );
+    }
+
+    /// `peer` is the peer id, or the address if the session has no peer id.
+    pub fn messages(&self, protocol: &str, peer: &str, direction: Direction) -> Counter {
+        self.messages
+            .get_or_create(&MessageLabel::new(protocol, peer, direction))
+            .clone()
+    }
+
+    pub fn message_bytes(&self, protocol: &str, peer: &str, direction: Direction) -> Counter {
+        self.message_bytes
+            .get_or_create(&MessageLabel::new(protocol, peer, direction))
+            .clone()
+    }
+
+    pub fn active_substreams(&self, protocol: &str, peer: &str) -> Gauge {
+        let label = SubstreamLabel {
+            protocol: protocol.into(),
+            peer: peer.into(),
+        };
+        self.active_substreams.get_or_create(&label).clone()
+    }
+
+    pub fn reconnects(&self, address: &str) -> Counter {
+        let label = AddressLabel {
+            address: address.into(),
+        };
+        self.reconnects.get_or_create(&label).clone()
+    }
+}
+
+#[derive(Clone, Hash, PartialEq, Eq)]
+struct MessageLabel {
+    protocol: String,
+    peer: String,
+    direction: Direction,
+}
+
+impl MessageLabel {
+    fn new(protocol: &str, peer: &str, direction: Direction) -> Self {
+        Self {
+            protocol: protocol.into(),
+            peer: peer.into(),
+            direction,
+        }
+    }
+}
+
+impl Encode for MessageLabel {
+    fn encode(&self, writer: &mut dyn std::io::Write) -> Result<(), std::io::Error> {
+        let direction = match self.direction {
+            Direction::In => "in",
+            Direction::Out => "out",
+        };
+        write!(
+            writer,
+            "protocol=\"{}\",peer=\"{}\",direction=\"{}\"",
+            self.protocol, self.peer, direction
+        )
+    }
+}
+
+#[derive(Clone, Hash, PartialEq, Eq)]
+struct SubstreamLabel {
+    protocol: String,
+    peer: String,
+}
+
+impl Encode for SubstreamLabel {
+    fn encode(&self, writer: &mut dyn std::io::Write) -> Result<(), std::io::Error> {
+        write!(
+            writer,
+            "protocol=\"{}\",peer=\"{}\"",
+            self.protocol, self.peer
+        )
+    }
+}
+
+#[derive(Clone, Hash, PartialEq, Eq)]
+struct AddressLabel {
+    address: String,
+}
+
+impl Encode for AddressLabel {
+    fn encode(&self, writer: &mut dyn std::io::Write) -> Result<(), std::io::Error> {
+        write!(writer, "address=\"{}\"", self.address)
+    }
+}
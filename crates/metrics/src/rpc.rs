@@ -15,10 +15,17 @@ pub enum RequestKind {
     Withdrawal,
 }
 
+#[derive(Clone, Hash, PartialEq, Eq, Encode)]
+pub enum CacheResult {
+    Hit,
+    Miss,
+}
+
 #[derive(Default)]
 pub struct RPCMetrics {
     execute_transactions: Family<ExecutionLabel, Counter>,
     in_queue_requests: Family<RequestLabel, Gauge>,
+    execute_cache: Family<CacheLabel, Counter>,
 }
 
 impl RPCMetrics {
@@ -28,6 +35,11 @@ impl RPCMetrics {
             "Number of execute_transaction requests",
             Box::new(self.execute_transactions.clone()),
         );
+        registry.register(
+            "execute_cache",
+            "Number of execute_raw_l2transaction cache lookups",
+            Box::new(self.execute_cache.clone()),
+        );
 
         if config.node_mode == gw_config::NodeMode::FullNode {
             registry.register(
@@ -49,6 +61,12 @@ impl RPCMetrics {
             .get_or_create(&RequestLabel { kind })
             .clone()
     }
+
+    pub fn execute_cache(&self, result: CacheResult) -> Counter {
+        self.execute_cache
+            .get_or_create(&CacheLabel { result })
+            .clone()
+    }
 }
 
 // Label for the execute_transactions metric.
@@ -68,3 +86,8 @@ impl Encode for ExecutionLabel {
 struct RequestLabel {
     kind: RequestKind,
 }
+
+#[derive(Clone, Hash, PartialEq, Eq, Encode)]
+struct CacheLabel {
+    result: CacheResult,
+}
@@ -0,0 +1,136 @@
+use gw_store::Store;
+use gw_telemetry::metric::{
+    encoding::text::Encode, family::Family, gauge::Gauge, registry::Registry, Lazy,
+};
+
+static STORE_METRICS: Lazy<StoreMetrics> = Lazy::new(StoreMetrics::default);
+
+pub fn store() -> &'static StoreMetrics {
+    &STORE_METRICS
+}
+
+#[derive(Default)]
+pub struct StoreMetrics {
+    live_sst_files_size: Family<ColumnLabel, Gauge>,
+    mem_tables_size: Family<ColumnLabel, Gauge>,
+    estimate_num_keys: Family<ColumnLabel, Gauge>,
+    estimate_pending_compaction_bytes: Family<ColumnLabel, Gauge>,
+    block_cache_hit: Gauge,
+    block_cache_miss: Gauge,
+    write_stall_micros: Gauge,
+    state_cache_hits: Family<CacheLabel, Gauge>,
+    state_cache_misses: Family<CacheLabel, Gauge>,
+    state_cache_entries: Family<CacheLabel, Gauge>,
+}
+
+impl StoreMetrics {
+    pub(crate) fn register(&self, registry: &mut Registry) {
+        registry.register(
+            "live_sst_files_size_bytes",
+            "Size of live SST files by column",
+            Box::new(self.live_sst_files_size.clone()),
+        );
+        registry.register(
+            "mem_tables_size_bytes",
+            "Size of active and unflushed immutable memtables by column",
+            Box::new(self.mem_tables_size.clone()),
+        );
+        registry.register(
+            "estimate_num_keys",
+            "Estimated number of keys by column",
+            Box::new(self.estimate_num_keys.clone()),
+        );
+        registry.register(
+            "estimate_pending_compaction_bytes",
+            "Estimated bytes to be rewritten by compaction by column",
+            Box::new(self.estimate_pending_compaction_bytes.clone()),
+        );
+        registry.register(
+            "block_cache_hit",
+            "Number of block cache hits, requires store.statistics",
+            Box::new(self.block_cache_hit.clone()),
+        );
+        registry.register(
+            "block_cache_miss",
+            "Number of block cache misses, requires store.statistics",
+            Box::new(self.block_cache_miss.clone()),
+        );
+        registry.register(
+            "write_stall_micros",
+            "Time writes are stalled in microseconds, requires store.statistics",
+            Box::new(self.write_stall_micros.clone()),
+        );
+        registry.register(
+            "state_cache_hits",
+            "Number of state cache hits by cache",
+            Box::new(self.state_cache_hits.clone()),
+        );
+        registry.register(
+            "state_cache_misses",
+            "Number of state cache misses by cache",
+            Box::new(self.state_cache_misses.clone()),
+        );
+        registry.register(
+            "state_cache_entries",
+            "Number of entries in state caches by cache",
+            Box::new(self.state_cache_entries.clone()),
+        );
+    }
+
+    /// Read column family properties and statistics from the store.
+    pub fn update(&self, store: &Store) {
+        for stat in store.gather_cf_stats() {
+            let label = ColumnLabel { col: stat.col };
+            self.live_sst_files_size
+                .get_or_create(&label)
+                .set(stat.live_sst_files_size);
+            self.mem_tables_size
+                .get_or_create(&label)
+                .set(stat.mem_tables_size);
+            self.estimate_num_keys
+                .get_or_create(&label)
+                .set(stat.estimate_num_keys);
+            self.estimate_pending_compaction_bytes
+                .get_or_create(&label)
+                .set(stat.estimate_pending_compaction_bytes);
+        }
+        if let Some(stat) = store.gather_db_stats() {
+            self.block_cache_hit.set(stat.block_cache_hit);
+            self.block_cache_miss.set(stat.block_cache_miss);
+            self.write_stall_micros.set(stat.stall_micros);
+        }
+        for stat in store.cache_stats() {
+            let label = CacheLabel { cache: stat.name };
+            self.state_cache_hits.get_or_create(&label).set(stat.hits);
+            self.state_cache_misses
+                .get_or_create(&label)
+                .set(stat.misses);
+            self.state_cache_entries
+                .get_or_create(&label)
+                .set(stat.len as u64);
+        }
+    }
+}
+
+#[derive(Clone, Hash, PartialEq, Eq)]
+struct ColumnLabel {
+    col: usize,
+}
+
+// Manual impl because usize does not implement Encode.
+impl Encode for ColumnLabel {
+    fn encode(&self, writer: &mut dyn std::io::Write) -> Result<(), std::io::Error> {
+        write!(writer, "col=\"{}\"", self.col)
+    }
+}
+
+#[derive(Clone, Hash, PartialEq, Eq)]
+struct CacheLabel {
+    cache: &'static str,
+}
+
+impl Encode for CacheLabel {
+    fn encode(&self, writer: &mut dyn std::io::Write) -> Result<(), std::io::Error> {
+        write!(writer, "cache=\"{}\"", self.cache)
+    }
+}
@@ -42,6 +42,10 @@ async fn main() -> anyhow::Result<()> {
             dial: Vec::new(),
             secret_key_path: Some("examples/server-key".into()),
             allowed_peer_ids: Some(vec!["Qme22rAhVjej4UCYxzW52L8PtYVv3XHeY2JqRKuwJn5ZFQ".into()]),
+            peer_score: Default::default(),
+            session_limits: Default::default(),
+            keepalive: None,
+            tls: None,
         }
     } else {
         P2PNetworkConfig {
@@ -51,6 +55,10 @@ async fn main() -> anyhow::Result<()> {
             ],
             secret_key_path: Some("examples/client-key".into()),
             allowed_peer_ids: None,
+            peer_score: Default::default(),
+            session_limits: Default::default(),
+            keepalive: None,
+            tls: None,
         }
     };
     let mut network = P2PNetwork::init(&config, [protocol()]).await?;
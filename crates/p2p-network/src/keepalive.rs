@@ -0,0 +1,56 @@
+//! Keepalive protocol: both sides ping every `interval_secs`, and close the
+//! session if no ping is received in `timeout_secs`.
+
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use futures_util::StreamExt;
+use gw_config::KeepaliveConfig;
+use tentacle::{builder::MetaBuilder, service::ProtocolMeta};
+
+use crate::{FnSpawn, P2P_KEEPALIVE_PROTOCOL, P2P_KEEPALIVE_PROTOCOL_NAME};
+
+pub(crate) fn keepalive_protocol(config: &KeepaliveConfig) -> ProtocolMeta {
+    let interval = Duration::from_secs(config.interval_secs.max(1));
+    let timeout = Duration::from_secs(config.timeout_secs).max(interval);
+    let spawn = FnSpawn(move |context, control, mut read_part| {
+        let control = control.clone();
+        let session_id = context.id;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            let mut last_received = Instant::now();
+            loop {
+                tokio::select! {
+                    msg = read_part.next() => match msg {
+                        Some(Ok(_)) => last_received = Instant::now(),
+                        // Session closed.
+                        _ => break,
+                    },
+                    _ = ticker.tick() => {
+                        if last_received.elapsed() > timeout {
+                            log::warn!(
+                                "no keepalive in {:?}, closing. session: {}",
+                                timeout,
+                                session_id
+                            );
+                            let _ = control.disconnect(session_id).await;
+                            break;
+                        }
+                        let ping = Bytes::from_static(&[0]);
+                        let result = control
+                            .send_message_to(session_id, P2P_KEEPALIVE_PROTOCOL, ping)
+                            .await;
+                        if result.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    });
+    MetaBuilder::new()
+        .name(|_| P2P_KEEPALIVE_PROTOCOL_NAME.into())
+        .id(P2P_KEEPALIVE_PROTOCOL)
+        .protocol_spawn(spawn)
+        .build()
+}
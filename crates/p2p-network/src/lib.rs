@@ -1,13 +1,17 @@
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet},
-    sync::Arc,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 
-use anyhow::{Context, Result};
-use gw_config::P2PNetworkConfig;
+use anyhow::{bail, Context, Result};
+use gw_config::{P2PNetworkConfig, SessionLimitsConfig};
 use gw_utils::ExponentialBackoff;
-use socket2::SockRef;
+use socket2::{SockRef, TcpKeepalive};
 use tentacle::{
     async_trait,
     builder::ServiceBuilder,
@@ -16,21 +20,67 @@ use tentacle::{
     secio::{PeerId, SecioKeyPair},
     service::{
         ProtocolMeta, Service, ServiceAsyncControl, ServiceError, ServiceEvent, TargetProtocol,
+        TcpSocket,
     },
     traits::{ProtocolSpawn, ServiceHandle},
     utils::extract_peer_id,
-    ProtocolId, SubstreamReadPart,
+    ProtocolId, SessionId, SubstreamReadPart,
 };
 
+mod keepalive;
+mod metrics;
+mod peer_score;
+pub mod test_peer;
+mod tls;
+
+pub use metrics::SubstreamMetrics;
+pub use peer_score::{Misbehavior, PeerLedger};
+
 const RECONNECT_BASE_DURATION: Duration = Duration::from_secs(2);
 
 /// Wrapper for tentacle Service. Automatically reconnect dial addresses.
 pub struct P2PNetwork {
     service: Service<SHandle>,
+    status: Arc<P2PNetworkStatus>,
+    peer_ledger: Arc<PeerLedger>,
+}
+
+/// Live status of the p2p network, shared with other components (e.g. RPC).
+#[derive(Default)]
+pub struct P2PNetworkStatus {
+    peer_count: AtomicUsize,
+    listening: AtomicBool,
+}
+
+impl P2PNetworkStatus {
+    /// Number of currently connected (and allowed) peers.
+    pub fn peer_count(&self) -> usize {
+        self.peer_count.load(Ordering::Relaxed)
+    }
+
+    /// Whether the network is listening for incoming connections.
+    pub fn is_listening(&self) -> bool {
+        self.listening.load(Ordering::Relaxed)
+    }
 }
 
 impl P2PNetwork {
     pub async fn init<PS>(config: &P2PNetworkConfig, protocols: PS) -> Result<Self>
+    where
+        PS: IntoIterator,
+        PS::Item: Into<ProtocolMeta>,
+    {
+        let peer_ledger = Arc::new(PeerLedger::new(&config.peer_score)?);
+        Self::init_with_peer_ledger(config, protocols, peer_ledger).await
+    }
+
+    /// Init with a peer ledger shared with protocols, so that they can report
+    /// misbehaving peers.
+    pub async fn init_with_peer_ledger<PS>(
+        config: &P2PNetworkConfig,
+        protocols: PS,
+        peer_ledger: Arc<PeerLedger>,
+    ) -> Result<Self>
     where
         PS: IntoIterator,
         PS::Item: Into<ProtocolMeta>,
@@ -53,17 +103,57 @@ impl P2PNetwork {
         } else {
             SecioKeyPair::secp256k1_generated()
         };
+        let listen: Option<MultiAddr> = match config.listen {
+            Some(ref listen) => Some(listen.parse().context("parse listen address")?),
+            None => None,
+        };
+        if listen.is_none() {
+            if dial_vec.is_empty() {
+                bail!("p2p network has neither listen nor dial addresses");
+            }
+            log::info!("p2p network is outbound only");
+        }
+        // Probe idle connections sooner than the TCP default of 2 hours.
+        let tcp_keepalive = config.keepalive.as_ref().map(|c| {
+            let interval = Duration::from_secs(c.interval_secs.max(1));
+            TcpKeepalive::new()
+                .with_time(interval)
+                .with_interval(interval)
+        });
+        let tcp_config = move |socket: TcpSocket| {
+            let sock_ref = SockRef::from(&socket);
+            sock_ref.set_nodelay(true)?;
+            if let Some(ref tcp_keepalive) = tcp_keepalive {
+                sock_ref.set_tcp_keepalive(tcp_keepalive)?;
+            }
+            Ok(socket)
+        };
+        let limits = config.session_limits.clone();
         let mut builder = ServiceBuilder::new()
             .forever(true)
-            .tcp_config(|socket| {
-                let sock_ref = SockRef::from(&socket);
-                sock_ref.set_nodelay(true)?;
-                Ok(socket)
-            })
+            // Drop excess connections before the handshake. The handle checks
+            // the inbound limits precisely.
+            .max_connection_number(limits.max_inbound_sessions + dial_vec.len())
+            .timeout(Duration::from_secs(limits.handshake_timeout_secs.max(1)))
+            .tcp_config(tcp_config.clone())
             .key_pair(key_pair);
+        match config.tls {
+            Some(ref tls) => {
+                let tls_config = tls::tls_config(tls).context("TLS config")?;
+                builder = builder.tls_config(tls_config).tcp_config_on_tls(tcp_config);
+            }
+            None => {
+                if listen.iter().chain(&dial_vec).any(tls::is_tls) {
+                    bail!("p2p_network_config.tls is required for TLS addresses");
+                }
+            }
+        }
         for p in protocols {
             builder = builder.insert_protocol(p.into());
         }
+        if let Some(ref keepalive) = config.keepalive {
+            builder = builder.insert_protocol(keepalive::keepalive_protocol(keepalive));
+        }
         let allowed_peer_ids = if let Some(ref allowed) = config.allowed_peer_ids {
             let mut allowed_peer_ids = HashSet::new();
             for a in allowed {
@@ -81,9 +171,15 @@ impl P2PNetwork {
         } else {
             None
         };
+        let status = Arc::new(P2PNetworkStatus::default());
         let mut service = builder.build(SHandle {
             dial_backoff,
             allowed_peer_ids,
+            sessions: HashMap::new(),
+            inbound_sessions: HashMap::new(),
+            limits,
+            status: status.clone(),
+            peer_ledger: peer_ledger.clone(),
         });
         let control = service.control().clone();
         // Send dial in another task to avoid deadlock.
@@ -99,19 +195,29 @@ impl P2PNetwork {
             });
         }
         // Listen must succeed.
-        if let Some(listen) = config.listen.as_deref() {
-            service
-                .listen(listen.parse().context("parse listen address")?)
-                .await
-                .context("listen")?;
+        if let Some(listen) = listen {
+            service.listen(listen).await.context("listen")?;
+            status.listening.store(true, Ordering::Relaxed);
         }
-        Ok(Self { service })
+        Ok(Self {
+            service,
+            status,
+            peer_ledger,
+        })
     }
 
     pub fn control(&self) -> &ServiceAsyncControl {
         self.service.control()
     }
 
+    pub fn status(&self) -> Arc<P2PNetworkStatus> {
+        self.status.clone()
+    }
+
+    pub fn peer_ledger(&self) -> Arc<PeerLedger> {
+        self.peer_ledger.clone()
+    }
+
     pub async fn run(&mut self) {
         self.service.run().await;
     }
@@ -121,6 +227,13 @@ impl P2PNetwork {
 struct SHandle {
     allowed_peer_ids: Option<HashSet<PeerId>>,
     dial_backoff: HashMap<MultiAddr, ExponentialBackoff>,
+    // Allowed open sessions and their peer ids.
+    sessions: HashMap<SessionId, Option<PeerId>>,
+    // Allowed inbound sessions and their remote IPs.
+    inbound_sessions: HashMap<SessionId, Option<IpAddr>>,
+    limits: SessionLimitsConfig,
+    status: Arc<P2PNetworkStatus>,
+    peer_ledger: Arc<PeerLedger>,
 }
 
 impl SHandle {
@@ -135,6 +248,7 @@ impl SHandle {
         };
         if let Entry::Occupied(mut o) = entry {
             let dial = o.key().clone();
+            gw_metrics::p2p().reconnects(&dial.to_string()).inc();
             let backoff = o.get_mut();
             let sleep = backoff.next_sleep();
             // Reconnect in a newly spawned task so that we don't block the whole tentacle service.
@@ -147,6 +261,35 @@ impl SHandle {
         }
     }
 
+    /// Report misbehavior of the peer of session `id`, and disconnect it if
+    /// it's banned.
+    async fn report(&self, context: &ServiceContext, id: SessionId, misbehavior: Misbehavior) {
+        if let Some(Some(peer_id)) = self.sessions.get(&id) {
+            if self.peer_ledger.report(peer_id, misbehavior) {
+                let _ = context.control().disconnect(id).await;
+            }
+        }
+    }
+
+    /// Returns the reason if the inbound session exceeds limits.
+    fn check_inbound_limits(&self, ip: Option<IpAddr>) -> Option<&'static str> {
+        if self.inbound_sessions.len() >= self.limits.max_inbound_sessions {
+            return Some("too many inbound sessions");
+        }
+        let ip = ip?;
+        let same_ip = self.inbound_sessions.values().filter(|i| **i == Some(ip));
+        if same_ip.count() >= self.limits.max_inbound_sessions_per_ip {
+            return Some("too many inbound sessions from the IP");
+        }
+        None
+    }
+
+    fn update_peer_count(&self) {
+        self.status
+            .peer_count
+            .store(self.sessions.len(), Ordering::Relaxed);
+    }
+
     fn reset(&mut self, address: MultiAddr) {
         let address_without_peer_id: MultiAddr = address
             .iter()
@@ -169,8 +312,28 @@ impl ServiceHandle for SHandle {
     // some just tell users that they need to pay attention
     async fn handle_error(&mut self, context: &mut ServiceContext, error: ServiceError) {
         log::info!("service error: {:?}", error);
-        if let ServiceError::DialerError { address, error: _ } = error {
-            self.re_dial(context, address);
+        match error {
+            ServiceError::DialerError { address, error: _ } => self.re_dial(context, address),
+            ServiceError::ProtocolError { id, .. } => {
+                self.report(context, id, Misbehavior::MalformedFrame).await;
+            }
+            ServiceError::MuxerError {
+                session_context, ..
+            } => {
+                self.report(context, session_context.id, Misbehavior::MalformedFrame)
+                    .await;
+            }
+            // Peers may not enable optional protocols. No protocol name means
+            // timeout or network errors.
+            ServiceError::ProtocolSelectError {
+                proto_name: Some(name),
+                session_context,
+            } if !OPTIONAL_PROTOCOL_NAMES.contains(&name.as_str()) => {
+                let id = session_context.id;
+                self.report(context, id, Misbehavior::ProtocolViolation)
+                    .await;
+            }
+            _ => {}
         }
     }
 
@@ -178,31 +341,67 @@ impl ServiceHandle for SHandle {
         log::info!("service event: {:?}", event);
         match event {
             ServiceEvent::SessionClose { session_context } => {
+                if self.sessions.remove(&session_context.id).is_some() {
+                    self.update_peer_count();
+                }
+                self.inbound_sessions.remove(&session_context.id);
                 self.re_dial(context, session_context.address.clone());
             }
             ServiceEvent::SessionOpen { session_context } => {
-                // Check allow list.
+                // Check allow list and ban list.
+                let peer_id = extract_peer_id(&session_context.address);
                 let mut allow = true;
                 if let Some(ref allowed) = self.allowed_peer_ids {
-                    if let Some(peer_id) = extract_peer_id(&session_context.address) {
-                        if !allowed.contains(&peer_id) {
+                    if let Some(ref peer_id) = peer_id {
+                        if !allowed.contains(peer_id) {
                             allow = false;
                         }
                     } else {
                         allow = false;
                     }
                 };
+                if matches!(peer_id, Some(ref p) if self.peer_ledger.is_banned(p)) {
+                    log::info!("reject banned peer {}", session_context.address);
+                    allow = false;
+                }
+                let inbound = session_context.ty.is_inbound();
+                let ip = extract_ip(&session_context.address);
+                if allow && inbound {
+                    if let Some(reason) = self.check_inbound_limits(ip) {
+                        log::info!("reject {}: {}", session_context.address, reason);
+                        allow = false;
+                    }
+                }
                 if !allow {
                     let _ = context.control().disconnect(session_context.id).await;
                 } else {
+                    if inbound {
+                        self.inbound_sessions.insert(session_context.id, ip);
+                    }
+                    self.sessions.insert(session_context.id, peer_id);
+                    self.update_peer_count();
                     self.reset(session_context.address.clone());
                 }
             }
+            ServiceEvent::ListenStarted { .. } => {
+                self.status.listening.store(true, Ordering::Relaxed);
+            }
+            ServiceEvent::ListenClose { .. } => {
+                self.status.listening.store(false, Ordering::Relaxed);
+            }
             _ => (),
         }
     }
 }
 
+fn extract_ip(address: &MultiAddr) -> Option<IpAddr> {
+    address.iter().find_map(|p| match p {
+        Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+        Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+        _ => None,
+    })
+}
+
 /// ProtocolSpawn helper.
 pub struct FnSpawn<F: Fn(Arc<SessionContext>, &ServiceAsyncControl, SubstreamReadPart)>(pub F);
 
@@ -225,3 +424,16 @@ impl<F: Fn(Arc<SessionContext>, &ServiceAsyncControl, SubstreamReadPart)> Protoc
 // blocks and mem block transactions.
 pub const P2P_SYNC_PROTOCOL: ProtocolId = ProtocolId::new(3);
 pub const P2P_SYNC_PROTOCOL_NAME: &str = "/p2p/sync";
+
+// Keepalive pings, see `P2PNetworkConfig::keepalive`.
+pub const P2P_KEEPALIVE_PROTOCOL: ProtocolId = ProtocolId::new(4);
+pub const P2P_KEEPALIVE_PROTOCOL_NAME: &str = "/p2p/keepalive";
+
+// Transactions relayed from readonly nodes to the full node.
+pub const P2P_TX_RELAY_PROTOCOL: ProtocolId = ProtocolId::new(5);
+pub const P2P_TX_RELAY_PROTOCOL_NAME: &str = "/p2p/tx-relay";
+
+// Protocols that peers may not enable, failing to open them is not
+// misbehavior.
+const OPTIONAL_PROTOCOL_NAMES: [&str; 2] =
+    [P2P_KEEPALIVE_PROTOCOL_NAME, P2P_TX_RELAY_PROTOCOL_NAME];
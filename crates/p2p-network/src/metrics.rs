@@ -0,0 +1,52 @@
+//! Message metrics of protocol substreams, tagged by protocol and peer.
+
+use gw_metrics::p2p::Direction;
+use gw_telemetry::metric::{counter::Counter, gauge::Gauge};
+use tentacle::{context::SessionContext, utils::extract_peer_id};
+
+/// Protocols create one for each substream, and report messages they receive
+/// and send. The substream is counted as active until this is dropped.
+pub struct SubstreamMetrics {
+    messages_in: Counter,
+    bytes_in: Counter,
+    messages_out: Counter,
+    bytes_out: Counter,
+    active: Gauge,
+}
+
+impl SubstreamMetrics {
+    pub fn new(protocol: &str, context: &SessionContext) -> Self {
+        let peer = match extract_peer_id(&context.address) {
+            Some(peer_id) => peer_id.to_base58(),
+            None => context.address.to_string(),
+        };
+        let metrics = gw_metrics::p2p();
+        let active = metrics.active_substreams(protocol, &peer);
+        active.inc();
+        Self {
+            messages_in: metrics.messages(protocol, &peer, Direction::In),
+            bytes_in: metrics.message_bytes(protocol, &peer, Direction::In),
+            messages_out: metrics.messages(protocol, &peer, Direction::Out),
+            bytes_out: metrics.message_bytes(protocol, &peer, Direction::Out),
+            active,
+        }
+    }
+
+    /// `msg` as received on the wire, e.g. before decompression.
+    pub fn received(&self, msg: &[u8]) {
+        self.messages_in.inc();
+        self.bytes_in.inc_by(msg.len() as u64);
+    }
+
+    /// `msg` as sent on the wire, e.g. after compression.
+    pub fn sent(&self, msg: &[u8]) {
+        self.messages_out.inc();
+        self.bytes_out.inc_by(msg.len() as u64);
+    }
+}
+
+impl Drop for SubstreamMetrics {
+    fn drop(&mut self) {
+        self.active.dec();
+    }
+}
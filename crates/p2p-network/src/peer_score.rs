@@ -0,0 +1,164 @@
+//! Misbehavior scores of peers and timed bans.
+//!
+//! Each misbehavior adds a penalty to the peer's score, which halves every
+//! `score_half_life_secs`, so that occasional errors of honest peers are
+//! forgotten. A peer whose score reaches `ban_threshold` is banned for
+//! `ban_secs`, its sessions are closed on open. Bans are saved to
+//! `ban_list_path` if set, one `<peer id> <banned until unix secs>` per line.
+
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context, Result};
+use gw_config::PeerScoreConfig;
+use tentacle::secio::PeerId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Misbehavior {
+    /// Undecodable frames, e.g. garbage or oversized frames.
+    MalformedFrame,
+    /// E.g. failed protocol negotiation.
+    ProtocolViolation,
+    /// A well-framed but invalid protocol message.
+    InvalidMessage,
+    /// Messages sent when none are expected.
+    Flooding,
+}
+
+impl Misbehavior {
+    fn penalty(self) -> f64 {
+        match self {
+            Misbehavior::MalformedFrame | Misbehavior::ProtocolViolation => 20.0,
+            Misbehavior::InvalidMessage | Misbehavior::Flooding => 50.0,
+        }
+    }
+}
+
+struct Score {
+    value: f64,
+    updated: Instant,
+}
+
+impl Score {
+    fn decayed(&self, now: Instant, half_life: Duration) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated);
+        self.value * 0.5f64.powf(elapsed.as_secs_f64() / half_life.as_secs_f64())
+    }
+}
+
+/// Scores and bans of peers, shared by the network and its protocols.
+pub struct PeerLedger {
+    config: PeerScoreConfig,
+    scores: Mutex<HashMap<PeerId, Score>>,
+    // Peer id -> banned until.
+    banned: Mutex<HashMap<PeerId, SystemTime>>,
+}
+
+impl PeerLedger {
+    /// Load bans from `config.ban_list_path` if it exists.
+    pub fn new(config: &PeerScoreConfig) -> Result<Self> {
+        let banned = match config.ban_list_path {
+            Some(ref path) if path.exists() => load_ban_list(path)
+                .with_context(|| format!("load ban list from {}", path.to_string_lossy()))?,
+            _ => HashMap::new(),
+        };
+        Ok(Self {
+            config: config.clone(),
+            scores: Default::default(),
+            banned: Mutex::new(banned),
+        })
+    }
+
+    /// Add the penalty of `misbehavior` to the peer's score, and ban the peer
+    /// if the score reaches the threshold. Returns whether the peer is banned.
+    pub fn report(&self, peer_id: &PeerId, misbehavior: Misbehavior) -> bool {
+        let now = Instant::now();
+        let half_life = Duration::from_secs(self.config.score_half_life_secs.max(1));
+        let score = {
+            let mut scores = self.scores.lock().unwrap();
+            // Forget peers whose scores have decayed away.
+            scores.retain(|_, s| s.decayed(now, half_life) >= 1.0);
+            let score = scores.entry(peer_id.clone()).or_insert(Score {
+                value: 0.0,
+                updated: now,
+            });
+            score.value = score.decayed(now, half_life) + misbehavior.penalty();
+            score.updated = now;
+            score.value
+        };
+        log::info!(
+            "peer {} misbehaved: {:?}, score {:.0}",
+            peer_id.to_base58(),
+            misbehavior,
+            score
+        );
+        if score < f64::from(self.config.ban_threshold) {
+            return false;
+        }
+        self.scores.lock().unwrap().remove(peer_id);
+        self.ban(peer_id.clone());
+        true
+    }
+
+    /// Ban the peer for `ban_secs` regardless of its score, e.g. for provably
+    /// malicious messages.
+    pub fn ban(&self, peer_id: PeerId) {
+        let duration = Duration::from_secs(self.config.ban_secs);
+        log::warn!("ban peer {} for {:?}", peer_id.to_base58(), duration);
+        let until = SystemTime::now() + duration;
+        let mut banned = self.banned.lock().unwrap();
+        let entry = banned.entry(peer_id).or_insert(until);
+        *entry = (*entry).max(until);
+        let now = SystemTime::now();
+        banned.retain(|_, until| *until > now);
+        if let Some(ref path) = self.config.ban_list_path {
+            if let Err(err) = save_ban_list(path, &banned) {
+                log::warn!("save ban list to {}: {:#}", path.to_string_lossy(), err);
+            }
+        }
+    }
+
+    pub fn is_banned(&self, peer_id: &PeerId) -> bool {
+        let banned = self.banned.lock().unwrap();
+        matches!(banned.get(peer_id), Some(until) if *until > SystemTime::now())
+    }
+}
+
+fn load_ban_list(path: &Path) -> Result<HashMap<PeerId, SystemTime>> {
+    let content = fs::read_to_string(path)?;
+    let now = SystemTime::now();
+    let mut banned = HashMap::new();
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        let (peer_id, until) = line
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| anyhow!("invalid line {}", line))?;
+        let peer_id: PeerId = peer_id
+            .parse()
+            .map_err(|_| anyhow!("invalid peer id {}", peer_id))?;
+        let until = UNIX_EPOCH + Duration::from_secs(until.parse().context("parse ban time")?);
+        if until > now {
+            banned.insert(peer_id, until);
+        }
+    }
+    Ok(banned)
+}
+
+fn save_ban_list(path: &Path, banned: &HashMap<PeerId, SystemTime>) -> Result<()> {
+    let mut content = String::new();
+    for (peer_id, until) in banned {
+        let until = until.duration_since(UNIX_EPOCH)?.as_secs();
+        writeln!(content, "{} {}", peer_id.to_base58(), until)?;
+    }
+    // Write to a temporary file first so that the list is never truncated.
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
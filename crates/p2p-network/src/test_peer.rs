@@ -0,0 +1,159 @@
+//! A scriptable peer for p2p protocol tests.
+//!
+//! The peer dials a node, opens one protocol and runs a script of actions on
+//! the session. Unlike a real peer it sends whatever it is told to, e.g.
+//! malformed, truncated, replayed or oversized messages, so tests can assert
+//! that the node neither panics nor stalls on hostile input.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use bytes::Bytes;
+use futures_util::StreamExt;
+use tentacle::{
+    builder::{MetaBuilder, ServiceBuilder},
+    multiaddr::MultiAddr,
+    secio::{PeerId, SecioKeyPair},
+    service::{ServiceAsyncControl, TargetProtocol},
+    ProtocolId, SessionId, SubstreamReadPart,
+};
+use tokio::sync::mpsc;
+use tokio_util::codec::length_delimited::LengthDelimitedCodec;
+
+use crate::FnSpawn;
+
+#[derive(Debug, Clone)]
+pub enum PeerAction {
+    /// Send a message as is.
+    Send(Bytes),
+    /// Wait for the next message from the node.
+    Receive,
+    Sleep(Duration),
+    /// Wait for the node to close the session.
+    ExpectDisconnect,
+}
+
+pub struct ScriptedPeer {
+    protocol_id: ProtocolId,
+    protocol_name: String,
+    timeout: Duration,
+    max_frame_length: usize,
+    key_pair: SecioKeyPair,
+}
+
+impl ScriptedPeer {
+    pub fn new(protocol_id: ProtocolId, protocol_name: &str) -> Self {
+        Self {
+            protocol_id,
+            protocol_name: protocol_name.into(),
+            timeout: Duration::from_secs(10),
+            max_frame_length: 8 * 1024 * 1024,
+            key_pair: SecioKeyPair::secp256k1_generated(),
+        }
+    }
+
+    /// The peer keeps its generated key, and peer id, across runs.
+    pub fn peer_id(&self) -> PeerId {
+        self.key_pair.peer_id()
+    }
+
+    /// Timeout of connecting and of each action. Default is 10 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Maximum length of frames sent and received by this peer. Raise it
+    /// above the node's limit to send oversized messages. Default is 8MiB,
+    /// the tentacle default.
+    pub fn max_frame_length(mut self, max_frame_length: usize) -> Self {
+        self.max_frame_length = max_frame_length;
+        self
+    }
+
+    /// Dial `address` and run `script`, returns messages received by
+    /// `PeerAction::Receive`. Fails if an action times out, or if the node
+    /// closes the session before `PeerAction::ExpectDisconnect`.
+    pub async fn run(&self, address: MultiAddr, script: &[PeerAction]) -> Result<Vec<Bytes>> {
+        let (session_tx, mut session_rx) = mpsc::unbounded_channel();
+        let max_frame_length = self.max_frame_length;
+        let protocol_name = self.protocol_name.clone();
+        let protocol = MetaBuilder::new()
+            .id(self.protocol_id)
+            .name(move |_| protocol_name.clone())
+            .codec(move || {
+                let codec = LengthDelimitedCodec::builder()
+                    .max_frame_length(max_frame_length)
+                    .new_codec();
+                Box::new(codec)
+            })
+            .protocol_spawn(FnSpawn(move |context, control, read_part| {
+                let _ = session_tx.send((context.id, control.clone(), read_part));
+            }))
+            .build();
+        let mut service = ServiceBuilder::new()
+            .insert_protocol(protocol)
+            .key_pair(self.key_pair.clone())
+            .build(());
+        let control = service.control().clone();
+        let service_handle = tokio::spawn(async move { service.run().await });
+
+        let result = async {
+            (control.dial(address, TargetProtocol::All).await)
+                .map_err(|err| anyhow!("dial: {:?}", err))?;
+            let (session_id, control, read_part) =
+                tokio::time::timeout(self.timeout, session_rx.recv())
+                    .await
+                    .context("open protocol timeout")?
+                    .context("service closed")?;
+            self.run_script(session_id, &control, read_part, script)
+                .await
+        }
+        .await;
+
+        let _ = control.shutdown().await;
+        service_handle.abort();
+        result
+    }
+
+    async fn run_script(
+        &self,
+        session_id: SessionId,
+        control: &ServiceAsyncControl,
+        mut read_part: SubstreamReadPart,
+        script: &[PeerAction],
+    ) -> Result<Vec<Bytes>> {
+        let mut received = Vec::new();
+        for (i, action) in script.iter().enumerate() {
+            match action {
+                PeerAction::Send(msg) => {
+                    (control
+                        .send_message_to(session_id, self.protocol_id, msg.clone())
+                        .await)
+                        .map_err(|err| anyhow!("action {}: send: {:?}", i, err))?;
+                }
+                PeerAction::Receive => {
+                    let msg = tokio::time::timeout(self.timeout, read_part.next())
+                        .await
+                        .with_context(|| format!("action {}: receive timeout", i))?;
+                    match msg {
+                        Some(Ok(msg)) => received.push(msg),
+                        Some(Err(err)) => bail!("action {}: receive: {}", i, err),
+                        None => bail!("action {}: disconnected", i),
+                    }
+                }
+                PeerAction::Sleep(duration) => tokio::time::sleep(*duration).await,
+                PeerAction::ExpectDisconnect => loop {
+                    let msg = tokio::time::timeout(self.timeout, read_part.next())
+                        .await
+                        .with_context(|| format!("action {}: not disconnected", i))?;
+                    match msg {
+                        Some(Ok(_)) => continue,
+                        _ => break,
+                    }
+                },
+            }
+        }
+        Ok(received)
+    }
+}
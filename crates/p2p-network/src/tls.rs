@@ -0,0 +1,74 @@
+//! TLS transport, see `P2PTlsConfig`.
+
+use std::{fs::File, io::BufReader, path::Path};
+
+use anyhow::{anyhow, Context, Result};
+use gw_config::P2PTlsConfig;
+use tentacle::{
+    multiaddr::{MultiAddr, Protocol},
+    service::TlsConfig,
+};
+use tokio_rustls::rustls::{
+    server::AllowAnyAuthenticatedClient, Certificate, ClientConfig, PrivateKey, RootCertStore,
+    ServerConfig,
+};
+
+pub(crate) fn is_tls(address: &MultiAddr) -> bool {
+    address.iter().any(|p| matches!(p, Protocol::Tls(_)))
+}
+
+pub(crate) fn tls_config(config: &P2PTlsConfig) -> Result<TlsConfig> {
+    let certs = load_certs(&config.cert_path)?;
+    let key = load_key(&config.key_path)?;
+    let mut roots = RootCertStore::empty();
+    for ca in load_certs(&config.ca_path)? {
+        roots.add(&ca).context("add CA certificate")?;
+    }
+
+    let server = ServerConfig::builder().with_safe_defaults();
+    let server = if config.client_auth {
+        server.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots.clone()))
+    } else {
+        server.with_no_client_auth()
+    };
+    let server = server
+        .with_single_cert(certs.clone(), key.clone())
+        .context("TLS server config")?;
+    // Always present the certificate, in case the peer requires client auth.
+    let client = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_single_cert(certs, key)
+        .context("TLS client config")?;
+    Ok(TlsConfig::new(Some(server), Some(client)))
+}
+
+fn load_certs(path: &Path) -> Result<Vec<Certificate>> {
+    let mut reader = open(path)?;
+    let certs = rustls_pemfile::certs(&mut reader)
+        .with_context(|| format!("read certificates from {}", path.to_string_lossy()))?;
+    if certs.is_empty() {
+        return Err(anyhow!("no certificate in {}", path.to_string_lossy()));
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn load_key(path: &Path) -> Result<PrivateKey> {
+    let mut reader = open(path)?;
+    loop {
+        let item = rustls_pemfile::read_one(&mut reader)
+            .with_context(|| format!("read private key from {}", path.to_string_lossy()))?;
+        match item {
+            Some(rustls_pemfile::Item::PKCS8Key(key))
+            | Some(rustls_pemfile::Item::RSAKey(key))
+            | Some(rustls_pemfile::Item::ECKey(key)) => return Ok(PrivateKey(key)),
+            Some(_) => continue,
+            None => return Err(anyhow!("no private key in {}", path.to_string_lossy())),
+        }
+    }
+}
+
+fn open(path: &Path) -> Result<BufReader<File>> {
+    let file = File::open(path).with_context(|| format!("open {}", path.to_string_lossy()))?;
+    Ok(BufReader::new(file))
+}
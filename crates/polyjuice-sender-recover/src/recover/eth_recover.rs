@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use anyhow::{anyhow, bail, Result};
 use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, registry_address::RegistryAddress, state::State};
 use gw_generator::{error::TransactionError, typed_transaction::types::TypedRawTransaction};
-use gw_store::state::{traits::JournalDB, MemStateDB};
+use gw_store::state::traits::JournalDB;
 use gw_traits::CodeStore;
 use gw_types::{
     bytes::Bytes,
@@ -205,10 +205,10 @@ impl EthRecover {
         }
     }
 
-    pub fn mock_sender_if_not_exists(
+    pub fn mock_sender_if_not_exists<S: State + CodeStore + JournalDB>(
         &self,
         tx: L2Transaction,
-        state: &mut MemStateDB,
+        state: &mut S,
     ) -> Result<L2Transaction, PolyjuiceTxSenderRecoverError> {
         let sender_id: u32 = tx.raw().from_id().unpack();
         if 0 != sender_id {
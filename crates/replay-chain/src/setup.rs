@@ -44,6 +44,7 @@ pub async fn setup(args: SetupArgs) -> Result<Context> {
         path: to_db_store,
         options_file: config.store.options_file.clone(),
         cache_size: config.store.cache_size,
+        ..Default::default()
     };
     let local_store = Store::open(&store_config, COLUMNS).unwrap();
     let rollup_type_script = {
@@ -132,6 +133,7 @@ pub async fn setup(args: SetupArgs) -> Result<Context> {
             path: from_db_store,
             options_file: config.store.options_file.clone(),
             cache_size: config.store.cache_size,
+            ..Default::default()
         };
         Store::open(&store_config, from_db_columns).unwrap()
     };
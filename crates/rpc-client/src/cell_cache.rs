@@ -0,0 +1,127 @@
+//! Cache of L1 cell content, e.g. the rollup config cell, script cells and dep
+//! groups.
+//!
+//! Output and data of a cell never change once the cell is created, only its
+//! status does, so cached content never goes stale. An upgraded script or
+//! config is a new cell with a new out point, replaced out points are evicted
+//! when they are found dead.
+#![allow(clippy::mutable_key_type)]
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context, Result};
+use gw_types::{
+    offchain::CellInfo,
+    packed::{self, OutPoint},
+    prelude::*,
+};
+
+#[derive(Clone, Default)]
+pub struct CellCache {
+    // Persist cells in this directory, in memory only if None.
+    dir: Option<Arc<PathBuf>>,
+    cells: Arc<Mutex<HashMap<OutPoint, CellInfo>>>,
+}
+
+impl CellCache {
+    /// Persist cached cells in `dir`, so they survive restarts.
+    pub fn open(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        Ok(Self {
+            dir: Some(Arc::new(dir.to_owned())),
+            cells: Default::default(),
+        })
+    }
+
+    pub fn get(&self, out_point: &OutPoint) -> Option<CellInfo> {
+        if let Some(cell) = self.cells.lock().unwrap().get(out_point) {
+            return Some(cell.clone());
+        }
+
+        let path = self.path(out_point)?;
+        let bytes = fs::read(&path).ok()?;
+        let cell: CellInfo = match packed::CellInfoReader::from_slice(&bytes) {
+            Ok(cell) if cell.out_point().as_slice() == out_point.as_slice() => cell.unpack(),
+            _ => {
+                log::warn!("[cell cache] remove invalid {}", path.display());
+                let _ = fs::remove_file(&path);
+                return None;
+            }
+        };
+        let mut cells = self.cells.lock().unwrap();
+        cells.insert(out_point.clone(), cell.clone());
+        Some(cell)
+    }
+
+    pub fn insert(&self, cell: CellInfo) {
+        if let Some(path) = self.path(&cell.out_point) {
+            // Write to a temporary file first so readers never see partial content.
+            let tmp = path.with_extension("tmp");
+            let result =
+                fs::write(&tmp, cell.pack().as_slice()).and_then(|_| fs::rename(&tmp, &path));
+            if let Err(err) = result {
+                log::warn!("[cell cache] write {}: {}", path.display(), err);
+            }
+        }
+        let mut cells = self.cells.lock().unwrap();
+        cells.insert(cell.out_point.clone(), cell);
+    }
+
+    pub fn remove(&self, out_point: &OutPoint) {
+        if self.cells.lock().unwrap().remove(out_point).is_some() {
+            log::info!("[cell cache] evict {}", out_point);
+        }
+        if let Some(path) = self.path(out_point) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    fn path(&self, out_point: &OutPoint) -> Option<PathBuf> {
+        let tx_hash = ckb_fixed_hash::H256(out_point.tx_hash().unpack());
+        let index: u32 = out_point.index().unpack();
+        let dir = self.dir.as_ref()?;
+        Some(dir.join(format!("{:x}-{}", tx_hash, index)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gw_types::{bytes::Bytes, packed::CellOutput};
+
+    use super::*;
+
+    fn cell(index: u32) -> CellInfo {
+        CellInfo {
+            out_point: OutPoint::new_builder()
+                .tx_hash([1u8; 32].pack())
+                .index(index.pack())
+                .build(),
+            output: CellOutput::new_builder().capacity(100u64.pack()).build(),
+            data: Bytes::from(vec![index as u8; 4]),
+        }
+    }
+
+    #[test]
+    fn test_cell_cache_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = CellCache::open(dir.path()).unwrap();
+        cache.insert(cell(0));
+        cache.insert(cell(1));
+
+        // Reopen, cells are read from disk.
+        let cache = CellCache::open(dir.path()).unwrap();
+        let cached = cache.get(&cell(0).out_point).unwrap();
+        assert_eq!(cached.data, cell(0).data);
+        assert_eq!(cached.output.as_slice(), cell(0).output.as_slice());
+
+        cache.remove(&cell(1).out_point);
+        let cache = CellCache::open(dir.path()).unwrap();
+        assert!(cache.get(&cell(1).out_point).is_none());
+        assert!(cache.get(&cell(0).out_point).is_some());
+    }
+}
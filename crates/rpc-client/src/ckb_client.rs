@@ -57,6 +57,10 @@ impl CkbClient {
     pub async fn estimate_cycles(&self, tx: Transaction) -> Result<EstimateCycles>;
     pub async fn local_node_info(&self) -> Result<LocalNode>;
     pub async fn get_blockchain_info(&self) -> Result<ChainInfo>;
+    pub async fn get_fee_rate_statics(
+        &self,
+        target: Option<Uint64>,
+    ) -> Result<Option<FeeRateStatics>>;
 }
 
 impl CkbClient {
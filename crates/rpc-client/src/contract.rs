@@ -1,4 +1,8 @@
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Instant,
+};
 
 use anyhow::{anyhow, bail, Result};
 use arc_swap::ArcSwap;
@@ -68,11 +72,54 @@ impl ContractsCellDepManager {
         .await?;
         log::trace!("[contracts dep] refresh {}ms", now.elapsed().as_millis());
 
+        // Evict cached content of replaced cells.
+        let new_out_points = dep_out_points(&deps);
+        for out_point in dep_out_points(&self.load()).difference(&new_out_points) {
+            let out_point: gw_types::packed::OutPoint = out_point.clone().into();
+            self.rpc_client.cell_cache.remove(&out_point);
+        }
+
         self.deps.store(Arc::new(deps));
         Ok(())
     }
 }
 
+fn dep_out_points(deps: &ContractsCellDep) -> HashSet<OutPoint> {
+    let ContractsCellDep {
+        rollup_config,
+        rollup_cell_type,
+        deposit_cell_lock,
+        stake_cell_lock,
+        custodian_cell_lock,
+        withdrawal_cell_lock,
+        challenge_cell_lock,
+        l1_sudt_type,
+        omni_lock,
+        delegate_cell_lock,
+        delegate_cell,
+        allowed_eoa_locks,
+        allowed_contract_types,
+    } = deps;
+    [
+        rollup_config,
+        rollup_cell_type,
+        deposit_cell_lock,
+        stake_cell_lock,
+        custodian_cell_lock,
+        withdrawal_cell_lock,
+        challenge_cell_lock,
+        l1_sudt_type,
+        omni_lock,
+    ]
+    .into_iter()
+    .chain(delegate_cell_lock)
+    .chain(delegate_cell)
+    .chain(allowed_eoa_locks.values())
+    .chain(allowed_contract_types.values())
+    .map(|dep| dep.out_point.clone())
+    .collect()
+}
+
 pub fn check_script(
     script_config: &SystemTypeScriptConfig,
     rollup_config: &RollupConfig,
@@ -0,0 +1,74 @@
+use anyhow::{bail, Result};
+use gw_config::DepositFilterConfig;
+use gw_types::{
+    h256::{H256Ext, H256},
+    packed::DepositRequest,
+    prelude::*,
+};
+
+/// Verify a deposit request against the deposit filter. The capacity is
+/// checked when querying deposit cells.
+pub fn verify_deposit_filter(filter: &DepositFilterConfig, request: &DepositRequest) -> Result<()> {
+    let sudt_script_hash: H256 = request.sudt_script_hash().unpack();
+    if sudt_script_hash.is_zero() {
+        return Ok(());
+    }
+
+    if let Some(ref allowlist) = filter.sudt_allowlist {
+        if !allowlist.iter().any(|h| h.0 == sudt_script_hash) {
+            bail!("sudt {:x?} not in allowlist", sudt_script_hash);
+        }
+    }
+
+    let amount: u128 = request.amount().unpack();
+    let min_amount = (filter.sudt_min_amounts.iter())
+        .find(|m| m.sudt_script_hash.0 == sudt_script_hash)
+        .map(|m| m.amount);
+    if let Some(min_amount) = min_amount {
+        if amount < min_amount {
+            bail!(
+                "sudt {:x?} amount {} lower than {}",
+                sudt_script_hash,
+                amount,
+                min_amount
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use ckb_fixed_hash::H256;
+    use gw_config::{DepositFilterConfig, SudtDepositMinAmount};
+    use gw_types::{packed::DepositRequest, prelude::*};
+
+    use super::verify_deposit_filter;
+
+    #[test]
+    fn test_verify_deposit_filter() {
+        let sudt_a = [1u8; 32];
+        let sudt_b = [2u8; 32];
+        let deposit = |sudt_script_hash: [u8; 32], amount: u128| {
+            DepositRequest::new_builder()
+                .sudt_script_hash(sudt_script_hash.pack())
+                .amount(amount.pack())
+                .build()
+        };
+
+        let mut filter = DepositFilterConfig::default();
+        assert!(verify_deposit_filter(&filter, &deposit(sudt_b, 1)).is_ok());
+
+        filter.sudt_allowlist = Some(vec![H256(sudt_a)]);
+        filter.sudt_min_amounts = vec![SudtDepositMinAmount {
+            sudt_script_hash: H256(sudt_a),
+            amount: 100,
+        }];
+        // CKB deposits
+        assert!(verify_deposit_filter(&filter, &deposit([0u8; 32], 0)).is_ok());
+        assert!(verify_deposit_filter(&filter, &deposit(sudt_a, 100)).is_ok());
+        assert!(verify_deposit_filter(&filter, &deposit(sudt_a, 99)).is_err());
+        assert!(verify_deposit_filter(&filter, &deposit(sudt_b, 100)).is_err());
+    }
+}
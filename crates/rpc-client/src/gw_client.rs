@@ -1,6 +1,9 @@
 use crate::utils::{JsonH256, TracingHttpClient};
 use anyhow::Result;
-use gw_jsonrpc_types::{ckb_jsonrpc_types::*, godwoken::RegistryAddress};
+use gw_jsonrpc_types::{
+    ckb_jsonrpc_types::*,
+    godwoken::{BackupInfo, RegistryAddress},
+};
 use jsonrpc_utils::rpc_client;
 
 #[derive(Clone)]
@@ -16,6 +19,7 @@ impl GWClient {
         script_hash: JsonH256,
         registry_id: Uint32,
     ) -> Result<Option<RegistryAddress>>;
+    pub async fn gw_create_backup(&self) -> Result<BackupInfo>;
 }
 
 impl GWClient {
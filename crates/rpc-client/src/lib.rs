@@ -1,5 +1,7 @@
+pub mod cell_cache;
 pub mod ckb_client;
 pub mod contract;
+pub mod deposit;
 pub mod error;
 pub mod gw_client;
 pub mod indexer_client;
@@ -3,6 +3,7 @@
 use std::{collections::HashSet, time::Duration};
 
 use anyhow::{anyhow, Result};
+use gw_config::DepositFilterConfig;
 use gw_jsonrpc_types::ckb_jsonrpc_types::{self, BlockNumber, OutputsValidator, Uint32};
 use gw_types::{
     bytes::Bytes,
@@ -21,7 +22,9 @@ use rand::prelude::*;
 use tracing::instrument;
 
 use crate::{
+    cell_cache::CellCache,
     ckb_client::CkbClient,
+    deposit::verify_deposit_filter,
     indexer_client::CkbIndexerClient,
     indexer_types::{Cell, Order, ScriptType, SearchKey, SearchKeyFilter},
     utils::DEFAULT_QUERY_LIMIT,
@@ -105,6 +108,7 @@ pub struct RPCClient {
     pub ckb: CkbClient,
     pub rollup_type_script: ckb_types::packed::Script,
     pub rollup_config: RollupConfig,
+    pub cell_cache: CellCache,
 }
 
 impl RPCClient {
@@ -119,9 +123,14 @@ impl RPCClient {
             ckb,
             rollup_type_script,
             rollup_config,
+            cell_cache: CellCache::default(),
         }
     }
 
+    pub fn with_cell_cache(self, cell_cache: CellCache) -> Self {
+        Self { cell_cache, ..self }
+    }
+
     /// query lived rollup cell
     #[instrument(skip_all)]
     pub async fn query_rollup_cell(&self) -> Result<Option<CellInfo>> {
@@ -205,12 +214,16 @@ impl RPCClient {
             CellInfo {
                 output,
                 data,
-                out_point,
+                out_point: out_point.clone(),
             }
         });
         let status = match cell_with_status.status.as_str() {
             "live" => CellStatus::Live,
-            "dead" => CellStatus::Dead,
+            "dead" => {
+                // The cell is consumed, e.g. a script or config cell is upgraded.
+                self.cell_cache.remove(&out_point);
+                CellStatus::Dead
+            }
             "unknown" => CellStatus::Unknown,
             err => return Err(anyhow!("can't parse cell status: {}", err)),
         };
@@ -220,6 +233,22 @@ impl RPCClient {
         }))
     }
 
+    /// Get output and data of a cell, from the cell cache if possible.
+    ///
+    /// Content of a cell never changes, but a cached cell may be dead already,
+    /// use `get_cell` for checking status.
+    #[instrument(skip_all, fields(tx_hash = %out_point.tx_hash(), index = Unpack::<u32>::unpack(&out_point.index())))]
+    pub async fn get_cell_content(&self, out_point: OutPoint) -> Result<Option<CellInfo>> {
+        if let Some(cell) = self.cell_cache.get(&out_point) {
+            return Ok(Some(cell));
+        }
+        let cell = self.get_cell(out_point).await?.and_then(|c| c.cell);
+        if let Some(ref cell) = cell {
+            self.cell_cache.insert(cell.clone());
+        }
+        Ok(cell)
+    }
+
     #[instrument(skip_all, fields(tx_hash = %out_point.tx_hash(), index = Unpack::<u32>::unpack(&out_point.index())))]
     pub async fn get_cell_from_mempool(&self, out_point: OutPoint) -> Result<Option<CellInfo>> {
         let tx = match self
@@ -269,17 +298,23 @@ impl RPCClient {
 
     /// return all lived deposit requests
     /// NOTICE the returned cells may contains invalid cells.
-    #[instrument(skip(self, dead_cells))]
+    ///
+    /// Cells rejected by `filter` are skipped and left on L1.
+    #[instrument(skip(self, dead_cells, filter))]
     pub async fn query_deposit_cells(
         &self,
         count: usize,
         deposit_minimal_blocks: u64,
         min_ckb_deposit_capacity: u64,
         min_sudt_deposit_capacity: u64,
+        filter: &DepositFilterConfig,
         dead_cells: &HashSet<OutPoint>,
     ) -> Result<Vec<DepositInfo>> {
         const BLOCKS_TO_SEARCH: u64 = 2000;
 
+        let min_ckb_deposit_capacity = min_ckb_deposit_capacity.max(filter.min_capacity);
+        let min_sudt_deposit_capacity = min_sudt_deposit_capacity.max(filter.min_capacity);
+
         let tip_number: u64 = self.get_tip().await?.number().unpack();
         let mut deposit_infos = Vec::new();
 
@@ -365,6 +400,11 @@ impl RPCClient {
                     continue;
                 }
 
+                if let Err(err) = verify_deposit_filter(filter, &request) {
+                    log::debug!(target: "collect-deposit-cells", "skip filtered deposit cell: {}", err);
+                    continue;
+                }
+
                 let info = DepositInfo { cell, request };
                 deposit_infos.push(info);
             }
@@ -666,6 +706,7 @@ impl RPCClient {
         &self,
         compatible_finalized_timepoint: &CompatibleFinalizedTimepoint,
         exclusions: &HashSet<OutPoint>,
+        owner_lock_hashes: &HashSet<H256>,
         max_cells: usize,
     ) -> Result<Vec<CellInfo>> {
         let withdrawal_lock = Script::new_builder()
@@ -703,6 +744,14 @@ impl RPCClient {
                     continue;
                 }
 
+                if !owner_lock_hashes.is_empty() {
+                    let owner_lock_hash = crate::withdrawal::owner_lock_hash(&info);
+                    if !matches!(owner_lock_hash, Some(h) if owner_lock_hashes.contains(&h)) {
+                        log::debug!("[finalized withdrawal] skip, not a configured owner");
+                        continue;
+                    }
+                }
+
                 collected.push(info);
                 if collected.len() >= max_cells {
                     break;
@@ -2,6 +2,7 @@ use anyhow::{bail, Result};
 use gw_types::{
     bytes::Bytes,
     core::{ScriptHashType, Timepoint},
+    h256::H256,
     offchain::{CellInfo, CompatibleFinalizedTimepoint},
     packed::{Byte32, Script, ScriptReader, WithdrawalLockArgs, WithdrawalLockArgsReader},
     prelude::*,
@@ -16,6 +17,14 @@ pub fn verify_unlockable_to_owner(
     verify_finalized_owner_lock(info, compatible_finalized_timepoint)
 }
 
+/// Owner lock hash in the lock args of a withdrawal cell.
+pub fn owner_lock_hash(info: &CellInfo) -> Option<H256> {
+    let args: Bytes = info.output.lock().args().unpack();
+    let lock_args = args.get(32..32 + WithdrawalLockArgs::TOTAL_SIZE)?;
+    let lock_args = WithdrawalLockArgs::from_slice(lock_args).ok()?;
+    Some(lock_args.owner_lock_hash().unpack())
+}
+
 fn verify_l1_sudt_script(info: &CellInfo, l1_sudt_script_hash: &Byte32) -> Result<()> {
     if let Some(sudt_type) = info.output.type_().to_opt() {
         if info.data.len() < ckb_types::packed::Uint128::TOTAL_SIZE {
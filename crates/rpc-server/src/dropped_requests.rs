@@ -0,0 +1,46 @@
+use std::sync::Mutex;
+
+use gw_types::h256::*;
+use lru::LruCache;
+
+use crate::utils::unix_millis;
+
+#[derive(Debug, Clone)]
+pub struct DroppedRequest {
+    pub reason: String,
+    /// Unix timestamp in milliseconds.
+    pub dropped_at: u64,
+}
+
+/// Recently dropped transactions and withdrawal requests.
+///
+/// Submit RPCs return once a request is queued, requests may be dropped later
+/// by verification or execution. (For get_dropped_request RPC calls.)
+pub struct DroppedRequests {
+    cache: Mutex<LruCache<H256, DroppedRequest>>,
+}
+
+impl DroppedRequests {
+    pub fn new(capacity: usize) -> Self {
+        DroppedRequests {
+            cache: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    pub fn insert(&self, hash: H256, reason: String) {
+        let request = DroppedRequest {
+            reason,
+            dropped_at: unix_millis(),
+        };
+        self.cache.lock().unwrap().put(hash, request);
+    }
+
+    /// Forget a request, e.g. it's submitted again.
+    pub fn remove(&self, hash: &H256) {
+        self.cache.lock().unwrap().pop(hash);
+    }
+
+    pub fn get(&self, hash: &H256) -> Option<DroppedRequest> {
+        self.cache.lock().unwrap().peek(hash).cloned()
+    }
+}
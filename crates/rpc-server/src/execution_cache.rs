@@ -0,0 +1,78 @@
+use std::sync::Mutex;
+
+use gw_common::blake2b::new_blake2b;
+use gw_types::{
+    h256::*, offchain::RunResult, packed::RawL2Transaction, prelude::*,
+    registry_address::RegistryAddress,
+};
+use lru::LruCache;
+
+struct Entry {
+    // Mem pool state version the result is computed at. None for results at
+    // a history block, which never change.
+    state_version: Option<u64>,
+    run_result: RunResult,
+}
+
+/// Results of recent identical `gw_execute_raw_l2transaction` requests, e.g.
+/// dashboards issuing the same `eth_call` many times per block.
+pub struct ExecutionCache {
+    cache: Mutex<LruCache<H256, Entry>>,
+}
+
+impl ExecutionCache {
+    pub fn new(capacity: usize) -> Self {
+        ExecutionCache {
+            cache: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Key of a request executed on top of the tip block `tip_block_hash`.
+    pub fn key(
+        tip_block_hash: &H256,
+        raw_tx: &RawL2Transaction,
+        block_number: Option<u64>,
+        registry_address: Option<&RegistryAddress>,
+    ) -> H256 {
+        let mut hasher = new_blake2b();
+        hasher.update(tip_block_hash.as_slice());
+        hasher.update(raw_tx.as_slice());
+        match block_number {
+            Some(n) => {
+                hasher.update(&[1]);
+                hasher.update(&n.to_le_bytes());
+            }
+            None => hasher.update(&[0]),
+        }
+        match registry_address {
+            Some(a) => {
+                hasher.update(&[1]);
+                hasher.update(&a.to_bytes());
+            }
+            None => hasher.update(&[0]),
+        }
+        let mut key = H256::zero();
+        hasher.finalize(&mut key);
+        key
+    }
+
+    /// Get the cached result. Results computed at another state version are
+    /// evicted.
+    pub fn get(&self, key: &H256, state_version: Option<u64>) -> Option<RunResult> {
+        let mut cache = self.cache.lock().unwrap();
+        let entry = cache.get(key)?;
+        if entry.state_version != state_version {
+            cache.pop(key);
+            return None;
+        }
+        Some(entry.run_result.clone())
+    }
+
+    pub fn insert(&self, key: H256, state_version: Option<u64>, run_result: RunResult) {
+        let entry = Entry {
+            state_version,
+            run_result,
+        };
+        self.cache.lock().unwrap().put(key, entry);
+    }
+}
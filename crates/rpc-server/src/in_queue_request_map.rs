@@ -1,8 +1,12 @@
 use std::sync::{Arc, RwLock};
-use std::{collections::HashMap, sync::Weak};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Weak,
+};
 
 use gw_types::h256::*;
 use gw_types::packed::{L2Transaction, WithdrawalRequestExtra};
+use gw_types::prelude::*;
 
 use crate::registry::Request;
 
@@ -11,17 +15,44 @@ use crate::registry::Request;
 /// (For get_transaction and get_withdrawal RPC calls.)
 #[derive(Default)]
 pub struct InQueueRequestMap {
-    map: RwLock<HashMap<H256, Request>>,
+    inner: RwLock<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    map: HashMap<H256, Request>,
+    /// Request hashes by sender, for gw_get_account_queue.
+    by_sender: HashMap<RequestSender, HashSet<H256>>,
+}
+
+/// Txs are sent by account id, withdrawals by account script hash.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum RequestSender {
+    AccountId(u32),
+    ScriptHash(H256),
+}
+
+impl From<&Request> for RequestSender {
+    fn from(req: &Request) -> Self {
+        match req {
+            Request::Tx(tx) => RequestSender::AccountId(tx.raw().from_id().unpack()),
+            Request::Withdrawal(w) => {
+                RequestSender::ScriptHash(w.raw().account_script_hash().unpack())
+            }
+        }
+    }
 }
 
 impl InQueueRequestMap {
     pub(crate) fn insert(self: &Arc<Self>, k: H256, v: Request) -> Option<InQueueRequestHandle> {
         gw_metrics::rpc().in_queue_requests((&v).into()).inc();
 
-        let mut map = self.map.write().unwrap();
-        let inserted = map.insert(k, v).is_none();
+        let mut inner = self.inner.write().unwrap();
+        let sender = RequestSender::from(&v);
+        let inserted = inner.map.insert(k, v).is_none();
 
         if inserted {
+            inner.by_sender.entry(sender).or_default().insert(k);
             Some(InQueueRequestHandle {
                 map: Arc::downgrade(self),
                 hash: k,
@@ -32,28 +63,66 @@ impl InQueueRequestMap {
     }
 
     fn remove(&self, k: &H256) {
-        let mut map = self.map.write().unwrap();
-        if let Some(v) = map.remove(k) {
+        let mut inner = self.inner.write().unwrap();
+        if let Some(v) = inner.map.remove(k) {
             gw_metrics::rpc().in_queue_requests((&v).into()).dec();
+
+            let sender = RequestSender::from(&v);
+            if let Some(hashes) = inner.by_sender.get_mut(&sender) {
+                hashes.remove(k);
+                if hashes.is_empty() {
+                    inner.by_sender.remove(&sender);
+                }
+            }
         }
     }
 
     pub(crate) fn get_transaction(&self, k: &H256) -> Option<L2Transaction> {
-        match self.map.read().unwrap().get(k)? {
+        match self.inner.read().unwrap().map.get(k)? {
             Request::Tx(tx) => Some(tx.clone()),
             _ => None,
         }
     }
 
     pub(crate) fn get_withdrawal(&self, k: &H256) -> Option<WithdrawalRequestExtra> {
-        match self.map.read().unwrap().get(k)? {
+        match self.inner.read().unwrap().map.get(k)? {
             Request::Withdrawal(w) => Some(w.clone()),
             _ => None,
         }
     }
 
     pub(crate) fn contains(&self, k: &H256) -> bool {
-        self.map.read().unwrap().contains_key(k)
+        self.inner.read().unwrap().map.contains_key(k)
+    }
+
+    /// In queue requests sent by the account.
+    pub(crate) fn requests_from(
+        &self,
+        account_id: u32,
+        script_hash: &H256,
+    ) -> Vec<(H256, Request)> {
+        let inner = self.inner.read().unwrap();
+        let senders = [
+            RequestSender::AccountId(account_id),
+            RequestSender::ScriptHash(*script_hash),
+        ];
+        senders
+            .iter()
+            .filter_map(|sender| inner.by_sender.get(sender))
+            .flatten()
+            .filter_map(|hash| Some((*hash, inner.map.get(hash)?.clone())))
+            .collect()
+    }
+
+    /// Count in queue (txs, withdrawals).
+    pub(crate) fn count(&self) -> (usize, usize) {
+        let inner = self.inner.read().unwrap();
+        let txs = inner
+            .map
+            .values()
+            .filter(|v| matches!(v, Request::Tx(_)))
+            .count();
+        (txs, inner.map.len() - txs)
     }
 }
 
@@ -63,6 +132,13 @@ pub(crate) struct InQueueRequestHandle {
     hash: H256,
 }
 
+impl InQueueRequestHandle {
+    /// Hash of the request in the map, signature hash for txs from id zero.
+    pub(crate) fn hash(&self) -> H256 {
+        self.hash
+    }
+}
+
 impl Drop for InQueueRequestHandle {
     fn drop(&mut self) {
         if let Some(map) = self.map.upgrade() {
@@ -1,4 +1,7 @@
+pub(crate) mod dropped_requests;
+pub(crate) mod execution_cache;
 pub(crate) mod in_queue_request_map;
+pub(crate) mod packaging_records;
 pub mod registry;
 pub mod server;
 
@@ -0,0 +1,93 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use gw_mem_pool::fee::types::{FeeEntry, FeeItemKind};
+use gw_types::h256::*;
+
+/// Max not fetched candidates recorded per mem block, highest priority first.
+const MAX_NOT_FETCHED: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackagingDecision {
+    Packaged,
+    /// Returned to the queue, e.g. the mem block cycles limit is reached.
+    Deferred(String),
+    Dropped(String),
+    /// Still in the queue when the mem block was produced.
+    NotFetched,
+}
+
+/// Ordering inputs and the decision of a request.
+#[derive(Debug, Clone)]
+pub struct PackagingCandidate {
+    pub hash: H256,
+    pub kind: FeeItemKind,
+    pub fee: u128,
+    pub cycles_limit: u64,
+    /// Arrival order in the fee queue
+    pub order: usize,
+    /// Unix timestamp in milliseconds
+    pub received_at: u64,
+    pub decision: PackagingDecision,
+}
+
+impl PackagingCandidate {
+    pub fn new(
+        entry: &FeeEntry,
+        hash: H256,
+        received_at: u64,
+        decision: PackagingDecision,
+    ) -> Self {
+        PackagingCandidate {
+            hash,
+            kind: entry.item.kind(),
+            fee: entry.fee,
+            cycles_limit: entry.cycles_limit,
+            order: entry.order,
+            received_at,
+            decision,
+        }
+    }
+}
+
+/// Packaging decisions of recent mem blocks, in the order they were made.
+///
+/// (For debug_explain_packaging RPC calls.)
+pub struct PackagingRecords {
+    capacity: usize,
+    blocks: Mutex<BTreeMap<u64, Vec<PackagingCandidate>>>,
+}
+
+impl PackagingRecords {
+    pub fn new(capacity: usize) -> Self {
+        PackagingRecords {
+            capacity,
+            blocks: Default::default(),
+        }
+    }
+
+    pub fn record(&self, block_number: u64, candidate: PackagingCandidate) {
+        self.record_many(block_number, std::iter::once(candidate));
+    }
+
+    /// Record candidates left in the queue, expects them in priority order.
+    pub fn record_not_fetched(
+        &self,
+        block_number: u64,
+        candidates: impl Iterator<Item = PackagingCandidate>,
+    ) {
+        self.record_many(block_number, candidates.take(MAX_NOT_FETCHED));
+    }
+
+    fn record_many(&self, block_number: u64, candidates: impl Iterator<Item = PackagingCandidate>) {
+        let mut blocks = self.blocks.lock().unwrap();
+        blocks.entry(block_number).or_default().extend(candidates);
+        while blocks.len() > self.capacity {
+            let oldest = *blocks.keys().next().expect("oldest");
+            blocks.remove(&oldest);
+        }
+    }
+
+    pub fn get(&self, block_number: u64) -> Option<Vec<PackagingCandidate>> {
+        self.blocks.lock().unwrap().get(&block_number).cloned()
+    }
+}
@@ -1,7 +1,11 @@
 use std::{
     convert::TryInto,
     fmt::Display,
-    sync::Arc,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
@@ -11,36 +15,52 @@ use gw_common::blake2b::new_blake2b;
 use gw_common::builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID};
 use gw_common::state::State;
 use gw_config::{
-    BackendForkConfig, ChainConfig, FeeConfig, GaslessTxSupportConfig, MemPoolConfig, NodeMode,
-    RPCMethods, RPCRateLimit, RPCServerConfig, SyscallCyclesConfig, SystemTypeScriptConfig,
+    ChainConfig, FeeConfig, GaslessTxSupportConfig, MemPoolConfig, NodeMode, RPCMethods,
+    RPCRateLimit, RPCServerConfig, SyscallCyclesConfig, SystemTypeScriptConfig,
 };
-use gw_generator::backend_manage::BackendManage;
 use gw_generator::generator::CyclesPool;
 use gw_generator::utils::get_tx_type;
 use gw_generator::{
-    error::TransactionError, sudt::build_l2_sudt_script,
-    verification::transaction::TransactionVerifier, Generator,
+    error::TransactionError,
+    sudt::build_l2_sudt_script,
+    verification::{intrinsic::IntrinsicVerifier, transaction::TransactionVerifier},
+    Generator,
 };
 use gw_jsonrpc_types::{
-    ckb_jsonrpc_types::{JsonBytes, Script, Uint32, Uint64},
-    debug::DebugRunResult,
+    ckb_jsonrpc_types::{JsonBytes, Script, Uint128, Uint32, Uint64},
+    debug::{self, DebugRunResult, PackagingExplanation},
     godwoken::*,
-    test_mode::TestModePayload,
+    test_mode::{ChallengeStatus, TestModePayload},
     JsonCalcHash,
 };
-use gw_mem_pool::fee::{
-    queue::FeeQueue,
-    types::{FeeEntry, FeeItem, FeeItemKind, FeeItemSender},
+use gw_mem_pool::{
+    denylist::{self, Denylist},
+    fee::{
+        ordering::ordering_policy,
+        queue::FeeQueue,
+        types::{FeeEntry, FeeItem, FeeItemKind, FeeItemSender},
+    },
+    pool::{pre_verify_transaction, PreVerifiedTransaction},
+    priority_lane::PriorityLane,
+    throttle::AccountThrottle,
+    tx_relay::{TxRelay, TxRelayError},
+    withdrawal_limit::{WithdrawalAsset, WithdrawalLimits},
 };
+use gw_metrics::rpc::CacheResult;
+use gw_p2p_network::P2PNetworkStatus;
 use gw_polyjuice_sender_recover::recover::PolyjuiceSenderRecover;
 use gw_rpc_client::rpc_client::RPCClient;
 use gw_store::{
     autorocks::Direction,
     chain_view::ChainView,
+    deposit_history::DepositCursor,
+    fee_rebate::TxOrigin,
+    log_index::LogIndexKey,
     mem_pool_state::MemPoolState,
     schema::COLUMN_ACCOUNT_SMT_LEAF,
-    state::{history::history_state::RWConfig, BlockStateDB, MemStateDB},
+    state::{history::history_state::RWConfig, traits::JournalDB, BlockStateDB, MemStateDB},
     traits::chain_store::ChainStore,
+    transaction::{StoreTransaction, TransactionSnapshot},
     CfMemStat, Store,
 };
 use gw_telemetry::traits::{TelemetryContext, TelemetryContextNewSpan, TelemetrySpanExt};
@@ -49,24 +69,37 @@ use gw_types::packed::RawL2Transaction;
 use gw_types::{
     bytes::Bytes,
     h256::*,
-    packed::{self, BlockInfo, Byte32, L2Transaction, RollupConfig, WithdrawalRequestExtra},
+    packed::{
+        self, BlockInfo, Byte32, L2Transaction, RollupConfig, TransactionKey,
+        WithdrawalRequestExtra,
+    },
     prelude::*,
     U256,
 };
-use gw_utils::RollupContext;
+use gw_utils::{
+    calc_finalizing_range,
+    maintenance::{MaintenanceMode, MaintenanceState},
+    production::{ProductionControl, ProductionState},
+    script_log::{parse_log, GwLog},
+    RollupContext,
+};
 use gw_version::Version;
 use jsonrpc_core::{ErrorCode, MetaIoHandler};
 use jsonrpc_utils::{pub_sub::Session, rpc};
 use lru::LruCache;
 use once_cell::sync::Lazy;
 use pprof::ProfilerGuard;
+use sha3::{Digest, Keccak256};
 use std::collections::HashMap;
 use tokio::sync::{mpsc, Mutex};
 use tracing::instrument;
 
 use crate::apis::debug::replay_transaction;
+use crate::dropped_requests::DroppedRequests;
+use crate::execution_cache::ExecutionCache;
 use crate::in_queue_request_map::{InQueueRequestHandle, InQueueRequestMap};
-use crate::utils::{to_h256, to_jsonh256};
+use crate::packaging_records::{PackagingCandidate, PackagingDecision, PackagingRecords};
+use crate::utils::{to_h256, to_jsonh256, unix_millis};
 
 static PROFILER_GUARD: Lazy<tokio::sync::Mutex<Option<ProfilerGuard>>> =
     Lazy::new(|| tokio::sync::Mutex::new(None));
@@ -82,6 +115,10 @@ const HEADER_NOT_FOUND_ERR_CODE: i64 = -32000;
 const INVALID_NONCE_ERR_CODE: i64 = -32001;
 const BUSY_ERR_CODE: i64 = -32006;
 const CUSTODIAN_NOT_ENOUGH_CODE: i64 = -32007;
+const ACCOUNT_THROTTLED_ERR_CODE: i64 = -32008;
+const MAINTENANCE_ERR_CODE: i64 = -32009;
+const MEM_POOL_PAUSED_ERR_CODE: i64 = -32010;
+const WITHDRAWAL_LIMITED_ERR_CODE: i64 = -32011;
 
 type SendTransactionRateLimiter = Mutex<LruCache<u32, Instant>>;
 
@@ -125,6 +162,73 @@ fn rpc_error_with_data(
     })
 }
 
+fn to_maintenance_status(state: MaintenanceState) -> MaintenanceStatus {
+    match state {
+        MaintenanceState::Running => MaintenanceStatus::Running,
+        MaintenanceState::Entering => MaintenanceStatus::Entering,
+        MaintenanceState::Active => MaintenanceStatus::Active,
+    }
+}
+
+fn to_block_production_status(state: ProductionState) -> BlockProductionStatus {
+    match state {
+        ProductionState::Running => BlockProductionStatus::Running,
+        ProductionState::Pausing => BlockProductionStatus::Pausing,
+        ProductionState::Paused => BlockProductionStatus::Paused,
+    }
+}
+
+fn to_json_packaging_candidate(candidate: PackagingCandidate) -> debug::PackagingCandidate {
+    let kind = match candidate.kind {
+        FeeItemKind::Tx => debug::PackagingItemKind::Tx,
+        FeeItemKind::PendingCreateSenderTx => debug::PackagingItemKind::PendingCreateSenderTx,
+        FeeItemKind::Withdrawal => debug::PackagingItemKind::Withdrawal,
+    };
+    let (decision, reason) = match candidate.decision {
+        PackagingDecision::Packaged => (debug::PackagingDecision::Packaged, None),
+        PackagingDecision::Deferred(reason) => (debug::PackagingDecision::Deferred, Some(reason)),
+        PackagingDecision::Dropped(reason) => (debug::PackagingDecision::Dropped, Some(reason)),
+        PackagingDecision::NotFetched => (debug::PackagingDecision::NotFetched, None),
+    };
+    debug::PackagingCandidate {
+        hash: to_jsonh256(candidate.hash),
+        kind,
+        fee: candidate.fee.into(),
+        cycles_limit: candidate.cycles_limit.into(),
+        order: (candidate.order as u64).into(),
+        received_at: candidate.received_at.into(),
+        decision,
+        reason,
+    }
+}
+
+fn to_json_denylist_entry(entry: denylist::DenylistEntry) -> DenylistEntry {
+    match entry {
+        denylist::DenylistEntry::ScriptHash(hash) => DenylistEntry::ScriptHash(to_jsonh256(hash)),
+        denylist::DenylistEntry::RegistryAddress(addr) => {
+            DenylistEntry::RegistryAddress(addr.into())
+        }
+    }
+}
+
+fn to_json_backup_info(info: gw_store::autorocks::BackupInfo) -> BackupInfo {
+    BackupInfo {
+        id: info.id.into(),
+        timestamp: (info.timestamp as u64).into(),
+        size: info.size.into(),
+        number_files: info.number_files.into(),
+    }
+}
+
+fn from_json_denylist_entry(entry: DenylistEntry) -> denylist::DenylistEntry {
+    match entry {
+        DenylistEntry::ScriptHash(hash) => denylist::DenylistEntry::ScriptHash(to_h256(hash)),
+        DenylistEntry::RegistryAddress(addr) => {
+            denylist::DenylistEntry::RegistryAddress(addr.into())
+        }
+    }
+}
+
 fn method_not_found() -> MyRpcError {
     MyRpcError(jsonrpc_core::Error::method_not_found())
 }
@@ -138,6 +242,7 @@ fn header_not_found_err() -> MyRpcError {
 pub trait TestModeRpc {
     async fn tests_get_global_state(&self) -> Result<GlobalState>;
     async fn tests_produce_block(&self, payload: TestModePayload) -> Result<()>;
+    async fn tests_get_challenge_status(&self) -> Result<ChallengeStatus>;
 }
 
 #[async_trait]
@@ -148,12 +253,26 @@ impl<T: TestModeRpc + Send + Sync + ?Sized> TestModeRpc for Arc<T> {
     async fn tests_produce_block(&self, payload: TestModePayload) -> Result<()> {
         T::tests_produce_block(self, payload).await
     }
+    async fn tests_get_challenge_status(&self) -> Result<ChallengeStatus> {
+        T::tests_get_challenge_status(self).await
+    }
 }
 
 pub struct RequestContext {
-    _in_queue_handle: InQueueRequestHandle,
+    in_queue_handle: InQueueRequestHandle,
+    origin: Option<Bytes>,
+    /// Unix timestamp in milliseconds
+    received_at: u64,
     trace: gw_telemetry::Context,
     in_queue_span: tracing::Span,
+    /// Deferred by the soft cycles limit before, counted in metrics once.
+    cycles_deferred: bool,
+}
+
+impl RequestContext {
+    fn request_hash(&self) -> H256 {
+        self.in_queue_handle.hash()
+    }
 }
 
 impl TelemetryContext for RequestContext {
@@ -189,7 +308,19 @@ pub struct RegistryArgs {
     pub system_type_script_config: SystemTypeScriptConfig,
     pub gasless_tx_support_config: Option<GaslessTxSupportConfig>,
     pub polyjuice_sender_recover: PolyjuiceSenderRecover,
-    pub debug_backend_forks: Option<Vec<BackendForkConfig>>,
+    /// Generator with the debug backend forks, the generator is used if it's
+    /// not set.
+    pub debug_generator: Option<Arc<Generator>>,
+    pub p2p_network_status: Option<Arc<P2PNetworkStatus>>,
+    pub maintenance_mode: Option<Arc<MaintenanceMode>>,
+    pub production_control: Option<Arc<ProductionControl>>,
+    pub backup_dir: Option<PathBuf>,
+    /// The config file, to reload backend forks from.
+    pub config_path: Option<PathBuf>,
+    /// Readonly nodes relay submitted txs to the full node with it.
+    pub tx_relay: Option<Arc<TxRelay>>,
+    /// Txs relayed by readonly nodes, submitted like txs from the RPC.
+    pub relayed_txs: Option<mpsc::Receiver<L2Transaction>>,
 }
 
 pub struct Registry {
@@ -202,6 +333,10 @@ pub struct Registry {
     pub(crate) backend_info: Vec<BackendInfo>,
     pub(crate) node_mode: NodeMode,
     pub(crate) submit_tx: mpsc::Sender<(Request, RequestContext)>,
+    /// Channel of priority lane txs, so they don't wait behind normal txs
+    /// while the mem block is full.
+    pub(crate) priority_submit_tx: mpsc::Sender<(Request, RequestContext)>,
+    pub(crate) priority_lane: PriorityLane,
     pub(crate) rpc_client: RPCClient,
     pub(crate) send_tx_rate_limit: Option<SendTransactionRateLimiter>,
     pub(crate) send_tx_rate_limit_config: Option<RPCRateLimit>,
@@ -210,11 +345,27 @@ pub struct Registry {
     pub(crate) gasless_tx_support_config: Option<GaslessTxSupportConfig>,
     pub(crate) mem_pool_state: Arc<MemPoolState>,
     pub(crate) in_queue_request_map: Option<Arc<InQueueRequestMap>>,
+    dropped_requests: Option<Arc<DroppedRequests>>,
+    packaging_records: Option<Arc<PackagingRecords>>,
+    execution_cache: Option<ExecutionCache>,
     pub(crate) polyjuice_sender_recover: Arc<PolyjuiceSenderRecover>,
     pub(crate) debug_generator: Arc<Generator>,
     pub(crate) system_type_script_config: SystemTypeScriptConfig,
     pub(crate) system_type_scripts: SystemTypeScripts,
     pub(crate) fee_config: FeeConfig,
+    pub(crate) p2p_network_status: Option<Arc<P2PNetworkStatus>>,
+    pub(crate) account_throttle: Option<Arc<AccountThrottle>>,
+    pub(crate) denylist: Option<Arc<Denylist>>,
+    pub(crate) withdrawal_limits: Option<Arc<WithdrawalLimits>>,
+    pub(crate) maintenance_mode: Option<Arc<MaintenanceMode>>,
+    pub(crate) production_control: Option<Arc<ProductionControl>>,
+    pub(crate) backup_dir: Option<PathBuf>,
+    pub(crate) config_path: Option<PathBuf>,
+    pub(crate) tx_relay: Option<Arc<TxRelay>>,
+    // Set by the pause/resume mem pool admin RPCs.
+    mem_pool_paused: AtomicBool,
+    // Serialize updates of the finalized block SMT.
+    finalized_block_smt_lock: Mutex<()>,
 }
 
 impl Registry {
@@ -234,35 +385,79 @@ impl Registry {
             fee_config,
             system_type_script_config,
             polyjuice_sender_recover,
-            debug_backend_forks,
+            debug_generator,
             gasless_tx_support_config,
+            p2p_network_status,
+            maintenance_mode,
+            production_control,
+            backup_dir,
+            config_path,
+            tx_relay,
+            relayed_txs,
         } = args;
 
         let backend_info = get_backend_info(generator.clone());
 
-        let mem_pool_state = match mem_pool.as_ref() {
-            Some(pool) => {
-                let mem_pool = pool.lock().await;
-                mem_pool.mem_pool_state()
-            }
-            None => Arc::new(MemPoolState::new(
-                MemStateDB::from_store(store.get_snapshot()).expect("mem state DB"),
-                true,
-            )),
-        };
+        let (mem_pool_state, account_throttle, denylist, withdrawal_limits) =
+            match mem_pool.as_ref() {
+                Some(pool) => {
+                    let mem_pool = pool.lock().await;
+                    (
+                        mem_pool.mem_pool_state(),
+                        Some(mem_pool.account_throttle()),
+                        Some(mem_pool.denylist()),
+                        Some(mem_pool.withdrawal_limits()),
+                    )
+                }
+                None => (
+                    Arc::new(MemPoolState::new(
+                        MemStateDB::from_store(store.get_snapshot()).expect("mem state DB"),
+                        true,
+                    )),
+                    None,
+                    None,
+                    None,
+                ),
+            };
         let in_queue_request_map = if matches!(node_mode, NodeMode::FullNode | NodeMode::Test) {
             Some(Arc::new(InQueueRequestMap::default()))
         } else {
             None
         };
+        let dropped_requests = match server_config.dropped_requests_cache_size {
+            size if size > 0 && in_queue_request_map.is_some() => {
+                Some(Arc::new(DroppedRequests::new(size)))
+            }
+            _ => None,
+        };
+        let debug_enabled = server_config.enable_methods.contains(&RPCMethods::Debug);
+        let packaging_records = match server_config.packaging_records_size {
+            size if size > 0 && debug_enabled && in_queue_request_map.is_some() => {
+                Some(Arc::new(PackagingRecords::new(size)))
+            }
+            _ => None,
+        };
+        let execution_cache = match server_config.execute_cache_size {
+            0 => None,
+            size => Some(ExecutionCache::new(size)),
+        };
         let (submit_tx, submit_rx) = mpsc::channel(RequestSubmitter::MAX_CHANNEL_SIZE);
+        let (priority_submit_tx, priority_submit_rx) =
+            mpsc::channel(RequestSubmitter::MAX_CHANNEL_SIZE);
         let polyjuice_sender_recover = Arc::new(polyjuice_sender_recover);
         if let Some(mem_pool) = mem_pool.as_ref().to_owned() {
             let submitter = RequestSubmitter {
                 mem_pool: Arc::clone(mem_pool),
                 submit_rx,
-                queue: FeeQueue::new(),
+                priority_submit_rx,
+                queues: SubmitQueues {
+                    normal: FeeQueue::with_policy(ordering_policy(mem_pool_config.tx_ordering)),
+                    priority: FeeQueue::with_policy(ordering_policy(mem_pool_config.tx_ordering)),
+                    priority_lane: PriorityLane::new(&mem_pool_config.priority_lane),
+                    deferred: Vec::new(),
+                },
                 queue_order: QueueOrder(0),
+                last_mem_block_number: 0,
                 fee_config: fee_config.clone(),
                 generator: generator.clone(),
                 mem_pool_state: mem_pool_state.clone(),
@@ -270,6 +465,8 @@ impl Registry {
                 polyjuice_sender_recover: Arc::clone(&polyjuice_sender_recover),
                 mem_pool_config: mem_pool_config.clone(),
                 gasless_tx_support_config: gasless_tx_support_config.clone(),
+                dropped_requests: dropped_requests.clone(),
+                packaging_records: packaging_records.clone(),
             };
             tokio::spawn(submitter.in_background());
         }
@@ -278,11 +475,8 @@ impl Registry {
             .as_ref()
             .map(|send_tx_rate_limit| Mutex::new(lru::LruCache::new(send_tx_rate_limit.lru_size)));
 
-        let debug_generator = match debug_backend_forks {
-            Some(config) => {
-                let backend_manage = BackendManage::from_config(config)?;
-                Arc::new(generator.clone_with_new_backends(backend_manage))
-            }
+        let debug_generator = match debug_generator {
+            Some(debug_generator) => debug_generator,
             None => {
                 log::warn!("Enable debug RPC without setting the 'debug_backend_switches' option. Fallback to non-debugging version backends, the debug log may not work");
                 generator.clone()
@@ -304,7 +498,8 @@ impl Registry {
                 .collect(),
         };
 
-        Ok(Self {
+        let priority_lane = PriorityLane::new(&mem_pool_config.priority_lane);
+        let registry: Arc<Self> = Self {
             mem_pool,
             store,
             generator,
@@ -314,6 +509,8 @@ impl Registry {
             backend_info,
             node_mode,
             submit_tx,
+            priority_submit_tx,
+            priority_lane,
             rpc_client,
             send_tx_rate_limit,
             send_tx_rate_limit_config,
@@ -324,11 +521,129 @@ impl Registry {
             system_type_script_config,
             mem_pool_state,
             in_queue_request_map,
+            dropped_requests,
+            packaging_records,
+            execution_cache,
             polyjuice_sender_recover,
             debug_generator,
             system_type_scripts,
+            p2p_network_status,
+            account_throttle,
+            denylist,
+            withdrawal_limits,
+            maintenance_mode,
+            production_control,
+            backup_dir,
+            config_path,
+            tx_relay,
+            mem_pool_paused: AtomicBool::new(false),
+            finalized_block_smt_lock: Mutex::new(()),
+        }
+        .into();
+        if let Some(relayed_txs) = relayed_txs {
+            tokio::spawn(submit_relayed_txs(registry.clone(), relayed_txs));
+        }
+        Ok(registry)
+    }
+
+    fn admin_maintenance_mode(&self) -> Result<&MaintenanceMode> {
+        if !self
+            .server_config
+            .enable_methods
+            .contains(&RPCMethods::Admin)
+        {
+            return Err(method_not_found());
+        }
+        self.maintenance_mode
+            .as_deref()
+            .ok_or_else(|| rpc_error(ErrorCode::InvalidRequest, "block producer is not running"))
+    }
+
+    fn admin_production_control(&self) -> Result<&ProductionControl> {
+        if !self
+            .server_config
+            .enable_methods
+            .contains(&RPCMethods::Admin)
+        {
+            return Err(method_not_found());
+        }
+        self.production_control
+            .as_deref()
+            .ok_or_else(|| rpc_error(ErrorCode::InvalidRequest, "block producer is not running"))
+    }
+
+    fn admin_denylist(&self) -> Result<&Denylist> {
+        if !self
+            .server_config
+            .enable_methods
+            .contains(&RPCMethods::Admin)
+        {
+            return Err(method_not_found());
+        }
+        self.denylist
+            .as_deref()
+            .ok_or_else(|| rpc_error(ErrorCode::InvalidRequest, "mem pool is not running"))
+    }
+
+    fn admin_withdrawal_limits(&self) -> Result<&WithdrawalLimits> {
+        if !self
+            .server_config
+            .enable_methods
+            .contains(&RPCMethods::Admin)
+        {
+            return Err(method_not_found());
+        }
+        self.withdrawal_limits
+            .as_deref()
+            .ok_or_else(|| rpc_error(ErrorCode::InvalidRequest, "mem pool is not running"))
+    }
+
+    fn admin_mem_pool_paused(&self) -> Result<&AtomicBool> {
+        if !self
+            .server_config
+            .enable_methods
+            .contains(&RPCMethods::Admin)
+        {
+            return Err(method_not_found());
+        }
+        if self.mem_pool.is_none() {
+            return Err(rpc_error(
+                ErrorCode::InvalidRequest,
+                "mem pool is not running",
+            ));
+        }
+        Ok(&self.mem_pool_paused)
+    }
+
+    /// Reject submissions of new txs and withdrawals while the mem pool is
+    /// paused. Queued requests are still packaged.
+    fn check_mem_pool_paused(&self) -> Result<()> {
+        if self.mem_pool_paused.load(Ordering::SeqCst) {
+            gw_metrics::mem_pool().paused_rejected_requests.inc();
+            return Err(rpc_error(MEM_POOL_PAUSED_ERR_CODE, "mem pool is paused"));
+        }
+        Ok(())
+    }
+
+    /// Txs of priority lane senders are submitted through their own channel.
+    fn is_priority_lane_sender(&self, sender_id: u32) -> Result<bool> {
+        if !self.priority_lane.is_enabled() || 0 == sender_id {
+            return Ok(false);
+        }
+        let state = self.mem_pool_state.load_state_db();
+        let script_hash = state.get_script_hash(sender_id)?;
+        Ok(self.priority_lane.contains(&script_hash))
+    }
+
+    /// Reject submissions of new txs and withdrawals in maintenance mode.
+    fn check_maintenance_fence(&self) -> Result<()> {
+        match self.maintenance_mode {
+            Some(ref mode) if mode.is_fenced() => Err(rpc_error(
+                MAINTENANCE_ERR_CODE,
+                "node is in maintenance mode",
+            )),
+            _ => Ok(()),
         }
-        .into())
     }
 
     pub fn to_handler(self: Arc<Self>) -> MetaIoHandler<Option<Session>> {
@@ -378,11 +693,69 @@ impl QueueOrder {
     }
 }
 
+/// Fee queues of request submitter. Txs from priority lane senders are kept
+/// in a separate queue and fetched before others.
+struct SubmitQueues {
+    normal: FeeQueue<RequestContext>,
+    priority: FeeQueue<RequestContext>,
+    priority_lane: PriorityLane,
+    /// Txs deferred by the soft cycles limit, they aren't fetched again until
+    /// the mem block moves to a new tip.
+    deferred: Vec<(FeeEntry, RequestContext)>,
+}
+
+impl SubmitQueues {
+    fn is_empty(&self) -> bool {
+        self.normal.is_empty() && self.priority.is_empty()
+    }
+
+    fn add(&mut self, entry: FeeEntry, ctx: RequestContext, state: &impl State) {
+        if self.is_priority_lane_entry(&entry, state) {
+            self.priority.add(entry, ctx);
+        } else {
+            self.normal.add(entry, ctx);
+        }
+    }
+
+    /// Queue txs deferred by the soft cycles limit again for the new mem block.
+    fn requeue_deferred(&mut self, state: &impl State) {
+        for (entry, ctx) in std::mem::take(&mut self.deferred) {
+            self.add(entry, ctx, state);
+        }
+    }
+
+    /// Drop queued entries invalidated by the new tip.
+    fn remove_stale(&mut self, state: &impl State) {
+        for queue in [&mut self.normal, &mut self.priority] {
+            match queue.remove_stale(state) {
+                Ok(0) => {}
+                Ok(count) => log::debug!("[mem-pool] drop {} stale queued requests", count),
+                Err(err) => log::error!("remove stale queued requests error: {}", err),
+            }
+        }
+    }
+
+    fn is_priority_lane_entry(&self, entry: &FeeEntry, state: &impl State) -> bool {
+        if !self.priority_lane.is_enabled() {
+            return false;
+        }
+        match (&entry.item, &entry.sender) {
+            (FeeItem::Tx(_), FeeItemSender::AccountId(id)) => state
+                .get_script_hash(*id)
+                .map(|h| self.priority_lane.contains(&h))
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+}
+
 struct RequestSubmitter {
     mem_pool: Arc<Mutex<gw_mem_pool::pool::MemPool>>,
     submit_rx: mpsc::Receiver<(Request, RequestContext)>,
-    queue: FeeQueue<RequestContext>,
+    priority_submit_rx: mpsc::Receiver<(Request, RequestContext)>,
+    queues: SubmitQueues,
     queue_order: QueueOrder,
+    last_mem_block_number: u64,
     fee_config: FeeConfig,
     generator: Arc<Generator>,
     mem_pool_state: Arc<MemPoolState>,
@@ -390,6 +763,28 @@ struct RequestSubmitter {
     polyjuice_sender_recover: Arc<PolyjuiceSenderRecover>,
     mem_pool_config: MemPoolConfig,
     gasless_tx_support_config: Option<GaslessTxSupportConfig>,
+    dropped_requests: Option<Arc<DroppedRequests>>,
+    packaging_records: Option<Arc<PackagingRecords>>,
+}
+
+fn record_packaging(
+    packaging_records: Option<&PackagingRecords>,
+    block_number: u64,
+    entry: &FeeEntry,
+    ctx: &RequestContext,
+    decision: PackagingDecision,
+) {
+    if let Some(packaging_records) = packaging_records {
+        let hash = ctx.request_hash();
+        let candidate = PackagingCandidate::new(entry, hash, ctx.received_at, decision);
+        packaging_records.record(block_number, candidate);
+    }
+}
+
+fn record_dropped(dropped_requests: Option<&DroppedRequests>, hash: H256, reason: String) {
+    if let Some(dropped_requests) = dropped_requests {
+        dropped_requests.insert(hash, reason);
+    }
 }
 
 #[instrument(skip_all, fields(req_kind = req.kind()))]
@@ -433,11 +828,114 @@ fn req_to_entry(
     }
 }
 
+/// Run stateless verification (size limit, intrinsic gas, signature) of fetched
+/// txs in parallel, so it doesn't have to be done while holding the mem pool lock.
+///
+/// Returns entries that passed, with the pre-verified tx if the entry was
+/// pre-verified. Txs from id zero and withdrawals are passed through as is.
+#[instrument(skip_all, fields(count = items.len()))]
+async fn pre_verify_items(
+    generator: &Arc<Generator>,
+    mem_pool_state: &Arc<MemPoolState>,
+    dropped_requests: Option<&DroppedRequests>,
+    packaging_records: Option<&PackagingRecords>,
+    items: Vec<(FeeEntry, RequestContext)>,
+) -> Vec<(FeeEntry, RequestContext, Option<PreVerifiedTransaction>)> {
+    let block_number: u64 = mem_pool_state
+        .get_mem_pool_block_info()
+        .map(|b| b.number().unpack())
+        .unwrap_or_default();
+
+    let tasks = items.into_iter().map(|(entry, ctx)| {
+        let generator = Arc::clone(generator);
+        let mem_pool_state = Arc::clone(mem_pool_state);
+        tokio::task::spawn_blocking(move || {
+            let result = match entry.item {
+                FeeItem::Tx(ref tx) if matches!(entry.sender, FeeItemSender::AccountId(_)) => {
+                    let state = mem_pool_state.load_state_db();
+                    Some(pre_verify_transaction(
+                        &generator,
+                        &state,
+                        tx.clone(),
+                        block_number,
+                    ))
+                }
+                _ => None,
+            };
+            (entry, ctx, result)
+        })
+    });
+
+    let mut verified = Vec::new();
+    for joined in futures::future::join_all(tasks).await {
+        match joined {
+            Ok((entry, ctx, None)) => verified.push((entry, ctx, None)),
+            Ok((entry, ctx, Some(Ok(tx)))) => verified.push((entry, ctx, Some(tx))),
+            Ok((entry, ctx, Some(Err(err)))) => {
+                let hash: Byte32 = entry.item.hash().pack();
+                log::info!("pre-verify {:?} {} failed {}", entry.item.kind(), hash, err);
+                record_dropped(dropped_requests, ctx.request_hash(), err.to_string());
+                let decision = PackagingDecision::Dropped(err.to_string());
+                record_packaging(packaging_records, block_number, &entry, &ctx, decision);
+            }
+            Err(err) => log::error!("pre-verify task failed {}", err),
+        }
+    }
+    verified
+}
+
 impl RequestSubmitter {
     const MAX_CHANNEL_SIZE: usize = 10000;
     const MAX_BATCH_SIZE: usize = 20;
     const INTERVAL_MS: Duration = Duration::from_millis(100);
 
+    fn enqueue(&mut self, req: Request, mut ctx: RequestContext, state: &(impl State + CodeStore)) {
+        gw_telemetry::with_span_ref(&ctx.in_queue_span, |span| span.end());
+        ctx.in_queue_span = ctx.trace.new_span(tracing::info_span!("fee_queue.add"));
+        let _entered = ctx.in_queue_span.clone().entered();
+
+        let kind = req.kind();
+        let hash = req.hash();
+        match req_to_entry(
+            &self.fee_config,
+            self.gasless_tx_support_config.as_ref(),
+            self.generator.clone(),
+            req,
+            state,
+            self.queue_order.next(&self.queues.normal),
+        ) {
+            Ok(entry) => {
+                if entry.cycles_limit > self.mem_pool_config.mem_block.max_cycles_limit {
+                    log::info!(
+                        "req kind {} hash {} exceeded mem block max cycles limit, drop it",
+                        kind,
+                        hash,
+                    );
+                    record_dropped(
+                        self.dropped_requests.as_deref(),
+                        ctx.request_hash(),
+                        "exceeded mem block max cycles limit".to_string(),
+                    );
+                } else {
+                    self.queues.add(entry, ctx, state);
+                }
+            }
+            Err(err) => {
+                log::error!(
+                    "Failed to convert req to entry kind: {}, hash: {}, err: {}",
+                    kind,
+                    hash,
+                    err
+                );
+                record_dropped(
+                    self.dropped_requests.as_deref(),
+                    ctx.request_hash(),
+                    err.to_string(),
+                );
+            }
+        }
+    }
+
     async fn in_background(mut self) {
         // First mem pool reinject txs
         {
@@ -456,7 +954,7 @@ impl RequestSubmitter {
             while let Some(hash) = mem_pool.pending_restored_tx_hashes().pop_front() {
                 match db.get_mem_pool_transaction(&hash) {
                     Ok(Some(tx)) => {
-                        if let Err(err) = mem_pool.push_transaction(tx) {
+                        if let Err(err) = mem_pool.reinject_transaction(tx) {
                             log::error!("reinject mem block tx {} failed {}", hash.pack(), err);
                         }
                     }
@@ -476,7 +974,7 @@ impl RequestSubmitter {
 
         loop {
             // check mem block empty slots
-            loop {
+            let (fetch_normal, priority_slots) = loop {
                 log::debug!("[Mem-pool background job] check mem-pool acquire mem_pool",);
                 let t = Instant::now();
                 let mem_pool = self.mem_pool.lock().await;
@@ -485,136 +983,162 @@ impl RequestSubmitter {
                     t.elapsed().as_millis()
                 );
                 // continue to batch process if we have enough mem block slots
-                if !mem_pool.is_mem_txs_full(Self::MAX_BATCH_SIZE)
-                    && mem_pool.cycles_pool().available_cycles()
-                        >= self.fee_config.minimal_tx_cycles_limit()
-                {
-                    break;
+                let minimal_cycles = self.fee_config.minimal_tx_cycles_limit();
+                let has_cycles = mem_pool.cycles_pool().available_cycles() >= minimal_cycles;
+                let fetch_normal = mem_pool.normal_available_cycles() >= minimal_cycles
+                    && !mem_pool.is_mem_txs_full(Self::MAX_BATCH_SIZE);
+                let priority_slots = if has_cycles {
+                    mem_pool.priority_lane_slots()
+                } else {
+                    0
+                };
+                if fetch_normal || (priority_slots > 0 && !self.queues.priority.is_empty()) {
+                    break (fetch_normal, priority_slots);
                 }
                 drop(mem_pool);
+
+                // priority lane txs must not wait in the channel while the
+                // mem block is full of normal txs, normal txs stay in their
+                // channel for backpressure
+                if self.queues.priority_lane.is_enabled() {
+                    let state = self.mem_pool_state.load_state_db();
+                    while let Ok((req, ctx)) = self.priority_submit_rx.try_recv() {
+                        self.enqueue(req, ctx, &state);
+                    }
+                }
+
                 // sleep and try again
                 tokio::time::sleep(Self::INTERVAL_MS).await;
-            }
-
-            // mem-pool can process more txs
-            let queue = &mut self.queue;
-            let queue_order = &mut self.queue_order;
+            };
 
             // wait next tx if queue is empty
-            if queue.is_empty() {
-                // blocking current task until we receive a tx
-                let (req, mut ctx) = match self.submit_rx.recv().await {
-                    Some(req) => req,
-                    None => {
-                        log::error!("rpc submit tx is closed");
-                        return;
+            if self.queues.is_empty() {
+                // blocking current task until we receive a tx, or until the
+                // mem block may have moved on if there are deferred txs
+                let has_deferred = !self.queues.deferred.is_empty();
+                let submit_rx = &mut self.submit_rx;
+                let priority_submit_rx = &mut self.priority_submit_rx;
+                let recv = async move {
+                    tokio::select! {
+                        received = priority_submit_rx.recv() => received,
+                        received = submit_rx.recv() => received,
                     }
                 };
-
-                gw_telemetry::with_span_ref(&ctx.in_queue_span, |span| span.end());
-                ctx.in_queue_span = ctx.trace.new_span(tracing::info_span!("fee_queue.add"));
-                let _entered = ctx.in_queue_span.clone().entered();
-
-                let state = self.mem_pool_state.load_state_db();
-
-                let kind = req.kind();
-                let hash = req.hash();
-                match req_to_entry(
-                    &self.fee_config,
-                    self.gasless_tx_support_config.as_ref(),
-                    self.generator.clone(),
-                    req,
-                    &state,
-                    queue_order.next(queue),
-                ) {
-                    Ok(entry) => {
-                        if entry.cycles_limit > self.mem_pool_config.mem_block.max_cycles_limit {
-                            log::info!(
-                                "req kind {} hash {} exceeded mem block max cycles limit, drop it",
-                                kind,
-                                hash,
-                            );
-                        } else {
-                            queue.add(entry, ctx);
-                        }
+                let received = if has_deferred {
+                    tokio::time::timeout(Self::INTERVAL_MS, recv).await.ok()
+                } else {
+                    Some(recv.await)
+                };
+                match received {
+                    Some(Some((req, ctx))) => {
+                        let state = self.mem_pool_state.load_state_db();
+                        self.enqueue(req, ctx, &state);
                     }
-                    Err(err) => {
-                        log::error!(
-                            "Failed to convert req to entry kind: {}, hash: {}, err: {}",
-                            kind,
-                            hash,
-                            err
-                        );
+                    Some(None) => {
+                        log::error!("rpc submit tx is closed");
+                        return;
                     }
+                    None => {}
                 }
             }
 
             // push txs to fee priority queue
             let state = self.mem_pool_state.load_state_db();
-            while let Ok((req, mut ctx)) = self.submit_rx.try_recv() {
-                gw_telemetry::with_span_ref(&ctx.in_queue_span, |span| span.end());
-                ctx.in_queue_span = ctx.trace.new_span(tracing::info_span!("fee_queue.add"));
-                let _entered = ctx.in_queue_span.clone().entered();
-
-                let kind = req.kind();
-                let hash = req.hash();
-                match req_to_entry(
-                    &self.fee_config,
-                    self.gasless_tx_support_config.as_ref(),
-                    self.generator.clone(),
-                    req,
-                    &state,
-                    queue_order.next(queue),
-                ) {
-                    Ok(entry) => {
-                        if entry.cycles_limit > self.mem_pool_config.mem_block.max_cycles_limit {
-                            log::info!(
-                                "req kind {} hash {} exceeded mem block max cycles limit, drop it",
-                                kind,
-                                hash,
-                            );
-                        } else {
-                            queue.add(entry, ctx);
-                        }
-                    }
-                    Err(err) => {
-                        log::error!(
-                            "Failed to convert req to entry kind: {}, hash: {}, err: {}",
-                            kind,
-                            hash,
-                            err
-                        );
-                    }
+            while let Ok((req, ctx)) = self.priority_submit_rx.try_recv() {
+                self.enqueue(req, ctx, &state);
+            }
+            while let Ok((req, ctx)) = self.submit_rx.try_recv() {
+                self.enqueue(req, ctx, &state);
+            }
+
+            // re-validate queued requests once the mem block moved to a new tip
+            let mem_block_number = self
+                .mem_pool_state
+                .load_shared()
+                .mem_block
+                .as_ref()
+                .map_or(0, |b| b.number().unpack());
+            if mem_block_number != self.last_mem_block_number {
+                if let Some(ref packaging_records) = self.packaging_records {
+                    let queues = [&self.queues.priority, &self.queues.normal];
+                    let not_fetched = queues.into_iter().flat_map(|queue| queue.iter());
+                    let candidates = not_fetched.map(|(entry, ctx)| {
+                        let decision = PackagingDecision::NotFetched;
+                        PackagingCandidate::new(
+                            entry,
+                            ctx.request_hash(),
+                            ctx.received_at,
+                            decision,
+                        )
+                    });
+                    packaging_records.record_not_fetched(self.last_mem_block_number, candidates);
                 }
+                self.last_mem_block_number = mem_block_number;
+                self.queues.requeue_deferred(&state);
+                self.queues.remove_stale(&state);
             }
 
-            // fetch items from PQ
-            let items = match queue.fetch(&state, Self::MAX_BATCH_SIZE) {
+            // mem-pool can process more txs
+            let queues = &mut self.queues;
+
+            // fetch items from PQ, priority lane first
+            let priority_count = if fetch_normal {
+                Self::MAX_BATCH_SIZE
+            } else {
+                priority_slots.min(Self::MAX_BATCH_SIZE)
+            };
+            let mut items = match queues.priority.fetch(&state, priority_count) {
                 Ok(items) => items,
                 Err(err) => {
                     log::error!(
-                        "Fetch items({}) from queue({}) error: {}",
-                        Self::MAX_BATCH_SIZE,
-                        queue.len(),
+                        "Fetch items({}) from priority queue({}) error: {}",
+                        priority_count,
+                        queues.priority.len(),
                         err
                     );
                     continue;
                 }
             };
+            if fetch_normal && items.len() < Self::MAX_BATCH_SIZE {
+                let count = Self::MAX_BATCH_SIZE - items.len();
+                match queues.normal.fetch(&state, count) {
+                    Ok(normal_items) => items.extend(normal_items),
+                    Err(err) => {
+                        log::error!(
+                            "Fetch items({}) from queue({}) error: {}",
+                            count,
+                            queues.normal.len(),
+                            err
+                        );
+                        continue;
+                    }
+                }
+            }
 
             if !items.is_empty() {
+                // stateless verification without holding the mem pool lock
+                let items = pre_verify_items(
+                    &self.generator,
+                    &self.mem_pool_state,
+                    self.dropped_requests.as_deref(),
+                    self.packaging_records.as_deref(),
+                    items,
+                )
+                .await;
+
                 // recover accounts for polyjuice tx from id zero
                 let eth_recover = &self.polyjuice_sender_recover.eth;
-                let txs_from_zero = items
-                    .iter()
-                    .filter_map(|(entry, _handle)| match entry.item {
-                        FeeItem::Tx(ref tx)
-                            if matches!(entry.sender, FeeItemSender::PendingCreate(_)) =>
-                        {
-                            Some(tx)
-                        }
-                        _ => None,
-                    });
+                let txs_from_zero =
+                    items
+                        .iter()
+                        .filter_map(|(entry, _handle, _)| match entry.item {
+                            FeeItem::Tx(ref tx)
+                                if matches!(entry.sender, FeeItemSender::PendingCreate(_)) =>
+                            {
+                                Some(tx)
+                            }
+                            _ => None,
+                        });
                 let recovered_senders = eth_recover.recover_sender_accounts(txs_from_zero, &state);
 
                 log::debug!("[Mem-pool background job] acquire mem_pool",);
@@ -624,6 +1148,8 @@ impl RequestSubmitter {
                     "[Mem-pool background job] unlock mem_pool {}ms",
                     t.elapsed().as_millis()
                 );
+                let packaging_block_number: u64 =
+                    mem_pool.mem_block().block_info().number().unpack();
 
                 if let Err(err) = match recovered_senders.build_create_tx(eth_recover, &state) {
                     Ok(Some(create_accounts_tx)) => mem_pool.push_transaction(create_accounts_tx),
@@ -635,8 +1161,15 @@ impl RequestSubmitter {
                     {
                         log::info!("[tx from zero] mem block cycles limit reached, retry later");
 
-                        for (entry, handle) in items {
-                            queue.add(entry, handle);
+                        for (entry, handle, _) in items {
+                            record_packaging(
+                                self.packaging_records.as_deref(),
+                                packaging_block_number,
+                                &entry,
+                                &handle,
+                                PackagingDecision::Deferred(err.to_string()),
+                            );
+                            queues.add(entry, handle, &state);
                         }
                         continue;
                     }
@@ -647,15 +1180,21 @@ impl RequestSubmitter {
                 let state = self.mem_pool_state.load_state_db();
                 let mut block_cycles_limit_reached = false;
 
-                for (entry, ctx) in items {
+                for (entry, mut ctx, pre_verified) in items {
                     gw_telemetry::with_span_ref(&ctx.in_queue_span, |span| span.end());
                     let push_span = ctx.new_span(|_| tracing::info_span!("mem_pool.push"));
                     let _entered = push_span.enter();
 
                     if let FeeItemKind::Tx = entry.item.kind() {
-                        if !block_cycles_limit_reached
-                            && entry.cycles_limit > mem_pool.cycles_pool().available_cycles()
-                        {
+                        // Priority lane txs may use the reserved cycles and
+                        // aren't deferred by the soft cycles limit.
+                        let is_priority_lane = queues.is_priority_lane_entry(&entry, &state);
+                        let available_cycles = if is_priority_lane {
+                            mem_pool.cycles_pool().available_cycles()
+                        } else {
+                            mem_pool.normal_available_cycles()
+                        };
+                        if !block_cycles_limit_reached && entry.cycles_limit > available_cycles {
                             let hash: Byte32 = entry.item.hash().pack();
                             log::info!("mem block cycles limit reached for tx {}", hash);
 
@@ -663,11 +1202,63 @@ impl RequestSubmitter {
                         }
 
                         if block_cycles_limit_reached {
-                            queue.add(entry, ctx);
+                            record_packaging(
+                                self.packaging_records.as_deref(),
+                                packaging_block_number,
+                                &entry,
+                                &ctx,
+                                PackagingDecision::Deferred(
+                                    "mem block cycles limit reached".to_string(),
+                                ),
+                            );
+                            queues.add(entry, ctx, &state);
+                            continue;
+                        }
+
+                        // Defer heavy txs instead of executing and rolling them
+                        // back late. Lighter txs can still fill the budget. Txs
+                        // are never deferred in an empty mem block, so a tx
+                        // above the soft cycles limit is still packaged.
+                        let soft_available_cycles = mem_pool
+                            .soft_available_cycles()
+                            .filter(|_| mem_pool.cycles_pool().cycles_used() > 0);
+                        if let Some(soft_available_cycles) = soft_available_cycles
+                            .filter(|cycles| !is_priority_lane && entry.cycles_limit > *cycles)
+                        {
+                            let hash: Byte32 = entry.item.hash().pack();
+                            log::debug!(
+                                "defer tx {} cycles limit {} soft available cycles {}",
+                                hash,
+                                entry.cycles_limit,
+                                soft_available_cycles
+                            );
+                            if !ctx.cycles_deferred {
+                                ctx.cycles_deferred = true;
+                                gw_metrics::mem_pool().cycles_deferred_txs.inc();
+                            }
+
+                            if soft_available_cycles == 0 {
+                                log::info!("mem block soft cycles limit reached");
+                                block_cycles_limit_reached = true;
+                            }
+                            record_packaging(
+                                self.packaging_records.as_deref(),
+                                packaging_block_number,
+                                &entry,
+                                &ctx,
+                                PackagingDecision::Deferred(
+                                    "mem block soft cycles limit reached".to_string(),
+                                ),
+                            );
+                            queues.deferred.push((entry, ctx));
                             continue;
                         }
                     }
 
+                    let tx_origin = ctx.origin.clone().map(|origin| TxOrigin {
+                        origin,
+                        fee: entry.fee,
+                    });
                     let maybe_ok = match entry.item.clone() {
                         FeeItem::Tx(tx)
                             if matches!(entry.sender, FeeItemSender::PendingCreate(_)) =>
@@ -677,6 +1268,13 @@ impl RequestSubmitter {
                                 Ok(id) => id,
                                 Err(err) => {
                                     log::info!("[from tx zero] {:x} {}", tx.hash().pack(), err);
+                                    record_packaging(
+                                        self.packaging_records.as_deref(),
+                                        packaging_block_number,
+                                        &entry,
+                                        &ctx,
+                                        PackagingDecision::Dropped(err.to_string()),
+                                    );
                                     continue;
                                 }
                             };
@@ -691,9 +1289,12 @@ impl RequestSubmitter {
                                 tx.hash().pack()
                             );
 
-                            mem_pool.push_transaction(tx)
+                            mem_pool.push_transaction_with_origin(tx, tx_origin)
                         }
-                        FeeItem::Tx(tx) => mem_pool.push_transaction(tx),
+                        FeeItem::Tx(tx) => match pre_verified {
+                            Some(tx) => mem_pool.push_pre_verified_transaction(tx, tx_origin),
+                            None => mem_pool.push_transaction_with_origin(tx, tx_origin),
+                        },
                         FeeItem::Withdrawal(withdrawal) => {
                             mem_pool.push_withdrawal_request(withdrawal).await
                         }
@@ -708,13 +1309,41 @@ impl RequestSubmitter {
                             log::info!("mem block cycles limit reached for tx {}", hash);
 
                             block_cycles_limit_reached = true;
-                            queue.add(entry, ctx);
+                            record_packaging(
+                                self.packaging_records.as_deref(),
+                                packaging_block_number,
+                                &entry,
+                                &ctx,
+                                PackagingDecision::Deferred(err.to_string()),
+                            );
+                            queues.add(entry, ctx, &state);
 
                             continue;
                         }
 
                         log::info!("push {:?} {} failed {}", entry.item.kind(), hash, err);
+                        record_dropped(
+                            self.dropped_requests.as_deref(),
+                            ctx.request_hash(),
+                            err.to_string(),
+                        );
+                        record_packaging(
+                            self.packaging_records.as_deref(),
+                            packaging_block_number,
+                            &entry,
+                            &ctx,
+                            PackagingDecision::Dropped(err.to_string()),
+                        );
+                        continue;
                     }
+
+                    record_packaging(
+                        self.packaging_records.as_deref(),
+                        packaging_block_number,
+                        &entry,
+                        &ctx,
+                        PackagingDecision::Packaged,
+                    );
                 }
 
                 if block_cycles_limit_reached {
@@ -737,6 +1366,8 @@ pub trait GwRpc {
     ) -> Result<Option<L2TransactionWithStatus>>;
     async fn gw_get_pending_tx_hashes(&self) -> Result<Vec<JsonH256>>;
     async fn gw_is_request_in_queue(&self, hash: JsonH256) -> Result<bool>;
+    async fn gw_get_dropped_request(&self, hash: JsonH256) -> Result<Option<DroppedRequest>>;
+    async fn gw_get_request_status(&self, hash: JsonH256) -> Result<RequestStatus>;
     async fn gw_get_block_committed_info(
         &self,
         block_hash: JsonH256,
@@ -751,7 +1382,11 @@ pub trait GwRpc {
     async fn gw_get_block_hash(&self, block_number: Uint64) -> Result<Option<JsonH256>>;
     async fn gw_get_tip_block_hash(&self) -> Result<JsonH256>;
     async fn gw_get_transaction_receipt(&self, tx_hash: JsonH256) -> Result<Option<TxReceipt>>;
-    async fn gw_execute_l2transaction(&self, l2tx: L2TransactionJsonBytes) -> Result<RunResult>;
+    async fn gw_execute_l2transaction(
+        &self,
+        l2tx: L2TransactionJsonBytes,
+        block_number: Option<Uint64>,
+    ) -> Result<RunResult>;
     async fn gw_execute_raw_l2transaction(
         &self,
         tx: RawL2TransactionJsonBytes,
@@ -761,6 +1396,7 @@ pub trait GwRpc {
     async fn gw_submit_l2transaction(
         &self,
         l2tx: L2TransactionJsonBytes,
+        origin_token: Option<String>,
     ) -> Result<Option<JsonH256>>;
     async fn gw_submit_withdrawal_request(
         &self,
@@ -817,6 +1453,32 @@ pub trait GwRpc {
     async fn gw_get_fee_config(&self) -> Result<gw_jsonrpc_types::godwoken::FeeConfig>;
     async fn gw_get_mem_pool_state_root(&self) -> Result<JsonH256>;
     async fn gw_get_mem_pool_state_ready(&self) -> Result<bool>;
+    async fn gw_mem_pool_stats(&self) -> Result<MemPoolStats>;
+    async fn gw_get_account_queue(
+        &self,
+        address: RegistryAddressJsonBytes,
+    ) -> Result<Option<AccountQueue>>;
+    async fn gw_get_state_root_proof(&self, block_hash: JsonH256)
+        -> Result<Option<StateRootProof>>;
+    async fn gw_get_state_proof(
+        &self,
+        block_hash: JsonH256,
+        keys: Vec<JsonH256>,
+    ) -> Result<Option<StateProof>>;
+    async fn gw_get_deposit_history(
+        &self,
+        address: RegistryAddressJsonBytes,
+        cursor: Option<JsonBytes>,
+        limit: Option<Uint32>,
+    ) -> Result<DepositHistory>;
+    async fn gw_get_logs(
+        &self,
+        from_block: Uint64,
+        to_block: Uint64,
+        address: Option<ckb_fixed_hash::H160>,
+        topic: Option<JsonH256>,
+        limit: Option<Uint32>,
+    ) -> Result<Vec<IndexedLog>>;
 
     async fn gw_start_profiler(&self) -> Result<()>;
     async fn gw_report_pprof(&self) -> Result<()>;
@@ -829,6 +1491,43 @@ pub trait GwRpc {
         tx_hash: JsonH256,
         max_cycles: Option<Uint64>,
     ) -> Result<Option<DebugRunResult>>;
+    async fn debug_explain_packaging(
+        &self,
+        block_number: Uint64,
+        hash: JsonH256,
+    ) -> Result<Option<PackagingExplanation>>;
+
+    async fn gw_enter_maintenance_mode(&self) -> Result<MaintenanceStatus>;
+    async fn gw_resume_from_maintenance_mode(&self) -> Result<MaintenanceStatus>;
+    async fn gw_get_maintenance_status(&self) -> Result<MaintenanceStatus>;
+    async fn gw_pause_block_production(&self) -> Result<BlockProductionStatus>;
+    async fn gw_resume_block_production(&self) -> Result<BlockProductionStatus>;
+    async fn gw_pause_mem_pool(&self) -> Result<bool>;
+    async fn gw_resume_mem_pool(&self) -> Result<bool>;
+    async fn gw_get_denylist(&self) -> Result<Vec<DenylistEntry>>;
+    async fn gw_add_to_denylist(&self, entry: DenylistEntry) -> Result<bool>;
+    async fn gw_remove_from_denylist(&self, entry: DenylistEntry) -> Result<bool>;
+    async fn gw_get_withdrawal_limits(&self) -> Result<Vec<WithdrawalLimit>>;
+    async fn gw_set_withdrawal_limit(
+        &self,
+        sudt_script_hash: Option<JsonH256>,
+        limit: Option<Uint128>,
+    ) -> Result<()>;
+    async fn gw_get_fee_rebates(
+        &self,
+        from_block: Uint64,
+        to_block: Uint64,
+    ) -> Result<Vec<FeeRebate>>;
+    async fn gw_create_backup(&self) -> Result<BackupInfo>;
+    async fn gw_reload_backend_forks(&self) -> Result<bool>;
+
+    // Ethereum compatible methods, for client libraries and probes
+    #[rpc(name = "net_peerCount")]
+    async fn net_peer_count(&self) -> Result<Uint64>;
+    #[rpc(name = "net_listening")]
+    async fn net_listening(&self) -> Result<bool>;
+    #[rpc(name = "web3_sha3")]
+    async fn web3_sha3(&self, data: JsonBytes) -> Result<JsonH256>;
 }
 
 #[async_trait]
@@ -861,6 +1560,23 @@ impl GwRpc for Arc<Registry> {
             .as_deref()
             .map_or(false, |m| m.contains(&hash)))
     }
+    #[instrument(skip_all)]
+    async fn gw_get_dropped_request(&self, hash: JsonH256) -> Result<Option<DroppedRequest>> {
+        let dropped_requests = match self.dropped_requests {
+            Some(ref dropped_requests) => dropped_requests,
+            None => return Err(method_not_found()),
+        };
+        let dropped = dropped_requests.get(&to_h256(hash.clone()));
+        Ok(dropped.map(|dropped| DroppedRequest {
+            hash,
+            reason: dropped.reason,
+            dropped_at: dropped.dropped_at.into(),
+        }))
+    }
+    #[instrument(skip_all)]
+    async fn gw_get_request_status(&self, hash: JsonH256) -> Result<RequestStatus> {
+        gw_get_request_status(self, hash).await
+    }
     async fn gw_get_block_committed_info(
         &self,
         block_hash: JsonH256,
@@ -908,8 +1624,12 @@ impl GwRpc for Arc<Registry> {
     async fn gw_get_transaction_receipt(&self, tx_hash: JsonH256) -> Result<Option<TxReceipt>> {
         gw_get_transaction_receipt(self, tx_hash).await
     }
-    async fn gw_execute_l2transaction(&self, l2tx: L2TransactionJsonBytes) -> Result<RunResult> {
-        gw_execute_l2transaction(self.clone(), l2tx).await
+    async fn gw_execute_l2transaction(
+        &self,
+        l2tx: L2TransactionJsonBytes,
+        block_number: Option<Uint64>,
+    ) -> Result<RunResult> {
+        gw_execute_l2transaction(self.clone(), l2tx, block_number).await
     }
     async fn gw_execute_raw_l2transaction(
         &self,
@@ -922,11 +1642,12 @@ impl GwRpc for Arc<Registry> {
     async fn gw_submit_l2transaction(
         &self,
         l2tx: L2TransactionJsonBytes,
+        origin_token: Option<String>,
     ) -> Result<Option<JsonH256>> {
-        if self.node_mode == NodeMode::ReadOnly {
+        if self.node_mode == NodeMode::ReadOnly && self.tx_relay.is_none() {
             return Err(method_not_found());
         }
-        gw_submit_l2transaction(self, l2tx).await
+        gw_submit_l2transaction(self, l2tx, origin_token).await
     }
     async fn gw_submit_withdrawal_request(
         &self,
@@ -1034,6 +1755,8 @@ impl GwRpc for Arc<Registry> {
             gw_scripts,
             eoa_scripts,
             gasless_tx_support: self.gasless_tx_support_config.clone(),
+            block_production: (self.production_control.as_deref())
+                .map(|c| to_block_production_status(c.state())),
         })
     }
     #[instrument(skip_all)]
@@ -1073,11 +1796,65 @@ impl GwRpc for Arc<Registry> {
     }
 
     #[instrument(skip_all)]
-    async fn gw_start_profiler(&self) -> Result<()> {
-        if !self
-            .server_config
-            .enable_methods
-            .contains(&RPCMethods::PProf)
+    async fn gw_mem_pool_stats(&self) -> Result<MemPoolStats> {
+        gw_mem_pool_stats(self).await
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_get_account_queue(
+        &self,
+        address: RegistryAddressJsonBytes,
+    ) -> Result<Option<AccountQueue>> {
+        gw_get_account_queue(self, address).await
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_get_state_root_proof(
+        &self,
+        block_hash: JsonH256,
+    ) -> Result<Option<StateRootProof>> {
+        gw_get_state_root_proof(self, block_hash).await
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_get_state_proof(
+        &self,
+        block_hash: JsonH256,
+        keys: Vec<JsonH256>,
+    ) -> Result<Option<StateProof>> {
+        gw_get_state_proof(self, block_hash, keys).await
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_get_deposit_history(
+        &self,
+        address: RegistryAddressJsonBytes,
+        cursor: Option<JsonBytes>,
+        limit: Option<Uint32>,
+    ) -> Result<DepositHistory> {
+        gw_get_deposit_history(self, address, cursor, limit).await
+    }
+
+    /// Logs of an address and/or a topic in blocks `from_block..=to_block`,
+    /// oldest first.
+    #[instrument(skip_all)]
+    async fn gw_get_logs(
+        &self,
+        from_block: Uint64,
+        to_block: Uint64,
+        address: Option<ckb_fixed_hash::H160>,
+        topic: Option<JsonH256>,
+        limit: Option<Uint32>,
+    ) -> Result<Vec<IndexedLog>> {
+        gw_get_logs(self, from_block, to_block, address, topic, limit).await
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_start_profiler(&self) -> Result<()> {
+        if !self
+            .server_config
+            .enable_methods
+            .contains(&RPCMethods::PProf)
         {
             return Err(method_not_found());
         }
@@ -1179,6 +1956,290 @@ impl GwRpc for Arc<Registry> {
 
         Ok(replay_transaction(self.clone(), tx_hash, max_cycles).await?)
     }
+
+    #[instrument(skip_all)]
+    async fn debug_explain_packaging(
+        &self,
+        block_number: Uint64,
+        hash: JsonH256,
+    ) -> Result<Option<PackagingExplanation>> {
+        let packaging_records = match self.packaging_records {
+            Some(ref packaging_records) => packaging_records,
+            None => return Err(method_not_found()),
+        };
+
+        let block_number: u64 = block_number.into();
+        let mut candidates = match packaging_records.get(block_number) {
+            Some(candidates) => candidates,
+            None => return Ok(None),
+        };
+        let hash = to_h256(hash);
+        let candidate = match candidates.iter().rposition(|c| c.hash == hash) {
+            Some(pos) => {
+                let candidate = candidates.remove(pos);
+                candidates.truncate(pos);
+                Some(to_json_packaging_candidate(candidate))
+            }
+            None => None,
+        };
+        Ok(Some(PackagingExplanation {
+            block_number: block_number.into(),
+            candidate,
+            considered_before: candidates
+                .into_iter()
+                .map(to_json_packaging_candidate)
+                .collect(),
+        }))
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_enter_maintenance_mode(&self) -> Result<MaintenanceStatus> {
+        let maintenance_mode = self.admin_maintenance_mode()?;
+        if maintenance_mode.enter() {
+            log::info!("[RPC] entering maintenance mode");
+        }
+        Ok(to_maintenance_status(maintenance_mode.state()))
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_resume_from_maintenance_mode(&self) -> Result<MaintenanceStatus> {
+        let maintenance_mode = self.admin_maintenance_mode()?;
+        if maintenance_mode.resume() {
+            log::info!("[RPC] resumed from maintenance mode");
+        }
+        Ok(to_maintenance_status(maintenance_mode.state()))
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_get_maintenance_status(&self) -> Result<MaintenanceStatus> {
+        let maintenance_mode = self.admin_maintenance_mode()?;
+        Ok(to_maintenance_status(maintenance_mode.state()))
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_pause_block_production(&self) -> Result<BlockProductionStatus> {
+        let production_control = self.admin_production_control()?;
+        if production_control.pause() {
+            log::info!("[RPC] pausing block production");
+        }
+        Ok(to_block_production_status(production_control.state()))
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_resume_block_production(&self) -> Result<BlockProductionStatus> {
+        let production_control = self.admin_production_control()?;
+        if production_control.resume() {
+            log::info!("[RPC] resumed block production");
+        }
+        Ok(to_block_production_status(production_control.state()))
+    }
+
+    /// Stop accepting new txs and withdrawals. Returns false if it's already paused.
+    #[instrument(skip_all)]
+    async fn gw_pause_mem_pool(&self) -> Result<bool> {
+        let paused = self.admin_mem_pool_paused()?;
+        let changed = !paused.swap(true, Ordering::SeqCst);
+        if changed {
+            log::info!("[RPC] mem pool paused");
+            gw_metrics::mem_pool().paused.set(1);
+        }
+        Ok(changed)
+    }
+
+    /// Accept new txs and withdrawals again. Returns false if it isn't paused.
+    #[instrument(skip_all)]
+    async fn gw_resume_mem_pool(&self) -> Result<bool> {
+        let paused = self.admin_mem_pool_paused()?;
+        let changed = paused.swap(false, Ordering::SeqCst);
+        if changed {
+            log::info!("[RPC] mem pool resumed");
+            gw_metrics::mem_pool().paused.set(0);
+        }
+        Ok(changed)
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_get_denylist(&self) -> Result<Vec<DenylistEntry>> {
+        let denylist = self.admin_denylist()?;
+        Ok(denylist
+            .entries()
+            .into_iter()
+            .map(to_json_denylist_entry)
+            .collect())
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_add_to_denylist(&self, entry: DenylistEntry) -> Result<bool> {
+        let denylist = self.admin_denylist()?;
+        let added = denylist.add(from_json_denylist_entry(entry.clone()))?;
+        if added {
+            log::info!("[RPC] add to denylist {:?}", entry);
+        }
+        Ok(added)
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_remove_from_denylist(&self, entry: DenylistEntry) -> Result<bool> {
+        let denylist = self.admin_denylist()?;
+        let removed = denylist.remove(&from_json_denylist_entry(entry.clone()))?;
+        if removed {
+            log::info!("[RPC] remove from denylist {:?}", entry);
+        }
+        Ok(removed)
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_get_withdrawal_limits(&self) -> Result<Vec<WithdrawalLimit>> {
+        let limits = self.admin_withdrawal_limits()?;
+        let window_secs = limits.window_secs();
+        Ok(limits
+            .status()
+            .into_iter()
+            .map(|s| WithdrawalLimit {
+                sudt_script_hash: match s.asset {
+                    WithdrawalAsset::Ckb => None,
+                    WithdrawalAsset::Sudt(hash) => Some(to_jsonh256(hash)),
+                },
+                limit: s.limit.map(Into::into),
+                withdrawn: s.withdrawn.into(),
+                window_secs: window_secs.into(),
+            })
+            .collect())
+    }
+
+    /// Override the withdrawal limit of CKB, or of a sUDT, until restart.
+    /// `limit` None lifts the limit.
+    #[instrument(skip_all)]
+    async fn gw_set_withdrawal_limit(
+        &self,
+        sudt_script_hash: Option<JsonH256>,
+        limit: Option<Uint128>,
+    ) -> Result<()> {
+        let limits = self.admin_withdrawal_limits()?;
+        let asset = match sudt_script_hash {
+            Some(hash) => WithdrawalAsset::Sudt(to_h256(hash)),
+            None => WithdrawalAsset::Ckb,
+        };
+        let limit = limit.map(|l| l.value());
+        limits.set_limit(asset, limit);
+        log::info!("[RPC] set withdrawal limit of {} to {:?}", asset, limit);
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn gw_get_fee_rebates(
+        &self,
+        from_block: Uint64,
+        to_block: Uint64,
+    ) -> Result<Vec<FeeRebate>> {
+        if !self
+            .server_config
+            .enable_methods
+            .contains(&RPCMethods::Admin)
+        {
+            return Err(method_not_found());
+        }
+        let (from_block, to_block): (u64, u64) = (from_block.into(), to_block.into());
+        if from_block > to_block {
+            return Err(rpc_error(
+                ErrorCode::InvalidParams,
+                "from_block is greater than to_block",
+            ));
+        }
+
+        let rebates = self
+            .store
+            .begin_transaction()
+            .get_fee_rebates(from_block, to_block)?;
+        Ok(rebates
+            .into_iter()
+            .map(|rebate| FeeRebate {
+                block_number: rebate.block_number.into(),
+                origin: String::from_utf8_lossy(&rebate.origin).into_owned(),
+                txs: rebate.txs.into(),
+                fee: rebate.fee.into(),
+            })
+            .collect())
+    }
+
+    /// Create an incremental backup of the store in `store.backup_dir`
+    /// while the node keeps running.
+    #[instrument(skip_all)]
+    async fn gw_create_backup(&self) -> Result<BackupInfo> {
+        if !self
+            .server_config
+            .enable_methods
+            .contains(&RPCMethods::Admin)
+        {
+            return Err(method_not_found());
+        }
+        let backup_dir = self
+            .backup_dir
+            .clone()
+            .ok_or_else(|| rpc_error(ErrorCode::InvalidRequest, "store.backup_dir is not set"))?;
+
+        let store = self.store.clone();
+        let info = tokio::task::spawn_blocking(move || store.create_backup(&backup_dir)).await??;
+        log::info!("[RPC] created backup {}", info.id);
+        Ok(to_json_backup_info(info))
+    }
+
+    /// Reload backend forks from the config file. Forks up to the mem block
+    /// can't be changed. Returns false if the forks are unchanged.
+    #[instrument(skip_all)]
+    async fn gw_reload_backend_forks(&self) -> Result<bool> {
+        if !self
+            .server_config
+            .enable_methods
+            .contains(&RPCMethods::Admin)
+        {
+            return Err(method_not_found());
+        }
+        let config_path = self
+            .config_path
+            .clone()
+            .ok_or_else(|| rpc_error(ErrorCode::InvalidRequest, "config file is unknown"))?;
+
+        let generator = self.generator.clone();
+        // Unless it falls back to the generator.
+        let debug_generator = Some(self.debug_generator.clone())
+            .filter(|debug_generator| !Arc::ptr_eq(debug_generator, &generator));
+        let store = self.store.clone();
+        let reloaded = tokio::task::spawn_blocking(move || -> anyhow::Result<bool> {
+            let tip_number = store.get_last_valid_tip_block()?.raw().number().unpack();
+            generator.backend_manage().reload_from_config_file(
+                &config_path,
+                tip_number + 1,
+                debug_generator.as_deref().map(Generator::backend_manage),
+            )
+        })
+        .await??;
+        log::info!("[RPC] reload backend forks, changed: {}", reloaded);
+        Ok(reloaded)
+    }
+
+    #[instrument(skip_all)]
+    async fn net_peer_count(&self) -> Result<Uint64> {
+        let count = self
+            .p2p_network_status
+            .as_deref()
+            .map_or(0, P2PNetworkStatus::peer_count);
+        Ok((count as u64).into())
+    }
+
+    #[instrument(skip_all)]
+    async fn net_listening(&self) -> Result<bool> {
+        Ok(self
+            .p2p_network_status
+            .as_deref()
+            .map_or(false, P2PNetworkStatus::is_listening))
+    }
+
+    #[instrument(skip_all)]
+    async fn web3_sha3(&self, data: JsonBytes) -> Result<JsonH256> {
+        let hash: [u8; 32] = Keccak256::digest(data.as_bytes()).into();
+        Ok(to_jsonh256(hash))
+    }
 }
 
 #[instrument(skip_all)]
@@ -1220,6 +2281,49 @@ async fn gw_get_transaction(
     }))
 }
 
+async fn gw_get_request_status(ctx: &Registry, hash: JsonH256) -> Result<RequestStatus> {
+    let h = to_h256(hash.clone());
+    let mut status = RequestStatus {
+        hash,
+        status: RequestStatusKind::Unknown,
+        block_number: None,
+        block_hash: None,
+        reason: None,
+        dropped_at: None,
+    };
+
+    if (ctx.in_queue_request_map.as_deref()).map_or(false, |m| m.contains(&h)) {
+        status.status = RequestStatusKind::Queued;
+        return Ok(status);
+    }
+
+    let db = ctx.store.get_snapshot();
+    let committed = match db.get_transaction_info(&h)? {
+        Some(info) => Some((info.block_number(), info.key().block_hash())),
+        None => {
+            (db.get_withdrawal_info(&h)?).map(|info| (info.block_number(), info.key().block_hash()))
+        }
+    };
+    if let Some((block_number, block_hash)) = committed {
+        status.status = RequestStatusKind::Committed;
+        status.block_number = Some(block_number.unpack().into());
+        status.block_hash = Some(to_jsonh256(block_hash.unpack()));
+        return Ok(status);
+    }
+
+    if db.get_mem_pool_transaction(&h)?.is_some() || db.get_mem_pool_withdrawal(&h)?.is_some() {
+        status.status = RequestStatusKind::Pending;
+        return Ok(status);
+    }
+
+    if let Some(dropped) = (ctx.dropped_requests.as_deref()).and_then(|d| d.get(&h)) {
+        status.status = RequestStatusKind::Dropped;
+        status.reason = Some(dropped.reason);
+        status.dropped_at = Some(dropped.dropped_at.into());
+    }
+    Ok(status)
+}
+
 #[instrument(skip_all)]
 async fn gw_get_block_committed_info(
     block_hash: JsonH256,
@@ -1390,29 +2494,69 @@ fn verify_sender_balance<S: State + CodeStore>(
     Ok(())
 }
 
+/// Cycles limit of executing a tx by RPCs at `block_number`.
+fn execute_max_cycles(ctx: &Registry, block_number: u64) -> u64 {
+    ctx.generator
+        .fork_config()
+        .max_execute_tx_cycles(block_number)
+        .unwrap_or(ctx.mem_pool_config.execute_l2tx_max_cycles)
+}
+
+/// Block info of the main chain block `block_number`, to execute txs on its
+/// post state.
+fn history_block_info(db: &StoreTransaction, block_number: u64) -> Result<BlockInfo> {
+    let block_hash = match db.get_block_hash_by_number(block_number)? {
+        Some(block_hash) => block_hash,
+        None => return Err(header_not_found_err()),
+    };
+    let raw_block = match db.get_block(&block_hash)? {
+        Some(block) => block.raw(),
+        None => return Err(header_not_found_err()),
+    };
+    // State history of blocks before the pruned one is removed.
+    if matches!(db.get_pruned_block_number(), Some(pruned) if block_number < pruned) {
+        return Err(rpc_error(
+            ErrorCode::InvalidParams,
+            format!("state history of block {} is pruned", block_number),
+        ));
+    }
+    let block_info = BlockInfo::new_builder()
+        .block_producer(raw_block.block_producer())
+        .timestamp(raw_block.timestamp())
+        .number(raw_block.number())
+        .build();
+    Ok(block_info)
+}
+
 #[instrument(skip_all)]
 async fn gw_execute_l2transaction(
     ctx: Arc<Registry>,
     tx: L2TransactionJsonBytes,
+    block_number_opt: Option<Uint64>,
 ) -> Result<RunResult> {
-    if ctx.mem_pool.is_none() {
+    let block_number_opt = block_number_opt.map(|n| n.value());
+    if ctx.mem_pool.is_none() && block_number_opt.is_none() {
         return Err(method_not_found());
     }
 
     let tx = tx.0;
-    let raw_block = ctx.store.get_last_valid_tip_block()?.raw();
-    let block_producer = raw_block.block_producer();
-    let timestamp = raw_block.timestamp();
-    let number = {
-        let number: u64 = raw_block.number().unpack();
-        number.saturating_add(1)
+    let mut db_txn = ctx.store.begin_transaction();
+    let block_info = match block_number_opt {
+        Some(block_number) => history_block_info(&db_txn, block_number)?,
+        None => {
+            let raw_block = db_txn.get_last_valid_tip_block()?.raw();
+            let number = {
+                let number: u64 = raw_block.number().unpack();
+                number.saturating_add(1)
+            };
+            BlockInfo::new_builder()
+                .block_producer(raw_block.block_producer())
+                .timestamp(raw_block.timestamp())
+                .number(number.pack())
+                .build()
+        }
     };
-
-    let block_info = BlockInfo::new_builder()
-        .block_producer(block_producer)
-        .timestamp(timestamp)
-        .number(number.pack())
-        .build();
+    let number: u64 = block_info.number().unpack();
 
     let tx_hash = tx.hash();
 
@@ -1420,8 +2564,18 @@ async fn gw_execute_l2transaction(
     // NOTE: for tx from id 0, it's balance will be verified after mock account
     let from_id: u32 = tx.raw().from_id().unpack();
     if 0 != from_id {
-        let state = ctx.mem_pool_state.load_state_db();
-        if let Err(err) = verify_sender_balance(ctx.generator.rollup_context(), &state, &tx.raw()) {
+        let check_balance_result = match block_number_opt {
+            Some(block_number) => {
+                let state =
+                    BlockStateDB::from_store(&mut db_txn, RWConfig::history_block(block_number))?;
+                verify_sender_balance(ctx.generator.rollup_context(), &state, &tx.raw())
+            }
+            None => {
+                let state = ctx.mem_pool_state.load_state_db();
+                verify_sender_balance(ctx.generator.rollup_context(), &state, &tx.raw())
+            }
+        };
+        if let Err(err) = check_balance_result {
             return Err(rpc_error(
                 ErrorCode::InvalidRequest,
                 format!("check balance err: {}", err),
@@ -1433,46 +2587,44 @@ async fn gw_execute_l2transaction(
     let mut run_result = tokio::task::spawn_blocking(move || {
         let _entered = execution_span.entered();
 
-        let db = ctx.store.get_snapshot();
-        let tip_block_hash = db.get_last_valid_tip_block_hash()?;
-        let chain_view = ChainView::new(&db, tip_block_hash);
-        let mut state = ctx.mem_pool_state.load_state_db();
+        let snap = db_txn.snapshot();
+        let chain_view = {
+            let tip_block_hash = snap.get_last_valid_tip_block_hash()?;
+            ChainView::new(&snap, tip_block_hash)
+        };
         let mut cycles_pool = CyclesPool::new(
-            ctx.mem_pool_config.mem_block.max_cycles_limit,
+            ctx.mem_pool_config
+                .mem_block
+                .block_cycles_limit(ctx.generator.fork_config(), number),
             ctx.mem_pool_config.mem_block.syscall_cycles.clone(),
         );
-
-        // Mock sender account if not exists
-        let eth_recover = &ctx.polyjuice_sender_recover.eth;
-        let tx = eth_recover.mock_sender_if_not_exists(tx, &mut state)?;
-        if 0 == from_id {
-            verify_sender_balance(ctx.generator.rollup_context(), &state, &tx.raw())
-                .map_err(|err| anyhow!("check balance err: {}", err))?;
+        // State changes are discarded, historical state is never committed
+        // since the db transaction is dropped.
+        match block_number_opt {
+            Some(block_number) => {
+                let mut state =
+                    BlockStateDB::from_store(&mut db_txn, RWConfig::history_block(block_number))?;
+                verify_and_execute_tx(
+                    &ctx,
+                    &chain_view,
+                    &mut state,
+                    tx,
+                    &block_info,
+                    &mut cycles_pool,
+                )
+            }
+            None => {
+                let mut state = ctx.mem_pool_state.load_state_db();
+                verify_and_execute_tx(
+                    &ctx,
+                    &chain_view,
+                    &mut state,
+                    tx,
+                    &block_info,
+                    &mut cycles_pool,
+                )
+            }
         }
-
-        // tx basic verification
-        let polyjuice_creator_id = ctx.generator.get_polyjuice_creator_id(&state)?;
-        TransactionVerifier::new(
-            &state,
-            ctx.generator.rollup_context(),
-            polyjuice_creator_id,
-            ctx.generator.fork_config(),
-        )
-        .verify(&tx, block_info.number().unpack())?;
-        // verify tx signature
-        ctx.generator.check_transaction_signature(&state, &tx)?;
-        // execute tx
-        let raw_tx = tx.raw();
-        let run_result = ctx.generator.execute_transaction(
-            &chain_view,
-            &mut state,
-            &block_info,
-            &raw_tx,
-            Some(ctx.mem_pool_config.execute_l2tx_max_cycles),
-            Some(&mut cycles_pool),
-        )?;
-
-        anyhow::Ok(run_result)
     })
     .await??;
     gw_metrics::rpc()
@@ -1498,6 +2650,50 @@ async fn gw_execute_l2transaction(
     Ok(run_result.into())
 }
 
+/// Mock the sender if it doesn't exist, then verify and execute `tx` on
+/// `state`.
+fn verify_and_execute_tx<S: State + CodeStore + JournalDB>(
+    ctx: &Registry,
+    chain_view: &ChainView<TransactionSnapshot>,
+    state: &mut S,
+    tx: L2Transaction,
+    block_info: &BlockInfo,
+    cycles_pool: &mut CyclesPool,
+) -> anyhow::Result<gw_types::offchain::RunResult> {
+    let number: u64 = block_info.number().unpack();
+    let from_id: u32 = tx.raw().from_id().unpack();
+    // Mock sender account if not exists
+    let eth_recover = &ctx.polyjuice_sender_recover.eth;
+    let tx = eth_recover.mock_sender_if_not_exists(tx, state)?;
+    if 0 == from_id {
+        verify_sender_balance(ctx.generator.rollup_context(), state, &tx.raw())
+            .map_err(|err| anyhow!("check balance err: {}", err))?;
+    }
+
+    // tx basic verification
+    let polyjuice_creator_id = ctx.generator.get_polyjuice_creator_id(state)?;
+    TransactionVerifier::new(
+        state,
+        ctx.generator.rollup_context(),
+        polyjuice_creator_id,
+        ctx.generator.fork_config(),
+        ctx.generator.deployment_policy(),
+    )
+    .verify(&tx, number)?;
+    // verify tx signature
+    ctx.generator.check_transaction_signature(state, &tx)?;
+    // execute tx
+    let raw_tx = tx.raw();
+    ctx.generator.execute_transaction(
+        chain_view,
+        state,
+        block_info,
+        &raw_tx,
+        Some(execute_max_cycles(ctx, number)),
+        Some(cycles_pool),
+    )
+}
+
 #[instrument(skip_all)]
 async fn gw_execute_raw_l2transaction(
     ctx: Arc<Registry>,
@@ -1512,37 +2708,44 @@ async fn gw_execute_raw_l2transaction(
     let mut db_txn = ctx.store.begin_transaction();
 
     let block_info = match block_number_opt {
-        Some(block_number) => {
-            let db = &db_txn;
-            let block_hash = match db.get_block_hash_by_number(block_number)? {
-                Some(block_hash) => block_hash,
-                None => return Err(header_not_found_err()),
-            };
-            let raw_block = match ctx.store.get_block(&block_hash)? {
-                Some(block) => block.raw(),
-                None => return Err(header_not_found_err()),
-            };
-            let block_producer = raw_block.block_producer();
-            let timestamp = raw_block.timestamp();
-            let number: u64 = raw_block.number().unpack();
-
-            BlockInfo::new_builder()
-                .block_producer(block_producer)
-                .timestamp(timestamp)
-                .number(number.pack())
-                .build()
-        }
+        Some(block_number) => history_block_info(&db_txn, block_number)?,
         None => ctx
             .mem_pool_state
             .get_mem_pool_block_info()
             .expect("get mem pool block info"),
     };
 
-    let execute_l2tx_max_cycles = ctx.mem_pool_config.execute_l2tx_max_cycles;
     let tx_hash: H256 = raw_l2tx.hash();
     let block_number: u64 = block_info.number().unpack();
+    let execute_l2tx_max_cycles = execute_max_cycles(&ctx, block_number);
+
+    // Results at the mem block change with the mem pool state.
+    let state_version = match block_number_opt {
+        Some(_) => None,
+        None => Some(ctx.mem_pool_state.version()),
+    };
+    let cache_key = match ctx.execution_cache {
+        Some(ref cache) => {
+            let tip_block_hash = db_txn.get_last_valid_tip_block_hash()?;
+            let key = ExecutionCache::key(
+                &tip_block_hash,
+                &raw_l2tx,
+                block_number_opt,
+                registry_address_opt.as_ref(),
+            );
+            if let Some(run_result) = cache.get(&key, state_version) {
+                gw_metrics::rpc().execute_cache(CacheResult::Hit).inc();
+                return to_execute_raw_result(run_result, tx_hash, block_number);
+            }
+            gw_metrics::rpc().execute_cache(CacheResult::Miss).inc();
+            Some(key)
+        }
+        None => None,
+    };
     let mut cycles_pool = CyclesPool::new(
-        ctx.mem_pool_config.mem_block.max_cycles_limit,
+        ctx.mem_pool_config
+            .mem_block
+            .block_cycles_limit(ctx.generator.fork_config(), block_number),
         ctx.mem_pool_config.mem_block.syscall_cycles.clone(),
     );
 
@@ -1571,7 +2774,7 @@ async fn gw_execute_raw_l2transaction(
 
     // execute tx in task
     let execution_span = tracing::info_span!("execution");
-    let mut run_result = tokio::task::spawn_blocking(move || {
+    let run_result = tokio::task::spawn_blocking(move || {
         let _entered = execution_span.entered();
 
         let eth_recover = &ctx.polyjuice_sender_recover.eth;
@@ -1627,9 +2830,21 @@ async fn gw_execute_raw_l2transaction(
                 )?
             }
         };
+        if let (Some(cache), Some(key)) = (ctx.execution_cache.as_ref(), cache_key) {
+            cache.insert(key, state_version, run_result.clone());
+        }
         anyhow::Ok(run_result)
     })
     .await??;
+
+    to_execute_raw_result(run_result, tx_hash, block_number)
+}
+
+fn to_execute_raw_result(
+    mut run_result: gw_types::offchain::RunResult,
+    tx_hash: H256,
+    block_number: u64,
+) -> Result<RunResult> {
     gw_metrics::rpc()
         .execute_transactions(run_result.exit_code)
         .inc();
@@ -1656,7 +2871,23 @@ async fn gw_execute_raw_l2transaction(
 async fn gw_submit_l2transaction(
     ctx: &Registry,
     l2tx: L2TransactionJsonBytes,
+    origin_token: Option<String>,
 ) -> Result<Option<JsonH256>> {
+    // Resolve the origin from its secret token, never trust a client supplied
+    // origin id. The token isn't echoed back in the error.
+    let origin = match origin_token {
+        Some(token) => match ctx
+            .server_config
+            .fee_rebate_origins
+            .iter()
+            .find(|o| o.token == token)
+        {
+            Some(o) => Some(o.origin.clone()),
+            None => return Err(rpc_error(ErrorCode::InvalidParams, "invalid origin token")),
+        },
+        None => None,
+    };
+
     let tx = l2tx.0;
     let tx_hash: H256 = tx.hash();
 
@@ -1691,8 +2922,20 @@ async fn gw_submit_l2transaction(
         rate_limiter.put(sender_id, Instant::now());
     }
 
-    // TODO use TransactionVerifier after remove sender auto creator
-    // verify tx size
+    ctx.check_maintenance_fence()?;
+    ctx.check_mem_pool_paused()?;
+
+    // check account throttle, so clients can back off before the tx is queued
+    if let Some(ref account_throttle) = ctx.account_throttle {
+        if 0 != sender_id {
+            if let Err(err) = account_throttle.check(sender_id) {
+                return Err(rpc_error(ACCOUNT_THROTTLED_ERR_CODE, err.to_string()));
+            }
+        }
+    }
+
+    // intrinsic checks, the nonce and balance are checked by mem pool since
+    // txs from id zero have no sender accounts yet
     {
         // block info
         let block_info = ctx
@@ -1700,16 +2943,16 @@ async fn gw_submit_l2transaction(
             .load_shared()
             .mem_block
             .expect("mem block info");
-        // check tx size
-        let max_tx_size = ctx
-            .generator
-            .fork_config()
-            .max_tx_size(block_info.number().unpack());
-        if tx.as_slice().len() > max_tx_size {
-            let err = TransactionError::ExceededMaxTxSize {
-                max_size: max_tx_size,
-                tx_size: tx.as_slice().len(),
-            };
+        let state = ctx.mem_pool_state.load_state_db();
+        let polyjuice_creator_id = ctx.generator.get_polyjuice_creator_id(&state)?;
+        let verifier = IntrinsicVerifier::new(
+            &state,
+            ctx.generator.rollup_context(),
+            polyjuice_creator_id,
+            ctx.generator.fork_config(),
+            ctx.generator.deployment_policy(),
+        );
+        if let Err(err) = verifier.verify(&tx, block_info.number().unpack()) {
             return Err(rpc_error(ErrorCode::InvalidRequest, err.to_string()));
         }
     }
@@ -1740,7 +2983,21 @@ async fn gw_submit_l2transaction(
         }
     }
 
-    let permit = ctx.submit_tx.try_reserve().map_err(|err| match err {
+    // Readonly nodes relay the tx to the full node.
+    if let Some(ref tx_relay) = ctx.tx_relay {
+        return match tx_relay.relay(tx) {
+            Ok(_) => Ok(tx_hash_json),
+            Err(TxRelayError::Full) => Err(rpc_error(BUSY_ERR_CODE, "tx relay busy")),
+            Err(err) => Err(err.into()),
+        };
+    }
+
+    let submit_tx = if ctx.is_priority_lane_sender(sender_id)? {
+        &ctx.priority_submit_tx
+    } else {
+        &ctx.submit_tx
+    };
+    let permit = submit_tx.try_reserve().map_err(|err| match err {
         mpsc::error::TrySendError::Full(_) => rpc_error(BUSY_ERR_CODE, "mem pool service busy"),
         e => e.into(),
     })?;
@@ -1765,12 +3022,18 @@ async fn gw_submit_l2transaction(
         .insert(tx_hash_in_queue, request.clone())
     {
         // Send if the request wasn't already in the map.
+        if let Some(ref dropped_requests) = ctx.dropped_requests {
+            dropped_requests.remove(&tx_hash_in_queue);
+        }
         let in_queue_span = tracing::info_span!("submit_queue.send");
         let _entered = in_queue_span.clone().entered();
         let ctx = RequestContext {
-            _in_queue_handle: handle,
+            in_queue_handle: handle,
+            origin: origin.map(Bytes::from),
+            received_at: unix_millis(),
             trace: gw_telemetry::current_context(),
             in_queue_span,
+            cycles_deferred: false,
         };
         permit.send((request, ctx));
     }
@@ -1778,14 +3041,37 @@ async fn gw_submit_l2transaction(
     Ok(tx_hash_json)
 }
 
+async fn submit_relayed_txs(ctx: Arc<Registry>, mut relayed_txs: mpsc::Receiver<L2Transaction>) {
+    while let Some(tx) = relayed_txs.recv().await {
+        let tx_hash = tx.hash();
+        if let Err(err) = gw_submit_l2transaction(&ctx, MolJsonBytes(tx), None).await {
+            log::info!(
+                "[tx relay] reject tx {}: {}",
+                faster_hex::hex_string(&tx_hash),
+                err.0.message
+            );
+        }
+    }
+}
+
 #[instrument(skip_all)]
 async fn gw_submit_withdrawal_request(
     ctx: &Registry,
     withdrawal: WithdrawalRequestExtraJsonBytes,
 ) -> Result<JsonH256> {
+    ctx.check_maintenance_fence()?;
+    ctx.check_mem_pool_paused()?;
+
     let withdrawal = withdrawal.0;
     let withdrawal_hash = withdrawal.hash();
 
+    // check withdrawal limits, it is checked again on mem pool admission
+    if let Some(ref withdrawal_limits) = ctx.withdrawal_limits {
+        if let Err(err) = withdrawal_limits.check(&withdrawal.raw()) {
+            return Err(rpc_error(WITHDRAWAL_LIMITED_ERR_CODE, err.to_string()));
+        }
+    }
+
     let last_valid = ctx.store.get_last_valid_tip_block_hash()?;
     let last_valid = ctx
         .store
@@ -1826,12 +3112,18 @@ async fn gw_submit_withdrawal_request(
         .insert(withdrawal_hash, request.clone())
     {
         // Send if the request wasn't already in the map.
+        if let Some(ref dropped_requests) = ctx.dropped_requests {
+            dropped_requests.remove(&withdrawal_hash);
+        }
         let in_queue_span = tracing::info_span!("submit_queue.send");
         let _entered = in_queue_span.clone().entered();
         let ctx = RequestContext {
-            _in_queue_handle: handle,
+            in_queue_handle: handle,
+            origin: None,
+            received_at: unix_millis(),
             trace: gw_telemetry::current_context(),
             in_queue_span,
+            cycles_deferred: false,
         };
         permit.send((request, ctx));
     }
@@ -2021,6 +3313,409 @@ async fn gw_get_registry_address_by_script_hash(
     Ok(addr.map(Into::into))
 }
 
+/// Prove the post state root of a finalized block against the post global
+/// state of the last finalized block, for verifying Godwoken state elsewhere.
+async fn gw_get_state_root_proof(
+    ctx: &Registry,
+    block_hash: JsonH256,
+) -> Result<Option<StateRootProof>> {
+    let block_hash = to_h256(block_hash);
+    let _guard = ctx.finalized_block_smt_lock.lock().await;
+    tokio::task::block_in_place(|| {
+        let mut db = ctx.store.begin_transaction();
+        let block = match db.get_block(&block_hash)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let block_number: u64 = block.raw().number().unpack();
+        // return None if block is not on the main chain
+        if db.get_block_hash_by_number(block_number)? != Some(block_hash) {
+            return Ok(None);
+        }
+
+        let last_confirmed_hash: H256 = db
+            .get_last_confirmed_block_number_hash()
+            .context("get last confirmed block")?
+            .block_hash()
+            .unpack();
+        let last_confirmed = db
+            .get_block(&last_confirmed_hash)?
+            .context("get last confirmed block")?;
+        let finalized_end = calc_finalizing_range(
+            &ctx.rollup_config,
+            ctx.generator.fork_config(),
+            &db,
+            &last_confirmed,
+        )?
+        .end;
+        if block_number >= finalized_end {
+            return Err(rpc_error(
+                ErrorCode::InvalidParams,
+                "block is not finalized yet",
+            ));
+        }
+        let finalized_block_number = finalized_end - 1;
+
+        let root = db.advance_finalized_block_smt(finalized_block_number)?;
+        let finalized_block_hash = db
+            .get_block_hash_by_number(finalized_block_number)?
+            .context("get finalized block hash")?;
+        let global_state = db
+            .get_block_post_global_state(&finalized_block_hash)?
+            .context("get finalized block global state")?;
+        let global_block_root: H256 = global_state.block().merkle_root().unpack();
+        if root != global_block_root {
+            return Err(anyhow!("finalized block root mismatch").into());
+        }
+        let key = packed::RawL2Block::compute_smt_key(block_number);
+        let block_proof = db
+            .finalized_block_smt()?
+            .merkle_proof(vec![key.into()])?
+            .compile(vec![key.into()])?;
+        db.commit()?;
+
+        Ok(Some(StateRootProof {
+            block_hash: to_jsonh256(block_hash),
+            raw_block: block.raw().into(),
+            finalized_block_number: finalized_block_number.into(),
+            finalized_block_hash: to_jsonh256(finalized_block_hash),
+            global_state: global_state.into(),
+            block_proof: JsonBytes::from_vec(block_proof.0),
+        }))
+    })
+}
+
+const MAX_STATE_PROOF_KEYS: usize = 100;
+
+/// Prove values of state keys after a block, e.g. account fields at a
+/// finalized block for bridges.
+async fn gw_get_state_proof(
+    ctx: &Registry,
+    block_hash: JsonH256,
+    keys: Vec<JsonH256>,
+) -> Result<Option<StateProof>> {
+    if keys.is_empty() || keys.len() > MAX_STATE_PROOF_KEYS {
+        return Err(rpc_error(
+            ErrorCode::InvalidParams,
+            format!(
+                "number of keys must be in range [1, {}]",
+                MAX_STATE_PROOF_KEYS
+            ),
+        ));
+    }
+    let block_hash = to_h256(block_hash);
+    let keys: Vec<H256> = keys.into_iter().map(to_h256).collect();
+    let proof = tokio::task::block_in_place(|| ctx.store.generate_state_proof(&block_hash, &keys))?;
+    Ok(proof.map(|proof| StateProof {
+        block_hash: to_jsonh256(block_hash),
+        block_number: proof.block_number.into(),
+        post_account: proof.post_account.into(),
+        leaves: proof
+            .leaves
+            .into_iter()
+            .map(|(k, v)| KVPair {
+                k: to_jsonh256(k),
+                v: to_jsonh256(v),
+            })
+            .collect(),
+        proof: JsonBytes::from_vec(proof.proof),
+    }))
+}
+
+const DEFAULT_DEPOSIT_HISTORY_LIMIT: u32 = 20;
+const MAX_DEPOSIT_HISTORY_LIMIT: u32 = 100;
+
+async fn gw_get_deposit_history(
+    ctx: &Registry,
+    address: RegistryAddressJsonBytes,
+    cursor: Option<JsonBytes>,
+    limit: Option<Uint32>,
+) -> Result<DepositHistory> {
+    let cursor: Option<DepositCursor> =
+        match cursor {
+            Some(cursor) => Some(cursor.as_bytes().try_into().map_err(|_| {
+                rpc_error(ErrorCode::InvalidParams, "invalid deposit history cursor")
+            })?),
+            None => None,
+        };
+    let limit = limit.map_or(DEFAULT_DEPOSIT_HISTORY_LIMIT, |l| l.value());
+    if limit == 0 || limit > MAX_DEPOSIT_HISTORY_LIMIT {
+        return Err(rpc_error(
+            ErrorCode::InvalidParams,
+            format!("limit must be in range [1, {}]", MAX_DEPOSIT_HISTORY_LIMIT),
+        ));
+    }
+
+    let script_hash = {
+        let state = ctx.mem_pool_state.load_state_db();
+        state.get_script_hash_by_registry_address(&address.0)?
+    };
+    let records = match script_hash {
+        Some(script_hash) => ctx.store.begin_transaction().get_deposit_history(
+            &script_hash,
+            cursor.as_ref(),
+            limit as usize,
+        )?,
+        None => Vec::new(),
+    };
+
+    let next_cursor = match records.last() {
+        Some(last) if records.len() == limit as usize => {
+            Some(JsonBytes::from_vec(last.cursor().to_vec()))
+        }
+        _ => None,
+    };
+    Ok(DepositHistory {
+        deposits: records
+            .into_iter()
+            .map(|record| DepositRecord {
+                block_number: record.block_number.into(),
+                l1_tx_hash: to_jsonh256(record.l1_tx_hash),
+                l1_output_index: record.l1_output_index.into(),
+                capacity: record.capacity.into(),
+                amount: record.amount.into(),
+                sudt_script_hash: to_jsonh256(record.sudt_script_hash),
+            })
+            .collect(),
+        cursor: next_cursor,
+    })
+}
+
+const DEFAULT_GET_LOGS_LIMIT: u32 = 100;
+const MAX_GET_LOGS_LIMIT: u32 = 1000;
+// Max logs of the address to scan when filtering by both address and topic.
+const MAX_GET_LOGS_SCAN: usize = 10_000;
+
+async fn gw_get_logs(
+    ctx: &Registry,
+    from_block: Uint64,
+    to_block: Uint64,
+    address: Option<ckb_fixed_hash::H160>,
+    topic: Option<JsonH256>,
+    limit: Option<Uint32>,
+) -> Result<Vec<IndexedLog>> {
+    let (from_block, to_block): (u64, u64) = (from_block.into(), to_block.into());
+    if from_block > to_block {
+        return Err(rpc_error(
+            ErrorCode::InvalidParams,
+            "from_block is greater than to_block",
+        ));
+    }
+    let limit = limit.map_or(DEFAULT_GET_LOGS_LIMIT, |l| l.value());
+    if limit == 0 || limit > MAX_GET_LOGS_LIMIT {
+        return Err(rpc_error(
+            ErrorCode::InvalidParams,
+            format!("limit must be in range [1, {}]", MAX_GET_LOGS_LIMIT),
+        ));
+    }
+    let topic = topic.map(to_h256);
+    let db = ctx.store.begin_transaction();
+    let cursors = match (address, topic) {
+        (Some(address), Some(_)) => {
+            let cursors = db.get_log_cursors(
+                &LogIndexKey::Address(address.0),
+                from_block,
+                to_block,
+                MAX_GET_LOGS_SCAN + 1,
+            );
+            if cursors.len() > MAX_GET_LOGS_SCAN {
+                return Err(rpc_error(
+                    ErrorCode::InvalidParams,
+                    "too many logs of the address, narrow the block range",
+                ));
+            }
+            cursors
+        }
+        (Some(address), None) => db.get_log_cursors(
+            &LogIndexKey::Address(address.0),
+            from_block,
+            to_block,
+            limit as usize,
+        ),
+        (None, Some(topic)) => db.get_log_cursors(
+            &LogIndexKey::Topic(topic),
+            from_block,
+            to_block,
+            limit as usize,
+        ),
+        (None, None) => {
+            return Err(rpc_error(
+                ErrorCode::InvalidParams,
+                "address or topic is required",
+            ))
+        }
+    };
+
+    let mut logs = Vec::new();
+    for cursor in cursors {
+        if logs.len() >= limit as usize {
+            break;
+        }
+        let block_hash = match db.get_block_hash_by_number(cursor.block_number)? {
+            Some(hash) => hash,
+            None => continue,
+        };
+        let tx_key = TransactionKey::new_builder()
+            .block_hash(block_hash.pack())
+            .index(cursor.tx_index.pack())
+            .build();
+        let (tx, receipt) = match (
+            db.get_transaction_by_key(&tx_key)?,
+            db.get_transaction_receipt_by_key(&tx_key)?,
+        ) {
+            (Some(tx), Some(receipt)) => (tx, receipt),
+            _ => continue,
+        };
+        let log = match receipt.logs().get(cursor.log_index as usize) {
+            Some(log) => log,
+            None => continue,
+        };
+        let (address, data, topics) = match parse_log(&log)? {
+            GwLog::PolyjuiceUser {
+                address,
+                data,
+                topics,
+            } => (address, data, topics),
+            _ => continue,
+        };
+        if matches!(topic, Some(ref topic) if !topics.contains(topic)) {
+            continue;
+        }
+        logs.push(IndexedLog {
+            block_number: cursor.block_number.into(),
+            block_hash: to_jsonh256(block_hash),
+            tx_hash: to_jsonh256(tx.hash()),
+            tx_index: cursor.tx_index.into(),
+            log_index: cursor.log_index.into(),
+            address: ckb_fixed_hash::H160(address),
+            data: JsonBytes::from_vec(data),
+            topics: topics.into_iter().map(to_jsonh256).collect(),
+        });
+    }
+    Ok(logs)
+}
+
+async fn gw_get_account_queue(
+    ctx: &Registry,
+    address: RegistryAddressJsonBytes,
+) -> Result<Option<AccountQueue>> {
+    let mem_pool = ctx.mem_pool.as_ref().ok_or_else(method_not_found)?;
+    let in_queue_request_map = ctx
+        .in_queue_request_map
+        .as_deref()
+        .ok_or_else(method_not_found)?;
+
+    let state = ctx.mem_pool_state.load_state_db();
+    let script_hash = match state.get_script_hash_by_registry_address(&address.0)? {
+        Some(script_hash) => script_hash,
+        None => return Ok(None),
+    };
+    let account_id = match state.get_account_id_by_script_hash(&script_hash)? {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    let next_nonce = state.get_nonce(account_id)?;
+    let denied = match ctx.denylist {
+        Some(ref denylist) => denylist.check(&state, &script_hash).err(),
+        None => None,
+    };
+    let pending_nonces = mem_pool.lock().await.pending_nonces(account_id);
+
+    let mut queued: Vec<_> = in_queue_request_map
+        .requests_from(account_id, &script_hash)
+        .into_iter()
+        .map(|(hash, req)| {
+            let (kind, nonce): (_, u32) = match req {
+                Request::Tx(tx) => (QueuedRequestKind::Tx, tx.raw().nonce().unpack()),
+                Request::Withdrawal(w) => (QueuedRequestKind::Withdrawal, w.raw().nonce().unpack()),
+            };
+            (hash, kind, nonce)
+        })
+        .collect();
+    queued.sort_unstable_by_key(|(_, _, nonce)| *nonce);
+
+    // Requests are promoted in nonce order, a missing nonce blocks the rest.
+    let mut expected_nonce = next_nonce;
+    let queued = queued
+        .into_iter()
+        .map(|(hash, kind, nonce)| {
+            let reason = if let Some(ref err) = denied {
+                Some(err.to_string())
+            } else if nonce < next_nonce {
+                Some(format!("nonce too low, expected {}", next_nonce))
+            } else if nonce > expected_nonce {
+                Some(format!("nonce gap, missing nonce {}", expected_nonce))
+            } else {
+                expected_nonce = expected_nonce.max(nonce.saturating_add(1));
+                None
+            };
+            QueuedRequest {
+                hash: to_jsonh256(hash),
+                kind,
+                nonce: nonce.into(),
+                promotable: reason.is_none(),
+                reason,
+            }
+        })
+        .collect();
+
+    Ok(Some(AccountQueue {
+        account_id: account_id.into(),
+        next_nonce: next_nonce.into(),
+        pending_nonces: pending_nonces.into_iter().map(Into::into).collect(),
+        queued,
+    }))
+}
+
+async fn gw_mem_pool_stats(ctx: &Registry) -> Result<MemPoolStats> {
+    const TOP_SENDERS: usize = 10;
+
+    let mem_pool = ctx.mem_pool.as_ref().ok_or_else(method_not_found)?;
+    let stats = mem_pool.lock().await.stats(TOP_SENDERS);
+    let (queued_txs, queued_withdrawals) = ctx
+        .in_queue_request_map
+        .as_deref()
+        .map_or((0, 0), InQueueRequestMap::count);
+    let sync_mode = match ctx.node_mode {
+        NodeMode::ReadOnly if ctx.p2p_network_status.is_some() => MemPoolSyncMode::Subscribing,
+        NodeMode::FullNode | NodeMode::Test if stats.sync_server_enabled => {
+            MemPoolSyncMode::Publishing
+        }
+        _ => MemPoolSyncMode::Disabled,
+    };
+
+    Ok(MemPoolStats {
+        pending_txs: (stats.pending_txs as u32).into(),
+        pending_withdrawals: (stats.pending_withdrawals as u32).into(),
+        pending_accounts: (stats.pending_accounts as u32).into(),
+        queued_txs: (queued_txs as u32).into(),
+        queued_withdrawals: (queued_withdrawals as u32).into(),
+        top_senders: stats
+            .top_senders
+            .into_iter()
+            .map(|s| MemPoolSenderUsage {
+                account_id: s.account_id.into(),
+                txs: (s.txs as u32).into(),
+                withdrawals: (s.withdrawals as u32).into(),
+            })
+            .collect(),
+        oldest_entry_age_ms: stats
+            .oldest_entry_age
+            .map(|age| (age.as_millis() as u64).into()),
+        mem_block: MemBlockUsage {
+            number: stats.mem_block_number.into(),
+            txs: (stats.mem_block_txs as u32).into(),
+            max_txs: (stats.max_txs as u32).into(),
+            withdrawals: (stats.mem_block_withdrawals as u32).into(),
+            max_withdrawals: (stats.max_withdrawals as u32).into(),
+            deposits: (stats.mem_block_deposits as u32).into(),
+            cycles_used: stats.cycles_used.into(),
+            max_cycles: stats.max_cycles.into(),
+        },
+        sync_mode,
+    })
+}
+
 fn get_backend_info(generator: Arc<Generator>) -> Vec<BackendInfo> {
     generator
         .backend_manage()
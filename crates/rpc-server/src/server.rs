@@ -28,6 +28,7 @@ pub async fn start_jsonrpc_server(
     listen_addr: SocketAddr,
     handler: Arc<MetaIoHandler<Option<Session>>>,
     liveness: Arc<Liveness>,
+    serve_metrics_endpoint: bool,
     _shutdown_send: mpsc::Sender<()>,
     mut sub_shutdown: broadcast::Receiver<()>,
 ) -> Result<()> {
@@ -41,10 +42,13 @@ pub async fn start_jsonrpc_server(
     incoming.set_keepalive(Some(Duration::from_secs(10)));
     incoming.set_nodelay(true);
 
-    let app = Router::new()
+    let mut app = Router::new()
         .route("/livez", get(serve_liveness))
-        .with_state(liveness)
-        .route("/metrics", get(serve_metrics))
+        .with_state(liveness);
+    if serve_metrics_endpoint {
+        app = app.route("/metrics", get(serve_metrics));
+    }
+    let app = app
         .route("/", post(handle_jsonrpc_with_tracing))
         .route("/*path", post(handle_jsonrpc_with_tracing))
         .with_state(handler)
@@ -1,3 +1,5 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use ckb_fixed_hash::H256 as JsonH256;
 use gw_types::h256::H256;
 
@@ -10,3 +12,10 @@ pub(crate) fn to_h256(v: JsonH256) -> H256 {
 pub(crate) fn to_jsonh256(v: H256) -> JsonH256 {
     v.into()
 }
+
+/// Current unix timestamp in milliseconds.
+pub(crate) fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
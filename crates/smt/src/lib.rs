@@ -3,6 +3,7 @@
 pub mod merkle_utils;
 pub mod smt;
 pub mod smt_h256_ext;
+pub mod verify;
 
 // re-exports
 pub use gw_hash::blake2b;
@@ -0,0 +1,118 @@
+//! Proof verification for external verifiers.
+//!
+//! Only depends on the hasher and the compiled proof format, builds without
+//! the `std` feature:
+//!
+//! ```toml
+//! gw-smt = { path = "...", default-features = false }
+//! ```
+//!
+//! Proofs are `CompiledMerkleProof` bytes, e.g. `kv_state_proof` and
+//! `block_proof` of an L2 block or proofs returned by RPC.
+
+use gw_types::h256::H256;
+
+use crate::{
+    merkle_utils::calculate_state_checkpoint,
+    smt::{Blake2bHasher, CompiledMerkleProof, Error, SMTH256},
+    smt_h256_ext::SMTH256Ext,
+};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "std")] {
+        use std::vec::Vec;
+    } else {
+        extern crate alloc;
+        use alloc::vec::Vec;
+    }
+}
+
+/// Verify a compiled proof of multiple leaves. Absent keys are proven with
+/// zero values.
+pub fn verify_multi_proof(
+    root: &H256,
+    proof: &[u8],
+    leaves: impl IntoIterator<Item = (H256, H256)>,
+) -> Result<bool, Error> {
+    let leaves: Vec<(SMTH256, SMTH256)> = leaves
+        .into_iter()
+        .map(|(key, value)| (key.into(), value.into()))
+        .collect();
+    CompiledMerkleProof(proof.to_vec()).verify::<Blake2bHasher>(&(*root).into(), leaves)
+}
+
+/// Verify a compiled proof of a single leaf.
+pub fn verify_proof(root: &H256, proof: &[u8], key: H256, value: H256) -> Result<bool, Error> {
+    verify_multi_proof(root, proof, [(key, value)])
+}
+
+/// Verify that a block hash is in the block SMT under its block number.
+pub fn verify_block_proof(
+    block_root: &H256,
+    proof: &[u8],
+    block_number: u64,
+    block_hash: H256,
+) -> Result<bool, Error> {
+    let key: [u8; 32] = SMTH256::from_u64(block_number).into();
+    verify_proof(block_root, proof, key, block_hash)
+}
+
+/// Verify kv pairs against an account merkle state. Checkpoints commit to
+/// the account count as well, see `calculate_state_checkpoint`.
+pub fn verify_account_state_proof(
+    checkpoint: &H256,
+    account_root: &H256,
+    account_count: u32,
+    proof: &[u8],
+    kv_pairs: impl IntoIterator<Item = (H256, H256)>,
+) -> Result<bool, Error> {
+    if &calculate_state_checkpoint(account_root, account_count) != checkpoint {
+        return Ok(false);
+    }
+    verify_multi_proof(account_root, proof, kv_pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smt::{default_store::DefaultStore, SMT};
+
+    #[test]
+    fn test_verify_proofs() {
+        let mut tree = SMT::<DefaultStore<SMTH256>>::default();
+        let leaves: Vec<(H256, H256)> = (1u8..=4).map(|i| ([i; 32], [i + 10; 32])).collect();
+        for (key, value) in leaves.iter() {
+            tree.update((*key).into(), (*value).into()).unwrap();
+        }
+        let root: H256 = (*tree.root()).into();
+        let keys: Vec<SMTH256> = leaves.iter().map(|(k, _)| (*k).into()).collect();
+        let proof = tree
+            .merkle_proof(keys.clone())
+            .unwrap()
+            .compile(keys)
+            .unwrap();
+
+        assert!(verify_multi_proof(&root, &proof.0, leaves.clone()).unwrap());
+        let mut tampered = leaves.clone();
+        tampered[0].1 = [0xff; 32];
+        assert!(!verify_multi_proof(&root, &proof.0, tampered).unwrap());
+
+        let (key, value) = leaves[1];
+        let single = tree
+            .merkle_proof(vec![key.into()])
+            .unwrap()
+            .compile(vec![key.into()])
+            .unwrap();
+        assert!(verify_proof(&root, &single.0, key, value).unwrap());
+
+        let count = leaves.len() as u32;
+        let checkpoint = calculate_state_checkpoint(&root, count);
+        assert!(
+            verify_account_state_proof(&checkpoint, &root, count, &proof.0, leaves.clone())
+                .unwrap()
+        );
+        assert!(
+            !verify_account_state_proof(&checkpoint, &root, count + 1, &proof.0, leaves).unwrap()
+        );
+    }
+}
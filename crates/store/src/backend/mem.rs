@@ -0,0 +1,238 @@
+//! Pure-Rust in-memory backend, for embedding and tests without RocksDB.
+//!
+//! Columns are persistent ordered maps, so snapshots are cheap clones. Data
+//! is not persisted.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Bound,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use anyhow::{bail, Result};
+use autorocks::Direction;
+use im::OrdMap;
+
+use super::{Backend, BackendRead, BackendTransaction};
+use crate::{schema::Col, traits::kv_store::KVIter};
+
+type Column = OrdMap<Box<[u8]>, Box<[u8]>>;
+type Key = (Col, Box<[u8]>);
+
+#[derive(Clone)]
+pub struct MemBackend {
+    shared: Arc<Mutex<Shared>>,
+}
+
+struct Shared {
+    columns: Vec<Column>,
+    /// Sequence number of the last commit.
+    seq: u64,
+    /// Sequence number of the last commit writing each key.
+    versions: HashMap<Key, u64>,
+    /// Keys with uncommitted writes, and the id of the writing transaction.
+    locks: HashMap<Key, u64>,
+    next_tx_id: u64,
+}
+
+impl MemBackend {
+    pub fn new(columns: usize) -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(Shared {
+                columns: vec![Column::new(); columns],
+                seq: 0,
+                versions: HashMap::new(),
+                locks: HashMap::new(),
+                next_tx_id: 0,
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Shared> {
+        self.shared.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn column(&self, col: Col) -> Column {
+        self.lock().columns[col].clone()
+    }
+}
+
+impl Backend for MemBackend {
+    type Transaction = MemTransaction;
+    type Snapshot = MemSnapshot;
+
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        Ok(self.lock().columns[col].get(key).cloned())
+    }
+
+    fn begin_transaction(&self) -> MemTransaction {
+        let mut shared = self.lock();
+        let id = shared.next_tx_id;
+        shared.next_tx_id += 1;
+        MemTransaction {
+            backend: self.clone(),
+            id,
+            start_seq: shared.seq,
+            writes: BTreeMap::new(),
+        }
+    }
+
+    fn snapshot(&self) -> MemSnapshot {
+        MemSnapshot {
+            columns: self.lock().columns.clone(),
+        }
+    }
+}
+
+pub struct MemSnapshot {
+    columns: Vec<Column>,
+}
+
+impl BackendRead for MemSnapshot {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        Ok(self.columns[col].get(key).cloned())
+    }
+
+    fn iter_from(&self, col: Col, from: Option<&[u8]>, dir: Direction) -> KVIter<'_> {
+        Box::new(ColumnIter::new(self.columns[col].clone(), from, dir))
+    }
+}
+
+pub struct MemTransaction {
+    backend: MemBackend,
+    id: u64,
+    start_seq: u64,
+    /// Uncommitted writes, None for deletes.
+    writes: BTreeMap<Key, Option<Box<[u8]>>>,
+}
+
+impl MemTransaction {
+    fn write(&mut self, col: Col, key: &[u8], value: Option<&[u8]>) -> Result<()> {
+        let key: Key = (col, key.into());
+        {
+            let mut shared = self.backend.lock();
+            match shared.locks.get(&key) {
+                Some(id) if *id != self.id => bail!("key is locked by another transaction"),
+                Some(_) => {}
+                None => {
+                    if shared.versions.get(&key).copied().unwrap_or(0) > self.start_seq {
+                        bail!("key is written by another transaction");
+                    }
+                    shared.locks.insert(key.clone(), self.id);
+                }
+            }
+        }
+        self.writes.insert(key, value.map(Into::into));
+        Ok(())
+    }
+
+    fn release_locks(&mut self) {
+        if self.writes.is_empty() {
+            return;
+        }
+        let mut shared = self.backend.lock();
+        for key in self.writes.keys() {
+            shared.locks.remove(key);
+        }
+    }
+}
+
+impl BackendRead for MemTransaction {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        match self.writes.get(&(col, key.into())) {
+            Some(value) => Ok(value.clone()),
+            None => self.backend.get(col, key),
+        }
+    }
+
+    fn iter_from(&self, col: Col, from: Option<&[u8]>, dir: Direction) -> KVIter<'_> {
+        let mut column = self.backend.column(col);
+        let writes = self
+            .writes
+            .range((col, Box::default())..)
+            .take_while(|((c, _), _)| *c == col);
+        for ((_, key), value) in writes {
+            match value {
+                Some(value) => column.insert(key.clone(), value.clone()),
+                None => column.remove(key),
+            };
+        }
+        Box::new(ColumnIter::new(column, from, dir))
+    }
+}
+
+impl BackendTransaction for MemTransaction {
+    fn put(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
+        self.write(col, key, Some(value))
+    }
+
+    fn delete(&mut self, col: Col, key: &[u8]) -> Result<()> {
+        self.write(col, key, None)
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        let writes = std::mem::take(&mut self.writes);
+        let mut shared = self.backend.lock();
+        shared.seq += 1;
+        let seq = shared.seq;
+        for (key, value) in writes {
+            let (col, k) = &key;
+            match value {
+                Some(value) => shared.columns[*col].insert(k.clone(), value),
+                None => shared.columns[*col].remove(k),
+            };
+            shared.locks.remove(&key);
+            shared.versions.insert(key, seq);
+        }
+        drop(shared);
+        // Later writes conflict with commits before here.
+        self.start_seq = seq;
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        self.release_locks();
+        self.writes.clear();
+        Ok(())
+    }
+}
+
+impl Drop for MemTransaction {
+    fn drop(&mut self) {
+        self.release_locks();
+    }
+}
+
+/// Iterates an owned column, seeking past the last key for every step.
+struct ColumnIter {
+    column: Column,
+    /// None if exhausted.
+    next: Option<Bound<Box<[u8]>>>,
+    backward: bool,
+}
+
+impl ColumnIter {
+    fn new(column: Column, from: Option<&[u8]>, dir: Direction) -> Self {
+        Self {
+            column,
+            next: Some(from.map_or(Bound::Unbounded, |k| Bound::Included(k.into()))),
+            backward: matches!(dir, Direction::Backward),
+        }
+    }
+}
+
+impl Iterator for ColumnIter {
+    type Item = (Box<[u8]>, Box<[u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bound = self.next.take()?;
+        let (key, value) = if self.backward {
+            self.column.range((Bound::Unbounded, bound)).next_back()
+        } else {
+            self.column.range((bound, Bound::Unbounded)).next()
+        }
+        .map(|(k, v)| (k.clone(), v.clone()))?;
+        self.next = Some(Bound::Excluded(key.clone()));
+        Some((key, value))
+    }
+}
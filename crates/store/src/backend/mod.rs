@@ -0,0 +1,54 @@
+//! Storage backends
+//!
+//! The store is written against RocksDB transaction db semantics. These
+//! traits pin down the semantics the store relies on, so that a backend other
+//! than RocksDB, e.g. the pure-Rust [`mem::MemBackend`], can be checked
+//! against them:
+//!
+//! - Columns are numbered from 0, see [`crate::schema`]. Keys are ordered
+//!   bytewise.
+//! - Transactions read their own uncommitted writes, and the latest committed
+//!   data otherwise.
+//! - Uncommitted writes are not visible outside the transaction.
+//! - Writing a key fails if another transaction has an uncommitted write to
+//!   it, or has committed a write to it since this transaction began.
+//! - Snapshots are consistent views of committed data at the time they are
+//!   taken.
+//!
+//! [`crate::Store`] doesn't dispatch through these traits yet, it still uses
+//! RocksDB directly, so RocksDB remains a required dependency even with the
+//! `mem-backend` feature.
+
+use anyhow::Result;
+use autorocks::Direction;
+
+use crate::{schema::Col, traits::kv_store::KVIter};
+
+#[cfg(feature = "mem-backend")]
+pub mod mem;
+mod rocksdb;
+
+pub trait Backend: Clone + Send + Sync {
+    type Transaction: BackendTransaction;
+    type Snapshot: BackendRead + Send + Sync;
+
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<Box<[u8]>>>;
+    fn begin_transaction(&self) -> Self::Transaction;
+    fn snapshot(&self) -> Self::Snapshot;
+}
+
+pub trait BackendRead {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<Box<[u8]>>>;
+
+    /// Iterate a column in the direction, starting at `from`, or the first
+    /// key not after `from` for backward iteration. Starts at the first or
+    /// last key if `from` is None.
+    fn iter_from(&self, col: Col, from: Option<&[u8]>, dir: Direction) -> KVIter<'_>;
+}
+
+pub trait BackendTransaction: BackendRead {
+    fn put(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<()>;
+    fn delete(&mut self, col: Col, key: &[u8]) -> Result<()>;
+    fn commit(&mut self) -> Result<()>;
+    fn rollback(&mut self) -> Result<()>;
+}
@@ -0,0 +1,79 @@
+//! The default backend.
+
+use anyhow::Result;
+use autorocks::{moveit::slot, DbIterator, Direction, Snapshot, Transaction, TransactionDb};
+
+use super::{Backend, BackendRead, BackendTransaction};
+use crate::{schema::Col, traits::kv_store::KVIter};
+
+fn seek<T>(iter: &mut DbIterator<T>, from: Option<&[u8]>, backward: bool) {
+    match from {
+        Some(from) if backward => iter.seek_for_prev(from),
+        Some(from) => iter.seek(from),
+        None => {}
+    }
+}
+
+impl Backend for TransactionDb {
+    type Transaction = Transaction;
+    type Snapshot = Snapshot;
+
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        slot!(slice);
+        Ok(TransactionDb::get(self, col, key, slice)?.map(|p| p.as_ref().into()))
+    }
+
+    fn begin_transaction(&self) -> Transaction {
+        TransactionDb::begin_transaction(self)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        TransactionDb::snapshot(self)
+    }
+}
+
+impl BackendRead for Snapshot {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        slot!(slice);
+        Ok(Snapshot::get(self, col, key, slice)?.map(|p| p.as_ref().into()))
+    }
+
+    fn iter_from(&self, col: Col, from: Option<&[u8]>, dir: Direction) -> KVIter<'_> {
+        let backward = matches!(dir, Direction::Backward);
+        let mut iter = self.iter(col, dir);
+        seek(&mut iter, from, backward);
+        Box::new(iter)
+    }
+}
+
+impl BackendRead for Transaction {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<Box<[u8]>>> {
+        slot!(slice);
+        Ok(Transaction::get(self, col, key, slice)?.map(|p| p.as_ref().into()))
+    }
+
+    fn iter_from(&self, col: Col, from: Option<&[u8]>, dir: Direction) -> KVIter<'_> {
+        let backward = matches!(dir, Direction::Backward);
+        let mut iter = self.iter(col, dir);
+        seek(&mut iter, from, backward);
+        Box::new(iter)
+    }
+}
+
+impl BackendTransaction for Transaction {
+    fn put(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
+        Ok(Transaction::put(self, col, key, value)?)
+    }
+
+    fn delete(&mut self, col: Col, key: &[u8]) -> Result<()> {
+        Ok(Transaction::delete(self, col, key)?)
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        Ok(Transaction::commit(self)?)
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        Ok(Transaction::rollback(self)?)
+    }
+}
@@ -0,0 +1,66 @@
+//! Online backup and restore, backed by RocksDB's backup engine.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use autorocks::{BackupEngine, BackupInfo};
+
+use crate::Store;
+
+impl Store {
+    /// Create an incremental backup in `backup_dir` while the store is in
+    /// use, and verify it.
+    pub fn create_backup(&self, backup_dir: &Path) -> Result<BackupInfo> {
+        let mut engine = open_engine(backup_dir)?;
+        let id = engine.create_new_backup(self.as_inner(), true)?;
+        engine
+            .verify_backup(id)
+            .with_context(|| format!("verify backup {}", id))?;
+        let info = engine
+            .backup_infos()
+            .into_iter()
+            .find(|i| i.id == id)
+            .context("created backup not found")?;
+        log::info!(
+            "created backup {} in {}, size: {}, files: {}",
+            info.id,
+            backup_dir.display(),
+            info.size,
+            info.number_files,
+        );
+        Ok(info)
+    }
+}
+
+pub fn list_backups(backup_dir: &Path) -> Result<Vec<BackupInfo>> {
+    Ok(open_engine(backup_dir)?.backup_infos())
+}
+
+/// Verify sizes and checksums of files of the backup.
+pub fn verify_backup(backup_dir: &Path, backup_id: u32) -> Result<()> {
+    open_engine(backup_dir)?.verify_backup(backup_id)?;
+    Ok(())
+}
+
+/// Restore the backup, or the latest backup if `backup_id` is None, to
+/// `db_dir`.
+///
+/// `db_dir` must not exist or be empty.
+pub fn restore_backup(backup_dir: &Path, backup_id: Option<u32>, db_dir: &Path) -> Result<()> {
+    if db_dir.exists() && db_dir.read_dir()?.next().is_some() {
+        bail!("restore target {} is not empty", db_dir.display());
+    }
+    let engine = open_engine(backup_dir)?;
+    if let Some(id) = backup_id {
+        engine
+            .verify_backup(id)
+            .with_context(|| format!("verify backup {}", id))?;
+    }
+    engine.restore(backup_id, db_dir)?;
+    Ok(())
+}
+
+fn open_engine(backup_dir: &Path) -> Result<BackupEngine> {
+    BackupEngine::open(backup_dir)
+        .with_context(|| format!("open backup dir {}", backup_dir.display()))
+}
@@ -0,0 +1,224 @@
+//! In-memory LRU caches of hot state reads
+//!
+//! Scripts and data are content-addressed and never deleted, so cached
+//! entries never go stale. Account SMT leaves, i.e. account fields like
+//! nonces, balances and script hashes, change with every block applied or
+//! reverted. Each commit writing leaves invalidates the written keys, and
+//! bumps a generation so that snapshots taken before the commit stop using
+//! the cache.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use gw_config::StoreStateCacheConfig;
+use lru::LruCache;
+use serde::Serialize;
+
+use crate::schema::{Col, COLUMN_ACCOUNT_SMT_LEAF, COLUMN_DATA, COLUMN_SCRIPT};
+
+pub(crate) const CACHED_COLUMNS: [Col; 3] = [COLUMN_SCRIPT, COLUMN_DATA, COLUMN_ACCOUNT_SMT_LEAF];
+
+#[derive(Default)]
+pub struct StoreCache {
+    caches: [Option<ColumnCache>; 3],
+}
+
+impl StoreCache {
+    pub fn new(config: &StoreStateCacheConfig) -> Self {
+        Self {
+            caches: [
+                ColumnCache::new("scripts", config.scripts),
+                ColumnCache::new("data", config.data),
+                ColumnCache::new("account_fields", config.account_fields),
+            ],
+        }
+    }
+
+    pub(crate) fn column(&self, col: Col) -> Option<&ColumnCache> {
+        let i = CACHED_COLUMNS.iter().position(|c| *c == col)?;
+        self.caches[i].as_ref()
+    }
+
+    /// Generation of the account state, None if a commit writing it is in
+    /// progress.
+    pub(crate) fn state_generation(&self) -> Option<u64> {
+        self.column(COLUMN_ACCOUNT_SMT_LEAF)
+            .and_then(|c| c.generation())
+    }
+
+    pub fn stats(&self) -> Vec<CacheStat> {
+        self.caches.iter().flatten().map(|c| c.stat()).collect()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CacheStat {
+    pub name: &'static str,
+    pub len: usize,
+    pub capacity: usize,
+    /// Cumulative counters since the store is opened.
+    pub hits: u64,
+    pub misses: u64,
+}
+
+pub(crate) struct ColumnCache {
+    name: &'static str,
+    inner: Mutex<Inner>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct Inner {
+    lru: LruCache<Box<[u8]>, Box<[u8]>>,
+    generation: u64,
+    /// Number of commits writing the column in progress.
+    writers: usize,
+}
+
+impl Inner {
+    fn valid(&self, generation: u64) -> bool {
+        self.writers == 0 && self.generation == generation
+    }
+}
+
+impl ColumnCache {
+    fn new(name: &'static str, capacity: usize) -> Option<Self> {
+        if capacity == 0 {
+            return None;
+        }
+        Some(Self {
+            name,
+            inner: Mutex::new(Inner {
+                lru: LruCache::new(capacity),
+                generation: 0,
+                writers: 0,
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn generation(&self) -> Option<u64> {
+        let inner = self.lock();
+        (inner.writers == 0).then(|| inner.generation)
+    }
+
+    /// Look up the key if the column is unchanged since `generation`.
+    pub(crate) fn get(&self, key: &[u8], generation: u64) -> Option<Box<[u8]>> {
+        let value = {
+            let mut inner = self.lock();
+            if !inner.valid(generation) {
+                return None;
+            }
+            inner.lru.get(key).cloned()
+        };
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    /// Insert a value read from the db if the column is unchanged since
+    /// `generation`, which must be taken before the read.
+    pub(crate) fn insert(&self, key: &[u8], value: Box<[u8]>, generation: u64) {
+        let mut inner = self.lock();
+        if inner.valid(generation) {
+            inner.lru.put(key.into(), value);
+        }
+    }
+
+    /// Stop serving and populating the cache until `end_write`.
+    pub(crate) fn begin_write(&self) {
+        let mut inner = self.lock();
+        inner.writers += 1;
+        inner.generation += 1;
+    }
+
+    /// Drop the written keys, must be called after the commit whether it
+    /// succeeds or not.
+    pub(crate) fn end_write(&self, keys: &[Box<[u8]>]) {
+        let mut inner = self.lock();
+        for key in keys {
+            inner.lru.pop(key);
+        }
+        inner.writers -= 1;
+        inner.generation += 1;
+    }
+
+    fn stat(&self) -> CacheStat {
+        let (len, capacity) = {
+            let inner = self.lock();
+            (inner.lru.len(), inner.lru.cap())
+        };
+        CacheStat {
+            name: self.name,
+            len,
+            capacity,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Writes of a transaction to cached columns.
+#[derive(Default)]
+pub(crate) struct CacheWrites {
+    written: [bool; 3],
+    state_keys: Vec<Box<[u8]>>,
+}
+
+impl CacheWrites {
+    pub(crate) fn record(&mut self, col: Col, key: &[u8]) {
+        if let Some(i) = CACHED_COLUMNS.iter().position(|c| *c == col) {
+            self.written[i] = true;
+            if col == COLUMN_ACCOUNT_SMT_LEAF {
+                self.state_keys.push(key.into());
+            }
+        }
+    }
+
+    /// Uncommitted values must not be cached.
+    pub(crate) fn written(&self, col: Col) -> bool {
+        CACHED_COLUMNS
+            .iter()
+            .position(|c| *c == col)
+            .map_or(false, |i| self.written[i])
+    }
+
+    pub(crate) fn state_keys(&self) -> &[Box<[u8]>] {
+        &self.state_keys
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.written = [false; 3];
+        self.state_keys.clear();
+    }
+}
+
+/// Read through the cache if it is valid at `generation`.
+pub(crate) fn read_through(
+    cache: Option<&ColumnCache>,
+    generation: Option<u64>,
+    key: &[u8],
+    read: impl FnOnce() -> Option<Box<[u8]>>,
+) -> Option<Box<[u8]>> {
+    let (cache, generation) = match (cache, generation) {
+        (Some(cache), Some(generation)) => (cache, generation),
+        _ => return read(),
+    };
+    if let Some(value) = cache.get(key, generation) {
+        return Some(value);
+    }
+    let value = read();
+    if let Some(ref value) = value {
+        cache.insert(key, value.clone(), generation);
+    }
+    value
+}
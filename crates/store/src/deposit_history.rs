@@ -0,0 +1,149 @@
+//! Deposit history of layer2 accounts.
+//!
+//! Deposits are indexed by the account script hash when a block is inserted,
+//! linking each deposit to the L1 cell it was collected from. Only blocks
+//! inserted after the index was introduced are indexed.
+
+use anyhow::{anyhow, Result};
+use autorocks::Direction;
+use gw_types::{h256::H256, packed, prelude::*};
+
+use crate::{
+    schema::COLUMN_DEPOSIT_HISTORY,
+    traits::{
+        chain_store::ChainStore,
+        kv_store::{KVStoreRead, KVStoreWrite},
+    },
+    transaction::StoreTransaction,
+};
+
+const SCRIPT_HASH_LEN: usize = 32;
+const CURSOR_LEN: usize = 12;
+const KEY_LEN: usize = SCRIPT_HASH_LEN + CURSOR_LEN;
+const VALUE_LEN: usize = 32 + 4 + 8 + 16 + 32;
+
+/// Position of a deposit on L2: block number (u64 big endian) ++ index of the
+/// deposit in the block (u32 big endian).
+pub type DepositCursor = [u8; CURSOR_LEN];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositRecord {
+    /// Block which credited the deposit.
+    pub block_number: u64,
+    /// Index of the deposit in the block.
+    pub index: u32,
+    /// Out point of the deposit cell on L1.
+    pub l1_tx_hash: H256,
+    pub l1_output_index: u32,
+    pub capacity: u64,
+    pub amount: u128,
+    pub sudt_script_hash: H256,
+}
+
+impl DepositRecord {
+    pub fn cursor(&self) -> DepositCursor {
+        deposit_cursor(self.block_number, self.index)
+    }
+}
+
+impl StoreTransaction {
+    /// Index deposits of a block. Called when a block is inserted.
+    pub fn insert_block_deposit_history(
+        &mut self,
+        block_number: u64,
+        deposit_info_vec: &packed::DepositInfoVec,
+    ) -> Result<()> {
+        for (index, info) in deposit_info_vec.clone().into_iter().enumerate() {
+            let request = info.request();
+            let key = deposit_history_key(
+                &request.script().hash(),
+                &deposit_cursor(block_number, index as u32),
+            );
+            let out_point = info.cell().out_point();
+            let mut value = Vec::with_capacity(VALUE_LEN);
+            value.extend_from_slice(out_point.tx_hash().as_slice());
+            value.extend_from_slice(out_point.index().as_slice());
+            value.extend_from_slice(request.capacity().as_slice());
+            value.extend_from_slice(request.amount().as_slice());
+            value.extend_from_slice(request.sudt_script_hash().as_slice());
+            self.insert_raw(COLUMN_DEPOSIT_HISTORY, &key, &value)?;
+        }
+        Ok(())
+    }
+
+    /// Remove deposits of a block from the index. Must be called before the
+    /// deposit info vec of the block is deleted.
+    pub fn delete_block_deposit_history(&mut self, block_number: u64) -> Result<()> {
+        let deposit_info_vec = match self.get_block_deposit_info_vec(block_number) {
+            Some(deposit_info_vec) => deposit_info_vec,
+            None => return Ok(()),
+        };
+        for (index, info) in deposit_info_vec.into_iter().enumerate() {
+            let key = deposit_history_key(
+                &info.request().script().hash(),
+                &deposit_cursor(block_number, index as u32),
+            );
+            self.delete(COLUMN_DEPOSIT_HISTORY, &key)?;
+        }
+        Ok(())
+    }
+
+    /// Deposits of an account, newest first, before `cursor` if any.
+    pub fn get_deposit_history(
+        &self,
+        account_script_hash: &H256,
+        cursor: Option<&DepositCursor>,
+        limit: usize,
+    ) -> Result<Vec<DepositRecord>> {
+        let start = deposit_history_key(account_script_hash, cursor.unwrap_or(&[0xff; CURSOR_LEN]));
+        let mut iter = self.get_iter(COLUMN_DEPOSIT_HISTORY, Direction::Backward);
+        iter.seek_for_prev(&start);
+
+        let mut records = Vec::new();
+        for (key, value) in iter {
+            if records.len() >= limit || !key.starts_with(account_script_hash) {
+                break;
+            }
+            // The cursor itself is returned by the previous page.
+            if cursor.is_some() && *key == start[..] {
+                continue;
+            }
+            records.push(decode_deposit_record(&key, &value)?);
+        }
+        Ok(records)
+    }
+}
+
+fn deposit_cursor(block_number: u64, index: u32) -> DepositCursor {
+    let mut cursor = [0u8; CURSOR_LEN];
+    cursor[..8].copy_from_slice(&block_number.to_be_bytes());
+    cursor[8..].copy_from_slice(&index.to_be_bytes());
+    cursor
+}
+
+fn deposit_history_key(account_script_hash: &H256, cursor: &DepositCursor) -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    key[..SCRIPT_HASH_LEN].copy_from_slice(account_script_hash);
+    key[SCRIPT_HASH_LEN..].copy_from_slice(cursor);
+    key
+}
+
+fn decode_deposit_record(key: &[u8], value: &[u8]) -> Result<DepositRecord> {
+    if key.len() != KEY_LEN || value.len() != VALUE_LEN {
+        return Err(anyhow!("invalid deposit history"));
+    }
+    let (block_number, index) = key[SCRIPT_HASH_LEN..].split_at(8);
+    let (l1_tx_hash, value) = value.split_at(32);
+    let (l1_output_index, value) = value.split_at(4);
+    let (capacity, value) = value.split_at(8);
+    let (amount, sudt_script_hash) = value.split_at(16);
+    Ok(DepositRecord {
+        block_number: u64::from_be_bytes(block_number.try_into()?),
+        index: u32::from_be_bytes(index.try_into()?),
+        l1_tx_hash: l1_tx_hash.try_into()?,
+        l1_output_index: u32::from_le_bytes(l1_output_index.try_into()?),
+        capacity: u64::from_le_bytes(capacity.try_into()?),
+        amount: u128::from_le_bytes(amount.try_into()?),
+        sudt_script_hash: sudt_script_hash.try_into()?,
+    })
+}
@@ -0,0 +1,150 @@
+//! Fee rebate accounting of txs submitted through RPC providers.
+//!
+//! The origin of a tx is recorded with the tx when it is pushed into mem pool,
+//! and removed with it when the tx leaves the pool. When the tx is packaged
+//! into a local block, its fee is credited to the origin of that block, so
+//! operators can share fees with RPC providers by block range.
+
+use anyhow::{anyhow, Result};
+use autorocks::Direction;
+use gw_types::{bytes::Bytes, h256::H256, packed, prelude::*};
+
+use crate::{
+    schema::{COLUMN_FEE_REBATE, COLUMN_TX_ORIGIN},
+    traits::kv_store::{KVStoreRead, KVStoreWrite},
+    transaction::StoreTransaction,
+};
+
+const FEE_LEN: usize = 16;
+const TXS_LEN: usize = 8;
+const BLOCK_NUMBER_LEN: usize = 8;
+
+/// Origin of a tx in mem pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOrigin {
+    pub origin: Bytes,
+    /// Fee limit of the tx, i.e. gas price * gas limit for polyjuice txs.
+    pub fee: u128,
+}
+
+/// Fee credited to an origin in a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeRebate {
+    pub block_number: u64,
+    pub origin: Bytes,
+    pub txs: u64,
+    pub fee: u128,
+}
+
+impl StoreTransaction {
+    pub fn insert_tx_origin(&mut self, tx_hash: &H256, origin: &TxOrigin) -> Result<()> {
+        let mut value = Vec::with_capacity(FEE_LEN + origin.origin.len());
+        value.extend_from_slice(&origin.fee.to_le_bytes());
+        value.extend_from_slice(&origin.origin);
+        self.insert_raw(COLUMN_TX_ORIGIN, tx_hash.as_slice(), &value)
+    }
+
+    pub fn get_tx_origin(&self, tx_hash: &H256) -> Result<Option<TxOrigin>> {
+        let value = match self.get(COLUMN_TX_ORIGIN, tx_hash.as_slice()) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        if value.len() < FEE_LEN {
+            return Err(anyhow!("invalid tx origin"));
+        }
+        let (fee, origin) = value.split_at(FEE_LEN);
+        Ok(Some(TxOrigin {
+            origin: Bytes::copy_from_slice(origin),
+            fee: u128::from_le_bytes(fee.try_into()?),
+        }))
+    }
+
+    /// Credit fees of txs with origin to their origins, and remove the tx
+    /// origins. Called when a local block is inserted.
+    pub fn account_block_fee_rebates(&mut self, block: &packed::L2Block) -> Result<()> {
+        let block_number: u64 = block.raw().number().unpack();
+        for tx in block.transactions().into_iter() {
+            let tx_hash = tx.hash();
+            let tx_origin = match self.get_tx_origin(&tx_hash)? {
+                Some(tx_origin) => tx_origin,
+                None => continue,
+            };
+
+            let key = fee_rebate_key(block_number, &tx_origin.origin);
+            let (txs, fee) = match self.get(COLUMN_FEE_REBATE, &key) {
+                Some(value) => decode_fee_rebate_value(&value)?,
+                None => (0, 0),
+            };
+            let value =
+                encode_fee_rebate_value(txs.saturating_add(1), fee.saturating_add(tx_origin.fee));
+            self.insert_raw(COLUMN_FEE_REBATE, &key, &value)?;
+            self.delete(COLUMN_TX_ORIGIN, tx_hash.as_slice())?;
+        }
+        Ok(())
+    }
+
+    /// Fee rebates of blocks in range [from_block, to_block].
+    pub fn get_fee_rebates(&self, from_block: u64, to_block: u64) -> Result<Vec<FeeRebate>> {
+        let mut iter = self.get_iter(COLUMN_FEE_REBATE, Direction::Forward);
+        iter.seek(&from_block.to_be_bytes());
+
+        let mut rebates = Vec::new();
+        for (key, value) in iter {
+            if key.len() < BLOCK_NUMBER_LEN {
+                return Err(anyhow!("invalid fee rebate key"));
+            }
+            let (block_number, origin) = key.split_at(BLOCK_NUMBER_LEN);
+            let block_number = u64::from_be_bytes(block_number.try_into()?);
+            if block_number > to_block {
+                break;
+            }
+            let (txs, fee) = decode_fee_rebate_value(&value)?;
+            rebates.push(FeeRebate {
+                block_number,
+                origin: Bytes::copy_from_slice(origin),
+                txs,
+                fee,
+            });
+        }
+        Ok(rebates)
+    }
+
+    pub fn delete_block_fee_rebates(&mut self, block_number: u64) -> Result<()> {
+        let keys: Vec<_> = {
+            let mut iter = self.get_iter(COLUMN_FEE_REBATE, Direction::Forward);
+            iter.seek(&block_number.to_be_bytes());
+            iter.map(|(key, _)| key)
+                .take_while(|key| key.starts_with(&block_number.to_be_bytes()))
+                .collect()
+        };
+        for key in keys {
+            self.delete(COLUMN_FEE_REBATE, &key)?;
+        }
+        Ok(())
+    }
+}
+
+fn fee_rebate_key(block_number: u64, origin: &[u8]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(BLOCK_NUMBER_LEN + origin.len());
+    key.extend_from_slice(&block_number.to_be_bytes());
+    key.extend_from_slice(origin);
+    key
+}
+
+fn encode_fee_rebate_value(txs: u64, fee: u128) -> [u8; TXS_LEN + FEE_LEN] {
+    let mut value = [0u8; TXS_LEN + FEE_LEN];
+    value[..TXS_LEN].copy_from_slice(&txs.to_le_bytes());
+    value[TXS_LEN..].copy_from_slice(&fee.to_le_bytes());
+    value
+}
+
+fn decode_fee_rebate_value(value: &[u8]) -> Result<(u64, u128)> {
+    if value.len() != TXS_LEN + FEE_LEN {
+        return Err(anyhow!("invalid fee rebate value"));
+    }
+    let (txs, fee) = value.split_at(TXS_LEN);
+    Ok((
+        u64::from_le_bytes(txs.try_into()?),
+        u128::from_le_bytes(fee.try_into()?),
+    ))
+}
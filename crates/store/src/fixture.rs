@@ -0,0 +1,209 @@
+//! State fixtures for tests
+//!
+//! A fixture is a minimal subset of state, e.g. of mainnet, selected by
+//! accounts: their fields, registry addresses, sUDT balances and given storage
+//! slots, with their scripts and data. Export records every state key read
+//! while querying these, so the fixture has exactly the keys the queries need.
+//! State keys are hashed, so storage slots can't be enumerated and have to be
+//! given explicitly.
+//!
+//! Fixtures are JSON with sorted keys, the same selection of the same state
+//! always exports the same file.
+
+use std::{cell::RefCell, collections::BTreeMap};
+
+use anyhow::{anyhow, bail, Context, Result};
+use gw_common::{builtins::ETH_REGISTRY_ACCOUNT_ID, error::Error as StateError, state::State};
+use gw_smt::smt::{SMT, SMTH256};
+use gw_traits::CodeStore;
+use gw_types::{bytes::Bytes, h256::*, packed::Script, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    smt::smt_store::SMTStateStore,
+    snapshot::StoreSnapshot,
+    state::{
+        history::history_state::RWConfig,
+        overlay::{mem_state::MemStateTree, mem_store::MemStore},
+        traits::JournalDB,
+        BlockStateDB, MemStateDB,
+    },
+    traits::chain_store::ChainStore,
+    Store,
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FixtureOptions {
+    pub accounts: Vec<u32>,
+    /// sUDT balances of the accounts to export. The sUDT accounts are
+    /// exported too.
+    pub sudt_ids: Vec<u32>,
+    /// Storage slots, `(account id, raw key)`. Values which are hashes of
+    /// stored data, e.g. contract code, also export the data.
+    pub storage: Vec<(u32, H256)>,
+}
+
+/// Hashes and bytes are hex strings with 0x prefix.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StateFixture {
+    /// The block exported from, for reference.
+    pub block_number: u64,
+    pub block_hash: String,
+    pub account_count: u32,
+    /// Non-zero state values by state key.
+    pub kvs: BTreeMap<String, String>,
+    /// Molecule encoded scripts by script hash.
+    pub scripts: BTreeMap<String, String>,
+    pub data: BTreeMap<String, String>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn from_hex(s: &str) -> Result<Vec<u8>> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    hex::decode(s).with_context(|| format!("invalid hex {}", s))
+}
+
+fn h256_from_hex(s: &str) -> Result<H256> {
+    let bytes = from_hex(s)?;
+    let h: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| anyhow!("invalid 32 bytes hex {}", s))?;
+    Ok(h)
+}
+
+/// Records values of state keys read.
+struct Recorder<'a, S> {
+    inner: &'a S,
+    kvs: RefCell<BTreeMap<H256, H256>>,
+}
+
+impl<S: State> State for Recorder<'_, S> {
+    fn get_raw(&self, key: &H256) -> Result<H256, StateError> {
+        let value = self.inner.get_raw(key)?;
+        self.kvs.borrow_mut().insert(*key, value);
+        Ok(value)
+    }
+
+    fn update_raw(&mut self, _key: H256, _value: H256) -> Result<(), StateError> {
+        Err(StateError::Store)
+    }
+
+    fn get_account_count(&self) -> Result<u32, StateError> {
+        self.inner.get_account_count()
+    }
+
+    fn set_account_count(&mut self, _count: u32) -> Result<(), StateError> {
+        Err(StateError::Store)
+    }
+
+    fn calculate_root(&self) -> Result<H256, StateError> {
+        self.inner.calculate_root()
+    }
+}
+
+impl Store {
+    /// Export a fixture of the state at the last valid tip block.
+    pub fn export_state_fixture(&self, opts: &FixtureOptions) -> Result<StateFixture> {
+        let db = self.begin_transaction();
+        let tip = db.get_last_valid_tip_block()?;
+        let state = BlockStateDB::from_store(db, RWConfig::readonly())?;
+        let recorder = Recorder {
+            inner: &state,
+            kvs: Default::default(),
+        };
+
+        let mut accounts = opts.accounts.clone();
+        accounts.extend(&opts.sudt_ids);
+        accounts.sort_unstable();
+        accounts.dedup();
+        let mut scripts = BTreeMap::new();
+        let mut data = BTreeMap::new();
+        let mut addresses = Vec::new();
+        for &id in &accounts {
+            let script_hash = recorder.get_script_hash(id)?;
+            if script_hash.is_zero() {
+                bail!("account {} not found", id);
+            }
+            recorder.get_nonce(id)?;
+            recorder.get_account_id_by_script_hash(&script_hash)?;
+            let script = state
+                .get_script(&script_hash)
+                .with_context(|| format!("script of account {}", id))?;
+            scripts.insert(script_hash, script);
+            if let Some(address) = recorder
+                .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &script_hash)?
+            {
+                recorder.get_script_hash_by_registry_address(&address)?;
+                addresses.push(address);
+            }
+        }
+        for &sudt_id in &opts.sudt_ids {
+            recorder.get_sudt_total_supply(sudt_id)?;
+            for address in &addresses {
+                recorder.get_sudt_balance(sudt_id, address)?;
+            }
+        }
+        for (id, key) in &opts.storage {
+            let value = recorder.get_value(*id, key.as_slice())?;
+            if !value.is_zero() && recorder.is_data_hash_exist(&value)? {
+                if let Some(bytes) = state.get_data(&value) {
+                    data.insert(value, bytes);
+                }
+            }
+        }
+        let account_count = recorder.get_account_count()?;
+
+        let kvs = recorder.kvs.into_inner();
+        Ok(StateFixture {
+            block_number: tip.raw().number().unpack(),
+            block_hash: to_hex(&tip.hash()),
+            account_count,
+            kvs: kvs
+                .iter()
+                .filter(|(_, v)| !v.is_zero())
+                .map(|(k, v)| (to_hex(k.as_slice()), to_hex(v.as_slice())))
+                .collect(),
+            scripts: scripts
+                .iter()
+                .map(|(k, s)| (to_hex(k.as_slice()), to_hex(s.as_slice())))
+                .collect(),
+            data: data
+                .iter()
+                .map(|(k, d)| (to_hex(k.as_slice()), to_hex(d)))
+                .collect(),
+        })
+    }
+}
+
+impl StateFixture {
+    /// Load the fixture into a state.
+    pub fn load(&self, state: &mut (impl State + CodeStore)) -> Result<()> {
+        for (k, v) in &self.kvs {
+            state.update_raw(h256_from_hex(k)?, h256_from_hex(v)?)?;
+        }
+        state.set_account_count(self.account_count)?;
+        for (hash, script) in &self.scripts {
+            let script = Script::from_slice(&from_hex(script)?)
+                .map_err(|err| anyhow!("invalid script {}: {}", hash, err))?;
+            state.insert_script(h256_from_hex(hash)?, script);
+        }
+        for (hash, data) in &self.data {
+            state.insert_data(h256_from_hex(hash)?, Bytes::from(from_hex(data)?));
+        }
+        Ok(())
+    }
+
+    /// New mem state with only the fixture, on top of `snapshot` which is
+    /// usually of an empty store.
+    pub fn to_mem_state_db(&self, snapshot: StoreSnapshot) -> Result<MemStateDB> {
+        let smt = SMT::new(SMTH256::zero(), SMTStateStore::new(MemStore::new(snapshot)));
+        let mut state = MemStateDB::new(MemStateTree::new(smt, 0));
+        self.load(&mut state)?;
+        state.finalise()?;
+        Ok(state)
+    }
+}
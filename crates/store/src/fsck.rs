@@ -0,0 +1,404 @@
+//! Database integrity checks.
+//!
+//! Checks the main chain index and block contents in a block range, the
+//! account and block SMT roots against the tip global state, and optionally
+//! scans columns of block contents for keys of blocks that are neither on the
+//! main chain nor bad blocks, e.g. contents of reverted blocks.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use autorocks::Direction;
+use gw_smt::smt_h256_ext::SMTH256;
+use gw_types::{
+    h256::H256,
+    packed::{self, TransactionKey},
+    prelude::*,
+};
+use serde::Serialize;
+
+use crate::{
+    schema::{
+        Col, COLUMN_BAD_BLOCK, COLUMN_BLOCK, COLUMN_BLOCK_GLOBAL_STATE, COLUMN_BLOCK_STATE_CHANGES,
+        COLUMN_TRANSACTION, COLUMN_TRANSACTION_INFO, COLUMN_TRANSACTION_RECEIPT, COLUMN_WITHDRAWAL,
+        COLUMN_WITHDRAWAL_INFO,
+    },
+    smt::smt_store::SMTBlockStore,
+    traits::{
+        chain_store::ChainStore,
+        kv_store::{KVStoreIter, KVStoreRead, KVStoreWrite},
+    },
+    Store,
+};
+
+const DELETE_BATCH: usize = 1000;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FsckOptions {
+    /// Defaults to the genesis block.
+    pub from_block: Option<u64>,
+    /// Defaults to the last valid tip block.
+    pub to_block: Option<u64>,
+    /// Scan columns of block contents for orphaned keys. It reads whole
+    /// columns.
+    pub check_orphans: bool,
+    /// Delete orphaned keys found. Implies `check_orphans`.
+    pub delete_orphans: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FsckIssue {
+    /// No block hash indexed for the block number.
+    MissingBlockHash {
+        block_number: u64,
+    },
+    /// The block hash is indexed to another block number.
+    BlockNumberMismatch {
+        block_number: u64,
+        block_hash: String,
+        indexed: Option<u64>,
+    },
+    MissingBlock {
+        block_number: u64,
+        block_hash: String,
+    },
+    /// The stored block has another hash or number.
+    CorruptedBlock {
+        block_number: u64,
+        block_hash: String,
+    },
+    ParentHashMismatch {
+        block_number: u64,
+        parent_block_hash: String,
+        indexed: String,
+    },
+    MissingGlobalState {
+        block_number: u64,
+    },
+    /// Account of the post global state is not the block post account.
+    GlobalStateAccountMismatch {
+        block_number: u64,
+    },
+    /// The block is not in the block SMT.
+    MissingBlockSmtLeaf {
+        block_number: u64,
+    },
+    MissingTransaction {
+        block_number: u64,
+        tx_index: u32,
+    },
+    /// The stored transaction is not the transaction of the block.
+    TransactionMismatch {
+        block_number: u64,
+        tx_index: u32,
+    },
+    /// The transaction info is missing or points to another transaction key.
+    TransactionInfoMismatch {
+        block_number: u64,
+        tx_index: u32,
+        tx_hash: String,
+    },
+    MissingTransactionReceipt {
+        block_number: u64,
+        tx_index: u32,
+    },
+    /// Root of the stored account SMT is not the post account root of the last
+    /// valid tip block.
+    AccountSmtRootMismatch {
+        tip_block_number: u64,
+        expected: String,
+        actual: String,
+    },
+    /// Root of the stored block SMT is not the block root of the tip global
+    /// state.
+    BlockSmtRootMismatch {
+        expected: String,
+        actual: String,
+    },
+    /// Key of a block neither on the main chain nor a bad block.
+    Orphan {
+        column: Col,
+        key: String,
+    },
+}
+
+impl FsckIssue {
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            FsckIssue::Orphan { .. } => "delete orphaned keys with `--delete-orphans`",
+            FsckIssue::AccountSmtRootMismatch { .. } | FsckIssue::BlockSmtRootMismatch { .. } => {
+                "restore the database from a backup, or bootstrap it from a state snapshot"
+            }
+            FsckIssue::MissingTransactionReceipt { .. } => {
+                "receipts are only used by RPCs, re-import the block with `import-block` to restore them"
+            }
+            _ => "restore the database from a backup, or re-import blocks from this one with `import-block`",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FsckFinding {
+    #[serde(flatten)]
+    pub issue: FsckIssue,
+    pub suggestion: &'static str,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct FsckReport {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub findings: Vec<FsckFinding>,
+    pub deleted_orphans: usize,
+}
+
+impl FsckReport {
+    fn push(&mut self, issue: FsckIssue) {
+        let suggestion = issue.suggestion();
+        self.findings.push(FsckFinding { issue, suggestion });
+    }
+
+    /// Issues left, deleted orphans excluded.
+    pub fn issues(&self) -> impl Iterator<Item = &FsckIssue> {
+        let deleted = self.deleted_orphans > 0;
+        self.findings
+            .iter()
+            .map(|f| &f.issue)
+            .filter(move |issue| !(deleted && matches!(issue, FsckIssue::Orphan { .. })))
+    }
+}
+
+impl Store {
+    /// Check integrity of the database. Store must not be written during the
+    /// check, except orphans deleted at the end.
+    pub fn fsck(&self, opts: &FsckOptions) -> Result<FsckReport> {
+        let snap = self.get_snapshot();
+        let tip_block = snap.get_last_valid_tip_block()?;
+        let mut report = FsckReport {
+            from_block: opts.from_block.unwrap_or(0),
+            to_block: match opts.to_block {
+                Some(to) => to,
+                None => tip_block.raw().number().unpack(),
+            },
+            ..Default::default()
+        };
+
+        let oldest = snap.get_oldest_block_number().unwrap_or(0);
+        let snapshot_block = snap.get_snapshot_block_number();
+        let block_smt = SMTBlockStore::new(self.get_snapshot()).to_smt()?;
+        for block_number in report.from_block..=report.to_block {
+            let block_hash = match snap.get_block_hash_by_number(block_number)? {
+                Some(hash) => hash,
+                None => {
+                    report.push(FsckIssue::MissingBlockHash { block_number });
+                    continue;
+                }
+            };
+            let indexed = snap.get_block_number(&block_hash)?;
+            if indexed != Some(block_number) {
+                report.push(FsckIssue::BlockNumberMismatch {
+                    block_number,
+                    block_hash: to_hex(&block_hash),
+                    indexed,
+                });
+            }
+            let leaf = block_smt.get(&packed::RawL2Block::compute_smt_key(block_number).into())?;
+            if leaf != SMTH256::from(block_hash) {
+                report.push(FsckIssue::MissingBlockSmtLeaf { block_number });
+            }
+            // Blocks before the oldest one only have their hashes indexed.
+            if block_number < oldest {
+                continue;
+            }
+
+            let block = match snap.get_block(&block_hash)? {
+                Some(block) => block,
+                None => {
+                    report.push(FsckIssue::MissingBlock {
+                        block_number,
+                        block_hash: to_hex(&block_hash),
+                    });
+                    continue;
+                }
+            };
+            let number: u64 = block.raw().number().unpack();
+            if block.hash() != block_hash || number != block_number {
+                report.push(FsckIssue::CorruptedBlock {
+                    block_number,
+                    block_hash: to_hex(&block_hash),
+                });
+                continue;
+            }
+            if block_number > 0 {
+                let parent_block_hash: H256 = block.raw().parent_block_hash().unpack();
+                if let Some(indexed) = snap.get_block_hash_by_number(block_number - 1)? {
+                    if indexed != parent_block_hash {
+                        report.push(FsckIssue::ParentHashMismatch {
+                            block_number,
+                            parent_block_hash: to_hex(&parent_block_hash),
+                            indexed: to_hex(&indexed),
+                        });
+                    }
+                }
+            }
+            match snap.get_block_post_global_state(&block_hash)? {
+                Some(global_state) => {
+                    if global_state.account().as_slice() != block.raw().post_account().as_slice() {
+                        report.push(FsckIssue::GlobalStateAccountMismatch { block_number });
+                    }
+                }
+                None => report.push(FsckIssue::MissingGlobalState { block_number }),
+            }
+
+            // Blocks of a state snapshot have no receipts.
+            let has_receipts = snapshot_block.map_or(true, |n| block_number > n);
+            for (index, tx) in block.transactions().into_iter().enumerate() {
+                let tx_index = index as u32;
+                let key = TransactionKey::new_builder()
+                    .block_hash(block_hash.pack())
+                    .index(tx_index.pack())
+                    .build();
+                let tx_hash = tx.hash();
+                match snap.get_transaction_by_key(&key)? {
+                    Some(stored) if stored.hash() == tx_hash => {}
+                    Some(_) => report.push(FsckIssue::TransactionMismatch {
+                        block_number,
+                        tx_index,
+                    }),
+                    None => report.push(FsckIssue::MissingTransaction {
+                        block_number,
+                        tx_index,
+                    }),
+                }
+                let info = snap.get_transaction_info(&tx_hash)?;
+                if !info.map_or(false, |info| info.key().as_slice() == key.as_slice()) {
+                    report.push(FsckIssue::TransactionInfoMismatch {
+                        block_number,
+                        tx_index,
+                        tx_hash: to_hex(&tx_hash),
+                    });
+                }
+                if has_receipts && snap.get_transaction_receipt_by_key(&key)?.is_none() {
+                    report.push(FsckIssue::MissingTransactionReceipt {
+                        block_number,
+                        tx_index,
+                    });
+                }
+            }
+        }
+
+        // SMT roots
+        {
+            let expected: H256 = tip_block.raw().post_account().merkle_root().unpack();
+            let actual: H256 = {
+                let mut db = self.begin_transaction();
+                let root = *db.state_smt()?.root();
+                root.into()
+            };
+            if expected != actual {
+                report.push(FsckIssue::AccountSmtRootMismatch {
+                    tip_block_number: tip_block.raw().number().unpack(),
+                    expected: to_hex(&expected),
+                    actual: to_hex(&actual),
+                });
+            }
+
+            let tip_block_hash = snap.get_tip_block_hash()?;
+            if let Some(global_state) = snap.get_block_post_global_state(&tip_block_hash)? {
+                let expected: H256 = global_state.block().merkle_root().unpack();
+                let actual: H256 = (*block_smt.root()).into();
+                if expected != actual {
+                    report.push(FsckIssue::BlockSmtRootMismatch {
+                        expected: to_hex(&expected),
+                        actual: to_hex(&actual),
+                    });
+                }
+            }
+        }
+
+        if opts.check_orphans || opts.delete_orphans {
+            let orphans = find_orphans(&snap)?;
+            for (column, key) in orphans.iter() {
+                report.push(FsckIssue::Orphan {
+                    column: *column,
+                    key: format!("0x{}", hex::encode(key)),
+                });
+            }
+            if opts.delete_orphans {
+                report.deleted_orphans = self.delete_orphans(&orphans)?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn delete_orphans(&self, orphans: &[(Col, Box<[u8]>)]) -> Result<usize> {
+        for batch in orphans.chunks(DELETE_BATCH) {
+            let mut db = self.begin_transaction();
+            for (col, key) in batch {
+                db.delete(*col, key)?;
+            }
+            db.commit()?;
+        }
+        Ok(orphans.len())
+    }
+}
+
+/// Keys of blocks neither indexed nor bad blocks, in columns keyed by block
+/// hash or block hash ++ index, and transaction and withdrawal infos pointing
+/// to such blocks.
+fn find_orphans(snap: &(impl ChainStore + KVStoreIter)) -> Result<Vec<(Col, Box<[u8]>)>> {
+    let mut live = HashMap::new();
+    let mut is_live = |block_hash: H256| -> Result<bool> {
+        if let Some(live) = live.get(&block_hash) {
+            return Ok(*live);
+        }
+        let is_live = snap.get_block_number(&block_hash)?.is_some()
+            || snap.get(COLUMN_BAD_BLOCK, block_hash.as_slice()).is_some();
+        live.insert(block_hash, is_live);
+        Ok(is_live)
+    };
+
+    let mut orphans = Vec::new();
+    for col in [
+        COLUMN_BLOCK,
+        COLUMN_BLOCK_GLOBAL_STATE,
+        COLUMN_BLOCK_STATE_CHANGES,
+        COLUMN_TRANSACTION,
+        COLUMN_TRANSACTION_RECEIPT,
+        COLUMN_WITHDRAWAL,
+    ] {
+        for (key, _) in snap.iter(col, Direction::Forward) {
+            let block_hash: H256 = match key.get(..32) {
+                Some(hash) => hash.try_into().expect("32 bytes"),
+                None => {
+                    orphans.push((col, key));
+                    continue;
+                }
+            };
+            if !is_live(block_hash)? {
+                orphans.push((col, key));
+            }
+        }
+    }
+    for (key, value) in snap.iter(COLUMN_TRANSACTION_INFO, Direction::Forward) {
+        let info = packed::TransactionInfoReader::from_slice(&value);
+        let block_hash = info.map(|info| info.key().block_hash().unpack());
+        if !block_hash.map_or(Ok(false), &mut is_live)? {
+            orphans.push((COLUMN_TRANSACTION_INFO, key));
+        }
+    }
+    for (key, value) in snap.iter(COLUMN_WITHDRAWAL_INFO, Direction::Forward) {
+        let info = packed::WithdrawalInfoReader::from_slice(&value);
+        let block_hash = info.map(|info| info.key().block_hash().unpack());
+        if !block_hash.map_or(Ok(false), &mut is_live)? {
+            orphans.push((COLUMN_WITHDRAWAL_INFO, key));
+        }
+    }
+    Ok(orphans)
+}
+
+fn to_hex(hash: &H256) -> String {
+    format!("0x{}", hex::encode(hash))
+}
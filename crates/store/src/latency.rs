@@ -0,0 +1,129 @@
+//! Per-column latency tracing of store operations.
+//!
+//! Disabled by default. When enabled by [`StoreConfig`], latency of
+//! get/put/delete/iter operations is recorded into histograms labeled by
+//! column, and operations slower than the threshold are logged together with
+//! the key prefix and the caller location.
+
+use std::{
+    panic::Location,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Instant,
+};
+
+use gw_config::StoreConfig;
+use gw_telemetry::metric::{
+    encoding::text::Encode,
+    family::Family,
+    histogram::{exponential_buckets, Histogram},
+    registry::Registry,
+    Lazy,
+};
+
+use crate::schema::Col;
+
+/// Number of key bytes to log for slow operations.
+const SLOW_OP_KEY_PREFIX_LEN: usize = 8;
+
+static LATENCY_METRICS_ENABLED: AtomicBool = AtomicBool::new(false);
+// 0 means slow operation logging is disabled.
+static SLOW_OP_THRESHOLD_MICROS: AtomicU64 = AtomicU64::new(0);
+
+static STORE_LATENCY_METRICS: Lazy<StoreLatencyMetrics> = Lazy::new(StoreLatencyMetrics::default);
+
+pub fn store_latency() -> &'static StoreLatencyMetrics {
+    &STORE_LATENCY_METRICS
+}
+
+/// Apply latency tracing config, called when the store is opened.
+pub fn init(config: &StoreConfig) {
+    LATENCY_METRICS_ENABLED.store(config.latency_metrics, Ordering::Relaxed);
+    let threshold_micros = config
+        .slow_op_threshold_ms
+        .map_or(0, |ms| ms.saturating_mul(1000).max(1));
+    SLOW_OP_THRESHOLD_MICROS.store(threshold_micros, Ordering::Relaxed);
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum StoreOp {
+    Get,
+    Put,
+    Delete,
+    Iter,
+}
+
+pub struct StoreLatencyMetrics {
+    latency: Family<OpLabel, Histogram>,
+}
+
+impl Default for StoreLatencyMetrics {
+    fn default() -> Self {
+        Self {
+            // 1us ~ 1s
+            latency: Family::new_with_constructor(|| {
+                Histogram::new(exponential_buckets(0.000_001, 4.0, 11))
+            }),
+        }
+    }
+}
+
+impl StoreLatencyMetrics {
+    pub fn register(&self, registry: &mut Registry) {
+        registry.register(
+            "op_latency_seconds",
+            "Latency of store operations by column",
+            Box::new(self.latency.clone()),
+        );
+    }
+
+    fn observe(&self, op: StoreOp, col: Col, seconds: f64) {
+        self.latency
+            .get_or_create(&OpLabel { op, col })
+            .observe(seconds);
+    }
+}
+
+/// Run a store operation, record its latency and log it if it is slow.
+#[inline]
+#[track_caller]
+pub(crate) fn trace_op<T>(op: StoreOp, col: Col, key: &[u8], f: impl FnOnce() -> T) -> T {
+    let metrics_enabled = LATENCY_METRICS_ENABLED.load(Ordering::Relaxed);
+    let threshold_micros = SLOW_OP_THRESHOLD_MICROS.load(Ordering::Relaxed);
+    if !metrics_enabled && threshold_micros == 0 {
+        return f();
+    }
+
+    let t = Instant::now();
+    let ret = f();
+    let elapsed = t.elapsed();
+
+    if metrics_enabled {
+        store_latency().observe(op, col, elapsed.as_secs_f64());
+    }
+    if threshold_micros > 0 && elapsed.as_micros() >= threshold_micros as u128 {
+        let prefix = &key[..key.len().min(SLOW_OP_KEY_PREFIX_LEN)];
+        log::warn!(
+            "[store] slow {:?} col {} key {} ({} bytes) took {}us, caller {}",
+            op,
+            col,
+            hex::encode(prefix),
+            key.len(),
+            elapsed.as_micros(),
+            Location::caller(),
+        );
+    }
+    ret
+}
+
+#[derive(Clone, Hash, PartialEq, Eq)]
+struct OpLabel {
+    op: StoreOp,
+    col: Col,
+}
+
+// Manual impl because usize does not implement Encode.
+impl Encode for OpLabel {
+    fn encode(&self, writer: &mut dyn std::io::Write) -> Result<(), std::io::Error> {
+        write!(writer, "op=\"{:?}\",col=\"{}\"", self.op, self.col)
+    }
+}
@@ -1,6 +1,15 @@
 pub extern crate autorocks;
 
+pub mod backend;
+pub mod backup;
+pub mod cache;
 pub mod chain_view;
+pub mod deposit_history;
+pub mod fee_rebate;
+pub mod fixture;
+pub mod fsck;
+pub mod latency;
+pub mod log_index;
 pub mod mem_pool_state;
 pub mod migrate;
 pub mod readonly;
@@ -8,11 +17,12 @@ pub mod schema;
 pub mod smt;
 pub mod snapshot;
 pub mod state;
+pub mod state_proof;
 mod store_impl;
 pub mod traits;
 pub mod transaction;
 
-pub use store_impl::{CfMemStat, Store};
+pub use store_impl::{CfMemStat, CfStat, DbStat, Store, WriteStall};
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,213 @@
+//! Index of polyjuice user logs by address and by topic.
+//!
+//! Logs are indexed when a block is inserted with its tx receipts, so that
+//! logs of an address or a topic in a block range are found without scanning
+//! all receipts of the range.
+
+use anyhow::Result;
+use autorocks::Direction;
+use gw_types::{
+    h256::H256,
+    packed::{self, TransactionKey},
+    prelude::*,
+};
+
+use crate::{
+    schema::COLUMN_LOG_INDEX,
+    traits::{
+        chain_store::ChainStore,
+        kv_store::{KVStoreRead, KVStoreWrite},
+    },
+    transaction::StoreTransaction,
+};
+
+/// Service flag of polyjuice user logs, i.e. EVM logs.
+const GW_LOG_POLYJUICE_USER: u8 = 0x3;
+const ADDRESS_PREFIX: u8 = 0;
+const TOPIC_PREFIX: u8 = 1;
+const CURSOR_LEN: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogIndexKey {
+    Address([u8; 20]),
+    Topic(H256),
+}
+
+impl LogIndexKey {
+    fn prefix(&self) -> Vec<u8> {
+        let mut prefix = Vec::with_capacity(33);
+        match self {
+            LogIndexKey::Address(address) => {
+                prefix.push(ADDRESS_PREFIX);
+                prefix.extend_from_slice(address);
+            }
+            LogIndexKey::Topic(topic) => {
+                prefix.push(TOPIC_PREFIX);
+                prefix.extend_from_slice(topic.as_slice());
+            }
+        }
+        prefix
+    }
+}
+
+/// Position of a log: block number (u64 big endian) ++ index of the tx in the
+/// block (u32 big endian) ++ index of the log in the tx receipt (u32 big
+/// endian).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LogCursor {
+    pub block_number: u64,
+    pub tx_index: u32,
+    pub log_index: u32,
+}
+
+impl LogCursor {
+    fn to_bytes(self) -> [u8; CURSOR_LEN] {
+        let mut cursor = [0u8; CURSOR_LEN];
+        cursor[..8].copy_from_slice(&self.block_number.to_be_bytes());
+        cursor[8..12].copy_from_slice(&self.tx_index.to_be_bytes());
+        cursor[12..].copy_from_slice(&self.log_index.to_be_bytes());
+        cursor
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != CURSOR_LEN {
+            return None;
+        }
+        Some(Self {
+            block_number: u64::from_be_bytes(bytes[..8].try_into().ok()?),
+            tx_index: u32::from_be_bytes(bytes[8..12].try_into().ok()?),
+            log_index: u32::from_be_bytes(bytes[12..].try_into().ok()?),
+        })
+    }
+}
+
+impl StoreTransaction {
+    /// Index logs of a block. Called when a block is inserted with receipts.
+    pub fn insert_block_log_index(
+        &mut self,
+        block_number: u64,
+        tx_receipts: &[packed::TxReceipt],
+    ) -> Result<()> {
+        for (key, cursor) in block_log_index(block_number, tx_receipts) {
+            self.insert_raw(COLUMN_LOG_INDEX, &index_key(&key, &cursor), &[])?;
+        }
+        Ok(())
+    }
+
+    /// Remove logs of a block from the index. Must be called before the tx
+    /// receipts of the block are deleted.
+    pub fn delete_block_log_index(&mut self, block: &packed::L2Block) -> Result<()> {
+        let block_hash = block.hash();
+        let tx_receipts = (0..block.transactions().len())
+            .map(|index| {
+                let key = TransactionKey::new_builder()
+                    .block_hash(block_hash.pack())
+                    .index(index.pack())
+                    .build();
+                self.get_transaction_receipt_by_key(&key)
+            })
+            .collect::<Result<Option<Vec<_>>>>()?;
+        let tx_receipts = match tx_receipts {
+            Some(tx_receipts) => tx_receipts,
+            // Not indexed.
+            None => return Ok(()),
+        };
+        for (key, cursor) in block_log_index(block.raw().number().unpack(), &tx_receipts) {
+            self.delete(COLUMN_LOG_INDEX, &index_key(&key, &cursor))?;
+        }
+        Ok(())
+    }
+
+    /// Logs of `key` in blocks `from_block..=to_block`, oldest first, at most
+    /// `limit` ones.
+    pub fn get_log_cursors(
+        &self,
+        key: &LogIndexKey,
+        from_block: u64,
+        to_block: u64,
+        limit: usize,
+    ) -> Vec<LogCursor> {
+        let prefix = key.prefix();
+        let start = LogCursor {
+            block_number: from_block,
+            tx_index: 0,
+            log_index: 0,
+        };
+        let mut iter = self.get_iter(COLUMN_LOG_INDEX, Direction::Forward);
+        iter.seek(&index_key(key, &start));
+
+        let mut cursors = Vec::new();
+        for (k, _) in iter {
+            if cursors.len() >= limit || !k.starts_with(&prefix) {
+                break;
+            }
+            let cursor = match LogCursor::from_bytes(&k[prefix.len()..]) {
+                Some(cursor) => cursor,
+                None => break,
+            };
+            if cursor.block_number > to_block {
+                break;
+            }
+            cursors.push(cursor);
+        }
+        cursors
+    }
+}
+
+fn index_key(key: &LogIndexKey, cursor: &LogCursor) -> Vec<u8> {
+    let mut index_key = key.prefix();
+    index_key.extend_from_slice(&cursor.to_bytes());
+    index_key
+}
+
+fn block_log_index(
+    block_number: u64,
+    tx_receipts: &[packed::TxReceipt],
+) -> Vec<(LogIndexKey, LogCursor)> {
+    let mut index = Vec::new();
+    for (tx_index, receipt) in tx_receipts.iter().enumerate() {
+        for (log_index, log) in receipt.logs().into_iter().enumerate() {
+            let (address, topics) = match parse_user_log(&log) {
+                Some(parsed) => parsed,
+                None => continue,
+            };
+            let cursor = LogCursor {
+                block_number,
+                tx_index: tx_index as u32,
+                log_index: log_index as u32,
+            };
+            index.push((LogIndexKey::Address(address), cursor));
+            for topic in topics {
+                index.push((LogIndexKey::Topic(topic), cursor));
+            }
+        }
+    }
+    // A topic may appear more than once in a log.
+    index.sort_unstable();
+    index.dedup();
+    index
+}
+
+/// Address and topics of a polyjuice user log: address (20 bytes) ++ data
+/// size (u32 little endian) ++ data ++ topics count (u32 little endian) ++
+/// topics.
+pub fn parse_user_log(log: &packed::LogItem) -> Option<([u8; 20], Vec<H256>)> {
+    if u8::from(log.service_flag()) != GW_LOG_POLYJUICE_USER {
+        return None;
+    }
+    let data = log.data().raw_data();
+    let address: [u8; 20] = data.get(..20)?.try_into().ok()?;
+    let data_size = u32::from_le_bytes(data.get(20..24)?.try_into().ok()?) as usize;
+    let offset = 24usize.checked_add(data_size)?;
+    let topics_count =
+        u32::from_le_bytes(data.get(offset..offset.checked_add(4)?)?.try_into().ok()?) as usize;
+    let topics = data.get(offset + 4..)?;
+    if topics.len() != topics_count.checked_mul(32)? {
+        return None;
+    }
+    let topics = topics
+        .chunks_exact(32)
+        .map(|t| t.try_into().expect("32 bytes"))
+        .collect();
+    Some((address, topics))
+}
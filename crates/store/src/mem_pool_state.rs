@@ -1,5 +1,5 @@
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU64, Ordering},
     Arc,
 };
 
@@ -8,7 +8,10 @@ use gw_types::packed::{self, BlockInfo};
 
 use crate::{
     snapshot::StoreSnapshot,
-    state::{overlay::mem_store::MemStore, MemStateDB},
+    state::{
+        overlay::mem_store::{MemStore, OverlaySize},
+        MemStateDB,
+    },
 };
 
 pub const META_MEM_BLOCK_INFO: &[u8] = b"MEM_BLOCK_INFO";
@@ -26,6 +29,7 @@ pub struct Shared {
 pub struct MemPoolState {
     inner: ArcSwap<Shared>,
     completed_initial_syncing: AtomicBool,
+    version: AtomicU64,
 }
 
 impl MemPoolState {
@@ -36,6 +40,7 @@ impl MemPoolState {
                 mem_block: None,
             })),
             completed_initial_syncing: AtomicBool::new(completed_initial_syncing),
+            version: AtomicU64::new(0),
         }
     }
 
@@ -71,6 +76,18 @@ impl MemPoolState {
             .clone()
     }
 
+    /// Size of the state changes of the mem block, which are only in memory
+    /// until the block is produced.
+    pub fn overlay_size(&self) -> OverlaySize {
+        self.inner
+            .load()
+            .state_db
+            .inner_smt_tree()
+            .store()
+            .inner_store()
+            .overlay_size()
+    }
+
     /// Load shared
     pub fn load_shared(&self) -> Shared {
         Shared::clone(&self.inner.load())
@@ -79,6 +96,13 @@ impl MemPoolState {
     /// Store shared
     pub fn store_shared(&self, shared: Arc<Shared>) {
         self.inner.store(shared);
+        self.version.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Incremented each time the state is replaced, for invalidating results
+    /// computed from an old state.
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::SeqCst)
     }
 
     pub fn completed_initial_syncing(&self) -> bool {
@@ -2,7 +2,11 @@
 // And check present db version is still compatible. Godwoken must run on a valid db.
 // If godwoken with an advanced verion runs on an old db, this is the time we can run migrations.
 
-use std::{cmp::Ordering, collections::BTreeMap};
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
 
 use anyhow::{bail, Result};
 use autorocks::{
@@ -10,6 +14,7 @@ use autorocks::{
     TransactionDb,
 };
 use gw_config::StoreConfig;
+use gw_types::{packed::TransactionKey, prelude::*};
 
 use crate::{
     schema::{
@@ -17,6 +22,8 @@ use crate::{
         META_TIP_BLOCK_HASH_KEY, MIGRATION_VERSION_KEY, REMOVED_COLUMN_BLOCK_DEPOSIT_REQUESTS,
         REMOVED_COLUMN_L2BLOCK_COMMITTED_INFO,
     },
+    traits::chain_store::ChainStore,
+    transaction::StoreTransaction,
     Store,
 };
 
@@ -35,7 +42,7 @@ pub fn open_or_create_db(config: &StoreConfig, factory: MigrationFactory) -> Res
                      so that the current executable binary couldn't open this database.\n\
                      Please download the latest executable binary."
                 );
-                bail!("The database is created by a higher version executable binary");
+                bail!(DOWNGRADE_ERROR);
             }
             Ordering::Equal => Ok(Store::open(config, COLUMNS)?.into_inner()),
             Ordering::Less => {
@@ -55,6 +62,51 @@ pub fn open_or_create_db(config: &StoreConfig, factory: MigrationFactory) -> Res
     }
 }
 
+/// Plan the migrations [`open_or_create_db`] would run, without running them.
+///
+/// Like [`open_or_create_db`], it creates missing column families of an
+/// existing database, but doesn't write any data.
+pub fn dry_run_migrations(
+    config: &StoreConfig,
+    factory: &MigrationFactory,
+) -> Result<MigrationPlan> {
+    let target_version = factory.last_db_version().map(str::to_string);
+    let read_only_db = match DbOptions::new(&config.path, 1).open_read_only() {
+        Ok(db) => db,
+        Err(e) if e.sub_code == Status_SubCode::kPathNotFound => {
+            // A new database is initialized to the last version.
+            return Ok(MigrationPlan {
+                db_version: None,
+                target_version,
+                migrations: Vec::new(),
+            });
+        }
+        Err(e) => bail!(e),
+    };
+
+    match check_readonly_db_version(&read_only_db, factory.last_db_version())? {
+        Ordering::Greater => bail!(DOWNGRADE_ERROR),
+        Ordering::Equal => {
+            slot!(slice);
+            let db_version = read_only_db
+                .get(read_only_db.default_col(), MIGRATION_VERSION_KEY, slice)?
+                .map(|v| String::from_utf8_lossy(&v).into_owned());
+            Ok(MigrationPlan {
+                db_version,
+                target_version,
+                migrations: Vec::new(),
+            })
+        }
+        Ordering::Less => {
+            drop(read_only_db);
+            let db = Store::open(config, COLUMNS)?.into_inner();
+            factory.plan(&db)
+        }
+    }
+}
+
+const DOWNGRADE_ERROR: &str = "The database is created by a higher version executable binary";
+
 //TODO: Replace with migration db version when we have our first migration impl.
 pub(crate) fn init_db_version(db: &TransactionDb, db_ver: Option<&str>) -> Result<()> {
     if let Some(db_ver) = db_ver {
@@ -64,6 +116,14 @@ pub(crate) fn init_db_version(db: &TransactionDb, db_ver: Option<&str>) -> Resul
     Ok(())
 }
 
+fn get_db_version(db: &TransactionDb) -> Result<Option<String>> {
+    slot!(slice);
+    let version = db
+        .get(db.default_col(), MIGRATION_VERSION_KEY, slice)?
+        .map(|v| String::from_utf8(v.to_vec()).expect("version bytes to utf8"));
+    Ok(version)
+}
+
 fn check_readonly_db_version(db: &ReadOnlyDb, db_ver: Option<&str>) -> Result<Ordering> {
     slot!(slice);
     let version = match db.get(db.default_col(), MIGRATION_VERSION_KEY, slice)? {
@@ -97,6 +157,61 @@ pub trait Migration {
     fn migrate(&self, db: TransactionDb) -> Result<TransactionDb>;
     // Version can be genereated with: date '+%Y%m%d%H%M%S'
     fn version(&self) -> &str;
+    /// Check whether the migration can run on the db, e.g. some migrations
+    /// refuse to run on existing data. Used by dry runs.
+    fn check(&self, _db: &TransactionDb) -> Result<()> {
+        Ok(())
+    }
+    /// Estimate the work of the migration without changing the db. Used by
+    /// dry runs.
+    fn estimate(&self, _db: &TransactionDb) -> Result<MigrationEstimate> {
+        Ok(MigrationEstimate::default())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MigrationEstimate {
+    /// Rows, e.g. blocks, the migration processes. None if unknown.
+    pub rows: Option<u64>,
+    /// None if unknown.
+    pub duration: Option<Duration>,
+}
+
+impl MigrationEstimate {
+    pub fn negligible() -> Self {
+        Self {
+            rows: Some(0),
+            duration: Some(Duration::ZERO),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PlannedMigration {
+    pub version: String,
+    pub estimate: MigrationEstimate,
+    /// Why the migration would fail, see [`Migration::check`].
+    pub blocker: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct MigrationPlan {
+    /// None if absent.
+    pub db_version: Option<String>,
+    pub target_version: Option<String>,
+    /// Migrations to run, in order.
+    pub migrations: Vec<PlannedMigration>,
+}
+
+impl MigrationPlan {
+    /// Estimated downtime, None if the duration of some migration is unknown.
+    pub fn estimated_duration(&self) -> Option<Duration> {
+        self.migrations.iter().map(|m| m.estimate.duration).sum()
+    }
+
+    pub fn is_blocked(&self) -> bool {
+        self.migrations.iter().any(|m| m.blocker.is_some())
+    }
 }
 
 struct DefaultMigration;
@@ -108,15 +223,16 @@ impl Migration for DefaultMigration {
     fn version(&self) -> &str {
         return "20211229181750";
     }
+    fn estimate(&self, _db: &TransactionDb) -> Result<MigrationEstimate> {
+        Ok(MigrationEstimate::negligible())
+    }
 }
 
 struct DecoupleBlockProducingSubmissionAndConfirmationMigration;
 
 impl Migration for DecoupleBlockProducingSubmissionAndConfirmationMigration {
     fn migrate(&self, mut db: TransactionDb) -> Result<TransactionDb> {
-        if db.iter(COLUMN_BLOCK, Direction::Forward).next().is_some() {
-            bail!("Cannot migrate a database with existing data to version 20220517. You have to deploy a new node");
-        }
+        self.check(&db)?;
 
         db.drop_cf(REMOVED_COLUMN_L2BLOCK_COMMITTED_INFO)?;
         db.drop_cf(REMOVED_COLUMN_BLOCK_DEPOSIT_REQUESTS)?;
@@ -125,12 +241,31 @@ impl Migration for DecoupleBlockProducingSubmissionAndConfirmationMigration {
     fn version(&self) -> &str {
         "20220517"
     }
+    fn check(&self, db: &TransactionDb) -> Result<()> {
+        if db.iter(COLUMN_BLOCK, Direction::Forward).next().is_some() {
+            bail!("Cannot migrate a database with existing data to version 20220517. You have to deploy a new node");
+        }
+        Ok(())
+    }
+    fn estimate(&self, _db: &TransactionDb) -> Result<MigrationEstimate> {
+        Ok(MigrationEstimate::negligible())
+    }
 }
 
 struct BadBlockColumnMigration;
 
 impl Migration for BadBlockColumnMigration {
     fn migrate(&self, mut db: TransactionDb) -> Result<TransactionDb> {
+        self.check(&db)?;
+
+        // Clear this reused column.
+        db.drop_cf(COLUMN_BAD_BLOCK)?;
+        Ok(db)
+    }
+    fn version(&self) -> &str {
+        "20221024"
+    }
+    fn check(&self, db: &TransactionDb) -> Result<()> {
         // Check that there are no bad blocks.
         slot!(slice1, slice2);
         let tip = db.get(COLUMN_META, META_TIP_BLOCK_HASH_KEY, slice1)?;
@@ -138,15 +273,104 @@ impl Migration for BadBlockColumnMigration {
         if tip.as_deref() != valid_tip.as_deref() {
             bail!("Cannot migrate to version 20221024 when there are bad blocks. You have to rewind or revert first");
         }
-        drop(tip);
-        drop(valid_tip);
+        Ok(())
+    }
+    fn estimate(&self, _db: &TransactionDb) -> Result<MigrationEstimate> {
+        Ok(MigrationEstimate::negligible())
+    }
+}
 
-        // Clear this reused column.
-        db.drop_cf(COLUMN_BAD_BLOCK)?;
-        Ok(db)
+/// Backfill the log index of existing blocks.
+struct LogIndexMigration;
+
+impl LogIndexMigration {
+    const BATCH_BLOCKS: u64 = 1000;
+    const SAMPLE_BLOCKS: u64 = 100;
+
+    /// Blocks to index, None if there is no block.
+    fn block_range(store: &Store) -> Option<(u64, u64)> {
+        let tip: u64 = store
+            .get_last_valid_tip_block()
+            .ok()?
+            .raw()
+            .number()
+            .unpack();
+        let oldest = store.get_oldest_block_number().unwrap_or(0);
+        Some((oldest, tip))
+    }
+
+    fn index_block(store: &Store, tx_db: &mut StoreTransaction, number: u64) -> Result<()> {
+        let block = match store.get_block_hash_by_number(number)? {
+            Some(hash) => store.get_block(&hash)?,
+            None => None,
+        };
+        let block = match block {
+            Some(block) => block,
+            None => bail!("block #{} not found", number),
+        };
+        let tx_receipts = (0..block.transactions().len())
+            .map(|index| {
+                let key = TransactionKey::new_builder()
+                    .block_hash(block.hash().pack())
+                    .index(index.pack())
+                    .build();
+                store.get_transaction_receipt_by_key(&key)
+            })
+            .collect::<Result<Option<Vec<_>>>>()?;
+        // Receipts are not available for blocks of a state snapshot.
+        if let Some(tx_receipts) = tx_receipts {
+            tx_db.insert_block_log_index(number, &tx_receipts)?;
+        }
+        Ok(())
+    }
+}
+
+impl Migration for LogIndexMigration {
+    fn migrate(&self, db: TransactionDb) -> Result<TransactionDb> {
+        let store = Store::new(db);
+        let (oldest, tip) = match Self::block_range(&store) {
+            Some(range) => range,
+            None => return Ok(store.into_inner()),
+        };
+        log::info!("index logs of blocks {}..={}", oldest, tip);
+
+        let mut tx_db = store.begin_transaction();
+        for number in oldest..=tip {
+            Self::index_block(&store, &mut tx_db, number)?;
+            if number % Self::BATCH_BLOCKS == 0 || number == tip {
+                tx_db.commit()?;
+                tx_db = store.begin_transaction();
+                log::info!("indexed logs of blocks up to #{}", number);
+            }
+        }
+        drop(tx_db);
+        Ok(store.into_inner())
     }
     fn version(&self) -> &str {
-        "20221024"
+        "20230301"
+    }
+    fn estimate(&self, db: &TransactionDb) -> Result<MigrationEstimate> {
+        let store = Store::new(db.clone());
+        let (oldest, tip) = match Self::block_range(&store) {
+            Some(range) => range,
+            None => return Ok(MigrationEstimate::negligible()),
+        };
+        // Index the latest blocks without committing to measure the speed.
+        let sample_from = oldest.max(tip.saturating_sub(Self::SAMPLE_BLOCKS - 1));
+        let mut tx_db = store.begin_transaction();
+        let started = Instant::now();
+        for number in sample_from..=tip {
+            Self::index_block(&store, &mut tx_db, number)?;
+        }
+        let elapsed = started.elapsed();
+        tx_db.rollback()?;
+
+        let blocks = tip - oldest + 1;
+        let sampled = tip - sample_from + 1;
+        Ok(MigrationEstimate {
+            rows: Some(blocks),
+            duration: Some(elapsed.mul_f64(blocks as f64 / sampled as f64)),
+        })
     }
 }
 
@@ -156,6 +380,14 @@ pub struct SMTTrieMigrationPlaceHolder;
 #[cfg(feature = "smt-trie")]
 impl Migration for SMTTrieMigrationPlaceHolder {
     fn migrate(&self, db: TransactionDb) -> Result<TransactionDb> {
+        self.check(&db)?;
+        Ok(db)
+    }
+    fn version(&self) -> &str {
+        // Use a very large version so that enabling smt-trie feature always needs migration.
+        "9999-20221125-smt-trie"
+    }
+    fn check(&self, db: &TransactionDb) -> Result<()> {
         use crate::schema::{
             COLUMN_ACCOUNT_SMT_LEAF, COLUMN_BLOCK_SMT_LEAF, COLUMN_REVERTED_BLOCK_SMT_LEAF,
         };
@@ -169,7 +401,7 @@ impl Migration for SMTTrieMigrationPlaceHolder {
         .iter()
         .all(|col| db.iter(*col, Direction::Forward).next().is_none());
         if smts_all_empty {
-            return Ok(db);
+            return Ok(());
         }
 
         bail!(
@@ -177,9 +409,8 @@ impl Migration for SMTTrieMigrationPlaceHolder {
             Self.version(),
         );
     }
-    fn version(&self) -> &str {
-        // Use a very large version so that enabling smt-trie feature always needs migration.
-        "9999-20221125-smt-trie"
+    fn estimate(&self, _db: &TransactionDb) -> Result<MigrationEstimate> {
+        Ok(MigrationEstimate::negligible())
     }
 }
 
@@ -194,6 +425,7 @@ pub fn init_migration_factory() -> MigrationFactory {
     factory.insert(Box::new(
         DecoupleBlockProducingSubmissionAndConfirmationMigration,
     ));
+    factory.insert(Box::new(LogIndexMigration));
     #[cfg(feature = "smt-trie")]
     factory.insert(Box::new(SMTTrieMigrationPlaceHolder));
     factory
@@ -215,13 +447,12 @@ impl MigrationFactory {
     }
 
     fn migrate(&self, db: TransactionDb) -> Result<TransactionDb> {
-        slot!(slice);
-        let db_version = db
-            .get(db.default_col(), MIGRATION_VERSION_KEY, slice)?
-            .map(|v| String::from_utf8(v.to_vec()).expect("version bytes to utf8"))
-            .unwrap_or_else(|| "".to_string());
+        let db_version = get_db_version(&db)?;
+        if db_version.as_deref() > self.last_db_version() {
+            bail!(DOWNGRADE_ERROR);
+        }
         let mut db = db;
-        let v = db_version.as_str();
+        let v = db_version.as_deref().unwrap_or_default();
         let mut last_version = None;
         for (mv, migration) in &self.migration_map {
             let mv = mv.as_str();
@@ -237,6 +468,31 @@ impl MigrationFactory {
         Ok(db)
     }
 
+    /// Migrations [`Self::migrate`] would run on the db, with their estimates
+    /// and check results.
+    pub fn plan(&self, db: &TransactionDb) -> Result<MigrationPlan> {
+        let db_version = get_db_version(db)?;
+        if db_version.as_deref() > self.last_db_version() {
+            bail!(DOWNGRADE_ERROR);
+        }
+        let v = db_version.as_deref().unwrap_or_default();
+        let mut migrations = Vec::new();
+        for (mv, migration) in &self.migration_map {
+            if mv.as_str() > v {
+                migrations.push(PlannedMigration {
+                    version: mv.clone(),
+                    estimate: migration.estimate(db)?,
+                    blocker: migration.check(db).err().map(|e| e.to_string()),
+                });
+            }
+        }
+        Ok(MigrationPlan {
+            db_version,
+            target_version: self.last_db_version().map(str::to_string),
+            migrations,
+        })
+    }
+
     fn last_db_version(&self) -> Option<&str> {
         self.migration_map.values().last().map(|m| m.version())
     }
@@ -254,6 +510,7 @@ mod tests {
             path: dir.path().to_owned(),
             options_file: None,
             cache_size: None,
+            ..Default::default()
         };
         let old_db = Store::open(&config, COLUMNS)?.into_inner();
         let factory = init_migration_factory();
@@ -280,6 +537,7 @@ mod tests {
             path: dir.path().to_owned(),
             options_file: None,
             cache_size: None,
+            ..Default::default()
         };
         let db = open_or_create_db(&config, init_migration_factory())?;
         {
@@ -297,4 +555,45 @@ mod tests {
         assert_eq!(v, Some(Ok(factory.last_db_version().unwrap().to_string())));
         Ok(())
     }
+
+    #[test]
+    fn test_migration_plan() -> Result<()> {
+        let dir = tempfile::tempdir().expect("create temp dir");
+
+        let config = StoreConfig {
+            path: dir.path().to_owned(),
+            ..Default::default()
+        };
+        // A new database is initialized to the last version.
+        let factory = init_migration_factory();
+        let plan = dry_run_migrations(&config, &factory)?;
+        assert!(plan.db_version.is_none() && plan.migrations.is_empty());
+
+        let db = Store::open(&config, COLUMNS)?.into_inner();
+        let plan = factory.plan(&db)?;
+        assert_eq!(plan.db_version, None);
+        assert_eq!(
+            plan.migrations.iter().map(|m| m.version.as_str()).last(),
+            factory.last_db_version()
+        );
+        assert!(!plan.is_blocked());
+        // Empty db.
+        assert_eq!(plan.estimated_duration(), Some(Duration::ZERO));
+
+        // Nothing to do after migrations.
+        let db = factory.migrate(db)?;
+        let plan = factory.plan(&db)?;
+        assert_eq!(plan.db_version.as_deref(), factory.last_db_version());
+        assert!(plan.migrations.is_empty());
+
+        // Refuse to downgrade.
+        db.put(
+            db.default_col(),
+            MIGRATION_VERSION_KEY,
+            b"99999999999999-higher",
+        )?;
+        assert!(factory.plan(&db).is_err());
+        assert!(factory.migrate(db).is_err());
+        Ok(())
+    }
 }
@@ -1,12 +1,14 @@
 use std::path::Path;
 
 use anyhow::Result;
-use autorocks::{moveit::slot, DbOptions, ReadOnlyDb};
-use gw_types::{from_box_should_be_ok, h256::H256, packed, prelude::*};
+use autorocks::{moveit::slot, DbIterator, DbOptions, Direction, ReadOnlyDb};
 
 use crate::{
-    schema::{Col, COLUMN_REVERTED_BLOCK_SMT_ROOT},
-    traits::{chain_store::ChainStore, kv_store::KVStoreRead},
+    schema::Col,
+    traits::{
+        chain_store::ChainStore,
+        kv_store::{KVIter, KVStoreIter, KVStoreRead},
+    },
 };
 
 #[derive(Clone)]
@@ -24,14 +26,23 @@ impl StoreReadonly {
         Ok(Self::new(db))
     }
 
-    pub fn iter_reverted_block_smt_root(
-        &self,
-        root: H256,
-    ) -> impl Iterator<Item = (H256, Vec<H256>)> + '_ {
-        RervertedBlockHashesIter {
-            snap: self,
-            next_root: root,
-        }
+    /// Open a secondary instance of the db at `path`, which can be used by
+    /// a running node at the same time. Info logs of the secondary instance
+    /// are kept in `secondary_path`.
+    pub fn open_secondary(path: &Path, secondary_path: &Path, columns: usize) -> Result<Self> {
+        let db = DbOptions::new(path, columns).open_as_secondary(secondary_path)?;
+        Ok(Self::new(db))
+    }
+
+    /// Apply new writes of the primary instance. Only for secondary
+    /// instances.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.inner.try_catch_up_with_primary()?;
+        Ok(())
+    }
+
+    pub fn iter(&self, col: Col, dir: Direction) -> DbIterator<&'_ ReadOnlyDb> {
+        self.inner.iter(col, dir)
     }
 }
 
@@ -47,29 +58,8 @@ impl KVStoreRead for StoreReadonly {
     }
 }
 
-pub struct RervertedBlockHashesIter<'a> {
-    snap: &'a StoreReadonly,
-    next_root: H256,
-}
-
-impl<'a> Iterator for RervertedBlockHashesIter<'a> {
-    type Item = (H256, Vec<H256>);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let snap = &self.snap;
-        let root = self.next_root;
-
-        snap.get(COLUMN_REVERTED_BLOCK_SMT_ROOT, root.as_slice())
-            .map(|slice| {
-                let mut block_hashes: Vec<_> =
-                    from_box_should_be_ok!(packed::Byte32VecReader, slice).unpack();
-
-                // First hash is root
-                let last_hash_idx = block_hashes.len().saturating_sub(1);
-                block_hashes.swap(0, last_hash_idx);
-                self.next_root = block_hashes.pop().expect("iter prev reverted block root");
-
-                (root, block_hashes)
-            })
+impl KVStoreIter for StoreReadonly {
+    fn iter(&self, col: Col, dir: Direction) -> KVIter<'_> {
+        Box::new(self.inner.iter(col, dir))
     }
 }
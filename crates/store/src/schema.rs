@@ -3,7 +3,7 @@
 /// Column families alias type
 pub type Col = usize;
 /// Total column number
-pub const COLUMNS: usize = 37;
+pub const COLUMNS: usize = 43;
 /// Column store meta data
 pub const COLUMN_META: Col = 0;
 /// Column store chain index
@@ -82,6 +82,24 @@ pub const COLUMN_BLOCK_SUBMIT_TX_HASH: Col = 7;
 pub const COLUMN_BLOCK_DEPOSIT_INFO_VEC: Col = 16;
 /// block number (in big endian) -> FinalizedCustodianCapacity.
 pub const COLUMN_BLOCK_POST_FINALIZED_CUSTODIAN_CAPACITY: Col = 36;
+/// Tx hash -> fee (u128 little endian) ++ origin.
+///
+/// Origin of txs submitted through RPC providers, removed once the tx is
+/// packaged.
+pub const COLUMN_TX_ORIGIN: Col = 37;
+/// Block number (in big endian) ++ origin -> txs (u64 little endian) ++ fee
+/// (u128 little endian).
+pub const COLUMN_FEE_REBATE: Col = 38;
+/// Column finalized block smt branch
+pub const COLUMN_FINALIZED_BLOCK_SMT_BRANCH: Col = 39;
+/// Column finalized block smt leaf
+pub const COLUMN_FINALIZED_BLOCK_SMT_LEAF: Col = 40;
+/// Account script hash ++ block number (in big endian) ++ deposit index (u32
+/// in big endian) -> L1 out point ++ capacity ++ amount ++ sudt script hash.
+pub const COLUMN_DEPOSIT_HISTORY: Col = 41;
+/// (0 ++ address | 1 ++ topic) ++ block number (in big endian) ++ tx index
+/// (u32 in big endian) ++ log index (u32 in big endian) -> empty.
+pub const COLUMN_LOG_INDEX: Col = 42;
 
 /// chain id
 pub const META_CHAIN_ID_KEY: &[u8] = b"CHAIN_ID";
@@ -98,6 +116,19 @@ pub const META_LAST_VALID_TIP_BLOCK_HASH_KEY: &[u8] = b"LAST_VALID_TIP_BLOCK_HAS
 pub const META_LAST_CONFIRMED_BLOCK_NUMBER_HASH_KEY: &[u8] = b"LAST_CONFIRMED_BLOCK_NUMBER";
 /// track the last submitted l2 block NumberAndHash
 pub const META_LAST_SUBMITTED_BLOCK_NUMBER_HASH_KEY: &[u8] = b"LAST_SUBMITTED_BLOCK_NUMBER";
+/// track the last block number inserted into the finalized block SMT
+pub const META_FINALIZED_BLOCK_SMT_TIP_KEY: &[u8] = b"FINALIZED_BLOCK_SMT_TIP";
+/// For stores bootstrapped from a state snapshot, the oldest block with full
+/// content. Blocks before it only have their hashes indexed.
+pub const META_OLDEST_BLOCK_NUMBER_KEY: &[u8] = b"OLDEST_BLOCK_NUMBER";
+/// For stores bootstrapped from a state snapshot, the snapshot block. State
+/// history before it is not available.
+pub const META_SNAPSHOT_BLOCK_NUMBER_KEY: &[u8] = b"SNAPSHOT_BLOCK_NUMBER";
+/// State history of blocks up to this one is pruned.
+pub const META_PRUNED_BLOCK_NUMBER_KEY: &[u8] = b"PRUNED_BLOCK_NUMBER";
+/// Block whose post state root mismatched the locally computed one. The node
+/// halts until it's cleared.
+pub const META_STATE_MISMATCH_HALT_KEY: &[u8] = b"STATE_MISMATCH_HALT";
 
 /// CHAIN_SPEC_HASH_KEY tracks the hash of chain spec which created current database
 pub const CHAIN_SPEC_HASH_KEY: &[u8] = b"chain-spec-hash";
@@ -1,7 +1,9 @@
 pub mod smt_block;
+pub mod smt_finalized_block;
 pub mod smt_reverted_block;
 pub mod smt_state;
 
 pub use smt_block::SMTBlockStore;
+pub use smt_finalized_block::SMTFinalizedBlockStore;
 pub use smt_reverted_block::SMTRevertedBlockStore;
 pub use smt_state::SMTStateStore;
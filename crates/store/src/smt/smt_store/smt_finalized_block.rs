@@ -0,0 +1,119 @@
+//! Implement SMTStore trait
+//!
+//! Block SMT of finalized blocks only. Finalized blocks are never reverted, so
+//! once it contains blocks `0..=n` its root is the block root of the post
+//! global state of block `n`.
+
+use std::convert::TryInto;
+
+use gw_smt::{
+    smt::SMT,
+    smt_h256_ext::SMTH256,
+    sparse_merkle_tree::{
+        error::Error as SMTError,
+        traits::{StoreReadOps, StoreWriteOps},
+        BranchKey, BranchNode,
+    },
+};
+
+use crate::{
+    schema::{COLUMN_FINALIZED_BLOCK_SMT_BRANCH, COLUMN_FINALIZED_BLOCK_SMT_LEAF},
+    smt::serde::{branch_key_to_vec, branch_node_to_vec, slice_to_branch_node},
+    traits::{
+        chain_store::ChainStore,
+        kv_store::{KVStoreRead, KVStoreWrite},
+    },
+};
+
+pub struct SMTFinalizedBlockStore<DB>(DB);
+
+impl<DB: KVStoreRead + ChainStore> SMTFinalizedBlockStore<DB> {
+    pub fn to_smt(self) -> anyhow::Result<SMT<Self>> {
+        Ok(SMT::new_with_store(self)?)
+    }
+}
+
+impl<DB> SMTFinalizedBlockStore<DB> {
+    pub fn new(store: DB) -> Self {
+        SMTFinalizedBlockStore(store)
+    }
+
+    pub fn inner_store(&self) -> &DB {
+        &self.0
+    }
+
+    pub fn inner_store_mut(&mut self) -> &mut DB {
+        &mut self.0
+    }
+}
+
+impl<DB: KVStoreRead> StoreReadOps<SMTH256> for SMTFinalizedBlockStore<DB> {
+    fn get_branch(&self, branch_key: &BranchKey) -> Result<Option<BranchNode>, SMTError> {
+        match self.0.get(
+            COLUMN_FINALIZED_BLOCK_SMT_BRANCH,
+            &branch_key_to_vec(branch_key),
+        ) {
+            Some(slice) => Ok(Some(slice_to_branch_node(&slice))),
+            None => Ok(None),
+        }
+    }
+
+    fn get_leaf(&self, leaf_key: &SMTH256) -> Result<Option<SMTH256>, SMTError> {
+        match self
+            .0
+            .get(COLUMN_FINALIZED_BLOCK_SMT_LEAF, leaf_key.as_slice())
+        {
+            Some(slice) if 32 == slice.len() => {
+                let leaf: [u8; 32] = slice.as_ref().try_into().unwrap();
+                Ok(Some(SMTH256::from(leaf)))
+            }
+            Some(_) => Err(SMTError::Store("get corrupted leaf".to_string())),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<DB: KVStoreWrite> StoreWriteOps<SMTH256> for SMTFinalizedBlockStore<DB> {
+    fn insert_branch(&mut self, branch_key: BranchKey, branch: BranchNode) -> Result<(), SMTError> {
+        self.0
+            .insert_raw(
+                COLUMN_FINALIZED_BLOCK_SMT_BRANCH,
+                &branch_key_to_vec(&branch_key),
+                &branch_node_to_vec(&branch),
+            )
+            .map_err(|err| SMTError::Store(format!("insert error {}", err)))?;
+
+        Ok(())
+    }
+
+    fn insert_leaf(&mut self, leaf_key: SMTH256, leaf: SMTH256) -> Result<(), SMTError> {
+        self.0
+            .insert_raw(
+                COLUMN_FINALIZED_BLOCK_SMT_LEAF,
+                leaf_key.as_slice(),
+                leaf.as_slice(),
+            )
+            .map_err(|err| SMTError::Store(format!("insert error {}", err)))?;
+
+        Ok(())
+    }
+
+    fn remove_branch(&mut self, branch_key: &BranchKey) -> Result<(), SMTError> {
+        self.0
+            .delete(
+                COLUMN_FINALIZED_BLOCK_SMT_BRANCH,
+                &branch_key_to_vec(branch_key),
+            )
+            .map_err(|err| SMTError::Store(format!("delete error {}", err)))?;
+
+        Ok(())
+    }
+
+    fn remove_leaf(&mut self, leaf_key: &SMTH256) -> Result<(), SMTError> {
+        self.0
+            .delete(COLUMN_FINALIZED_BLOCK_SMT_LEAF, leaf_key.as_slice())
+            .map_err(|err| SMTError::Store(format!("delete error {}", err)))?;
+
+        Ok(())
+    }
+}
@@ -1,17 +1,36 @@
-use autorocks::{moveit::slot, Direction, Snapshot};
+use std::sync::Arc;
+
+use autorocks::{moveit::slot, DbIterator, Direction, Snapshot};
 
 use crate::{
-    schema::{Col, COLUMN_MEM_POOL_TRANSACTION},
-    traits::{chain_store::ChainStore, kv_store::KVStoreRead},
+    cache::{read_through, StoreCache},
+    schema::{Col, COLUMN_ACCOUNT_SMT_LEAF, COLUMN_MEM_POOL_TRANSACTION},
+    traits::{
+        chain_store::ChainStore,
+        kv_store::{KVIter, KVStoreIter, KVStoreRead},
+    },
 };
 
+/// A consistent view of the store. It doesn't keep a transaction open, and
+/// can be held across threads.
 pub struct StoreSnapshot {
     inner: Snapshot,
+    cache: Arc<StoreCache>,
+    /// Generation of the account state cache when the snapshot is taken.
+    state_generation: Option<u64>,
 }
 
 impl StoreSnapshot {
-    pub(crate) fn new(inner: Snapshot) -> Self {
-        Self { inner }
+    pub(crate) fn new(
+        inner: Snapshot,
+        cache: Arc<StoreCache>,
+        state_generation: Option<u64>,
+    ) -> Self {
+        Self {
+            inner,
+            cache,
+            state_generation,
+        }
     }
 }
 
@@ -19,11 +38,25 @@ impl ChainStore for StoreSnapshot {}
 
 impl KVStoreRead for StoreSnapshot {
     fn get(&self, col: Col, key: &[u8]) -> Option<Box<[u8]>> {
-        slot!(slice);
-        self.inner
-            .get(col, key, slice)
-            .expect("db operation should be ok")
-            .map(|p| p.as_ref().into())
+        let cache = self.cache.column(col);
+        let generation = if col == COLUMN_ACCOUNT_SMT_LEAF {
+            self.state_generation
+        } else {
+            cache.and_then(|c| c.generation())
+        };
+        read_through(cache, generation, key, || {
+            slot!(slice);
+            self.inner
+                .get(col, key, slice)
+                .expect("db operation should be ok")
+                .map(|p| p.as_ref().into())
+        })
+    }
+}
+
+impl KVStoreIter for StoreSnapshot {
+    fn iter(&self, col: Col, dir: Direction) -> KVIter<'_> {
+        Box::new(self.inner.iter(col, dir))
     }
 }
 
@@ -34,3 +67,137 @@ impl StoreSnapshot {
             .map(|(k, _)| k)
     }
 }
+
+/// Key bounds of a scan. Keys must satisfy all of the bounds set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScanRange {
+    /// Keys starting with the prefix.
+    pub prefix: Option<Vec<u8>>,
+    /// Inclusive lower bound.
+    pub start: Option<Vec<u8>>,
+    /// Exclusive upper bound.
+    pub end: Option<Vec<u8>>,
+}
+
+impl ScanRange {
+    /// All keys.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn prefix(prefix: &[u8]) -> Self {
+        Self {
+            prefix: Some(prefix.to_vec()),
+            ..Default::default()
+        }
+    }
+
+    /// Keys in `start..end`.
+    pub fn range(start: &[u8], end: &[u8]) -> Self {
+        Self {
+            start: Some(start.to_vec()),
+            end: Some(end.to_vec()),
+            ..Default::default()
+        }
+    }
+
+    fn lower_bound(&self) -> Option<&[u8]> {
+        match (self.prefix.as_deref(), self.start.as_deref()) {
+            (Some(prefix), Some(start)) => Some(prefix.max(start)),
+            (prefix, start) => prefix.or(start),
+        }
+    }
+
+    /// Exclusive.
+    fn upper_bound(&self) -> Option<Vec<u8>> {
+        let prefix_end = self.prefix.as_deref().and_then(prefix_successor);
+        match (prefix_end, self.end.as_ref()) {
+            (Some(prefix_end), Some(end)) => Some(prefix_end.min(end.clone())),
+            (prefix_end, end) => prefix_end.or_else(|| end.cloned()),
+        }
+    }
+
+    fn contains(&self, key: &[u8]) -> bool {
+        self.prefix.as_ref().map_or(true, |p| key.starts_with(p))
+            && self.start.as_ref().map_or(true, |s| key >= s.as_slice())
+            && self.end.as_ref().map_or(true, |e| key < e.as_slice())
+    }
+}
+
+/// The smallest key greater than all keys starting with `prefix`, None if
+/// there is no such key.
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut successor = prefix.to_vec();
+    while let Some(last) = successor.pop() {
+        if last < u8::MAX {
+            successor.push(last + 1);
+            return Some(successor);
+        }
+    }
+    None
+}
+
+/// Iterator of a [`StoreSnapshot::scan`]. Yields key value pairs in the range.
+pub struct ScanIter<'a> {
+    inner: DbIterator<&'a Snapshot>,
+    range: ScanRange,
+    /// A backward scan seeks to the exclusive upper bound, which is skipped
+    /// if it exists.
+    skip: Option<Vec<u8>>,
+    exhausted: bool,
+}
+
+impl Iterator for ScanIter<'_> {
+    type Item = (Box<[u8]>, Box<[u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.exhausted {
+            return None;
+        }
+        let mut item = self.inner.next();
+        if let Some(skip) = self.skip.take() {
+            if matches!(item, Some((ref key, _)) if **key == *skip) {
+                item = self.inner.next();
+            }
+        }
+        match item {
+            // Keys are ordered, so the first key out of the range ends the
+            // scan.
+            Some((key, value)) if self.range.contains(&key) => Some((key, value)),
+            _ => {
+                self.exhausted = true;
+                None
+            }
+        }
+    }
+}
+
+impl StoreSnapshot {
+    /// Scan keys of `col` in `range` in `dir` order.
+    pub fn scan(&self, col: Col, range: ScanRange, dir: Direction) -> ScanIter<'_> {
+        let mut skip = None;
+        let inner = match dir {
+            Direction::Forward => {
+                let mut iter = self.inner.iter(col, Direction::Forward);
+                if let Some(lower_bound) = range.lower_bound() {
+                    iter.seek(lower_bound);
+                }
+                iter
+            }
+            Direction::Backward => {
+                let mut iter = self.inner.iter(col, Direction::Backward);
+                if let Some(upper_bound) = range.upper_bound() {
+                    iter.seek_for_prev(&upper_bound);
+                    skip = Some(upper_bound);
+                }
+                iter
+            }
+        };
+        ScanIter {
+            inner,
+            range,
+            skip,
+            exhausted: false,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! State DB
 
-use std::cell::RefCell;
+use std::{cell::RefCell, collections::HashSet};
 
 use anyhow::Result;
 use gw_common::{error::Error as StateError, state::State};
@@ -72,6 +72,13 @@ pub trait HistoryStateStore {
     type BlockStateRecordKeyIter: IntoIterator<Item = BlockStateRecordKey>;
     fn iter_block_state_record(&self, block_number: u64) -> Self::BlockStateRecordKeyIter;
     fn remove_block_state_record(&mut self, block_number: u64) -> Result<(), anyhow::Error>;
+    /// Remove state records of blocks `first..=last`.
+    fn remove_block_state_records(&mut self, first: u64, last: u64) -> Result<(), anyhow::Error> {
+        for block_number in first..=last {
+            self.remove_block_state_record(block_number)?;
+        }
+        Ok(())
+    }
     fn get_history_state(&self, block_number: u64, state_key: &H256) -> Option<H256>;
     fn record_block_state(
         &mut self,
@@ -90,6 +97,9 @@ impl<T: HistoryStateStore> HistoryStateStore for &mut T {
     fn remove_block_state_record(&mut self, block_number: u64) -> Result<(), anyhow::Error> {
         <T as HistoryStateStore>::remove_block_state_record(self, block_number)
     }
+    fn remove_block_state_records(&mut self, first: u64, last: u64) -> Result<(), anyhow::Error> {
+        <T as HistoryStateStore>::remove_block_state_records(self, first, last)
+    }
     fn get_history_state(&self, block_number: u64, state_key: &H256) -> Option<H256> {
         <T as HistoryStateStore>::get_history_state(self, block_number, state_key)
     }
@@ -112,6 +122,9 @@ impl<T: HistoryStateStore> HistoryStateStore for &RefCell<T> {
     fn remove_block_state_record(&mut self, block_number: u64) -> Result<(), anyhow::Error> {
         <T as HistoryStateStore>::remove_block_state_record(&mut self.borrow_mut(), block_number)
     }
+    fn remove_block_state_records(&mut self, first: u64, last: u64) -> Result<(), anyhow::Error> {
+        <T as HistoryStateStore>::remove_block_state_records(&mut self.borrow_mut(), first, last)
+    }
     fn get_history_state(&self, block_number: u64, state_key: &H256) -> Option<H256> {
         <T as HistoryStateStore>::get_history_state(&self.borrow(), block_number, state_key)
     }
@@ -155,6 +168,10 @@ impl<Store: HistoryStateStore + CodeStore + KVStore> HistoryState<Store> {
         }
     }
 
+    pub fn smt(&self) -> &SMT<SMTStateStore<Store>> {
+        &self.tree
+    }
+
     pub fn get_merkle_state(&self) -> AccountMerkleState {
         let root: H256 = (*self.tree.root()).into();
         AccountMerkleState::new_builder()
@@ -190,6 +207,34 @@ impl<Store: HistoryStateStore + CodeStore + KVStore> HistoryState<Store> {
         Ok(())
     }
 
+    /// Detach state of blocks `first..=last` from state tree, `last` must be
+    /// the tip. Keys touched by these blocks are reset to their values before
+    /// `first` in one batch, instead of block by block.
+    pub fn detach_blocks_state(&mut self, first: u64, last: u64) -> Result<()> {
+        let parent_block_number = first.saturating_sub(1);
+        let state_keys: HashSet<H256> = (first..=last)
+            .flat_map(|block_number| self.db().iter_block_state_record(block_number))
+            .map(|record_key| record_key.state_key())
+            .collect();
+        let reverted_leaves: Vec<_> = state_keys
+            .into_iter()
+            .map(|state_key| {
+                let last_value = self
+                    .db()
+                    .get_history_state(parent_block_number, &state_key)
+                    .unwrap_or_else(H256::zero);
+                (state_key.into(), last_value.into())
+            })
+            .collect();
+        self.tree
+            .update_all(reverted_leaves)
+            .map_err(|err| StateError::SMT(err.to_string()))?;
+
+        self.db_mut().remove_block_state_records(first, last)?;
+
+        Ok(())
+    }
+
     fn db(&self) -> &Store {
         self.tree.store().inner_store()
     }
@@ -257,6 +302,39 @@ impl<Store: KVStore + HistoryStateStore + CodeStore> State for HistoryState<Stor
         Ok(())
     }
 
+    fn update_raw_batch(&mut self, kvs: Vec<(H256, H256)>) -> Result<(), StateError> {
+        if matches!(self.rw_config.write, WriteOpt::Deny) {
+            return Err(StateError::Store);
+        }
+        let leaves = kvs
+            .iter()
+            .map(|(k, v)| ((*k).into(), (*v).into()))
+            .collect();
+        self.tree
+            .update_all(leaves)
+            .map_err(|err| StateError::SMT(err.to_string()))?;
+        if log_enabled!(log::Level::Trace) {
+            for (key, value) in &kvs {
+                let k: Byte32 = key.pack();
+                let v: Byte32 = value.pack();
+                log::trace!(
+                    "[state-trace] update_raw rw_config:{:?} k:{} v:{}",
+                    self.rw_config,
+                    k,
+                    v
+                );
+            }
+        }
+        if let WriteOpt::Block(block_number) = self.rw_config.write {
+            for (key, value) in kvs {
+                self.db_mut()
+                    .record_block_state(block_number, key, value)
+                    .expect("record block state");
+            }
+        }
+        Ok(())
+    }
+
     fn get_account_count(&self) -> Result<u32, StateError> {
         if log_enabled!(log::Level::Trace) {
             log::trace!(
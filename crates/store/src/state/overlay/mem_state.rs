@@ -80,6 +80,14 @@ impl State for MemStateTree {
         Ok(())
     }
 
+    fn update_raw_batch(&mut self, kvs: Vec<(H256, H256)>) -> Result<(), StateError> {
+        let leaves = kvs.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+        self.tree
+            .update_all(leaves)
+            .map_err(|err| StateError::SMT(err.to_string()))?;
+        Ok(())
+    }
+
     fn get_account_count(&self) -> Result<u32, StateError> {
         Ok(self.account_count)
     }
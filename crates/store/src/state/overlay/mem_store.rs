@@ -33,16 +33,45 @@ pub struct MemStore<S> {
     // (column, key) -> value.
     mem: ColumnsKeyValueMap,
     history_mem: KeyValueMapByBlock,
+    size: OverlaySize,
 }
 
+/// Size of the changes held in memory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OverlaySize {
+    /// Number of keys written or deleted, and history records.
+    pub keys: usize,
+    /// Bytes of keys and values.
+    pub bytes: usize,
+}
+
+const HISTORY_RECORD_BYTES: usize = 64;
+
 impl<S> MemStore<S> {
     pub fn new(inner: impl Into<Arc<S>>) -> Self {
         Self {
             inner: inner.into(),
             mem: Default::default(),
             history_mem: Default::default(),
+            size: Default::default(),
         }
     }
+
+    pub fn overlay_size(&self) -> OverlaySize {
+        self.size
+    }
+
+    fn track_write(&mut self, key: &[u8], value: Option<Value<Vec<u8>>>, new_len: usize) {
+        match value {
+            Some(Value::Exist(old)) => self.size.bytes -= old.len(),
+            Some(Value::Deleted) => {}
+            None => {
+                self.size.keys += 1;
+                self.size.bytes += key.len();
+            }
+        }
+        self.size.bytes += new_len;
+    }
 }
 
 impl<S: KVStoreRead> ChainStore for MemStore<S> {}
@@ -81,13 +110,16 @@ impl<S: KVStoreRead> KVStoreRead for MemStore<S> {
 
 impl<S> KVStoreWrite for MemStore<S> {
     fn insert_raw(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
-        self.mem
+        let old = self
+            .mem
             .insert((col, key.into()), Value::Exist(value.to_vec()));
+        self.track_write(key, old, value.len());
         Ok(())
     }
 
     fn delete(&mut self, col: Col, key: &[u8]) -> Result<()> {
-        self.mem.insert((col, key.into()), Value::Deleted);
+        let old = self.mem.insert((col, key.into()), Value::Deleted);
+        self.track_write(key, old, 0);
         Ok(())
     }
 }
@@ -110,7 +142,10 @@ impl<S: HistoryStateStore> HistoryStateStore for MemStore<S> {
     }
 
     fn remove_block_state_record(&mut self, block_number: u64) -> Result<()> {
-        self.history_mem.remove(&block_number);
+        if let Some(map) = self.history_mem.remove(&block_number) {
+            self.size.keys -= map.len();
+            self.size.bytes -= map.len() * HISTORY_RECORD_BYTES;
+        }
         Ok(())
     }
 
@@ -132,10 +167,15 @@ impl<S: HistoryStateStore> HistoryStateStore for MemStore<S> {
         state_key: H256,
         value: H256,
     ) -> Result<()> {
-        self.history_mem
+        let old = self
+            .history_mem
             .entry(block_number)
             .or_default()
             .insert(state_key, value);
+        if old.is_none() {
+            self.size.keys += 1;
+            self.size.bytes += HISTORY_RECORD_BYTES;
+        }
         Ok(())
     }
 }
@@ -149,6 +189,7 @@ impl<S> Clone for MemStore<S> {
             inner: self.inner.clone(),
             mem: self.mem.clone(),
             history_mem: self.history_mem.clone(),
+            size: self.size,
         }
     }
 }
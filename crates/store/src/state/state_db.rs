@@ -83,14 +83,34 @@ pub struct Revision {
     journal_len: usize,
 }
 
+/// Pseudo key recorded in [`StateTracker`] read and write keys when the
+/// account count is accessed. The account count isn't stored under an SMT key.
+pub const ACCOUNT_COUNT_KEY: H256 = [0u8; 32];
+
 #[derive(Debug, Default)]
 pub struct StateTracker {
     touched_keys: Mutex<HashSet<H256>>,
     write_data: Mutex<HashMap<H256, Bytes>>,
     read_data: Mutex<HashMap<H256, Bytes>>,
+    /// SMT keys, script hashes and data hashes read, including the ones not
+    /// found, and [`ACCOUNT_COUNT_KEY`].
+    read_keys: Mutex<HashSet<H256>>,
+    /// Same as `read_keys`, but written.
+    write_keys: Mutex<HashSet<H256>>,
+    /// Record `read_keys` and `write_keys`, see [`StateTracker::with_keys`].
+    track_keys: bool,
 }
 
 impl StateTracker {
+    /// A tracker also recording read and write keys, for conflict detection
+    /// of speculative execution.
+    pub fn with_keys() -> Self {
+        StateTracker {
+            track_keys: true,
+            ..Default::default()
+        }
+    }
+
     /// Return touched keys
     pub fn touched_keys(&self) -> &Mutex<HashSet<H256>> {
         &self.touched_keys
@@ -104,10 +124,37 @@ impl StateTracker {
         &self.read_data
     }
 
+    pub fn read_keys(&self) -> &Mutex<HashSet<H256>> {
+        &self.read_keys
+    }
+
+    pub fn write_keys(&self) -> &Mutex<HashSet<H256>> {
+        &self.write_keys
+    }
+
     /// Record a key in the tracker
     pub fn touch_key(&self, key: &H256) {
         self.touched_keys.lock().unwrap().insert(*key);
     }
+
+    /// Clear touched keys and data, read and write keys are kept.
+    pub fn clear_touched(&self) {
+        self.touched_keys.lock().unwrap().clear();
+        self.write_data.lock().unwrap().clear();
+        self.read_data.lock().unwrap().clear();
+    }
+
+    fn record_read(&self, key: &H256) {
+        if self.track_keys {
+            self.read_keys.lock().unwrap().insert(*key);
+        }
+    }
+
+    fn record_write(&self, key: &H256) {
+        if self.track_keys {
+            self.write_keys.lock().unwrap().insert(*key);
+        }
+    }
 }
 
 pub struct StateDB<S> {
@@ -197,6 +244,12 @@ impl<Store: ChainStore + HistoryStateStore + CodeStore + KVStore> BlockStateDB<S
     pub fn detach_block_state(&mut self, block_number: u64) -> Result<()> {
         self.state.detach_block_state(block_number)
     }
+
+    /// Detach state of blocks `first..=last` in one batch, see
+    /// [`HistoryState::detach_blocks_state`].
+    pub fn detach_blocks_state(&mut self, first: u64, last: u64) -> Result<()> {
+        self.state.detach_blocks_state(first, last)
+    }
 }
 
 impl<S: State + CodeStore> StateDB<S> {
@@ -272,7 +325,7 @@ impl<S: State + CodeStore> StateDB<S> {
         self.last_state_root
     }
 
-    pub(crate) fn is_dirty(&self) -> bool {
+    pub fn is_dirty(&self) -> bool {
         !self.journal.is_empty()
             || !self.revisions.is_empty()
             || self.dirty_account_count.is_some()
@@ -302,6 +355,37 @@ impl<S: State + CodeStore> StateDB<S> {
         self.journal.len()
     }
 
+    /// Raw key values written since the last finalise, sorted by key.
+    pub fn dirty_raw_state(&self) -> Vec<(H256, H256)> {
+        let mut kvs: Vec<_> = self.dirty_state.iter().map(|(k, v)| (*k, *v)).collect();
+        kvs.sort_unstable();
+        kvs
+    }
+
+    /// Apply dirty changes of `other` on top of this state, as if they were
+    /// made to this state. Changes are journaled, so they can be reverted.
+    ///
+    /// `other` is expected to be a clone of this state taken when it was
+    /// clean, see [`StateDB::clone`].
+    pub fn apply_dirty<T>(&mut self, other: &StateDB<T>) -> Result<(), StateError> {
+        if let Some(count) = other.dirty_account_count {
+            self.set_account_count(count)?;
+        }
+        for (key, value) in &other.dirty_state {
+            self.update_raw(*key, *value)?;
+        }
+        for (script_hash, script) in &other.dirty_scripts {
+            self.insert_script(*script_hash, script.clone());
+        }
+        for (data_hash, data) in &other.dirty_data {
+            self.insert_data(*data_hash, data.clone());
+        }
+        for log in &other.dirty_logs {
+            self.append_log(log.clone());
+        }
+        Ok(())
+    }
+
     /// Get changed keys after track point.
     ///
     /// Note: this only works if dirty state hasn't been finalised.
@@ -396,9 +480,8 @@ impl<S: State + CodeStore> JournalDB for StateDB<S> {
             self.state.set_account_count(count)?;
         }
         // write state
-        for (k, v) in &self.dirty_state {
-            self.state.update_raw(*k, *v)?;
-        }
+        let kvs = self.dirty_state.iter().map(|(k, v)| (*k, *v)).collect();
+        self.state.update_raw_batch(kvs)?;
         // write scripts
         for (script_hash, script) in &self.dirty_scripts {
             self.state.insert_script(*script_hash, script.to_owned());
@@ -454,6 +537,7 @@ impl<S: State + CodeStore> State for StateDB<S> {
     fn get_raw(&self, key: &H256) -> Result<H256, StateError> {
         if let Some(tracker) = self.state_tracker.as_ref() {
             tracker.touch_key(key);
+            tracker.record_read(key);
         }
         if let Some(v) = self.dirty_state.get(key) {
             return Ok(*v);
@@ -464,6 +548,7 @@ impl<S: State + CodeStore> State for StateDB<S> {
     fn update_raw(&mut self, key: H256, value: H256) -> Result<(), StateError> {
         if let Some(tracker) = self.state_tracker.as_ref() {
             tracker.touch_key(&key);
+            tracker.record_write(&key);
         }
         self.journal.push(JournalEntry::UpdateRaw {
             key,
@@ -474,6 +559,9 @@ impl<S: State + CodeStore> State for StateDB<S> {
     }
 
     fn get_account_count(&self) -> Result<u32, StateError> {
+        if let Some(tracker) = self.state_tracker.as_ref() {
+            tracker.record_read(&ACCOUNT_COUNT_KEY);
+        }
         if let Some(count) = self.dirty_account_count {
             return Ok(count);
         }
@@ -481,6 +569,9 @@ impl<S: State + CodeStore> State for StateDB<S> {
     }
 
     fn set_account_count(&mut self, count: u32) -> Result<(), StateError> {
+        if let Some(tracker) = self.state_tracker.as_ref() {
+            tracker.record_write(&ACCOUNT_COUNT_KEY);
+        }
         self.journal.push(JournalEntry::SetAccountCount {
             prev_count: self.dirty_account_count,
         });
@@ -500,6 +591,9 @@ impl<S: State + CodeStore> State for StateDB<S> {
 
 impl<S: CodeStore> CodeStore for StateDB<S> {
     fn insert_script(&mut self, script_hash: H256, script: packed::Script) {
+        if let Some(tracker) = self.state_tracker.as_ref() {
+            tracker.record_write(&script_hash);
+        }
         self.journal.push(JournalEntry::InsertScript {
             script_hash,
             prev_exist: self.dirty_scripts.contains_key(&script_hash),
@@ -508,6 +602,9 @@ impl<S: CodeStore> CodeStore for StateDB<S> {
     }
 
     fn get_script(&self, script_hash: &H256) -> Option<packed::Script> {
+        if let Some(tracker) = self.state_tracker.as_ref() {
+            tracker.record_read(script_hash);
+        }
         if let Some(script) = self.dirty_scripts.get(script_hash) {
             return Some(script.clone());
         }
@@ -516,6 +613,7 @@ impl<S: CodeStore> CodeStore for StateDB<S> {
 
     fn insert_data(&mut self, data_hash: H256, code: Bytes) {
         if let Some(state_tracker) = self.state_tracker.as_ref() {
+            state_tracker.record_write(&data_hash);
             state_tracker
                 .write_data()
                 .lock()
@@ -535,8 +633,9 @@ impl<S: CodeStore> CodeStore for StateDB<S> {
             .get(data_hash)
             .cloned()
             .or_else(|| self.state.get_data(data_hash));
-        if let Some(data) = data.as_ref() {
-            if let Some(state_tracker) = self.state_tracker.as_ref() {
+        if let Some(state_tracker) = self.state_tracker.as_ref() {
+            state_tracker.record_read(data_hash);
+            if let Some(data) = data.as_ref() {
                 state_tracker
                     .read_data()
                     .lock()
@@ -746,6 +845,52 @@ mod tests {
         assert!(cmp_dirty_state(&mem_1, &state));
     }
 
+    #[test]
+    fn test_apply_dirty_state() {
+        let store = Store::open_tmp().unwrap();
+        let mut state = new_state(store.get_snapshot());
+        let mut other = state.clone();
+        other
+            .update_raw(H256::from_u32(1), H256::from_u32(1))
+            .unwrap();
+        other.set_account_count(2).unwrap();
+
+        let snap = state.snapshot();
+        state.apply_dirty(&other).unwrap();
+        assert!(cmp_dirty_state(&other, &state));
+        assert_eq!(state.get_account_count().unwrap(), 2);
+
+        // applied changes can be reverted
+        state.revert(snap).unwrap();
+        assert_eq!(state.get_raw(&H256::from_u32(1)).unwrap(), H256::zero());
+        assert_eq!(state.get_account_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_state_tracker_keys() {
+        let store = Store::open_tmp().unwrap();
+        let mut state = new_state(store.get_snapshot());
+        let (k1, k2) = (H256::from_u32(1), H256::from_u32(2));
+
+        // read and write keys are only recorded by a tracker with keys
+        state.set_state_tracker(Default::default());
+        state.get_raw(&k1).unwrap();
+        state.update_raw(k2, k2).unwrap();
+        let tracker = state.take_state_tracker().unwrap();
+        assert_eq!(tracker.touched_keys().lock().unwrap().len(), 2);
+        assert!(tracker.read_keys().lock().unwrap().is_empty());
+        assert!(tracker.write_keys().lock().unwrap().is_empty());
+
+        state.set_state_tracker(super::StateTracker::with_keys());
+        state.get_raw(&k1).unwrap();
+        state.update_raw(k2, k2).unwrap();
+        let tracker = state.state_tracker().unwrap();
+        tracker.clear_touched();
+        assert!(tracker.touched_keys().lock().unwrap().is_empty());
+        assert_eq!(*tracker.read_keys().lock().unwrap(), [k1].into());
+        assert_eq!(*tracker.write_keys().lock().unwrap(), [k2].into());
+    }
+
     #[test]
     fn test_state_impl() {
         // test mem store
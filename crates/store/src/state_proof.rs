@@ -0,0 +1,95 @@
+//! Merkle proofs of account state at the current or historical blocks
+
+use anyhow::{bail, ensure, Result};
+use gw_smt::{smt::SMT, smt_h256_ext::SMTH256};
+use gw_types::{h256::H256, packed::AccountMerkleState, prelude::*};
+
+use crate::{
+    smt::smt_store::SMTStateStore,
+    state::{
+        history::history_state::{HistoryState, RWConfig},
+        overlay::mem_store::MemStore,
+    },
+    traits::chain_store::ChainStore,
+    Store,
+};
+
+pub struct StateProof {
+    pub block_number: u64,
+    /// Post account state of the block, the proof is against its merkle root.
+    pub post_account: AccountMerkleState,
+    /// Values of the keys in the order requested, zero if not set.
+    pub leaves: Vec<(H256, H256)>,
+    /// Compiled SMT proof of the leaves.
+    pub proof: Vec<u8>,
+}
+
+impl Store {
+    /// Prove values of state keys after the main chain block `block_hash`.
+    ///
+    /// State of blocks before the tip is restored in memory by detaching
+    /// later blocks with their state history, which costs more the older the
+    /// block is. Returns None if the block is not on the main chain, is after
+    /// the last valid tip, or its state history is pruned.
+    pub fn generate_state_proof(
+        &self,
+        block_hash: &H256,
+        keys: &[H256],
+    ) -> Result<Option<StateProof>> {
+        if keys.is_empty() {
+            bail!("no keys to prove");
+        }
+        let db = self.begin_transaction();
+        let block = match db.get_block(block_hash)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let block_number: u64 = block.raw().number().unpack();
+        if db.get_block_hash_by_number(block_number)? != Some(*block_hash) {
+            return Ok(None);
+        }
+        let tip = db.get_last_valid_tip_block()?;
+        let tip_number: u64 = tip.raw().number().unpack();
+        if block_number > tip_number {
+            return Ok(None);
+        }
+        // State at the pruned block number and later blocks is retained.
+        if block_number < tip_number
+            && matches!(db.get_pruned_block_number(), Some(pruned) if block_number < pruned)
+        {
+            return Ok(None);
+        }
+
+        let tip_account = tip.raw().post_account();
+        let tip_root: H256 = tip_account.merkle_root().unpack();
+        // Detached state only goes to the memory overlay.
+        let smt = SMT::new(tip_root.into(), SMTStateStore::new(MemStore::new(db)));
+        let mut state =
+            HistoryState::new(smt, tip_account.count().unpack(), RWConfig::detach_block());
+        if block_number < tip_number {
+            state.detach_blocks_state(block_number + 1, tip_number)?;
+        }
+        let post_account = block.raw().post_account();
+        let root: H256 = (*state.smt().root()).into();
+        let expected_root: H256 = post_account.merkle_root().unpack();
+        ensure!(
+            root == expected_root,
+            "state root of block #{} mismatch, the chain may have been reverted",
+            block_number
+        );
+
+        let smt = state.smt();
+        let leaves = keys
+            .iter()
+            .map(|k| Ok((*k, smt.get(&(*k).into())?.into())))
+            .collect::<Result<Vec<_>>>()?;
+        let smt_keys: Vec<SMTH256> = keys.iter().map(|k| (*k).into()).collect();
+        let proof = smt.merkle_proof(smt_keys.clone())?.compile(smt_keys)?;
+        Ok(Some(StateProof {
+            block_number,
+            post_account,
+            leaves,
+            proof: proof.0,
+        }))
+    }
+}
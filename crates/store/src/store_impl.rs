@@ -4,48 +4,109 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use autorocks::autorocks_sys::rocksdb::{
-    TransactionDBWriteOptimizations, TransactionOptions, WriteOptions,
+    CompressionType, Tickers, TransactionDBWriteOptimizations, TransactionOptions, WriteOptions,
 };
 use autorocks::moveit::{moveit, slot};
 use autorocks::{DbOptions, TransactionDb, WriteBatch};
-use gw_config::StoreConfig;
+use gw_config::{StoreCompression, StoreConfig, StoreStateCacheConfig, StoreTuningConfig};
 use gw_smt::smt::Blake2bHasher;
 use gw_types::prelude::*;
 use serde::Serialize;
 use tempfile::TempDir;
 
+use crate::cache::{CacheStat, CacheWrites, StoreCache};
 use crate::schema::{Col, COLUMNS};
 use crate::smt::smt_store::SMTBlockStore;
 use crate::state::{history::history_state::RWConfig, BlockStateDB};
 use crate::traits::{chain_store::ChainStore, kv_store::KVStoreRead};
 use crate::{snapshot::StoreSnapshot, transaction::StoreTransaction};
 
+pub(crate) fn write_skip_concurrency_control(
+    db: &TransactionDb,
+    write_batch: &mut WriteBatch,
+) -> Result<()> {
+    moveit! {
+        let options = WriteOptions::new();
+        let mut optimizations = TransactionDBWriteOptimizations::new();
+    }
+    optimizations.skip_concurrency_control = true;
+    db.write_with_options(&options, &optimizations, write_batch)?;
+    Ok(())
+}
+
+fn apply_tuning(opts: &mut DbOptions, tuning: &StoreTuningConfig) {
+    if let Some(size) = tuning.block_cache_size {
+        opts.block_cache_size(size);
+    }
+    if let Some(size) = tuning.write_buffer_size {
+        opts.write_buffer_size(size);
+    }
+    if let Some(ref levels) = tuning.compression_per_level {
+        let levels: Vec<_> = levels
+            .iter()
+            .map(|c| match c {
+                StoreCompression::None => CompressionType::kNoCompression,
+                StoreCompression::Snappy => CompressionType::kSnappyCompression,
+                StoreCompression::Lz4 => CompressionType::kLZ4Compression,
+                StoreCompression::Zstd => CompressionType::kZSTD,
+            })
+            .collect();
+        opts.compression_per_level(&levels);
+    }
+    if let Some(jobs) = tuning.max_background_jobs {
+        opts.max_background_jobs(jobs);
+    }
+}
+
 #[derive(Clone)]
 pub struct Store {
     db: TransactionDb,
+    cache: Arc<StoreCache>,
     _temp_dir: Option<Arc<TempDir>>,
 }
 
 impl Store {
     pub fn open(config: &StoreConfig, columns: usize) -> Result<Self> {
+        crate::latency::init(config);
         let mut opts = DbOptions::new(&config.path, columns);
         if let Some(ref opts_file) = config.options_file {
             opts.load_options_from_file(opts_file, config.cache_size.unwrap_or(0))?;
         }
+        if config.statistics {
+            opts.enable_statistics();
+        }
+        apply_tuning(&mut opts, &config.tuning);
         opts.create_if_missing(true);
         opts.create_missing_column_families(true);
         let db = opts.open()?;
         // TODO: repair.
-        Ok(Self::new(db))
+        Ok(Self::new(db).with_state_cache(&config.state_cache))
     }
 
+    /// New store without state caches, see `with_state_cache`.
     pub fn new(db: TransactionDb) -> Self {
         Store {
             db,
+            cache: Default::default(),
             _temp_dir: None,
         }
     }
 
+    /// Enable in-memory LRU caches of scripts, data and account fields.
+    ///
+    /// Only writes through transactions of this store (and its clones) keep
+    /// the caches coherent, so don't write the cached columns with write
+    /// batches or another store on the same db.
+    pub fn with_state_cache(mut self, config: &StoreStateCacheConfig) -> Self {
+        self.cache = Arc::new(StoreCache::new(config));
+        self
+    }
+
+    /// Hit and miss counts of the state caches.
+    pub fn cache_stats(&self) -> Vec<CacheStat> {
+        self.cache.stats()
+    }
+
     pub fn open_tmp() -> Result<Self> {
         let dir = tempfile::tempdir()?;
         Ok(Self {
@@ -53,6 +114,7 @@ impl Store {
                 .create_if_missing(true)
                 .create_missing_column_families(true)
                 .open()?,
+            cache: Default::default(),
             _temp_dir: Some(dir.into()),
         })
     }
@@ -60,6 +122,10 @@ impl Store {
     pub fn begin_transaction(&self) -> StoreTransaction {
         StoreTransaction {
             inner: self.db.begin_transaction(),
+            db: self.db.clone(),
+            range_deletes: Vec::new(),
+            cache: self.cache.clone(),
+            cache_writes: CacheWrites::default(),
         }
     }
 
@@ -78,6 +144,10 @@ impl Store {
             inner: self
                 .db
                 .begin_transaction_with_options(&write_options, &transaction_options),
+            db: self.db.clone(),
+            range_deletes: Vec::new(),
+            cache: self.cache.clone(),
+            cache_writes: CacheWrites::default(),
         }
     }
 
@@ -105,17 +175,55 @@ impl Store {
         result
     }
 
+    /// Size and compaction stats of each column.
+    pub fn gather_cf_stats(&self) -> Vec<CfStat> {
+        let db = self.as_inner();
+        let get = |c, p| db.get_int_property(c, p).unwrap_or(0);
+        (0..=db.default_col())
+            .map(|c| CfStat {
+                col: c,
+                live_sst_files_size: get(c, "rocksdb.live-sst-files-size"),
+                mem_tables_size: get(c, "rocksdb.cur-size-all-mem-tables"),
+                estimate_num_keys: get(c, "rocksdb.estimate-num-keys"),
+                estimate_pending_compaction_bytes: get(
+                    c,
+                    "rocksdb.estimate-pending-compaction-bytes",
+                ),
+            })
+            .collect()
+    }
+
+    /// None if statistics is not enabled, see `StoreConfig::statistics`.
+    pub fn gather_db_stats(&self) -> Option<DbStat> {
+        let db = self.as_inner();
+        Some(DbStat {
+            block_cache_hit: db.get_ticker_count(Tickers::BLOCK_CACHE_HIT)?,
+            block_cache_miss: db.get_ticker_count(Tickers::BLOCK_CACHE_MISS)?,
+            stall_micros: db.get_ticker_count(Tickers::STALL_MICROS)?,
+        })
+    }
+
+    /// Whether rocksdb is delaying or stopping writes, e.g. because compaction
+    /// can't keep up.
+    pub fn write_stall(&self) -> WriteStall {
+        let db = self.as_inner();
+        let col = db.default_col();
+        let stopped = db
+            .get_int_property(col, "rocksdb.is-write-stopped")
+            .unwrap_or(0);
+        if stopped > 0 {
+            return WriteStall::Stopped;
+        }
+        match db.get_int_property(col, "rocksdb.actual-delayed-write-rate") {
+            Some(rate) if rate > 0 => WriteStall::Delayed { rate },
+            _ => WriteStall::None,
+        }
+    }
+
     /// Transactional range delete is not supported. If there are range deletes
     /// in the write_batch, must use this.
     pub fn write_skip_concurrency_control(&self, write_batch: &mut WriteBatch) -> Result<()> {
-        moveit! {
-            let options = WriteOptions::new();
-            let mut optimizations = TransactionDBWriteOptimizations::new();
-        }
-        optimizations.skip_concurrency_control = true;
-        self.db
-            .write_with_options(&options, &optimizations, write_batch)?;
-        Ok(())
+        write_skip_concurrency_control(&self.db, write_batch)
     }
 
     pub fn check_state(&self) -> Result<()> {
@@ -145,7 +253,10 @@ impl Store {
     }
 
     pub fn get_snapshot(&self) -> StoreSnapshot {
-        StoreSnapshot::new(self.db.snapshot())
+        // Take the generation first, so that commits after it invalidate the
+        // cache for the snapshot.
+        let state_generation = self.cache.state_generation();
+        StoreSnapshot::new(self.db.snapshot(), self.cache.clone(), state_generation)
     }
 
     pub fn as_inner(&self) -> &TransactionDb {
@@ -173,6 +284,34 @@ impl KVStoreRead for Store {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriteStall {
+    None,
+    /// Writes are slowed down to `rate` bytes per second.
+    Delayed {
+        rate: u64,
+    },
+    Stopped,
+}
+
+#[derive(Clone, Debug)]
+pub struct CfStat {
+    pub col: usize,
+    pub live_sst_files_size: u64,
+    pub mem_tables_size: u64,
+    pub estimate_num_keys: u64,
+    pub estimate_pending_compaction_bytes: u64,
+}
+
+/// Cumulative counters since the db is opened.
+#[derive(Clone, Debug)]
+pub struct DbStat {
+    pub block_cache_hit: u64,
+    pub block_cache_miss: u64,
+    /// Time writes are stalled.
+    pub stall_micros: u64,
+}
+
 #[derive(Serialize)]
 pub struct CfMemStat {
     // Column name.
@@ -0,0 +1,75 @@
+use autorocks::Direction;
+
+use crate::{
+    backend::{Backend, BackendRead, BackendTransaction},
+    schema::COLUMN_DATA,
+    Store,
+};
+
+fn keys(iter: impl Iterator<Item = (Box<[u8]>, Box<[u8]>)>) -> Vec<Vec<u8>> {
+    iter.map(|(k, _)| k.to_vec()).collect()
+}
+
+fn check_semantics<B: Backend>(backend: B) {
+    let col = COLUMN_DATA;
+    let mut tx = backend.begin_transaction();
+    for key in [b"a", b"b", b"c"] {
+        tx.put(col, key, b"1").unwrap();
+    }
+    // Uncommitted writes are only visible in the transaction.
+    assert_eq!(tx.get(col, b"a").unwrap().as_deref(), Some(&b"1"[..]));
+    assert_eq!(backend.get(col, b"a").unwrap(), None);
+    tx.commit().unwrap();
+    let snap = backend.snapshot();
+
+    let mut tx = backend.begin_transaction();
+    tx.put(col, b"b", b"2").unwrap();
+    tx.delete(col, b"c").unwrap();
+    tx.put(col, b"d", b"2").unwrap();
+    assert_eq!(tx.get(col, b"c").unwrap(), None);
+    assert_eq!(
+        keys(tx.iter_from(col, Some(b"b"), Direction::Forward)),
+        [b"b".to_vec(), b"d".to_vec()]
+    );
+    assert_eq!(
+        keys(tx.iter_from(col, Some(b"c"), Direction::Backward)),
+        [b"b".to_vec(), b"a".to_vec()]
+    );
+
+    // Writing a key with an uncommitted write of another transaction fails.
+    let mut other = backend.begin_transaction();
+    assert!(other.put(col, b"b", b"3").is_err());
+    other.rollback().unwrap();
+    tx.commit().unwrap();
+
+    // Snapshots don't see later commits.
+    assert_eq!(snap.get(col, b"b").unwrap().as_deref(), Some(&b"1"[..]));
+    assert_eq!(
+        keys(snap.iter_from(col, None, Direction::Backward)),
+        [b"c".to_vec(), b"b".to_vec(), b"a".to_vec()]
+    );
+    assert_eq!(
+        keys(backend.snapshot().iter_from(col, None, Direction::Forward)),
+        [b"a".to_vec(), b"b".to_vec(), b"d".to_vec()]
+    );
+
+    // Rolled back writes are discarded.
+    let mut tx = backend.begin_transaction();
+    tx.put(col, b"e", b"1").unwrap();
+    tx.rollback().unwrap();
+    assert_eq!(backend.get(col, b"e").unwrap(), None);
+}
+
+#[test]
+fn test_rocksdb_backend() {
+    let store = Store::open_tmp().unwrap();
+    check_semantics(store.as_inner().clone());
+}
+
+#[cfg(feature = "mem-backend")]
+#[test]
+fn test_mem_backend() {
+    use crate::{backend::mem::MemBackend, schema::COLUMNS};
+
+    check_semantics(MemBackend::new(COLUMNS));
+}
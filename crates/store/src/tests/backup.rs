@@ -0,0 +1,43 @@
+use autorocks::DbOptions;
+
+use crate::{
+    backup::{list_backups, restore_backup, verify_backup},
+    schema::{COLUMNS, COLUMN_META},
+    traits::kv_store::{KVStoreRead, KVStoreWrite},
+    Store,
+};
+
+#[test]
+fn test_backup_and_restore() {
+    let store = Store::open_tmp().unwrap();
+    let backup_dir = tempfile::tempdir().unwrap();
+
+    let mut db = store.begin_transaction();
+    db.insert_raw(COLUMN_META, b"key", b"1").unwrap();
+    db.commit().unwrap();
+    let first = store.create_backup(backup_dir.path()).unwrap();
+
+    let mut db = store.begin_transaction();
+    db.insert_raw(COLUMN_META, b"key", b"2").unwrap();
+    db.commit().unwrap();
+    let second = store.create_backup(backup_dir.path()).unwrap();
+    assert!(second.id > first.id);
+
+    let backups = list_backups(backup_dir.path()).unwrap();
+    assert_eq!(backups, vec![first.clone(), second]);
+    verify_backup(backup_dir.path(), first.id).unwrap();
+
+    let restore_dir = tempfile::tempdir().unwrap();
+    restore_backup(backup_dir.path(), Some(first.id), restore_dir.path()).unwrap();
+    // Target is not empty now.
+    assert!(restore_backup(backup_dir.path(), None, restore_dir.path()).is_err());
+
+    let restored = Store::new(
+        DbOptions::new(restore_dir.path(), COLUMNS)
+            .create_missing_column_families(true)
+            .open()
+            .unwrap(),
+    );
+    let value = restored.get(COLUMN_META, b"key");
+    assert_eq!(value.as_deref(), Some(&b"1"[..]));
+}
@@ -0,0 +1,77 @@
+use gw_config::StoreStateCacheConfig;
+
+use crate::{
+    cache::CacheStat,
+    schema::{COLUMN_ACCOUNT_SMT_LEAF, COLUMN_SCRIPT},
+    traits::kv_store::{KVStoreRead, KVStoreWrite},
+    Store,
+};
+
+fn stat(store: &Store, name: &str) -> CacheStat {
+    store
+        .cache_stats()
+        .into_iter()
+        .find(|s| s.name == name)
+        .unwrap()
+}
+
+#[test]
+fn test_state_cache() {
+    let store = Store::open_tmp()
+        .unwrap()
+        .with_state_cache(&StoreStateCacheConfig::default());
+    let mut db = store.begin_transaction();
+    db.insert_raw(COLUMN_SCRIPT, b"script", b"s").unwrap();
+    db.insert_raw(COLUMN_ACCOUNT_SMT_LEAF, b"key", b"v1")
+        .unwrap();
+    // Uncommitted values are not cached.
+    assert_eq!(db.get(COLUMN_SCRIPT, b"script").as_deref(), Some(&b"s"[..]));
+    assert_eq!(stat(&store, "scripts").len, 0);
+    db.commit().unwrap();
+
+    let snap = store.get_snapshot();
+    for _ in 0..2 {
+        assert_eq!(
+            snap.get(COLUMN_SCRIPT, b"script").as_deref(),
+            Some(&b"s"[..])
+        );
+        assert_eq!(
+            snap.get(COLUMN_ACCOUNT_SMT_LEAF, b"key").as_deref(),
+            Some(&b"v1"[..])
+        );
+    }
+    let scripts = stat(&store, "scripts");
+    assert_eq!((scripts.hits, scripts.misses, scripts.len), (1, 1, 1));
+    let fields = stat(&store, "account_fields");
+    assert_eq!((fields.hits, fields.misses, fields.len), (1, 1, 1));
+
+    // Applying a block invalidates the account fields written.
+    let mut db = store.begin_transaction();
+    db.insert_raw(COLUMN_ACCOUNT_SMT_LEAF, b"key", b"v2")
+        .unwrap();
+    db.commit().unwrap();
+    assert_eq!(stat(&store, "account_fields").len, 0);
+
+    // The old snapshot no longer uses the cache, so it neither sees nor
+    // caches stale values.
+    assert_eq!(
+        snap.get(COLUMN_ACCOUNT_SMT_LEAF, b"key").as_deref(),
+        Some(&b"v1"[..])
+    );
+    assert_eq!(stat(&store, "account_fields").len, 0);
+
+    let snap = store.get_snapshot();
+    let db = store.begin_transaction();
+    for _ in 0..2 {
+        assert_eq!(
+            snap.get(COLUMN_ACCOUNT_SMT_LEAF, b"key").as_deref(),
+            Some(&b"v2"[..])
+        );
+        assert_eq!(
+            db.get(COLUMN_ACCOUNT_SMT_LEAF, b"key").as_deref(),
+            Some(&b"v2"[..])
+        );
+    }
+    let fields = stat(&store, "account_fields");
+    assert_eq!((fields.hits, fields.misses, fields.len), (4, 2, 1));
+}
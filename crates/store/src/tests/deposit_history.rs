@@ -0,0 +1,75 @@
+use gw_types::{
+    packed::{CellInfo, DepositInfo, DepositInfoVec, DepositRequest, OutPoint, Script},
+    prelude::*,
+};
+
+use crate::{deposit_history::DepositRecord, Store};
+
+fn build_deposit(script: &Script, l1_tx_hash: [u8; 32], capacity: u64) -> DepositInfo {
+    let request = DepositRequest::new_builder()
+        .capacity(capacity.pack())
+        .script(script.clone())
+        .build();
+    let out_point = OutPoint::new_builder()
+        .tx_hash(l1_tx_hash.pack())
+        .index(1u32.pack())
+        .build();
+    DepositInfo::new_builder()
+        .request(request)
+        .cell(CellInfo::new_builder().out_point(out_point).build())
+        .build()
+}
+
+#[test]
+fn test_deposit_history() {
+    let store = Store::open_tmp().unwrap();
+    let mut db = store.begin_transaction();
+
+    let alice = Script::new_builder().args([1u8; 32][..].pack()).build();
+    let bob = Script::new_builder().args([2u8; 32][..].pack()).build();
+    let deposits_of_block = |number: u8| {
+        DepositInfoVec::new_builder()
+            .push(build_deposit(&alice, [number; 32], 100))
+            .push(build_deposit(&bob, [number; 32], 200))
+            .push(build_deposit(&alice, [number + 1; 32], 300))
+            .build()
+    };
+    for number in [3u64, 5] {
+        let deposits = deposits_of_block(number as u8);
+        db.set_block_deposit_info_vec(number, &deposits.as_reader())
+            .unwrap();
+        db.insert_block_deposit_history(number, &deposits).unwrap();
+    }
+
+    let history = db.get_deposit_history(&alice.hash(), None, 3).unwrap();
+    let positions: Vec<_> = history.iter().map(|r| (r.block_number, r.index)).collect();
+    assert_eq!(positions, vec![(5, 2), (5, 0), (3, 2)]);
+    assert_eq!(
+        history[0],
+        DepositRecord {
+            block_number: 5,
+            index: 2,
+            l1_tx_hash: [6u8; 32],
+            l1_output_index: 1,
+            capacity: 300,
+            amount: 0,
+            sudt_script_hash: [0u8; 32],
+        }
+    );
+
+    // Next page
+    let cursor = history[2].cursor();
+    let history = db
+        .get_deposit_history(&alice.hash(), Some(&cursor), 3)
+        .unwrap();
+    let positions: Vec<_> = history.iter().map(|r| (r.block_number, r.index)).collect();
+    assert_eq!(positions, vec![(3, 0)]);
+
+    let history = db.get_deposit_history(&bob.hash(), None, 10).unwrap();
+    assert_eq!(history.len(), 2);
+
+    db.delete_block_deposit_history(5).unwrap();
+    let history = db.get_deposit_history(&alice.hash(), None, 10).unwrap();
+    let positions: Vec<_> = history.iter().map(|r| (r.block_number, r.index)).collect();
+    assert_eq!(positions, vec![(3, 2), (3, 0)]);
+}
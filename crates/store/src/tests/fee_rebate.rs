@@ -0,0 +1,73 @@
+use gw_types::{
+    bytes::Bytes,
+    packed::{L2Block, L2Transaction, RawL2Block, RawL2Transaction},
+    prelude::*,
+};
+
+use crate::{
+    fee_rebate::{FeeRebate, TxOrigin},
+    Store,
+};
+
+fn build_tx(nonce: u32) -> L2Transaction {
+    L2Transaction::new_builder()
+        .raw(RawL2Transaction::new_builder().nonce(nonce.pack()).build())
+        .build()
+}
+
+#[test]
+fn test_account_block_fee_rebates() {
+    let store = Store::open_tmp().unwrap();
+    let mut db = store.begin_transaction();
+
+    let txs: Vec<_> = (0..3).map(build_tx).collect();
+    let origin = |fee, name: &'static str| TxOrigin {
+        origin: Bytes::from_static(name.as_bytes()),
+        fee,
+    };
+    db.insert_tx_origin(&txs[0].hash(), &origin(100, "alice"))
+        .unwrap();
+    db.insert_tx_origin(&txs[1].hash(), &origin(200, "alice"))
+        .unwrap();
+
+    let block = L2Block::new_builder()
+        .raw(RawL2Block::new_builder().number(5u64.pack()).build())
+        .transactions(txs.clone().pack())
+        .build();
+    db.account_block_fee_rebates(&block).unwrap();
+
+    assert!(db.get_tx_origin(&txs[0].hash()).unwrap().is_none());
+    let rebates = db.get_fee_rebates(0, 10).unwrap();
+    assert_eq!(
+        rebates,
+        vec![FeeRebate {
+            block_number: 5,
+            origin: Bytes::from_static(b"alice"),
+            txs: 2,
+            fee: 300,
+        }]
+    );
+    assert!(db.get_fee_rebates(6, 10).unwrap().is_empty());
+
+    db.delete_block_fee_rebates(5).unwrap();
+    assert!(db.get_fee_rebates(0, 10).unwrap().is_empty());
+}
+
+#[test]
+fn test_remove_mem_pool_transaction_removes_tx_origin() {
+    let store = Store::open_tmp().unwrap();
+    let mut db = store.begin_transaction();
+
+    let tx = build_tx(0);
+    let tx_hash = tx.hash();
+    let origin = TxOrigin {
+        origin: Bytes::from_static(b"alice"),
+        fee: 100,
+    };
+    db.insert_mem_pool_transaction(&tx_hash, tx).unwrap();
+    db.insert_tx_origin(&tx_hash, &origin).unwrap();
+    assert_eq!(db.get_tx_origin(&tx_hash).unwrap(), Some(origin));
+
+    db.remove_mem_pool_transaction(&tx_hash).unwrap();
+    assert!(db.get_tx_origin(&tx_hash).unwrap().is_none());
+}
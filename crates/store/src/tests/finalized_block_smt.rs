@@ -0,0 +1,44 @@
+use gw_types::{
+    packed::{L2Block, RawL2Block},
+    prelude::*,
+};
+
+use crate::{
+    schema::COLUMN_BLOCK,
+    traits::{chain_store::ChainStore, kv_store::KVStoreWrite},
+    transaction::StoreTransaction,
+    Store,
+};
+
+fn attach_block(db: &mut StoreTransaction, number: u64) {
+    let block = L2Block::new_builder()
+        .raw(RawL2Block::new_builder().number(number.pack()).build())
+        .build();
+    db.insert_raw(COLUMN_BLOCK, &block.hash(), block.as_slice())
+        .unwrap();
+    db.attach_block(block).unwrap();
+}
+
+#[test]
+fn test_advance_finalized_block_smt() {
+    let store = Store::open_tmp().unwrap();
+    let mut db = store.begin_transaction();
+    for number in 0..=2 {
+        attach_block(&mut db, number);
+    }
+    let root_at_2 = db.get_block_smt_root().unwrap();
+    for number in 3..=5 {
+        attach_block(&mut db, number);
+    }
+
+    // Root of finalized blocks 0..=2 is the block root at block 2.
+    assert_eq!(db.advance_finalized_block_smt(2).unwrap(), root_at_2);
+    assert_eq!(db.get_finalized_block_smt_tip(), Some(2));
+    // Advancing to an older block is a no-op.
+    assert_eq!(db.advance_finalized_block_smt(1).unwrap(), root_at_2);
+    assert_eq!(
+        db.advance_finalized_block_smt(5).unwrap(),
+        db.get_block_smt_root().unwrap()
+    );
+    assert_eq!(db.get_finalized_block_smt_tip(), Some(5));
+}
@@ -0,0 +1,124 @@
+use gw_common::{
+    builtins::ETH_REGISTRY_ACCOUNT_ID, registry_address::RegistryAddress, state::State,
+};
+use gw_traits::CodeStore;
+use gw_types::{
+    bytes::Bytes,
+    h256::*,
+    packed::{AccountMerkleState, L2Block, RawL2Block, Script},
+    prelude::*,
+    U256,
+};
+
+use crate::{
+    fixture::FixtureOptions,
+    schema::COLUMN_BLOCK,
+    state::{history::history_state::RWConfig, traits::JournalDB, BlockStateDB},
+    traits::{chain_store::ChainStore, kv_store::KVStoreWrite},
+    Store,
+};
+
+fn script(n: u8) -> Script {
+    Script::new_builder().args(vec![n].pack()).build()
+}
+
+fn insert_block(store: &Store, number: u64, post_account: AccountMerkleState) {
+    let block = L2Block::new_builder()
+        .raw(
+            RawL2Block::new_builder()
+                .number(number.pack())
+                .post_account(post_account)
+                .build(),
+        )
+        .build();
+    let mut db = store.begin_transaction();
+    db.insert_raw(COLUMN_BLOCK, &block.hash(), block.as_slice())
+        .unwrap();
+    db.attach_block(block).unwrap();
+    db.commit().unwrap();
+}
+
+#[test]
+fn test_export_and_load_state_fixture() {
+    let store = Store::open_tmp().unwrap();
+    let mut db = store.begin_transaction();
+    db.set_block_smt_root(H256::zero()).unwrap();
+    db.commit().unwrap();
+    insert_block(&store, 0, AccountMerkleState::default());
+
+    let address = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![3u8; 20]);
+    let slot = [7u8; 32];
+    let code = Bytes::from_static(b"contract code");
+    let code_hash = [8u8; 32];
+    let post_account = {
+        let mut db = store.begin_transaction();
+        let mut state = BlockStateDB::from_store(&mut db, RWConfig::attach_block(1)).unwrap();
+        // 0 meta, 1 sudt, 2 eth registry, 3 eoa, 4 contract, 5 unrelated
+        for n in 0..6 {
+            let script = script(n);
+            state.create_account(script.hash()).unwrap();
+            state.insert_script(script.hash(), script);
+        }
+        state
+            .mapping_registry_address_to_script_hash(address.clone(), script(3).hash())
+            .unwrap();
+        state.mint_sudt(1, &address, U256::from(100u64)).unwrap();
+        state.set_nonce(3, 2).unwrap();
+        state.update_value(4, &slot, code_hash).unwrap();
+        state.store_data_hash(code_hash).unwrap();
+        state.insert_data(code_hash, code.clone());
+        state.update_value(5, &slot, H256::one()).unwrap();
+        state.finalise().unwrap();
+        let post_account = AccountMerkleState::new_builder()
+            .merkle_root(state.calculate_root().unwrap().pack())
+            .count(state.get_account_count().unwrap().pack())
+            .build();
+        db.commit().unwrap();
+        post_account
+    };
+    insert_block(&store, 1, post_account);
+
+    let opts = FixtureOptions {
+        accounts: vec![4, 3],
+        sudt_ids: vec![1],
+        storage: vec![(4, slot)],
+    };
+    let fixture = store.export_state_fixture(&opts).unwrap();
+    assert_eq!(fixture.block_number, 1);
+    assert_eq!(fixture.account_count, 6);
+    assert_eq!(fixture.scripts.len(), 3);
+    assert_eq!(fixture.data.len(), 1);
+    // Deterministic
+    assert_eq!(store.export_state_fixture(&opts).unwrap(), fixture);
+
+    // Load into a state without anything else.
+    let empty = Store::open_tmp().unwrap();
+    let state = fixture.to_mem_state_db(empty.get_snapshot()).unwrap();
+    assert_eq!(state.get_account_count().unwrap(), 6);
+    assert_eq!(state.get_script_hash(3).unwrap(), script(3).hash());
+    assert_eq!(state.get_nonce(3).unwrap(), 2);
+    assert_eq!(
+        state
+            .get_account_id_by_script_hash(&script(4).hash())
+            .unwrap(),
+        Some(4)
+    );
+    assert_eq!(
+        state
+            .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &script(3).hash())
+            .unwrap(),
+        Some(address.clone())
+    );
+    assert_eq!(
+        state.get_sudt_balance(1, &address).unwrap(),
+        U256::from(100u64)
+    );
+    assert_eq!(state.get_value(4, &slot).unwrap(), code_hash);
+    assert!(state.is_data_hash_exist(&code_hash).unwrap());
+    assert_eq!(state.get_data(&code_hash), Some(code));
+    assert_eq!(state.get_script(&script(3).hash()), Some(script(3)));
+    // Accounts not selected are not exported.
+    assert!(state.get_script_hash(5).unwrap().is_zero());
+    assert!(state.get_value(5, &slot).unwrap().is_zero());
+    assert_eq!(state.get_script(&script(5).hash()), None);
+}
@@ -0,0 +1,140 @@
+use gw_types::{
+    h256::*,
+    packed::{
+        BlockMerkleState, DepositInfoVec, GlobalState, L2Block, L2Transaction, RawL2Block,
+        TransactionKey, TxReceipt,
+    },
+    prelude::*,
+};
+
+use crate::{
+    fsck::{FsckIssue, FsckOptions},
+    schema::{COLUMN_BLOCK, COLUMN_BLOCK_GLOBAL_STATE, COLUMN_TRANSACTION_RECEIPT},
+    traits::{chain_store::ChainStore, kv_store::KVStoreWrite},
+    transaction::StoreTransaction,
+    Store,
+};
+
+fn build_block(number: u64, parent_block_hash: H256, txs: Vec<L2Transaction>) -> L2Block {
+    let raw = RawL2Block::new_builder()
+        .number(number.pack())
+        .parent_block_hash(parent_block_hash.pack())
+        .build();
+    L2Block::new_builder()
+        .raw(raw)
+        .transactions(txs.pack())
+        .build()
+}
+
+fn attach_block(db: &mut StoreTransaction, block: &L2Block) {
+    db.insert_block_without_receipts(
+        block.clone(),
+        GlobalState::default(),
+        DepositInfoVec::default(),
+        vec![],
+    )
+    .unwrap();
+    db.attach_block(block.clone()).unwrap();
+    let number: u64 = block.raw().number().unpack();
+    let block_merkle_state = BlockMerkleState::new_builder()
+        .merkle_root(db.get_block_smt_root().unwrap().pack())
+        .count((number + 1).pack())
+        .build();
+    let global_state = GlobalState::new_builder().block(block_merkle_state).build();
+    db.insert_raw(
+        COLUMN_BLOCK_GLOBAL_STATE,
+        &block.hash(),
+        global_state.as_slice(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_fsck() {
+    let store = Store::open_tmp().unwrap();
+    let mut db = store.begin_transaction();
+    let mut parent_block_hash = H256::zero();
+    let mut blocks = Vec::new();
+    for number in 0..=2 {
+        let txs = match number {
+            2 => vec![L2Transaction::default()],
+            _ => vec![],
+        };
+        let block = build_block(number, parent_block_hash, txs);
+        attach_block(&mut db, &block);
+        parent_block_hash = block.hash();
+        blocks.push(block);
+    }
+    db.commit().unwrap();
+
+    let opts = FsckOptions {
+        check_orphans: true,
+        ..Default::default()
+    };
+    let report = store.fsck(&opts).unwrap();
+    assert_eq!((report.from_block, report.to_block), (0, 2));
+    let issues: Vec<_> = report.issues().cloned().collect();
+    assert_eq!(
+        issues,
+        vec![FsckIssue::MissingTransactionReceipt {
+            block_number: 2,
+            tx_index: 0
+        }]
+    );
+
+    let mut db = store.begin_transaction();
+    let tx_key = TransactionKey::new_builder()
+        .block_hash(blocks[2].hash().pack())
+        .index(0u32.pack())
+        .build();
+    db.insert_raw(
+        COLUMN_TRANSACTION_RECEIPT,
+        tx_key.as_slice(),
+        TxReceipt::default().as_slice(),
+    )
+    .unwrap();
+    db.delete(COLUMN_BLOCK_GLOBAL_STATE, &blocks[1].hash())
+        .unwrap();
+    // Contents of a block not on the main chain.
+    let orphan = build_block(3, blocks[2].hash(), vec![]);
+    db.insert_block_without_receipts(
+        orphan.clone(),
+        GlobalState::default(),
+        DepositInfoVec::default(),
+        vec![],
+    )
+    .unwrap();
+    db.commit().unwrap();
+
+    let report = store.fsck(&opts).unwrap();
+    let issues: Vec<_> = report.issues().cloned().collect();
+    let orphan_key = format!("0x{}", hex::encode(orphan.hash()));
+    assert_eq!(
+        issues,
+        vec![
+            FsckIssue::MissingGlobalState { block_number: 1 },
+            FsckIssue::Orphan {
+                column: COLUMN_BLOCK,
+                key: orphan_key.clone(),
+            },
+            FsckIssue::Orphan {
+                column: COLUMN_BLOCK_GLOBAL_STATE,
+                key: orphan_key,
+            },
+        ]
+    );
+
+    let report = store
+        .fsck(&FsckOptions {
+            delete_orphans: true,
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(report.deleted_orphans, 2);
+    let issues: Vec<_> = report.issues().cloned().collect();
+    assert_eq!(
+        issues,
+        vec![FsckIssue::MissingGlobalState { block_number: 1 }]
+    );
+    assert!(store.get_block(&orphan.hash()).unwrap().is_none());
+}
@@ -0,0 +1,87 @@
+use gw_types::{
+    bytes::Bytes,
+    packed::{LogItem, TxReceipt},
+    prelude::*,
+};
+
+use crate::{
+    log_index::{parse_user_log, LogCursor, LogIndexKey},
+    Store,
+};
+
+fn user_log(address: [u8; 20], topics: &[[u8; 32]]) -> LogItem {
+    let mut data = address.to_vec();
+    data.extend_from_slice(&3u32.to_le_bytes());
+    data.extend_from_slice(b"log");
+    data.extend_from_slice(&(topics.len() as u32).to_le_bytes());
+    for topic in topics {
+        data.extend_from_slice(topic);
+    }
+    LogItem::new_builder()
+        .service_flag(3u8.into())
+        .data(Bytes::from(data).pack())
+        .build()
+}
+
+fn receipt(logs: Vec<LogItem>) -> TxReceipt {
+    TxReceipt::new_builder().logs(logs.pack()).build()
+}
+
+fn cursor(block_number: u64, tx_index: u32, log_index: u32) -> LogCursor {
+    LogCursor {
+        block_number,
+        tx_index,
+        log_index,
+    }
+}
+
+#[test]
+fn test_log_index() {
+    let store = Store::open_tmp().unwrap();
+    let mut db = store.begin_transaction();
+
+    let (alice, bob) = ([1u8; 20], [2u8; 20]);
+    let (transfer, approval) = ([10u8; 32], [11u8; 32]);
+    let non_user_log = LogItem::new_builder().service_flag(1u8.into()).build();
+    for number in [3u64, 5] {
+        let receipts = [
+            receipt(vec![
+                non_user_log.clone(),
+                user_log(alice, &[transfer, transfer]),
+            ]),
+            receipt(vec![user_log(bob, &[approval]), user_log(alice, &[])]),
+        ];
+        db.insert_block_log_index(number, &receipts).unwrap();
+    }
+
+    let by_alice = db.get_log_cursors(&LogIndexKey::Address(alice), 0, 10, 10);
+    assert_eq!(
+        by_alice,
+        vec![
+            cursor(3, 0, 1),
+            cursor(3, 1, 1),
+            cursor(5, 0, 1),
+            cursor(5, 1, 1)
+        ]
+    );
+    let by_transfer = db.get_log_cursors(&LogIndexKey::Topic(transfer), 0, 10, 10);
+    assert_eq!(by_transfer, vec![cursor(3, 0, 1), cursor(5, 0, 1)]);
+
+    // Block range and limit
+    let by_bob = db.get_log_cursors(&LogIndexKey::Address(bob), 4, 5, 10);
+    assert_eq!(by_bob, vec![cursor(5, 1, 0)]);
+    let by_alice = db.get_log_cursors(&LogIndexKey::Address(alice), 3, 3, 1);
+    assert_eq!(by_alice, vec![cursor(3, 0, 1)]);
+    assert!(db
+        .get_log_cursors(&LogIndexKey::Topic(approval), 6, 10, 10)
+        .is_empty());
+}
+
+#[test]
+fn test_parse_user_log() {
+    let log = user_log([1u8; 20], &[[10u8; 32]]);
+    assert_eq!(parse_user_log(&log), Some(([1u8; 20], vec![[10u8; 32]])));
+
+    let non_user_log = LogItem::new_builder().service_flag(1u8.into()).build();
+    assert_eq!(parse_user_log(&non_user_log), None);
+}
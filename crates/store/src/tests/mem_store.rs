@@ -0,0 +1,42 @@
+use gw_types::h256::*;
+
+use crate::{
+    schema::COLUMN_DATA,
+    state::{
+        history::history_state::HistoryStateStore,
+        overlay::mem_store::{MemStore, OverlaySize},
+    },
+    traits::kv_store::KVStoreWrite,
+    Store,
+};
+
+#[test]
+fn test_overlay_size() {
+    let store = Store::open_tmp().unwrap();
+    let mut mem = MemStore::new(store.begin_transaction());
+    let size = |keys, bytes| OverlaySize { keys, bytes };
+
+    mem.insert_raw(COLUMN_DATA, b"k1", b"value").unwrap();
+    assert_eq!(mem.overlay_size(), size(1, 7));
+    // Overwriting only changes the value size.
+    mem.insert_raw(COLUMN_DATA, b"k1", b"v").unwrap();
+    assert_eq!(mem.overlay_size(), size(1, 3));
+    mem.delete(COLUMN_DATA, b"k1").unwrap();
+    assert_eq!(mem.overlay_size(), size(1, 2));
+    mem.delete(COLUMN_DATA, b"k2").unwrap();
+    assert_eq!(mem.overlay_size(), size(2, 4));
+
+    // Clones start with the same size and are tracked separately.
+    let mut clone = mem.clone();
+    clone.insert_raw(COLUMN_DATA, b"k3", b"v").unwrap();
+    assert_eq!(clone.overlay_size(), size(3, 7));
+    assert_eq!(mem.overlay_size(), size(2, 4));
+
+    mem.record_block_state(1, H256::from_u32(1), H256::from_u32(1))
+        .unwrap();
+    mem.record_block_state(1, H256::from_u32(1), H256::from_u32(2))
+        .unwrap();
+    assert_eq!(mem.overlay_size(), size(3, 68));
+    mem.remove_block_state_record(1).unwrap();
+    assert_eq!(mem.overlay_size(), size(2, 4));
+}
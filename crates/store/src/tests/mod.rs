@@ -1,2 +1,16 @@
+mod backend;
+mod backup;
+mod cache;
+mod deposit_history;
+mod fee_rebate;
+mod finalized_block_smt;
+mod fixture;
+mod fsck;
+mod log_index;
+mod mem_store;
+mod prune;
+mod secondary;
+mod snapshot;
 mod state_db;
+mod state_proof;
 mod transaction;
@@ -0,0 +1,60 @@
+use gw_types::{
+    h256::*,
+    packed::{L2Block, RawL2Block},
+    prelude::*,
+};
+
+use crate::{
+    schema::COLUMN_BLOCK,
+    state::history::history_state::HistoryStateStore,
+    traits::{chain_store::ChainStore, kv_store::KVStoreWrite},
+    transaction::StoreTransaction,
+    Store,
+};
+
+fn attach_block(db: &mut StoreTransaction, number: u64) -> L2Block {
+    let block = L2Block::new_builder()
+        .raw(RawL2Block::new_builder().number(number.pack()).build())
+        .build();
+    db.insert_raw(COLUMN_BLOCK, &block.hash(), block.as_slice())
+        .unwrap();
+    db.attach_block(block.clone()).unwrap();
+    block
+}
+
+#[test]
+fn test_prune_state_history() {
+    let store = Store::open_tmp().unwrap();
+    let mut db = store.begin_transaction();
+    let blocks: Vec<_> = (0..=4).map(|n| attach_block(&mut db, n)).collect();
+    let (key_a, key_b) = (H256::from_u32(1), H256::from_u32(2));
+    for number in 1..=3 {
+        db.record_block_state(number, key_a, H256::from_u32(number as u32))
+            .unwrap();
+    }
+    db.record_block_state(1, key_b, H256::from_u32(10)).unwrap();
+    db.set_block_state_changes(blocks[1].hash(), "{}").unwrap();
+
+    assert_eq!(db.prune_state_history(2).unwrap(), 3);
+    assert_eq!(db.get_pruned_block_number(), Some(2));
+    // Overwritten records are pruned, the last ones are kept.
+    let records: Vec<_> = (db.iter_block_state_record(1).into_iter())
+        .map(|k| k.state_key())
+        .collect();
+    assert_eq!(records, vec![key_b]);
+    assert!(db.get_block_state_changes(&blocks[1].hash()).is_none());
+    assert_eq!(db.get_history_state(2, &key_a), Some(H256::from_u32(2)));
+    assert_eq!(db.get_history_state(3, &key_a), Some(H256::from_u32(3)));
+    assert_eq!(db.get_history_state(4, &key_b), Some(H256::from_u32(10)));
+
+    // Pruned blocks are skipped.
+    assert_eq!(db.prune_state_history(1).unwrap(), 0);
+    assert_eq!(db.prune_state_history(3).unwrap(), 1);
+    assert!(db.iter_block_state_record(2).is_empty());
+    assert_eq!(db.get_history_state(4, &key_a), Some(H256::from_u32(3)));
+    assert_eq!(db.get_history_state(4, &key_b), Some(H256::from_u32(10)));
+
+    // Pruned blocks can't be detached.
+    db.detach_block(&blocks[4]).unwrap();
+    assert!(db.detach_block(&blocks[3]).is_err());
+}
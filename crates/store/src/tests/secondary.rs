@@ -0,0 +1,45 @@
+use autorocks::DbOptions;
+
+use crate::{
+    readonly::StoreReadonly,
+    schema::{COLUMNS, COLUMN_META},
+    traits::kv_store::{KVStoreRead, KVStoreWrite},
+    Store,
+};
+
+#[test]
+fn test_secondary_catch_up_with_primary() {
+    let dir = tempfile::tempdir().unwrap();
+    let secondary_dir = tempfile::tempdir().unwrap();
+    let store = Store::new(
+        DbOptions::new(dir.path(), COLUMNS)
+            .create_if_missing(true)
+            .create_missing_column_families(true)
+            .open()
+            .unwrap(),
+    );
+    let mut db = store.begin_transaction();
+    db.insert_raw(COLUMN_META, b"key", b"1").unwrap();
+    db.commit().unwrap();
+
+    let secondary =
+        StoreReadonly::open_secondary(dir.path(), secondary_dir.path(), COLUMNS).unwrap();
+    assert_eq!(
+        secondary.get(COLUMN_META, b"key").as_deref(),
+        Some(&b"1"[..])
+    );
+
+    let mut db = store.begin_transaction();
+    db.insert_raw(COLUMN_META, b"key", b"2").unwrap();
+    db.commit().unwrap();
+    assert_eq!(
+        secondary.get(COLUMN_META, b"key").as_deref(),
+        Some(&b"1"[..])
+    );
+
+    secondary.try_catch_up_with_primary().unwrap();
+    assert_eq!(
+        secondary.get(COLUMN_META, b"key").as_deref(),
+        Some(&b"2"[..])
+    );
+}
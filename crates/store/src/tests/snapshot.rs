@@ -0,0 +1,68 @@
+use autorocks::Direction;
+
+use crate::{
+    schema::COLUMN_DATA,
+    snapshot::{ScanRange, StoreSnapshot},
+    traits::kv_store::KVStoreWrite,
+    Store,
+};
+
+fn scan_keys(snap: &StoreSnapshot, range: ScanRange, dir: Direction) -> Vec<Vec<u8>> {
+    snap.scan(COLUMN_DATA, range, dir)
+        .map(|(key, _)| key.to_vec())
+        .collect()
+}
+
+fn keys(keys: &[&[u8]]) -> Vec<Vec<u8>> {
+    keys.iter().map(|k| k.to_vec()).collect()
+}
+
+#[test]
+fn test_snapshot_scan() {
+    let store = Store::open_tmp().unwrap();
+    let mut db = store.begin_transaction();
+    for key in [&b"a1"[..], b"a2", b"b1", b"b2", b"c1", b"\xff", b"\xff\x01"] {
+        db.insert_raw(COLUMN_DATA, key, b"v").unwrap();
+    }
+    db.commit().unwrap();
+    let snap = store.get_snapshot();
+
+    // Writes after the snapshot are not visible.
+    let mut db = store.begin_transaction();
+    db.insert_raw(COLUMN_DATA, b"a3", b"v").unwrap();
+    db.delete(COLUMN_DATA, b"b1").unwrap();
+    db.commit().unwrap();
+
+    use Direction::{Backward, Forward};
+    assert_eq!(
+        scan_keys(&snap, ScanRange::prefix(b"a"), Forward),
+        keys(&[b"a1", b"a2"])
+    );
+    assert_eq!(
+        scan_keys(&snap, ScanRange::prefix(b"a"), Backward),
+        keys(&[b"a2", b"a1"])
+    );
+    assert_eq!(
+        scan_keys(&snap, ScanRange::range(b"a2", b"c1"), Forward),
+        keys(&[b"a2", b"b1", b"b2"])
+    );
+    assert_eq!(
+        scan_keys(&snap, ScanRange::range(b"a2", b"c1"), Backward),
+        keys(&[b"b2", b"b1", b"a2"])
+    );
+    // Prefix and range together.
+    let range = ScanRange {
+        prefix: Some(b"b".to_vec()),
+        start: Some(b"b2".to_vec()),
+        end: None,
+    };
+    assert_eq!(scan_keys(&snap, range.clone(), Forward), keys(&[b"b2"]));
+    assert_eq!(scan_keys(&snap, range, Backward), keys(&[b"b2"]));
+    // Prefix without a successor.
+    assert_eq!(
+        scan_keys(&snap, ScanRange::prefix(b"\xff"), Backward),
+        keys(&[b"\xff\x01", b"\xff"])
+    );
+    assert_eq!(scan_keys(&snap, ScanRange::all(), Forward).len(), 7);
+    assert!(scan_keys(&snap, ScanRange::prefix(b"d"), Forward).is_empty());
+}
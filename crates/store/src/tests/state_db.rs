@@ -1,4 +1,5 @@
 use gw_common::{merkle_utils::calculate_state_checkpoint, state::State};
+use gw_smt::smt::SMT;
 use gw_types::{
     h256::*,
     packed::{
@@ -9,7 +10,12 @@ use gw_types::{
 
 use crate::{
     schema::COLUMN_BLOCK,
-    state::{history::history_state::RWConfig, traits::JournalDB, BlockStateDB},
+    smt::smt_store::SMTStateStore,
+    state::{
+        history::history_state::{HistoryState, HistoryStateStore, RWConfig},
+        traits::JournalDB,
+        BlockStateDB,
+    },
     traits::{chain_store::ChainStore, kv_store::KVStoreWrite},
     transaction::StoreTransaction,
     Store,
@@ -244,3 +250,121 @@ fn test_state_with_version() {
         assert_eq!(v, H256::zero());
     }
 }
+
+#[test]
+fn test_detach_blocks_state() {
+    let store = Store::open_tmp().unwrap();
+    let mut prev_txs_state_checkpoint = calculate_state_checkpoint(&H256::zero(), 0);
+    let genesis = L2Block::new_builder()
+        .raw(
+            RawL2Block::new_builder()
+                .submit_transactions(
+                    SubmitTransactions::new_builder()
+                        .prev_state_checkpoint(prev_txs_state_checkpoint.pack())
+                        .build(),
+                )
+                .build(),
+        )
+        .build();
+    let mut db = store.begin_transaction();
+    db.set_block_smt_root(H256::zero()).unwrap();
+    commit_block(&mut db, genesis);
+    db.commit().unwrap();
+
+    // block n sets key n and overwrites key 1
+    for n in 1..=3u32 {
+        let mut db = store.begin_transaction();
+        let mut state =
+            BlockStateDB::from_store(&mut db, RWConfig::attach_block(n.into())).unwrap();
+        state
+            .update_raw(H256::from_u32(1), H256::from_u32(10 + n))
+            .unwrap();
+        state
+            .update_raw(H256::from_u32(n + 1), H256::from_u32(n))
+            .unwrap();
+        let block = build_block(&mut state, n.into(), prev_txs_state_checkpoint);
+        prev_txs_state_checkpoint = state.calculate_state_checkpoint().unwrap();
+        commit_block(&mut db, block);
+        db.commit().unwrap();
+    }
+
+    // detach block 2 and 3
+    {
+        let mut db = store.begin_transaction();
+        db.detach_block(&db.get_tip_block().unwrap()).unwrap();
+        db.detach_block(&db.get_tip_block().unwrap()).unwrap();
+        {
+            let mut state = BlockStateDB::from_store(&mut db, RWConfig::detach_block()).unwrap();
+            state.detach_blocks_state(2, 3).unwrap();
+        }
+        let expected_root: H256 = db
+            .get_tip_block()
+            .unwrap()
+            .raw()
+            .post_account()
+            .merkle_root()
+            .unpack();
+        {
+            let state = BlockStateDB::from_store(&mut db, RWConfig::readonly()).unwrap();
+            assert_eq!(state.calculate_root().unwrap(), expected_root);
+        }
+        db.commit().unwrap();
+    }
+    {
+        let mut db = store.begin_transaction();
+        {
+            let state = BlockStateDB::from_store(&mut db, RWConfig::readonly()).unwrap();
+            assert_eq!(
+                state.get_raw(&H256::from_u32(1)).unwrap(),
+                H256::from_u32(11)
+            );
+            assert_eq!(
+                state.get_raw(&H256::from_u32(2)).unwrap(),
+                H256::from_u32(1)
+            );
+            assert_eq!(state.get_raw(&H256::from_u32(3)).unwrap(), H256::zero());
+            assert_eq!(state.get_raw(&H256::from_u32(4)).unwrap(), H256::zero());
+        }
+        assert!(db.iter_block_state_record(2).is_empty());
+        assert!(db.iter_block_state_record(3).is_empty());
+        assert_eq!(
+            db.get_history_state(3, &H256::from_u32(1)),
+            Some(H256::from_u32(11))
+        );
+    }
+}
+
+#[test]
+fn test_update_raw_batch() {
+    // Update existing keys, delete one and insert new ones.
+    let mut kvs: Vec<(H256, H256)> = (8..24)
+        .map(|i| (H256::from_u32(i), H256::from_u32(i + 100)))
+        .collect();
+    kvs.push((H256::from_u32(1), H256::zero()));
+
+    let update = |batch: bool| {
+        let store = Store::open_tmp().unwrap();
+        let mut db = store.begin_transaction();
+        let smt = SMT::new(H256::zero().into(), SMTStateStore::new(&mut db));
+        let mut state = HistoryState::new(smt, 0, RWConfig::attach_block(1));
+        for i in 1..16 {
+            state
+                .update_raw(H256::from_u32(i), H256::from_u32(i))
+                .unwrap();
+        }
+        if batch {
+            state.update_raw_batch(kvs.clone()).unwrap();
+        } else {
+            for (k, v) in kvs.iter() {
+                state.update_raw(*k, *v).unwrap();
+            }
+        }
+        let root = state.calculate_root().unwrap();
+        drop(state);
+        for (k, v) in kvs.iter() {
+            assert_eq!(db.get_history_state(1, k), Some(*v));
+        }
+        root
+    };
+    assert_eq!(update(true), update(false));
+}
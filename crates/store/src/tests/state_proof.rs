@@ -0,0 +1,98 @@
+use gw_common::state::State;
+use gw_smt::verify::verify_multi_proof;
+use gw_types::{
+    h256::*,
+    packed::{AccountMerkleState, L2Block, RawL2Block},
+    prelude::*,
+};
+
+use crate::{
+    schema::COLUMN_BLOCK,
+    state::{history::history_state::RWConfig, traits::JournalDB, BlockStateDB},
+    traits::{chain_store::ChainStore, kv_store::KVStoreWrite},
+    Store,
+};
+
+fn attach_block(store: &Store, number: u64, kvs: &[(u32, u32)]) -> L2Block {
+    let mut db = store.begin_transaction();
+    let post_account = if number == 0 {
+        AccountMerkleState::default()
+    } else {
+        let mut state = BlockStateDB::from_store(&mut db, RWConfig::attach_block(number)).unwrap();
+        for (k, v) in kvs {
+            state
+                .update_raw(H256::from_u32(*k), H256::from_u32(*v))
+                .unwrap();
+        }
+        state.finalise().unwrap();
+        AccountMerkleState::new_builder()
+            .merkle_root(state.calculate_root().unwrap().pack())
+            .build()
+    };
+    let block = L2Block::new_builder()
+        .raw(
+            RawL2Block::new_builder()
+                .number(number.pack())
+                .post_account(post_account)
+                .build(),
+        )
+        .build();
+    db.insert_raw(COLUMN_BLOCK, &block.hash(), block.as_slice())
+        .unwrap();
+    db.attach_block(block.clone()).unwrap();
+    db.commit().unwrap();
+    block
+}
+
+#[test]
+fn test_generate_state_proof() {
+    let store = Store::open_tmp().unwrap();
+    let mut db = store.begin_transaction();
+    db.set_block_smt_root(H256::zero()).unwrap();
+    db.commit().unwrap();
+    attach_block(&store, 0, &[]);
+    let block_1 = attach_block(&store, 1, &[(1, 1), (2, 2)]);
+    let block_2 = attach_block(&store, 2, &[(1, 3), (3, 3)]);
+
+    let keys: Vec<_> = (1..=4).map(H256::from_u32).collect();
+    for (block, values) in [(&block_1, [1, 2, 0, 0]), (&block_2, [3, 2, 3, 0])] {
+        let proof = store
+            .generate_state_proof(&block.hash(), &keys)
+            .unwrap()
+            .unwrap();
+        let root: H256 = block.raw().post_account().merkle_root().unpack();
+        let expected: Vec<_> = keys
+            .iter()
+            .zip(values)
+            .map(|(k, v)| (*k, H256::from_u32(v)))
+            .collect();
+        assert_eq!(proof.leaves, expected);
+        assert!(verify_multi_proof(&root, &proof.proof, proof.leaves).unwrap());
+    }
+    // Historical proofs don't touch the store.
+    assert_eq!(
+        store.get_last_valid_tip_block().unwrap().hash(),
+        block_2.hash()
+    );
+
+    // State at the pruned block number is retained.
+    let mut db = store.begin_transaction();
+    db.prune_state_history(1).unwrap();
+    db.commit().unwrap();
+    assert!(store
+        .generate_state_proof(&block_1.hash(), &keys)
+        .unwrap()
+        .is_some());
+    // But not before it.
+    let mut db = store.begin_transaction();
+    db.prune_state_history(2).unwrap();
+    db.commit().unwrap();
+    assert!(store
+        .generate_state_proof(&block_1.hash(), &keys)
+        .unwrap()
+        .is_none());
+    assert!(store
+        .generate_state_proof(&H256::from_u32(42), &keys)
+        .unwrap()
+        .is_none());
+}
@@ -96,6 +96,39 @@ pub trait ChainStore: KVStoreRead {
         Some(from_box_should_be_ok!(NumberHashReader, data))
     }
 
+    /// Last block number in the finalized block SMT, None if it's empty.
+    fn get_finalized_block_smt_tip(&self) -> Option<u64> {
+        let data = self.get(COLUMN_META, META_FINALIZED_BLOCK_SMT_TIP_KEY)?;
+        Some(packed::Uint64Reader::from_slice_should_be_ok(data.as_ref()).unpack())
+    }
+
+    /// Oldest block with full content, None if the store is not bootstrapped
+    /// from a state snapshot, i.e. all blocks are available.
+    fn get_oldest_block_number(&self) -> Option<u64> {
+        let data = self.get(COLUMN_META, META_OLDEST_BLOCK_NUMBER_KEY)?;
+        Some(packed::Uint64Reader::from_slice_should_be_ok(data.as_ref()).unpack())
+    }
+
+    /// Block the store is bootstrapped from, None if the store is not
+    /// bootstrapped from a state snapshot.
+    fn get_snapshot_block_number(&self) -> Option<u64> {
+        let data = self.get(COLUMN_META, META_SNAPSHOT_BLOCK_NUMBER_KEY)?;
+        Some(packed::Uint64Reader::from_slice_should_be_ok(data.as_ref()).unpack())
+    }
+
+    /// Block up to which state history is pruned, None if nothing is pruned.
+    /// State at these blocks is not available, and they can't be detached.
+    fn get_pruned_block_number(&self) -> Option<u64> {
+        let data = self.get(COLUMN_META, META_PRUNED_BLOCK_NUMBER_KEY)?;
+        Some(packed::Uint64Reader::from_slice_should_be_ok(data.as_ref()).unpack())
+    }
+
+    /// Block the node halted on because of a post state root mismatch.
+    fn get_state_mismatch_halt(&self) -> Option<H256> {
+        let data = self.get(COLUMN_META, META_STATE_MISMATCH_HALT_KEY)?;
+        Some(packed::Byte32Reader::from_slice_should_be_ok(data.as_ref()).unpack())
+    }
+
     fn get_block_status(&self, block_number: u64) -> BlockStatus {
         if Some(block_number)
             <= self
@@ -293,6 +326,17 @@ pub trait ChainStore: KVStoreRead {
         }
     }
 
+    /// Iterate reverted block hashes of `root` and its previous roots.
+    fn iter_reverted_block_smt_root(&self, root: H256) -> RervertedBlockHashesIter<'_, Self>
+    where
+        Self: Sized,
+    {
+        RervertedBlockHashesIter {
+            snap: self,
+            next_root: root,
+        }
+    }
+
     fn get_reverted_block_hashes_by_root(
         &self,
         reverted_block_smt_root: &H256,
@@ -360,3 +404,30 @@ pub trait ChainStore: KVStoreRead {
 impl<T: ChainStore> ChainStore for &T {}
 impl<T: ChainStore> ChainStore for &mut T {}
 impl<T: ChainStore> ChainStore for &RefCell<T> {}
+
+pub struct RervertedBlockHashesIter<'a, S> {
+    snap: &'a S,
+    next_root: H256,
+}
+
+impl<'a, S: KVStoreRead> Iterator for RervertedBlockHashesIter<'a, S> {
+    type Item = (H256, Vec<H256>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let snap = &self.snap;
+        let root = self.next_root;
+
+        snap.get(COLUMN_REVERTED_BLOCK_SMT_ROOT, root.as_slice())
+            .map(|slice| {
+                let mut block_hashes: Vec<_> =
+                    from_box_should_be_ok!(packed::Byte32VecReader, slice).unpack();
+
+                // First hash is root
+                let last_hash_idx = block_hashes.len().saturating_sub(1);
+                block_hashes.swap(0, last_hash_idx);
+                self.next_root = block_hashes.pop().expect("iter prev reverted block root");
+
+                (root, block_hashes)
+            })
+    }
+}
@@ -1,6 +1,7 @@
 use std::cell::RefCell;
 
 use anyhow::Result;
+use autorocks::Direction;
 
 use crate::schema::Col;
 
@@ -8,6 +9,14 @@ pub trait KVStoreRead {
     fn get(&self, col: Col, key: &[u8]) -> Option<Box<[u8]>>;
 }
 
+pub type KVIter<'a> = Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
+
+/// Iterate a column of a consistent view of the store, i.e. a snapshot or a
+/// readonly db.
+pub trait KVStoreIter {
+    fn iter(&self, col: Col, dir: Direction) -> KVIter<'_>;
+}
+
 pub trait KVStoreWrite {
     fn insert_raw(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<()>;
     fn delete(&mut self, col: Col, key: &[u8]) -> Result<()>;
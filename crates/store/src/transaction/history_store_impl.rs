@@ -38,6 +38,29 @@ impl HistoryStateStore for StoreTransaction {
         Ok(())
     }
 
+    fn remove_block_state_records(&mut self, first: u64, last: u64) -> Result<(), Error> {
+        // Reverse records are read by history queries, delete them in the
+        // transaction.
+        for block_number in first..=last {
+            for record_key in self.iter_block_state_record(block_number) {
+                let reverse_key = BlockStateRecordKeyReverse::new(
+                    record_key.block_number(),
+                    &record_key.state_key(),
+                );
+                self.delete(COLUMN_BLOCK_STATE_REVERSE_RECORD, reverse_key.as_slice())?;
+            }
+        }
+        // Records are only reached through reverse records or by block number.
+        // Records left behind are overwritten or reset to their current value
+        // when the block number is attached and detached again.
+        self.delete_range_after_commit(
+            COLUMN_BLOCK_STATE_RECORD,
+            &first.to_be_bytes(),
+            &(last + 1).to_be_bytes(),
+        );
+        Ok(())
+    }
+
     fn get_history_state(&self, block_number: u64, state_key: &H256) -> Option<H256> {
         let key = BlockStateRecordKeyReverse::new(block_number, state_key);
         let mut raw_iter = self.get_iter(
@@ -1,5 +1,6 @@
 mod code_store_impl;
 mod history_store_impl;
+mod prune;
 mod store_transaction;
 mod transaction_snapshot;
 
@@ -0,0 +1,80 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use autorocks::Direction;
+use gw_types::{h256::H256, prelude::*};
+
+use crate::{
+    schema::{
+        COLUMN_BLOCK_STATE_RECORD, COLUMN_BLOCK_STATE_REVERSE_RECORD, COLUMN_META,
+        META_PRUNED_BLOCK_NUMBER_KEY,
+    },
+    state::history::{
+        block_state_record::{BlockStateRecordKey, BlockStateRecordKeyReverse},
+        history_state::HistoryStateStore,
+    },
+    traits::{
+        chain_store::ChainStore,
+        kv_store::{KVStoreRead, KVStoreWrite},
+    },
+};
+
+use super::StoreTransaction;
+
+impl StoreTransaction {
+    /// Prune state history of blocks up to `to_block`, i.e. block state
+    /// records overwritten by later records up to `to_block`, and block state
+    /// changes. Returns the number of newly pruned blocks.
+    ///
+    /// The last record of every state key is kept, so state at `to_block` and
+    /// later blocks is still available. State before `to_block` is not, and
+    /// these blocks can't be detached anymore. Callers must make sure they
+    /// are finalized.
+    pub fn prune_state_history(&mut self, to_block: u64) -> Result<u64> {
+        let first = self.get_pruned_block_number().map_or(0, |n| n + 1);
+        if to_block < first {
+            return Ok(0);
+        }
+
+        let mut state_keys = HashSet::new();
+        for block_number in first..=to_block {
+            for record_key in self.iter_block_state_record(block_number) {
+                state_keys.insert(record_key.state_key());
+            }
+            if let Some(block_hash) = self.get_block_hash_by_number(block_number)? {
+                self.delete_block_state_changes(block_hash)?;
+            }
+        }
+        // Only keys touched by the pruned blocks have new stale records, the
+        // last records kept by previous prunings included.
+        for state_key in state_keys {
+            let mut stale = self.iter_state_key_records(&state_key, to_block);
+            stale.pop();
+            for block_number in stale {
+                let reverse_key = BlockStateRecordKeyReverse::new(block_number, &state_key);
+                self.delete(COLUMN_BLOCK_STATE_REVERSE_RECORD, reverse_key.as_slice())?;
+                let key = BlockStateRecordKey::new(block_number, &state_key);
+                self.delete(COLUMN_BLOCK_STATE_RECORD, key.as_slice())?;
+            }
+        }
+
+        self.insert_raw(
+            COLUMN_META,
+            META_PRUNED_BLOCK_NUMBER_KEY,
+            to_block.pack().as_slice(),
+        )?;
+        Ok(to_block - first + 1)
+    }
+
+    /// Block numbers of the records of `state_key` up to `to_block`, in
+    /// ascending order.
+    fn iter_state_key_records(&self, state_key: &H256, to_block: u64) -> Vec<u64> {
+        let start_key = BlockStateRecordKeyReverse::new(0, state_key);
+        let mut iter = self.get_iter(COLUMN_BLOCK_STATE_REVERSE_RECORD, Direction::Forward);
+        iter.seek(start_key.as_slice());
+        iter.map(|(key, _value)| BlockStateRecordKeyReverse::from_slice(&key))
+            .take_while(|key| &key.state_key() == state_key && key.block_number() <= to_block)
+            .map(|key| key.block_number())
+            .collect()
+    }
+}
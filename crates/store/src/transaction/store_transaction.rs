@@ -1,6 +1,7 @@
 #![allow(clippy::mutable_key_type)]
 
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use anyhow::{bail, Context, Result};
 use autorocks::moveit::slot;
@@ -18,8 +19,13 @@ use gw_types::{
     prelude::*,
 };
 
+use crate::cache::{read_through, CacheWrites, StoreCache};
+use crate::latency::{trace_op, StoreOp};
 use crate::schema::*;
-use crate::smt::smt_store::{SMTBlockStore, SMTRevertedBlockStore, SMTStateStore};
+use crate::smt::smt_store::{
+    SMTBlockStore, SMTFinalizedBlockStore, SMTRevertedBlockStore, SMTStateStore,
+};
+use crate::store_impl::write_skip_concurrency_control;
 use crate::traits::chain_store::ChainStore;
 use crate::traits::kv_store::KVStoreRead;
 use crate::traits::kv_store::{KVStore, KVStoreWrite};
@@ -28,25 +34,49 @@ use super::TransactionSnapshot;
 
 pub struct StoreTransaction {
     pub(crate) inner: autorocks::Transaction,
+    pub(crate) db: autorocks::TransactionDb,
+    /// Range deletes to apply after commit, `(col, begin_key, end_key)`.
+    pub(crate) range_deletes: Vec<(Col, Vec<u8>, Vec<u8>)>,
+    pub(crate) cache: Arc<StoreCache>,
+    pub(crate) cache_writes: CacheWrites,
 }
 
 impl KVStoreRead for StoreTransaction {
+    #[track_caller]
     fn get(&self, col: Col, key: &[u8]) -> Option<Box<[u8]>> {
-        slot!(slice);
-        self.inner
-            .get(col, key, slice)
-            .expect("db operation should be ok")
-            .map(|p| p.as_ref().into())
+        trace_op(StoreOp::Get, col, key, || {
+            let cache = if self.cache_writes.written(col) {
+                None
+            } else {
+                self.cache.column(col)
+            };
+            let generation = cache.and_then(|c| c.generation());
+            read_through(cache, generation, key, || {
+                slot!(slice);
+                self.inner
+                    .get(col, key, slice)
+                    .expect("db operation should be ok")
+                    .map(|p| p.as_ref().into())
+            })
+        })
     }
 }
 
 impl KVStoreWrite for StoreTransaction {
+    #[track_caller]
     fn insert_raw(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
-        Ok(self.inner.put(col, key, value)?)
+        self.cache_writes.record(col, key);
+        trace_op(StoreOp::Put, col, key, || {
+            Ok(self.inner.put(col, key, value)?)
+        })
     }
 
+    #[track_caller]
     fn delete(&mut self, col: Col, key: &[u8]) -> Result<()> {
-        Ok(self.inner.delete(col, key)?)
+        self.cache_writes.record(col, key);
+        trace_op(StoreOp::Delete, col, key, || {
+            Ok(self.inner.delete(col, key)?)
+        })
     }
 }
 impl KVStore for StoreTransaction {}
@@ -54,27 +84,56 @@ impl ChainStore for StoreTransaction {}
 
 impl StoreTransaction {
     pub fn commit(&mut self) -> Result<()> {
-        self.inner.commit()?;
+        match self.cache.column(COLUMN_ACCOUNT_SMT_LEAF) {
+            Some(cache) if self.cache_writes.written(COLUMN_ACCOUNT_SMT_LEAF) => {
+                cache.begin_write();
+                let result = self.inner.commit();
+                cache.end_write(self.cache_writes.state_keys());
+                result?;
+            }
+            _ => self.inner.commit()?,
+        }
+        self.cache_writes.clear();
+        if !self.range_deletes.is_empty() {
+            let mut write_batch = self.db.new_write_batch();
+            for (col, begin_key, end_key) in self.range_deletes.drain(..) {
+                write_batch.delete_range(col, &begin_key, &end_key)?;
+            }
+            write_skip_concurrency_control(&self.db, &mut write_batch)?;
+        }
         Ok(())
     }
 
     pub fn rollback(&mut self) -> Result<()> {
         self.inner.rollback()?;
+        self.range_deletes.clear();
+        self.cache_writes.clear();
         Ok(())
     }
 
+    /// Delete entries in the range of [`begin_key`, `end_key`) after commit.
+    ///
+    /// Transactional range delete is not supported, so the entries are still
+    /// visible in this transaction, and remain if the process exits between
+    /// commit and the range delete. Only use it for entries that are harmless
+    /// to leave behind.
+    pub fn delete_range_after_commit(&mut self, col: Col, begin_key: &[u8], end_key: &[u8]) {
+        (self.range_deletes).push((col, begin_key.to_vec(), end_key.to_vec()));
+    }
+
     pub fn snapshot(&self) -> TransactionSnapshot {
         TransactionSnapshot {
             inner: self.inner.timestamped_snapshot(),
         }
     }
 
+    #[track_caller]
     pub(crate) fn get_iter(
         &self,
         col: Col,
         dir: Direction,
     ) -> DbIterator<&'_ autorocks::Transaction> {
-        self.inner.iter(col, dir)
+        trace_op(StoreOp::Iter, col, &[], || self.inner.iter(col, dir))
     }
 
     pub fn setup_chain_id(&mut self, chain_id: H256) -> Result<()> {
@@ -138,18 +197,8 @@ impl StoreTransaction {
         withdrawals: Vec<packed::WithdrawalRequestExtra>,
     ) -> Result<()> {
         debug_assert_eq!(block.transactions().len(), tx_receipts.len());
-        debug_assert_eq!(block.withdrawals().len(), withdrawals.len());
         let block_hash = block.hash();
-        self.insert_raw(COLUMN_BLOCK, &block_hash, block.as_slice())?;
-        self.insert_raw(
-            COLUMN_BLOCK_GLOBAL_STATE,
-            &block_hash,
-            global_state.as_slice(),
-        )?;
-        self.set_block_deposit_info_vec(
-            block.raw().number().unpack(),
-            &deposit_info_vec.as_reader(),
-        )?;
+        let block_number: u64 = block.raw().number().unpack();
 
         // Verify prev tx state and insert
         {
@@ -182,23 +231,53 @@ impl StoreTransaction {
             }
         }
 
-        for (index, (tx, tx_receipt)) in block
-            .transactions()
-            .into_iter()
-            .zip(tx_receipts.iter())
-            .enumerate()
-        {
+        self.insert_block_without_receipts(block, global_state, deposit_info_vec, withdrawals)?;
+        for (index, tx_receipt) in tx_receipts.iter().enumerate() {
             let key = TransactionKey::new_builder()
                 .block_hash(block_hash.pack())
                 .index(index.pack())
                 .build();
-            self.insert_raw(COLUMN_TRANSACTION, key.as_slice(), tx.as_slice())?;
             self.insert_raw(
                 COLUMN_TRANSACTION_RECEIPT,
                 key.as_slice(),
                 tx_receipt.as_slice(),
             )?;
         }
+        self.insert_block_log_index(block_number, &tx_receipts)?;
+
+        Ok(())
+    }
+
+    /// Insert a block whose tx receipts are not available, e.g. a recent block
+    /// of a state snapshot.
+    pub fn insert_block_without_receipts(
+        &mut self,
+        block: packed::L2Block,
+        global_state: packed::GlobalState,
+        deposit_info_vec: packed::DepositInfoVec,
+        withdrawals: Vec<packed::WithdrawalRequestExtra>,
+    ) -> Result<()> {
+        debug_assert_eq!(block.withdrawals().len(), withdrawals.len());
+        let block_hash = block.hash();
+        self.insert_raw(COLUMN_BLOCK, &block_hash, block.as_slice())?;
+        self.insert_raw(
+            COLUMN_BLOCK_GLOBAL_STATE,
+            &block_hash,
+            global_state.as_slice(),
+        )?;
+        self.set_block_deposit_info_vec(
+            block.raw().number().unpack(),
+            &deposit_info_vec.as_reader(),
+        )?;
+        self.insert_block_deposit_history(block.raw().number().unpack(), &deposit_info_vec)?;
+
+        for (index, tx) in block.transactions().into_iter().enumerate() {
+            let key = TransactionKey::new_builder()
+                .block_hash(block_hash.pack())
+                .index(index.pack())
+                .build();
+            self.insert_raw(COLUMN_TRANSACTION, key.as_slice(), tx.as_slice())?;
+        }
         for (index, withdrawal) in withdrawals.into_iter().enumerate() {
             let key = WithdrawalKey::new_builder()
                 .block_hash(block_hash.pack())
@@ -226,6 +305,43 @@ impl StoreTransaction {
         SMTRevertedBlockStore::new(self).to_smt()
     }
 
+    pub fn finalized_block_smt(&mut self) -> Result<SMT<SMTFinalizedBlockStore<&mut Self>>> {
+        SMTFinalizedBlockStore::new(self).to_smt()
+    }
+
+    /// Insert blocks up to `finalized_block_number` into the finalized block
+    /// SMT. Returns the root of the SMT.
+    pub fn advance_finalized_block_smt(&mut self, finalized_block_number: u64) -> Result<H256> {
+        const BATCH: u64 = 1000;
+
+        let mut next = match self.get_finalized_block_smt_tip() {
+            Some(tip) => tip + 1,
+            None => 0,
+        };
+        while next <= finalized_block_number {
+            let end = finalized_block_number.min(next + BATCH - 1);
+            let mut leaves = Vec::with_capacity((end - next + 1) as usize);
+            for number in next..=end {
+                let block_hash = self
+                    .get_block_hash_by_number(number)?
+                    .with_context(|| format!("get block hash of #{}", number))?;
+                leaves.push((packed::RawL2Block::compute_smt_key(number), block_hash));
+            }
+            let mut smt = self.finalized_block_smt()?;
+            for (key, block_hash) in leaves {
+                smt.update(key.into(), block_hash.into())
+                    .context("update finalized block smt")?;
+            }
+            self.insert_raw(
+                COLUMN_META,
+                META_FINALIZED_BLOCK_SMT_TIP_KEY,
+                end.pack().as_slice(),
+            )?;
+            next = end + 1;
+        }
+        Ok((*self.finalized_block_smt()?.root()).into())
+    }
+
     // TODO: prune db state
     pub fn get_reverted_block_hashes(&self) -> Result<HashSet<H256>> {
         let iter = self.get_iter(COLUMN_REVERTED_BLOCK_SMT_LEAF, Direction::Backward);
@@ -257,6 +373,56 @@ impl StoreTransaction {
         self.set_block_smt_root(root.into())
     }
 
+    /// Index a block without its content. Used to bootstrap a store from a
+    /// state snapshot.
+    pub fn insert_block_hash(&mut self, block_number: u64, block_hash: &H256) -> Result<()> {
+        let number = block_number.pack();
+        self.insert_raw(COLUMN_INDEX, number.as_slice(), block_hash.as_slice())?;
+        self.insert_raw(COLUMN_INDEX, block_hash.as_slice(), number.as_slice())?;
+
+        let mut block_smt = self.block_smt()?;
+        block_smt
+            .update(
+                packed::RawL2Block::compute_smt_key(block_number).into(),
+                (*block_hash).into(),
+            )
+            .context("update block smt")?;
+        let root = *block_smt.root();
+        self.set_block_smt_root(root.into())
+    }
+
+    /// See [`ChainStore::get_oldest_block_number`] and
+    /// [`ChainStore::get_snapshot_block_number`].
+    pub fn set_snapshot_block_numbers(
+        &mut self,
+        oldest_block_number: u64,
+        snapshot_block_number: u64,
+    ) -> Result<()> {
+        self.insert_raw(
+            COLUMN_META,
+            META_OLDEST_BLOCK_NUMBER_KEY,
+            oldest_block_number.pack().as_slice(),
+        )?;
+        self.insert_raw(
+            COLUMN_META,
+            META_SNAPSHOT_BLOCK_NUMBER_KEY,
+            snapshot_block_number.pack().as_slice(),
+        )
+    }
+
+    /// See [`ChainStore::get_state_mismatch_halt`].
+    pub fn set_state_mismatch_halt(&mut self, block_hash: &H256) -> Result<()> {
+        self.insert_raw(
+            COLUMN_META,
+            META_STATE_MISMATCH_HALT_KEY,
+            block_hash.as_slice(),
+        )
+    }
+
+    pub fn clear_state_mismatch_halt(&mut self) -> Result<()> {
+        self.delete(COLUMN_META, META_STATE_MISMATCH_HALT_KEY)
+    }
+
     fn set_last_valid_tip_block_hash(&mut self, block_hash: &H256) -> Result<()> {
         self.insert_raw(
             COLUMN_META,
@@ -404,8 +570,34 @@ impl StoreTransaction {
         Ok(())
     }
 
-    /// Delete bad block and block global state.
+    /// Save withdrawals of a bad block, so a challenge on them can be
+    /// verified and cancelled offchain.
+    pub fn insert_bad_block_withdrawals(
+        &mut self,
+        block_hash: &H256,
+        withdrawals: &[packed::WithdrawalRequestExtra],
+    ) -> Result<()> {
+        for (index, withdrawal) in withdrawals.iter().enumerate() {
+            let key = WithdrawalKey::new_builder()
+                .block_hash(block_hash.pack())
+                .index(index.pack())
+                .build();
+            self.insert_raw(COLUMN_WITHDRAWAL, key.as_slice(), withdrawal.as_slice())?;
+        }
+        Ok(())
+    }
+
+    /// Delete bad block, its withdrawals and block global state.
     pub fn delete_bad_block(&mut self, block_hash: &H256) -> Result<()> {
+        if let Some(block) = self.get_bad_block(block_hash) {
+            for index in 0..block.withdrawals().len() {
+                let key = WithdrawalKey::new_builder()
+                    .block_hash(block_hash.pack())
+                    .index(index.pack())
+                    .build();
+                self.delete(COLUMN_WITHDRAWAL, key.as_slice())?;
+            }
+        }
         self.delete(COLUMN_BAD_BLOCK, block_hash.as_slice())?;
         self.delete(COLUMN_BLOCK_GLOBAL_STATE, block_hash.as_slice())?;
         Ok(())
@@ -512,6 +704,21 @@ impl StoreTransaction {
             let tip = self.get_last_valid_tip_block_hash()?;
             assert_eq!(tip, block.raw().hash(), "Must detach from tip");
         }
+        {
+            let number: u64 = block.raw().number().unpack();
+            if Some(number) <= self.get_snapshot_block_number() {
+                bail!(
+                    "can't detach block #{}, state before it is not available in the store bootstrapped from a state snapshot",
+                    number
+                );
+            }
+            if Some(number) <= self.get_pruned_block_number() {
+                bail!(
+                    "can't detach block #{}, state history of it is pruned",
+                    number
+                );
+            }
+        }
         {
             let number: u64 = block.raw().number().unpack();
             let hash: Byte32 = block.hash().pack();
@@ -533,6 +740,8 @@ impl StoreTransaction {
         // remove index
         let block_number = block.raw().number();
         self.delete(COLUMN_INDEX, block_number.as_slice())?;
+        self.delete_block_fee_rebates(block_number.unpack())?;
+        self.delete_block_log_index(block)?;
         self.delete(COLUMN_INDEX, block_hash.as_slice())?;
 
         // update block tree
@@ -578,6 +787,7 @@ impl StoreTransaction {
         }
 
         self.delete_submit_tx(block_number)?;
+        self.delete_block_deposit_history(block_number)?;
         self.delete_block_deposit_info_vec(block_number)?;
         self.delete_block_post_finalized_custodian_capacity(block_number)?;
         self.delete_block_state_changes(block_hash)?;
@@ -617,6 +827,7 @@ impl StoreTransaction {
     pub fn remove_mem_pool_transaction(&mut self, tx_hash: &H256) -> Result<()> {
         self.delete(COLUMN_MEM_POOL_TRANSACTION, tx_hash.as_slice())?;
         self.delete(COLUMN_MEM_POOL_TRANSACTION_RECEIPT, tx_hash.as_slice())?;
+        self.delete(COLUMN_TX_ORIGIN, tx_hash.as_slice())?;
         Ok(())
     }
 
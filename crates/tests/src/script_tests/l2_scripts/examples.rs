@@ -88,6 +88,7 @@ fn test_example_sum() {
         let backend_manage = BackendManage::from_config(vec![BackendForkConfig {
             fork_height: 0,
             sudt_proxy: Default::default(),
+            batch_create_eth_accounts: None,
             backends: vec![BackendConfig {
                 generator: Resource::file_system(SUM_PROGRAM_PATH.to_path_buf()),
                 generator_debug: None,
@@ -133,6 +134,89 @@ fn test_example_sum() {
     }
 }
 
+#[test]
+fn test_example_sum_syscall_trace() {
+    let store = Store::open_tmp().unwrap();
+    let mut tree = new_state(store.get_snapshot());
+    let chain_view = DummyChainStore;
+    let sender_script = Script::new_builder()
+        .code_hash(ALWAYS_SUCCESS_CODE_HASH.pack())
+        .args([1u8; 20].to_vec().pack())
+        .hash_type(ScriptHashType::Type.into())
+        .build();
+    let from_id = tree
+        .create_account_from_script(sender_script.clone())
+        .expect("create account");
+    tree.mapping_registry_address_to_script_hash(
+        RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![42u8; 20]),
+        sender_script.hash(),
+    )
+    .unwrap();
+    let contract_id = tree
+        .create_account_from_script(
+            Script::new_builder()
+                .code_hash(SUM_PROGRAM_CODE_HASH.pack())
+                .args([0u8; 20].to_vec().pack())
+                .hash_type(ScriptHashType::Type.into())
+                .build(),
+        )
+        .expect("create account");
+
+    let backend_manage = BackendManage::from_config(vec![BackendForkConfig {
+        fork_height: 0,
+        sudt_proxy: Default::default(),
+        batch_create_eth_accounts: None,
+        backends: vec![BackendConfig {
+            generator: Resource::file_system(SUM_PROGRAM_PATH.to_path_buf()),
+            generator_debug: None,
+            generator_checksum: file_checksum(&*SUM_PROGRAM_PATH).unwrap().into(),
+            validator_script_type_hash: (*SUM_PROGRAM_CODE_HASH).into(),
+            backend_type: BackendType::Unknown,
+        }],
+    }])
+    .unwrap();
+    let rollup_context = RollupContext {
+        rollup_config: Default::default(),
+        rollup_script_hash: [42u8; 32],
+        ..Default::default()
+    };
+    let generator = Generator::new(
+        backend_manage,
+        AccountLockManage::default(),
+        rollup_context,
+        Default::default(),
+    );
+
+    let block_info = new_block_info(&Default::default(), 1, 0);
+    let raw_tx = RawL2Transaction::new_builder()
+        .from_id(from_id.pack())
+        .to_id(contract_id.pack())
+        .args(Bytes::from(7u64.to_le_bytes().to_vec()).pack())
+        .build();
+    let run_result = generator
+        .trace_transaction(&chain_view, &mut tree, &block_info, &raw_tx, None)
+        .expect("trace");
+    assert_eq!(run_result.execution_traces.len(), 1);
+    let mut trace = run_result.execution_traces[0].clone();
+    assert!(!trace.syscalls.is_empty());
+
+    let max_cycles = 7000_0000;
+    let replay = generator
+        .replay_execution_trace(1, &trace, max_cycles)
+        .expect("replay");
+    assert_eq!(replay.divergence, None);
+    assert_eq!(replay.exit_code, 0);
+    assert_eq!(replay.syscalls, trace.syscalls.len());
+
+    // diverge at the first syscall
+    trace.syscalls[0].args[0] += 1;
+    let replay = generator
+        .replay_execution_trace(1, &trace, max_cycles)
+        .expect("replay");
+    assert!(replay.divergence.unwrap().starts_with("#0 "));
+    assert_eq!(replay.syscalls, 0);
+}
+
 pub enum AccountOp {
     Load {
         account_id: u32,
@@ -227,6 +311,7 @@ fn test_example_account_operation() {
     let backend_manage = BackendManage::from_config(vec![BackendForkConfig {
         fork_height: 0,
         sudt_proxy: Default::default(),
+        batch_create_eth_accounts: None,
         backends: vec![BackendConfig {
             generator: Resource::file_system(ACCOUNT_OP_PROGRAM_PATH.clone()),
             generator_debug: None,
@@ -466,6 +551,7 @@ fn test_example_recover_account() {
     let backend_manage = BackendManage::from_config(vec![BackendForkConfig {
         fork_height: 0,
         sudt_proxy: Default::default(),
+        batch_create_eth_accounts: None,
         backends: vec![BackendConfig {
             generator: Resource::file_system(RECOVER_PROGRAM_PATH.clone()),
             generator_debug: None,
@@ -647,6 +733,7 @@ fn test_sudt_total_supply() {
         let backend_manage = BackendManage::from_config(vec![BackendForkConfig {
             fork_height: 0,
             sudt_proxy: Default::default(),
+            batch_create_eth_accounts: None,
             backends: vec![BackendConfig {
                 generator: Resource::file_system(SUDT_TOTAL_SUPPLY_PROGRAM_PATH.clone()),
                 generator_debug: None,
@@ -1,23 +1,30 @@
 use super::super::utils::init_env_log;
-use super::{new_block_info, run_contract};
+use super::{new_block_info, run_contract, DummyChainStore};
 use crate::script_tests::l2_scripts::run_contract_get_result;
 use crate::script_tests::utils::context::TestingContext;
-use crate::testing_tool::chain::ALWAYS_SUCCESS_CODE_HASH;
+use crate::testing_tool::chain::{build_backend_manage, ALWAYS_SUCCESS_CODE_HASH};
 use gw_common::{
-    builtins::{CKB_SUDT_ACCOUNT_ID, RESERVED_ACCOUNT_ID},
+    builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID, RESERVED_ACCOUNT_ID},
+    registry_address::RegistryAddress,
     state::State,
 };
+use gw_config::{BackendForkConfig, SyscallCyclesConfig};
 use gw_generator::{
-    error::TransactionError, syscalls::error_codes::GW_ERROR_DUPLICATED_SCRIPT_HASH,
-    traits::StateExt,
+    account_lock_manage::AccountLockManage, error::TransactionError, generator::CyclesPool,
+    syscalls::error_codes::GW_ERROR_DUPLICATED_SCRIPT_HASH, traits::StateExt, Generator,
 };
 use gw_types::U256;
 use gw_types::{
-    core::ScriptHashType,
+    bytes::Bytes,
+    core::{AllowedEoaType, ScriptHashType},
     h256::*,
-    packed::{CreateAccount, Fee, MetaContractArgs, Script},
+    packed::{
+        AllowedTypeHash, BatchCreateEthAccounts, CreateAccount, Fee, L2Transaction,
+        MetaContractArgs, RawL2Transaction, Script, ScriptVec, TxReceipt,
+    },
     prelude::*,
 };
+use gw_utils::RollupContext;
 
 #[test]
 fn test_meta_contract() {
@@ -224,3 +231,189 @@ fn test_insufficient_balance_to_pay_fee() {
     )
     .expect("contract created successful");
 }
+
+#[test]
+fn test_batch_create_eth_accounts_in_one_syscall() {
+    init_env_log();
+    let rollup_config = TestingContext::default_rollup_config()
+        .as_builder()
+        .allowed_eoa_type_hashes(
+            vec![AllowedTypeHash::new(
+                AllowedEoaType::Eth,
+                *ALWAYS_SUCCESS_CODE_HASH,
+            )]
+            .pack(),
+        )
+        .build();
+    let setup = || {
+        let mut ctx = TestingContext::setup_with_config(rollup_config.clone());
+        let a_script = Script::new_builder()
+            .code_hash([0u8; 32].pack())
+            .args([0u8; 20].to_vec().pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build();
+        let a_script_hash = a_script.hash();
+        let a_id = ctx
+            .state
+            .create_account_from_script(a_script)
+            .expect("create account");
+        let a_address = ctx.create_eth_address(a_script_hash, [1u8; 20]);
+        ctx.state
+            .mint_sudt(CKB_SUDT_ACCOUNT_ID, &a_address, U256::from(4000u64))
+            .expect("mint CKB for account A to pay fee");
+        (ctx, a_id, a_address)
+    };
+    let eth_script = |eth_address: [u8; 20]| {
+        let mut args = [42u8; 32].to_vec();
+        args.extend_from_slice(&eth_address);
+        Script::new_builder()
+            .code_hash(ALWAYS_SUCCESS_CODE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(args.pack())
+            .build()
+    };
+    let batch_create_args = |scripts: Vec<Script>| {
+        let fee = Fee::new_builder()
+            .amount(1000u128.pack())
+            .registry_id(ETH_REGISTRY_ACCOUNT_ID.pack())
+            .build();
+        let args = MetaContractArgs::new_builder()
+            .set(
+                BatchCreateEthAccounts::new_builder()
+                    .scripts(ScriptVec::new_builder().set(scripts).build())
+                    .fee(fee)
+                    .build(),
+            )
+            .build();
+        args.as_bytes()
+    };
+    // Execute with a cycles pool, sys_batch_create_eth_accounts is enabled
+    // by the backend fork at block 1 if `batch` is true. Returns the run
+    // result and the receipt of the tx.
+    let execute = |ctx: &mut TestingContext,
+                   a_id: u32,
+                   a_address: &RegistryAddress,
+                   args: Bytes,
+                   batch: bool| {
+        let mut backend_manage = build_backend_manage(&ctx.rollup_config);
+        let fork = BackendForkConfig {
+            fork_height: 1,
+            backends: Vec::new(),
+            sudt_proxy: None,
+            batch_create_eth_accounts: Some(batch),
+        };
+        backend_manage.register_backend_fork(fork, false).unwrap();
+        let rollup_ctx = RollupContext {
+            rollup_config: ctx.rollup_config.clone(),
+            rollup_script_hash: [42u8; 32],
+            ..Default::default()
+        };
+        let generator = Generator::new(
+            backend_manage,
+            AccountLockManage::default(),
+            rollup_ctx,
+            Default::default(),
+        );
+        let raw_tx = RawL2Transaction::new_builder()
+            .from_id(a_id.pack())
+            .to_id(RESERVED_ACCOUNT_ID.pack())
+            .nonce(ctx.state.get_nonce(a_id).unwrap().pack())
+            .args(args.pack())
+            .build();
+        let block_info = new_block_info(a_address, 1, 0);
+        let mut cycles_pool = CyclesPool::new(u64::MAX, SyscallCyclesConfig::default());
+        let run_result = generator
+            .execute_transaction(
+                &DummyChainStore,
+                &mut ctx.state,
+                &block_info,
+                &raw_tx,
+                None,
+                Some(&mut cycles_pool),
+            )
+            .expect("execute");
+        let tx = L2Transaction::new_builder().raw(raw_tx).build();
+        let post_state = ctx.state.calculate_merkle_state().unwrap();
+        let receipt = TxReceipt::build_receipt(tx.witness_hash(), run_result.clone(), post_state);
+        (run_result, receipt)
+    };
+
+    let eth_addresses: Vec<[u8; 20]> = (2u8..5).map(|i| [i; 20]).collect();
+    let scripts: Vec<Script> = eth_addresses.iter().map(|a| eth_script(*a)).collect();
+
+    let (mut ctx, a_id, a_address) = setup();
+    let (run_result, receipt) = execute(
+        &mut ctx,
+        a_id,
+        &a_address,
+        batch_create_args(scripts.clone()),
+        true,
+    );
+    assert_eq!(run_result.exit_code, 0);
+    let last_id = {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(&run_result.return_data);
+        u32::from_le_bytes(buf)
+    };
+    assert_eq!(last_id, a_id + scripts.len() as u32);
+    for (i, (script, eth_address)) in scripts.iter().zip(&eth_addresses).enumerate() {
+        let script_hash = script.hash();
+        let id = ctx
+            .state
+            .get_account_id_by_script_hash(&script_hash)
+            .unwrap();
+        assert_eq!(id, Some(a_id + 1 + i as u32));
+        let address = RegistryAddress::new(ctx.eth_registry_id, eth_address.to_vec());
+        let mapped_script_hash = ctx
+            .state
+            .get_script_hash_by_registry_address(&address)
+            .unwrap();
+        assert_eq!(mapped_script_hash, Some(script_hash));
+    }
+
+    // the receipts, return data and virtual cycles must be the same as
+    // creating accounts one by one, only the execution cycles are lower
+    let (mut expected_ctx, _, _) = setup();
+    let (expected_run_result, expected_receipt) = execute(
+        &mut expected_ctx,
+        a_id,
+        &a_address,
+        batch_create_args(scripts.clone()),
+        false,
+    );
+    assert_eq!(receipt.as_slice(), expected_receipt.as_slice());
+    assert_eq!(run_result.return_data, expected_run_result.return_data);
+    assert_eq!(
+        run_result.write_data_hashes,
+        expected_run_result.write_data_hashes
+    );
+    assert_eq!(
+        run_result.cycles.r#virtual,
+        expected_run_result.cycles.r#virtual
+    );
+    assert!(run_result.cycles.execution < expected_run_result.cycles.execution);
+    assert_eq!(
+        ctx.state.calculate_root().unwrap(),
+        expected_ctx.state.calculate_root().unwrap()
+    );
+
+    // an existing account after a new one
+    let args = batch_create_args(vec![eth_script([5u8; 20]), scripts[1].clone()]);
+    let (run_result, receipt) = execute(&mut ctx, a_id, &a_address, args.clone(), true);
+    assert_eq!(run_result.exit_code, GW_ERROR_DUPLICATED_SCRIPT_HASH);
+    let (expected_run_result, expected_receipt) =
+        execute(&mut expected_ctx, a_id, &a_address, args, false);
+    assert_eq!(
+        expected_run_result.exit_code,
+        GW_ERROR_DUPLICATED_SCRIPT_HASH
+    );
+    assert_eq!(receipt.as_slice(), expected_receipt.as_slice());
+    assert_eq!(
+        run_result.cycles.r#virtual,
+        expected_run_result.cycles.r#virtual
+    );
+    assert_eq!(
+        ctx.state.calculate_root().unwrap(),
+        expected_ctx.state.calculate_root().unwrap()
+    );
+}
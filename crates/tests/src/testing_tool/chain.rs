@@ -355,6 +355,7 @@ pub fn build_backend_manage(rollup_config: &RollupConfig) -> BackendManage {
             permit_sudt_transfer_from_dangerous_contract: false,
             address_list: Vec::new(),
         }),
+        batch_create_eth_accounts: None,
         backends,
     }])
     .expect("default backend")
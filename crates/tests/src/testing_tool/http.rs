@@ -0,0 +1,43 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    thread,
+};
+
+/// Serve one response per body in order, a connection each, then stop.
+/// Returns the `http://` url of the server.
+///
+/// Requests are read fully but otherwise ignored, and every response is a
+/// `200 OK` that closes the connection.
+pub fn serve<B: AsRef<[u8]> + Send + 'static>(bodies: Vec<B>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for body in bodies {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(len) = line.strip_prefix("content-length:") {
+                    content_length = len.trim().parse().unwrap();
+                }
+            }
+            let mut request = vec![0; content_length];
+            reader.read_exact(&mut request).unwrap();
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.as_ref().len()
+            );
+            let stream = reader.get_mut();
+            stream.write_all(header.as_bytes()).unwrap();
+            stream.write_all(body.as_ref()).unwrap();
+        }
+    });
+    url
+}
@@ -2,7 +2,9 @@ pub mod bad_block;
 pub mod chain;
 pub mod common;
 pub mod eth_wallet;
+pub mod http;
 pub mod mem_pool_provider;
+pub mod p2p;
 pub mod polyjuice;
 pub mod rpc_server;
 pub mod verify_tx;
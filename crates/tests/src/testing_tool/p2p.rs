@@ -0,0 +1,35 @@
+use std::{future::Future, net::TcpListener};
+
+use gw_config::P2PNetworkConfig;
+
+const INIT_ATTEMPTS: usize = 5;
+
+/// Init a p2p network listening on a free local port, returns what `init`
+/// returns and the listen address.
+///
+/// `init` is called with `config` whose `listen` is set to the address. The
+/// port is found free by binding and releasing it, so another process may take
+/// it before the network binds it. `init` is retried with another port then.
+pub async fn init_on_free_port<T, F, Fut>(mut config: P2PNetworkConfig, mut init: F) -> (T, String)
+where
+    F: FnMut(P2PNetworkConfig) -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let port = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let address = format!("/ip4/127.0.0.1/tcp/{}", port);
+        config.listen = Some(address.clone());
+        match init(config.clone()).await {
+            Ok(t) => return (t, address),
+            Err(err) if attempt < INIT_ATTEMPTS => {
+                eprintln!("init p2p network on {} failed, retry: {:#}", address, err);
+            }
+            Err(err) => panic!("init p2p network on {}: {:#}", address, err),
+        }
+    }
+}
@@ -71,7 +71,14 @@ impl RPCServer {
             system_type_script_config: Default::default(),
             gasless_tx_support_config: None,
             polyjuice_sender_recover,
-            debug_backend_forks: None,
+            debug_generator: None,
+            p2p_network_status: None,
+            maintenance_mode: None,
+            production_control: None,
+            backup_dir: None,
+            config_path: None,
+            tx_relay: None,
+            relayed_txs: None,
         }
     }
 
@@ -93,7 +100,7 @@ impl RPCServer {
     pub async fn submit_l2transaction(&self, tx: &L2Transaction) -> RpcResult<Option<H256>> {
         let r = self
             .inner
-            .gw_submit_l2transaction(MolJsonBytes(tx.clone()))
+            .gw_submit_l2transaction(MolJsonBytes(tx.clone()), None)
             .await?;
         Ok(r.map(Into::into))
     }
@@ -101,7 +108,19 @@ impl RPCServer {
     pub async fn execute_l2transaction(&self, tx: &L2Transaction) -> RpcResult<RunResult> {
         let r = self
             .inner
-            .gw_execute_l2transaction(MolJsonBytes(tx.clone()))
+            .gw_execute_l2transaction(MolJsonBytes(tx.clone()), None)
+            .await?;
+        Ok(r)
+    }
+
+    pub async fn execute_l2transaction_at_block(
+        &self,
+        tx: &L2Transaction,
+        block_number: u64,
+    ) -> RpcResult<RunResult> {
+        let r = self
+            .inner
+            .gw_execute_l2transaction(MolJsonBytes(tx.clone()), Some(block_number.into()))
             .await?;
         Ok(r)
     }
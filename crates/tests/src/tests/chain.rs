@@ -7,9 +7,12 @@ use crate::testing_tool::{
 };
 
 use gw_block_producer::produce_block::ProduceBlockResult;
-use gw_chain::chain::{
-    Chain, ChallengeCell, L1Action, L1ActionContext, RevertL1ActionContext, RevertedL1Action,
-    SyncEvent, SyncParam,
+use gw_chain::{
+    chain::{
+        Chain, ChallengeCell, L1Action, L1ActionContext, RevertL1ActionContext, RevertedL1Action,
+        SyncEvent, SyncParam,
+    },
+    state_mismatch::StateMismatchHaltError,
 };
 use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, ckb_decimal::CKBCapacity, state::State};
 use gw_store::{
@@ -22,7 +25,7 @@ use gw_types::{
     h256::*,
     packed::{
         CellInput, CellOutput, DepositInfoVec, DepositRequest, GlobalState, RawWithdrawalRequest,
-        Script, WithdrawalRequest, WithdrawalRequestExtra,
+        Script, WithdrawalKey, WithdrawalRequest, WithdrawalRequestExtra,
     },
     prelude::*,
 };
@@ -854,6 +857,26 @@ async fn test_rewind_to_last_valid_tip_just_after_bad_block_reverted() {
     let tip_block_number: u64 = tip_block.raw().number().unpack();
     assert_eq!(tip_block_number, 8);
 
+    // Withdrawals of the bad block are saved for verifying the challenge.
+    let bad_withdrawal_key = WithdrawalKey::new_builder()
+        .block_hash(bad_block_result.block.hash().pack())
+        .index(0u32.pack())
+        .build();
+    let bad_withdrawal = chain
+        .store()
+        .get_withdrawal_by_key(&bad_withdrawal_key)
+        .unwrap()
+        .expect("bad block withdrawal");
+    assert_eq!(
+        bad_withdrawal.request().as_slice(),
+        bad_block_result
+            .block
+            .withdrawals()
+            .get(0)
+            .unwrap()
+            .as_slice()
+    );
+
     // challenge bad block
     let challenge_context = match chain.last_sync_event() {
         SyncEvent::BadBlock { context } => context.to_owned(),
@@ -954,6 +977,14 @@ async fn test_rewind_to_last_valid_tip_just_after_bad_block_reverted() {
         local_reverted_block_smt_root,
         Unpack::<H256>::unpack(&last_valid_tip_global_state.unwrap().reverted_block_root())
     );
+    assert!(chain
+        .store()
+        .get_withdrawal_by_key(&bad_withdrawal_key)
+        .unwrap()
+        .is_none());
+    // Already at last valid tip
+    let mut store_tx = chain.store().begin_transaction();
+    assert!(!chain.rewind_to_last_valid_tip(&mut store_tx).unwrap());
 
     // Produce new block
     let bob_script = Script::new_builder()
@@ -1002,6 +1033,81 @@ async fn test_rewind_to_last_valid_tip_just_after_bad_block_reverted() {
     assert_eq!(tip_block_number, 8);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_halt_on_state_mismatch() {
+    let rollup_type_script = Script::default();
+    let mut chain = setup_chain(rollup_type_script.clone()).await;
+    let dump_dir = tempfile::tempdir().unwrap();
+    chain.set_state_mismatch_dump_path(dump_dir.path().to_path_buf());
+
+    let block_result = {
+        let mem_pool = chain.mem_pool().as_ref().unwrap();
+        let mut mem_pool = mem_pool.lock().await;
+        construct_block(&chain, &mut mem_pool, Default::default())
+            .await
+            .unwrap()
+    };
+    let ProduceBlockResult {
+        block,
+        global_state,
+        ..
+    } = block_result;
+    let bad_block = {
+        let raw = block.raw();
+        let post_account = raw
+            .post_account()
+            .as_builder()
+            .merkle_root([42u8; 32].pack());
+        let raw = raw.as_builder().post_account(post_account.build()).build();
+        block.clone().as_builder().raw(raw).build()
+    };
+    let update_local = |chain: &mut Chain, block| {
+        let mut store_tx = chain.store().begin_transaction();
+        chain.update_local(
+            &mut store_tx,
+            block,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            global_state.clone(),
+        )?;
+        store_tx.commit()
+    };
+
+    let err = update_local(&mut chain, bad_block.clone()).unwrap_err();
+    assert!(err.is::<StateMismatchHaltError>());
+    assert_eq!(
+        chain.store().get_state_mismatch_halt(),
+        Some(bad_block.hash())
+    );
+    let dir = (dump_dir.path()).join(format!("1-{}", hex::encode(bad_block.hash())));
+    let roots: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(dir.join("roots.json")).unwrap()).unwrap();
+    assert_eq!(
+        roots["actual_post_account"]["merkle_root"],
+        format!(
+            "0x{}",
+            hex::encode(block.raw().post_account().merkle_root().as_slice())
+        )
+    );
+    assert!(dir.join("block.json").exists());
+    assert!(dir.join("state_diffs.json").exists());
+
+    // Valid blocks are refused too, also after restarts.
+    let err = update_local(&mut chain, block.clone()).unwrap_err();
+    assert!(err.is::<StateMismatchHaltError>());
+    let mut chain = restart_chain(&chain, rollup_type_script.clone(), None).await;
+    let err = update_local(&mut chain, block.clone()).unwrap_err();
+    assert!(err.is::<StateMismatchHaltError>());
+
+    // Override.
+    let mut store_tx = chain.store().begin_transaction();
+    store_tx.clear_state_mismatch_halt().unwrap();
+    store_tx.commit().unwrap();
+    let mut chain = restart_chain(&chain, rollup_type_script, None).await;
+    update_local(&mut chain, block).unwrap();
+}
+
 async fn produce_empty_block(chain: &mut Chain, rollup_cell: CellOutput) {
     let block_result = {
         let mem_pool = chain.mem_pool().as_ref().unwrap();
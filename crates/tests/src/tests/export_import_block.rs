@@ -212,7 +212,7 @@ async fn test_export_import_block() {
         )
         .await
     };
-    let import_block = ImportBlock::new_unchecked(import_chain, export_path);
+    let import_block = ImportBlock::new_unchecked(import_chain, export_path).verify_execution(2);
     let import_store = import_block.store().clone();
     import_block.execute().await.unwrap();
 
@@ -230,6 +230,7 @@ async fn test_export_import_block() {
 
     let import_tx_db = import_store.begin_transaction();
     check_block_post_state(&import_tx_db, tip_block_number, &post_global_state).unwrap();
+    let partial_import_store = import_store.clone();
 
     // Test reverted block root
     generate_and_revert_a_bad_block(&mut chain, &rollup_cell, accounts[0].clone()).await;
@@ -311,6 +312,49 @@ async fn test_export_import_block() {
 
     let import_tx_db = import_store.begin_transaction();
     check_block_post_state(&import_tx_db, tip_block_number, &post_global_state).unwrap();
+
+    // Export compressed blocks
+    let export_path = {
+        let tmp_dir = tempfile::tempdir().expect("create temp dir");
+        let mut path_buf = tmp_dir.path().to_path_buf();
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap();
+        path_buf.set_file_name(format!("export_block_{}.zst", now.as_secs()));
+        path_buf
+    };
+    let store_readonly = StoreReadonly::open(store_dir.path(), COLUMNS).unwrap();
+    let export_block =
+        ExportBlock::new_unchecked(store_readonly, export_path.clone(), 0, tip_block_number)
+            .compress(true);
+    export_block.execute().unwrap();
+
+    // Import into the store with blocks of the first export, they are skipped
+    let import_chain = {
+        let mut account_lock_manage = AccountLockManage::default();
+        account_lock_manage
+            .register_lock_algorithm(*ALWAYS_SUCCESS_CODE_HASH, Arc::new(AlwaysSuccess));
+        account_lock_manage
+            .register_lock_algorithm(*ETH_ACCOUNT_LOCK_CODE_HASH, Arc::new(Secp256k1Eth));
+        setup_chain_with_account_lock_manage(
+            rollup_type_script,
+            rollup_config,
+            account_lock_manage,
+            Some(partial_import_store),
+            None,
+            None,
+        )
+        .await
+    };
+    let import_block = ImportBlock::new_unchecked(import_chain, export_path);
+    let import_store = import_block.store().clone();
+    import_block.execute().await.unwrap();
+
+    let import_tip_block_hash = import_store.get_tip_block_hash().unwrap();
+    assert_eq!(tip_block_hash, import_tip_block_hash);
+
+    let import_tx_db = import_store.begin_transaction();
+    check_block_post_state(&import_tx_db, tip_block_number, &post_global_state).unwrap();
 }
 
 async fn generate_and_revert_a_bad_block(
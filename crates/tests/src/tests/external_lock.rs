@@ -0,0 +1,75 @@
+use std::{
+    net::TcpListener,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use gw_generator::{
+    account_lock_manage::{external::ExternalLockAlgorithm, LockAlgorithm},
+    error::LockAlgorithmError,
+};
+
+use crate::testing_tool::http::serve;
+
+#[test]
+fn test_external_lock_algorithm() {
+    let url = serve(vec![
+        r#"{"jsonrpc":"2.0","id":0,"result":"0x0102"}"#,
+        r#"{"jsonrpc":"2.0","id":0,"error":{"code":-32000,"message":"bad signature"}}"#,
+    ]);
+    let lock = ExternalLockAlgorithm::new(url, Duration::from_secs(5), 1).unwrap();
+    assert_eq!(
+        lock.recover([1u8; 32], &[2u8; 65]).unwrap().as_ref(),
+        &[1u8, 2]
+    );
+    assert_eq!(
+        lock.recover([1u8; 32], &[2u8; 65]),
+        Err(LockAlgorithmError::InvalidSignature("bad signature".into()))
+    );
+    // The server is gone.
+    assert!(matches!(
+        lock.recover([1u8; 32], &[2u8; 65]),
+        Err(LockAlgorithmError::VerifierUnavailable(_))
+    ));
+}
+
+#[test]
+fn test_external_lock_algorithm_timeout() {
+    // Connections are accepted by the OS but never answered.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    const TIMEOUT: Duration = Duration::from_millis(500);
+    let recover_concurrently = |max_concurrent_calls| {
+        let lock = ExternalLockAlgorithm::new(url.clone(), TIMEOUT, max_concurrent_calls).unwrap();
+        let lock = Arc::new(lock);
+        let start = Instant::now();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let lock = lock.clone();
+                thread::spawn(move || lock.recover([1u8; 32], &[2u8; 65]))
+            })
+            .collect();
+        for handle in handles {
+            let result = handle.join().unwrap();
+            assert!(
+                matches!(result, Err(LockAlgorithmError::VerifierUnavailable(_))),
+                "{:?}",
+                result
+            );
+        }
+        start.elapsed()
+    };
+
+    // Calls time out after the configured timeout, in parallel.
+    let elapsed = recover_concurrently(4);
+    assert!(
+        elapsed >= TIMEOUT && elapsed < TIMEOUT * 2,
+        "elapsed: {:?}",
+        elapsed
+    );
+    // Calls waiting for a free slot time out too.
+    let elapsed = recover_concurrently(1);
+    assert!(elapsed < TIMEOUT * 2, "elapsed: {:?}", elapsed);
+    drop(listener);
+}
@@ -0,0 +1,144 @@
+use ckb_crypto::secp::Privkey;
+use gw_types::{
+    packed::{
+        BlockInfo, BlockSync, BlockSyncUnion, Confirmed, MemBlockDelta, MemBlockSnapshot,
+        NextMemBlock, NumberHash, PushTransaction, Script,
+    },
+    prelude::*,
+};
+use gw_utils::{
+    mem_block_signature::{sighash_lock_signer, verify_block_sync, BlockSyncSigner},
+    wallet::Wallet,
+};
+
+fn number_hash(number: u64, hash: [u8; 32]) -> NumberHash {
+    NumberHash::new_builder()
+        .number(number.pack())
+        .block_hash(hash.pack())
+        .build()
+}
+
+fn mem_block(number: u64) -> BlockSync {
+    let mem_block = NextMemBlock::new_builder()
+        .block_info(BlockInfo::new_builder().number(number.pack()).build())
+        .build();
+    BlockSync::new_builder().set(mem_block).build()
+}
+
+#[test]
+fn test_mem_block_signature() {
+    let rollup_type_hash = [1u8; 32];
+    let wallet = Wallet::try_from(Privkey::from_slice(&[1u8; 32])).unwrap();
+    let signer = sighash_lock_signer(wallet.lock_script()).unwrap();
+    let block_sync_signer = BlockSyncSigner::new(wallet, rollup_type_hash);
+
+    // Mem block 2 on top of block 1.
+    let signed = block_sync_signer
+        .sign(number_hash(1, [4u8; 32]), mem_block(2))
+        .unwrap();
+    let msg = verify_block_sync(&signed, &rollup_type_hash, &signer).unwrap();
+    assert_eq!(msg.as_slice(), mem_block(2).as_slice());
+
+    // Another signer.
+    let other = Wallet::try_from(Privkey::from_slice(&[2u8; 32])).unwrap();
+    let other_signer = sighash_lock_signer(other.lock_script()).unwrap();
+    assert!(verify_block_sync(&signed, &rollup_type_hash, &other_signer).is_err());
+    // Another rollup.
+    assert!(verify_block_sync(&signed, &[2u8; 32], &signer).is_err());
+    // Tampered mem block.
+    let tampered = signed
+        .clone()
+        .as_builder()
+        .message(mem_block(3).as_bytes().pack())
+        .build();
+    assert!(verify_block_sync(&tampered, &rollup_type_hash, &signer).is_err());
+    // Replayed on another parent block.
+    let replayed = signed
+        .clone()
+        .as_builder()
+        .number_hash(number_hash(1, [5u8; 32]))
+        .build();
+    assert!(verify_block_sync(&replayed, &rollup_type_hash, &signer).is_err());
+    // Garbage signature.
+    let garbage = signed
+        .clone()
+        .as_builder()
+        .signature([0u8; 65][..].pack())
+        .build();
+    assert!(verify_block_sync(&garbage, &rollup_type_hash, &signer).is_err());
+
+    // Messages must be bound to their blocks.
+    let unbound = block_sync_signer
+        .sign(number_hash(2, [4u8; 32]), mem_block(2))
+        .unwrap();
+    assert!(verify_block_sync(&unbound, &rollup_type_hash, &signer).is_err());
+    let confirmed = Confirmed::new_builder()
+        .number_hash(number_hash(1, [4u8; 32]))
+        .build();
+    let confirmed = BlockSync::new_builder().set(confirmed).build();
+    let signed = block_sync_signer
+        .sign(number_hash(1, [4u8; 32]), confirmed.clone())
+        .unwrap();
+    verify_block_sync(&signed, &rollup_type_hash, &signer).unwrap();
+    let unbound = block_sync_signer
+        .sign(number_hash(1, [5u8; 32]), confirmed)
+        .unwrap();
+    assert!(verify_block_sync(&unbound, &rollup_type_hash, &signer).is_err());
+    // Txs are bound to the parent block of their mem block.
+    let push_tx = BlockSync::new_builder()
+        .set(PushTransaction::default())
+        .build();
+    let signed = block_sync_signer
+        .sign(number_hash(1, [4u8; 32]), push_tx)
+        .unwrap();
+    let msg = verify_block_sync(&signed, &rollup_type_hash, &signer).unwrap();
+    assert!(matches!(msg.to_enum(), BlockSyncUnion::PushTransaction(_)));
+    // So are mem block snapshots.
+    let snapshot = |number: u64| {
+        let mem_block = NextMemBlock::new_builder()
+            .block_info(BlockInfo::new_builder().number(number.pack()).build())
+            .build();
+        let snapshot = MemBlockSnapshot::new_builder().mem_block(mem_block).build();
+        BlockSync::new_builder().set(snapshot).build()
+    };
+    let signed = block_sync_signer
+        .sign(number_hash(1, [4u8; 32]), snapshot(2))
+        .unwrap();
+    verify_block_sync(&signed, &rollup_type_hash, &signer).unwrap();
+    let unbound = block_sync_signer
+        .sign(number_hash(1, [4u8; 32]), snapshot(3))
+        .unwrap();
+    assert!(verify_block_sync(&unbound, &rollup_type_hash, &signer).is_err());
+    // And mem block deltas.
+    let delta = |number: u64| {
+        let delta = MemBlockDelta::new_builder()
+            .block_number(number.pack())
+            .build();
+        BlockSync::new_builder().set(delta).build()
+    };
+    let delta_signed = block_sync_signer
+        .sign(number_hash(1, [4u8; 32]), delta(2))
+        .unwrap();
+    verify_block_sync(&delta_signed, &rollup_type_hash, &signer).unwrap();
+    let unbound = block_sync_signer
+        .sign(number_hash(1, [4u8; 32]), delta(3))
+        .unwrap();
+    assert!(verify_block_sync(&unbound, &rollup_type_hash, &signer).is_err());
+    // Signed messages can't be nested.
+    let nested = BlockSync::new_builder().set(signed).build();
+    let nested = block_sync_signer
+        .sign(number_hash(1, [4u8; 32]), nested)
+        .unwrap();
+    assert!(verify_block_sync(&nested, &rollup_type_hash, &signer).is_err());
+
+    // Only sighash locks are accepted as signer locks.
+    let lock = Wallet::try_from(Privkey::from_slice(&[1u8; 32]))
+        .unwrap()
+        .lock_script()
+        .clone()
+        .as_builder()
+        .code_hash([3u8; 32].pack())
+        .build();
+    assert!(sighash_lock_signer(&lock).is_err());
+    assert!(sighash_lock_signer(&Script::default()).is_err());
+}
@@ -0,0 +1,148 @@
+use gw_chain::chain::{RevertL1ActionContext, RevertedL1Action, SyncParam};
+use gw_common::{
+    builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID},
+    registry_address::RegistryAddress,
+    state::State,
+};
+use gw_config::{MemBlockConfig, MemPoolConfig};
+use gw_store::traits::chain_store::ChainStore;
+use gw_types::{
+    h256::*,
+    offchain::SudtTransferParams,
+    packed::{DepositInfoVec, DepositRequest, L2Transaction, Script},
+    prelude::*,
+    U256,
+};
+
+use crate::testing_tool::{
+    chain::{into_deposit_info_cell, TestChain},
+    common::random_always_success_script,
+};
+
+const CKB: u64 = 100000000;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_overflow_txs_survive_reinjection() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mem_pool_config = MemPoolConfig {
+        mem_block: MemBlockConfig {
+            max_txs: 2,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let rollup_type_script = Script::default();
+    let mut chain = {
+        let chain = TestChain::setup(rollup_type_script).await;
+        chain.update_mem_pool_config(mem_pool_config).await
+    };
+    let rollup_script_hash = chain.rollup_type_hash();
+    let rollup_context = chain.inner.generator().rollup_context();
+
+    const DEPOSIT_CAPACITY: u64 = 1000 * CKB;
+    let accounts: Vec<_> = (0..4)
+        .map(|_| random_always_success_script(&rollup_script_hash))
+        .collect();
+    let deposits = accounts.iter().map(|account_script| {
+        DepositRequest::new_builder()
+            .capacity(DEPOSIT_CAPACITY.pack())
+            .sudt_script_hash(H256::zero().pack())
+            .amount(0.pack())
+            .script(account_script.to_owned())
+            .registry_id(ETH_REGISTRY_ACCOUNT_ID.pack())
+            .build()
+    });
+    let deposit_info_vec = DepositInfoVec::new_builder()
+        .extend(deposits.map(|d| into_deposit_info_cell(rollup_context, d).pack()))
+        .build();
+    chain.produce_block(deposit_info_vec, vec![]).await.unwrap();
+
+    let txs: Vec<_> = {
+        let state = chain.mem_pool_state().await.load_state_db();
+        let to_script = random_always_success_script(&rollup_script_hash);
+        accounts
+            .iter()
+            .map(|account_script| {
+                let from_id = state
+                    .get_account_id_by_script_hash(&account_script.hash())
+                    .unwrap()
+                    .unwrap();
+                let params = SudtTransferParams {
+                    chain_id: chain.chain_id(),
+                    from_id,
+                    sudt_id: CKB_SUDT_ACCOUNT_ID,
+                    nonce: 0,
+                    to_address: RegistryAddress::new(
+                        ETH_REGISTRY_ACCOUNT_ID,
+                        to_script.hash()[0..20].to_vec(),
+                    ),
+                    amount: U256::from(CKB as u128),
+                    fee_registry_id: ETH_REGISTRY_ACCOUNT_ID,
+                    fee_amount: 1000,
+                };
+                L2Transaction::build_sudt_transfer(&params, Default::default()).unwrap()
+            })
+            .collect()
+    };
+    let tx_hashes: Vec<H256> = txs.iter().map(|tx| tx.hash()).collect();
+
+    // Package the first 2 txs into block 2, then fill the next mem block.
+    {
+        let mut mem_pool = chain.mem_pool().await;
+        mem_pool.push_transaction(txs[0].clone()).unwrap();
+        mem_pool.push_transaction(txs[1].clone()).unwrap();
+    }
+    chain
+        .produce_block(Default::default(), vec![])
+        .await
+        .unwrap();
+    {
+        let mut mem_pool = chain.mem_pool().await;
+        mem_pool.push_transaction(txs[2].clone()).unwrap();
+        mem_pool.push_transaction(txs[3].clone()).unwrap();
+        let err = mem_pool.push_transaction(txs[0].clone()).unwrap_err();
+        assert!(err.to_string().contains("Mem block is full"), "{}", err);
+    }
+
+    // Revert block 2, its txs are re-injected before the pending ones and
+    // fill the mem block.
+    let tip_block = chain.store().get_tip_block().unwrap();
+    let prev_global_state = {
+        let parent_block_hash: H256 = tip_block.raw().parent_block_hash().unpack();
+        let db = chain.store().begin_transaction();
+        db.get_block_post_global_state(&parent_block_hash)
+            .unwrap()
+            .unwrap()
+    };
+    let param = SyncParam {
+        updates: vec![],
+        reverts: vec![RevertedL1Action {
+            prev_global_state,
+            context: RevertL1ActionContext::SubmitValidBlock { l2block: tip_block },
+        }],
+    };
+    chain.inner.sync(param).await.unwrap();
+    assert!(chain.inner.last_sync_event().is_success());
+    chain.inner.notify_new_tip().await.unwrap();
+
+    {
+        let mem_pool = chain.mem_pool().await;
+        assert_eq!(mem_pool.mem_block().txs(), &tx_hashes[..2]);
+    }
+    // The overflow txs are kept in the pool.
+    {
+        let db = chain.store().begin_transaction();
+        for tx_hash in &tx_hashes[2..] {
+            assert!(db.get_mem_pool_transaction(tx_hash).unwrap().is_some());
+        }
+    }
+
+    // And packaged into the next mem block.
+    chain
+        .produce_block(Default::default(), vec![])
+        .await
+        .unwrap();
+    let mem_pool = chain.mem_pool().await;
+    assert_eq!(mem_pool.mem_block().txs(), &tx_hashes[2..]);
+}
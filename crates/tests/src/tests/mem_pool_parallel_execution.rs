@@ -0,0 +1,118 @@
+use gw_common::{
+    builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID},
+    registry_address::RegistryAddress,
+    state::State,
+};
+use gw_config::{MemBlockConfig, MemPoolConfig};
+use gw_mem_pool::pool::MemPool;
+use gw_types::{
+    h256::*,
+    offchain::SudtTransferParams,
+    packed::{DepositInfoVec, DepositRequest, L2Transaction, Script},
+    prelude::*,
+    U256,
+};
+use gw_utils::local_cells::LocalCellsManager;
+
+use crate::testing_tool::{
+    chain::{into_deposit_info_cell, TestChain},
+    common::random_always_success_script,
+};
+
+const CKB: u64 = 100000000;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_reinject_txs_in_parallel() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mem_pool_config = MemPoolConfig {
+        mem_block: MemBlockConfig {
+            parallel_execution_workers: 4,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let rollup_type_script = Script::default();
+    let mut chain = {
+        let chain = TestChain::setup(rollup_type_script).await;
+        chain.update_mem_pool_config(mem_pool_config).await
+    };
+    let rollup_script_hash = chain.rollup_type_hash();
+    let rollup_context = chain.inner.generator().rollup_context();
+
+    const DEPOSIT_CAPACITY: u64 = 1000 * CKB;
+    let accounts: Vec<_> = (0..8)
+        .map(|_| random_always_success_script(&rollup_script_hash))
+        .collect();
+    let deposits = accounts.iter().map(|account_script| {
+        DepositRequest::new_builder()
+            .capacity(DEPOSIT_CAPACITY.pack())
+            .sudt_script_hash(H256::zero().pack())
+            .amount(0.pack())
+            .script(account_script.to_owned())
+            .registry_id(ETH_REGISTRY_ACCOUNT_ID.pack())
+            .build()
+    });
+    let deposit_info_vec = DepositInfoVec::new_builder()
+        .extend(deposits.map(|d| into_deposit_info_cell(rollup_context, d).pack()))
+        .build();
+    chain.produce_block(deposit_info_vec, vec![]).await.unwrap();
+
+    // Independent transfers, transfers to the same address and transfers of
+    // the same sender.
+    let shared_to = random_always_success_script(&rollup_script_hash);
+    let txs: Vec<_> = {
+        let state = chain.mem_pool_state().await.load_state_db();
+        let mut txs = Vec::new();
+        for (i, account_script) in accounts.iter().enumerate() {
+            let from_id = state
+                .get_account_id_by_script_hash(&account_script.hash())
+                .unwrap()
+                .unwrap();
+            let nonces = if i == 0 { 0..3 } else { 0..1 };
+            for nonce in nonces {
+                let to_script = if i % 2 == 0 {
+                    shared_to.clone()
+                } else {
+                    random_always_success_script(&rollup_script_hash)
+                };
+                let params = SudtTransferParams {
+                    chain_id: chain.chain_id(),
+                    from_id,
+                    sudt_id: CKB_SUDT_ACCOUNT_ID,
+                    nonce,
+                    to_address: RegistryAddress::new(
+                        ETH_REGISTRY_ACCOUNT_ID,
+                        to_script.hash()[0..20].to_vec(),
+                    ),
+                    amount: U256::from(CKB as u128),
+                    fee_registry_id: ETH_REGISTRY_ACCOUNT_ID,
+                    fee_amount: 1000,
+                };
+                txs.push(L2Transaction::build_sudt_transfer(&params, Default::default()).unwrap());
+            }
+        }
+        txs
+    };
+
+    let mut mem_pool = chain.mem_pool().await;
+    for tx in txs.clone() {
+        mem_pool.push_transaction(tx).unwrap();
+    }
+    let post_states = |mem_pool: &MemPool| -> Vec<Vec<u8>> {
+        let post_states = mem_pool.mem_block().tx_post_states().iter();
+        post_states.map(|s| s.as_slice().to_vec()).collect()
+    };
+    let txs_before = mem_pool.mem_block().txs().to_vec();
+    let post_states_before = post_states(&mem_pool);
+    assert_eq!(txs_before.len(), txs.len());
+
+    // Reset re-injects txs in parallel, the result must be the same as
+    // executing them serially.
+    mem_pool
+        .reset_mem_block(&LocalCellsManager::default())
+        .await
+        .unwrap();
+    assert_eq!(mem_pool.mem_block().txs(), txs_before.as_slice());
+    assert_eq!(post_states(&mem_pool), post_states_before);
+}
@@ -2,11 +2,24 @@ mod calc_finalizing_range;
 mod chain;
 mod deposit_withdrawal;
 mod export_import_block;
+mod external_lock;
 mod mem_block_repackage;
+mod mem_block_signature;
 mod mem_pool_ckb_transfer_create_new_recipient_account;
+mod mem_pool_deferred_txs;
+mod mem_pool_parallel_execution;
 mod meta_contract_args;
+mod p2p_block_range;
+mod p2p_hostile_peer;
+mod p2p_keepalive;
+mod p2p_mem_block_delta;
+mod p2p_session_limits;
+mod p2p_tls;
+mod p2p_tx_relay;
 mod polyjuice_sender_recover;
+mod resource_url;
 mod restore_mem_block;
 mod restore_mem_pool_pending_withdrawal;
 mod rpc_server;
+mod state_snapshot;
 mod unlock_withdrawal_to_owner;
@@ -0,0 +1,119 @@
+use std::sync::{Arc, Mutex};
+
+use crate::testing_tool::{
+    chain::{produce_empty_block, setup_chain},
+    p2p::init_on_free_port,
+};
+
+use gw_config::{P2PNetworkConfig, SyncServerConfig};
+use gw_mem_pool::block_sync_server::{
+    block_sync_server_protocol, BlockRangeServer, BlockSyncServerState,
+};
+use gw_p2p_network::{
+    test_peer::{PeerAction, ScriptedPeer},
+    P2PNetwork, PeerLedger, P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME,
+};
+use gw_store::traits::chain_store::ChainStore;
+use gw_types::{
+    packed::{
+        BlockSyncReader, BlockSyncUnionReader, NumberHash, P2PBlockRangeRequest,
+        P2PBlockRangeResponseReader,
+    },
+    prelude::*,
+};
+use gw_utils::compression::StreamDecoder;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_block_range_serving() {
+    let mut chain = setup_chain(Default::default()).await;
+    for _ in 0..5 {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+    // Blocks 1..=3 are submitted and confirmed.
+    let store = chain.store().clone();
+    let mut db = store.begin_transaction();
+    for b in 1..=3u64 {
+        db.set_block_submit_tx_hash(b, &[b as u8; 32]).unwrap();
+    }
+    let last_confirmed = NumberHash::new_builder()
+        .number(3u64.pack())
+        .block_hash(store.get_block_hash_by_number(3).unwrap().unwrap().pack())
+        .build();
+    db.set_last_confirmed_block_number_hash(&last_confirmed.as_reader())
+        .unwrap();
+    db.commit().unwrap();
+
+    let config = P2PNetworkConfig::default();
+    let sync_server_config = SyncServerConfig {
+        block_range_max_blocks: 2,
+        ..Default::default()
+    };
+    let state = Arc::new(Mutex::new(BlockSyncServerState::new(&sync_server_config)));
+    let block_range_server = BlockRangeServer::new(store.clone(), &sync_server_config);
+    let block_range_server = Some(Arc::new(block_range_server));
+    let ledger = Arc::new(PeerLedger::new(&config.peer_score).unwrap());
+    let (mut network, address) = init_on_free_port(config, |config| {
+        let protocol = block_sync_server_protocol(
+            state.clone(),
+            None,
+            block_range_server.clone(),
+            ledger.clone(),
+        );
+        let ledger = ledger.clone();
+        async move { P2PNetwork::init_with_peer_ledger(&config, [protocol], ledger).await }
+    })
+    .await;
+    tokio::spawn(async move { network.run().await });
+
+    let request = |start: u64| {
+        let request = P2PBlockRangeRequest::new_builder()
+            .start_block_number(start.pack())
+            .max_blocks(16u32.pack())
+            .build();
+        PeerAction::Send(request.as_bytes())
+    };
+    // Ranges are capped by the server, and end at the last confirmed block.
+    let mut script = vec![request(1)];
+    script.extend(vec![PeerAction::Receive; 1 + 2 * 3]);
+    script.push(request(3));
+    script.extend(vec![PeerAction::Receive; 1 + 3]);
+    script.push(request(4));
+    script.push(PeerAction::Receive);
+    let received = ScriptedPeer::new(P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME)
+        .run(address.parse().unwrap(), &script)
+        .await
+        .unwrap();
+
+    let mut decoder = StreamDecoder::new();
+    let mut messages = received.iter().map(|msg| decoder.decode(msg).unwrap());
+    for (start, blocks) in [(1u64, 2u32), (3, 1), (4, 0)] {
+        let response = messages.next().unwrap();
+        let response = P2PBlockRangeResponseReader::from_slice(&response).unwrap();
+        assert_eq!(response.blocks().unpack(), blocks);
+        for b in start..start + u64::from(blocks) {
+            let block_hash = store.get_block_hash_by_number(b).unwrap().unwrap();
+            let local_block = messages.next().unwrap();
+            match BlockSyncReader::from_slice(&local_block).unwrap().to_enum() {
+                BlockSyncUnionReader::LocalBlock(l) => {
+                    assert_eq!(l.block().raw().hash(), block_hash);
+                }
+                _ => panic!("expect local block {}", b),
+            }
+            let submitted = messages.next().unwrap();
+            match BlockSyncReader::from_slice(&submitted).unwrap().to_enum() {
+                BlockSyncUnionReader::Submitted(s) => {
+                    assert_eq!(s.tx_hash().as_slice(), &[b as u8; 32]);
+                }
+                _ => panic!("expect submitted {}", b),
+            }
+            let confirmed = messages.next().unwrap();
+            match BlockSyncReader::from_slice(&confirmed).unwrap().to_enum() {
+                BlockSyncUnionReader::Confirmed(c) => {
+                    assert_eq!(c.number_hash().number().unpack(), b);
+                }
+                _ => panic!("expect confirmed {}", b),
+            }
+        }
+    }
+    assert!(messages.next().is_none());
+}
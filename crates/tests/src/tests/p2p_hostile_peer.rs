@@ -0,0 +1,211 @@
+//! Block sync server against hostile peers: malformed, truncated, replayed
+//! and oversized messages must close the session, and never stall the server
+//! for well-behaved peers. Peers misbehaving repeatedly are banned.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use gw_config::{P2PNetworkConfig, PeerScoreConfig, SyncServerConfig};
+use gw_mem_pool::block_sync_server::{block_sync_server_protocol, BlockSyncServerState};
+use gw_metrics::p2p::Direction;
+use gw_p2p_network::{
+    test_peer::{PeerAction, ScriptedPeer},
+    P2PNetwork, PeerLedger, P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME,
+};
+use gw_types::{
+    packed::{
+        L2Block, LocalBlock, P2PStateSyncRequest, P2PSyncRequest, P2PSyncResponseReader, RawL2Block,
+    },
+    prelude::*,
+};
+use gw_utils::compression::StreamDecoder;
+
+use crate::testing_tool::p2p::init_on_free_port;
+
+async fn start_server(
+    state: Arc<Mutex<BlockSyncServerState>>,
+    peer_score: PeerScoreConfig,
+) -> (String, Arc<PeerLedger>) {
+    let config = P2PNetworkConfig {
+        listen: None,
+        dial: Vec::new(),
+        secret_key_path: None,
+        allowed_peer_ids: None,
+        peer_score,
+        session_limits: Default::default(),
+        keepalive: None,
+        tls: None,
+    };
+    let ledger = Arc::new(PeerLedger::new(&config.peer_score).unwrap());
+    let (mut network, address) = init_on_free_port(config, |config| {
+        let protocol = block_sync_server_protocol(state.clone(), None, None, ledger.clone());
+        let ledger = ledger.clone();
+        async move { P2PNetwork::init_with_peer_ledger(&config, [protocol], ledger).await }
+    })
+    .await;
+    tokio::spawn(async move { network.run().await });
+    (address, ledger)
+}
+
+fn peer() -> ScriptedPeer {
+    ScriptedPeer::new(P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME).timeout(Duration::from_secs(5))
+}
+
+fn request(block_number: u64, block_hash: [u8; 32]) -> Bytes {
+    P2PSyncRequest::new_builder()
+        .block_number(block_number.pack())
+        .block_hash(block_hash.pack())
+        .build()
+        .as_bytes()
+}
+
+fn is_found(compressed: &[u8]) -> bool {
+    let response = StreamDecoder::new().decode(compressed).unwrap();
+    let response = P2PSyncResponseReader::from_slice(&response).unwrap();
+    response.item_id() == 0
+}
+
+/// A well-behaved peer still gets served.
+async fn honest_request(address: &str, block_hash: [u8; 32]) {
+    let script = [
+        PeerAction::Send(request(1, block_hash)),
+        PeerAction::Receive,
+    ];
+    let received = peer().run(address.parse().unwrap(), &script).await.unwrap();
+    assert!(is_found(&received[0]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_block_sync_server_hostile_peers() {
+    let state = Arc::new(Mutex::new(BlockSyncServerState::new(
+        &SyncServerConfig::default(),
+    )));
+    let block = L2Block::new_builder()
+        .raw(RawL2Block::new_builder().number(1u64.pack()).build())
+        .build();
+    let block_hash = block.raw().hash();
+    state
+        .lock()
+        .unwrap()
+        .publish_local_block(LocalBlock::new_builder().block(block).build());
+    let (address, _) = start_server(state, PeerScoreConfig::default()).await;
+    honest_request(&address, block_hash).await;
+
+    let valid = request(1, block_hash);
+    let scripts = [
+        // Malformed.
+        vec![PeerAction::Send(Bytes::from_static(b"hello"))],
+        // Truncated.
+        vec![PeerAction::Send(valid.slice(..valid.len() - 1))],
+        // Empty.
+        vec![PeerAction::Send(Bytes::new())],
+        // Replayed after the subscription started.
+        vec![
+            PeerAction::Send(valid.clone()),
+            PeerAction::Receive,
+            PeerAction::Send(valid.clone()),
+        ],
+    ];
+    for mut script in scripts {
+        script.push(PeerAction::ExpectDisconnect);
+        peer().run(address.parse().unwrap(), &script).await.unwrap();
+        honest_request(&address, block_hash).await;
+    }
+
+    // Oversized, above the 8MiB frame limit of the server.
+    let oversized = vec![
+        PeerAction::Send(Bytes::from(vec![0u8; 9 * 1024 * 1024])),
+        PeerAction::ExpectDisconnect,
+    ];
+    (peer().max_frame_length(16 * 1024 * 1024))
+        .run(address.parse().unwrap(), &oversized)
+        .await
+        .unwrap();
+    honest_request(&address, block_hash).await;
+
+    // Requests for unknown blocks are answered with TryAgain, and may be
+    // retried on the same session.
+    let unknown = request(2, [1; 32]);
+    let script = [
+        PeerAction::Send(unknown.clone()),
+        PeerAction::Receive,
+        PeerAction::Send(unknown),
+        PeerAction::Receive,
+    ];
+    let received = peer().run(address.parse().unwrap(), &script).await.unwrap();
+    assert_eq!(received.len(), 2);
+    honest_request(&address, block_hash).await;
+
+    // State sync is not served, so it's answered with TryAgain.
+    let script = [
+        PeerAction::Send(P2PStateSyncRequest::default().as_bytes()),
+        PeerAction::Receive,
+    ];
+    let received = peer().run(address.parse().unwrap(), &script).await.unwrap();
+    assert!(!is_found(&received[0]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_block_sync_server_bans_misbehaving_peer() {
+    let dir = tempfile::tempdir().unwrap();
+    let peer_score = PeerScoreConfig {
+        ban_list_path: Some(dir.path().join("banned_peers")),
+        ..Default::default()
+    };
+    let state = Arc::new(Mutex::new(BlockSyncServerState::new(
+        &SyncServerConfig::default(),
+    )));
+    let block = L2Block::new_builder()
+        .raw(RawL2Block::new_builder().number(1u64.pack()).build())
+        .build();
+    let block_hash = block.raw().hash();
+    state
+        .lock()
+        .unwrap()
+        .publish_local_block(LocalBlock::new_builder().block(block).build());
+    let (address, ledger) = start_server(state, peer_score.clone()).await;
+
+    // Each invalid request adds 50 to the score, which decays, and the peer
+    // is banned when it reaches 100.
+    let hostile = peer();
+    let malformed = [
+        PeerAction::Send(Bytes::from_static(b"hello")),
+        PeerAction::ExpectDisconnect,
+    ];
+    hostile
+        .run(address.parse().unwrap(), &malformed)
+        .await
+        .unwrap();
+    assert!(!ledger.is_banned(&hostile.peer_id()));
+    // Messages are counted by peer.
+    let hostile_id = hostile.peer_id().to_base58();
+    let metrics = gw_metrics::p2p();
+    let received = metrics.messages(P2P_SYNC_PROTOCOL_NAME, &hostile_id, Direction::In);
+    assert_eq!(received.get(), 1);
+    let received_bytes = metrics.message_bytes(P2P_SYNC_PROTOCOL_NAME, &hostile_id, Direction::In);
+    assert_eq!(received_bytes.get(), 5);
+    for _ in 0..2 {
+        // May fail to open the protocol once banned.
+        let _ = hostile.run(address.parse().unwrap(), &malformed).await;
+    }
+    assert!(ledger.is_banned(&hostile.peer_id()));
+
+    // The banned peer is not served, others are.
+    let script = [
+        PeerAction::Send(request(1, block_hash)),
+        PeerAction::Receive,
+    ];
+    assert!(hostile
+        .run(address.parse().unwrap(), &script)
+        .await
+        .is_err());
+    honest_request(&address, block_hash).await;
+
+    // Bans survive restarts.
+    let ledger = PeerLedger::new(&peer_score).unwrap();
+    assert!(ledger.is_banned(&hostile.peer_id()));
+    assert!(!ledger.is_banned(&peer().peer_id()));
+}
@@ -0,0 +1,52 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use gw_config::{KeepaliveConfig, P2PNetworkConfig, SyncServerConfig};
+use gw_mem_pool::block_sync_server::{block_sync_server_protocol, BlockSyncServerState};
+use gw_p2p_network::{
+    test_peer::{PeerAction, ScriptedPeer},
+    P2PNetwork, PeerLedger, P2P_KEEPALIVE_PROTOCOL, P2P_KEEPALIVE_PROTOCOL_NAME,
+};
+
+use crate::testing_tool::p2p::init_on_free_port;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_keepalive_closes_silent_sessions() {
+    let config = P2PNetworkConfig {
+        keepalive: Some(KeepaliveConfig {
+            interval_secs: 1,
+            timeout_secs: 3,
+        }),
+        ..Default::default()
+    };
+    let state = Arc::new(Mutex::new(BlockSyncServerState::new(
+        &SyncServerConfig::default(),
+    )));
+    let ledger = Arc::new(PeerLedger::new(&config.peer_score).unwrap());
+    let (mut network, address) = init_on_free_port(config, |config| {
+        let protocol = block_sync_server_protocol(state.clone(), None, None, ledger.clone());
+        let ledger = ledger.clone();
+        async move { P2PNetwork::init_with_peer_ledger(&config, [protocol], ledger).await }
+    })
+    .await;
+    tokio::spawn(async move { network.run().await });
+
+    let peer = ScriptedPeer::new(P2P_KEEPALIVE_PROTOCOL, P2P_KEEPALIVE_PROTOCOL_NAME)
+        .timeout(Duration::from_secs(10));
+    // A peer that keeps pinging stays connected.
+    let ping = Bytes::from_static(&[0]);
+    let mut script = Vec::new();
+    for _ in 0..5 {
+        script.push(PeerAction::Receive);
+        script.push(PeerAction::Send(ping.clone()));
+    }
+    let received = peer.run(address.parse().unwrap(), &script).await.unwrap();
+    assert_eq!(received.len(), 5);
+
+    // A silent peer is disconnected after the timeout.
+    let script = [PeerAction::Receive, PeerAction::ExpectDisconnect];
+    peer.run(address.parse().unwrap(), &script).await.unwrap();
+}
@@ -0,0 +1,131 @@
+//! Mem block deltas: the block sync server sends each peer the tx hashes since
+//! its last ack, and a snapshot to peers that ask for a resync.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use gw_config::{P2PNetworkConfig, SyncServerConfig};
+use gw_mem_pool::block_sync_server::{block_sync_server_protocol, BlockSyncServerState};
+use gw_p2p_network::{
+    test_peer::{PeerAction, ScriptedPeer},
+    P2PNetwork, PeerLedger, P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME,
+};
+use gw_types::{
+    packed::{
+        BlockInfo, BlockSync, BlockSyncUnion, Byte, L2Block, L2Transaction, LocalBlock,
+        MemBlockAck, NextMemBlock, NumberHash, P2PSyncRequest, RawL2Block, RawL2Transaction,
+    },
+    prelude::*,
+};
+use gw_utils::compression::StreamDecoder;
+
+use crate::testing_tool::p2p::init_on_free_port;
+
+fn tx(nonce: u32) -> L2Transaction {
+    let raw = RawL2Transaction::new_builder()
+        .from_id(2u32.pack())
+        .nonce(nonce.pack())
+        .build();
+    L2Transaction::new_builder().raw(raw).build()
+}
+
+fn ack(block_number: u64, tx_count: u32, resync: bool) -> PeerAction {
+    let ack = MemBlockAck::new_builder()
+        .block_number(block_number.pack())
+        .tx_count(tx_count.pack())
+        .resync(Byte::new(resync.into()))
+        .build();
+    PeerAction::Send(ack.as_bytes())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_mem_block_delta() {
+    let sync_server_config = SyncServerConfig {
+        mem_block_delta_interval_secs: Some(1),
+        ..Default::default()
+    };
+    let state = Arc::new(Mutex::new(BlockSyncServerState::new(&sync_server_config)));
+    let block = L2Block::new_builder()
+        .raw(RawL2Block::new_builder().number(1u64.pack()).build())
+        .build();
+    let block_hash = block.raw().hash();
+    let parent = NumberHash::new_builder()
+        .number(1u64.pack())
+        .block_hash(block_hash.pack())
+        .build();
+    let mem_block = NextMemBlock::new_builder()
+        .block_info(BlockInfo::new_builder().number(2u64.pack()).build())
+        .build();
+    {
+        let mut state = state.lock().unwrap();
+        state.publish_local_block(LocalBlock::new_builder().block(block).build());
+        state.publish_next_mem_block(parent, mem_block);
+        state.publish_transaction(tx(0));
+        state.publish_transaction(tx(1));
+    }
+
+    let config = P2PNetworkConfig::default();
+    let ledger = Arc::new(PeerLedger::new(&config.peer_score).unwrap());
+    let (mut network, address) = init_on_free_port(config, |config| {
+        let protocol = block_sync_server_protocol(state.clone(), None, None, ledger.clone());
+        let ledger = ledger.clone();
+        async move { P2PNetwork::init_with_peer_ledger(&config, [protocol], ledger).await }
+    })
+    .await;
+    tokio::spawn(async move { network.run().await });
+
+    let request = P2PSyncRequest::new_builder()
+        .block_number(1u64.pack())
+        .block_hash(block_hash.pack())
+        .build();
+    let script = [
+        PeerAction::Send(request.as_bytes()),
+        // Found, the mem block and its txs.
+        PeerAction::Receive,
+        PeerAction::Receive,
+        PeerAction::Receive,
+        PeerAction::Receive,
+        // The first delta has all txs.
+        PeerAction::Receive,
+        // Missed the last tx.
+        ack(2, 1, true),
+        PeerAction::Receive,
+        // Acks are only expected for deltas.
+        ack(2, 2, false),
+        PeerAction::ExpectDisconnect,
+    ];
+    let received = ScriptedPeer::new(P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME)
+        .timeout(Duration::from_secs(5))
+        .run(address.parse().unwrap(), &script)
+        .await
+        .unwrap();
+    assert_eq!(received.len(), 6);
+
+    let mut decoder = StreamDecoder::new();
+    let messages: Vec<BlockSync> = received
+        .iter()
+        .map(|msg| decoder.decode(msg).unwrap())
+        .skip(1)
+        .map(|msg| BlockSync::from_slice(&msg).unwrap())
+        .collect();
+    match messages[3].to_enum() {
+        BlockSyncUnion::MemBlockDelta(d) => {
+            let block_number: u64 = d.block_number().unpack();
+            let start: u32 = d.start().unpack();
+            let tx_hashes: Vec<[u8; 32]> = d.tx_hashes().unpack();
+            assert_eq!((block_number, start), (2, 0));
+            assert_eq!(tx_hashes, vec![tx(0).hash(), tx(1).hash()]);
+        }
+        _ => panic!("expect mem block delta"),
+    }
+    match messages[4].to_enum() {
+        BlockSyncUnion::MemBlockSnapshot(s) => {
+            let block_number: u64 = s.mem_block().block_info().number().unpack();
+            assert_eq!(block_number, 2);
+            assert_eq!(s.transactions().len(), 2);
+        }
+        _ => panic!("expect mem block snapshot"),
+    }
+}
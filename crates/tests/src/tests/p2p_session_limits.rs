@@ -0,0 +1,74 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use bytes::Bytes;
+use gw_config::{P2PNetworkConfig, SessionLimitsConfig};
+use gw_mem_pool::tx_relay::tx_relay_server_protocol;
+use gw_p2p_network::{
+    test_peer::{PeerAction, ScriptedPeer},
+    P2PNetwork, PeerLedger, P2P_TX_RELAY_PROTOCOL, P2P_TX_RELAY_PROTOCOL_NAME,
+};
+use tokio::task::JoinHandle;
+
+use crate::testing_tool::p2p::init_on_free_port;
+
+fn peer() -> ScriptedPeer {
+    ScriptedPeer::new(P2P_TX_RELAY_PROTOCOL, P2P_TX_RELAY_PROTOCOL_NAME)
+        .timeout(Duration::from_secs(3))
+}
+
+/// Open a session and keep it open for `secs`.
+fn hold_session(address: &str, secs: u64) -> JoinHandle<Result<Vec<Bytes>>> {
+    let address = address.parse().unwrap();
+    let script = [PeerAction::Sleep(Duration::from_secs(secs))];
+    tokio::spawn(async move { peer().run(address, &script).await })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_inbound_sessions_per_ip_limit() {
+    let config = P2PNetworkConfig {
+        session_limits: SessionLimitsConfig {
+            max_inbound_sessions_per_ip: 1,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let ledger = Arc::new(PeerLedger::new(&config.peer_score).unwrap());
+    let ((mut network, _relayed_txs), address) = init_on_free_port(config, |config| {
+        let (protocol, relayed_txs) = tx_relay_server_protocol(ledger.clone());
+        let ledger = ledger.clone();
+        async move {
+            let network = P2PNetwork::init_with_peer_ledger(&config, [protocol], ledger).await?;
+            Ok::<_, anyhow::Error>((network, relayed_txs))
+        }
+    })
+    .await;
+    let status = network.status();
+    tokio::spawn(async move { network.run().await });
+
+    // The first session from 127.0.0.1 stays open.
+    let first = hold_session(&address, 4);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(status.peer_count(), 1);
+
+    // The second one is closed, maybe before the protocol opens.
+    let script = [PeerAction::ExpectDisconnect];
+    let result = peer().run(address.parse().unwrap(), &script).await;
+    if let Err(err) = result {
+        assert!(
+            err.to_string().contains("open protocol timeout"),
+            "{:#}",
+            err
+        );
+    }
+    assert_eq!(status.peer_count(), 1);
+
+    // The slot is freed when the first session closes.
+    first.await.unwrap().unwrap();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(status.peer_count(), 0);
+    let third = hold_session(&address, 2);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(status.peer_count(), 1);
+    third.await.unwrap().unwrap();
+}
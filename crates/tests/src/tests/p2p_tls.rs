@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+
+use gw_config::{P2PNetworkConfig, P2PTlsConfig};
+use gw_mem_pool::tx_relay::TxRelay;
+use gw_p2p_network::P2PNetwork;
+
+async fn init_error(config: &P2PNetworkConfig) -> String {
+    let (_relay, protocol) = TxRelay::new();
+    let err = P2PNetwork::init(config, [protocol]).await.err().unwrap();
+    format!("{:#}", err)
+}
+
+#[tokio::test]
+async fn test_tls_config() {
+    // TLS addresses require the TLS config.
+    let config = P2PNetworkConfig {
+        dial: vec!["/ip4/127.0.0.1/tcp/443/tls/godwoken.example".into()],
+        ..Default::default()
+    };
+    let err = init_error(&config).await;
+    assert!(err.contains("p2p_network_config.tls"), "{}", err);
+
+    let config = P2PNetworkConfig {
+        tls: Some(P2PTlsConfig {
+            cert_path: PathBuf::from("/nonexistent/cert.pem"),
+            key_path: PathBuf::from("/nonexistent/key.pem"),
+            ca_path: PathBuf::from("/nonexistent/ca.pem"),
+            client_auth: true,
+        }),
+        ..config
+    };
+    let err = init_error(&config).await;
+    assert!(err.contains("/nonexistent/cert.pem"), "{}", err);
+}
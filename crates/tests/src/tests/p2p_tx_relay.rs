@@ -0,0 +1,71 @@
+use std::{sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use gw_config::P2PNetworkConfig;
+use gw_mem_pool::tx_relay::{tx_relay_server_protocol, TxRelay};
+use gw_p2p_network::{
+    test_peer::{PeerAction, ScriptedPeer},
+    P2PNetwork, PeerLedger, P2P_TX_RELAY_PROTOCOL, P2P_TX_RELAY_PROTOCOL_NAME,
+};
+use gw_types::{
+    packed::{L2Transaction, RawL2Transaction},
+    prelude::*,
+};
+
+use crate::testing_tool::p2p::init_on_free_port;
+
+fn tx(nonce: u32) -> L2Transaction {
+    let raw = RawL2Transaction::new_builder()
+        .from_id(2u32.pack())
+        .nonce(nonce.pack())
+        .build();
+    L2Transaction::new_builder().raw(raw).build()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_tx_relay() {
+    // Full node.
+    let config = P2PNetworkConfig::default();
+    let ledger = Arc::new(PeerLedger::new(&config.peer_score).unwrap());
+    let ((mut network, mut relayed_txs), address) = init_on_free_port(config, |config| {
+        let (protocol, relayed_txs) = tx_relay_server_protocol(ledger.clone());
+        let ledger = ledger.clone();
+        async move {
+            let network = P2PNetwork::init_with_peer_ledger(&config, [protocol], ledger).await?;
+            Ok::<_, anyhow::Error>((network, relayed_txs))
+        }
+    })
+    .await;
+    tokio::spawn(async move { network.run().await });
+
+    // Readonly node.
+    let config = P2PNetworkConfig {
+        dial: vec![address.clone()],
+        ..Default::default()
+    };
+    let (relay, protocol) = TxRelay::new();
+    let mut network = P2PNetwork::init(&config, [protocol]).await.unwrap();
+    tokio::spawn(async move { network.run().await });
+
+    // Txs queued before the session opens are relayed too.
+    assert_eq!(relay.relay(tx(0)), Ok(true));
+    // Resubmitted txs are only relayed once.
+    assert_eq!(relay.relay(tx(0)), Ok(false));
+    assert_eq!(relay.relay(tx(1)), Ok(true));
+    for nonce in 0..2u32 {
+        let relayed = tokio::time::timeout(Duration::from_secs(10), relayed_txs.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(relayed.as_slice(), tx(nonce).as_slice());
+    }
+
+    // Peers relaying invalid txs are disconnected.
+    let peer = ScriptedPeer::new(P2P_TX_RELAY_PROTOCOL, P2P_TX_RELAY_PROTOCOL_NAME)
+        .timeout(Duration::from_secs(5));
+    let script = [
+        PeerAction::Send(Bytes::from_static(b"garbage")),
+        PeerAction::ExpectDisconnect,
+    ];
+    peer.run(address.parse().unwrap(), &script).await.unwrap();
+}
@@ -0,0 +1,31 @@
+use ckb_fixed_hash::H256;
+use gw_builtin_binaries::{content_checksum, Resource};
+
+use crate::testing_tool::http::serve;
+
+#[test]
+fn test_url_resource() {
+    const BODY: &[u8] = b"generator";
+    let dir = tempfile::tempdir().unwrap();
+    // Served once, the second get must be read from the cache.
+    let url = format!("{}/generator", serve(vec![BODY]));
+    let resource = Resource::Url {
+        url,
+        sha256: H256(content_checksum(BODY)),
+        cache_dir: Some(dir.path().to_path_buf()),
+    };
+    assert!(!resource.exists());
+    assert_eq!(resource.get().unwrap().as_ref(), BODY);
+    assert!(resource.exists());
+    assert_eq!(resource.get().unwrap().as_ref(), BODY);
+
+    let url = format!("{}/generator", serve(vec![BODY]));
+    let resource = Resource::Url {
+        url,
+        sha256: H256(content_checksum(b"other generator")),
+        cache_dir: Some(dir.path().to_path_buf()),
+    };
+    let err = resource.get().unwrap_err();
+    assert!(err.to_string().contains("checksum mismatch"), "{}", err);
+    assert!(!resource.exists());
+}
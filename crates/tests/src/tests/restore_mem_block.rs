@@ -8,7 +8,6 @@ use crate::testing_tool::common::random_always_success_script;
 use crate::testing_tool::mem_pool_provider::DummyMemPoolProvider;
 use crate::testing_tool::rpc_server::RPCServer;
 
-use bytes::Bytes;
 use gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
 use gw_common::registry_address::RegistryAddress;
 use gw_common::state::State;
@@ -16,9 +15,9 @@ use gw_rpc_server::registry::Registry;
 use gw_store::state::history::history_state::RWConfig;
 use gw_store::state::BlockStateDB;
 use gw_types::h256::*;
+use gw_types::offchain::{SudtTransferParams, WithdrawalParams};
 use gw_types::packed::{
-    DepositInfoVec, DepositRequest, Fee, L2Transaction, RawL2Transaction, RawWithdrawalRequest,
-    SUDTArgs, SUDTTransfer, Script, WithdrawalRequest, WithdrawalRequestExtra,
+    DepositInfoVec, DepositRequest, L2Transaction, Script, WithdrawalRequestExtra,
 };
 use gw_types::prelude::*;
 use gw_types::U256;
@@ -81,19 +80,15 @@ async fn test_restore_mem_block() {
         withdrawal_accounts
             .map(|account_script| {
                 let owner_lock = Script::default();
-                let raw = RawWithdrawalRequest::new_builder()
-                    .capacity(WITHDRAWAL_CAPACITY.pack())
-                    .account_script_hash(account_script.hash().pack())
-                    .sudt_script_hash(H256::zero().pack())
-                    .owner_lock_hash(owner_lock.hash().pack())
-                    .registry_id(gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID.pack())
-                    .chain_id(TEST_CHAIN_ID.pack())
-                    .build();
-                let withdrawal = WithdrawalRequest::new_builder().raw(raw).build();
-                WithdrawalRequestExtra::new_builder()
-                    .request(withdrawal)
-                    .owner_lock(owner_lock)
-                    .build()
+                let params = WithdrawalParams {
+                    chain_id: TEST_CHAIN_ID,
+                    capacity: WITHDRAWAL_CAPACITY,
+                    account_script_hash: account_script.hash(),
+                    registry_id: gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID,
+                    owner_lock,
+                    ..Default::default()
+                };
+                WithdrawalRequestExtra::build(&params, Default::default()).unwrap()
             })
             .collect()
     };
@@ -129,23 +124,16 @@ async fn test_restore_mem_block() {
                 let to_script = random_always_success_script(&rollup_script_hash);
                 let to_addr =
                     RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, to_script.hash()[0..20].to_vec());
-                let transfer = SUDTTransfer::new_builder()
-                    .amount(U256::from(DEPOSIT_CAPACITY as u128 / 2).pack())
-                    .to_address(Bytes::from(to_addr.to_bytes()).pack())
-                    .fee(
-                        Fee::new_builder()
-                            .registry_id(gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID.pack())
-                            .build(),
-                    )
-                    .build();
-                let args = SUDTArgs::new_builder().set(transfer).build();
-                let raw = RawL2Transaction::new_builder()
-                    .from_id(from_id.unwrap().pack())
-                    .to_id(gw_common::builtins::CKB_SUDT_ACCOUNT_ID.pack()) // 1 is reserved for sudt
-                    .args(args.as_bytes().pack())
-                    .chain_id(crate::testing_tool::chain::TEST_CHAIN_ID.pack())
-                    .build();
-                L2Transaction::new_builder().raw(raw).build()
+                let params = SudtTransferParams {
+                    chain_id: TEST_CHAIN_ID,
+                    from_id: from_id.unwrap(),
+                    sudt_id: gw_common::builtins::CKB_SUDT_ACCOUNT_ID,
+                    to_address: to_addr,
+                    amount: U256::from(DEPOSIT_CAPACITY as u128 / 2),
+                    fee_registry_id: gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID,
+                    ..Default::default()
+                };
+                L2Transaction::build_sudt_transfer(&params, Default::default()).unwrap()
             })
             .collect()
     };
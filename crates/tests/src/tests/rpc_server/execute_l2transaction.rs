@@ -1,17 +1,25 @@
 use anyhow::anyhow;
-use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, registry_address::RegistryAddress, state::State};
+use gw_common::{
+    builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID, RESERVED_ACCOUNT_ID},
+    registry_address::RegistryAddress,
+    state::State,
+};
+use gw_generator::account_lock_manage::secp256k1::Secp256k1Eth;
 use gw_polyjuice_sender_recover::recover::error::PolyjuiceTxSenderRecoverError;
 use gw_store::state::traits::JournalDB;
 use gw_types::{
     bytes::Bytes,
     h256::*,
-    packed::{RawL2Transaction, Script},
+    packed::{
+        CreateAccount, DepositInfoVec, DepositRequest, Fee, L2Transaction, MetaContractArgs,
+        RawL2Transaction, Script,
+    },
     prelude::*,
     U256,
 };
 
 use crate::testing_tool::{
-    chain::TestChain,
+    chain::{into_deposit_info_cell, TestChain},
     eth_wallet::EthWallet,
     polyjuice::{erc20::SudtErc20ArgsBuilder, PolyjuiceAccount, PolyjuiceSystemLog},
     rpc_server::RPCServer,
@@ -336,3 +344,176 @@ async fn test_invalid_polyjuice_tx_from_id_zero() {
     };
     assert!(err.to_string().contains(&expected_err.to_string()));
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_polyjuice_tx_with_block_number() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let mut chain = TestChain::setup(rollup_type_script).await;
+    let rpc_server = RPCServer::build(&chain, None).await.unwrap();
+
+    // Deposit test account in block 1
+    const DEPOSIT_CAPACITY: u64 = 12345768 * 10u64.pow(8);
+    let test_wallet = EthWallet::random(chain.rollup_type_hash());
+    let deposit = DepositRequest::new_builder()
+        .capacity(DEPOSIT_CAPACITY.pack())
+        .sudt_script_hash(H256::zero().pack())
+        .amount(0.pack())
+        .script(test_wallet.account_script().to_owned())
+        .registry_id(ETH_REGISTRY_ACCOUNT_ID.pack())
+        .build();
+    let deposit_info_vec = DepositInfoVec::new_builder()
+        .push(into_deposit_info_cell(chain.inner.generator().rollup_context(), deposit).pack())
+        .build();
+    chain.produce_block(deposit_info_vec, vec![]).await.unwrap();
+
+    let mem_pool_state = chain.mem_pool_state().await;
+    let state = mem_pool_state.load_state_db();
+    let test_account_id = state
+        .get_account_id_by_script_hash(&test_wallet.account_script_hash())
+        .unwrap()
+        .unwrap();
+
+    // Create polyjuice account and deploy erc20 contract in block 2
+    let polyjuice_account = PolyjuiceAccount::build_script(chain.rollup_type_hash());
+    let meta_contract_script_hash = state.get_script_hash(RESERVED_ACCOUNT_ID).unwrap();
+    let fee = Fee::new_builder()
+        .registry_id(ETH_REGISTRY_ACCOUNT_ID.pack())
+        .amount(0u128.pack())
+        .build();
+    let create_polyjuice = CreateAccount::new_builder()
+        .fee(fee)
+        .script(polyjuice_account.clone())
+        .build();
+    let args = MetaContractArgs::new_builder()
+        .set(create_polyjuice)
+        .build();
+    let raw_l2tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(test_account_id.pack())
+        .to_id(RESERVED_ACCOUNT_ID.pack())
+        .nonce(0u32.pack())
+        .args(args.as_bytes().pack())
+        .build();
+    let signing_message = Secp256k1Eth::eip712_signing_message(
+        chain.chain_id(),
+        &raw_l2tx,
+        test_wallet.reg_address().to_owned(),
+        meta_contract_script_hash,
+    )
+    .unwrap();
+    let sign = test_wallet.sign_message(signing_message).unwrap();
+    let create_tx = L2Transaction::new_builder()
+        .raw(raw_l2tx)
+        .signature(sign.pack())
+        .build();
+    {
+        let mut mem_pool = chain.mem_pool().await;
+        mem_pool.push_transaction(create_tx).unwrap();
+    }
+
+    let state = mem_pool_state.load_state_db();
+    let polyjuice_account_id = state
+        .get_account_id_by_script_hash(&polyjuice_account.hash())
+        .unwrap()
+        .unwrap();
+    let deploy_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(test_account_id.pack())
+        .to_id(polyjuice_account_id.pack())
+        .nonce(1u32.pack())
+        .args(deploy_args.pack())
+        .build();
+    let deploy_tx = test_wallet.sign_polyjuice_tx(&state, raw_tx).unwrap();
+    let deploy_tx_hash: H256 = deploy_tx.hash();
+    {
+        let mut mem_pool = chain.mem_pool().await;
+        mem_pool.push_transaction(deploy_tx).unwrap();
+    }
+    let system_log = PolyjuiceSystemLog::parse_from_tx_hash(&chain, deploy_tx_hash).unwrap();
+    assert_eq!(system_log.status_code, 0);
+    chain
+        .produce_block(Default::default(), vec![])
+        .await
+        .unwrap();
+
+    // Transfer in block 3
+    let state = mem_pool_state.load_state_db();
+    let erc20_contract_account_id = system_log.contract_account_id(&state).unwrap();
+    let to_wallet = EthWallet::random(chain.rollup_type_hash());
+    let transfer_amount: U256 = 40000u128.into();
+    let transfer_args =
+        SudtErc20ArgsBuilder::transfer(to_wallet.reg_address(), transfer_amount).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(test_account_id.pack())
+        .to_id(erc20_contract_account_id.pack())
+        .nonce(2u32.pack())
+        .args(transfer_args.pack())
+        .build();
+    let transfer_tx = test_wallet.sign_polyjuice_tx(&state, raw_tx).unwrap();
+    {
+        let mut mem_pool = chain.mem_pool().await;
+        mem_pool.push_transaction(transfer_tx).unwrap();
+    }
+    chain
+        .produce_block(Default::default(), vec![])
+        .await
+        .unwrap();
+
+    // Txs are verified against the historical state, including the nonce
+    let state = mem_pool_state.load_state_db();
+    let balance_of = |nonce: u32| {
+        let balance_args = SudtErc20ArgsBuilder::balance_of(to_wallet.reg_address()).finish();
+        let raw_tx = RawL2Transaction::new_builder()
+            .chain_id(chain.chain_id().pack())
+            .from_id(test_account_id.pack())
+            .to_id(erc20_contract_account_id.pack())
+            .nonce(nonce.pack())
+            .args(balance_args.pack())
+            .build();
+        test_wallet.sign_polyjuice_tx(&state, raw_tx).unwrap()
+    };
+
+    let run_result = rpc_server
+        .execute_l2transaction_at_block(&balance_of(2), 2)
+        .await
+        .unwrap();
+    assert_eq!(
+        U256::zero(),
+        U256::from_big_endian(run_result.return_data.as_bytes())
+    );
+
+    let run_result = rpc_server
+        .execute_l2transaction_at_block(&balance_of(3), 3)
+        .await
+        .unwrap();
+    assert_eq!(
+        transfer_amount,
+        U256::from_big_endian(run_result.return_data.as_bytes())
+    );
+
+    let err = rpc_server
+        .execute_l2transaction_at_block(&balance_of(3), 2)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid nonce"), "{}", err);
+
+    // Execution is read-only
+    let run_result = rpc_server
+        .execute_l2transaction(&balance_of(3))
+        .await
+        .unwrap();
+    assert_eq!(
+        transfer_amount,
+        U256::from_big_endian(run_result.return_data.as_bytes())
+    );
+
+    let err = rpc_server
+        .execute_l2transaction_at_block(&balance_of(3), 100)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("header not found"), "{}", err);
+}
@@ -0,0 +1,99 @@
+use gw_common::builtins::CKB_SUDT_ACCOUNT_ID;
+use gw_metrics::rpc::CacheResult;
+use gw_store::state::traits::JournalDB;
+use gw_types::{
+    h256::*,
+    packed::{RawL2Transaction, Script},
+    prelude::*,
+    U256,
+};
+
+use crate::testing_tool::{
+    chain::TestChain,
+    eth_wallet::EthWallet,
+    polyjuice::{erc20::SudtErc20ArgsBuilder, PolyjuiceAccount, PolyjuiceSystemLog},
+    rpc_server::RPCServer,
+};
+
+async fn balance_of(rpc_server: &RPCServer, raw_tx: &RawL2Transaction) -> U256 {
+    let run_result = rpc_server
+        .execute_raw_l2transaction(raw_tx, None, None)
+        .await
+        .unwrap();
+    U256::from_big_endian(run_result.return_data.as_bytes())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_execution_cache() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let mut chain = TestChain::setup(rollup_type_script).await;
+    let rpc_server = {
+        let mut args = RPCServer::default_registry_args(
+            &chain.inner,
+            chain.rollup_type_script.to_owned(),
+            None,
+        );
+        args.server_config.execute_cache_size = 16;
+        RPCServer::build_from_registry_args(args).await.unwrap()
+    };
+    chain
+        .produce_block(Default::default(), vec![])
+        .await
+        .unwrap();
+
+    // Deploy erc20 contract
+    let mem_pool_state = chain.mem_pool_state().await;
+    let mut state = mem_pool_state.load_state_db();
+    let test_wallet = EthWallet::random(chain.rollup_type_hash());
+    let test_account_id = test_wallet
+        .create_account(&mut state, 1000000u128.into())
+        .unwrap();
+    let polyjuice_account = PolyjuiceAccount::create(chain.rollup_type_hash(), &mut state).unwrap();
+    let deploy_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(test_account_id.pack())
+        .to_id(polyjuice_account.id.pack())
+        .nonce(0u32.pack())
+        .args(deploy_args.pack())
+        .build();
+    let deploy_tx = test_wallet.sign_polyjuice_tx(&state, raw_tx).unwrap();
+    let deploy_tx_hash: H256 = deploy_tx.hash();
+    state.finalise().unwrap();
+    mem_pool_state.store_state_db(state);
+    {
+        let mut mem_pool = chain.mem_pool().await;
+        mem_pool.push_transaction(deploy_tx).unwrap();
+    }
+    let system_log = PolyjuiceSystemLog::parse_from_tx_hash(&chain, deploy_tx_hash).unwrap();
+    assert_eq!(system_log.status_code, 0);
+
+    let state = mem_pool_state.load_state_db();
+    let erc20_contract_account_id = system_log.contract_account_id(&state).unwrap();
+    let balance_args = SudtErc20ArgsBuilder::balance_of(test_wallet.reg_address()).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(test_account_id.pack())
+        .to_id(erc20_contract_account_id.pack())
+        .nonce(1u32.pack())
+        .args(balance_args.pack())
+        .build();
+    let hits = || gw_metrics::rpc().execute_cache(CacheResult::Hit).get();
+
+    let balance = balance_of(&rpc_server, &raw_tx).await;
+    let hits_before = hits();
+    assert_eq!(balance_of(&rpc_server, &raw_tx).await, balance);
+    assert!(hits() > hits_before, "identical request is cached");
+
+    // Results are invalidated when the mem pool state changes.
+    let mut state = mem_pool_state.load_state_db();
+    let minted: U256 = 42u128.into();
+    test_wallet
+        .mint_sudt(&mut state, CKB_SUDT_ACCOUNT_ID, minted)
+        .unwrap();
+    state.finalise().unwrap();
+    mem_pool_state.store_state_db(state);
+    assert_eq!(balance_of(&rpc_server, &raw_tx).await, balance + minted);
+}
@@ -4,6 +4,8 @@ use gw_common::{
     builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID},
     state::State,
 };
+use gw_generator::error::{TransactionError, TransactionValidateError};
+use gw_mem_pool::pool::pre_verify_transaction;
 use gw_smt::blake2b::new_blake2b;
 use gw_store::state::traits::JournalDB;
 use gw_types::prelude::*;
@@ -512,3 +514,60 @@ async fn test_invalid_polyjuice_tx_from_id_zero() {
         "unrecoverable txs should not be committed"
     );
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_pre_verify_without_mem_pool_lock() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let chain = TestChain::setup(rollup_type_script).await;
+
+    let mem_pool_state = chain.mem_pool_state().await;
+    let mut state = mem_pool_state.load_state_db();
+
+    let wallet = EthWallet::random(chain.rollup_type_hash());
+    let account_id = wallet
+        .create_account(&mut state, 1000000u128.into())
+        .unwrap();
+    let polyjuice_account = PolyjuiceAccount::create(chain.rollup_type_hash(), &mut state).unwrap();
+    state.finalise().unwrap();
+    mem_pool_state.store_state_db(state);
+
+    let deploy_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(account_id.pack())
+        .to_id(polyjuice_account.id.pack())
+        .nonce(0u32.pack())
+        .args(deploy_args.pack())
+        .build();
+    let state = mem_pool_state.load_state_db();
+    let tx = wallet.sign_polyjuice_tx(&state, raw_tx.clone()).unwrap();
+    let invalid_raw_tx = raw_tx
+        .as_builder()
+        .chain_id((chain.chain_id() + 1).pack())
+        .build();
+    let invalid_tx = wallet.sign_polyjuice_tx(&state, invalid_raw_tx).unwrap();
+    let block_number = mem_pool_state
+        .get_mem_pool_block_info()
+        .unwrap()
+        .number()
+        .unpack();
+
+    // Txs are pre-verified while the mem pool is locked, e.g. by the block
+    // producer.
+    let mut mem_pool = chain.mem_pool().await;
+    let generator = chain.inner.generator();
+    let err = pre_verify_transaction(generator, &state, invalid_tx, block_number).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<TransactionValidateError>(),
+        Some(TransactionValidateError::Transaction(
+            TransactionError::InvalidChainId { .. }
+        ))
+    ));
+
+    let tx = pre_verify_transaction(generator, &state, tx, block_number).unwrap();
+    let tx_hash = tx.tx().hash();
+    mem_pool.push_pre_verified_transaction(tx, None).unwrap();
+    assert!(mem_pool.mem_block().txs_set().contains(&tx_hash));
+}
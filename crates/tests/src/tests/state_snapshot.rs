@@ -0,0 +1,229 @@
+use std::sync::{Arc, Mutex};
+
+use crate::testing_tool::{
+    chain::{produce_empty_block, setup_chain_with_account_lock_manage, ALWAYS_SUCCESS_CODE_HASH},
+    p2p::init_on_free_port,
+};
+
+use ckb_crypto::secp::Privkey;
+use gw_chain::chain::Chain;
+use gw_config::{P2PNetworkConfig, StoreConfig, SyncServerConfig};
+use gw_generator::account_lock_manage::{always_success::AlwaysSuccess, AccountLockManage};
+use gw_mem_pool::block_sync_server::{
+    block_sync_server_protocol, BlockSyncServerState, StateSyncServer,
+};
+use gw_p2p_network::{
+    test_peer::{PeerAction, ScriptedPeer},
+    P2PNetwork, PeerLedger, P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME,
+};
+use gw_store::{readonly::StoreReadonly, schema::COLUMNS, traits::chain_store::ChainStore, Store};
+use gw_types::{
+    core::{AllowedEoaType, ScriptHashType},
+    packed::{
+        AllowedTypeHash, P2PStateSyncRequest, P2PSyncResponseReader, P2PSyncResponseUnionReader,
+        RollupConfig, Script, StateSnapshotEntry,
+    },
+    prelude::*,
+};
+use gw_utils::{
+    compression::StreamDecoder,
+    export_block::check_block_post_state,
+    state_snapshot::{export_snapshot, import_snapshot, verify_snapshot, SnapshotImporter},
+    wallet::Wallet,
+};
+
+const FINALITY_BLOCKS: u64 = 3;
+
+fn rollup_config() -> RollupConfig {
+    RollupConfig::new_builder()
+        .allowed_eoa_type_hashes(
+            vec![AllowedTypeHash::new(
+                AllowedEoaType::Eth,
+                *ALWAYS_SUCCESS_CODE_HASH,
+            )]
+            .pack(),
+        )
+        .finality_blocks(FINALITY_BLOCKS.pack())
+        .build()
+}
+
+async fn setup_chain(store: Store) -> Chain {
+    let rollup_type_script = Script::new_builder()
+        .code_hash([2u8; 32].pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args([1u8; 32][..].pack())
+        .build();
+    let mut account_lock_manage = AccountLockManage::default();
+    account_lock_manage.register_lock_algorithm(*ALWAYS_SUCCESS_CODE_HASH, Arc::new(AlwaysSuccess));
+    setup_chain_with_account_lock_manage(
+        rollup_type_script,
+        rollup_config(),
+        account_lock_manage,
+        Some(store),
+        None,
+        None,
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_export_import_state_snapshot() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let store_dir = tempfile::tempdir().expect("create temp dir");
+    let store = {
+        let config = StoreConfig {
+            path: store_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        Store::open(&config, COLUMNS).unwrap()
+    };
+    let mut chain = setup_chain(store).await;
+    for _ in 0..FINALITY_BLOCKS * 2 {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+    let fork_config = chain.generator().fork_config().clone();
+
+    // Export
+    let wallet = Wallet::try_from(Privkey::from_slice(&[1u8; 32])).unwrap();
+    let mut signer = [0u8; 20];
+    signer.copy_from_slice(&wallet.lock_script().args().raw_data());
+    let export_store = StoreReadonly::open(store_dir.path(), COLUMNS).unwrap();
+    let mut snapshot = Vec::new();
+    let header = export_snapshot(
+        &export_store,
+        &rollup_config(),
+        &fork_config,
+        &wallet,
+        &mut snapshot,
+    )
+    .unwrap();
+    let block_number: u64 = header.block_number().unpack();
+    let oldest_block_number: u64 = header.oldest_block_number().unpack();
+    assert_eq!(block_number, FINALITY_BLOCKS * 2);
+    assert_eq!(oldest_block_number, block_number - FINALITY_BLOCKS);
+
+    // Verify
+    verify_snapshot(&snapshot[..], &signer).unwrap();
+    assert!(verify_snapshot(&snapshot[..], &[0u8; 20]).is_err());
+    let mut tampered = snapshot.clone();
+    tampered[16] ^= 1;
+    assert!(verify_snapshot(&tampered[..], &signer).is_err());
+
+    // Import
+    let import_store = Store::open_tmp().unwrap();
+    import_snapshot(&import_store, &snapshot[..]).unwrap();
+    assert!(import_snapshot(&import_store, &snapshot[..]).is_err());
+
+    let tip_block_hash = export_store.get_tip_block_hash().unwrap();
+    assert_eq!(import_store.get_tip_block_hash().unwrap(), tip_block_hash);
+    let post_global_state = export_store
+        .get_block_post_global_state(&tip_block_hash)
+        .unwrap()
+        .unwrap();
+    check_block_post_state(
+        &import_store.begin_transaction(),
+        block_number,
+        &post_global_state,
+    )
+    .unwrap();
+    assert_eq!(
+        import_store.get_oldest_block_number(),
+        Some(oldest_block_number)
+    );
+    assert!(import_store
+        .get_block(&import_store.get_block_hash_by_number(0).unwrap().unwrap())
+        .unwrap()
+        .is_none());
+
+    // Blocks after the snapshot block can be produced on the imported store.
+    let mut import_chain = setup_chain(import_store.clone()).await;
+    produce_empty_block(&mut import_chain).await.unwrap();
+    let tip_block_number: u64 = import_store
+        .get_tip_block()
+        .unwrap()
+        .raw()
+        .number()
+        .unpack();
+    assert_eq!(tip_block_number, block_number + 1);
+
+    // State before the snapshot block is not available.
+    let mut db = import_store.begin_transaction();
+    db.detach_block(&import_store.get_tip_block().unwrap())
+        .unwrap();
+    assert!(db
+        .detach_block(&import_store.get_block(&tip_block_hash).unwrap().unwrap())
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_state_sync_over_p2p() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let store = Store::open_tmp().unwrap();
+    let mut chain = setup_chain(store.clone()).await;
+    for _ in 0..FINALITY_BLOCKS * 2 {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+
+    // Serve the state.
+    let config = P2PNetworkConfig::default();
+    let state = Arc::new(Mutex::new(BlockSyncServerState::new(
+        &SyncServerConfig::default(),
+    )));
+    let state_sync_server =
+        StateSyncServer::new(store.clone(), chain.generator().rollup_context().clone());
+    let ledger = Arc::new(PeerLedger::new(&config.peer_score).unwrap());
+    let state_sync_server = Some(Arc::new(state_sync_server));
+    let (mut network, address) = init_on_free_port(config, |config| {
+        let protocol = block_sync_server_protocol(
+            state.clone(),
+            state_sync_server.clone(),
+            None,
+            ledger.clone(),
+        );
+        let ledger = ledger.clone();
+        async move { P2PNetwork::init_with_peer_ledger(&config, [protocol], ledger).await }
+    })
+    .await;
+    tokio::spawn(async move { network.run().await });
+
+    // Found, header, one chunk, blocks after the finalized one, trailer.
+    let mut script = vec![PeerAction::Send(P2PStateSyncRequest::default().as_bytes())];
+    script.extend(vec![PeerAction::Receive; 4 + FINALITY_BLOCKS as usize + 1]);
+    let received = ScriptedPeer::new(P2P_SYNC_PROTOCOL, P2P_SYNC_PROTOCOL_NAME)
+        .run(address.parse().unwrap(), &script)
+        .await
+        .unwrap();
+
+    // Messages of a session share one compression context.
+    let mut decoder = StreamDecoder::new();
+    let mut messages = received.iter().map(|msg| decoder.decode(msg).unwrap());
+    let response = messages.next().unwrap();
+    let response = P2PSyncResponseReader::from_slice(&response).unwrap();
+    assert!(matches!(
+        response.to_enum(),
+        P2PSyncResponseUnionReader::Found(_)
+    ));
+
+    let import_store = Store::open_tmp().unwrap();
+    let mut importer = SnapshotImporter::new(&import_store).unwrap();
+    let mut header = None;
+    for msg in messages {
+        assert!(header.is_none());
+        let entry = StateSnapshotEntry::from_slice(&msg).unwrap();
+        header = importer.push(entry).unwrap();
+    }
+    let header = header.expect("trailer");
+    let block_number: u64 = header.block_number().unpack();
+    assert_eq!(block_number, FINALITY_BLOCKS * 2);
+    assert_eq!(
+        import_store.get_tip_block_hash().unwrap(),
+        store.get_tip_block_hash().unwrap()
+    );
+    let last_confirmed = import_store.get_last_confirmed_block_number_hash().unwrap();
+    assert_eq!(
+        last_confirmed.block_hash().as_slice(),
+        header.block_hash().as_slice()
+    );
+}
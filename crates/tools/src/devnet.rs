@@ -0,0 +1,355 @@
+//! Local devnet: a CKB dev chain with a miner, godwoken scripts and genesis
+//! deployed on it, and a godwoken full node.
+//!
+//! Processes run in background. Their pids and the RPC endpoints are saved in
+//! `devnet.json` under the devnet directory, for `devnet status` and
+//! `devnet down`.
+
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use gw_rpc_client::ckb_client::CkbClient;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    account::{privkey_to_eth_address, read_privkey},
+    deploy_genesis::{deploy_rollup_cell, DeployRollupCellArgs},
+    deploy_scripts::deploy_scripts,
+    generate_config::{GenerateConfigCommand, GENERATE_CONFIG_COMMAND},
+    godwoken_rpc::GodwokenRpcClient,
+    types::{BuildScriptsResult, UserRollupConfig},
+    utils::deploy::DeployContextArgs,
+};
+
+pub const DEVNET_COMMAND: &str = "devnet";
+
+/// Key of the genesis issued cells of the CKB dev chain spec. It pays for all
+/// deployments and is the block producer wallet. Never use it elsewhere.
+const DEV_CHAIN_PRIVKEY: &str =
+    "0xd00c06bfd800d27397002dca6fb0993d5ba6399b4238b2f29ee9deb97593d2bc";
+const DEV_CHAIN_LOCK_ARG: &str = "0xc8328aabcd9b9e8e64fbc566c4385c3bdeb219d7";
+const SECP256K1_BLAKE160_CODE_HASH: &str =
+    "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8";
+const STATE_FILE: &str = "devnet.json";
+
+/// Run a local devnet with CKB and godwoken
+#[derive(Parser)]
+#[clap(name = DEVNET_COMMAND)]
+pub struct DevnetCommand {
+    #[clap(subcommand)]
+    action: DevnetAction,
+}
+
+#[derive(Subcommand)]
+enum DevnetAction {
+    /// Start CKB, deploy scripts and genesis, then start a godwoken full node
+    Up(UpArgs),
+    /// Stop all devnet processes
+    Down(DownArgs),
+    /// Show devnet processes and chain tips
+    Status(DirArgs),
+}
+
+#[derive(Args)]
+struct DirArgs {
+    /// Devnet directory, chain data, configs and logs are kept here
+    #[clap(long, default_value = "devnet")]
+    dir: PathBuf,
+}
+
+#[derive(Args)]
+struct UpArgs {
+    #[clap(flatten)]
+    dir: DirArgs,
+    /// Scripts build result json file path, the input of deploy-scripts
+    #[clap(short = 'c', long)]
+    scripts_deployment_config_path: PathBuf,
+    /// The user rollup config json file path
+    #[clap(short, long)]
+    rollup_config: PathBuf,
+    /// The omni lock config json file path
+    #[clap(long)]
+    omni_lock_config_path: Option<PathBuf>,
+    /// CKB binary
+    #[clap(long, default_value = "ckb")]
+    ckb_bin: PathBuf,
+    /// Godwoken binary
+    #[clap(long, default_value = "godwoken")]
+    godwoken_bin: PathBuf,
+    /// CKB jsonrpc URL, must match the RPC listen address of the dev chain
+    #[clap(long, default_value = "http://127.0.0.1:8114")]
+    ckb_rpc: String,
+    /// Godwoken RPC server listening address
+    #[clap(long, default_value = "localhost:8119")]
+    rpc_server_url: String,
+}
+
+#[derive(Args)]
+struct DownArgs {
+    #[clap(flatten)]
+    dir: DirArgs,
+    /// Also remove the devnet directory
+    #[clap(long)]
+    clean: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DevnetState {
+    ckb_rpc: String,
+    godwoken_rpc: String,
+    processes: Vec<DevnetProcess>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DevnetProcess {
+    name: String,
+    pid: u32,
+}
+
+impl DevnetCommand {
+    pub async fn run(self) -> Result<()> {
+        match self.action {
+            DevnetAction::Up(args) => up(args).await,
+            DevnetAction::Down(args) => down(args),
+            DevnetAction::Status(args) => status(args).await,
+        }
+    }
+}
+
+async fn up(args: UpArgs) -> Result<()> {
+    let dir = &args.dir.dir;
+    if dir.join(STATE_FILE).exists() {
+        bail!(
+            "devnet in {} is already up, run devnet down first",
+            dir.to_string_lossy()
+        );
+    }
+    fs::create_dir_all(dir)?;
+    let dir = &dir.canonicalize()?;
+    let privkey_path = dir.join("pk");
+    fs::write(&privkey_path, DEV_CHAIN_PRIVKEY)?;
+
+    // CKB dev chain.
+    let ckb_dir = dir.join("ckb");
+    if !ckb_dir.join("ckb.toml").exists() {
+        log::info!("init CKB dev chain in {}", ckb_dir.to_string_lossy());
+        let status = Command::new(&args.ckb_bin)
+            .args(["init", "--chain", "dev", "--ba-arg", DEV_CHAIN_LOCK_ARG])
+            .args(["--ba-code-hash", SECP256K1_BLAKE160_CODE_HASH])
+            .args(["--ba-hash-type", "type", "-C"])
+            .arg(&ckb_dir)
+            .status()
+            .context("run ckb init")?;
+        if !status.success() {
+            bail!("ckb init exited with {}", status);
+        }
+    }
+    let mut state = DevnetState {
+        ckb_rpc: args.ckb_rpc.clone(),
+        godwoken_rpc: format!("http://{}", args.rpc_server_url),
+        processes: Vec::new(),
+    };
+    let mut ckb_run = Command::new(&args.ckb_bin);
+    ckb_run.args(["run", "--indexer", "-C"]).arg(&ckb_dir);
+    state.processes.push(spawn(dir, "ckb", &mut ckb_run)?);
+    let mut ckb_miner = Command::new(&args.ckb_bin);
+    ckb_miner.args(["miner", "-C"]).arg(&ckb_dir);
+    state
+        .processes
+        .push(spawn(dir, "ckb-miner", &mut ckb_miner)?);
+    // Save early, so devnet down can stop the processes if a later step fails.
+    write_json(&dir.join(STATE_FILE), &state)?;
+    wait_for_ckb(&args.ckb_rpc).await?;
+
+    // Scripts and genesis.
+    let context = DeployContextArgs {
+        ckb_rpc: args.ckb_rpc.clone(),
+        ckb_indexer_rpc: None,
+        privkey_path: privkey_path.clone(),
+    }
+    .build()
+    .await?;
+    let scripts: BuildScriptsResult = read_json(&args.scripts_deployment_config_path)?;
+    let scripts_deployment = deploy_scripts(&context, &scripts).await?;
+    let scripts_deployment_path = dir.join("scripts-deployment.json");
+    write_json(&scripts_deployment_path, &scripts_deployment)?;
+
+    let user_rollup_config: UserRollupConfig = read_json(&args.rollup_config)?;
+    let genesis_deployment = deploy_rollup_cell(DeployRollupCellArgs {
+        privkey_path: &privkey_path,
+        ckb_rpc_url: &args.ckb_rpc,
+        ckb_indexer_rpc_url: None,
+        scripts_result: &scripts_deployment,
+        user_rollup_config: &user_rollup_config,
+        timestamp: None,
+        skip_config_check: false,
+    })
+    .await?;
+    let genesis_deployment_path = dir.join("genesis-deployment.json");
+    write_json(&genesis_deployment_path, &genesis_deployment)?;
+
+    // Godwoken full node.
+    let config_path = dir.join("config.toml");
+    let block_producer_address = privkey_to_eth_address(&read_privkey(&privkey_path)?)?;
+    let mut config_args: Vec<OsString> = vec![
+        GENERATE_CONFIG_COMMAND.into(),
+        "-o".into(),
+        config_path.clone().into(),
+        "--scripts-deployment-path".into(),
+        scripts_deployment_path.into(),
+        "-g".into(),
+        genesis_deployment_path.into(),
+        "-r".into(),
+        args.rollup_config.clone().into(),
+        "-c".into(),
+        args.scripts_deployment_config_path.clone().into(),
+        "--ckb-rpc".into(),
+        args.ckb_rpc.clone().into(),
+        "--node-mode".into(),
+        "fullnode".into(),
+        "-k".into(),
+        privkey_path.into(),
+        "--store-path".into(),
+        dir.join("gw-db").into(),
+        "--block-producer-address".into(),
+        hex::encode(block_producer_address).into(),
+        "--rpc-server-url".into(),
+        args.rpc_server_url.clone().into(),
+    ];
+    if let Some(ref p) = args.omni_lock_config_path {
+        config_args.push("--omni-lock-config-path".into());
+        config_args.push(p.into());
+    }
+    GenerateConfigCommand::try_parse_from(config_args)?
+        .run()
+        .await?;
+
+    let mut godwoken_run = Command::new(&args.godwoken_bin);
+    godwoken_run.args(["run", "-c"]).arg(&config_path);
+    state
+        .processes
+        .push(spawn(dir, "godwoken", &mut godwoken_run)?);
+    write_json(&dir.join(STATE_FILE), &state)?;
+
+    println!("devnet is up, logs are in {}", dir.to_string_lossy());
+    println!("CKB RPC: {}", state.ckb_rpc);
+    println!("Godwoken RPC: {}", state.godwoken_rpc);
+    Ok(())
+}
+
+fn down(args: DownArgs) -> Result<()> {
+    let dir = &args.dir.dir;
+    let state_path = dir.join(STATE_FILE);
+    if state_path.exists() {
+        let state: DevnetState = read_json(&state_path)?;
+        // Stop godwoken before CKB.
+        for p in state.processes.iter().rev() {
+            if is_running(p.pid) {
+                log::info!("stop {} ({})", p.name, p.pid);
+                Command::new("kill").arg(p.pid.to_string()).status()?;
+            }
+        }
+        fs::remove_file(&state_path)?;
+    } else {
+        log::info!("devnet in {} is not up", dir.to_string_lossy());
+    }
+    if args.clean && dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+async fn status(args: DirArgs) -> Result<()> {
+    let state_path = args.dir.join(STATE_FILE);
+    if !state_path.exists() {
+        println!("devnet in {} is not up", args.dir.to_string_lossy());
+        return Ok(());
+    }
+    let state: DevnetState = read_json(&state_path)?;
+    for p in state.processes.iter() {
+        let status = if is_running(p.pid) {
+            "running"
+        } else {
+            "stopped"
+        };
+        println!("{} ({}): {}", p.name, p.pid, status);
+    }
+
+    let ckb_tip = match CkbClient::with_url(&state.ckb_rpc)?
+        .get_tip_block_number()
+        .await
+    {
+        Ok(number) => number.value().to_string(),
+        Err(err) => format!("unavailable, {}", err),
+    };
+    println!("CKB RPC: {}, tip: {}", state.ckb_rpc, ckb_tip);
+    let godwoken_tip = match GodwokenRpcClient::new(&state.godwoken_rpc)
+        .get_tip_block_hash()
+        .await
+    {
+        Ok(Some(hash)) => format!("{:#x}", hash),
+        Ok(None) => "none".to_string(),
+        Err(err) => format!("unavailable, {}", err),
+    };
+    println!(
+        "Godwoken RPC: {}, tip: {}",
+        state.godwoken_rpc, godwoken_tip
+    );
+    Ok(())
+}
+
+/// Spawn a background process, stdout and stderr go to `<dir>/<name>.log`.
+fn spawn(dir: &Path, name: &str, command: &mut Command) -> Result<DevnetProcess> {
+    let log_path = dir.join(format!("{}.log", name));
+    let log = File::create(&log_path)
+        .with_context(|| format!("create {}", log_path.to_string_lossy()))?;
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .spawn()
+        .with_context(|| format!("spawn {}", name))?;
+    log::info!("started {} ({})", name, child.id());
+    Ok(DevnetProcess {
+        name: name.to_string(),
+        pid: child.id(),
+    })
+}
+
+fn is_running(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .map_or(false, |s| s.success())
+}
+
+async fn wait_for_ckb(ckb_rpc: &str) -> Result<()> {
+    let client = CkbClient::with_url(ckb_rpc)?;
+    for _ in 0..60 {
+        if client.get_tip_block_number().await.is_ok() {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    bail!("CKB RPC {} is not ready", ckb_rpc);
+}
+
+fn read_json<T: DeserializeOwned>(p: &Path) -> Result<T> {
+    let ctx = || format!("read file {}", p.to_string_lossy());
+    let c = fs::read(p).with_context(ctx)?;
+    let r = serde_json::from_slice(&c).with_context(ctx)?;
+    Ok(r)
+}
+
+fn write_json<T: Serialize>(p: &Path, content: &T) -> Result<()> {
+    fs::write(p, serde_json::to_string_pretty(content)?)
+        .with_context(|| format!("write file {}", p.to_string_lossy()))
+}
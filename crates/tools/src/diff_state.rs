@@ -0,0 +1,232 @@
+//! Compare the state of two godwoken nodes at a block, for diagnosing state
+//! divergence.
+//!
+//! Block hashes are compared first. If the nodes have different blocks at the
+//! chosen number, the first different block is found by bisection. Otherwise
+//! the state they serve at the block is compared: storage slots written by the
+//! block, then script hash, nonce and CKB balance of accounts.
+
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use ckb_types::H256;
+use clap::Parser;
+use gw_common::builtins::CKB_SUDT_ACCOUNT_ID;
+use gw_jsonrpc_types::godwoken::StateChangeEvent;
+
+use crate::godwoken_rpc::GodwokenRpcClient;
+
+pub const DIFF_STATE_COMMAND: &str = "diff-state";
+
+/// Compare the state of two godwoken nodes at a block
+#[derive(Parser)]
+#[clap(name = DIFF_STATE_COMMAND)]
+pub struct DiffStateCommand {
+    /// Godwoken RPC URL of the first node
+    #[clap(long)]
+    rpc_a: String,
+    /// Godwoken RPC URL of the second node. To compare with a local store,
+    /// start a readonly node on it.
+    #[clap(long)]
+    rpc_b: String,
+    /// Block number to compare at
+    #[clap(short, long)]
+    block: u64,
+    /// Compare this many random accounts instead of walking all accounts
+    #[clap(long)]
+    sample: Option<usize>,
+    /// Stop after reporting this many differences
+    #[clap(long, default_value = "10")]
+    max_diffs: usize,
+}
+
+impl DiffStateCommand {
+    pub async fn run(self) -> Result<()> {
+        let a = GodwokenRpcClient::new(&self.rpc_a);
+        let b = GodwokenRpcClient::new(&self.rpc_b);
+        let block = self.block;
+
+        let (block_a, block_b) =
+            tokio::try_join!(a.get_block_by_number(block), b.get_block_by_number(block))?;
+        let block_a = block_a.with_context(|| format!("block {} not found on node a", block))?;
+        let block_b = block_b.with_context(|| format!("block {} not found on node b", block))?;
+        if block_a.hash != block_b.hash {
+            println!(
+                "block {} differs: {:#x} on node a, {:#x} on node b",
+                block, block_a.hash, block_b.hash
+            );
+            let first = first_different_block(&a, &b, block).await?;
+            println!("first different block: {}", first);
+            return Ok(());
+        }
+
+        let post_account = block_a.raw.post_account;
+        let account_count: u32 = post_account.count.into();
+        println!(
+            "block {} {:#x}, state root {:#x}, {} accounts",
+            block, block_a.hash, post_account.merkle_root, account_count
+        );
+
+        let mut report = Report::new(self.max_diffs);
+
+        // Storage slots written by the block are the most likely to diverge.
+        match a.get_state_changes_by_block(&block_a.hash).await? {
+            Some(changes) => {
+                let slots: BTreeSet<(u32, H256)> = changes
+                    .transactions
+                    .into_iter()
+                    .flat_map(|tx| tx.events)
+                    .filter_map(|event| match event {
+                        StateChangeEvent::AccountState { id, key, .. } => {
+                            let key = H256::from_slice(key.as_bytes()).ok()?;
+                            Some((id, key))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                println!(
+                    "comparing {} storage slots written by the block",
+                    slots.len()
+                );
+                for (id, key) in slots {
+                    let (value_a, value_b) = tokio::try_join!(
+                        a.get_storage_at(id, &key, block),
+                        b.get_storage_at(id, &key, block)
+                    )?;
+                    if value_a != value_b {
+                        report.diff(format!(
+                            "account {} storage {:#x}: {:#x} != {:#x}",
+                            id, key, value_a, value_b
+                        ));
+                    }
+                    if report.is_full() {
+                        return report.finish();
+                    }
+                }
+            }
+            None => println!("state changes of the block are not recorded on node a"),
+        }
+
+        let account_ids: Vec<u32> = match self.sample {
+            Some(n) => {
+                let n = n.min(account_count as usize);
+                let mut ids: Vec<u32> =
+                    rand::seq::index::sample(&mut rand::thread_rng(), account_count as usize, n)
+                        .into_iter()
+                        .map(|id| id as u32)
+                        .collect();
+                ids.sort_unstable();
+                ids
+            }
+            None => (0..account_count).collect(),
+        };
+        println!("comparing {} accounts", account_ids.len());
+        for id in account_ids {
+            for diff in diff_account(&a, &b, id, block).await? {
+                report.diff(diff);
+            }
+            if report.is_full() {
+                break;
+            }
+        }
+        report.finish()
+    }
+}
+
+/// Blocks at `different` differ. Assume genesis blocks are the same.
+async fn first_different_block(
+    a: &GodwokenRpcClient,
+    b: &GodwokenRpcClient,
+    different: u64,
+) -> Result<u64> {
+    let (mut same, mut different) = (0, different);
+    while different - same > 1 {
+        let mid = same + (different - same) / 2;
+        let (hash_a, hash_b) = tokio::try_join!(a.get_block_hash(mid), b.get_block_hash(mid))?;
+        if hash_a.is_some() && hash_a == hash_b {
+            same = mid;
+        } else {
+            different = mid;
+        }
+    }
+    Ok(different)
+}
+
+async fn diff_account(
+    a: &GodwokenRpcClient,
+    b: &GodwokenRpcClient,
+    id: u32,
+    block: u64,
+) -> Result<Vec<String>> {
+    let mut diffs = Vec::new();
+
+    let (script_hash_a, script_hash_b) =
+        tokio::try_join!(a.get_script_hash(id), b.get_script_hash(id))?;
+    if script_hash_a != script_hash_b {
+        diffs.push(format!(
+            "account {} script hash: {:#x} != {:#x}",
+            id, script_hash_a, script_hash_b
+        ));
+        return Ok(diffs);
+    }
+
+    let (nonce_a, nonce_b) =
+        tokio::try_join!(a.get_nonce_at(id, block), b.get_nonce_at(id, block))?;
+    if nonce_a != nonce_b {
+        diffs.push(format!("account {} nonce: {} != {}", id, nonce_a, nonce_b));
+    }
+
+    // Balances are queried by registry address, only eth addresses are
+    // supported.
+    let address = a
+        .get_registry_address_by_script_hash(&script_hash_a)
+        .await?;
+    if let Some(address) = address {
+        let (balance_a, balance_b) = tokio::try_join!(
+            a.get_balance_at(&address, CKB_SUDT_ACCOUNT_ID, block),
+            b.get_balance_at(&address, CKB_SUDT_ACCOUNT_ID, block)
+        )?;
+        if balance_a != balance_b {
+            diffs.push(format!(
+                "account {} CKB balance: {} != {}",
+                id, balance_a, balance_b
+            ));
+        }
+    }
+
+    Ok(diffs)
+}
+
+struct Report {
+    diffs: usize,
+    max_diffs: usize,
+}
+
+impl Report {
+    fn new(max_diffs: usize) -> Self {
+        Self {
+            diffs: 0,
+            max_diffs,
+        }
+    }
+
+    fn diff(&mut self, diff: String) {
+        self.diffs += 1;
+        println!("{}", diff);
+    }
+
+    fn is_full(&self) -> bool {
+        self.diffs >= self.max_diffs
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.diffs == 0 {
+            println!("no differences found");
+        } else if self.is_full() {
+            println!("stopped after {} differences", self.diffs);
+        } else {
+            println!("{} differences found", self.diffs);
+        }
+        Ok(())
+    }
+}
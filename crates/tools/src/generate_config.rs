@@ -177,6 +177,7 @@ pub async fn generate_node_config(cmd: GenerateConfigCommand) -> Result<()> {
 
     let challenger_config = ChallengerConfig {
         rewards_receiver_lock: user_rollup_config.reward_lock.clone(),
+        dry_run: false,
     };
 
     let wallet_config = cmd.privkey_path.map(|p| WalletConfig { privkey_path: p });
@@ -249,6 +250,7 @@ pub async fn generate_node_config(cmd: GenerateConfigCommand) -> Result<()> {
             permit_sudt_transfer_from_dangerous_contract: true,
             address_list: Vec::new(),
         }),
+        batch_create_eth_accounts: None,
         backends,
     }];
 
@@ -280,6 +282,7 @@ pub async fn generate_node_config(cmd: GenerateConfigCommand) -> Result<()> {
         backend_forks,
         increase_max_l2_tx_cycles_to_500m: None,
         upgrade_global_state_version_to_v2: Some(0),
+        cycle_limit_forks: Default::default(),
         genesis,
         chain,
         system_type_scripts,
@@ -294,6 +297,7 @@ pub async fn generate_node_config(cmd: GenerateConfigCommand) -> Result<()> {
     let rpc_client: RPCClientConfig = RPCClientConfig {
         indexer_url: cmd.ckb_indexer_rpc,
         ckb_url: cmd.ckb_rpc,
+        cell_cache_dir: None,
     };
     let rpc_server = RPCServerConfig {
         listen: cmd.rpc_server_url,
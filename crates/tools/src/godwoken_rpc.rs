@@ -11,9 +11,9 @@ use ckb_jsonrpc_types::Script;
 use ckb_types::H256;
 use gw_common::{builtins::ETH_REGISTRY_ACCOUNT_ID, registry_address::RegistryAddress};
 use gw_jsonrpc_types::{
-    ckb_jsonrpc_types::{JsonBytes, Uint32},
+    ckb_jsonrpc_types::{JsonBytes, Uint32, Uint64},
     debugger::{DumpChallengeTarget, ReprMockTransaction},
-    godwoken::{RunResult, TxReceipt},
+    godwoken::{BlockStateChanges, L2BlockView, RunResult, TxReceipt},
 };
 use gw_types::U256;
 
@@ -54,6 +54,62 @@ impl GodwokenRpcClient {
             .map(|opt| opt.map(Into::into))
     }
 
+    pub async fn get_block_hash(&self, block_number: u64) -> Result<Option<H256>> {
+        let params = serde_json::to_value((Uint64::from(block_number),))?;
+        self.rpc::<Option<H256>>("get_block_hash", params).await
+    }
+
+    pub async fn get_block_by_number(&self, block_number: u64) -> Result<Option<L2BlockView>> {
+        let params = serde_json::to_value((Uint64::from(block_number),))?;
+        self.rpc::<Option<L2BlockView>>("get_block_by_number", params)
+            .await
+    }
+
+    pub async fn get_state_changes_by_block(
+        &self,
+        block_hash: &H256,
+    ) -> Result<Option<BlockStateChanges>> {
+        let params = serde_json::to_value((block_hash,))?;
+        self.rpc::<Option<BlockStateChanges>>("state_changes_by_block", params)
+            .await
+    }
+
+    /// Balance at `block_number`.
+    pub async fn get_balance_at(
+        &self,
+        addr: &RegistryAddress,
+        sudt_id: u32,
+        block_number: u64,
+    ) -> Result<U256> {
+        let params = serde_json::to_value((
+            JsonBytes::from_vec(addr.to_bytes()),
+            AccountID::from(sudt_id),
+            Uint64::from(block_number),
+        ))?;
+        self.rpc::<U256>("get_balance", params).await
+    }
+
+    /// Nonce at `block_number`.
+    pub async fn get_nonce_at(&self, account_id: u32, block_number: u64) -> Result<u32> {
+        let params =
+            serde_json::to_value((AccountID::from(account_id), Uint64::from(block_number)))?;
+        self.rpc::<Uint32>("get_nonce", params)
+            .await
+            .map(Into::into)
+    }
+
+    /// Storage value at `block_number`.
+    pub async fn get_storage_at(
+        &self,
+        account_id: u32,
+        key: &H256,
+        block_number: u64,
+    ) -> Result<H256> {
+        let params =
+            serde_json::to_value((AccountID::from(account_id), key, Uint64::from(block_number)))?;
+        self.rpc::<H256>("get_storage_at", params).await
+    }
+
     pub async fn get_balance(&self, addr: &RegistryAddress, sudt_id: u32) -> Result<U256> {
         let params = serde_json::to_value((
             JsonBytes::from_vec(addr.to_bytes()),
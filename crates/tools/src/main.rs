@@ -6,11 +6,14 @@ mod create_creator_account;
 mod deploy_genesis;
 mod deploy_scripts;
 mod deposit_ckb;
+mod devnet;
+mod diff_state;
 mod dump_tx;
 mod generate_config;
 mod get_balance;
 pub mod godwoken_rpc;
 mod hasher;
+mod onboard_accounts;
 mod polyjuice;
 mod prepare_scripts;
 mod report_accounts;
@@ -34,6 +37,8 @@ use anyhow::{anyhow, Result};
 use clap::{value_t, App, Arg, CommandFactory, FromArgMatches, SubCommand};
 use deploy_genesis::DeployRollupCellArgs;
 use deploy_scripts::{DeployScriptsCommand, DEPLOY_SCRIPTS_COMMAND};
+use devnet::{DevnetCommand, DEVNET_COMMAND};
+use diff_state::{DiffStateCommand, DIFF_STATE_COMMAND};
 use dump_tx::ChallengeBlock;
 use generate_config::{GenerateConfigCommand, GENERATE_CONFIG_COMMAND};
 use godwoken_rpc::GodwokenRpcClient;
@@ -41,6 +46,7 @@ use gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
 use gw_jsonrpc_types::godwoken::ChallengeTargetType;
 use gw_rpc_client::indexer_client::CkbIndexerClient;
 use gw_types::{offchain::CompatibleFinalizedTimepoint, prelude::*};
+use onboard_accounts::{OnboardAccountsCommand, ONBOARD_ACCOUNTS_COMMAND};
 use tracing_subscriber::prelude::*;
 
 use crate::{
@@ -135,6 +141,9 @@ async fn main() -> Result<()> {
                 ),
         )
         .subcommand(GenerateConfigCommand::command())
+        .subcommand(DevnetCommand::command())
+        .subcommand(DiffStateCommand::command())
+        .subcommand(OnboardAccountsCommand::command())
         .subcommand(
             SubCommand::with_name("prepare-scripts")
                 .about("Prepare scripts used by godwoken")
@@ -812,6 +821,15 @@ async fn main() -> Result<()> {
         Some((GENERATE_CONFIG_COMMAND, m)) => {
             GenerateConfigCommand::from_arg_matches(m)?.run().await?;
         }
+        Some((DEVNET_COMMAND, m)) => {
+            DevnetCommand::from_arg_matches(m)?.run().await?;
+        }
+        Some((DIFF_STATE_COMMAND, m)) => {
+            DiffStateCommand::from_arg_matches(m)?.run().await?;
+        }
+        Some((ONBOARD_ACCOUNTS_COMMAND, m)) => {
+            OnboardAccountsCommand::from_arg_matches(m)?.run().await?;
+        }
         Some(("prepare-scripts", m)) => {
             let mode = value_t!(m, "mode", prepare_scripts::ScriptsBuildMode).unwrap();
             let input_path = Path::new(m.value_of("input-path").unwrap());
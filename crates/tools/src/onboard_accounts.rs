@@ -0,0 +1,189 @@
+//! Onboard ETH accounts in batches, e.g. receivers of an airdrop.
+//!
+//! Accounts and their ETH registry mappings are created by the
+//! `BatchCreateEthAccounts` action of the Meta contract, one transaction per
+//! batch. Addresses which already have an account are skipped.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use ckb_jsonrpc_types::JsonBytes;
+use clap::Parser;
+use gw_common::{
+    builtins::{ETH_REGISTRY_ACCOUNT_ID, RESERVED_ACCOUNT_ID},
+    registry_address::RegistryAddress,
+};
+use gw_generator::account_lock_manage::eip712::{self, traits::EIP712Encode};
+use gw_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    packed::{
+        BatchCreateEthAccounts, Fee, L2Transaction, MetaContractArgs, RawL2Transaction, Script,
+        ScriptVec,
+    },
+    prelude::*,
+};
+
+use crate::{
+    account::{eth_sign, privkey_to_eth_address, privkey_to_l2_script_hash, read_privkey},
+    godwoken_rpc::GodwokenRpcClient,
+    types::ScriptsDeploymentResult,
+    utils::transaction::{read_config, wait_for_l2_tx},
+};
+
+pub const ONBOARD_ACCOUNTS_COMMAND: &str = "onboard-eth-accounts";
+
+/// Create accounts of ETH addresses in batches
+#[derive(Parser)]
+#[clap(name = ONBOARD_ACCOUNTS_COMMAND)]
+pub struct OnboardAccountsCommand {
+    /// The private key file path of the sender paying fees
+    #[clap(short = 'k', long)]
+    privkey_path: PathBuf,
+    /// The config.toml file path
+    #[clap(short = 'o', long)]
+    config_path: PathBuf,
+    /// The scripts deployment results json file path
+    #[clap(long)]
+    scripts_deployment_path: PathBuf,
+    /// Godwoken jsonrpc rpc sever URL
+    #[clap(short, long, default_value = "http://127.0.0.1:8119")]
+    godwoken_rpc_url: String,
+    /// File of ETH addresses to onboard, one hex address per line
+    #[clap(short, long)]
+    addresses_path: PathBuf,
+    /// Max accounts created by a transaction
+    #[clap(long, default_value = "50")]
+    batch_size: usize,
+    /// Fee of each transaction, in CKB
+    #[clap(short, long, default_value = "0")]
+    fee: u128,
+}
+
+impl OnboardAccountsCommand {
+    pub async fn run(self) -> Result<()> {
+        if self.batch_size == 0 {
+            bail!("batch size must be greater than 0");
+        }
+        let scripts_deployment: ScriptsDeploymentResult = {
+            let content = std::fs::read_to_string(&self.scripts_deployment_path)?;
+            serde_json::from_str(&content)?
+        };
+        let config = read_config(&self.config_path)?;
+        let consensus = config.consensus.get_config();
+        let rollup_type_hash = &consensus.genesis.rollup_type_hash;
+        let chain_id: u64 = consensus.genesis.rollup_config.chain_id.into();
+        let privkey = read_privkey(&self.privkey_path)?;
+        let eth_lock_code_hash: [u8; 32] = scripts_deployment
+            .eth_account_lock
+            .script_type_hash
+            .clone()
+            .into();
+
+        let mut rpc_client = GodwokenRpcClient::new(&self.godwoken_rpc_url);
+        let from_script_hash =
+            privkey_to_l2_script_hash(&privkey, rollup_type_hash, &scripts_deployment)?;
+        let from_id = rpc_client
+            .get_account_id_by_script_hash(from_script_hash)
+            .await?
+            .ok_or_else(|| anyhow!("account of the private key not found"))?;
+        let sender_address = RegistryAddress::new(
+            ETH_REGISTRY_ACCOUNT_ID,
+            privkey_to_eth_address(&privkey)?.to_vec(),
+        );
+        let meta_contract_script_hash: [u8; 32] = rpc_client
+            .get_script_hash(RESERVED_ACCOUNT_ID)
+            .await?
+            .into();
+
+        let mut scripts = Vec::new();
+        for address in read_addresses(&self.addresses_path)? {
+            let mut args = rollup_type_hash.as_bytes().to_vec();
+            args.extend_from_slice(&address);
+            let script = Script::new_builder()
+                .code_hash(eth_lock_code_hash.pack())
+                .hash_type(ScriptHashType::Type.into())
+                .args(Bytes::from(args).pack())
+                .build();
+            let script_hash: [u8; 32] = script.hash();
+            if let Some(id) = rpc_client
+                .get_account_id_by_script_hash(script_hash.into())
+                .await?
+            {
+                log::info!("skip 0x{}, account {} exists", hex::encode(address), id);
+                continue;
+            }
+            scripts.push(script);
+        }
+        log::info!("onboard {} accounts", scripts.len());
+
+        for batch in scripts.chunks(self.batch_size) {
+            let fee = Fee::new_builder()
+                .registry_id(ETH_REGISTRY_ACCOUNT_ID.pack())
+                .amount(self.fee.pack())
+                .build();
+            let onboard = BatchCreateEthAccounts::new_builder()
+                .scripts(ScriptVec::new_builder().set(batch.to_vec()).build())
+                .fee(fee)
+                .build();
+            let args = MetaContractArgs::new_builder().set(onboard).build();
+            let nonce = rpc_client.get_nonce(from_id).await?;
+            let raw_tx = RawL2Transaction::new_builder()
+                .chain_id(chain_id.pack())
+                .from_id(from_id.pack())
+                .to_id(RESERVED_ACCOUNT_ID.pack())
+                .nonce(nonce.pack())
+                .args(args.as_bytes().pack())
+                .build();
+
+            let message = {
+                let typed_tx = eip712::types::L2Transaction::from_raw(
+                    &raw_tx,
+                    sender_address.clone(),
+                    meta_contract_script_hash,
+                )?;
+                let domain_seperator = eip712::types::EIP712Domain {
+                    name: "Godwoken".to_string(),
+                    version: "1".to_string(),
+                    chain_id,
+                    verifying_contract: None,
+                    salt: None,
+                };
+                typed_tx.eip712_message(EIP712Encode::hash_struct(&domain_seperator))
+            };
+            let signature = eth_sign(&message.into(), privkey.clone())?;
+            let tx = L2Transaction::new_builder()
+                .raw(raw_tx)
+                .signature(signature.pack())
+                .build();
+
+            let tx_hash = rpc_client
+                .submit_l2transaction(JsonBytes::from_bytes(tx.as_bytes()))
+                .await?;
+            log::info!("onboard {} accounts, tx hash: {:#x}", batch.len(), tx_hash);
+            let receipt = wait_for_l2_tx(&mut rpc_client, &tx_hash, 180, true)
+                .await?
+                .ok_or_else(|| anyhow!("receipt of tx {:#x} not found", tx_hash))?;
+            let exit_code: u32 = receipt.exit_code.into();
+            if exit_code != 0 {
+                bail!("tx {:#x} failed, exit code: {}", tx_hash, exit_code as i8);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_addresses(path: &Path) -> Result<Vec<[u8; 20]>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("read {}", path.to_string_lossy()))?;
+    let lines = content.lines().map(str::trim).filter(|l| !l.is_empty());
+    lines
+        .map(|line| {
+            let hex_address = line.trim_start_matches("0x");
+            let mut address = [0u8; 20];
+            hex::decode_to_slice(hex_address, &mut address)
+                .with_context(|| format!("invalid ETH address {}", line))?;
+            Ok(address)
+        })
+        .collect()
+}
@@ -32,8 +32,8 @@ fn is_older_block_finalized(
     Ok(compatible_finalized_timepoint.is_finalized(&older_block_timepoint))
 }
 
-// Returns the highest block that is finalized for `block`.
-fn find_finalized_upper_bound(
+/// Returns the highest block that is finalized for `block`.
+pub fn find_finalized_upper_bound(
     rollup_config: &RollupConfig,
     fork_config: &ForkConfig,
     db: &impl ChainStore,
@@ -67,6 +67,12 @@ fn find_finalized_upper_bound(
     // NOTE: To ensure that at least one finalized block is found below, start a binary search at
     // `upgrade_global_state_version_to_v2 - 1`.
     l = l.saturating_sub(1);
+    // Blocks before the oldest one are not available in stores bootstrapped
+    // from a state snapshot. The oldest one is finalized for blocks after the
+    // snapshot block.
+    if let Some(oldest) = db.get_oldest_block_number() {
+        l = l.max(oldest);
+    }
     let mut r = block.raw().number().unpack().saturating_sub(1);
     while l < r {
         let mid = l + (r - l + 1) / 2;
@@ -1,10 +1,8 @@
-use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::io::{self, BufRead, ErrorKind, Read, Write};
 
 use anyhow::{anyhow, bail, Context, Result};
 use gw_smt::smt_h256_ext::SMTH256Ext;
-use gw_store::{
-    readonly::StoreReadonly, traits::chain_store::ChainStore, transaction::StoreTransaction,
-};
+use gw_store::{traits::chain_store::ChainStore, transaction::StoreTransaction};
 use gw_types::{
     bytes::Bytes,
     h256::*,
@@ -12,8 +10,33 @@ use gw_types::{
     packed::{self, GlobalState},
     prelude::*,
 };
+use zstd::stream::{read::Decoder, write::Encoder};
+
+/// Exported block files may be compressed as a whole by zstd. They are detected
+/// by the zstd frame magic number, which is far too large to be the size of an
+/// uncompressed block.
+const ZSTD_MAGIC: [u8; 4] = 0xFD2FB528u32.to_le_bytes();
+/// Same as block sync.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Compress exported blocks written to `writer`. Call `finish` on the returned
+/// encoder after all blocks are written.
+pub fn compress_writer<W: Write>(writer: W) -> Result<Encoder<'static, W>> {
+    Ok(Encoder::new(writer, COMPRESSION_LEVEL)?)
+}
 
-pub fn export_block(snap: &StoreReadonly, block_number: u64) -> Result<ExportedBlock> {
+/// Decompress exported blocks read from `reader` if they are compressed.
+pub fn decompress_reader<'a, R: BufRead + Send + 'a>(
+    mut reader: R,
+) -> Result<Box<dyn Read + Send + 'a>> {
+    if reader.fill_buf()?.starts_with(&ZSTD_MAGIC) {
+        Ok(Box::new(Decoder::with_buffer(reader)?))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+pub fn export_block(snap: &impl ChainStore, block_number: u64) -> Result<ExportedBlock> {
     let block_hash = snap
         .get_block_hash_by_number(block_number)?
         .ok_or_else(|| anyhow!("block {} not found", block_number))?;
@@ -118,20 +141,26 @@ pub fn read_block(reader: &mut impl Read) -> Result<Option<(ExportedBlock, usize
     Ok(Some((packed.into(), full_size)))
 }
 
-pub struct ExportedBlockReader<Reader: Read + Seek> {
+/// Reads exported blocks sequentially, so it also works on a decompression
+/// stream.
+pub struct ExportedBlockReader<Reader: Read> {
     inner: Reader,
+    peeked: Option<(ExportedBlock, usize)>,
 }
 
-impl<Reader: Read + Seek> ExportedBlockReader<Reader> {
+impl<Reader: Read> ExportedBlockReader<Reader> {
     pub fn new(reader: Reader) -> Self {
-        ExportedBlockReader { inner: reader }
+        ExportedBlockReader {
+            inner: reader,
+            peeked: None,
+        }
     }
 
-    pub fn peek_block(&mut self) -> Result<Option<(ExportedBlock, usize)>> {
-        let pos = self.inner.stream_position()?;
-        let block = read_block(&mut self.inner)?;
-        self.inner.seek(SeekFrom::Start(pos))?;
-        Ok(block)
+    pub fn peek_block(&mut self) -> Result<Option<(&ExportedBlock, usize)>> {
+        if self.peeked.is_none() {
+            self.peeked = read_block(&mut self.inner)?;
+        }
+        Ok(self.peeked.as_ref().map(|(block, size)| (block, *size)))
     }
 
     pub fn skip_blocks(&mut self, blocks: u64) -> Result<(u64, u64)> {
@@ -143,17 +172,22 @@ impl<Reader: Read + Seek> ExportedBlockReader<Reader> {
             None => return Ok((count, size)),
         };
 
-        while count < blocks {
-            let pos = self.inner.stream_position()?;
+        if blocks > 0 {
+            if let Some((_block, block_size)) = self.peeked.take() {
+                count += 1;
+                size += block_size as u64;
+            }
+        }
 
+        while count < blocks {
             let full_size = match read_block_size(&mut self.inner)? {
                 Some(size) => size,
                 None => return Ok((count, size)),
             };
-            let offset = full_size.saturating_sub(4);
+            let offset = full_size.saturating_sub(4) as u64;
 
-            let new_pos = self.inner.seek(SeekFrom::Current(offset as i64))?;
-            if new_pos.saturating_sub(pos) != full_size as u64 {
+            let skipped = io::copy(&mut (&mut self.inner).take(offset), &mut io::sink())?;
+            if skipped != offset {
                 bail!("block {} corrupted", from_block + count);
             }
 
@@ -165,10 +199,13 @@ impl<Reader: Read + Seek> ExportedBlockReader<Reader> {
     }
 }
 
-impl<Reader: Read + Seek> Iterator for ExportedBlockReader<Reader> {
+impl<Reader: Read> Iterator for ExportedBlockReader<Reader> {
     type Item = Result<(ExportedBlock, usize)>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(block) = self.peeked.take() {
+            return Some(Ok(block));
+        }
         read_block(&mut self.inner).transpose()
     }
 }
@@ -240,7 +277,10 @@ pub fn check_block_post_state(
     Ok(())
 }
 
-fn get_bad_block_hashes(snap: &StoreReadonly, block_number: u64) -> Result<Option<Vec<Vec<H256>>>> {
+fn get_bad_block_hashes(
+    snap: &impl ChainStore,
+    block_number: u64,
+) -> Result<Option<Vec<Vec<H256>>>> {
     let parent_reverted_block_root = {
         let parent_block_number = block_number.saturating_sub(1);
         get_block_reverted_block_root(snap, parent_block_number)?
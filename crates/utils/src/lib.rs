@@ -7,18 +7,22 @@ pub mod gasless;
 pub mod genesis_info;
 pub mod liveness;
 pub mod local_cells;
+pub mod maintenance;
+pub mod mem_block_signature;
 pub mod polyjuice_parser;
+pub mod production;
 mod query_rollup_cell;
 mod rollup_context;
 pub mod script_log;
 pub mod since;
+pub mod state_snapshot;
 pub mod timepoint;
 pub mod transaction_skeleton;
 pub mod type_id;
 pub mod wallet;
 pub mod withdrawal;
 
-pub use calc_finalizing_range::calc_finalizing_range;
+pub use calc_finalizing_range::{calc_finalizing_range, find_finalized_upper_bound};
 pub use gw_rpc_client::ExponentialBackoff;
 pub use query_rollup_cell::query_rollup_cell;
 pub use rollup_context::RollupContext;
@@ -19,7 +19,7 @@ use gw_types::{
 };
 
 /// Manage local dead / live cells.
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct LocalCellsManager {
     dead_cells: HashSet<OutPoint>,
     local_live_cells: HashMap<OutPoint, CellInfo>,
@@ -0,0 +1,77 @@
+//! Maintenance mode of a full node.
+//!
+//! Operators request maintenance mode by the admin RPC, the block producer
+//! then stops producing new blocks, waits for in-flight submissions to be
+//! confirmed, saves mem block and marks the mode active. Submissions of new
+//! txs and withdrawals are rejected until resumed.
+
+use tokio::sync::watch;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceState {
+    Running,
+    /// Maintenance is requested, waiting for in-flight submissions.
+    Entering,
+    /// Block producing is stopped and pending state is saved.
+    Active,
+}
+
+pub struct MaintenanceMode {
+    state: watch::Sender<MaintenanceState>,
+}
+
+impl Default for MaintenanceMode {
+    fn default() -> Self {
+        let (state, _) = watch::channel(MaintenanceState::Running);
+        Self { state }
+    }
+}
+
+impl MaintenanceMode {
+    pub fn state(&self) -> MaintenanceState {
+        *self.state.borrow()
+    }
+
+    /// Writes are fenced once maintenance mode is requested.
+    pub fn is_fenced(&self) -> bool {
+        self.state() != MaintenanceState::Running
+    }
+
+    /// Request entering maintenance mode. Returns false if it is already requested.
+    pub fn enter(&self) -> bool {
+        self.state.send_if_modified(|state| match state {
+            MaintenanceState::Running => {
+                *state = MaintenanceState::Entering;
+                true
+            }
+            _ => false,
+        })
+    }
+
+    /// Resume from maintenance mode. Returns false if it isn't requested.
+    pub fn resume(&self) -> bool {
+        self.state.send_if_modified(|state| match state {
+            MaintenanceState::Entering | MaintenanceState::Active => {
+                *state = MaintenanceState::Running;
+                true
+            }
+            MaintenanceState::Running => false,
+        })
+    }
+
+    /// Mark maintenance mode active, called by block producer after it has
+    /// stopped and saved pending state.
+    pub fn set_active(&self) -> bool {
+        self.state.send_if_modified(|state| match state {
+            MaintenanceState::Entering => {
+                *state = MaintenanceState::Active;
+                true
+            }
+            _ => false,
+        })
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<MaintenanceState> {
+        self.state.subscribe()
+    }
+}
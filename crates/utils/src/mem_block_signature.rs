@@ -0,0 +1,153 @@
+//! Signatures of block sync messages published by the block producer, so that
+//! readonly nodes don't have to trust the p2p peer relaying them.
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use gw_common::blake2b::new_blake2b;
+use gw_types::{
+    bytes::Bytes,
+    core::ScriptHashType,
+    h256::*,
+    packed::{
+        BlockSync, BlockSyncReader, BlockSyncUnion, NextMemBlock, NumberHash, Script,
+        SignedBlockSync,
+    },
+    prelude::*,
+};
+
+use crate::wallet::{recover_signer, Wallet, SIGHASH_TYPE_HASH};
+
+/// blake2b(rollup type hash | number hash | message). The rollup type hash
+/// prevents replaying signatures of another rollup with the same producer key,
+/// and the number hash binds the message to a block.
+pub fn block_sync_signing_message(
+    rollup_type_hash: &H256,
+    number_hash: &NumberHash,
+    message: &[u8],
+) -> H256 {
+    let mut hasher = new_blake2b();
+    hasher.update(rollup_type_hash.as_slice());
+    hasher.update(number_hash.as_slice());
+    hasher.update(message);
+    let mut signing_message = [0u8; 32];
+    hasher.finalize(&mut signing_message);
+    signing_message
+}
+
+/// Signs block sync messages with the block producer wallet.
+pub struct BlockSyncSigner {
+    wallet: Wallet,
+    rollup_type_hash: H256,
+}
+
+impl BlockSyncSigner {
+    pub fn new(wallet: Wallet, rollup_type_hash: H256) -> Self {
+        Self {
+            wallet,
+            rollup_type_hash,
+        }
+    }
+
+    /// Sign `message` bound to `number_hash`, see `SignedBlockSync`.
+    pub fn sign(&self, number_hash: NumberHash, message: BlockSync) -> Result<SignedBlockSync> {
+        let signing_message =
+            block_sync_signing_message(&self.rollup_type_hash, &number_hash, message.as_slice());
+        let signature = self.wallet.sign_message(signing_message)?;
+        Ok(SignedBlockSync::new_builder()
+            .number_hash(number_hash)
+            .message(message.as_bytes().pack())
+            .signature(Bytes::copy_from_slice(&signature).pack())
+            .build())
+    }
+}
+
+/// The message in `signed`, without checking the signature. Fails if the
+/// message isn't bound to the block it's signed with.
+pub fn signed_message(signed: &SignedBlockSync) -> Result<BlockSync> {
+    let message = signed.message().raw_data();
+    BlockSyncReader::from_slice(&message).context("invalid signed message")?;
+    let message = BlockSync::new_unchecked(message);
+    check_bound_block(&signed.number_hash(), &message)?;
+    Ok(message)
+}
+
+/// Check that `signed` is signed by `signer`, i.e. blake160 of the block
+/// producer's public key, and return the signed message.
+pub fn verify_block_sync(
+    signed: &SignedBlockSync,
+    rollup_type_hash: &H256,
+    signer: &[u8; 20],
+) -> Result<BlockSync> {
+    let signing_message = block_sync_signing_message(
+        rollup_type_hash,
+        &signed.number_hash(),
+        &signed.message().raw_data(),
+    );
+    let recovered = recover_signer(signing_message, &signed.signature().raw_data())?;
+    ensure!(
+        &recovered == signer,
+        "block sync message is signed by 0x{}, not the block producer",
+        faster_hex::hex_string(&recovered)
+    );
+    signed_message(signed)
+}
+
+/// Blocks are bound to themselves, mem blocks, their snapshots, deltas and txs
+/// to the parent block of the mem block.
+fn check_bound_block(number_hash: &NumberHash, message: &BlockSync) -> Result<()> {
+    let number: u64 = number_hash.number().unpack();
+    let bound = |other: NumberHash| {
+        ensure!(
+            other.as_slice() == number_hash.as_slice(),
+            "message is not bound to its block"
+        );
+        Ok(())
+    };
+    match message.to_enum() {
+        BlockSyncUnion::LocalBlock(l) => {
+            let raw = l.block().raw();
+            bound(
+                NumberHash::new_builder()
+                    .number(raw.number())
+                    .block_hash(raw.hash().pack())
+                    .build(),
+            )
+        }
+        BlockSyncUnion::Submitted(s) => bound(s.number_hash()),
+        BlockSyncUnion::Confirmed(c) => bound(c.number_hash()),
+        BlockSyncUnion::Revert(r) => bound(r.number_hash()),
+        BlockSyncUnion::NextMemBlock(m) => check_bound_mem_block(number, &m),
+        BlockSyncUnion::MemBlockSnapshot(s) => check_bound_mem_block(number, &s.mem_block()),
+        BlockSyncUnion::MemBlockDelta(d) => {
+            check_bound_mem_block_number(number, d.block_number().unpack())
+        }
+        BlockSyncUnion::PushTransaction(_) => Ok(()),
+        BlockSyncUnion::SignedBlockSync(_) => bail!("nested signed message"),
+    }
+}
+
+fn check_bound_mem_block(parent_number: u64, mem_block: &NextMemBlock) -> Result<()> {
+    check_bound_mem_block_number(parent_number, mem_block.block_info().number().unpack())
+}
+
+fn check_bound_mem_block_number(parent_number: u64, mem_block_number: u64) -> Result<()> {
+    ensure!(
+        Some(mem_block_number) == parent_number.checked_add(1),
+        "mem block {} is not bound to its parent block",
+        mem_block_number
+    );
+    Ok(())
+}
+
+/// Signer of a secp256k1 sighash lock, e.g. the block producer wallet's lock.
+pub fn sighash_lock_signer(lock: &Script) -> Result<[u8; 20]> {
+    ensure!(
+        lock.code_hash().as_slice() == SIGHASH_TYPE_HASH.as_slice()
+            && lock.hash_type() == ScriptHashType::Type.into(),
+        "not a secp256k1 sighash lock"
+    );
+    lock.args()
+        .raw_data()
+        .as_ref()
+        .try_into()
+        .map_err(|_| anyhow!("invalid sighash lock args"))
+}
@@ -0,0 +1,83 @@
+//! Pausing block production of a full node.
+//!
+//! Operators pause block production by the admin RPC, e.g. for upgrades. The
+//! block producer packages the in-flight mem block into a final block, then
+//! stops producing new blocks. Submitted blocks are still confirmed, L1 is
+//! still synced and txs are still accepted into mem pool, until resumed.
+
+use tokio::sync::watch;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProductionState {
+    Running,
+    /// Pause is requested, waiting for the in-flight mem block to be produced.
+    Pausing,
+    /// No new blocks are produced.
+    Paused,
+}
+
+impl ProductionState {
+    /// Value of the `block_producer_production_state` gauge.
+    pub fn as_metric(&self) -> u64 {
+        match self {
+            ProductionState::Running => 0,
+            ProductionState::Pausing => 1,
+            ProductionState::Paused => 2,
+        }
+    }
+}
+
+pub struct ProductionControl {
+    state: watch::Sender<ProductionState>,
+}
+
+impl Default for ProductionControl {
+    fn default() -> Self {
+        let (state, _) = watch::channel(ProductionState::Running);
+        Self { state }
+    }
+}
+
+impl ProductionControl {
+    pub fn state(&self) -> ProductionState {
+        *self.state.borrow()
+    }
+
+    /// Request pausing block production. Returns false if it is already requested.
+    pub fn pause(&self) -> bool {
+        self.state.send_if_modified(|state| match state {
+            ProductionState::Running => {
+                *state = ProductionState::Pausing;
+                true
+            }
+            _ => false,
+        })
+    }
+
+    /// Resume block production. Returns false if it isn't paused.
+    pub fn resume(&self) -> bool {
+        self.state.send_if_modified(|state| match state {
+            ProductionState::Pausing | ProductionState::Paused => {
+                *state = ProductionState::Running;
+                true
+            }
+            ProductionState::Running => false,
+        })
+    }
+
+    /// Mark block production paused, called by block producer after it has
+    /// produced the in-flight mem block.
+    pub fn set_paused(&self) -> bool {
+        self.state.send_if_modified(|state| match state {
+            ProductionState::Pausing => {
+                *state = ProductionState::Paused;
+                true
+            }
+            _ => false,
+        })
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<ProductionState> {
+        self.state.subscribe()
+    }
+}
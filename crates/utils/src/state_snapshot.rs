@@ -0,0 +1,622 @@
+//! State snapshots, to bootstrap a node from a trusted snapshot instead of
+//! replaying all blocks from genesis.
+//!
+//! A snapshot is taken at the last valid tip of the exporter, which must have
+//! been submitted to L1. It contains the account state, scripts and data,
+//! hashes of all blocks, and full content of recent blocks which are needed
+//! to calculate finalizing ranges of following blocks. Tx receipts and state
+//! history before the snapshot block are not included.
+//!
+//! A snapshot is a sequence of `StateSnapshotEntry`s: a header, chunks of
+//! state, recent blocks, then a trailer with the exporter's signature of all
+//! previous entries. Snapshots streamed to readonly nodes over p2p are not
+//! signed, the receiver checks them on L1 instead.
+
+use std::{
+    collections::HashSet,
+    io::{Read, Write},
+};
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use gw_common::blake2b::{self, new_blake2b, Blake2b};
+use gw_config::ForkConfig;
+use gw_jsonrpc_types::ckb_jsonrpc_types::Status;
+use gw_rpc_client::ckb_client::CkbClient;
+use gw_smt::smt_h256_ext::SMTH256Ext;
+use gw_store::{
+    autorocks::Direction,
+    schema::{
+        COLUMN_ACCOUNT_SMT_LEAF, COLUMN_ASSET_SCRIPT, COLUMN_DATA, COLUMN_REVERTED_BLOCK_SMT_LEAF,
+        COLUMN_SCRIPT,
+    },
+    state::history::history_state::HistoryStateStore,
+    traits::{
+        chain_store::ChainStore,
+        kv_store::{KVStoreIter, KVStoreWrite},
+    },
+    transaction::StoreTransaction,
+    Store,
+};
+use gw_types::{
+    bytes::Bytes,
+    h256::*,
+    offchain::{global_state_from_slice, ExportedBlock},
+    packed::{
+        self, KVPair, NumberHash, RollupConfig, Script, StateSnapshotChunk, StateSnapshotEntry,
+        StateSnapshotEntryReader, StateSnapshotHeader, StateSnapshotRecordUnion,
+        StateSnapshotTrailer,
+    },
+    prelude::*,
+};
+
+use crate::{
+    export_block::{check_block_post_state, export_block, read_block_size},
+    find_finalized_upper_bound,
+    wallet::{recover_signer, Wallet},
+};
+
+/// Maximum number of items in a chunk.
+const CHUNK_SIZE: usize = 10_000;
+/// Maximum bytes of items in a chunk, so that a chunk fits in a p2p message.
+const CHUNK_BYTES: usize = 1024 * 1024;
+
+/// Export a snapshot at the last valid tip of `snap`, signed by `wallet`.
+pub fn export_snapshot(
+    snap: &(impl ChainStore + KVStoreIter),
+    rollup_config: &RollupConfig,
+    fork_config: &ForkConfig,
+    wallet: &Wallet,
+    mut writer: impl Write,
+) -> Result<StateSnapshotHeader> {
+    let header = snapshot_header(snap, rollup_config, fork_config)?;
+    export_entries(snap, &header, Some(wallet), |entry| {
+        writer.write_all(entry.as_slice())?;
+        Ok(())
+    })?;
+    writer.flush()?;
+    Ok(header)
+}
+
+/// Export an unsigned snapshot with `header` entry by entry to `sink`, e.g.
+/// to stream it to a peer. `header` must be taken from `snap` with
+/// [`snapshot_header`].
+pub fn export_snapshot_entries(
+    snap: &(impl ChainStore + KVStoreIter),
+    header: &StateSnapshotHeader,
+    sink: impl FnMut(StateSnapshotEntry) -> Result<()>,
+) -> Result<()> {
+    export_entries(snap, header, None, sink)
+}
+
+/// Header of a snapshot at the last valid tip of `snap`. Fails if the tip
+/// block is not submitted to L1 yet.
+pub fn snapshot_header(
+    snap: &impl ChainStore,
+    rollup_config: &RollupConfig,
+    fork_config: &ForkConfig,
+) -> Result<StateSnapshotHeader> {
+    let block = snap.get_last_valid_tip_block()?;
+    let block_number: u64 = block.raw().number().unpack();
+    let block_hash = block.hash();
+    let post_global_state = snap
+        .get_block_post_global_state(&block_hash)?
+        .ok_or_else(|| anyhow!("block {} post global state not found", block_number))?;
+    let submit_tx_hash = snap
+        .get_block_submit_tx_hash(block_number)
+        .ok_or_else(|| anyhow!("block {} is not submitted", block_number))?;
+    let post_finalized_custodians = snap
+        .get_block_post_finalized_custodian_capacity(block_number)
+        .ok_or_else(|| anyhow!("block {} post finalized custodians not found", block_number))?;
+    // Blocks after the last finalized one are needed to calculate finalizing
+    // ranges of following blocks.
+    let oldest_block_number = find_finalized_upper_bound(rollup_config, fork_config, snap, &block)?;
+
+    let header = StateSnapshotHeader::new_builder()
+        .rollup_type_hash(snap.get_chain_id()?.pack())
+        .block_number(block_number.pack())
+        .block_hash(block_hash.pack())
+        .post_global_state(post_global_state)
+        .submit_tx_hash(submit_tx_hash.pack())
+        .post_finalized_custodians(post_finalized_custodians)
+        .oldest_block_number(oldest_block_number.pack())
+        .build();
+    Ok(header)
+}
+
+fn export_entries(
+    snap: &(impl ChainStore + KVStoreIter),
+    header: &StateSnapshotHeader,
+    wallet: Option<&Wallet>,
+    sink: impl FnMut(StateSnapshotEntry) -> Result<()>,
+) -> Result<()> {
+    let block_number: u64 = header.block_number().unpack();
+    let oldest_block_number: u64 = header.oldest_block_number().unpack();
+    let mut writer = EntryWriter::new(sink);
+    writer.write(header.clone())?;
+
+    for number in 0..oldest_block_number {
+        let hash = snap
+            .get_block_hash_by_number(number)?
+            .ok_or_else(|| anyhow!("block {} not found", number))?;
+        writer.push(ChunkItem::BlockHash(hash))?;
+    }
+    for (k, v) in snap.iter(COLUMN_ACCOUNT_SMT_LEAF, Direction::Forward) {
+        writer.push(ChunkItem::AccountSmtLeaf(
+            KVPair::new_builder()
+                .k(to_h256(&k)?.pack())
+                .v(to_h256(&v)?.pack())
+                .build(),
+        ))?;
+    }
+    for (_, v) in snap.iter(COLUMN_SCRIPT, Direction::Forward) {
+        writer.push(ChunkItem::Script(Script::from_slice(&v)?))?;
+    }
+    for (_, v) in snap.iter(COLUMN_DATA, Direction::Forward) {
+        writer.push(ChunkItem::Data(Bytes::from(v.into_vec())))?;
+    }
+    for (_, v) in snap.iter(COLUMN_ASSET_SCRIPT, Direction::Forward) {
+        writer.push(ChunkItem::AssetScript(Script::from_slice(&v)?))?;
+    }
+    for (k, v) in snap.iter(COLUMN_REVERTED_BLOCK_SMT_LEAF, Direction::Forward) {
+        if !to_h256(&v)?.is_zero() {
+            writer.push(ChunkItem::RevertedBlockHash(to_h256(&k)?))?;
+        }
+    }
+    writer.flush_chunk()?;
+
+    for number in oldest_block_number..=block_number {
+        let exported_block: packed::ExportedBlock = export_block(snap, number)?.into();
+        writer.write(exported_block)?;
+    }
+
+    writer.finish(wallet)
+}
+
+/// Check that a snapshot is complete and signed by `trusted_signer`, i.e.
+/// blake160 of the signer's public key. Returns the header of the snapshot.
+pub fn verify_snapshot(
+    mut reader: impl Read,
+    trusted_signer: &[u8; 20],
+) -> Result<StateSnapshotHeader> {
+    let mut hasher = new_blake2b();
+    let mut header = None;
+    while let Some(entry) = read_entry(&mut reader)? {
+        match entry.record().to_enum() {
+            StateSnapshotRecordUnion::StateSnapshotHeader(h) if header.is_none() => {
+                header = Some(h);
+            }
+            _ if header.is_none() => bail!("snapshot header is not the first entry"),
+            StateSnapshotRecordUnion::StateSnapshotHeader(_) => bail!("duplicate snapshot header"),
+            StateSnapshotRecordUnion::StateSnapshotTrailer(trailer) => {
+                ensure!(
+                    read_entry(&mut reader)?.is_none(),
+                    "unexpected entries after snapshot trailer"
+                );
+                let mut content_hash = [0u8; 32];
+                hasher.finalize(&mut content_hash);
+                let expected_content_hash: H256 = trailer.content_hash().unpack();
+                ensure!(
+                    content_hash == expected_content_hash,
+                    "snapshot content hash mismatch"
+                );
+                let signer = recover_signer(content_hash, &trailer.signature().raw_data())?;
+                ensure!(
+                    &signer == trusted_signer,
+                    "snapshot is signed by 0x{}, not the trusted signer",
+                    faster_hex::hex_string(&signer)
+                );
+                return header.context("snapshot header");
+            }
+            _ => {}
+        }
+        hasher.update(entry.as_slice());
+    }
+    bail!("snapshot trailer not found")
+}
+
+/// Check that the submission tx of the snapshot block is committed on L1, and
+/// its rollup cell has the same global state as the snapshot.
+pub async fn check_snapshot_on_chain(
+    ckb_client: &CkbClient,
+    header: &StateSnapshotHeader,
+) -> Result<()> {
+    let tx_hash: H256 = header.submit_tx_hash().unpack();
+    match ckb_client.get_transaction_status(tx_hash).await? {
+        Some(Status::Committed) => {}
+        status => bail!(
+            "submission tx {} of the snapshot block is not committed, status: {:?}",
+            header.submit_tx_hash(),
+            status
+        ),
+    }
+    let tx = ckb_client
+        .get_packed_transaction(tx_hash)
+        .await?
+        .ok_or_else(|| anyhow!("submission tx {} not found", header.submit_tx_hash()))?;
+
+    let rollup_type_hash: H256 = header.rollup_type_hash().unpack();
+    let (i, _) = tx
+        .raw()
+        .outputs()
+        .into_iter()
+        .enumerate()
+        .find(|(_i, output)| {
+            output.type_().to_opt().map(|type_| type_.hash()) == Some(rollup_type_hash)
+        })
+        .ok_or_else(|| anyhow!("no rollup cell found"))?;
+    let data = tx
+        .raw()
+        .outputs_data()
+        .get(i)
+        .ok_or_else(|| anyhow!("no rollup cell data"))?;
+    let global_state = global_state_from_slice(&data.raw_data())?;
+    ensure!(
+        global_state.as_slice() == header.post_global_state().as_slice(),
+        "global state of the snapshot block differs from L1"
+    );
+
+    Ok(())
+}
+
+/// Import a snapshot into an empty store. The snapshot should be checked with
+/// [`verify_snapshot`] and [`check_snapshot_on_chain`] first.
+///
+/// Entries are committed one by one, the store should be removed if it fails.
+pub fn import_snapshot(store: &Store, mut reader: impl Read) -> Result<StateSnapshotHeader> {
+    let mut importer = SnapshotImporter::new(store)?;
+    while let Some(entry) = read_entry(&mut reader)? {
+        if let Some(header) = importer.push(entry)? {
+            return Ok(header);
+        }
+    }
+    bail!("snapshot trailer not found")
+}
+
+/// Imports snapshot entries into an empty store as they arrive. The content
+/// hash and the state are checked when the trailer arrives, the signature is
+/// not checked.
+///
+/// Entries are committed one by one, the store should be removed if it fails.
+pub struct SnapshotImporter<'a> {
+    store: &'a Store,
+    hasher: Blake2b,
+    header: Option<StateSnapshotHeader>,
+    next_block_hash_number: u64,
+    next_block_number: u64,
+    finished: bool,
+}
+
+impl<'a> SnapshotImporter<'a> {
+    pub fn new(store: &'a Store) -> Result<Self> {
+        ensure!(!store.has_genesis()?, "store is not empty");
+        Ok(Self {
+            store,
+            hasher: new_blake2b(),
+            header: None,
+            next_block_hash_number: 0,
+            next_block_number: 0,
+            finished: false,
+        })
+    }
+
+    /// Import the next entry. Returns the header after the trailer is
+    /// imported and checked.
+    pub fn push(&mut self, entry: StateSnapshotEntry) -> Result<Option<StateSnapshotHeader>> {
+        ensure!(!self.finished, "unexpected entries after snapshot trailer");
+        let header = match self.header {
+            Some(ref header) => header.clone(),
+            None => {
+                let header = match entry.record().to_enum() {
+                    StateSnapshotRecordUnion::StateSnapshotHeader(header) => header,
+                    _ => bail!("snapshot header is not the first entry"),
+                };
+                let mut db = self.store.begin_transaction();
+                db.setup_chain_id(header.rollup_type_hash().unpack())?;
+                db.commit()?;
+                self.next_block_number = header.oldest_block_number().unpack();
+                self.hasher.update(entry.as_slice());
+                self.header = Some(header);
+                return Ok(None);
+            }
+        };
+
+        let mut db = self.store.begin_transaction();
+        match entry.record().to_enum() {
+            StateSnapshotRecordUnion::StateSnapshotChunk(chunk) => {
+                import_chunk(
+                    &mut db,
+                    header.block_number().unpack(),
+                    &mut self.next_block_hash_number,
+                    chunk,
+                )?;
+            }
+            StateSnapshotRecordUnion::ExportedBlock(block) => {
+                let block: ExportedBlock = block.into();
+                ensure!(
+                    block.block_number() == self.next_block_number,
+                    "expect block {}, got {}",
+                    self.next_block_number,
+                    block.block_number()
+                );
+                import_block(&mut db, block)?;
+                self.next_block_number += 1;
+            }
+            StateSnapshotRecordUnion::StateSnapshotTrailer(trailer) => {
+                let mut content_hash = [0u8; 32];
+                let hasher = std::mem::replace(&mut self.hasher, new_blake2b());
+                hasher.finalize(&mut content_hash);
+                let expected_content_hash: H256 = trailer.content_hash().unpack();
+                ensure!(
+                    content_hash == expected_content_hash,
+                    "snapshot content hash mismatch"
+                );
+                self.finish(&header)?;
+                return Ok(Some(header));
+            }
+            StateSnapshotRecordUnion::StateSnapshotHeader(_) => {
+                bail!("duplicate snapshot header")
+            }
+        }
+        db.commit()?;
+        self.hasher.update(entry.as_slice());
+        Ok(None)
+    }
+
+    fn finish(&mut self, header: &StateSnapshotHeader) -> Result<()> {
+        let block_number: u64 = header.block_number().unpack();
+        let oldest_block_number: u64 = header.oldest_block_number().unpack();
+        ensure!(
+            self.next_block_hash_number == oldest_block_number,
+            "expect hashes of blocks before {}, got {}",
+            oldest_block_number,
+            self.next_block_hash_number
+        );
+        ensure!(
+            self.next_block_number == block_number + 1,
+            "expect blocks up to {}, got {}",
+            block_number,
+            self.next_block_number.saturating_sub(1)
+        );
+
+        let mut db = self.store.begin_transaction();
+        let post_global_state = header.post_global_state();
+        check_block_post_state(&db, block_number, &post_global_state)?;
+        let account_root: H256 = (*db.state_smt()?.root()).into();
+        ensure!(
+            account_root == post_global_state.account().merkle_root().unpack(),
+            "account smt root diff"
+        );
+
+        db.set_block_post_finalized_custodian_capacity(
+            block_number,
+            &header.post_finalized_custodians().as_reader(),
+        )?;
+        let number_hash = NumberHash::new_builder()
+            .number(block_number.pack())
+            .block_hash(header.block_hash())
+            .build();
+        db.set_last_confirmed_block_number_hash(&number_hash.as_reader())?;
+        db.set_last_submitted_block_number_hash(&number_hash.as_reader())?;
+        db.set_snapshot_block_numbers(oldest_block_number, block_number)?;
+        db.commit()?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+fn import_chunk(
+    db: &mut StoreTransaction,
+    block_number: u64,
+    next_block_hash_number: &mut u64,
+    chunk: StateSnapshotChunk,
+) -> Result<()> {
+    let leaves: Vec<(H256, H256)> = chunk
+        .account_smt_leaves()
+        .into_iter()
+        .map(|kv| (kv.k().unpack(), kv.v().unpack()))
+        .collect();
+    let mut state_smt = db.state_smt()?;
+    for (k, v) in leaves.iter() {
+        state_smt
+            .update((*k).into(), (*v).into())
+            .context("update state smt")?;
+    }
+    // Record state of the snapshot block, so it can be read at the block.
+    for (k, v) in leaves {
+        db.record_block_state(block_number, k, v)?;
+    }
+
+    for script in chunk.scripts() {
+        db.insert_raw(COLUMN_SCRIPT, &script.hash(), script.as_slice())?;
+    }
+    for data in chunk.data() {
+        let data = data.raw_data();
+        db.insert_raw(COLUMN_DATA, &blake2b::hash(&data), &data)?;
+    }
+    db.insert_asset_scripts(chunk.asset_scripts().into_iter().collect())?;
+
+    for block_hash in chunk.block_hashes() {
+        db.insert_block_hash(*next_block_hash_number, &block_hash.unpack())?;
+        *next_block_hash_number += 1;
+    }
+
+    if !chunk.reverted_block_hashes().is_empty() {
+        let mut reverted_block_smt = db.reverted_block_smt()?;
+        for block_hash in chunk.reverted_block_hashes() {
+            let block_hash: H256 = block_hash.unpack();
+            reverted_block_smt
+                .update(block_hash.into(), SMTH256Ext::one())
+                .context("update reverted block smt")?;
+        }
+        let root = *reverted_block_smt.root();
+        db.set_reverted_block_smt_root(root.into())?;
+    }
+
+    Ok(())
+}
+
+fn import_block(db: &mut StoreTransaction, block: ExportedBlock) -> Result<()> {
+    let block_number = block.block_number();
+    db.insert_block_without_receipts(
+        block.block.clone(),
+        block.post_global_state,
+        block.deposit_info_vec,
+        block.withdrawals,
+    )?;
+    db.insert_asset_scripts(
+        block
+            .deposit_asset_scripts
+            .into_iter()
+            .collect::<HashSet<_>>(),
+    )?;
+    if let Some(submit_tx_hash) = block.submit_tx_hash {
+        db.set_block_submit_tx_hash(block_number, &submit_tx_hash)?;
+    }
+    db.attach_block(block.block)
+}
+
+fn read_entry(reader: &mut impl Read) -> Result<Option<StateSnapshotEntry>> {
+    let full_size = match read_block_size(reader)? {
+        Some(size) => size as usize,
+        None => return Ok(None),
+    };
+    if full_size <= 4 {
+        bail!("snapshot corrupted, entry size {}", full_size);
+    }
+
+    let mut buf = vec![0; full_size];
+    buf[..4].copy_from_slice(&(full_size as u32).to_le_bytes());
+    reader.read_exact(&mut buf[4..])?;
+
+    StateSnapshotEntryReader::verify(&buf, false)?;
+    Ok(Some(StateSnapshotEntry::new_unchecked(Bytes::from(buf))))
+}
+
+fn to_h256(slice: &[u8]) -> Result<H256> {
+    slice.try_into().map_err(|_| anyhow!("invalid h256"))
+}
+
+enum ChunkItem {
+    AccountSmtLeaf(KVPair),
+    Script(Script),
+    Data(Bytes),
+    AssetScript(Script),
+    BlockHash(H256),
+    RevertedBlockHash(H256),
+}
+
+impl ChunkItem {
+    /// Approximate size in a chunk.
+    fn size(&self) -> usize {
+        match self {
+            ChunkItem::AccountSmtLeaf(_) => 64,
+            ChunkItem::Script(script) | ChunkItem::AssetScript(script) => script.total_size(),
+            ChunkItem::Data(data) => data.len(),
+            ChunkItem::BlockHash(_) | ChunkItem::RevertedBlockHash(_) => 32,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ChunkBuilder {
+    account_smt_leaves: Vec<KVPair>,
+    scripts: Vec<Script>,
+    data: Vec<packed::Bytes>,
+    asset_scripts: Vec<Script>,
+    block_hashes: Vec<H256>,
+    reverted_block_hashes: Vec<H256>,
+    len: usize,
+    bytes: usize,
+}
+
+impl ChunkBuilder {
+    fn push(&mut self, item: ChunkItem) {
+        self.bytes += item.size();
+        match item {
+            ChunkItem::AccountSmtLeaf(kv) => self.account_smt_leaves.push(kv),
+            ChunkItem::Script(script) => self.scripts.push(script),
+            ChunkItem::Data(data) => self.data.push(data.pack()),
+            ChunkItem::AssetScript(script) => self.asset_scripts.push(script),
+            ChunkItem::BlockHash(hash) => self.block_hashes.push(hash),
+            ChunkItem::RevertedBlockHash(hash) => self.reverted_block_hashes.push(hash),
+        }
+        self.len += 1;
+    }
+
+    fn build(self) -> StateSnapshotChunk {
+        StateSnapshotChunk::new_builder()
+            .account_smt_leaves(
+                packed::KVPairVec::new_builder()
+                    .set(self.account_smt_leaves)
+                    .build(),
+            )
+            .scripts(packed::ScriptVec::new_builder().set(self.scripts).build())
+            .data(packed::BytesVec::new_builder().set(self.data).build())
+            .asset_scripts(
+                packed::ScriptVec::new_builder()
+                    .set(self.asset_scripts)
+                    .build(),
+            )
+            .block_hashes(self.block_hashes.pack())
+            .reverted_block_hashes(self.reverted_block_hashes.pack())
+            .build()
+    }
+}
+
+/// Writes entries and hashes them for the trailer.
+struct EntryWriter<F> {
+    sink: F,
+    hasher: Blake2b,
+    chunk: ChunkBuilder,
+}
+
+impl<F: FnMut(StateSnapshotEntry) -> Result<()>> EntryWriter<F> {
+    fn new(sink: F) -> Self {
+        Self {
+            sink,
+            hasher: new_blake2b(),
+            chunk: ChunkBuilder::default(),
+        }
+    }
+
+    fn write(&mut self, record: impl Into<StateSnapshotRecordUnion>) -> Result<()> {
+        let record = packed::StateSnapshotRecord::new_builder()
+            .set(record)
+            .build();
+        let entry = StateSnapshotEntry::new_builder().record(record).build();
+        self.hasher.update(entry.as_slice());
+        (self.sink)(entry)
+    }
+
+    fn push(&mut self, item: ChunkItem) -> Result<()> {
+        self.chunk.push(item);
+        if self.chunk.len >= CHUNK_SIZE || self.chunk.bytes >= CHUNK_BYTES {
+            self.flush_chunk()?;
+        }
+        Ok(())
+    }
+
+    fn flush_chunk(&mut self) -> Result<()> {
+        if self.chunk.len > 0 {
+            let chunk = std::mem::take(&mut self.chunk).build();
+            self.write(chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Write the trailer, signed by `wallet` if any.
+    fn finish(mut self, wallet: Option<&Wallet>) -> Result<()> {
+        let mut content_hash = [0u8; 32];
+        let hasher = std::mem::replace(&mut self.hasher, new_blake2b());
+        hasher.finalize(&mut content_hash);
+        let signature = match wallet {
+            Some(wallet) => Bytes::copy_from_slice(&wallet.sign_message(content_hash)?),
+            None => Bytes::new(),
+        };
+        let trailer = StateSnapshotTrailer::new_builder()
+            .content_hash(content_hash.pack())
+            .signature(signature.pack())
+            .build();
+        self.write(trailer)
+    }
+}
@@ -92,6 +92,16 @@ impl Wallet {
     }
 
     pub fn sign_tx_skeleton(&self, tx_skeleton: TransactionSkeleton) -> Result<Transaction> {
+        self.sign_tx_skeleton_with(tx_skeleton, &[])
+    }
+
+    /// Sign inputs locked by `co_signers` with their keys, and other inputs
+    /// with this wallet.
+    pub fn sign_tx_skeleton_with(
+        &self,
+        tx_skeleton: TransactionSkeleton,
+        co_signers: &[&Wallet],
+    ) -> Result<Transaction> {
         let signature_entries = tx_skeleton.signature_entries();
         let dummy_signatures: Vec<_> = {
             let entries = signature_entries.iter();
@@ -137,7 +147,12 @@ impl Wallet {
             let mut message = [0u8; 32];
             hasher.finalize(&mut message);
             // sign tx
-            let signature = Signature::new(entry.kind, self.sign_message(message)?);
+            let signer = co_signers
+                .iter()
+                .find(|w| w.lock.hash() == entry.lock_hash)
+                .copied()
+                .unwrap_or(self);
+            let signature = Signature::new(entry.kind, signer.sign_message(message)?);
             signatures.push(signature.as_bytes());
         }
         // seal
@@ -148,6 +163,19 @@ impl Wallet {
     }
 }
 
+/// Blake160 of the public key which signed `message`, i.e. the args of its
+/// sighash lock.
+pub fn recover_signer(message: H256, signature: &[u8]) -> Result<[u8; 20]> {
+    let signature = ckb_crypto::secp::Signature::from_slice(signature)
+        .map_err(|err| anyhow!("invalid signature: {}", err))?;
+    let pubkey = signature
+        .recover(&message.into())
+        .map_err(|err| anyhow!("invalid signature: {}", err))?;
+    let mut signer = [0u8; 20];
+    signer.copy_from_slice(&blake2b::hash(&pubkey.serialize())[..20]);
+    Ok(signer)
+}
+
 pub fn privkey_to_eth_account_script(
     privkey: &Privkey,
     rollup_script_hash: &H256,
@@ -478,6 +478,7 @@ impl Config {
         let backends = BackendForkConfig {
             fork_height: 0,
             sudt_proxy: None,
+            batch_create_eth_accounts: None,
             backends: vec![
                 {
                     let path: PathBuf = [base_path, META_GENERATOR_PATH].iter().collect();
@@ -443,6 +443,7 @@ pub fn setup() -> (Store, DummyState, Generator) {
     let fork_configs = vec![BackendForkConfig {
         fork_height: 0,
         sudt_proxy: None,
+        batch_create_eth_accounts: None,
         backends: vec![
             BackendConfig {
                 backend_type: BackendType::Meta,
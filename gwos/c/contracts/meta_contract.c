@@ -45,6 +45,42 @@ int handle_fee(gw_context_t *ctx, uint32_t registry_id, uint256_t amount) {
   return 0;
 }
 
+/* Create accounts of the scripts and register their ETH addresses one by one,
+ * account_id is set to the last created account */
+int create_eth_accounts(gw_context_t *ctx, mol_seg_t *scripts_seg,
+                        uint32_t *account_id) {
+  uint32_t scripts_size = MolReader_ScriptVec_length(scripts_seg);
+  uint8_t account_script_hash[32] = {0};
+  for (uint32_t i = 0; i < scripts_size; i++) {
+    mol_seg_res_t script_res = MolReader_ScriptVec_get(scripts_seg, i);
+    if (script_res.errno != MOL_OK) {
+      ckb_debug("invalid account script");
+      return GW_FATAL_INVALID_DATA;
+    }
+
+    int ret = ctx->sys_create(ctx, script_res.seg.ptr, script_res.seg.size,
+                              account_id);
+    if (ret != 0) {
+      ckb_debug("failed to create eth account");
+      return ret;
+    }
+
+    ret = ctx->sys_get_script_hash_by_account_id(ctx, *account_id,
+                                                 account_script_hash);
+    if (ret != 0) {
+      ckb_debug("failed to get created eth account script hash");
+      return ret;
+    }
+
+    ret = gw_register_eth_address(ctx, account_script_hash);
+    if (ret != 0) {
+      ckb_debug("failed to register eth address");
+      return ret;
+    }
+  }
+  return 0;
+}
+
 int main() {
   /* initialize context */
   gw_context_t ctx = {0};
@@ -124,36 +160,20 @@ int main() {
     /* create accounts */
     mol_seg_t scripts_seg =
         MolReader_BatchCreateEthAccounts_get_scripts(&msg.seg);
-    uint32_t scripts_size = MolReader_ScriptVec_length(&scripts_seg);
-
     uint32_t account_id = 0;
-    uint8_t account_script_hash[32] = {0};
-    for (uint32_t i = 0; i < scripts_size; i++) {
-      mol_seg_res_t script_res = MolReader_ScriptVec_get(&scripts_seg, i);
-      if (script_res.errno != MOL_OK) {
-        ckb_debug("invalid account script");
-        return GW_FATAL_INVALID_DATA;
-      }
-
-      ret = ctx.sys_create(&ctx, script_res.seg.ptr, script_res.seg.size,
-                           &account_id);
-      if (ret != 0) {
-        ckb_debug("failed to create eth account");
-        return ret;
-      }
-
-      ret = ctx.sys_get_script_hash_by_account_id(&ctx, account_id,
-                                                  account_script_hash);
-      if (ret != 0) {
-        ckb_debug("failed to get created eth account script hash");
-        return ret;
-      }
-
-      ret = gw_register_eth_address(&ctx, account_script_hash);
-      if (ret != 0) {
-        ckb_debug("failed to register eth address");
-        return ret;
-      }
+#ifdef GW_GENERATOR
+    /* the generator may create the accounts in one batch of state updates,
+     * with the same results and syscall cycles as creating them one by one */
+    ret = sys_batch_create_eth_accounts(&ctx, scripts_seg.ptr, scripts_seg.size,
+                                        &account_id);
+    if (ret == GW_UNIMPLEMENTED) {
+      ret = create_eth_accounts(&ctx, &scripts_seg, &account_id);
+    }
+#else
+    ret = create_eth_accounts(&ctx, &scripts_seg, &account_id);
+#endif
+    if (ret != 0) {
+      return ret;
     }
 
     ret = ctx.sys_set_program_return_data(&ctx, (uint8_t *)&account_id,
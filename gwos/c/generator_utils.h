@@ -21,6 +21,7 @@
 #define GW_SYS_STORE 3101
 #define GW_SYS_LOAD 3102
 #define GW_SYS_LOAD_ACCOUNT_SCRIPT 3105
+#define GW_SYS_BATCH_CREATE_ETH_ACCOUNTS 3106
 /* Syscall call / return */
 #define GW_SYS_SET_RETURN_DATA 3201
 /* Syscall data store / load */
@@ -344,6 +345,24 @@ int sys_create(gw_context_t *ctx, uint8_t *script, uint64_t script_len,
   return syscall(GW_SYS_CREATE, script, script_len, account_id, 0, 0, 0);
 }
 
+/* Create ETH EOA accounts of the scripts (a ScriptVec) and their ETH registry
+ * mappings in one batch of state updates, the account_id is set to the id of
+ * the last created account. The results and syscall cycles are the same as
+ * sys_create and gw_register_eth_address one by one.
+ *
+ * Returns GW_UNIMPLEMENTED without doing anything if it's not enabled by the
+ * backend fork, or if any script isn't an ETH EOA script, then the caller
+ * should create the accounts one by one. */
+int sys_batch_create_eth_accounts(gw_context_t *ctx, uint8_t *scripts,
+                                  uint64_t scripts_len, uint32_t *account_id) {
+  if (ctx == NULL) {
+    return GW_FATAL_INVALID_CONTEXT;
+  }
+
+  return syscall(GW_SYS_BATCH_CREATE_ETH_ACCOUNTS, scripts, scripts_len,
+                 account_id, 0, 0, 0);
+}
+
 int sys_recover_account(struct gw_context_t *ctx, uint8_t message[32],
                         uint8_t *signature, uint64_t signature_len,
                         uint8_t code_hash[32], uint8_t *script,
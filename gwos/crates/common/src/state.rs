@@ -157,6 +157,14 @@ pub trait State {
     // KV interface
     fn get_raw(&self, key: &H256) -> Result<H256, Error>;
     fn update_raw(&mut self, key: H256, value: H256) -> Result<(), Error>;
+    /// Update keys in one batch. Implementations backed by a SMT can compute
+    /// each branch node on the updated paths once instead of once per key.
+    fn update_raw_batch(&mut self, kvs: Vec<(H256, H256)>) -> Result<(), Error> {
+        for (key, value) in kvs {
+            self.update_raw(key, value)?;
+        }
+        Ok(())
+    }
     fn get_account_count(&self) -> Result<u32, Error>;
     fn set_account_count(&mut self, count: u32) -> Result<(), Error>;
     fn calculate_root(&self) -> Result<H256, Error>;
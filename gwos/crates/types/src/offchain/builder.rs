@@ -0,0 +1,197 @@
+//! Convenience constructors of common layer2 requests.
+//!
+//! Building requests by hand with molecule builders is verbose and easy to get
+//! subtly wrong, e.g. forgetting the chain id or the fee registry id, or
+//! putting a mismatched owner lock in a withdrawal. These constructors fill in
+//! every field and reject inputs that would never pass verification.
+
+use core::fmt;
+
+use crate::bytes::Bytes;
+use crate::h256::*;
+use crate::packed::{
+    Fee, L2Transaction, RawL2Transaction, RawWithdrawalRequest, SUDTArgs, SUDTTransfer, Script,
+    WithdrawalRequest, WithdrawalRequestExtra,
+};
+use crate::prelude::*;
+use crate::registry_address::RegistryAddress;
+use crate::U256;
+
+/// Meta contract account id, which is never a sUDT account.
+const META_CONTRACT_ACCOUNT_ID: u32 = 0;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// sUDT transfer targets the meta contract instead of a sUDT account.
+    InvalidSudtId(u32),
+    EmptyToAddress,
+    /// Withdrawal owner lock doesn't match `owner_lock_hash`.
+    OwnerLockHashMismatch {
+        expected: H256,
+        actual: H256,
+    },
+    /// Withdrawal of sUDT without a sUDT script hash.
+    MissingSudtScriptHash,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::InvalidSudtId(id) => write!(f, "invalid sudt id {}", id),
+            BuildError::EmptyToAddress => write!(f, "empty to address"),
+            BuildError::OwnerLockHashMismatch { expected, actual } => write!(
+                f,
+                "owner lock hash mismatch, expected {:?}, actual {:?}",
+                expected, actual
+            ),
+            BuildError::MissingSudtScriptHash => {
+                write!(f, "sudt amount without sudt script hash")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Parameters of a sUDT transfer.
+#[derive(Debug, Clone, Default)]
+pub struct SudtTransferParams {
+    pub chain_id: u64,
+    pub from_id: u32,
+    /// Account id of the sUDT, e.g. `CKB_SUDT_ACCOUNT_ID`.
+    pub sudt_id: u32,
+    pub nonce: u32,
+    pub to_address: RegistryAddress,
+    pub amount: U256,
+    /// Registry id of the fee payer.
+    pub fee_registry_id: u32,
+    /// Fee in CKB.
+    pub fee_amount: u128,
+}
+
+impl RawL2Transaction {
+    /// Build a sUDT transfer.
+    pub fn build_sudt_transfer(params: &SudtTransferParams) -> Result<Self, BuildError> {
+        if params.sudt_id == META_CONTRACT_ACCOUNT_ID {
+            return Err(BuildError::InvalidSudtId(params.sudt_id));
+        }
+        if params.to_address.is_empty() {
+            return Err(BuildError::EmptyToAddress);
+        }
+
+        let fee = Fee::new_builder()
+            .registry_id(params.fee_registry_id.pack())
+            .amount(params.fee_amount.pack())
+            .build();
+        let transfer = SUDTTransfer::new_builder()
+            .to_address(Bytes::from(params.to_address.to_bytes()).pack())
+            .amount(params.amount.pack())
+            .fee(fee)
+            .build();
+        let args = SUDTArgs::new_builder().set(transfer).build();
+        let raw = RawL2Transaction::new_builder()
+            .chain_id(params.chain_id.pack())
+            .from_id(params.from_id.pack())
+            .to_id(params.sudt_id.pack())
+            .nonce(params.nonce.pack())
+            .args(args.as_bytes().pack())
+            .build();
+        Ok(raw)
+    }
+}
+
+impl L2Transaction {
+    /// Build a signed sUDT transfer.
+    pub fn build_sudt_transfer(
+        params: &SudtTransferParams,
+        signature: Bytes,
+    ) -> Result<Self, BuildError> {
+        let raw = RawL2Transaction::build_sudt_transfer(params)?;
+        let tx = L2Transaction::new_builder()
+            .raw(raw)
+            .signature(signature.pack())
+            .build();
+        Ok(tx)
+    }
+}
+
+/// Parameters of a withdrawal request.
+#[derive(Debug, Clone, Default)]
+pub struct WithdrawalParams {
+    pub chain_id: u64,
+    pub nonce: u32,
+    /// CKB capacity to withdraw.
+    pub capacity: u64,
+    /// sUDT amount to withdraw, zero for CKB only withdrawals.
+    pub amount: u128,
+    pub sudt_script_hash: H256,
+    pub account_script_hash: H256,
+    pub registry_id: u32,
+    /// Layer1 lock of the withdrawal cell.
+    pub owner_lock: Script,
+    pub fee: u128,
+}
+
+impl RawWithdrawalRequest {
+    /// Build a withdrawal request.
+    pub fn build(params: &WithdrawalParams) -> Result<Self, BuildError> {
+        if params.amount > 0 && params.sudt_script_hash.is_zero() {
+            return Err(BuildError::MissingSudtScriptHash);
+        }
+
+        let raw = RawWithdrawalRequest::new_builder()
+            .chain_id(params.chain_id.pack())
+            .nonce(params.nonce.pack())
+            .capacity(params.capacity.pack())
+            .amount(params.amount.pack())
+            .sudt_script_hash(params.sudt_script_hash.pack())
+            .account_script_hash(params.account_script_hash.pack())
+            .registry_id(params.registry_id.pack())
+            .owner_lock_hash(params.owner_lock.hash().pack())
+            .fee(params.fee.pack())
+            .build();
+        Ok(raw)
+    }
+}
+
+impl WithdrawalRequest {
+    /// Build a signed withdrawal request.
+    pub fn build(params: &WithdrawalParams, signature: Bytes) -> Result<Self, BuildError> {
+        let raw = RawWithdrawalRequest::build(params)?;
+        let withdrawal = WithdrawalRequest::new_builder()
+            .raw(raw)
+            .signature(signature.pack())
+            .build();
+        Ok(withdrawal)
+    }
+}
+
+impl WithdrawalRequestExtra {
+    /// Build a signed withdrawal request along with its owner lock.
+    pub fn build(params: &WithdrawalParams, signature: Bytes) -> Result<Self, BuildError> {
+        let request = WithdrawalRequest::build(params, signature)?;
+        let extra = WithdrawalRequestExtra::new_builder()
+            .request(request)
+            .owner_lock(params.owner_lock.clone())
+            .build();
+        Ok(extra)
+    }
+
+    /// Pair a withdrawal request with its owner lock, checking the owner lock
+    /// hash.
+    pub fn with_owner_lock(
+        request: WithdrawalRequest,
+        owner_lock: Script,
+    ) -> Result<Self, BuildError> {
+        let expected: H256 = request.raw().owner_lock_hash().unpack();
+        let actual = owner_lock.hash();
+        if expected != actual {
+            return Err(BuildError::OwnerLockHashMismatch { expected, actual });
+        }
+        let extra = WithdrawalRequestExtra::new_builder()
+            .request(request)
+            .owner_lock(owner_lock)
+            .build();
+        Ok(extra)
+    }
+}
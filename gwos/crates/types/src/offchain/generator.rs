@@ -1,3 +1,7 @@
+use std::collections::HashMap;
+
+use crate::{bytes::Bytes, h256::H256};
+
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct CycleMeter {
     pub execution: u64,
@@ -9,3 +13,46 @@ impl CycleMeter {
         self.execution.saturating_add(self.r#virtual)
     }
 }
+
+/// Cycles of a tx by backend and syscall, collected if cycles profiling is
+/// enabled in the generator.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CyclesProfile {
+    /// Execution cycles by backend.
+    pub backends: HashMap<String, u64>,
+    /// Calls by syscall number.
+    pub syscalls: HashMap<u64, u64>,
+}
+
+impl CyclesProfile {
+    pub fn merge(&mut self, other: CyclesProfile) {
+        for (backend, cycles) in other.backends {
+            *self.backends.entry(backend).or_default() += cycles;
+        }
+        for (syscall, calls) in other.syscalls {
+            *self.syscalls.entry(syscall).or_default() += calls;
+        }
+    }
+}
+
+/// A syscall made by a backend, with the input it read from and the memory
+/// it wrote to the VM, recorded to replay the execution.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyscallTrace {
+    pub number: u64,
+    /// Registers A0 to A5.
+    pub args: [u64; 6],
+    pub input: Bytes,
+    /// Memory written by the syscall, by address.
+    pub writes: Vec<(u64, Bytes)>,
+    /// Register A0 after the syscall.
+    pub ret: u64,
+}
+
+/// Syscalls made by a backend in an execution, i.e. a call of a tx.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionTrace {
+    pub generator_checksum: H256,
+    pub exit_code: i8,
+    pub syscalls: Vec<SyscallTrace>,
+}
@@ -1,3 +1,4 @@
+mod builder;
 mod compatible_finalized_timepoint;
 mod error_receipt;
 mod exported_block;
@@ -9,11 +10,12 @@ mod rpc;
 mod run_result;
 mod store;
 
+pub use builder::*;
 pub use compatible_finalized_timepoint::CompatibleFinalizedTimepoint;
 pub use error_receipt::*;
 pub use exported_block::*;
 pub use extension::global_state_from_slice;
-pub use generator::CycleMeter;
+pub use generator::{CycleMeter, CyclesProfile, ExecutionTrace, SyscallTrace};
 pub use mem_block::*;
 pub use pool::*;
 pub use rpc::*;
@@ -3,7 +3,7 @@ use crate::h256::H256;
 use crate::packed::{LogItem, Script};
 use std::collections::HashSet;
 
-use super::CycleMeter;
+use super::{CycleMeter, CyclesProfile, ExecutionTrace};
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct RecoverAccount {
@@ -32,5 +32,14 @@ pub struct RunResult {
     pub cycles: CycleMeter,
     pub read_data_hashes: HashSet<H256>,
     pub write_data_hashes: HashSet<H256>,
+    /// State keys read by the tx, including script and data hashes, used to
+    /// detect conflicts between txs executed in parallel.
+    pub read_keys: HashSet<H256>,
+    /// State keys written by the tx, see `read_keys`.
+    pub write_keys: HashSet<H256>,
     pub debug_log_buf: Vec<u8>,
+    pub cycles_profile: Option<CyclesProfile>,
+    /// Syscall traces of backend executions, only recorded by
+    /// `Generator::trace_transaction`.
+    pub execution_traces: Vec<ExecutionTrace>,
 }